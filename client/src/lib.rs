@@ -0,0 +1,42 @@
+//! Integration surface for off-chain bots and backends
+//!
+//! Re-exports the generated contract clients and shared protocol types, and
+//! builds `InvokeHostFunction` operations for the common user flows so callers
+//! only need to wrap them in a transaction, simulate and sign.
+
+pub mod tx;
+
+// Generated contract clients
+pub use allowlist::AllowlistClient;
+pub use coffee_collateral::CoffeeCollateralClient;
+pub use escrow::EscrowClient;
+pub use gold_vault::GoldVaultClient;
+pub use governance::GovernanceClient;
+pub use hedging::HedgingClient;
+pub use receipts::ReceiptsClient;
+pub use registry::RegistryClient;
+pub use rewards::RewardsClient;
+pub use usdc_vault::USDCVaultClient;
+pub use yield_token::YieldTokenClient;
+
+// Shared protocol types
+pub use shared::{
+    AccrualBatch, Airdrop, AlertThresholds, AlertTier, BadDebtRecord, BalanceCheckpoint,
+    BalanceSnapshot, CoffeePrice, CollateralInfo, CollateralStatus, CollateralSummary,
+    CommitteeDelegate, CommitteeMember, CompensationSchedule, CompoundingFrequency,
+    CompoundingPlan, ContractError, ContractInfo, CreditHistory, DepositInfo, DepositPreview,
+    DepositReceipt, DepositTag, DepositTerms, DepositVoucher, DormancyPolicy, EarlyRepayment,
+    Encumbrance, EpochSummary, EscrowStatus, ExitFeeSchedule, ExpertiseArea, FacilityDraw,
+    FeeRebateTier, FeeSwitch, GovernanceProposal, GradePoint, HedgePosition, HedgeStatus,
+    HedgeSummary, HolderStats, Installment, KeeperBounty, LedgerClock, LiquidityFacility,
+    LoanCurrency, LoanEscrow, LoanFreeze, LoanPreview, LoanProposal, LoanTranche, LockPeriod,
+    LockTierCap, LotListing, LotSale, MemberStats, PositionPage, PositionRecord, PrepaymentPolicy,
+    PriceSnapshot, ProfitReport, ProposalStatus, ProtocolAddresses, ProtocolParameter, RateChange,
+    RateLock, RateTranche, RecoveryPlan, RevenueStream, RewardStake, Role, SessionKey,
+    SettlementSnapshot, StandingOrder, StateDigest, StipendAccount, StrategySlot, SyndicateShare,
+    TokenStats, TradeFill, TrancheStats, TreasuryTarget, UserYieldInfo, VaultHealthReport,
+    VaultLien, VaultType, VoteTally, VoteType, VotingRules, VoucherStatus, WithdrawalCap,
+    WithdrawalEpoch, YieldFeeSchedule, YieldStream,
+};
+
+pub use soroban_sdk::xdr;
@@ -0,0 +1,341 @@
+//! Operation builders for user-facing contract calls
+//!
+//! Each builder returns an unsigned `InvokeHostFunction` operation with no
+//! authorization entries; simulate the enclosing transaction to obtain the
+//! footprint and auth before signing.
+
+use shared::{LockPeriod, ProtocolParameter, VoteType};
+use soroban_sdk::xdr::{
+    Error, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Operation, OperationBody,
+    ScAddress, ScBytes, ScSymbol, ScVal, ScVec, UInt128Parts, VecM,
+};
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Build an operation invoking `function` on `contract` with pre-encoded arguments
+pub fn invoke(contract: &ScAddress, function: &str, args: Vec<ScVal>) -> Result<Operation> {
+    let invoke_args = InvokeContractArgs {
+        contract_address: contract.clone(),
+        function_name: ScSymbol(function.try_into()?),
+        args: args.try_into()?,
+    };
+
+    Ok(Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(invoke_args),
+            auth: VecM::default(),
+        }),
+    })
+}
+
+/// Deposit USDC into the USDC vault
+pub fn usdc_deposit(
+    vault: &ScAddress,
+    user: &ScAddress,
+    amount: u128,
+    lock_period: &LockPeriod,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "deposit",
+        vec![
+            address(user),
+            u128_val(amount),
+            lock_period_val(lock_period)?,
+        ],
+    )
+}
+
+/// Deposit USDC into the USDC vault tagged with an integrator's customer reference
+pub fn usdc_deposit_tagged(
+    vault: &ScAddress,
+    user: &ScAddress,
+    amount: u128,
+    lock_period: &LockPeriod,
+    integrator: &ScAddress,
+    customer_id: u64,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "deposit_tagged",
+        vec![
+            address(user),
+            u128_val(amount),
+            lock_period_val(lock_period)?,
+            address(integrator),
+            ScVal::U64(customer_id),
+        ],
+    )
+}
+
+/// Deposit USDC the user approved the vault to spend, signed by an operator
+pub fn usdc_deposit_from(
+    vault: &ScAddress,
+    operator: &ScAddress,
+    user: &ScAddress,
+    amount: u128,
+    lock_period: &LockPeriod,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "deposit_from",
+        vec![
+            address(operator),
+            address(user),
+            u128_val(amount),
+            lock_period_val(lock_period)?,
+        ],
+    )
+}
+
+/// Execute a user's due standing order on the USDC vault (keeper call)
+pub fn execute_standing_order(
+    vault: &ScAddress,
+    keeper: &ScAddress,
+    user: &ScAddress,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "execute_standing_order",
+        vec![address(keeper), address(user)],
+    )
+}
+
+/// Withdraw principal plus yield from the USDC vault
+pub fn usdc_withdraw(vault: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(vault, "withdraw", vec![address(user)])
+}
+
+/// Collect USDC queued by a withdrawal beyond the epoch cap
+pub fn claim_queued_withdrawal(vault: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(vault, "claim_queued_withdrawal", vec![address(user)])
+}
+
+/// Deposit a supported gold token into the gold vault
+pub fn gold_deposit(
+    vault: &ScAddress,
+    user: &ScAddress,
+    gold_asset: &ScAddress,
+    amount: u128,
+    lock_period: &LockPeriod,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "deposit",
+        vec![
+            address(user),
+            address(gold_asset),
+            u128_val(amount),
+            lock_period_val(lock_period)?,
+        ],
+    )
+}
+
+/// Deposit gold tokens the user approved the vault to spend, signed by an operator
+pub fn gold_deposit_from(
+    vault: &ScAddress,
+    operator: &ScAddress,
+    user: &ScAddress,
+    gold_asset: &ScAddress,
+    amount: u128,
+    lock_period: &LockPeriod,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "deposit_from",
+        vec![
+            address(operator),
+            address(user),
+            address(gold_asset),
+            u128_val(amount),
+            lock_period_val(lock_period)?,
+        ],
+    )
+}
+
+/// Withdraw gold plus yield from the gold vault
+pub fn gold_withdraw(vault: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(vault, "withdraw", vec![address(user)])
+}
+
+/// Withdraw from the gold vault, reverting if fewer than `min_gold_out` tokens would be paid
+pub fn gold_withdraw_with_min(
+    vault: &ScAddress,
+    user: &ScAddress,
+    min_gold_out: u128,
+) -> Result<Operation> {
+    invoke(
+        vault,
+        "withdraw_with_min",
+        vec![address(user), u128_val(min_gold_out)],
+    )
+}
+
+/// Checkpoint a holder's accrued yield on the yield token (permissionless)
+pub fn accrue(yield_token: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(yield_token, "accrue", vec![address(user)])
+}
+
+/// Compound another holder's yield for the keeper tip (permissionless)
+pub fn compound_for(
+    yield_token: &ScAddress,
+    caller: &ScAddress,
+    user: &ScAddress,
+) -> Result<Operation> {
+    invoke(
+        yield_token,
+        "compound_for",
+        vec![address(caller), address(user)],
+    )
+}
+
+/// Switch a holder between compounding and streaming their yield
+pub fn set_yield_stream(
+    yield_token: &ScAddress,
+    user: &ScAddress,
+    enabled: bool,
+) -> Result<Operation> {
+    invoke(
+        yield_token,
+        "set_yield_stream",
+        vec![address(user), ScVal::Bool(enabled)],
+    )
+}
+
+/// Claim streamed yield from the USDC vault
+pub fn claim_stream(vault: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(vault, "claim_stream", vec![address(user)])
+}
+
+/// Export the open deposit receipt of either vault as a transferable token
+pub fn export_receipt(vault: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(vault, "export_receipt", vec![address(user)])
+}
+
+/// Transfer an exported receipt token
+pub fn transfer_receipt(
+    receipts: &ScAddress,
+    from: &ScAddress,
+    to: &ScAddress,
+    token_id: u64,
+) -> Result<Operation> {
+    invoke(
+        receipts,
+        "transfer",
+        vec![address(from), address(to), ScVal::U64(token_id)],
+    )
+}
+
+/// Propose a protocol parameter change (requires the minimum yield token balance)
+pub fn propose_parameter_change(
+    governance: &ScAddress,
+    proposer: &ScAddress,
+    parameter: &ProtocolParameter,
+    new_value: u128,
+) -> Result<Operation> {
+    invoke(
+        governance,
+        "propose_parameter_change",
+        vec![
+            address(proposer),
+            parameter_val(parameter)?,
+            u128_val(new_value),
+        ],
+    )
+}
+
+/// Vote on a governance proposal with the voter's yield token balance
+pub fn vote(
+    governance: &ScAddress,
+    voter: &ScAddress,
+    proposal_id: &[u8; 32],
+    vote: &VoteType,
+) -> Result<Operation> {
+    invoke(
+        governance,
+        "vote_on_proposal",
+        vec![address(voter), bytes32(proposal_id)?, vote_type_val(vote)?],
+    )
+}
+
+/// Claim accrued liquidity-mining rewards
+pub fn claim_rewards(rewards: &ScAddress, user: &ScAddress) -> Result<Operation> {
+    invoke(rewards, "claim_rewards", vec![address(user)])
+}
+
+/// Claim an airdrop allocation with its merkle proof
+pub fn claim_airdrop(
+    rewards: &ScAddress,
+    user: &ScAddress,
+    airdrop_id: u32,
+    amount: u128,
+    proof: &[[u8; 32]],
+) -> Result<Operation> {
+    let proof = proof.iter().map(bytes32).collect::<Result<Vec<_>>>()?;
+    invoke(
+        rewards,
+        "claim_airdrop",
+        vec![
+            address(user),
+            ScVal::U32(airdrop_id),
+            u128_val(amount),
+            ScVal::Vec(Some(ScVec(proof.try_into()?))),
+        ],
+    )
+}
+
+/// Encode an address argument
+pub fn address(address: &ScAddress) -> ScVal {
+    ScVal::Address(address.clone())
+}
+
+/// Encode a u128 argument
+pub fn u128_val(value: u128) -> ScVal {
+    ScVal::U128(UInt128Parts {
+        hi: (value >> 64) as u64,
+        lo: value as u64,
+    })
+}
+
+/// Encode a `BytesN<32>` argument such as a proposal ID
+pub fn bytes32(bytes: &[u8; 32]) -> Result<ScVal> {
+    Ok(ScVal::Bytes(ScBytes(bytes.to_vec().try_into()?)))
+}
+
+/// Encode a `LockPeriod` the way `#[contracttype]` does
+pub fn lock_period_val(lock_period: &LockPeriod) -> Result<ScVal> {
+    unit_variant(match lock_period {
+        LockPeriod::ThreeMonths => "ThreeMonths",
+        LockPeriod::SixMonths => "SixMonths",
+        LockPeriod::TwelveMonths => "TwelveMonths",
+    })
+}
+
+/// Encode a `ProtocolParameter` the way `#[contracttype]` does
+pub fn parameter_val(parameter: &ProtocolParameter) -> Result<ScVal> {
+    unit_variant(match parameter {
+        ProtocolParameter::MinimumLockPeriod => "MinimumLockPeriod",
+        ProtocolParameter::MaximumYieldRate => "MaximumYieldRate",
+        ProtocolParameter::CollateralRatio => "CollateralRatio",
+        ProtocolParameter::ProtocolFeeRate => "ProtocolFeeRate",
+        ProtocolParameter::EmergencyWithdrawFee => "EmergencyWithdrawFee",
+        ProtocolParameter::TreasurySwap => "TreasurySwap",
+        ProtocolParameter::FeeSwitch => "FeeSwitch",
+    })
+}
+
+/// Encode a `VoteType` the way `#[contracttype]` does
+pub fn vote_type_val(vote: &VoteType) -> Result<ScVal> {
+    unit_variant(match vote {
+        VoteType::For => "For",
+        VoteType::Against => "Against",
+        VoteType::Abstain => "Abstain",
+    })
+}
+
+// Unit enum variants are encoded as a single-element vector holding the variant name
+fn unit_variant(name: &str) -> Result<ScVal> {
+    let symbol = ScVal::Symbol(ScSymbol(name.try_into()?));
+    Ok(ScVal::Vec(Some(ScVec(vec![symbol].try_into()?))))
+}
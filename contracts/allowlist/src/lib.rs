@@ -0,0 +1,193 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, ContractInfo, Role, REGISTRY_ALLOWLIST,
+};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env, String, Symbol, Vec};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and counter
+    Admin,
+    AllowedCount,
+    // Persistent: per-account approvals
+    Allowed(Address),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Allowlist;
+
+#[contractimpl]
+impl Allowlist {
+    /// Initialize the depositor allowlist
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::AllowedCount, &0u32);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Allowlist initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_ALLOWLIST),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Approve or revoke an account (admin only)
+    pub fn set_allowed(env: Env, admin: Address, account: Address, allowed: bool) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        // Revocations stay open while new approvals are paused
+        if allowed {
+            ensure_not_paused(&env);
+        }
+
+        Self::store_allowed(&env, &account, allowed);
+
+        log!(&env, "Allowlist entry for {} set to {}", account, allowed);
+    }
+
+    /// Approve a batch of accounts, e.g. a pilot cohort (admin only)
+    pub fn allow_many(env: Env, admin: Address, accounts: Vec<Address>) {
+        admin.require_auth();
+        ensure_not_paused(&env);
+        Self::verify_admin(&env, &admin);
+
+        for account in accounts.iter() {
+            Self::store_allowed(&env, &account, true);
+        }
+
+        log!(&env, "{} accounts allowlisted", accounts.len());
+    }
+
+    /// Check whether an account is allowlisted
+    pub fn is_allowed(env: Env, account: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Allowed(account))
+    }
+
+    /// Get the number of allowlisted accounts
+    pub fn allowed_count(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowedCount)
+            .unwrap_or(0)
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new approvals (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new approvals are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn store_allowed(env: &Env, account: &Address, allowed: bool) {
+        let key = DataKey::Allowed(account.clone());
+        let was_allowed = env.storage().persistent().has(&key);
+        if allowed == was_allowed {
+            return;
+        }
+
+        let count = Self::allowed_count(env.clone());
+        if allowed {
+            env.storage().persistent().set(&key, &true);
+            extend_persistent_ttl(env, &key);
+            env.storage()
+                .instance()
+                .set(&DataKey::AllowedCount, &(count + 1));
+        } else {
+            env.storage().persistent().remove(&key);
+            env.storage()
+                .instance()
+                .set(&DataKey::AllowedCount, &(count - 1));
+        }
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage the allowlist");
+        }
+    }
+}
@@ -1,17 +1,89 @@
 #![no_std]
-use soroban_sdk::{
-    contract, contractimpl, log, symbol_short, Address, Env, Symbol, String, BytesN, Vec
+use shared::{
+    begin_migration, ensure_not_paused, ensure_uninitialized, extend_instance_ttl,
+    extend_persistent_ttl, finish_migration, invoke_peer, is_initialized, mark_initialized,
+    migrate_instance_entry, migrate_persistent_entry, require_role, resolve_peer, role_holder,
+    set_paused, set_role_holder, stored_schema_version, transfer_or_abort, verify_migration_admin,
+    AlertThresholds, AlertTier, CoffeePrice, CollateralInfo, CollateralStatus, CollateralSummary,
+    ContractError, ContractInfo, GradePoint, LoanProposal, LotListing, LotOffer, LotSale, Role,
+    BASIS_POINTS, COFFEE_PRICE_FEED, COFFEE_PRICE_MAX_AGE, COLLATERAL_PAGE_SIZE,
+    COLLATERAL_RATIO_BASIS_POINTS, MAX_GRADE_MULTIPLIER_BPS, MAX_LIQUIDATION_BOUNTY_BPS,
+    REGISTRY_COFFEE_COLLATERAL, REGISTRY_GOVERNANCE, REGISTRY_ORACLE, REGISTRY_USDC_VAULT,
 };
 use soroban_sdk::token::TokenClient;
-use shared::{CollateralInfo, CollateralStatus, COLLATERAL_RATIO_BASIS_POINTS, STORAGE_INSTANCE_PERSISTENT};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, panic_with_error, symbol_short, Address, BytesN,
+    Env, IntoVal, String, Symbol, Vec,
+};
 
 // Storage Keys
-const COLLATERAL: Symbol = symbol_short!("COLLAT");
-const LOAN_COLLATERAL: Symbol = symbol_short!("LOAN");
-const ADMIN: Symbol = symbol_short!("ADMIN");
-const COMMITTEE: Symbol = symbol_short!("COMMIT");
-const ASSET_COUNTER: Symbol = symbol_short!("COUNTER");
-const VALUATION_ORACLE: Symbol = symbol_short!("ORACLE");
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and counters
+    Admin,
+    Committee,
+    AssetCounter,
+    ValuationOracle,
+    Registry,
+    PriceOracle,
+    BasePrice,
+    GradeCurve,
+    ProceedsToken,
+    ProceedsVault,
+    LiquidationBountyBps,
+    MarketToken,
+    AlertThresholds,
+    PledgedKg,
+    StatusCount(CollateralStatus),
+    ActiveValue,
+    ActiveGradeTotal,
+    // Persistent: collateral indexes by status and by loan
+    StatusIndex(CollateralStatus),
+    CollateralizedLoans,
+    // Persistent: per-asset records
+    Collateral(Address),
+    BatchId(Address),
+    FarmLocation(Address),
+    HarvestDate(Address),
+    Issuer(Address),
+    PledgedTo(Address),
+    Owner(Address),
+    DeliveryProof(Address),
+    // Persistent: marketplace listings, escrowed offers and last sales
+    Listing(Address),
+    Offer(Address, Address),
+    LastSale(Address),
+    // Persistent: per-loan records
+    LoanCollateral(BytesN<32>),
+    LoanAmount(BytesN<32>),
+    LiquidationShortfall(BytesN<32>),
+    LiquidatedTo(Address),
+    AlertTier(BytesN<32>),
+}
+
+// Schema v1 storage keys, only read when migrating
+mod v1 {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub const COLLATERAL: Symbol = symbol_short!("COLLAT");
+    pub const LOAN_COLLATERAL: Symbol = symbol_short!("LOAN");
+    pub const ADMIN: Symbol = symbol_short!("ADMIN");
+    pub const COMMITTEE: Symbol = symbol_short!("COMMIT");
+    pub const ASSET_COUNTER: Symbol = symbol_short!("COUNTER");
+    pub const VALUATION_ORACLE: Symbol = symbol_short!("ORACLE");
+    pub const REGISTRY: Symbol = symbol_short!("REGISTRY");
+    pub const LOAN_AMOUNT: Symbol = symbol_short!("LOANAMT");
+    pub const LIQUIDATION_SHORTFALL: Symbol = symbol_short!("SHORTFALL");
+    pub const BATCH_ID: &str = "batch_id";
+    pub const FARM_LOCATION: &str = "farm_location";
+    pub const HARVEST_DATE: &str = "harvest_date";
+    pub const ISSUER: &str = "issuer";
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 2;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[contract]
 pub struct CoffeeCollateral;
@@ -25,14 +97,152 @@ impl CoffeeCollateral {
         committee_contract: Address,
         valuation_oracle: Address,
     ) {
+        ensure_uninitialized(&env, Some(v1::ADMIN));
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Committee, &committee_contract);
+        env.storage()
+            .instance()
+            .set(&DataKey::ValuationOracle, &valuation_oracle);
+        env.storage().instance().set(&DataKey::AssetCounter, &0u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Coffee Collateral Registry initialized with admin: {}",
+            admin
+        );
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_COFFEE_COLLATERAL),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Migrate storage to the schema version of the current WASM (admin only)
+    ///
+    /// Moves config and counters; asset and loan records are moved with `migrate_records`.
+    ///
+    /// Storage from before versioning also needs the `deployer` contract that
+    /// created this one, whose record of the admin is trusted over the stored one.
+    pub fn migrate(env: Env, admin: Address, deployer: Option<Address>) {
+        admin.require_auth();
+
+        // Until the v2 migration has run the admin still sits under its v1 key
+        let stored_admin: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .or_else(|| env.storage().instance().get(&v1::ADMIN));
+        let stored_version = begin_migration(&env, STORAGE_VERSION);
+        verify_migration_admin(&env, stored_version, &admin, stored_admin, deployer);
+
+        match stored_version {
+            0 | 1 => Self::migrate_v1_instance(&env),
+            _ => panic_with_error!(&env, ContractError::InvalidMigration),
+        }
+        finish_migration(&env, stored_version, STORAGE_VERSION);
+    }
+
+    /// Move schema v1 asset and loan records to their `DataKey` entries (admin only)
+    ///
+    /// Persistent storage cannot be enumerated, so the assets and loan IDs are
+    /// supplied in batches from indexed events once `migrate` has run.
+    pub fn migrate_records(
+        env: Env,
+        admin: Address,
+        coffee_assets: Vec<Address>,
+        loan_ids: Vec<BytesN<32>>,
+    ) -> u32 {
         admin.require_auth();
-        
-        env.storage().instance().set(&ADMIN, &admin);
-        env.storage().instance().set(&COMMITTEE, &committee_contract);
-        env.storage().instance().set(&VALUATION_ORACLE, &valuation_oracle);
-        env.storage().instance().set(&ASSET_COUNTER, &0u64);
-        
-        log!(&env, "Coffee Collateral Registry initialized with admin: {}", admin);
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let mut migrated = 0u32;
+        for asset in coffee_assets.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::COLLATERAL, asset.clone()),
+                &DataKey::Collateral(asset.clone()),
+            ) {
+                migrate_persistent_entry(
+                    &env,
+                    &(Symbol::new(&env, v1::BATCH_ID), asset.clone()),
+                    &DataKey::BatchId(asset.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(Symbol::new(&env, v1::FARM_LOCATION), asset.clone()),
+                    &DataKey::FarmLocation(asset.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(Symbol::new(&env, v1::HARVEST_DATE), asset.clone()),
+                    &DataKey::HarvestDate(asset.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(Symbol::new(&env, v1::ISSUER), asset.clone()),
+                    &DataKey::Issuer(asset.clone()),
+                );
+                let info: CollateralInfo = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::Collateral(asset.clone()))
+                    .unwrap();
+                Self::index_collateral(&env, &asset, None, &info);
+                migrated += 1;
+            }
+        }
+
+        for loan_id in loan_ids.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::LOAN_COLLATERAL, loan_id.clone()),
+                &DataKey::LoanCollateral(loan_id.clone()),
+            ) {
+                migrate_persistent_entry(
+                    &env,
+                    &(v1::LOAN_AMOUNT, loan_id.clone()),
+                    &DataKey::LoanAmount(loan_id.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(v1::LIQUIDATION_SHORTFALL, loan_id.clone()),
+                    &DataKey::LiquidationShortfall(loan_id.clone()),
+                );
+                Self::index_loan(&env, &loan_id);
+                migrated += 1;
+            }
+        }
+
+        log!(&env, "Migrated {} collateral and loan records", migrated);
+
+        migrated
     }
 
     /// Create and register a new coffee asset as collateral
@@ -47,32 +257,39 @@ impl CoffeeCollateral {
         harvest_date: String,
     ) -> Address {
         issuer.require_auth();
-        
+        ensure_not_paused(&env);
+
         // Validate inputs
         if quality_grade == 0 || quality_grade > 100 {
             panic!("Quality grade must be between 1 and 100");
         }
-        
+
         if quantity_kg == 0 {
             panic!("Quantity must be greater than 0");
         }
-        
+
         if estimated_value_usd == 0 {
             panic!("Estimated value must be greater than 0");
         }
 
         // Generate unique asset code
-        let asset_counter: u64 = env.storage().instance().get(&ASSET_COUNTER).unwrap_or(0);
+        let asset_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AssetCounter)
+            .unwrap_or(0);
         let new_counter = asset_counter + 1;
-        env.storage().instance().set(&ASSET_COUNTER, &new_counter);
-        
+        env.storage()
+            .instance()
+            .set(&DataKey::AssetCounter, &new_counter);
+
         // Create simple asset code (COFFEE + counter will be handled differently)
         let asset_code = String::from_str(&env, "COFFEE_ASSET");
-        
+
         // Create Stellar asset (this is a placeholder - actual Stellar asset creation would need different approach)
         // For Soroban, we'll create a token contract instance
         let coffee_asset = env.current_contract_address(); // Placeholder - would be actual token address
-        
+
         // Store collateral metadata
         let collateral_info = CollateralInfo {
             asset_address: coffee_asset.clone(),
@@ -82,24 +299,27 @@ impl CoffeeCollateral {
             creation_time: env.ledger().timestamp(),
             status: CollateralStatus::Active,
         };
-        
-        env.storage()
-            .persistent()
-            .set(&(COLLATERAL.clone(), coffee_asset.clone()), &collateral_info);
-        
+
+        Self::store_collateral(&env, &coffee_asset, &collateral_info);
+
         // Store additional metadata
         env.storage()
             .persistent()
-            .set(&(Symbol::new(&env, "batch_id"), coffee_asset.clone()), &coffee_batch_id);
+            .set(&DataKey::BatchId(coffee_asset.clone()), &coffee_batch_id);
         env.storage()
             .persistent()
-            .set(&(Symbol::new(&env, "farm_location"), coffee_asset.clone()), &farm_location);
+            .set(&DataKey::FarmLocation(coffee_asset.clone()), &farm_location);
         env.storage()
             .persistent()
-            .set(&(Symbol::new(&env, "harvest_date"), coffee_asset.clone()), &harvest_date);
+            .set(&DataKey::HarvestDate(coffee_asset.clone()), &harvest_date);
         env.storage()
             .persistent()
-            .set(&(Symbol::new(&env, "issuer"), coffee_asset.clone()), &issuer);
+            .set(&DataKey::Issuer(coffee_asset.clone()), &issuer);
+        extend_persistent_ttl(&env, &DataKey::BatchId(coffee_asset.clone()));
+        extend_persistent_ttl(&env, &DataKey::FarmLocation(coffee_asset.clone()));
+        extend_persistent_ttl(&env, &DataKey::HarvestDate(coffee_asset.clone()));
+        extend_persistent_ttl(&env, &DataKey::Issuer(coffee_asset.clone()));
+        extend_instance_ttl(&env);
 
         log!(
             &env,
@@ -121,9 +341,15 @@ impl CoffeeCollateral {
         loan_amount: u128,
     ) {
         committee.require_auth();
-        
+        ensure_not_paused(&env);
+
         // Verify caller is authorized committee
-        let stored_committee: Address = env.storage().instance().get(&COMMITTEE).unwrap();
+        let stored_committee = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
         if committee != stored_committee {
             panic!("Only committee can register collateral for loans");
         }
@@ -132,7 +358,7 @@ impl CoffeeCollateral {
         let collateral_info: CollateralInfo = env
             .storage()
             .persistent()
-            .get(&(COLLATERAL.clone(), coffee_asset.clone()))
+            .get(&DataKey::Collateral(coffee_asset.clone()))
             .unwrap_or_else(|| panic!("Coffee asset not found"));
 
         // Verify collateral is active
@@ -140,20 +366,54 @@ impl CoffeeCollateral {
             panic!("Collateral is not active");
         }
 
+        // A lot can only back one loan
+        let pledged_to: Option<BytesN<32>> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PledgedTo(coffee_asset.clone()));
+        let newly_pledged = pledged_to.is_none();
+        if pledged_to.is_some_and(|pledged_to| pledged_to != loan_id) {
+            panic!("Collateral is already pledged to another loan");
+        }
+
         // Check collateralization ratio (150% requirement)
         let required_collateral_value = (loan_amount * COLLATERAL_RATIO_BASIS_POINTS) / 10000;
         if collateral_info.estimated_value_usd < required_collateral_value {
             panic!(
                 "Insufficient collateral value. Required: ${}, Available: ${}",
-                required_collateral_value,
-                collateral_info.estimated_value_usd
+                required_collateral_value, collateral_info.estimated_value_usd
+            );
+        }
+
+        // A lot joins the pledged book once, however often it is re-registered
+        if newly_pledged {
+            let pledged_kg = Self::get_pledged_quantity(env.clone());
+            env.storage().instance().set(
+                &DataKey::PledgedKg,
+                &(pledged_kg + collateral_info.quantity_kg),
             );
         }
 
         // Register collateral for loan
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::LoanCollateral(loan_id.clone()))
+        {
+            Self::index_loan(&env, &loan_id);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanCollateral(loan_id.clone()), &coffee_asset);
         env.storage()
             .persistent()
-            .set(&(LOAN_COLLATERAL.clone(), loan_id), &coffee_asset);
+            .set(&DataKey::LoanAmount(loan_id.clone()), &loan_amount);
+        env.storage()
+            .persistent()
+            .set(&DataKey::PledgedTo(coffee_asset.clone()), &loan_id);
+        extend_persistent_ttl(&env, &DataKey::LoanCollateral(loan_id.clone()));
+        extend_persistent_ttl(&env, &DataKey::LoanAmount(loan_id.clone()));
+        extend_persistent_ttl(&env, &DataKey::PledgedTo(coffee_asset.clone()));
 
         log!(
             &env,
@@ -164,20 +424,26 @@ impl CoffeeCollateral {
         );
     }
 
+    /// Get the kg of coffee pledged to loans and not yet liquidated: the collateral book
+    pub fn get_pledged_quantity(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PledgedKg)
+            .unwrap_or(0)
+    }
+
     /// Verify collateral for a loan
     pub fn verify_collateral(env: Env, loan_id: BytesN<32>) -> bool {
         let coffee_asset: Option<Address> = env
             .storage()
             .persistent()
-            .get(&(LOAN_COLLATERAL.clone(), loan_id));
+            .get(&DataKey::LoanCollateral(loan_id));
 
         match coffee_asset {
             Some(asset) => {
-                let collateral_info: Option<CollateralInfo> = env
-                    .storage()
-                    .persistent()
-                    .get(&(COLLATERAL.clone(), asset));
-                
+                let collateral_info: Option<CollateralInfo> =
+                    env.storage().persistent().get(&DataKey::Collateral(asset));
+
                 match collateral_info {
                     Some(info) => info.status == CollateralStatus::Active,
                     None => false,
@@ -187,156 +453,1395 @@ impl CoffeeCollateral {
         }
     }
 
-    /// Liquidate collateral for defaulted loan
-    pub fn liquidate_collateral(env: Env, committee: Address, loan_id: BytesN<32>) {
+    /// Release the lot pledged to a repaid or rejected loan (committee only)
+    ///
+    /// The lot leaves the pledged book and can be listed, sold or retired again.
+    pub fn release_collateral(env: Env, committee: Address, loan_id: BytesN<32>) -> Address {
         committee.require_auth();
-        
+
         // Verify caller is authorized committee
-        let stored_committee: Address = env.storage().instance().get(&COMMITTEE).unwrap();
+        let stored_committee = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
         if committee != stored_committee {
-            panic!("Only committee can liquidate collateral");
+            panic!("Only committee can release collateral");
         }
 
         let coffee_asset: Address = env
             .storage()
             .persistent()
-            .get(&(LOAN_COLLATERAL.clone(), loan_id))
+            .get(&DataKey::LoanCollateral(loan_id.clone()))
             .unwrap_or_else(|| panic!("No collateral found for loan"));
-
-        let mut collateral_info: CollateralInfo = env
+        let collateral_info: CollateralInfo = env
             .storage()
             .persistent()
-            .get(&(COLLATERAL.clone(), coffee_asset.clone()))
+            .get(&DataKey::Collateral(coffee_asset.clone()))
             .unwrap_or_else(|| panic!("Collateral info not found"));
+        if collateral_info.status != CollateralStatus::Active {
+            panic!("Only active collateral can be released");
+        }
 
-        // Update status to liquidated
-        collateral_info.status = CollateralStatus::Liquidated;
         env.storage()
             .persistent()
-            .set(&(COLLATERAL.clone(), coffee_asset.clone()), &collateral_info);
+            .remove(&DataKey::PledgedTo(coffee_asset.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::LoanCollateral(loan_id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::LoanAmount(loan_id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AlertTier(loan_id.clone()));
+        let pledged_kg = Self::get_pledged_quantity(env.clone());
+        env.storage().instance().set(
+            &DataKey::PledgedKg,
+            &pledged_kg.saturating_sub(collateral_info.quantity_kg),
+        );
+        extend_instance_ttl(&env);
 
-        // TODO: Implement actual liquidation logic (transfer to liquidator, auction, etc.)
-        
         log!(
             &env,
-            "Liquidated collateral {} for defaulted loan {}",
+            "Released coffee asset {} pledged to loan {}",
             coffee_asset,
             loan_id
         );
+
+        coffee_asset
     }
 
-    /// Update collateral valuation
-    pub fn update_valuation(
+    /// Liquidate collateral for defaulted loan
+    ///
+    /// `recovered_value` is what the sale of the lot returned; any gap to the
+    /// loan amount is kept as the liquidation shortfall for the bad-debt ledger.
+    pub fn liquidate_collateral(
         env: Env,
-        oracle: Address,
-        coffee_asset: Address,
-        new_valuation: u128,
-    ) {
-        oracle.require_auth();
-        
-        // Verify caller is authorized oracle
-        let stored_oracle: Address = env.storage().instance().get(&VALUATION_ORACLE).unwrap();
-        if oracle != stored_oracle {
-            panic!("Only valuation oracle can update valuations");
+        committee: Address,
+        loan_id: BytesN<32>,
+        recovered_value: u128,
+    ) -> u128 {
+        committee.require_auth();
+
+        // Verify caller is authorized committee
+        let stored_committee = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
+        if committee != stored_committee {
+            panic!("Only committee can liquidate collateral");
         }
 
-        let mut collateral_info: CollateralInfo = env
+        Self::ensure_liquidatable(&env, &stored_committee, &loan_id);
+
+        let coffee_asset: Address = env
             .storage()
             .persistent()
-            .get(&(COLLATERAL.clone(), coffee_asset.clone()))
-            .unwrap_or_else(|| panic!("Coffee asset not found"));
+            .get(&DataKey::LoanCollateral(loan_id.clone()))
+            .unwrap_or_else(|| panic!("No collateral found for loan"));
 
-        let old_valuation = collateral_info.estimated_value_usd;
-        collateral_info.estimated_value_usd = new_valuation;
-        
-        env.storage()
+        let collateral_info: CollateralInfo = env
+            .storage()
             .persistent()
-            .set(&(COLLATERAL.clone(), coffee_asset.clone()), &collateral_info);
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Collateral info not found"));
+
+        // TODO: Implement actual liquidation logic (transfer to liquidator, auction, etc.)
+        let shortfall = Self::record_liquidation(
+            &env,
+            &loan_id,
+            &coffee_asset,
+            collateral_info,
+            recovered_value,
+        );
 
         log!(
             &env,
-            "Updated valuation for coffee asset {} from ${} to ${}",
+            "Liquidated collateral {} for defaulted loan {}. Recovered: ${}, shortfall: ${}",
             coffee_asset,
-            old_valuation,
-            new_valuation
+            loan_id,
+            recovered_value,
+            shortfall
         );
-    }
 
-    /// Get collateral information
-    pub fn get_collateral_info(env: Env, coffee_asset: Address) -> Option<CollateralInfo> {
-        env.storage()
-            .persistent()
-            .get(&(COLLATERAL.clone(), coffee_asset))
+        shortfall
     }
 
-    /// Get collateral for a loan
-    pub fn get_loan_collateral(env: Env, loan_id: BytesN<32>) -> Option<Address> {
-        env.storage()
-            .persistent()
-            .get(&(LOAN_COLLATERAL.clone(), loan_id))
-    }
+    /// Buy the collateral of a defaulted loan at its price-index value less the bounty (anyone can call)
+    ///
+    /// Only possible once an installment is overdue past governance's liquidation grace
+    /// period. The lot is revalued from the price index, the liquidator pays that value
+    /// less the liquidation bounty and becomes the lot's buyer. The USDC vault receives up
+    /// to the loan amount and the borrower any surplus. Any gap between the net proceeds
+    /// and the loan amount is kept as the liquidation shortfall. Returns the bounty kept
+    /// by the liquidator.
+    pub fn liquidate(env: Env, liquidator: Address, loan_id: BytesN<32>) -> u128 {
+        liquidator.require_auth();
 
-    /// Get coffee batch details
-    pub fn get_coffee_details(env: Env, coffee_asset: Address) -> (String, String, String, Address) {
-        let batch_id: String = env
+        let governance = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
+        Self::ensure_liquidatable(&env, &governance, &loan_id);
+
+        let coffee_asset: Address = env
             .storage()
             .persistent()
-            .get(&(Symbol::new(&env, "batch_id"), coffee_asset.clone()))
-            .unwrap_or(String::from_str(&env, ""));
-        
-        let farm_location: String = env
+            .get(&DataKey::LoanCollateral(loan_id.clone()))
+            .unwrap_or_else(|| panic!("No collateral found for loan"));
+        let proceeds_token: Address = env
             .storage()
-            .persistent()
-            .get(&(Symbol::new(&env, "farm_location"), coffee_asset.clone()))
-            .unwrap_or(String::from_str(&env, ""));
-        
-        let harvest_date: String = env
+            .instance()
+            .get(&DataKey::ProceedsToken)
+            .unwrap_or_else(|| panic!("Liquidation proceeds token not set"));
+
+        // Only active lots can be revalued, so a lot is never liquidated twice
+        let recovered_value = Self::refresh_valuation(env.clone(), coffee_asset.clone());
+        let bounty_bps = Self::get_liquidation_bounty(env.clone()) as u128;
+        let bounty = recovered_value * bounty_bps / BASIS_POINTS;
+        let net_proceeds = recovered_value - bounty;
+
+        // Proceeds beyond the loan amount belong to the borrower, not the vault
+        let loan_amount: u128 = env
             .storage()
             .persistent()
-            .get(&(Symbol::new(&env, "harvest_date"), coffee_asset.clone()))
-            .unwrap_or(String::from_str(&env, ""));
-        
-        let issuer: Address = env
+            .get(&DataKey::LoanAmount(loan_id.clone()))
+            .unwrap_or(0);
+        let surplus = net_proceeds.saturating_sub(loan_amount);
+        let vault = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_USDC_VAULT,
+            &DataKey::ProceedsVault,
+        );
+        if net_proceeds > surplus {
+            transfer_or_abort(
+                &env,
+                &proceeds_token,
+                &liquidator,
+                &vault,
+                (net_proceeds - surplus) as i128,
+            );
+        }
+        if surplus > 0 {
+            let loan: Option<LoanProposal> = invoke_peer(
+                &env,
+                &governance,
+                "get_loan_proposal",
+                (loan_id.clone(),).into_val(&env),
+                ContractError::PeerCallFailed,
+            );
+            let borrower = loan.unwrap_or_else(|| panic!("Loan not found")).borrower;
+            transfer_or_abort(
+                &env,
+                &proceeds_token,
+                &liquidator,
+                &borrower,
+                surplus as i128,
+            );
+        }
+
+        let collateral_info: CollateralInfo = env
             .storage()
             .persistent()
-            .get(&(Symbol::new(&env, "issuer"), coffee_asset.clone()))
-            .unwrap_or_else(|| panic!("Issuer not found for coffee asset"));
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap();
+        let shortfall =
+            Self::record_liquidation(&env, &loan_id, &coffee_asset, collateral_info, net_proceeds);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LiquidatedTo(coffee_asset.clone()), &liquidator);
+        extend_persistent_ttl(&env, &DataKey::LiquidatedTo(coffee_asset.clone()));
 
-        (batch_id, farm_location, harvest_date, issuer)
+        log!(
+            &env,
+            "Collateral {} for defaulted loan {} bought by {} for ${} (bounty: ${}, shortfall: ${}, surplus: ${})",
+            coffee_asset,
+            loan_id,
+            liquidator,
+            net_proceeds,
+            bounty,
+            shortfall,
+            surplus
+        );
+
+        bounty
     }
 
-    /// List all active collateral assets
-    pub fn list_active_collateral(env: Env) -> Vec<Address> {
-        // This is a simplified implementation
-        // In practice, you'd want to maintain an index of active assets
-        Vec::new(&env) // Placeholder - would need proper indexing
+    /// Get the liquidator that bought a lot, if it was liquidated permissionlessly
+    pub fn get_liquidator(env: Env, coffee_asset: Address) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LiquidatedTo(coffee_asset))
     }
 
-    /// Mark collateral as expired (for time-sensitive coffee)
-    pub fn mark_expired(env: Env, admin: Address, coffee_asset: Address) {
+    /// Set the token liquidators pay in and the vault used when the registry has none (admin only)
+    pub fn set_liquidation_proceeds(env: Env, admin: Address, token: Address, vault: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set liquidation proceeds");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ProceedsToken, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::ProceedsVault, &vault);
+
+        log!(
+            &env,
+            "Liquidation proceeds set to {} paid into {}",
+            token,
+            vault
+        );
+    }
+
+    /// Set the liquidator's bounty, in bps of the recovered value (admin only)
+    pub fn set_liquidation_bounty(env: Env, admin: Address, bounty_bps: u32) {
         admin.require_auth();
-        
-        let stored_admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         if admin != stored_admin {
-            panic!("Only admin can mark collateral as expired");
+            panic!("Only admin can set the liquidation bounty");
+        }
+        if bounty_bps > MAX_LIQUIDATION_BOUNTY_BPS {
+            panic!(
+                "Liquidation bounty cannot exceed {} bps",
+                MAX_LIQUIDATION_BOUNTY_BPS
+            );
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidationBountyBps, &bounty_bps);
+
+        log!(&env, "Liquidation bounty set to {} bps", bounty_bps);
+    }
+
+    /// Get the liquidator's bounty, in bps of the recovered value
+    pub fn get_liquidation_bounty(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LiquidationBountyBps)
+            .unwrap_or(0)
+    }
+
+    /// Get the unrecovered loan amount from a liquidation
+    pub fn get_liquidation_shortfall(env: Env, loan_id: BytesN<32>) -> u128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LiquidationShortfall(loan_id))
+            .unwrap_or(0)
+    }
+
+    /// Update collateral valuation
+    pub fn update_valuation(env: Env, oracle: Address, coffee_asset: Address, new_valuation: u128) {
+        oracle.require_auth();
+
+        // Verify caller is authorized oracle
+        let stored_oracle: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::ValuationOracle)
+            .unwrap();
+        if oracle != stored_oracle {
+            panic!("Only valuation oracle can update valuations");
         }
 
         let mut collateral_info: CollateralInfo = env
             .storage()
             .persistent()
-            .get(&(COLLATERAL.clone(), coffee_asset.clone()))
+            .get(&DataKey::Collateral(coffee_asset.clone()))
             .unwrap_or_else(|| panic!("Coffee asset not found"));
 
-        collateral_info.status = CollateralStatus::Expired;
-        env.storage()
-            .persistent()
-            .set(&(COLLATERAL.clone(), coffee_asset.clone()), &collateral_info);
+        let old_valuation = collateral_info.estimated_value_usd;
+        collateral_info.estimated_value_usd = new_valuation;
+        Self::store_collateral(&env, &coffee_asset, &collateral_info);
 
-        log!(&env, "Marked coffee asset {} as expired", coffee_asset);
+        log!(
+            &env,
+            "Updated valuation for coffee asset {} from ${} to ${}",
+            coffee_asset,
+            old_valuation,
+            new_valuation
+        );
+
+        Self::check_health(&env, &coffee_asset, new_valuation);
     }
 
-    /// Calculate required collateral value for loan amount
-    pub fn calculate_required_collateral(env: Env, loan_amount: u128) -> u128 {
-        (loan_amount * COLLATERAL_RATIO_BASIS_POINTS) / 10000
+    /// Set the price feed used when the registry has no oracle entry (admin only)
+    pub fn set_price_oracle(env: Env, admin: Address, oracle: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the price oracle");
+        }
+
+        env.storage().instance().set(&DataKey::PriceOracle, &oracle);
+
+        log!(&env, "Coffee price oracle set to: {}", oracle);
+    }
+
+    /// Pull the base coffee price from the oracle feed (anyone can call)
+    pub fn refresh_base_price(env: Env) -> u128 {
+        let oracle = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_ORACLE,
+            &DataKey::PriceOracle,
+        );
+        let price_per_kg: u128 = invoke_peer(
+            &env,
+            &oracle,
+            "get_price",
+            (Symbol::new(&env, COFFEE_PRICE_FEED),).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        if price_per_kg == 0 {
+            panic!("Oracle returned a zero coffee price");
+        }
+
+        let base = CoffeePrice {
+            price_per_kg,
+            updated_at: env.ledger().timestamp(),
+        };
+        env.storage().instance().set(&DataKey::BasePrice, &base);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Coffee base price refreshed to {} per kg",
+            price_per_kg
+        );
+
+        price_per_kg
+    }
+
+    /// Get the last base coffee price pulled from the oracle
+    pub fn get_base_price(env: Env) -> Option<CoffeePrice> {
+        env.storage().instance().get(&DataKey::BasePrice)
+    }
+
+    /// Set the grade adjustment curve (governance or admin)
+    ///
+    /// Points must be in strictly increasing grade order; grades between points
+    /// are interpolated and grades outside them take the nearest point's multiplier.
+    pub fn set_grade_curve(env: Env, caller: Address, points: Vec<GradePoint>) {
+        caller.require_auth();
+
+        let governance = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if caller != governance && caller != admin {
+            panic!("Only governance or admin can set the grade curve");
+        }
+
+        let mut previous_grade = 0;
+        for point in points.iter() {
+            if point.grade == 0 || point.grade > 100 {
+                panic!("Quality grade must be between 1 and 100");
+            }
+            if point.grade <= previous_grade {
+                panic!("Grade curve points must be in increasing grade order");
+            }
+            if point.multiplier_bps > MAX_GRADE_MULTIPLIER_BPS {
+                panic!(
+                    "Grade multiplier cannot exceed {} bps",
+                    MAX_GRADE_MULTIPLIER_BPS
+                );
+            }
+            previous_grade = point.grade;
+        }
+
+        env.storage().instance().set(&DataKey::GradeCurve, &points);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Grade curve set with {} points by {}",
+            points.len(),
+            caller
+        );
+    }
+
+    /// Get the grade adjustment curve
+    pub fn get_grade_curve(env: Env) -> Vec<GradePoint> {
+        env.storage()
+            .instance()
+            .get(&DataKey::GradeCurve)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the share of the base price, in bps, a kg of the given grade is worth
+    pub fn grade_multiplier(env: Env, grade: u32) -> u32 {
+        if grade == 0 || grade > 100 {
+            panic!("Quality grade must be between 1 and 100");
+        }
+
+        // Without a curve every grade is valued at the base price
+        let points = Self::get_grade_curve(env);
+        let Some(first) = points.first() else {
+            return BASIS_POINTS as u32;
+        };
+        if grade <= first.grade {
+            return first.multiplier_bps;
+        }
+
+        let mut lower = first;
+        for upper in points.iter().skip(1) {
+            if grade <= upper.grade {
+                let span = (upper.grade - lower.grade) as i64;
+                let offset = (grade - lower.grade) as i64;
+                let delta = upper.multiplier_bps as i64 - lower.multiplier_bps as i64;
+                return (lower.multiplier_bps as i64 + delta * offset / span) as u32;
+            }
+            lower = upper;
+        }
+
+        lower.multiplier_bps
+    }
+
+    /// Get the current price of one kg of coffee of the given grade
+    pub fn price_per_kg(env: Env, grade: u32) -> u128 {
+        let base = Self::get_base_price(env.clone())
+            .unwrap_or_else(|| panic!("Coffee base price has not been set"));
+        if env.ledger().timestamp() > base.updated_at + COFFEE_PRICE_MAX_AGE {
+            panic!("Coffee base price is stale");
+        }
+
+        base.price_per_kg * Self::grade_multiplier(env, grade) as u128 / BASIS_POINTS
+    }
+
+    /// Revalue an active coffee asset from the price index (anyone can call)
+    pub fn refresh_valuation(env: Env, coffee_asset: Address) -> u128 {
+        let mut collateral_info: CollateralInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Coffee asset not found"));
+        if collateral_info.status != CollateralStatus::Active {
+            panic!("Only active collateral can be revalued");
+        }
+
+        let price_per_kg = Self::price_per_kg(env.clone(), collateral_info.quality_grade);
+        let old_valuation = collateral_info.estimated_value_usd;
+        let new_valuation = collateral_info.quantity_kg * price_per_kg;
+        if new_valuation == 0 {
+            panic!("Refreshed valuation must be greater than 0");
+        }
+
+        collateral_info.estimated_value_usd = new_valuation;
+        Self::store_collateral(&env, &coffee_asset, &collateral_info);
+
+        log!(
+            &env,
+            "Refreshed valuation for coffee asset {} from ${} to ${}",
+            coffee_asset,
+            old_valuation,
+            new_valuation
+        );
+
+        Self::check_health(&env, &coffee_asset, new_valuation);
+
+        new_valuation
+    }
+
+    /// Set the health factors that raise watch, warning and critical alerts (governance or admin)
+    ///
+    /// Thresholds are in bps of the loan amount and must strictly decrease from
+    /// watch to critical. `None` turns alerting off.
+    pub fn set_alert_thresholds(env: Env, caller: Address, thresholds: Option<AlertThresholds>) {
+        caller.require_auth();
+
+        let governance = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Committee,
+        );
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if caller != governance && caller != admin {
+            panic!("Only governance or admin can set alert thresholds");
+        }
+
+        match thresholds {
+            Some(thresholds) => {
+                if thresholds.critical_bps == 0
+                    || thresholds.warning_bps <= thresholds.critical_bps
+                    || thresholds.watch_bps <= thresholds.warning_bps
+                {
+                    panic!("Alert thresholds must decrease from watch to critical");
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::AlertThresholds, &thresholds);
+                log!(
+                    &env,
+                    "Alert thresholds set to {}/{}/{} bps by {}",
+                    thresholds.watch_bps,
+                    thresholds.warning_bps,
+                    thresholds.critical_bps,
+                    caller
+                );
+            }
+            None => {
+                env.storage().instance().remove(&DataKey::AlertThresholds);
+                log!(&env, "Alert thresholds cleared by {}", caller);
+            }
+        }
+    }
+
+    /// Get the health factors that raise alerts, if alerting is on
+    pub fn get_alert_thresholds(env: Env) -> Option<AlertThresholds> {
+        env.storage().instance().get(&DataKey::AlertThresholds)
+    }
+
+    /// Get a loan's health factor: its collateral value in bps of the loan amount
+    pub fn get_health_factor(env: Env, loan_id: BytesN<32>) -> u128 {
+        let coffee_asset: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanCollateral(loan_id.clone()))
+            .unwrap_or_else(|| panic!("No collateral found for loan"));
+        let collateral_info: CollateralInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset))
+            .unwrap_or_else(|| panic!("Collateral info not found"));
+
+        Self::health_factor(&env, &loan_id, collateral_info.estimated_value_usd)
+    }
+
+    /// Get the alert tier a loan was last placed in by a valuation update
+    pub fn get_alert_tier(env: Env, loan_id: BytesN<32>) -> AlertTier {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AlertTier(loan_id))
+            .unwrap_or(AlertTier::Healthy)
+    }
+
+    /// Set the token lots are listed and settled in, normally USDC (admin only)
+    ///
+    /// Offers already escrowed keep the token they were made in.
+    pub fn set_market_token(env: Env, admin: Address, token: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the market token");
+        }
+
+        env.storage().instance().set(&DataKey::MarketToken, &token);
+
+        log!(&env, "Market token set to: {}", token);
+    }
+
+    /// Get the current owner of a coffee asset: its last buyer, or else its issuer
+    pub fn get_owner(env: Env, coffee_asset: Address) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Owner(coffee_asset.clone()))
+            .or_else(|| {
+                env.storage()
+                    .persistent()
+                    .get(&DataKey::Issuer(coffee_asset))
+            })
+            .unwrap_or_else(|| panic!("Coffee asset not found"))
+    }
+
+    /// List an unencumbered lot for sale at a fixed price (owner only)
+    ///
+    /// Relisting replaces the asking price. Pledging the lot to a loan blocks the sale
+    /// until the pledge is gone.
+    pub fn list_lot(env: Env, owner: Address, coffee_asset: Address, price: u128) {
+        owner.require_auth();
+        ensure_not_paused(&env);
+        Self::ensure_sellable(&env, &owner, &coffee_asset);
+
+        if price == 0 {
+            panic!("Listing price must be greater than 0");
+        }
+
+        let listing = LotListing {
+            seller: owner.clone(),
+            price,
+            listed_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Listing(coffee_asset.clone()), &listing);
+        extend_persistent_ttl(&env, &DataKey::Listing(coffee_asset.clone()));
+
+        log!(
+            &env,
+            "Coffee asset {} listed by {} for {}",
+            coffee_asset,
+            owner,
+            price
+        );
+    }
+
+    /// Withdraw a lot from sale (seller only)
+    pub fn cancel_listing(env: Env, owner: Address, coffee_asset: Address) {
+        owner.require_auth();
+
+        let listing = Self::get_listing(env.clone(), coffee_asset.clone())
+            .unwrap_or_else(|| panic!("Coffee asset is not listed"));
+        if listing.seller != owner {
+            panic!("Only the seller can cancel a listing");
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Listing(coffee_asset.clone()));
+
+        log!(&env, "Listing for coffee asset {} cancelled", coffee_asset);
+    }
+
+    /// Get a lot's listing, if it is for sale
+    pub fn get_listing(env: Env, coffee_asset: Address) -> Option<LotListing> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Listing(coffee_asset))
+    }
+
+    /// Buy a listed lot at its asking price, paying the seller directly
+    ///
+    /// Aborts with `SlippageExceeded` if the asking price is above `max_price`.
+    pub fn buy_lot(env: Env, buyer: Address, coffee_asset: Address, max_price: u128) {
+        buyer.require_auth();
+        ensure_not_paused(&env);
+
+        let listing = Self::get_listing(env.clone(), coffee_asset.clone())
+            .unwrap_or_else(|| panic!("Coffee asset is not listed"));
+        if listing.price > max_price {
+            panic_with_error!(&env, ContractError::SlippageExceeded);
+        }
+        // The listing is stale if the lot changed hands or was pledged since
+        Self::ensure_sellable(&env, &listing.seller, &coffee_asset);
+
+        let token = Self::market_token(&env);
+        Self::settle_sale(
+            &env,
+            &coffee_asset,
+            &listing.seller,
+            &buyer,
+            &token,
+            listing.price,
+        );
+        transfer_or_abort(&env, &token, &buyer, &listing.seller, listing.price as i128);
+    }
+
+    /// Offer to buy a lot, escrowing the amount in this contract until accepted or withdrawn
+    pub fn make_offer(env: Env, buyer: Address, coffee_asset: Address, amount: u128) {
+        buyer.require_auth();
+        ensure_not_paused(&env);
+
+        if amount == 0 {
+            panic!("Offer must be greater than 0");
+        }
+        if Self::get_owner(env.clone(), coffee_asset.clone()) == buyer {
+            panic!("Owner cannot make an offer on their own lot");
+        }
+        let offer_key = DataKey::Offer(coffee_asset.clone(), buyer.clone());
+        if env.storage().persistent().has(&offer_key) {
+            panic!("Withdraw the existing offer before making a new one");
+        }
+
+        let token = Self::market_token(&env);
+        let offer = LotOffer {
+            amount,
+            token: token.clone(),
+        };
+        env.storage().persistent().set(&offer_key, &offer);
+        extend_persistent_ttl(&env, &offer_key);
+        transfer_or_abort(
+            &env,
+            &token,
+            &buyer,
+            &env.current_contract_address(),
+            amount as i128,
+        );
+
+        log!(
+            &env,
+            "Offer of {} on coffee asset {} by {}",
+            amount,
+            coffee_asset,
+            buyer
+        );
+    }
+
+    /// Withdraw an offer and its escrowed funds (buyer only)
+    pub fn withdraw_offer(env: Env, buyer: Address, coffee_asset: Address) -> u128 {
+        buyer.require_auth();
+
+        let offer = Self::take_offer(&env, &coffee_asset, &buyer);
+        transfer_or_abort(
+            &env,
+            &offer.token,
+            &env.current_contract_address(),
+            &buyer,
+            offer.amount as i128,
+        );
+
+        log!(
+            &env,
+            "Offer on coffee asset {} withdrawn by {}",
+            coffee_asset,
+            buyer
+        );
+
+        offer.amount
+    }
+
+    /// Accept a buyer's escrowed offer, selling the lot to them (owner only)
+    ///
+    /// The owner is paid in the token the offer was escrowed in.
+    pub fn accept_offer(env: Env, owner: Address, coffee_asset: Address, buyer: Address) -> u128 {
+        owner.require_auth();
+        ensure_not_paused(&env);
+        Self::ensure_sellable(&env, &owner, &coffee_asset);
+
+        let offer = Self::take_offer(&env, &coffee_asset, &buyer);
+        Self::settle_sale(
+            &env,
+            &coffee_asset,
+            &owner,
+            &buyer,
+            &offer.token,
+            offer.amount,
+        );
+        transfer_or_abort(
+            &env,
+            &offer.token,
+            &env.current_contract_address(),
+            &owner,
+            offer.amount as i128,
+        );
+
+        offer.amount
+    }
+
+    /// Get a buyer's escrowed offer on a lot, if any
+    pub fn get_offer(env: Env, coffee_asset: Address, buyer: Address) -> Option<LotOffer> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Offer(coffee_asset, buyer))
+    }
+
+    /// Get a lot's most recent marketplace sale, a reference price for valuations and liquidations
+    pub fn get_last_sale(env: Env, coffee_asset: Address) -> Option<LotSale> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LastSale(coffee_asset))
+    }
+
+    /// Get collateral information
+    pub fn get_collateral_info(env: Env, coffee_asset: Address) -> Option<CollateralInfo> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset))
+    }
+
+    /// Get collateral for a loan
+    pub fn get_loan_collateral(env: Env, loan_id: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanCollateral(loan_id))
+    }
+
+    /// Get the loan a coffee asset is pledged to, if any
+    pub fn get_pledged_loan(env: Env, coffee_asset: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PledgedTo(coffee_asset))
+    }
+
+    /// Get coffee batch details
+    pub fn get_coffee_details(
+        env: Env,
+        coffee_asset: Address,
+    ) -> (String, String, String, Address) {
+        let batch_id: String = env
+            .storage()
+            .persistent()
+            .get(&DataKey::BatchId(coffee_asset.clone()))
+            .unwrap_or(String::from_str(&env, ""));
+
+        let farm_location: String = env
+            .storage()
+            .persistent()
+            .get(&DataKey::FarmLocation(coffee_asset.clone()))
+            .unwrap_or(String::from_str(&env, ""));
+
+        let harvest_date: String = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HarvestDate(coffee_asset.clone()))
+            .unwrap_or(String::from_str(&env, ""));
+
+        let issuer: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Issuer(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Issuer not found for coffee asset"));
+
+        (batch_id, farm_location, harvest_date, issuer)
+    }
+
+    /// List the first page of active collateral assets
+    pub fn list_active_collateral(env: Env) -> Vec<Address> {
+        Self::list_collateral(env, CollateralStatus::Active, 0, COLLATERAL_PAGE_SIZE)
+    }
+
+    /// List collateral assets in a status, in the order they entered it
+    ///
+    /// `limit` is capped at `COLLATERAL_PAGE_SIZE`. Lots stored before the index
+    /// existed are listed once `migrate_records` or a status or valuation change
+    /// touches them.
+    pub fn list_collateral(
+        env: Env,
+        status: CollateralStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<Address> {
+        let index: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::StatusIndex(status))
+            .unwrap_or(Vec::new(&env));
+        let start = offset.min(index.len());
+        index.slice(start..(start + limit.min(COLLATERAL_PAGE_SIZE)).min(index.len()))
+    }
+
+    /// List loans with registered collateral, oldest registration first
+    ///
+    /// `limit` is capped at `COLLATERAL_PAGE_SIZE`.
+    pub fn list_collateralized_loans(env: Env, offset: u32, limit: u32) -> Vec<BytesN<32>> {
+        let index: Vec<BytesN<32>> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CollateralizedLoans)
+            .unwrap_or(Vec::new(&env));
+        let start = offset.min(index.len());
+        index.slice(start..(start + limit.min(COLLATERAL_PAGE_SIZE)).min(index.len()))
+    }
+
+    /// Get lot counts by status with the value and average grade of active lots
+    pub fn get_collateral_summary(env: Env) -> CollateralSummary {
+        let active = Self::status_count(&env, &CollateralStatus::Active);
+        let grade_total: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActiveGradeTotal)
+            .unwrap_or(0);
+        CollateralSummary {
+            active,
+            liquidated: Self::status_count(&env, &CollateralStatus::Liquidated),
+            expired: Self::status_count(&env, &CollateralStatus::Expired),
+            retired: Self::status_count(&env, &CollateralStatus::Retired),
+            active_value_usd: env
+                .storage()
+                .instance()
+                .get(&DataKey::ActiveValue)
+                .unwrap_or(0),
+            average_grade: if active == 0 {
+                0
+            } else {
+                (grade_total / active as u128) as u32
+            },
+        }
+    }
+
+    /// Mark collateral as expired (for time-sensitive coffee)
+    pub fn mark_expired(env: Env, admin: Address, coffee_asset: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can mark collateral as expired");
+        }
+
+        let mut collateral_info: CollateralInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Coffee asset not found"));
+
+        collateral_info.status = CollateralStatus::Expired;
+        Self::store_collateral(&env, &coffee_asset, &collateral_info);
+
+        log!(&env, "Marked coffee asset {} as expired", coffee_asset);
+    }
+
+    /// Retire a lot that was physically delivered or exported (owner only)
+    ///
+    /// The lot moves out of the active indexes and totals and its listing is
+    /// withdrawn; escrowed offers stay withdrawable by their buyers. Lots pledged to
+    /// a loan cannot be retired. `delivery_proof_hash` is kept as the record of the
+    /// delivery.
+    pub fn retire_asset(
+        env: Env,
+        owner: Address,
+        coffee_asset: Address,
+        delivery_proof_hash: BytesN<32>,
+    ) {
+        owner.require_auth();
+        ensure_not_paused(&env);
+
+        if Self::get_owner(env.clone(), coffee_asset.clone()) != owner {
+            panic!("Only the owner can retire a coffee asset");
+        }
+        let mut collateral_info: CollateralInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Coffee asset not found"));
+        if collateral_info.status != CollateralStatus::Active {
+            panic!("Only active coffee assets can be retired");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::PledgedTo(coffee_asset.clone()))
+        {
+            panic!("Collateral is pledged to a loan");
+        }
+
+        collateral_info.status = CollateralStatus::Retired;
+        Self::store_collateral(&env, &coffee_asset, &collateral_info);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Listing(coffee_asset.clone()));
+        env.storage().persistent().set(
+            &DataKey::DeliveryProof(coffee_asset.clone()),
+            &delivery_proof_hash,
+        );
+        extend_persistent_ttl(&env, &DataKey::DeliveryProof(coffee_asset.clone()));
+        extend_instance_ttl(&env);
+
+        env.events().publish(
+            (symbol_short!("retired"), coffee_asset.clone()),
+            (owner.clone(), delivery_proof_hash),
+        );
+
+        log!(
+            &env,
+            "Coffee asset {} retired by {} on delivery",
+            coffee_asset,
+            owner
+        );
+    }
+
+    /// Get the delivery proof hash a retired lot was retired with
+    pub fn get_delivery_proof(env: Env, coffee_asset: Address) -> Option<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DeliveryProof(coffee_asset))
+    }
+
+    /// Calculate required collateral value for loan amount
+    pub fn calculate_required_collateral(env: Env, loan_amount: u128) -> u128 {
+        (loan_amount * COLLATERAL_RATIO_BASIS_POINTS) / 10000
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can assign roles");
+        }
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new coffee assets and collateral registrations (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new coffee assets and collateral registrations are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address, e.g. the multisig contract (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can transfer admin rights");
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
+
+    /// Set the address registry used to resolve peer contracts (admin only)
+    pub fn set_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the registry");
+        }
+
+        env.storage().instance().set(&DataKey::Registry, &registry);
+
+        log!(&env, "Address registry set to: {}", registry);
+    }
+
+    /// Internal helper functions
+    fn migrate_v1_instance(env: &Env) {
+        migrate_instance_entry(env, &v1::ADMIN, &DataKey::Admin);
+        migrate_instance_entry(env, &v1::COMMITTEE, &DataKey::Committee);
+        migrate_instance_entry(env, &v1::ASSET_COUNTER, &DataKey::AssetCounter);
+        migrate_instance_entry(env, &v1::VALUATION_ORACLE, &DataKey::ValuationOracle);
+        migrate_instance_entry(env, &v1::REGISTRY, &DataKey::Registry);
+    }
+
+    fn ensure_liquidatable(env: &Env, governance: &Address, loan_id: &BytesN<32>) {
+        // A loan is only in default once an installment is overdue past the grace period
+        let liquidatable: bool = invoke_peer(
+            env,
+            governance,
+            "is_loan_liquidatable",
+            (loan_id.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        if !liquidatable {
+            panic!("Loan has no installment overdue past the grace period");
+        }
+    }
+
+    fn store_collateral(env: &Env, coffee_asset: &Address, info: &CollateralInfo) {
+        let key = DataKey::Collateral(coffee_asset.clone());
+        let previous: Option<CollateralInfo> = env.storage().persistent().get(&key);
+        env.storage().persistent().set(&key, info);
+        extend_persistent_ttl(env, &key);
+        Self::index_collateral(env, coffee_asset, previous.as_ref(), info);
+    }
+
+    fn index_collateral(
+        env: &Env,
+        coffee_asset: &Address,
+        previous: Option<&CollateralInfo>,
+        info: &CollateralInfo,
+    ) {
+        // Take the old record out of the totals, and out of its status index when it moves
+        let mut active_value: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActiveValue)
+            .unwrap_or(0);
+        let mut grade_total: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActiveGradeTotal)
+            .unwrap_or(0);
+        let mut indexed = false;
+        if let Some(previous) = previous {
+            let index_key = DataKey::StatusIndex(previous.status.clone());
+            let mut index: Vec<Address> = env
+                .storage()
+                .persistent()
+                .get(&index_key)
+                .unwrap_or(Vec::new(env));
+            if let Some(position) = index.first_index_of(coffee_asset) {
+                if previous.status == CollateralStatus::Active {
+                    active_value = active_value.saturating_sub(previous.estimated_value_usd);
+                    grade_total = grade_total.saturating_sub(previous.quality_grade as u128);
+                }
+                if previous.status == info.status {
+                    indexed = true;
+                } else {
+                    index.remove(position);
+                    env.storage().persistent().set(&index_key, &index);
+                    let count = Self::status_count(env, &previous.status);
+                    env.storage()
+                        .instance()
+                        .set(&DataKey::StatusCount(previous.status.clone()), &(count - 1));
+                }
+            }
+        }
+
+        if !indexed {
+            let index_key = DataKey::StatusIndex(info.status.clone());
+            let mut index: Vec<Address> = env
+                .storage()
+                .persistent()
+                .get(&index_key)
+                .unwrap_or(Vec::new(env));
+            index.push_back(coffee_asset.clone());
+            env.storage().persistent().set(&index_key, &index);
+            extend_persistent_ttl(env, &index_key);
+            let count = Self::status_count(env, &info.status);
+            env.storage()
+                .instance()
+                .set(&DataKey::StatusCount(info.status.clone()), &(count + 1));
+        }
+        if info.status == CollateralStatus::Active {
+            active_value += info.estimated_value_usd;
+            grade_total += info.quality_grade as u128;
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::ActiveValue, &active_value);
+        env.storage()
+            .instance()
+            .set(&DataKey::ActiveGradeTotal, &grade_total);
+    }
+
+    fn index_loan(env: &Env, loan_id: &BytesN<32>) {
+        let mut loans: Vec<BytesN<32>> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CollateralizedLoans)
+            .unwrap_or(Vec::new(env));
+        loans.push_back(loan_id.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::CollateralizedLoans, &loans);
+        extend_persistent_ttl(env, &DataKey::CollateralizedLoans);
+    }
+
+    fn status_count(env: &Env, status: &CollateralStatus) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::StatusCount(status.clone()))
+            .unwrap_or(0)
+    }
+
+    fn record_liquidation(
+        env: &Env,
+        loan_id: &BytesN<32>,
+        coffee_asset: &Address,
+        mut collateral_info: CollateralInfo,
+        recovered_value: u128,
+    ) -> u128 {
+        collateral_info.status = CollateralStatus::Liquidated;
+        Self::store_collateral(env, coffee_asset, &collateral_info);
+        let pledged_kg = Self::get_pledged_quantity(env.clone());
+        env.storage().instance().set(
+            &DataKey::PledgedKg,
+            &pledged_kg.saturating_sub(collateral_info.quantity_kg),
+        );
+
+        let loan_amount: u128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanAmount(loan_id.clone()))
+            .unwrap_or(0);
+        let shortfall = if recovered_value < loan_amount {
+            loan_amount - recovered_value
+        } else {
+            0
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::LiquidationShortfall(loan_id.clone()), &shortfall);
+        extend_persistent_ttl(env, &DataKey::LiquidationShortfall(loan_id.clone()));
+
+        shortfall
+    }
+
+    fn health_factor(env: &Env, loan_id: &BytesN<32>, valuation: u128) -> u128 {
+        let loan_amount: u128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanAmount(loan_id.clone()))
+            .unwrap_or(0);
+        if loan_amount == 0 {
+            return u128::MAX;
+        }
+
+        valuation * BASIS_POINTS / loan_amount
+    }
+
+    // Emits an alert when a revaluation moves the backed loan into a different tier
+    fn check_health(env: &Env, coffee_asset: &Address, valuation: u128) {
+        let Some(thresholds) = Self::get_alert_thresholds(env.clone()) else {
+            return;
+        };
+        let Some(loan_id) = env
+            .storage()
+            .persistent()
+            .get::<_, BytesN<32>>(&DataKey::PledgedTo(coffee_asset.clone()))
+        else {
+            return;
+        };
+
+        let health = Self::health_factor(env, &loan_id, valuation);
+        let tier = if health < thresholds.critical_bps {
+            AlertTier::Critical
+        } else if health < thresholds.warning_bps {
+            AlertTier::Warning
+        } else if health < thresholds.watch_bps {
+            AlertTier::Watch
+        } else {
+            AlertTier::Healthy
+        };
+        if tier == Self::get_alert_tier(env.clone(), loan_id.clone()) {
+            return;
+        }
+
+        let key = DataKey::AlertTier(loan_id.clone());
+        env.storage().persistent().set(&key, &tier);
+        extend_persistent_ttl(env, &key);
+
+        // Monitoring escalates on these before liquidation conditions are reached
+        env.events().publish(
+            (symbol_short!("alert"), loan_id.clone()),
+            (tier.clone(), health),
+        );
+
+        log!(
+            env,
+            "Loan {} moved to {:?} alert tier at health factor {} bps",
+            loan_id,
+            tier,
+            health
+        );
+    }
+
+    fn ensure_sellable(env: &Env, owner: &Address, coffee_asset: &Address) {
+        if Self::get_owner(env.clone(), coffee_asset.clone()) != *owner {
+            panic!("Only the owner can sell a coffee asset");
+        }
+
+        let collateral_info: CollateralInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Collateral(coffee_asset.clone()))
+            .unwrap_or_else(|| panic!("Coffee asset not found"));
+        if collateral_info.status != CollateralStatus::Active {
+            panic!("Collateral is not active");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::PledgedTo(coffee_asset.clone()))
+        {
+            panic!("Collateral is pledged to a loan");
+        }
+    }
+
+    fn market_token(env: &Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::MarketToken)
+            .unwrap_or_else(|| panic!("Market token not set"))
+    }
+
+    fn take_offer(env: &Env, coffee_asset: &Address, buyer: &Address) -> LotOffer {
+        let offer_key = DataKey::Offer(coffee_asset.clone(), buyer.clone());
+        let offer: LotOffer = env
+            .storage()
+            .persistent()
+            .get(&offer_key)
+            .unwrap_or_else(|| panic!("No offer found for buyer"));
+        env.storage().persistent().remove(&offer_key);
+        offer
+    }
+
+    fn settle_sale(
+        env: &Env,
+        coffee_asset: &Address,
+        seller: &Address,
+        buyer: &Address,
+        token: &Address,
+        price: u128,
+    ) {
+        // Ownership moves before payment; a failed payment reverts the whole sale
+        let sale = LotSale {
+            seller: seller.clone(),
+            buyer: buyer.clone(),
+            price,
+            token: token.clone(),
+            sold_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Owner(coffee_asset.clone()), buyer);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LastSale(coffee_asset.clone()), &sale);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Listing(coffee_asset.clone()));
+        extend_persistent_ttl(env, &DataKey::Owner(coffee_asset.clone()));
+        extend_persistent_ttl(env, &DataKey::LastSale(coffee_asset.clone()));
+        extend_instance_ttl(env);
+
+        log!(
+            env,
+            "Coffee asset {} sold by {} to {} for {}",
+            coffee_asset,
+            seller,
+            buyer,
+            price
+        );
     }
 }
@@ -0,0 +1,412 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, ContractInfo, DeployedContract, ProtocolAddresses, ProtocolConfig,
+    ProtocolWasmHashes, Role, REGISTRY_COFFEE_COLLATERAL, REGISTRY_GOLD_VAULT, REGISTRY_GOVERNANCE,
+    REGISTRY_ORACLE, REGISTRY_USDC_VAULT, REGISTRY_YIELD_TOKEN, TOTAL_COMMITTEE_SIZE,
+};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, Address, Bytes, BytesN, Env, IntoVal, String, Symbol,
+};
+use soroban_token_sdk::metadata::TokenMetadata;
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: admin of the deployer itself
+    Admin,
+    // Persistent: deployed address sets by admin and salt
+    Deployment(Address, BytesN<32>),
+    // Persistent: admin and salt each contract was deployed with
+    DeployedContract(Address),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Deployer;
+
+#[contractimpl]
+impl Deployer {
+    /// Initialize the deployer with the admin that assigns its roles
+    ///
+    /// Deploying stays open to anyone; the admin only manages the pauser.
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Deployer initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Deploy and initialize the full protocol in a single transaction
+    ///
+    /// Every contract is initialized with this deployer as its temporary admin,
+    /// cross-wired to its peers, and then handed over to `admin`, so there is
+    /// no window between init calls for anyone else to claim a contract.
+    ///
+    /// Contract salts are derived from `admin` as well as `salt`, so nobody
+    /// can take a deployment's addresses by calling first with the same salt.
+    pub fn deploy_protocol(
+        env: Env,
+        admin: Address,
+        salt: BytesN<32>,
+        wasm_hashes: ProtocolWasmHashes,
+        token_metadata: TokenMetadata,
+        config: ProtocolConfig,
+    ) -> ProtocolAddresses {
+        admin.require_auth();
+        ensure_not_paused(&env);
+
+        let deployment_key = DataKey::Deployment(admin.clone(), salt.clone());
+        if env.storage().persistent().has(&deployment_key) {
+            panic!("Protocol already deployed with this salt");
+        }
+
+        if config.initial_committee.len() != TOTAL_COMMITTEE_SIZE {
+            panic!(
+                "Committee must have exactly {} members",
+                TOTAL_COMMITTEE_SIZE
+            );
+        }
+
+        // Deploy all contracts first so their addresses can be cross-wired
+        let addresses = ProtocolAddresses {
+            yield_token: Self::deploy_contract(&env, &admin, &salt, 0, &wasm_hashes.yield_token),
+            usdc_vault: Self::deploy_contract(&env, &admin, &salt, 1, &wasm_hashes.usdc_vault),
+            gold_vault: Self::deploy_contract(&env, &admin, &salt, 2, &wasm_hashes.gold_vault),
+            governance: Self::deploy_contract(&env, &admin, &salt, 3, &wasm_hashes.governance),
+            coffee_collateral: Self::deploy_contract(
+                &env,
+                &admin,
+                &salt,
+                4,
+                &wasm_hashes.coffee_collateral,
+            ),
+            oracle: Self::deploy_contract(&env, &admin, &salt, 5, &wasm_hashes.oracle),
+            multisig: Self::deploy_contract(&env, &admin, &salt, 6, &wasm_hashes.multisig),
+            registry: Self::deploy_contract(&env, &admin, &salt, 7, &wasm_hashes.registry),
+        };
+
+        let deployer = env.current_contract_address();
+        let initialize = Symbol::new(&env, "initialize");
+
+        env.invoke_contract::<()>(
+            &addresses.registry,
+            &initialize,
+            (deployer.clone(),).into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.oracle,
+            &initialize,
+            (deployer.clone(),).into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.yield_token,
+            &initialize,
+            (deployer.clone(), token_metadata).into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.usdc_vault,
+            &initialize,
+            (
+                deployer.clone(),
+                config.usdc_contract.clone(),
+                addresses.yield_token.clone(),
+            )
+                .into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.gold_vault,
+            &initialize,
+            (
+                deployer.clone(),
+                addresses.yield_token.clone(),
+                addresses.oracle.clone(),
+                config.supported_gold_assets.clone(),
+            )
+                .into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.governance,
+            &initialize,
+            (
+                deployer.clone(),
+                addresses.yield_token.clone(),
+                config.initial_committee.clone(),
+                config.min_proposal_tokens,
+            )
+                .into_val(&env),
+        );
+
+        env.invoke_contract::<()>(
+            &addresses.coffee_collateral,
+            &initialize,
+            (
+                deployer.clone(),
+                addresses.governance.clone(),
+                addresses.oracle.clone(),
+            )
+                .into_val(&env),
+        );
+
+        // The multisig has no admin, so it only takes its signer set here
+        env.invoke_contract::<()>(
+            &addresses.multisig,
+            &initialize,
+            (config.multisig_signers, config.multisig_threshold).into_val(&env),
+        );
+
+        // Only the protocol's own vaults may mint and burn yield tokens
+        let set_authorized_vault = Symbol::new(&env, "set_authorized_vault");
+        for vault in [&addresses.usdc_vault, &addresses.gold_vault] {
+            env.invoke_contract::<()>(
+                &addresses.yield_token,
+                &set_authorized_vault,
+                (deployer.clone(), vault.clone(), true).into_val(&env),
+            );
+        }
+
+        // Register every contract and point its peers at the registry
+        let set_address = Symbol::new(&env, "set_address");
+        for (name, contract_address) in [
+            (REGISTRY_YIELD_TOKEN, &addresses.yield_token),
+            (REGISTRY_USDC_VAULT, &addresses.usdc_vault),
+            (REGISTRY_GOLD_VAULT, &addresses.gold_vault),
+            (REGISTRY_GOVERNANCE, &addresses.governance),
+            (REGISTRY_COFFEE_COLLATERAL, &addresses.coffee_collateral),
+            (REGISTRY_ORACLE, &addresses.oracle),
+        ] {
+            env.invoke_contract::<()>(
+                &addresses.registry,
+                &set_address,
+                (
+                    deployer.clone(),
+                    Symbol::new(&env, name),
+                    contract_address.clone(),
+                )
+                    .into_val(&env),
+            );
+        }
+
+        let set_registry = Symbol::new(&env, "set_registry");
+        for contract_address in [
+            &addresses.usdc_vault,
+            &addresses.gold_vault,
+            &addresses.governance,
+            &addresses.coffee_collateral,
+        ] {
+            env.invoke_contract::<()>(
+                contract_address,
+                &set_registry,
+                (deployer.clone(), addresses.registry.clone()).into_val(&env),
+            );
+        }
+
+        // Governance manages the vaults' lock tiers and settlement, and reports profits
+        let set_governance = Symbol::new(&env, "set_governance");
+        for contract_address in [
+            &addresses.usdc_vault,
+            &addresses.gold_vault,
+            &addresses.yield_token,
+        ] {
+            env.invoke_contract::<()>(
+                contract_address,
+                &set_governance,
+                (deployer.clone(), addresses.governance.clone()).into_val(&env),
+            );
+        }
+        env.invoke_contract::<()>(
+            &addresses.yield_token,
+            &Symbol::new(&env, "set_profit_reporter"),
+            (deployer.clone(), addresses.governance.clone()).into_val(&env),
+        );
+
+        // Hand every contract over to the real admin
+        let set_admin = Symbol::new(&env, "set_admin");
+        for contract_address in [
+            &addresses.yield_token,
+            &addresses.usdc_vault,
+            &addresses.gold_vault,
+            &addresses.governance,
+            &addresses.coffee_collateral,
+            &addresses.oracle,
+            &addresses.registry,
+        ] {
+            env.invoke_contract::<()>(
+                contract_address,
+                &set_admin,
+                (deployer.clone(), admin.clone()).into_val(&env),
+            );
+        }
+
+        env.storage().persistent().set(&deployment_key, &addresses);
+        extend_persistent_ttl(&env, &deployment_key);
+
+        log!(
+            &env,
+            "Protocol deployed for admin {}. Yield token: {}, USDC vault: {}, Gold vault: {}, Governance: {}, Coffee collateral: {}, Oracle: {}, Multisig: {}, Registry: {}",
+            admin,
+            addresses.yield_token,
+            addresses.usdc_vault,
+            addresses.gold_vault,
+            addresses.governance,
+            addresses.coffee_collateral,
+            addresses.oracle,
+            addresses.multisig,
+            addresses.registry
+        );
+
+        addresses
+    }
+
+    /// Get the addresses deployed for an admin with a given salt
+    pub fn get_deployment(env: Env, admin: Address, salt: BytesN<32>) -> Option<ProtocolAddresses> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Deployment(admin, salt))
+    }
+
+    /// Get the admin and salt a contract was deployed with, if this deployer created it
+    ///
+    /// Contracts migrating storage from before schema versioning check the
+    /// admin here instead of trusting their own stored admin.
+    pub fn get_deployed_contract(env: Env, contract: Address) -> Option<DeployedContract> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::DeployedContract(contract))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new protocol deployments (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new protocol deployments are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "deployer"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Internal helper functions
+    fn deploy_contract(
+        env: &Env,
+        admin: &Address,
+        salt: &BytesN<32>,
+        index: u8,
+        wasm_hash: &BytesN<32>,
+    ) -> Address {
+        // Derive a distinct salt per contract from the admin and deployment salt
+        let mut salt_bytes = admin.clone().to_xdr(env);
+        salt_bytes.append(&Bytes::from_array(env, &salt.to_array()));
+        salt_bytes.push_back(index);
+        let contract_salt: BytesN<32> = env.crypto().sha256(&salt_bytes).into();
+
+        let contract = env
+            .deployer()
+            .with_current_contract(contract_salt.clone())
+            .deploy(wasm_hash.clone());
+
+        let key = DataKey::DeployedContract(contract.clone());
+        env.storage().persistent().set(
+            &key,
+            &DeployedContract {
+                admin: admin.clone(),
+                salt: contract_salt,
+            },
+        );
+        extend_persistent_ttl(env, &key);
+
+        contract
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage the deployer");
+        }
+    }
+}
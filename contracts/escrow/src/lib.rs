@@ -0,0 +1,615 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, transfer_or_abort, ContractInfo, EscrowStatus, HoldbackStatus,
+    LoanEscrow, QaHoldback, Role, MAX_ESCROW_TIMEOUT, MIN_ESCROW_TIMEOUT, QA_SAMPLE_WINDOW,
+    REGISTRY_ESCROW,
+};
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, Address, BytesN, Env, String, Symbol, Vec,
+};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config, counter and per-token totals
+    Admin,
+    Partners,
+    NextEscrowId,
+    Reserved(Address),
+    QaLabs,
+    NextHoldbackId,
+    // Persistent: per-escrow records
+    Escrow(u64),
+    Holdback(u64),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Escrow;
+
+#[contractimpl]
+impl Escrow {
+    /// Initialize the loan disbursement escrow
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Partners, &Vec::<Address>::new(&env));
+        env.storage().instance().set(&DataKey::NextEscrowId, &1u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Loan escrow initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_ESCROW),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Register a fiat on/off-ramp partner allowed to claim escrows (admin only)
+    pub fn add_partner(env: Env, admin: Address, partner: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mut partners = Self::get_partners(env.clone());
+        if partners.contains(&partner) {
+            panic!("Partner already registered");
+        }
+
+        partners.push_back(partner.clone());
+        env.storage().instance().set(&DataKey::Partners, &partners);
+
+        log!(&env, "Ramp partner registered: {}", partner);
+    }
+
+    /// Deregister a ramp partner; its open escrows can then only be refunded (admin only)
+    pub fn remove_partner(env: Env, admin: Address, partner: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let partners = Self::get_partners(env.clone());
+        let mut remaining = Vec::new(&env);
+        for existing in partners.iter() {
+            if existing != partner {
+                remaining.push_back(existing);
+            }
+        }
+        if remaining.len() == partners.len() {
+            panic!("Partner not registered");
+        }
+        env.storage().instance().set(&DataKey::Partners, &remaining);
+
+        log!(&env, "Ramp partner removed: {}", partner);
+    }
+
+    /// Get every registered ramp partner
+    pub fn get_partners(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Partners)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Check whether an address is a registered ramp partner
+    pub fn is_partner(env: Env, partner: Address) -> bool {
+        Self::get_partners(env).contains(&partner)
+    }
+
+    /// Open an escrow over funds the funder has already transferred to this contract
+    ///
+    /// The escrowed amount must be covered by this contract's balance of the token
+    /// beyond what other open escrows already hold.
+    pub fn open(
+        env: Env,
+        funder: Address,
+        loan_id: BytesN<32>,
+        token: Address,
+        amount: u128,
+        recipient: Address,
+        partner: Address,
+        refund_to: Address,
+        timeout: u64,
+    ) -> u64 {
+        funder.require_auth();
+        ensure_not_paused(&env);
+
+        if amount == 0 {
+            panic!("Escrow amount must be greater than 0");
+        }
+        if !Self::is_partner(env.clone(), partner.clone()) {
+            panic!("Partner is not registered");
+        }
+        if !(MIN_ESCROW_TIMEOUT..=MAX_ESCROW_TIMEOUT).contains(&timeout) {
+            panic!(
+                "Escrow timeout must be between {} and {} seconds",
+                MIN_ESCROW_TIMEOUT, MAX_ESCROW_TIMEOUT
+            );
+        }
+
+        let reserved = Self::get_reserved(env.clone(), token.clone());
+        let balance = TokenClient::new(&env, &token).balance(&env.current_contract_address());
+        if (balance.max(0) as u128) < reserved + amount {
+            panic!("Escrow is not funded");
+        }
+
+        let escrow_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextEscrowId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextEscrowId, &(escrow_id + 1));
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserved(token.clone()), &(reserved + amount));
+
+        let now = env.ledger().timestamp();
+        let escrow = LoanEscrow {
+            id: escrow_id,
+            loan_id: loan_id.clone(),
+            token,
+            amount,
+            funder,
+            recipient,
+            partner: partner.clone(),
+            refund_to,
+            opened_at: now,
+            expires_at: now + timeout,
+            delivery_ref: None,
+            status: EscrowStatus::Open,
+        };
+        Self::store_escrow(&env, &escrow);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Escrow {} opened for loan {}: {} claimable by {} until {}",
+            escrow_id,
+            loan_id,
+            amount,
+            partner,
+            escrow.expires_at
+        );
+
+        escrow_id
+    }
+
+    /// Confirm the partner delivered the funds off-chain (recipient only, before expiry)
+    pub fn confirm_delivery(
+        env: Env,
+        recipient: Address,
+        escrow_id: u64,
+        delivery_ref: BytesN<32>,
+    ) {
+        recipient.require_auth();
+
+        let mut escrow = Self::load_escrow(&env, escrow_id);
+        if escrow.recipient != recipient {
+            panic!("Only the escrow recipient can confirm delivery");
+        }
+        if escrow.status != EscrowStatus::Open {
+            panic!("Escrow is not open");
+        }
+        if env.ledger().timestamp() > escrow.expires_at {
+            panic!("Escrow has expired");
+        }
+
+        escrow.delivery_ref = Some(delivery_ref.clone());
+        escrow.status = EscrowStatus::Confirmed;
+        Self::store_escrow(&env, &escrow);
+
+        log!(
+            &env,
+            "Delivery {} confirmed for escrow {} by {}",
+            delivery_ref,
+            escrow_id,
+            recipient
+        );
+    }
+
+    /// Claim a confirmed escrow (its registered partner only)
+    pub fn claim(env: Env, partner: Address, escrow_id: u64) -> u128 {
+        partner.require_auth();
+
+        let mut escrow = Self::load_escrow(&env, escrow_id);
+        if escrow.partner != partner {
+            panic!("Only the escrow partner can claim");
+        }
+        if !Self::is_partner(env.clone(), partner.clone()) {
+            panic!("Partner is not registered");
+        }
+        if escrow.status != EscrowStatus::Confirmed {
+            panic!("Delivery has not been confirmed");
+        }
+
+        escrow.status = EscrowStatus::Claimed;
+        Self::release(&env, &escrow, &partner);
+
+        log!(
+            &env,
+            "Escrow {} claimed by {}: {}",
+            escrow_id,
+            partner,
+            escrow.amount
+        );
+
+        escrow.amount
+    }
+
+    /// Return an unconfirmed escrow to its vault once it has expired (anyone can call)
+    pub fn refund(env: Env, escrow_id: u64) -> u128 {
+        let mut escrow = Self::load_escrow(&env, escrow_id);
+        if escrow.status != EscrowStatus::Open {
+            panic!("Only unconfirmed escrows can be refunded");
+        }
+        if env.ledger().timestamp() <= escrow.expires_at {
+            panic!("Escrow has not expired");
+        }
+
+        escrow.status = EscrowStatus::Refunded;
+        let refund_to = escrow.refund_to.clone();
+        Self::release(&env, &escrow, &refund_to);
+
+        log!(
+            &env,
+            "Escrow {} refunded to {}: {}",
+            escrow_id,
+            refund_to,
+            escrow.amount
+        );
+
+        escrow.amount
+    }
+
+    /// Register a QA lab allowed to post sample results for held-back loan funds (admin only)
+    pub fn add_qa_lab(env: Env, admin: Address, lab: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mut labs = Self::get_qa_labs(env.clone());
+        if labs.contains(&lab) {
+            panic!("QA lab already registered");
+        }
+
+        labs.push_back(lab.clone());
+        env.storage().instance().set(&DataKey::QaLabs, &labs);
+
+        log!(&env, "QA lab registered: {}", lab);
+    }
+
+    /// Deregister a QA lab; holdbacks it has not sampled wait for another lab (admin only)
+    pub fn remove_qa_lab(env: Env, admin: Address, lab: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let labs = Self::get_qa_labs(env.clone());
+        let mut remaining = Vec::new(&env);
+        for existing in labs.iter() {
+            if existing != lab {
+                remaining.push_back(existing);
+            }
+        }
+        if remaining.len() == labs.len() {
+            panic!("QA lab not registered");
+        }
+        env.storage().instance().set(&DataKey::QaLabs, &remaining);
+
+        log!(&env, "QA lab removed: {}", lab);
+    }
+
+    /// Get every registered QA lab
+    pub fn get_qa_labs(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::QaLabs)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Check whether an address is a registered QA lab
+    pub fn is_qa_lab(env: Env, lab: Address) -> bool {
+        Self::get_qa_labs(env).contains(&lab)
+    }
+
+    /// Hold back part of a loan's proceeds until the pledged lot passes a QA sample
+    ///
+    /// Funded like `open`. A registered lab has `QA_SAMPLE_WINDOW` to post the result
+    /// through `post_sample_result`; after that the holdback can be clawed back.
+    pub fn open_holdback(
+        env: Env,
+        funder: Address,
+        loan_id: BytesN<32>,
+        lot: Address,
+        token: Address,
+        amount: u128,
+        recipient: Address,
+        refund_to: Address,
+    ) -> u64 {
+        funder.require_auth();
+        ensure_not_paused(&env);
+
+        if amount == 0 {
+            panic!("Holdback amount must be greater than 0");
+        }
+
+        let reserved = Self::get_reserved(env.clone(), token.clone());
+        let balance = TokenClient::new(&env, &token).balance(&env.current_contract_address());
+        if (balance.max(0) as u128) < reserved + amount {
+            panic!("Holdback is not funded");
+        }
+
+        let holdback_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextHoldbackId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextHoldbackId, &(holdback_id + 1));
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserved(token.clone()), &(reserved + amount));
+
+        let now = env.ledger().timestamp();
+        let holdback = QaHoldback {
+            id: holdback_id,
+            loan_id: loan_id.clone(),
+            lot: lot.clone(),
+            token,
+            amount,
+            recipient,
+            refund_to,
+            opened_at: now,
+            expires_at: now + QA_SAMPLE_WINDOW,
+            lab: None,
+            sample_hash: None,
+            status: HoldbackStatus::Held,
+        };
+        Self::store_holdback(&env, &holdback);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Holdback {} of {} on loan {} awaits a QA sample of lot {}",
+            holdback_id,
+            amount,
+            loan_id,
+            lot
+        );
+
+        holdback_id
+    }
+
+    /// Post a lot's sample result, settling its holdback (registered QA labs only, before expiry)
+    ///
+    /// A passing result releases the holdback to the borrower; a failing one claws it
+    /// back to the vault. Returns the amount settled.
+    pub fn post_sample_result(
+        env: Env,
+        lab: Address,
+        holdback_id: u64,
+        sample_hash: BytesN<32>,
+        passed: bool,
+    ) -> u128 {
+        lab.require_auth();
+
+        if !Self::is_qa_lab(env.clone(), lab.clone()) {
+            panic!("QA lab is not registered");
+        }
+        let mut holdback = Self::load_holdback(&env, holdback_id);
+        if holdback.status != HoldbackStatus::Held {
+            panic!("Holdback has already been settled");
+        }
+        if env.ledger().timestamp() > holdback.expires_at {
+            panic!("Sample window has closed");
+        }
+
+        holdback.lab = Some(lab.clone());
+        holdback.sample_hash = Some(sample_hash.clone());
+        let to = if passed {
+            holdback.status = HoldbackStatus::Released;
+            holdback.recipient.clone()
+        } else {
+            holdback.status = HoldbackStatus::ClawedBack;
+            holdback.refund_to.clone()
+        };
+        Self::store_holdback(&env, &holdback);
+        Self::release_reserved(&env, &holdback.token, holdback.amount, &to);
+
+        log!(
+            &env,
+            "Lab {} posted sample {} for lot {} (passed: {}); holdback {} paid to {}",
+            lab,
+            sample_hash,
+            holdback.lot,
+            passed,
+            holdback_id,
+            to
+        );
+
+        holdback.amount
+    }
+
+    /// Return a holdback no lab sampled in time to its vault (anyone can call)
+    pub fn claw_back_holdback(env: Env, holdback_id: u64) -> u128 {
+        let mut holdback = Self::load_holdback(&env, holdback_id);
+        if holdback.status != HoldbackStatus::Held {
+            panic!("Holdback has already been settled");
+        }
+        if env.ledger().timestamp() <= holdback.expires_at {
+            panic!("Sample window is still open");
+        }
+
+        holdback.status = HoldbackStatus::ClawedBack;
+        Self::store_holdback(&env, &holdback);
+        Self::release_reserved(&env, &holdback.token, holdback.amount, &holdback.refund_to);
+
+        log!(
+            &env,
+            "Holdback {} clawed back to {} without a sample: {}",
+            holdback_id,
+            holdback.refund_to,
+            holdback.amount
+        );
+
+        holdback.amount
+    }
+
+    /// Get a QA holdback by id
+    pub fn get_holdback(env: Env, holdback_id: u64) -> Option<QaHoldback> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Holdback(holdback_id))
+    }
+
+    /// Get an escrow by id
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Option<LoanEscrow> {
+        env.storage().persistent().get(&DataKey::Escrow(escrow_id))
+    }
+
+    /// Get the amount of a token held for unsettled escrows
+    pub fn get_reserved(env: Env, token: Address) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Reserved(token))
+            .unwrap_or(0)
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new escrows and holdbacks (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new escrows and holdbacks are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn release(env: &Env, escrow: &LoanEscrow, to: &Address) {
+        // Settle the record before paying out
+        Self::store_escrow(env, escrow);
+        Self::release_reserved(env, &escrow.token, escrow.amount, to);
+    }
+
+    fn release_reserved(env: &Env, token: &Address, amount: u128, to: &Address) {
+        let reserved = Self::get_reserved(env.clone(), token.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::Reserved(token.clone()), &(reserved - amount));
+
+        transfer_or_abort(
+            env,
+            token,
+            &env.current_contract_address(),
+            to,
+            amount as i128,
+        );
+    }
+
+    fn load_escrow(env: &Env, escrow_id: u64) -> LoanEscrow {
+        Self::get_escrow(env.clone(), escrow_id).unwrap_or_else(|| panic!("Escrow not found"))
+    }
+
+    fn store_escrow(env: &Env, escrow: &LoanEscrow) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Escrow(escrow.id), escrow);
+        extend_persistent_ttl(env, &DataKey::Escrow(escrow.id));
+    }
+
+    fn load_holdback(env: &Env, holdback_id: u64) -> QaHoldback {
+        Self::get_holdback(env.clone(), holdback_id).unwrap_or_else(|| panic!("Holdback not found"))
+    }
+
+    fn store_holdback(env: &Env, holdback: &QaHoldback) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Holdback(holdback.id), holdback);
+        extend_persistent_ttl(env, &DataKey::Holdback(holdback.id));
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage the escrow");
+        }
+    }
+}
@@ -1,17 +1,109 @@
 #![no_std]
 use shared::{
-    DepositInfo, LockPeriod, VaultType, PAXG_ASSET, STORAGE_INSTANCE_PERSISTENT, WISDOMTREE_GOLD,
+    anchor_ledger_clock, begin_migration, compound_daily, deadline_time, deposit_to_strategy,
+    digest_accounting, drawn_liquidity, ensure_not_paused, ensure_uninitialized, enter_guard,
+    exit_guard, extend_instance_ttl, extend_persistent_ttl, facility_interest, finish_migration,
+    gold_usd_value, harvest_from_strategy, hash_deposit_terms, invoke_peer, is_initialized,
+    ledger_clock, lookup_registry, mark_initialized, migrate_instance_entry,
+    migrate_persistent_entry, mul_div, mul_div_up, mul_div_with_dust, read_oracle_price,
+    repay_drawn_liquidity, require_role, resolve_peer, role_holder, set_paused, set_role_holder,
+    stored_schema_version, swap_via_router, transfer_from_or_abort, transfer_or_abort,
+    try_invoke_peer, validate_liquidity_facility, verify_migration_admin, withdraw_from_strategy,
+    BadDebtSource, Beneficiary, CompoundingFrequency, ContractError, ContractInfo, DepositInfo,
+    DepositReceipt, DepositTag, DepositTerms, Encumbrance, FacilityDraw, LedgerClock,
+    LiquidityFacility, LockPeriod, LockTierCap, PositionPage, PositionRecord, PriceSnapshot,
+    RecoveryPlan, Role, SettlementSnapshot, StrategySlot, VaultHealthReport, VaultType,
+    BASIS_POINTS, BENEFICIARY_CHANGE_DELAY, FACILITY_LIQUIDATION_BONUS_BPS, GOLD_PRICE_PRECISION,
+    MAX_GOLD_HAIRCUT_BPS, MAX_RECOVERY_INACTIVITY, MAX_STRATEGY_SHARE_BPS, MIN_RECOVERY_INACTIVITY,
+    PAXG_ASSET, POSITION_EXPORT_PAGE_SIZE, REGISTRY_ALLOWLIST, REGISTRY_GOLD_VAULT,
+    REGISTRY_GOVERNANCE, REGISTRY_ORACLE, REGISTRY_RECEIPTS, REGISTRY_TRADE_ROUTER,
+    REGISTRY_USDC_VAULT, REGISTRY_YIELD_TOKEN, SHARE_PRICE_PRECISION, WISDOMTREE_GOLD,
 };
 use soroban_sdk::token::TokenClient;
-use soroban_sdk::{contract, contractimpl, log, symbol_short, Address, Env, IntoVal, Symbol, Vec};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, panic_with_error, symbol_short, Address, BytesN,
+    Env, IntoVal, Map, String, Symbol, Vec,
+};
 
 // Storage Keys
-const DEPOSIT: Symbol = symbol_short!("DEPOSIT");
-const VAULT_BALANCE: Symbol = symbol_short!("BALANCE");
-const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
-const ADMIN: Symbol = symbol_short!("ADMIN");
-const ORACLE: Symbol = symbol_short!("ORACLE");
-const SUPPORTED_ASSETS: Symbol = symbol_short!("ASSETS");
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and totals
+    Admin,
+    YieldToken,
+    Oracle,
+    SupportedAssets,
+    Registry,
+    Rewards,
+    Governance,
+    Receipts,
+    VaultBalance,
+    UnrecordedShortfall,
+    YieldPaid,
+    Haircut,
+    NextReceiptId,
+    Settlement,
+    SettlementHeld(Address),
+    TierCap(LockPeriod),
+    TierBalance(LockPeriod),
+    RoundingDust(Address),
+    StrategyWhitelisted(Address),
+    Strategy(Address),
+    Allowlist,
+    LaunchPhase,
+    DepositorCount,
+    Facility(Address),
+    FacilityDraw(Address),
+    FacilityLent(Address),
+    LiquidityLender,
+    LiquidityAsset,
+    // Persistent: per-user positions, deposit operators and deposit receipts
+    Deposit(Address),
+    DepositOperator(Address, Address),
+    GoldAmount(Address),
+    GoldAsset(Address),
+    HaircutBuffer(Address),
+    DepositPrice(Address),
+    ActiveReceipt(Address),
+    Receipt(u64),
+    Encumbrance(Address, u64),
+    DepositTag(Address),
+    Recovery(Address),
+    Beneficiary(Address),
+    // Persistent: append-only depositor index walked by position exports
+    Depositor(u64),
+    DepositorSlot(Address),
+}
+
+// Schema v1 storage keys, only read when migrating
+mod v1 {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub const DEPOSIT: Symbol = symbol_short!("DEPOSIT");
+    pub const VAULT_BALANCE: Symbol = symbol_short!("BALANCE");
+    pub const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
+    pub const ADMIN: Symbol = symbol_short!("ADMIN");
+    pub const ORACLE: Symbol = symbol_short!("ORACLE");
+    pub const SUPPORTED_ASSETS: Symbol = symbol_short!("ASSETS");
+    pub const REGISTRY: Symbol = symbol_short!("REGISTRY");
+    pub const REWARDS: Symbol = symbol_short!("REWARDS");
+    pub const GOVERNANCE: Symbol = symbol_short!("GOV");
+    pub const GOLD_AMOUNT: &str = "gold_amount";
+    pub const GOLD_ASSET: &str = "gold_asset";
+}
+
+// What a closed position is paid out in
+#[derive(Clone, Copy)]
+enum Payout {
+    Gold(u128),      // The position's gold, with the minimum gold out
+    Liquidity(u128), // Its USD value in drawn facility USDC, with the minimum out
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 2;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[contract]
 pub struct GoldVault;
@@ -26,125 +118,2506 @@ impl GoldVault {
         oracle_contract: Address,
         supported_gold_assets: Vec<Address>,
     ) {
+        ensure_uninitialized(&env, Some(v1::ADMIN));
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldToken, &yield_token_contract);
+        env.storage()
+            .instance()
+            .set(&DataKey::Oracle, &oracle_contract);
+        env.storage()
+            .instance()
+            .set(&DataKey::SupportedAssets, &supported_gold_assets);
+        env.storage().instance().set(&DataKey::VaultBalance, &0u128);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Gold Vault initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_GOLD_VAULT),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Migrate storage to the schema version of the current WASM (admin only)
+    ///
+    /// Moves config and totals; deposits are moved with `migrate_deposits`.
+    ///
+    /// Storage from before versioning also needs the `deployer` contract that
+    /// created this one, whose record of the admin is trusted over the stored one.
+    pub fn migrate(env: Env, admin: Address, deployer: Option<Address>) {
+        admin.require_auth();
+
+        // Until the v2 migration has run the admin still sits under its v1 key
+        let stored_admin: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .or_else(|| env.storage().instance().get(&v1::ADMIN));
+        let stored_version = begin_migration(&env, STORAGE_VERSION);
+        verify_migration_admin(&env, stored_version, &admin, stored_admin, deployer);
+
+        match stored_version {
+            0 | 1 => Self::migrate_v1_instance(&env),
+            _ => panic_with_error!(&env, ContractError::InvalidMigration),
+        }
+        finish_migration(&env, stored_version, STORAGE_VERSION);
+    }
+
+    /// Move schema v1 deposit records to their `DataKey` entries (admin only)
+    ///
+    /// Persistent storage cannot be enumerated, so the depositors are supplied
+    /// in batches from indexed deposit events once `migrate` has run.
+    pub fn migrate_deposits(env: Env, admin: Address, users: Vec<Address>) -> u32 {
         admin.require_auth();
 
-        env.storage().instance().set(&ADMIN, &admin);
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let gold_amount_key = Symbol::new(&env, v1::GOLD_AMOUNT);
+        let gold_asset_key = Symbol::new(&env, v1::GOLD_ASSET);
+        let mut migrated = 0u32;
+        for user in users.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::DEPOSIT, user.clone()),
+                &DataKey::Deposit(user.clone()),
+            ) {
+                migrate_persistent_entry(
+                    &env,
+                    &(gold_amount_key.clone(), user.clone()),
+                    &DataKey::GoldAmount(user.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(gold_asset_key.clone(), user.clone()),
+                    &DataKey::GoldAsset(user.clone()),
+                );
+                migrated += 1;
+            }
+
+            // Index every supplied depositor so exports reach positions opened before the index
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Deposit(user.clone()))
+            {
+                Self::track_depositor(&env, &user);
+            }
+        }
+
+        log!(&env, "Migrated {} deposit records", migrated);
+
+        migrated
+    }
+
+    /// Deposit gold tokens (PAXG/Wisdom Tree) into the vault with time lock
+    pub fn deposit(
+        env: Env,
+        user: Address,
+        gold_asset: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+    ) {
+        user.require_auth();
+        Self::open_deposit(&env, &user, gold_asset, amount, lock_period, false, None);
+    }
+
+    /// Deposit gold tokens naming the only address the user's positions may be paid out to
+    ///
+    /// Meant for a cold wallet, so a compromised depositor key cannot withdraw to
+    /// itself. The first designation takes effect at once; an existing beneficiary
+    /// can only be replaced through `change_beneficiary` and its delay.
+    pub fn deposit_to_beneficiary(
+        env: Env,
+        user: Address,
+        gold_asset: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        beneficiary: Address,
+    ) {
+        user.require_auth();
+        Self::designate_beneficiary(&env, &user, &beneficiary);
+        Self::open_deposit(&env, &user, gold_asset, amount, lock_period, false, None);
+    }
+
+    /// Deposit gold tokens the user approved the vault to spend, on their behalf
+    ///
+    /// The operator signs instead of the user and must be the user or one of their
+    /// approved deposit operators; the vault pulls the tokens with `transfer_from`.
+    pub fn deposit_from(
+        env: Env,
+        operator: Address,
+        user: Address,
+        gold_asset: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+    ) {
+        operator.require_auth();
+        Self::verify_deposit_operator(&env, &user, &operator);
+        Self::open_deposit(&env, &user, gold_asset, amount, lock_period, true, None);
+    }
+
+    /// Deposit gold tokens attributed to a customer of an integrator such as an exchange or custodian
+    ///
+    /// The tag is kept with the position and published in the deposit event so
+    /// pooled funds can be reconciled per customer off-chain.
+    pub fn deposit_tagged(
+        env: Env,
+        user: Address,
+        gold_asset: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        integrator: Address,
+        customer_id: u64,
+    ) {
+        user.require_auth();
+        let tag = DepositTag {
+            integrator,
+            customer_id,
+        };
+        Self::open_deposit(
+            &env,
+            &user,
+            gold_asset,
+            amount,
+            lock_period,
+            false,
+            Some(tag),
+        );
+    }
+
+    /// Approve or revoke an operator allowed to deposit the user's pre-approved gold tokens
+    pub fn set_deposit_operator(env: Env, user: Address, operator: Address, approved: bool) {
+        user.require_auth();
+
+        let key = DataKey::DepositOperator(user.clone(), operator.clone());
+        if approved {
+            env.storage().persistent().set(&key, &true);
+            extend_persistent_ttl(&env, &key);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        log!(
+            &env,
+            "Deposit operator {} for {} set to {}",
+            operator,
+            user,
+            approved
+        );
+    }
+
+    /// Check whether an operator may deposit on a user's behalf
+    pub fn is_deposit_operator(env: Env, user: Address, operator: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::DepositOperator(user, operator))
+    }
+
+    /// Withdraw gold tokens from the vault (only after lock period expires)
+    ///
+    /// Paid to the user's beneficiary when one is named.
+    pub fn withdraw(env: Env, user: Address) -> u128 {
+        Self::withdraw_with_min(env, user, 0)
+    }
+
+    /// Withdraw gold tokens, aborting with `SlippageExceeded` if fewer than `min_gold_out` would be paid
+    ///
+    /// The gold owed is the position's USD value at the live oracle price, so the
+    /// minimum protects against the price moving between signing and execution.
+    pub fn withdraw_with_min(env: Env, user: Address, min_gold_out: u128) -> u128 {
+        user.require_auth();
+        let recipient = Self::payout_address(&env, &user);
+        Self::withdraw_to(env, user, recipient, Payout::Gold(min_gold_out))
+    }
+
+    /// Name a recovery address that may claim the matured position after a long silence
+    ///
+    /// The recovery address can `claim_recovery` once `inactivity_period` has passed
+    /// since the later of the lock expiry and the depositor's last check-in.
+    pub fn set_recovery(env: Env, user: Address, recovery: Address, inactivity_period: u64) {
+        user.require_auth();
+
+        if !(MIN_RECOVERY_INACTIVITY..=MAX_RECOVERY_INACTIVITY).contains(&inactivity_period) {
+            panic!("Inactivity period must be between one and ten years");
+        }
+        if recovery == user {
+            panic!("Recovery address must differ from the depositor");
+        }
+
+        let plan = RecoveryPlan {
+            recovery: recovery.clone(),
+            inactivity_period,
+            last_active: env.ledger().timestamp(),
+        };
+        Self::store_recovery(&env, &user, &plan);
+
+        log!(
+            &env,
+            "User {} named recovery address {} after {}s of inactivity",
+            user,
+            recovery,
+            inactivity_period
+        );
+    }
+
+    /// Remove the depositor's recovery address
+    pub fn remove_recovery(env: Env, user: Address) {
+        user.require_auth();
+
+        if Self::get_recovery(env.clone(), user.clone()).is_none() {
+            panic!("No recovery address set");
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Recovery(user.clone()));
+
+        log!(&env, "User {} removed their recovery address", user);
+    }
+
+    /// Prove the depositor still holds their key, restarting the inactivity period
+    pub fn check_in(env: Env, user: Address) {
+        user.require_auth();
+
+        let mut plan = Self::get_recovery(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No recovery address set"));
+        plan.last_active = env.ledger().timestamp();
+        Self::store_recovery(&env, &user, &plan);
+    }
+
+    /// Get the depositor's recovery plan, if any
+    pub fn get_recovery(env: Env, user: Address) -> Option<RecoveryPlan> {
+        env.storage().persistent().get(&DataKey::Recovery(user))
+    }
+
+    /// Get when the recovery address may claim the depositor's position, if both exist
+    pub fn get_recovery_time(env: Env, user: Address) -> Option<u64> {
+        let plan = Self::get_recovery(env.clone(), user.clone())?;
+        let deposit_info: DepositInfo = env.storage().persistent().get(&DataKey::Deposit(user))?;
+        Some(deposit_info.unlock_time.max(plan.last_active) + plan.inactivity_period)
+    }
+
+    /// Withdraw an inactive depositor's matured position to their recovery address
+    ///
+    /// Pays out exactly as `withdraw` would, at the live gold price. A named
+    /// beneficiary is paid instead of the recovery address. The plan is spent once
+    /// claimed.
+    pub fn claim_recovery(env: Env, recovery: Address, user: Address) -> u128 {
+        recovery.require_auth();
+
+        let plan = Self::get_recovery(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No recovery address set"));
+        if plan.recovery != recovery {
+            panic!("Only the recovery address can claim the position");
+        }
+        let recovery_time = Self::get_recovery_time(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+        if deadline_time(&env) < recovery_time {
+            panic!("Recovery not allowed before: {}", recovery_time);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Recovery(user.clone()));
+        let recipient = match Self::get_beneficiary(env.clone(), user.clone()) {
+            Some(_) => Self::payout_address(&env, &user),
+            None => recovery.clone(),
+        };
+        let paid = Self::withdraw_to(env.clone(), user.clone(), recipient, Payout::Gold(0));
+
+        log!(
+            &env,
+            "Recovery address {} claimed the position of {}",
+            recovery,
+            user
+        );
+
+        paid
+    }
+
+    /// Request that payouts go to a new beneficiary once `BENEFICIARY_CHANGE_DELAY` has passed
+    ///
+    /// Until then the current beneficiary keeps receiving payouts and can cancel the
+    /// request with `cancel_beneficiary_change`. Naming the user themselves lifts the
+    /// designation once the delay has passed.
+    pub fn change_beneficiary(env: Env, user: Address, beneficiary: Address) {
+        user.require_auth();
+
+        let mut designation = Self::get_beneficiary(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No beneficiary set"));
+        designation.address = Self::payout_address(&env, &user);
+        designation.pending = Some(beneficiary.clone());
+        designation.effective_at = deadline_time(&env) + BENEFICIARY_CHANGE_DELAY;
+        Self::store_beneficiary(&env, &user, &designation);
+
+        log!(
+            &env,
+            "User {} asked to pay {} instead of {} from {}",
+            user,
+            beneficiary,
+            designation.address,
+            designation.effective_at
+        );
+    }
+
+    /// Cancel a beneficiary change that has not taken effect (the user or current beneficiary)
+    pub fn cancel_beneficiary_change(env: Env, caller: Address, user: Address) {
+        caller.require_auth();
+
+        let mut designation = Self::get_beneficiary(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No beneficiary set"));
+        if designation.pending.is_none() || deadline_time(&env) >= designation.effective_at {
+            panic!("No beneficiary change pending");
+        }
+        if caller != user && caller != designation.address {
+            panic!("Only the user or beneficiary can cancel the change");
+        }
+        designation.pending = None;
+        Self::store_beneficiary(&env, &user, &designation);
+
+        log!(
+            &env,
+            "Beneficiary change of {} cancelled by {}",
+            user,
+            caller
+        );
+    }
+
+    /// Get the user's beneficiary designation, including any change requested
+    pub fn get_beneficiary(env: Env, user: Address) -> Option<Beneficiary> {
+        env.storage().persistent().get(&DataKey::Beneficiary(user))
+    }
+
+    /// Get the address the user's positions are paid out to now
+    pub fn get_payout_address(env: Env, user: Address) -> Address {
+        Self::payout_address(&env, &user)
+    }
+
+    /// Enter global settlement: locks are waived and deposits close (governance only)
+    ///
+    /// Governance calls this once the committee approves a shutdown. Snapshots each
+    /// supported gold token held against recorded USD principal, so each depositor
+    /// redeems the same fraction of every token.
+    pub fn enter_settlement(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::verify_governance(&env, &caller);
+        Self::ensure_not_settled(&env);
+
+        let snapshot = Self::snapshot_settlement(&env, env.ledger().timestamp());
+
+        log!(
+            &env,
+            "Global settlement entered by {}: ${} of gold held against ${} recorded",
+            caller,
+            snapshot.held_value,
+            snapshot.recorded_liquidity
+        );
+    }
+
+    /// Re-take the settlement snapshot from the gold held now (permissionless)
+    ///
+    /// Retries strategies that failed to return their gold and picks up facility draws
+    /// repaid since, sharing them among the depositors yet to redeem.
+    pub fn refresh_settlement(env: Env) -> SettlementSnapshot {
+        let settlement = Self::get_settlement(env.clone())
+            .unwrap_or_else(|| panic!("Vault is not in global settlement"));
+
+        let snapshot = Self::snapshot_settlement(&env, settlement.settled_at);
+
+        log!(
+            &env,
+            "Settlement snapshot refreshed: ${} of gold held against ${} recorded",
+            snapshot.held_value,
+            snapshot.recorded_liquidity
+        );
+
+        snapshot
+    }
+
+    /// Redeem a pro-rata share of every gold token held at settlement, ignoring the lock
+    ///
+    /// Returns the claim's USD value at the settlement snapshot. The holder's
+    /// yield tokens are burned; yield stopped accruing at settlement. A position
+    /// pledged to a loan can only redeem once the lien is released.
+    pub fn redeem_settlement(env: Env, user: Address) -> u128 {
+        user.require_auth();
+
+        let snapshot = Self::get_settlement(env.clone())
+            .unwrap_or_else(|| panic!("Vault is not in global settlement"));
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+        Self::ensure_unencumbered(&env, &user);
+
+        let claim_usd_value = mul_div(
+            deposit_info.amount,
+            snapshot.held_value,
+            snapshot.recorded_liquidity,
+        )
+        .unwrap_or_else(|| panic!("Settlement claim overflow"));
+
+        enter_guard(&env);
+
+        // Effects: close the position before any outbound call
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap();
+        env.storage().instance().set(
+            &DataKey::VaultBalance,
+            &(vault_balance - deposit_info.amount),
+        );
+        Self::release_tier_balance(&env, &deposit_info.lock_period, deposit_info.amount);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Deposit(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DepositTag(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::GoldAmount(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::GoldAsset(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::HaircutBuffer(user.clone()));
         env.storage()
+            .persistent()
+            .remove(&DataKey::DepositPrice(user.clone()));
+        let exported_receipt = Self::close_receipt(&env, &user);
+        extend_instance_ttl(&env);
+
+        // Interactions: burn the frozen yield-token balance, then pay each token's share
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        let token_balance: i128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "balance",
+            (user.clone(),).into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+        if token_balance > 0 {
+            invoke_peer::<()>(
+                &env,
+                &yield_token_contract,
+                "burn_for_withdrawal",
+                (
+                    env.current_contract_address(),
+                    user.clone(),
+                    token_balance as u128,
+                )
+                    .into_val(&env),
+                ContractError::YieldTokenCallFailed,
+            );
+        }
+
+        let recipient = Self::payout_address(&env, &user);
+        let supported_assets: Vec<Address> = env
+            .storage()
             .instance()
-            .set(&YIELD_TOKEN, &yield_token_contract);
-        env.storage().instance().set(&ORACLE, &oracle_contract);
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(&env));
+        for asset in supported_assets.iter() {
+            let held: u128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::SettlementHeld(asset.clone()))
+                .unwrap_or(0);
+            let (share, dust) =
+                mul_div_with_dust(held, deposit_info.amount, snapshot.recorded_liquidity)
+                    .unwrap_or_else(|| panic!("Settlement claim overflow"));
+            Self::book_rounding_dust(&env, &asset, dust);
+            if share > 0 {
+                transfer_or_abort(
+                    &env,
+                    &asset,
+                    &env.current_contract_address(),
+                    &recipient,
+                    share as i128,
+                );
+                log!(
+                    &env,
+                    "User {} redeemed {} of gold token {}",
+                    user,
+                    share,
+                    asset
+                );
+            }
+        }
+
+        Self::remove_reward_stake(&env, &user);
+        Self::settle_receipt_token(&env, exported_receipt);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} redeemed ${} in global settlement for ${} principal",
+            user,
+            claim_usd_value,
+            deposit_info.amount
+        );
+
+        claim_usd_value
+    }
+
+    /// Get the global settlement snapshot, if settlement has started
+    pub fn get_settlement(env: Env) -> Option<SettlementSnapshot> {
+        env.storage().instance().get(&DataKey::Settlement)
+    }
+
+    /// Get USD value of gold amount using oracle
+    pub fn get_usd_value(env: Env, gold_asset: Address, gold_amount: u128) -> u128 {
+        let (_, gold_price_usd) = Self::gold_price(&env, &gold_asset);
+
+        gold_usd_value(gold_amount, gold_price_usd)
+            .unwrap_or_else(|| panic!("Gold USD value overflow"))
+    }
+
+    /// Get the gold asset of a user's open position
+    pub fn get_position_asset(env: Env, user: Address) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::GoldAsset(user))
+    }
+
+    /// Get the USD value of a user's open position's gold at the live oracle price, 0 without one
+    pub fn get_position_value(env: Env, user: Address) -> u128 {
+        let Some(gold_asset) = Self::get_position_asset(env.clone(), user.clone()) else {
+            return 0;
+        };
+        let gold_amount: u128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GoldAmount(user))
+            .unwrap_or(0);
+        Self::get_usd_value(env, gold_asset, gold_amount)
+    }
+
+    /// Get the oracle price a user's open position was valued at when deposited
+    ///
+    /// Positions opened before prices were recorded have none.
+    pub fn get_deposit_price(env: Env, user: Address) -> Option<PriceSnapshot> {
+        env.storage().persistent().get(&DataKey::DepositPrice(user))
+    }
+
+    /// Get user's deposit information
+    pub fn get_deposit_info(env: Env, user: Address) -> Option<DepositInfo> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+    }
+
+    /// Get lock expiry time for a user
+    pub fn get_lock_expiry(env: Env, user: Address) -> u64 {
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        deposit_info.unlock_time
+    }
+
+    /// Get a deposit receipt by its ID, including closed ones
+    pub fn get_receipt(env: Env, receipt_id: u64) -> Option<DepositReceipt> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Receipt(receipt_id))
+    }
+
+    /// Get the receipt for a user's open deposit
+    pub fn get_active_receipt(env: Env, user: Address) -> Option<DepositReceipt> {
+        let receipt_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ActiveReceipt(user));
+        receipt_id.and_then(|receipt_id| Self::get_receipt(env.clone(), receipt_id))
+    }
+
+    /// Pledge a user's open position as loan collateral (loan manager only)
+    ///
+    /// `position_id` is the position's deposit receipt ID. Withdrawal is blocked
+    /// until the loan manager calls `release_lien`.
+    pub fn encumber_position(
+        env: Env,
+        lien_holder: Address,
+        user: Address,
+        position_id: u64,
+        loan_id: BytesN<32>,
+    ) {
+        lien_holder.require_auth();
+        user.require_auth();
+        Self::verify_loan_manager(&env, &lien_holder);
+
+        let receipt = Self::get_active_receipt(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No open position found for user"));
+        if receipt.id != position_id {
+            panic!("Position is not the user's open position");
+        }
+
+        let encumbrance_key = DataKey::Encumbrance(user.clone(), position_id);
+        if env.storage().persistent().has(&encumbrance_key) {
+            panic!("Position is already encumbered");
+        }
+
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap();
+        let encumbrance = Encumbrance {
+            loan_id: loan_id.clone(),
+            lien_holder,
+            amount: deposit_info.amount,
+            encumbered_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&encumbrance_key, &encumbrance);
+        extend_persistent_ttl(&env, &encumbrance_key);
+
+        log!(
+            &env,
+            "Position {} of user {} encumbered for loan {}",
+            position_id,
+            user,
+            loan_id
+        );
+    }
+
+    /// Release the lien on a position once its loan no longer needs it (lien holder only)
+    pub fn release_lien(env: Env, lien_holder: Address, user: Address, position_id: u64) {
+        lien_holder.require_auth();
+
+        let encumbrance = Self::get_encumbrance(env.clone(), user.clone(), position_id)
+            .unwrap_or_else(|| panic!("Position is not encumbered"));
+        if encumbrance.lien_holder != lien_holder {
+            panic!("Only the lien holder can release the lien");
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Encumbrance(user.clone(), position_id));
+
+        log!(
+            &env,
+            "Lien on position {} of user {} released",
+            position_id,
+            user
+        );
+    }
+
+    /// Close an encumbered position whose loan defaulted, paying its gold to the lien holder (lien holder only)
+    ///
+    /// The lock period does not apply. Returns the gold paid, yield included.
+    pub fn seize_position(env: Env, lien_holder: Address, user: Address, position_id: u64) -> u128 {
+        lien_holder.require_auth();
+        Self::ensure_not_settled(&env);
+
+        let encumbrance = Self::get_encumbrance(env.clone(), user.clone(), position_id)
+            .unwrap_or_else(|| panic!("Position is not encumbered"));
+        if encumbrance.lien_holder != lien_holder {
+            panic!("Only the lien holder can seize the position");
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Encumbrance(user.clone(), position_id));
+        let seized = Self::close_position(
+            env.clone(),
+            user.clone(),
+            lien_holder.clone(),
+            Payout::Gold(0),
+        );
+
+        log!(
+            &env,
+            "Position {} of user {} seized by {} for loan {}",
+            position_id,
+            user,
+            lien_holder,
+            encumbrance.loan_id
+        );
+
+        seized
+    }
+
+    /// Get the lien on a position, if it backs a loan
+    pub fn get_encumbrance(env: Env, user: Address, position_id: u64) -> Option<Encumbrance> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Encumbrance(user, position_id))
+    }
+
+    /// Export the open deposit receipt as a transferable token in the receipts contract
+    ///
+    /// The token is an auditable record of the position; withdrawal stays with the depositor.
+    pub fn export_receipt(env: Env, user: Address) -> u64 {
+        user.require_auth();
+
+        let mut receipt = Self::get_active_receipt(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No open receipt found for user"));
+        if receipt.nft_id.is_some() {
+            panic!("Receipt already exported");
+        }
+
+        let receipts_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_RECEIPTS,
+            &DataKey::Receipts,
+        );
+        let nft_id: u64 = invoke_peer(
+            &env,
+            &receipts_contract,
+            "mint",
+            (
+                env.current_contract_address(),
+                user.clone(),
+                receipt.clone(),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+
+        receipt.nft_id = Some(nft_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt.id), &receipt);
+        extend_persistent_ttl(&env, &DataKey::Receipt(receipt.id));
+
+        log!(
+            &env,
+            "Receipt {} exported as token {} to {}",
+            receipt.id,
+            nft_id,
+            user
+        );
+
+        nft_id
+    }
+
+    /// Export normalized records of open positions for regulatory reporting (reporter, or admin if none is set)
+    ///
+    /// Pages through depositor slots as the USDC vault's `export_positions` does.
+    /// Principal and accrued yield are in USD, as credited at deposit.
+    pub fn export_positions(env: Env, caller: Address, cursor: u64, limit: u32) -> PositionPage {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Reporter, &caller, &admin);
+
+        let depositor_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DepositorCount)
+            .unwrap_or(0);
+        let start = cursor.min(depositor_count);
+        let end = (start + limit.min(POSITION_EXPORT_PAGE_SIZE) as u64).min(depositor_count);
+
+        let mut records = Vec::new(&env);
+        for slot in start..end {
+            let user: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Depositor(slot))
+                .unwrap();
+            if let Some(record) = Self::position_record(&env, &user) {
+                records.push_back(record);
+            }
+        }
+
+        PositionPage {
+            records,
+            next_cursor: if end < depositor_count {
+                Some(end)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Calculate yield rate based on lock period (same as USDC vault)
+    pub fn calculate_yield_rate(env: Env, lock_period: LockPeriod) -> u128 {
+        let base_rate = 500u128; // 5% base annual rate in basis points
+
+        match lock_period {
+            LockPeriod::ThreeMonths => base_rate,           // 5% APY
+            LockPeriod::SixMonths => (base_rate * 15) / 10, // 7.5% APY (1.5x)
+            LockPeriod::TwelveMonths => base_rate * 2,      // 10% APY (2x)
+        }
+    }
+
+    /// Get current vault balance in USD terms
+    pub fn get_vault_balance(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap_or(0)
+    }
+
+    /// Get gold redeemable per yield token for a supported asset, scaled by `SHARE_PRICE_PRECISION`
+    ///
+    /// Yield tokens are USD-denominated, so this is the inverse of the gold price.
+    /// Multiply by the yield token's `accrued_balance` to value a position at current accrual.
+    pub fn get_share_price(env: Env, gold_asset: Address) -> u128 {
+        Self::verify_supported_asset(&env, &gold_asset);
+
+        // USD value of one whole gold unit at share precision
+        let unit_usd_value = Self::get_usd_value(env.clone(), gold_asset, SHARE_PRICE_PRECISION);
+        if unit_usd_value == 0 {
+            panic!("Gold price unavailable");
+        }
+
+        mul_div(SHARE_PRICE_PRECISION, SHARE_PRICE_PRECISION, unit_usd_value)
+            .unwrap_or_else(|| panic!("Share price overflow"))
+    }
+
+    /// Get the USD value deposited in a lock tier
+    pub fn get_tier_balance(env: Env, lock_period: LockPeriod) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TierBalance(lock_period))
+            .unwrap_or(0)
+    }
+
+    /// Get the TVL cap for a lock tier, if one is set
+    pub fn get_tier_cap(env: Env, lock_period: LockPeriod) -> Option<LockTierCap> {
+        env.storage().instance().get(&DataKey::TierCap(lock_period))
+    }
+
+    /// Get the haircut applied to new gold deposits' USD value, in basis points
+    pub fn get_haircut(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::Haircut).unwrap_or(0)
+    }
+
+    /// Get the integrator and customer a position was tagged with at deposit
+    pub fn get_deposit_tag(env: Env, user: Address) -> Option<DepositTag> {
+        env.storage().persistent().get(&DataKey::DepositTag(user))
+    }
+
+    /// Get the gold held back from a user's deposit as a buffer against price moves
+    pub fn get_haircut_buffer(env: Env, user: Address) -> u128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HaircutBuffer(user))
+            .unwrap_or(0)
+    }
+
+    /// Get the fractions of a gold unit kept by rounding payouts down, in `DUST_PRECISION` parts of one unit
+    pub fn get_rounding_dust(env: Env, gold_asset: Address) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RoundingDust(gold_asset))
+            .unwrap_or(0)
+    }
+
+    /// Get the fixed-rate yield paid on exits since launch, in USD
+    ///
+    /// Governance reads it each epoch to credit realized yield to this vault. Harvested
+    /// gold stays in the vault to fund these payouts, so it is not counted separately.
+    pub fn get_yield_paid(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::YieldPaid)
+            .unwrap_or(0)
+    }
+
+    /// Get the recorded USD value that was minted as yield tokens
+    ///
+    /// Every deposit mints its recorded value, so this is the vault balance.
+    pub fn get_minted_liquidity(env: Env) -> u128 {
+        Self::get_vault_balance(env)
+    }
+
+    /// Get shortfalls that could not be recorded in the governance bad-debt ledger
+    pub fn get_unrecorded_shortfall(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::UnrecordedShortfall)
+            .unwrap_or(0)
+    }
+
+    /// Check vault accounting invariants (read-only, for monitoring and pre-upgrade checks)
+    ///
+    /// A stale or missing gold price does not abort the check: the asset is left out of
+    /// `held_value` and the report is flagged with `price_fresh: false` instead.
+    pub fn health_check(env: Env) -> VaultHealthReport {
+        let recorded_liquidity = Self::get_vault_balance(env.clone());
+
+        // Value every supported gold token held by the vault at the current price
+        let supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(&env));
+        let mut accounting_non_negative = true;
+        let mut price_fresh = true;
+        let mut held_value = 0u128;
+        for asset in supported_assets.iter() {
+            let gold_client = TokenClient::new(&env, &asset);
+            let token_balance = gold_client.balance(&env.current_contract_address());
+            if token_balance < 0 {
+                accounting_non_negative = false;
+                continue;
+            }
+            let allocated =
+                Self::get_strategy(env.clone(), asset.clone()).map_or(0, |slot| slot.allocated);
+            let lent = Self::get_facility_lent(env.clone(), asset.clone());
+            let Some(gold_price_usd) = Self::read_gold_price(&env, &asset).1 else {
+                price_fresh = false;
+                continue;
+            };
+            held_value += gold_usd_value(token_balance as u128 + allocated + lent, gold_price_usd)
+                .unwrap_or_else(|| panic!("Gold USD value overflow"));
+        }
+
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        let yield_token_supply: i128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "total_supply",
+            Vec::new(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+        if yield_token_supply < 0 {
+            accounting_non_negative = false;
+        }
+        let yield_token_supply = yield_token_supply.max(0) as u128;
+
+        // Held gold must back the recorded USD balance. The yield token is shared, so its
+        // supply must cover what both vaults minted, not this vault alone
+        let usdc_vault_minted = lookup_registry(&env, &DataKey::Registry, REGISTRY_USDC_VAULT)
+            .map_or(0, |usdc_vault| {
+                invoke_peer(
+                    &env,
+                    &usdc_vault,
+                    "get_minted_liquidity",
+                    Vec::new(&env),
+                    ContractError::PeerCallFailed,
+                )
+            });
+        let liquidity_covered = price_fresh && held_value >= recorded_liquidity;
+        let supply_consistent =
+            yield_token_supply >= Self::get_minted_liquidity(env.clone()) + usdc_vault_minted;
+
+        VaultHealthReport {
+            held_value,
+            recorded_liquidity,
+            yield_token_supply,
+            liquidity_covered,
+            supply_consistent,
+            accounting_non_negative,
+            price_fresh,
+            healthy: liquidity_covered && supply_consistent && accounting_non_negative,
+            checked_at: env.ledger().timestamp(),
+        }
+    }
+
+    /// Get the stored accounting figures the state digest is computed over
+    ///
+    /// Recorded USD value (`tvl`) and shortfalls not yet in the bad-debt ledger.
+    /// Gold prices are not stored, so held value is left out.
+    pub fn accounting_state(env: Env) -> Map<Symbol, u128> {
+        let mut figures = Map::new(&env);
+        figures.set(symbol_short!("tvl"), Self::get_vault_balance(env.clone()));
+        figures.set(
+            symbol_short!("shortfall"),
+            Self::get_unrecorded_shortfall(env),
+        );
+        figures
+    }
+
+    /// Get the SHA-256 digest of `accounting_state()`, recorded by governance each epoch
+    pub fn state_digest(env: Env) -> BytesN<32> {
+        digest_accounting(&env, &Self::accounting_state(env.clone()))
+    }
+
+    /// Add supported gold asset (admin only)
+    pub fn add_supported_asset(env: Env, admin: Address, new_asset: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can add supported assets");
+        }
+
+        let mut supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(&env));
+
+        supported_assets.push_back(new_asset.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::SupportedAssets, &supported_assets);
+
+        log!(&env, "Added supported gold asset: {}", new_asset);
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can assign roles");
+        }
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new deposits and strategy allocations (pauser, or admin if no pauser is set)
+    ///
+    /// Withdrawals and other exits stay open so depositors can always leave.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new deposits and strategy allocations are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address, e.g. the multisig contract (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can transfer admin rights");
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
+
+    /// Set the address registry used to resolve peer contracts (admin only)
+    pub fn set_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the registry");
+        }
+
+        env.storage().instance().set(&DataKey::Registry, &registry);
+
+        log!(&env, "Address registry set to: {}", registry);
+    }
+
+    /// Set the liquidity-mining rewards contract notified of deposits (admin only)
+    pub fn set_rewards(env: Env, admin: Address, rewards_contract: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the rewards contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Rewards, &rewards_contract);
+
+        log!(&env, "Rewards contract set to: {}", rewards_contract);
+    }
+
+    /// Set the receipts contract that exported deposit receipts are minted in (admin only)
+    pub fn set_receipts(env: Env, admin: Address, receipts_contract: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the receipts contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Receipts, &receipts_contract);
+
+        log!(&env, "Receipts contract set to: {}", receipts_contract);
+    }
+
+    /// Set the governance contract that keeps the bad-debt ledger (admin only)
+    pub fn set_governance(env: Env, admin: Address, governance: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the governance contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Governance, &governance);
+
+        log!(&env, "Governance contract set to: {}", governance);
+    }
+
+    /// Cap the share of vault TVL a lock tier may hold (governance or admin)
+    pub fn set_tier_cap(env: Env, caller: Address, lock_period: LockPeriod, cap: LockTierCap) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if cap.max_share_bps as u128 > BASIS_POINTS {
+            panic_with_error!(&env, ContractError::InvalidTierCap);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TierCap(lock_period.clone()), &cap);
+
+        log!(
+            &env,
+            "Tier cap for {:?} set to {} bps above ${} TVL",
+            lock_period,
+            cap.max_share_bps,
+            cap.min_tvl
+        );
+    }
+
+    /// Start a launch phase in which only allowlisted addresses may deposit (admin only)
+    pub fn start_launch_phase(env: Env, admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can start a launch phase");
+        }
+
+        env.storage().instance().set(&DataKey::LaunchPhase, &true);
+
+        log!(
+            &env,
+            "Launch phase started: deposits limited to the allowlist"
+        );
+    }
+
+    /// End the launch phase and open deposits to everyone (governance or admin)
+    pub fn end_launch_phase(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if !Self::is_launch_phase(env.clone()) {
+            panic!("No launch phase in progress");
+        }
+        env.storage().instance().remove(&DataKey::LaunchPhase);
+
+        log!(
+            &env,
+            "Launch phase ended by {}: deposits open to all",
+            caller
+        );
+    }
+
+    /// Check whether deposits are currently limited to the allowlist
+    pub fn is_launch_phase(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::LaunchPhase)
+    }
+
+    /// Set the allowlist consulted during the launch phase when the registry has none (admin only)
+    pub fn set_allowlist(env: Env, admin: Address, allowlist: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the allowlist");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Allowlist, &allowlist);
+
+        log!(&env, "Allowlist set to: {}", allowlist);
+    }
+
+    /// Cross-check lock expiries against the ledger sequence, or stop doing so (governance or admin)
+    ///
+    /// Enabling anchors the sequence clock at the current ledger. A lock then only
+    /// expires once both the timestamp and the ledgers closed since agree it has.
+    pub fn set_ledger_clock(env: Env, caller: Address, enabled: bool) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        anchor_ledger_clock(&env, enabled);
+
+        log!(
+            &env,
+            "Ledger-sequence lock checks set to {} by {}",
+            enabled,
+            caller
+        );
+    }
+
+    /// Get the ledger clock lock expiries are cross-checked against, if any
+    pub fn get_ledger_clock(env: Env) -> Option<LedgerClock> {
+        ledger_clock(&env)
+    }
+
+    /// Set the haircut on new deposits' USD value, up to `MAX_GOLD_HAIRCUT_BPS` (governance or admin)
+    ///
+    /// The haircut share of each deposit's gold is held as a buffer against oracle
+    /// error and price moves and released at withdrawal, less any part it covered.
+    pub fn set_haircut(env: Env, caller: Address, haircut_bps: u32) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if haircut_bps > MAX_GOLD_HAIRCUT_BPS {
+            panic!("Haircut cannot exceed {} bps", MAX_GOLD_HAIRCUT_BPS);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Haircut, &haircut_bps);
+
+        log!(&env, "Gold deposit haircut set to {} bps", haircut_bps);
+    }
+
+    /// Approve or revoke a yield strategy contract for gold allocations (admin only)
+    pub fn set_strategy_whitelisted(env: Env, admin: Address, strategy: Address, approved: bool) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can whitelist strategies");
+        }
+
+        if approved {
+            env.storage()
+                .instance()
+                .set(&DataKey::StrategyWhitelisted(strategy.clone()), &true);
+        } else {
+            env.storage()
+                .instance()
+                .remove(&DataKey::StrategyWhitelisted(strategy.clone()));
+        }
+
+        log!(&env, "Strategy {} whitelisted: {}", strategy, approved);
+    }
+
+    /// Check whether a strategy contract may receive gold allocations
+    pub fn is_strategy_whitelisted(env: Env, strategy: Address) -> bool {
+        env.storage()
+            .instance()
+            .has(&DataKey::StrategyWhitelisted(strategy))
+    }
+
+    /// Point a gold asset's strategy slot at a whitelisted strategy and cap its share (governance or admin)
+    ///
+    /// The cap is at most `MAX_STRATEGY_SHARE_BPS` of the vault's holdings of the
+    /// asset. A slot can only switch strategies once everything is deallocated.
+    pub fn set_strategy(
+        env: Env,
+        caller: Address,
+        gold_asset: Address,
+        strategy: Address,
+        max_share_bps: u32,
+    ) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::verify_supported_asset(&env, &gold_asset);
+
+        if !Self::is_strategy_whitelisted(env.clone(), strategy.clone()) {
+            panic!("Strategy is not whitelisted");
+        }
+        if max_share_bps > MAX_STRATEGY_SHARE_BPS {
+            panic!(
+                "Strategy share cannot exceed {} bps",
+                MAX_STRATEGY_SHARE_BPS
+            );
+        }
+
+        let slot = match Self::get_strategy(env.clone(), gold_asset.clone()) {
+            Some(slot) if slot.strategy == strategy => StrategySlot {
+                max_share_bps,
+                ..slot
+            },
+            Some(slot) if slot.allocated > 0 => panic!("Deallocate the current strategy first"),
+            _ => StrategySlot {
+                strategy: strategy.clone(),
+                max_share_bps,
+                allocated: 0,
+                harvested: 0,
+            },
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(gold_asset.clone()), &slot);
+
+        log!(
+            &env,
+            "Strategy for {} set to {} capped at {} bps",
+            gold_asset,
+            strategy,
+            max_share_bps
+        );
+    }
+
+    /// Get a gold asset's strategy slot, if one is set
+    pub fn get_strategy(env: Env, gold_asset: Address) -> Option<StrategySlot> {
+        env.storage().instance().get(&DataKey::Strategy(gold_asset))
+    }
+
+    /// Lend vault gold to the asset's strategy, within its cap (governance or admin)
+    ///
+    /// The strategy receives the gold and is then told to deploy it with `deposit`.
+    pub fn allocate_to_strategy(env: Env, caller: Address, gold_asset: Address, amount: u128) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+
+        let mut slot = Self::get_strategy(env.clone(), gold_asset.clone())
+            .unwrap_or_else(|| panic!("No strategy set for this asset"));
+        if amount == 0 {
+            panic!("Allocation must be greater than 0");
+        }
+
+        // allocated / (held + allocated) <= cap, cross-multiplied
+        let held = TokenClient::new(&env, &gold_asset)
+            .balance(&env.current_contract_address())
+            .max(0) as u128;
+        if amount > held {
+            panic!("Not enough idle gold to allocate");
+        }
+        let allocated = slot.allocated + amount;
+        if allocated * BASIS_POINTS > (held + slot.allocated) * slot.max_share_bps as u128 {
+            panic!("Allocation exceeds the strategy cap");
+        }
+
+        enter_guard(&env);
+
+        slot.allocated = allocated;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(gold_asset.clone()), &slot);
+        extend_instance_ttl(&env);
+
+        deposit_to_strategy(&env, &slot.strategy, &gold_asset, amount);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Allocated {} of gold token {} to strategy {}",
+            amount,
+            gold_asset,
+            slot.strategy
+        );
+    }
+
+    /// Bring gold back from the asset's strategy (governance or admin)
+    ///
+    /// Returns the gold actually received, which is also what the allocation drops by.
+    pub fn deallocate_from_strategy(
+        env: Env,
+        caller: Address,
+        gold_asset: Address,
+        amount: u128,
+    ) -> u128 {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        enter_guard(&env);
+        let returned = Self::recall_from_strategy(&env, &gold_asset, amount)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::StrategyCallFailed));
+        exit_guard(&env);
+
+        returned
+    }
+
+    /// Collect the yield the asset's strategy has earned into the vault (permissionless)
+    ///
+    /// Harvested gold stays in the vault backing depositor yield. Returns the gold received.
+    pub fn harvest_strategy(env: Env, gold_asset: Address) -> u128 {
+        let mut slot = Self::get_strategy(env.clone(), gold_asset.clone())
+            .unwrap_or_else(|| panic!("No strategy set for this asset"));
+
+        enter_guard(&env);
+
+        let harvested = harvest_from_strategy(&env, &slot.strategy, &gold_asset)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::StrategyCallFailed));
+        slot.harvested += harvested;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(gold_asset.clone()), &slot);
+        extend_instance_ttl(&env);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Harvested {} of gold token {} from strategy {}",
+            harvested,
+            gold_asset,
+            slot.strategy
+        );
+
+        harvested
+    }
+
+    /// Grant another vault a short-term gold borrowing facility, or withdraw it (governance or admin)
+    ///
+    /// Lets the USDC vault pay withdrawals in gold through its `draw_liquidity` during a
+    /// USDC spike. The cap is in units of the gold asset drawn. Withdrawing a facility
+    /// blocks new draws but leaves an open draw to be repaid.
+    pub fn set_liquidity_facility(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        facility: Option<LiquidityFacility>,
+    ) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        match facility {
+            Some(facility) => {
+                validate_liquidity_facility(&facility);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::Facility(borrower.clone()), &facility);
+
+                log!(
+                    &env,
+                    "Liquidity facility for {}: {} gold cap at {} bps for up to {}s",
+                    borrower,
+                    facility.cap,
+                    facility.rate_bps,
+                    facility.max_term
+                );
+            }
+            None => {
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::Facility(borrower.clone()));
+
+                log!(&env, "Liquidity facility for {} withdrawn", borrower);
+            }
+        }
+        extend_instance_ttl(&env);
+    }
+
+    /// Borrow gold under a granted facility, repayable by `term` seconds from now (borrowing vault)
+    ///
+    /// One draw may be open at a time. It is limited by the facility cap, the gold's USD
+    /// value against the borrower's recorded holdings (`holdings`, its `get_vault_balance`)
+    /// at the facility LTV, and idle gold.
+    pub fn draw_facility(
+        env: Env,
+        borrower: Address,
+        gold_asset: Address,
+        amount: u128,
+        term: u64,
+        holdings: u128,
+    ) -> FacilityDraw {
+        borrower.require_auth();
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+        Self::verify_supported_asset(&env, &gold_asset);
+
+        let facility = Self::get_liquidity_facility(env.clone(), borrower.clone())
+            .unwrap_or_else(|| panic!("No liquidity facility for borrower"));
+        if Self::get_facility_draw(env.clone(), borrower.clone()).is_some() {
+            panic!("Repay the open draw first");
+        }
+        if amount == 0 || amount > facility.cap {
+            panic!("Draw must be between 1 and the facility cap");
+        }
+        if term == 0 || term > facility.max_term {
+            panic!("Draw term exceeds the facility maximum");
+        }
+
+        // The borrower's recorded holdings back the draw. The borrowing vault reports them
+        // itself, as the lender cannot call back into a vault that is calling it
+        let draw_value = Self::get_usd_value(env.clone(), gold_asset.clone(), amount);
+        if draw_value * BASIS_POINTS > holdings * facility.max_ltv_bps as u128 {
+            panic!("Draw exceeds the facility LTV against borrower holdings");
+        }
+        let idle = TokenClient::new(&env, &gold_asset)
+            .balance(&env.current_contract_address())
+            .max(0) as u128;
+        if amount > idle {
+            panic!("Not enough idle gold to lend");
+        }
+
+        enter_guard(&env);
+
+        let now = env.ledger().timestamp();
+        let draw = FacilityDraw {
+            asset: gold_asset.clone(),
+            principal: amount,
+            rate_bps: facility.rate_bps,
+            drawn_at: now,
+            maturity: now + term,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::FacilityDraw(borrower.clone()), &draw);
+        let lent = Self::get_facility_lent(env.clone(), gold_asset.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::FacilityLent(gold_asset.clone()), &(lent + amount));
+        extend_instance_ttl(&env);
+
+        transfer_or_abort(
+            &env,
+            &gold_asset,
+            &env.current_contract_address(),
+            &borrower,
+            amount as i128,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Vault {} drew {} of gold token {} until {}",
+            borrower,
+            amount,
+            gold_asset,
+            draw.maturity
+        );
+
+        draw
+    }
+
+    /// Repay a vault's open draw with its interest in the gold lent, paid by `payer` (permissionless)
+    ///
+    /// Interest keeps accruing past maturity and stays in the vault backing depositor
+    /// yield, like harvested strategy gold. Returns the gold repaid.
+    pub fn repay_facility(env: Env, payer: Address, borrower: Address) -> u128 {
+        payer.require_auth();
+
+        enter_guard(&env);
+        let (repaid, interest) = Self::settle_facility_draw(&env, &payer, &borrower);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Vault {} repaid {} gold including {} interest",
+            borrower,
+            repaid,
+            interest
+        );
+
+        repaid
+    }
+
+    /// Repay an overdue draw in the borrower's place and take its holdings as collateral (permissionless)
+    ///
+    /// The liquidator pays the gold principal and interest. The borrowing vault then
+    /// surrenders holdings worth their USD value plus `FACILITY_LIQUIDATION_BONUS_BPS`
+    /// to the liquidator, or the whole call fails. Returns the gold repaid.
+    pub fn liquidate_facility(env: Env, liquidator: Address, borrower: Address) -> u128 {
+        liquidator.require_auth();
+        if !Self::is_facility_overdue(env.clone(), borrower.clone()) {
+            panic!("Only an overdue draw can be liquidated");
+        }
+
+        enter_guard(&env);
+        let asset = Self::get_facility_draw(env.clone(), borrower.clone())
+            .unwrap()
+            .asset;
+        let (repaid, _) = Self::settle_facility_draw(&env, &liquidator, &borrower);
+        let collateral_value = mul_div(
+            Self::get_usd_value(env.clone(), asset, repaid),
+            BASIS_POINTS + FACILITY_LIQUIDATION_BONUS_BPS,
+            BASIS_POINTS,
+        )
+        .unwrap_or_else(|| panic!("Collateral value overflow"));
+        invoke_peer::<u128>(
+            &env,
+            &borrower,
+            "surrender_facility_collateral",
+            (
+                env.current_contract_address(),
+                liquidator.clone(),
+                collateral_value,
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Liquidator {} repaid {} gold for vault {} and took collateral worth ${}",
+            liquidator,
+            repaid,
+            borrower,
+            collateral_value
+        );
+
+        repaid
+    }
+
+    /// Get the facility granted to a borrowing vault
+    pub fn get_liquidity_facility(env: Env, borrower: Address) -> Option<LiquidityFacility> {
+        env.storage().instance().get(&DataKey::Facility(borrower))
+    }
+
+    /// Get a borrowing vault's open draw
+    pub fn get_facility_draw(env: Env, borrower: Address) -> Option<FacilityDraw> {
+        env.storage()
+            .instance()
+            .get(&DataKey::FacilityDraw(borrower))
+    }
+
+    /// Get the gold interest accrued so far on a borrowing vault's open draw, rounded up
+    pub fn get_facility_interest(env: Env, borrower: Address) -> u128 {
+        Self::get_facility_draw(env.clone(), borrower)
+            .map_or(0, |draw| facility_interest(&env, &draw))
+    }
+
+    /// Check whether a borrowing vault's open draw is past maturity
+    pub fn is_facility_overdue(env: Env, borrower: Address) -> bool {
+        Self::get_facility_draw(env.clone(), borrower)
+            .map_or(false, |draw| env.ledger().timestamp() > draw.maturity)
+    }
+
+    /// Get a gold asset's amount lent out across all open facility draws
+    pub fn get_facility_lent(env: Env, gold_asset: Address) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FacilityLent(gold_asset))
+            .unwrap_or(0)
+    }
+
+    /// Borrow USDC from the USDC vault's facility to cover a gold withdrawal spike (governance or admin)
+    ///
+    /// The USDC is paid out to depositors through `withdraw_in_liquidity`, and the gold they
+    /// leave behind is sold with `swap_for_repayment` before `repay_liquidity`.
+    pub fn draw_liquidity(
+        env: Env,
+        caller: Address,
+        lender: Address,
+        amount: u128,
+        term: u64,
+    ) -> FacilityDraw {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+
+        if let Some(current) = Self::get_liquidity_lender(env.clone()) {
+            if drawn_liquidity(&env, &current).is_some() {
+                panic!("Repay the open liquidity draw first");
+            }
+        }
+
+        let draw: FacilityDraw = invoke_peer(
+            &env,
+            &lender,
+            "draw_facility",
+            (
+                env.current_contract_address(),
+                amount,
+                term,
+                Self::get_vault_balance(env.clone()),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidityLender, &lender);
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidityAsset, &draw.asset);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Drew {} USDC from vault {} until {}",
+            amount,
+            lender,
+            draw.maturity
+        );
+
+        draw
+    }
+
+    /// Repay the open liquidity draw from the USDC the vault holds (permissionless)
+    ///
+    /// Returns the USDC repaid, principal and interest.
+    pub fn repay_liquidity(env: Env) -> u128 {
+        let lender = Self::get_liquidity_lender(env.clone())
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+
+        enter_guard(&env);
+        let repaid = repay_drawn_liquidity(&env, &lender);
+        exit_guard(&env);
+
+        log!(&env, "Repaid {} drawn USDC to vault {}", repaid, lender);
+
+        repaid
+    }
+
+    /// Sell gold for the drawn USDC through the trade router while a draw is open (governance or admin)
+    ///
+    /// Aborts with `SlippageExceeded` if less than `min_amount_out` arrives. Returns the USDC bought.
+    pub fn swap_for_repayment(
+        env: Env,
+        caller: Address,
+        gold_asset: Address,
+        amount_in: u128,
+        min_amount_out: u128,
+    ) -> u128 {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::verify_supported_asset(&env, &gold_asset);
+
+        let lender = Self::get_liquidity_lender(env.clone())
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+        if drawn_liquidity(&env, &lender).is_none() {
+            panic!("No open liquidity draw to repay");
+        }
+        let liquidity_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiquidityAsset)
+            .unwrap();
+        let idle = TokenClient::new(&env, &gold_asset)
+            .balance(&env.current_contract_address())
+            .max(0) as u128;
+        if amount_in == 0 || amount_in > idle {
+            panic!("Swap must be between 1 and the vault's idle gold");
+        }
+
+        enter_guard(&env);
+        let router = lookup_registry(&env, &DataKey::Registry, REGISTRY_TRADE_ROUTER)
+            .unwrap_or_else(|| panic!("Trade router not configured"));
+        let bought = swap_via_router(
+            &env,
+            &router,
+            &gold_asset,
+            &liquidity_asset,
+            amount_in,
+            min_amount_out,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Swapped {} of gold token {} for {} USDC",
+            amount_in,
+            gold_asset,
+            bought
+        );
+
+        bought
+    }
+
+    /// Withdraw a matured position in drawn USDC instead of gold, at its USD value
+    ///
+    /// Pays the USD value `withdraw` would pay in gold, and aborts with `SlippageExceeded`
+    /// below `min_usdc_out`. The position's gold stays in the vault. Returns the USDC paid.
+    pub fn withdraw_in_liquidity(env: Env, user: Address, min_usdc_out: u128) -> u128 {
+        user.require_auth();
+        let recipient = Self::payout_address(&env, &user);
+        Self::withdraw_to(env, user, recipient, Payout::Liquidity(min_usdc_out))
+    }
+
+    /// Hand gold worth `value_usd` to a liquidator of an overdue liquidity draw (lending vault only)
+    ///
+    /// Called by the lender's `liquidate_facility` once the liquidator has repaid it. The
+    /// gold comes from the first supported asset the vault holds enough of. Returns the gold surrendered.
+    pub fn surrender_facility_collateral(
+        env: Env,
+        lender: Address,
+        recipient: Address,
+        value_usd: u128,
+    ) -> u128 {
+        lender.require_auth();
+        if Self::get_liquidity_lender(env.clone()) != Some(lender.clone()) {
+            panic!("Only the liquidity lender can claim collateral");
+        }
+
+        let supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(&env));
+        for gold_asset in supported_assets.iter() {
+            let gold_amount = mul_div_up(
+                value_usd,
+                GOLD_PRICE_PRECISION,
+                Self::get_gold_price(env.clone(), gold_asset.clone()),
+            )
+            .unwrap_or_else(|| panic!("Collateral amount overflow"));
+            let held = TokenClient::new(&env, &gold_asset)
+                .balance(&env.current_contract_address())
+                .max(0) as u128;
+            if held >= gold_amount {
+                transfer_or_abort(
+                    &env,
+                    &gold_asset,
+                    &env.current_contract_address(),
+                    &recipient,
+                    gold_amount as i128,
+                );
+
+                log!(
+                    &env,
+                    "Surrendered {} of gold token {} to {} for an overdue draw from vault {}",
+                    gold_amount,
+                    gold_asset,
+                    recipient,
+                    lender
+                );
+
+                return gold_amount;
+            }
+        }
+
+        panic!("Not enough idle gold to surrender");
+    }
+
+    /// Get the vault the last liquidity draw was taken from, if any
+    pub fn get_liquidity_lender(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::LiquidityLender)
+    }
+
+    /// Get a gold asset's fresh oracle price at 6 decimals
+    pub fn get_gold_price(env: Env, gold_asset: Address) -> u128 {
+        Self::gold_price(&env, &gold_asset).1
+    }
+
+    /// Internal helper functions
+    fn verify_supported_asset(env: &Env, asset: &Address) {
+        let supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(env));
+
+        for supported_asset in supported_assets.iter() {
+            if supported_asset == *asset {
+                return;
+            }
+        }
+
+        panic!("Unsupported gold asset");
+    }
+
+    fn determine_vault_type(env: &Env, gold_asset: &Address) -> VaultType {
+        if Self::is_paxg_asset(env, gold_asset) {
+            VaultType::PAXG
+        } else {
+            VaultType::WisdomTreeGold
+        }
+    }
+
+    fn gold_price(env: &Env, gold_asset: &Address) -> (&'static str, u128) {
+        // Never value gold at a missing, zero or stale price
+        let (price_feed, gold_price_usd) = Self::read_gold_price(env, gold_asset);
+        let gold_price_usd =
+            gold_price_usd.unwrap_or_else(|| panic_with_error!(env, ContractError::StalePrice));
+
+        (price_feed, gold_price_usd)
+    }
+
+    fn read_gold_price(env: &Env, gold_asset: &Address) -> (&'static str, Option<u128>) {
+        let oracle_contract =
+            resolve_peer(env, &DataKey::Registry, REGISTRY_ORACLE, &DataKey::Oracle);
+
+        // Determine price feed symbol based on asset
+        let price_feed = if Self::is_paxg_asset(env, gold_asset) {
+            "PAXG/USD"
+        } else {
+            "XAU/USD" // Generic gold price for Wisdom Tree or other gold tokens
+        };
+
+        (
+            price_feed,
+            read_oracle_price(env, &oracle_contract, price_feed),
+        )
+    }
+
+    fn is_paxg_asset(env: &Env, asset: &Address) -> bool {
+        // TODO: Replace with actual PAXG asset address comparison
+        // For now, assume first supported asset is PAXG
+        let supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(env));
+
+        if let Some(first_asset) = supported_assets.first() {
+            asset == first_asset.clone()
+        } else {
+            false
+        }
+    }
+
+    fn ensure_allowlisted(env: &Env, user: &Address) {
+        if !env.storage().instance().has(&DataKey::LaunchPhase) {
+            return;
+        }
+
+        // Fails closed: a launch phase with no resolvable allowlist admits nobody
+        let allowlist = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_ALLOWLIST,
+            &DataKey::Allowlist,
+        );
+        let allowed: bool = invoke_peer(
+            env,
+            &allowlist,
+            "is_allowed",
+            (user.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        if !allowed {
+            panic_with_error!(env, ContractError::NotAllowlisted);
+        }
+    }
+
+    fn verify_governance_or_admin(env: &Env, caller: &Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if *caller != admin && governance.as_ref() != Some(caller) {
+            panic!("Only governance or admin can manage vault risk parameters");
+        }
+    }
+
+    fn verify_governance(env: &Env, caller: &Address) {
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if governance.as_ref() != Some(caller) {
+            panic!("Only governance can enter global settlement");
+        }
+    }
+
+    fn verify_loan_manager(env: &Env, caller: &Address) {
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if governance.as_ref() != Some(caller) {
+            panic!("Only the loan manager can place liens");
+        }
+    }
+
+    fn ensure_unencumbered(env: &Env, user: &Address) {
+        let receipt_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ActiveReceipt(user.clone()));
+        if let Some(receipt_id) = receipt_id {
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Encumbrance(user.clone(), receipt_id))
+            {
+                panic!("Position is encumbered by a loan");
+            }
+        }
+    }
+
+    fn ensure_not_settled(env: &Env) {
+        if env.storage().instance().has(&DataKey::Settlement) {
+            panic_with_error!(env, ContractError::GlobalSettlement);
+        }
+    }
+
+    fn open_deposit(
+        env: &Env,
+        user: &Address,
+        gold_asset: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        pull: bool,
+        tag: Option<DepositTag>,
+    ) {
+        ensure_not_paused(env);
+        Self::ensure_not_settled(env);
+        Self::ensure_allowlisted(env, user);
+
+        if amount == 0 {
+            panic!("Deposit amount must be greater than 0");
+        }
+
+        // Verify the gold asset is supported
+        Self::verify_supported_asset(env, &gold_asset);
+
+        let current_time = env.ledger().timestamp();
+        let unlock_time = Self::calculate_unlock_time(current_time, &lock_period);
+
+        // Check if user already has a deposit
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Deposit(user.clone()))
+        {
+            panic!("User already has an active deposit. Withdraw first to make a new deposit.");
+        }
+
+        // A schema v1 position must be migrated before it can be replaced
+        if env.storage().persistent().has(&(v1::DEPOSIT, user.clone())) {
+            panic!("User has an unmigrated deposit. It must be migrated first.");
+        }
+
+        // Get USD value of the gold deposit, less the haircut held back as a buffer
+        let (price_feed, gold_price_usd) = Self::gold_price(env, &gold_asset);
+        let full_usd_value = gold_usd_value(amount, gold_price_usd)
+            .unwrap_or_else(|| panic!("Gold USD value overflow"));
+        let haircut_bps = Self::get_haircut(env.clone()) as u128;
+        let haircut_usd_value = mul_div_up(full_usd_value, haircut_bps, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Haircut overflow"));
+        let usd_value = full_usd_value - haircut_usd_value;
+        let haircut_buffer = amount * haircut_bps / BASIS_POINTS;
+
+        Self::enforce_tier_cap(env, &lock_period, usd_value);
+
+        enter_guard(env);
+
+        // Update vault and lock tier balances (in USD terms)
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultBalance, &(vault_balance + usd_value));
+        Self::add_tier_balance(env, &lock_period, usd_value);
+
+        // Create deposit info and its receipt
+        let vault_type = Self::determine_vault_type(env, &gold_asset);
+        let deposit_info = DepositInfo {
+            amount: usd_value, // Store as USD value for yield calculations
+            deposit_time: current_time,
+            unlock_time,
+            lock_period: lock_period.clone(),
+            vault_type: vault_type.clone(),
+        };
+
+        // Store deposit info with gold asset details
+        env.storage()
+            .persistent()
+            .set(&DataKey::Deposit(user.clone()), &deposit_info);
+
+        // Store original gold amount and asset for withdrawal
+        env.storage()
+            .persistent()
+            .set(&DataKey::GoldAmount(user.clone()), &amount);
+        env.storage()
+            .persistent()
+            .set(&DataKey::GoldAsset(user.clone()), &gold_asset);
+        extend_persistent_ttl(env, &DataKey::Deposit(user.clone()));
+        extend_persistent_ttl(env, &DataKey::GoldAmount(user.clone()));
+        extend_persistent_ttl(env, &DataKey::GoldAsset(user.clone()));
+        if haircut_buffer > 0 {
+            env.storage()
+                .persistent()
+                .set(&DataKey::HaircutBuffer(user.clone()), &haircut_buffer);
+            extend_persistent_ttl(env, &DataKey::HaircutBuffer(user.clone()));
+        }
+
+        // The valuation basis stays with the position for withdrawals and disputes
+        let deposit_price = PriceSnapshot {
+            feed: String::from_str(env, price_feed),
+            price: gold_price_usd,
+            ledger: env.ledger().sequence(),
+            observed_at: current_time,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::DepositPrice(user.clone()), &deposit_price);
+        extend_persistent_ttl(env, &DataKey::DepositPrice(user.clone()));
+        if let Some(tag) = &tag {
+            env.storage()
+                .persistent()
+                .set(&DataKey::DepositTag(user.clone()), tag);
+            extend_persistent_ttl(env, &DataKey::DepositTag(user.clone()));
+        }
+
+        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period.clone());
+        Self::issue_receipt(env, user, &gold_asset, amount, &deposit_info, yield_rate);
+        Self::track_depositor(env, user);
+        extend_instance_ttl(env);
+
+        // Transfer gold tokens from user to vault, pulling them under the user's allowance when they did not sign
+        if pull {
+            transfer_from_or_abort(
+                env,
+                &gold_asset,
+                &env.current_contract_address(),
+                user,
+                &env.current_contract_address(),
+                amount as i128,
+            );
+        } else {
+            transfer_or_abort(
+                env,
+                &gold_asset,
+                user,
+                &env.current_contract_address(),
+                amount as i128,
+            );
+        }
+
+        // Mint yield tokens on the USD value at the rate recorded on the receipt
+        let yield_token_contract: Address = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+
+        // A failed mint reverts the deposit, including the gold transfer
+        invoke_peer::<()>(
+            env,
+            &yield_token_contract,
+            "mint_for_deposit",
+            (
+                env.current_contract_address(),
+                user.clone(),
+                usd_value,
+                vault_type,
+                yield_rate,
+            )
+                .into_val(env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        Self::register_reward_stake(env, user, usd_value, &lock_period);
+        exit_guard(env);
+
+        // Integrators reconcile customer positions from this event
+        env.events().publish(
+            (symbol_short!("deposit"), user.clone()),
+            (
+                gold_asset,
+                amount,
+                usd_value,
+                lock_period.clone(),
+                unlock_time,
+                tag,
+            ),
+        );
+
+        log!(
+            env,
+            "User {} deposited {} gold tokens (${} USD value, {} held as haircut buffer) with {:?} lock period",
+            user,
+            amount,
+            usd_value,
+            haircut_buffer,
+            lock_period
+        );
+    }
+
+    fn release_haircut_buffer(
+        gold_amount: u128,
+        haircut_buffer: u128,
+        principal_usd: u128,
+        current_usd_value: u128,
+    ) -> u128 {
+        if haircut_buffer == 0 || current_usd_value == 0 {
+            return haircut_buffer;
+        }
+
+        // Gold now needed to repay the minted principal; anything beyond the
+        // unbuffered gold came from a price drop and is kept from the buffer
+        let principal_gold = mul_div_up(gold_amount, principal_usd, current_usd_value)
+            .unwrap_or_else(|| panic!("Gold withdrawal amount overflow"));
+        let buffer_used = principal_gold
+            .saturating_sub(gold_amount - haircut_buffer)
+            .min(haircut_buffer);
+        haircut_buffer - buffer_used
+    }
+
+    fn recall_from_strategy(env: &Env, gold_asset: &Address, amount: u128) -> Option<u128> {
+        let Some(mut slot) = Self::get_strategy(env.clone(), gold_asset.clone()) else {
+            return Some(0);
+        };
+        let requested = amount.min(slot.allocated);
+        if requested == 0 {
+            return Some(0);
+        }
+
+        let returned = withdraw_from_strategy(env, &slot.strategy, gold_asset, requested)?;
+        slot.allocated -= returned.min(slot.allocated);
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(gold_asset.clone()), &slot);
+
+        log!(
+            env,
+            "Recalled {} of gold token {} from strategy {}",
+            returned,
+            gold_asset,
+            slot.strategy
+        );
+
+        Some(returned)
+    }
+
+    fn snapshot_settlement(env: &Env, settled_at: u64) -> SettlementSnapshot {
+        let supported_assets: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::SupportedAssets)
+            .unwrap_or(Vec::new(env));
+        let mut held_value = 0u128;
+        for asset in supported_assets.iter() {
+            // Strategies return everything they hold so settlement can pay it out;
+            // a failing strategy must not block settlement, so its gold is left out
+            if let Some(slot) = Self::get_strategy(env.clone(), asset.clone()) {
+                if Self::recall_from_strategy(env, &asset, slot.allocated).is_none() {
+                    log!(
+                        env,
+                        "Strategy {} failed to return its gold at settlement",
+                        slot.strategy
+                    );
+                }
+            }
+            let gold_client = TokenClient::new(env, &asset);
+            let held = gold_client.balance(&env.current_contract_address()).max(0) as u128;
+            env.storage()
+                .instance()
+                .set(&DataKey::SettlementHeld(asset.clone()), &held);
+            held_value += Self::get_usd_value(env.clone(), asset, held);
+        }
+
+        let snapshot = SettlementSnapshot {
+            settled_at,
+            recorded_liquidity: Self::get_vault_balance(env.clone()),
+            held_value,
+        };
         env.storage()
             .instance()
-            .set(&SUPPORTED_ASSETS, &supported_gold_assets);
-        env.storage().instance().set(&VAULT_BALANCE, &0u128);
+            .set(&DataKey::Settlement, &snapshot);
+        extend_instance_ttl(env);
 
-        log!(&env, "Gold Vault initialized with admin: {}", admin);
+        snapshot
     }
 
-    /// Deposit gold tokens (PAXG/Wisdom Tree) into the vault with time lock
-    pub fn deposit(
-        env: Env,
-        user: Address,
-        gold_asset: Address,
-        amount: u128,
-        lock_period: LockPeriod,
-    ) {
-        user.require_auth();
+    fn settle_facility_draw(env: &Env, payer: &Address, borrower: &Address) -> (u128, u128) {
+        let draw = Self::get_facility_draw(env.clone(), borrower.clone())
+            .unwrap_or_else(|| panic!("No open draw for borrower"));
+        let interest = facility_interest(env, &draw);
 
-        if amount == 0 {
-            panic!("Deposit amount must be greater than 0");
-        }
+        env.storage()
+            .instance()
+            .remove(&DataKey::FacilityDraw(borrower.clone()));
+        let lent = Self::get_facility_lent(env.clone(), draw.asset.clone());
+        env.storage().instance().set(
+            &DataKey::FacilityLent(draw.asset.clone()),
+            &lent.saturating_sub(draw.principal),
+        );
+        extend_instance_ttl(env);
 
-        // Verify the gold asset is supported
-        Self::verify_supported_asset(&env, &gold_asset);
+        let repaid = draw.principal + interest;
+        transfer_or_abort(
+            env,
+            &draw.asset,
+            payer,
+            &env.current_contract_address(),
+            repaid as i128,
+        );
 
-        let current_time = env.ledger().timestamp();
-        let unlock_time = Self::calculate_unlock_time(current_time, &lock_period);
+        (repaid, interest)
+    }
 
-        // Check if user already has a deposit
-        if env
+    fn pay_in_liquidity(
+        env: &Env,
+        recipient: &Address,
+        usd_value: u128,
+        min_usdc_out: u128,
+    ) -> u128 {
+        // Gold is valued in USDC units, so the position's USD value is paid one for one
+        let usdc_contract: Address = env
             .storage()
-            .persistent()
-            .has(&(DEPOSIT.clone(), user.clone()))
-        {
-            panic!("User already has an active deposit. Withdraw first to make a new deposit.");
+            .instance()
+            .get(&DataKey::LiquidityAsset)
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+        if usd_value < min_usdc_out {
+            panic_with_error!(env, ContractError::SlippageExceeded);
         }
+        let held = TokenClient::new(env, &usdc_contract)
+            .balance(&env.current_contract_address())
+            .max(0) as u128;
+        if usd_value > held {
+            panic!("Not enough drawn liquidity to pay out");
+        }
+        transfer_or_abort(
+            env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            recipient,
+            usd_value as i128,
+        );
 
-        // Transfer gold tokens from user to vault
-        let gold_client = TokenClient::new(&env, &gold_asset);
-        gold_client.transfer(&user, &env.current_contract_address(), &(amount as i128));
-
-        // Get USD value of the gold deposit
-        let usd_value = Self::get_usd_value(env.clone(), gold_asset.clone(), amount);
+        usd_value
+    }
 
-        // Update vault balance (in USD terms)
-        let vault_balance: u128 = env.storage().instance().get(&VAULT_BALANCE).unwrap_or(0);
+    fn book_rounding_dust(env: &Env, gold_asset: &Address, dust: u128) {
+        if dust == 0 {
+            return;
+        }
+        let booked = Self::get_rounding_dust(env.clone(), gold_asset.clone());
         env.storage()
             .instance()
-            .set(&VAULT_BALANCE, &(vault_balance + usd_value));
-
-        // Create deposit info
-        let vault_type = Self::determine_vault_type(&env, &gold_asset);
-        let deposit_info = DepositInfo {
-            amount: usd_value, // Store as USD value for yield calculations
-            deposit_time: current_time,
-            unlock_time,
-            lock_period: lock_period.clone(),
-            vault_type,
-        };
+            .set(&DataKey::RoundingDust(gold_asset.clone()), &(booked + dust));
+    }
 
-        // Store deposit info with gold asset details
-        env.storage()
-            .persistent()
-            .set(&(DEPOSIT.clone(), user.clone()), &deposit_info);
+    fn verify_deposit_operator(env: &Env, user: &Address, operator: &Address) {
+        if operator != user
+            && !Self::is_deposit_operator(env.clone(), user.clone(), operator.clone())
+        {
+            panic!("Operator is not approved to deposit for this user");
+        }
+    }
 
-        // Store original gold amount and asset for withdrawal
-        env.storage()
-            .persistent()
-            .set(&(Symbol::new(&env, "gold_amount"), user.clone()), &amount);
-        env.storage().persistent().set(
-            &(Symbol::new(&env, "gold_asset"), user.clone()),
-            &gold_asset,
-        );
+    fn enforce_tier_cap(env: &Env, lock_period: &LockPeriod, usd_value: u128) {
+        let cap: Option<LockTierCap> = env
+            .storage()
+            .instance()
+            .get(&DataKey::TierCap(lock_period.clone()));
+        if let Some(cap) = cap {
+            let vault_balance = Self::get_vault_balance(env.clone()) + usd_value;
+            if vault_balance <= cap.min_tvl {
+                return;
+            }
 
-        // Calculate yield rate and mint yield tokens (based on USD value)
-        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period.clone());
-        let yield_token_contract: Address = env.storage().instance().get(&YIELD_TOKEN).unwrap();
+            // tier / vault > cap, cross-multiplied to avoid rounding
+            let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone()) + usd_value;
+            if tier_balance * BASIS_POINTS > vault_balance * cap.max_share_bps as u128 {
+                panic_with_error!(env, ContractError::TierCapExceeded);
+            }
+        }
+    }
 
-        env.invoke_contract(
-            &yield_token_contract,
-            &Symbol::new(&env, "mint_for_deposit"),
-            (
-                env.current_contract_address(),
-                user.clone(),
-                usd_value,
-                vault_type,
-                yield_rate,
-            )
-                .into_val(&env),
+    fn add_tier_balance(env: &Env, lock_period: &LockPeriod, usd_value: u128) {
+        let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone());
+        env.storage().instance().set(
+            &DataKey::TierBalance(lock_period.clone()),
+            &(tier_balance + usd_value),
         );
+    }
 
-        log!(
-            &env,
-            "User {} deposited {} gold tokens (${} USD value) with {:?} lock period",
-            user,
-            amount,
-            usd_value,
-            lock_period
+    fn release_tier_balance(env: &Env, lock_period: &LockPeriod, usd_value: u128) {
+        // Positions opened before tiers were tracked were never added
+        let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone());
+        env.storage().instance().set(
+            &DataKey::TierBalance(lock_period.clone()),
+            &tier_balance.saturating_sub(usd_value),
         );
     }
 
-    /// Withdraw gold tokens from the vault (only after lock period expires)
-    pub fn withdraw(env: Env, user: Address) -> u128 {
-        user.require_auth();
+    fn register_reward_stake(env: &Env, user: &Address, usd_value: u128, lock_period: &LockPeriod) {
+        // Liquidity mining is optional
+        let rewards_contract: Option<Address> = env.storage().instance().get(&DataKey::Rewards);
+        if let Some(rewards_contract) = rewards_contract {
+            // A rewards outage must not block deposits; the stake can be re-registered later
+            let registered = try_invoke_peer::<()>(
+                env,
+                &rewards_contract,
+                "register_stake",
+                (
+                    env.current_contract_address(),
+                    user.clone(),
+                    usd_value,
+                    lock_period.clone(),
+                )
+                    .into_val(env),
+            );
+            if registered.is_none() {
+                log!(env, "Reward stake registration failed for user {}", user);
+            }
+        }
+    }
+
+    fn withdraw_to(env: Env, user: Address, recipient: Address, payout: Payout) -> u128 {
+        Self::ensure_not_settled(&env);
+        Self::ensure_unencumbered(&env, &user);
 
         let deposit_info: DepositInfo = env
             .storage()
             .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
+            .get(&DataKey::Deposit(user.clone()))
             .unwrap_or_else(|| panic!("No deposit found for user"));
 
-        let current_time = env.ledger().timestamp();
+        let current_time = deadline_time(&env);
 
         if current_time < deposit_info.unlock_time {
             panic!(
@@ -153,217 +2626,484 @@ impl GoldVault {
             );
         }
 
+        Self::close_position(env, user, recipient, payout)
+    }
+
+    fn close_position(env: Env, user: Address, recipient: Address, payout: Payout) -> u128 {
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
         // Get original gold amount and asset
         let original_gold_amount: u128 = env
             .storage()
             .persistent()
-            .get(&(Symbol::new(&env, "gold_amount"), user.clone()))
+            .get(&DataKey::GoldAmount(user.clone()))
             .unwrap();
         let gold_asset: Address = env
             .storage()
             .persistent()
-            .get(&(Symbol::new(&env, "gold_asset"), user.clone()))
+            .get(&DataKey::GoldAsset(user.clone()))
+            .unwrap();
+        let haircut_buffer = Self::get_haircut_buffer(env.clone(), user.clone());
+        let deposit_price = Self::get_deposit_price(env.clone(), user.clone());
+
+        enter_guard(&env);
+
+        // Effects: close the position before any outbound call
+        // (only the deposit's USD value was recorded)
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
             .unwrap();
+        env.storage().instance().set(
+            &DataKey::VaultBalance,
+            &(vault_balance - deposit_info.amount),
+        );
+        Self::release_tier_balance(&env, &deposit_info.lock_period, deposit_info.amount);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Deposit(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DepositTag(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::GoldAmount(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::GoldAsset(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::HaircutBuffer(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DepositPrice(user.clone()));
+        let exported_receipt = Self::close_receipt(&env, &user);
+        extend_instance_ttl(&env);
 
-        // Calculate final USD amount including yield
-        let yield_token_contract: Address = env.storage().instance().get(&YIELD_TOKEN).unwrap();
+        // Interactions: settle yield and burn, then pay out
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
 
-        // Compound interest first
-        env.invoke_contract(
+        invoke_peer::<u128>(
+            &env,
             &yield_token_contract,
-            &Symbol::new(&env, "compound_interest"),
+            "compound_interest",
             (user.clone(),).into_val(&env),
+            ContractError::YieldTokenCallFailed,
         );
 
-        // Get final USD balance from yield token
-        let final_usd_amount: i128 = env.invoke_contract(
+        // Burn exactly the USD balance just read, with no other call in between
+        let final_usd_amount: i128 = invoke_peer(
+            &env,
             &yield_token_contract,
-            &Symbol::new(&env, "balance"),
+            "balance",
             (user.clone(),).into_val(&env),
+            ContractError::YieldTokenCallFailed,
         );
-
         let withdrawal_usd_value = final_usd_amount as u128;
 
-        // Calculate equivalent gold amount based on current price
-        let current_gold_usd_value =
-            Self::get_usd_value(env.clone(), gold_asset.clone(), original_gold_amount);
-        let gold_amount_to_return = if current_gold_usd_value > 0 {
-            (original_gold_amount * withdrawal_usd_value) / current_gold_usd_value
-        } else {
-            original_gold_amount // Fallback to original amount if price feed fails
-        };
+        // Only the rate schedule's share is counted; index yield is counted when distributed
+        let yield_rate = Self::calculate_yield_rate(env.clone(), deposit_info.lock_period.clone());
+        let scheduled = compound_daily(
+            deposit_info.amount,
+            yield_rate,
+            env.ledger()
+                .timestamp()
+                .saturating_sub(deposit_info.deposit_time),
+        )
+        .unwrap_or_else(|| panic!("Projected yield overflow"));
+        let yield_paid = withdrawal_usd_value
+            .min(scheduled)
+            .saturating_sub(deposit_info.amount);
+        if yield_paid > 0 {
+            let total_paid = Self::get_yield_paid(env.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::YieldPaid, &(total_paid + yield_paid));
+        }
 
-        // Burn yield tokens
-        env.invoke_contract(
+        invoke_peer::<()>(
+            &env,
             &yield_token_contract,
-            &Symbol::new(&env, "burn_for_withdrawal"),
+            "burn_for_withdrawal",
             (
                 env.current_contract_address(),
                 user.clone(),
                 withdrawal_usd_value,
             )
                 .into_val(&env),
+            ContractError::YieldTokenCallFailed,
         );
 
-        // Transfer gold tokens back to user
-        let gold_client = TokenClient::new(&env, &gold_asset);
-        gold_client.transfer(
-            &env.current_contract_address(),
-            &user,
-            &(gold_amount_to_return as i128),
+        // Calculate equivalent gold amount based on current price
+        let current_gold_usd_value =
+            Self::get_usd_value(env.clone(), gold_asset.clone(), original_gold_amount);
+        let buffer_released = Self::release_haircut_buffer(
+            original_gold_amount,
+            haircut_buffer,
+            deposit_info.amount,
+            current_gold_usd_value,
         );
 
-        // Update vault balance
-        let vault_balance: u128 = env.storage().instance().get(&VAULT_BALANCE).unwrap();
-        env.storage()
-            .instance()
-            .set(&VAULT_BALANCE, &(vault_balance - withdrawal_usd_value));
+        let amount_paid = match payout {
+            // The gold stays in the vault towards buying back the drawn liquidity
+            Payout::Liquidity(min_out) => {
+                let buffer_usd =
+                    Self::get_usd_value(env.clone(), gold_asset.clone(), buffer_released);
+                Self::pay_in_liquidity(&env, &recipient, withdrawal_usd_value + buffer_usd, min_out)
+            }
+            Payout::Gold(min_gold_out) => {
+                let (gold_amount_owed, dust) = if current_gold_usd_value > 0 {
+                    mul_div_with_dust(
+                        original_gold_amount,
+                        withdrawal_usd_value,
+                        current_gold_usd_value,
+                    )
+                    .unwrap_or_else(|| panic!("Gold withdrawal amount overflow"))
+                } else {
+                    (original_gold_amount, 0) // Fallback to original amount if price feed fails
+                };
+                Self::book_rounding_dust(&env, &gold_asset, dust);
+                let gold_amount_owed = gold_amount_owed + buffer_released;
 
-        // Clean up storage
-        env.storage()
-            .persistent()
-            .remove(&(DEPOSIT.clone(), user.clone()));
-        env.storage()
-            .persistent()
-            .remove(&(Symbol::new(&env, "gold_amount"), user.clone()));
-        env.storage()
-            .persistent()
-            .remove(&(Symbol::new(&env, "gold_asset"), user.clone()));
+                // Pay out what the vault holds, recalling gold lent to its strategy, and record any shortfall as bad debt
+                let gold_client = TokenClient::new(&env, &gold_asset);
+                let mut available_gold =
+                    gold_client.balance(&env.current_contract_address()) as u128;
+                if available_gold < gold_amount_owed {
+                    available_gold += Self::recall_from_strategy(
+                        &env,
+                        &gold_asset,
+                        gold_amount_owed - available_gold,
+                    )
+                    .unwrap_or_else(|| panic_with_error!(&env, ContractError::StrategyCallFailed));
+                }
+                let gold_amount_to_return = if available_gold < gold_amount_owed {
+                    let shortfall_usd = Self::get_usd_value(
+                        env.clone(),
+                        gold_asset.clone(),
+                        gold_amount_owed - available_gold,
+                    );
+                    Self::record_shortfall(&env, &user, shortfall_usd);
+                    available_gold
+                } else {
+                    gold_amount_owed
+                };
+                if gold_amount_to_return < min_gold_out {
+                    panic_with_error!(&env, ContractError::SlippageExceeded);
+                }
+
+                transfer_or_abort(
+                    &env,
+                    &gold_asset,
+                    &env.current_contract_address(),
+                    &recipient,
+                    gold_amount_to_return as i128,
+                );
+
+                gold_amount_to_return
+            }
+        };
+
+        Self::remove_reward_stake(&env, &user);
+        Self::settle_receipt_token(&env, exported_receipt);
+        exit_guard(&env);
 
         log!(
             &env,
-            "User {} withdrew {} gold tokens (${} USD value including yield)",
+            "User {} withdrew {} tokens (${} USD value including yield) to {}",
             user,
-            gold_amount_to_return,
-            withdrawal_usd_value
+            amount_paid,
+            withdrawal_usd_value,
+            recipient
         );
+        if let Some(deposit_price) = deposit_price {
+            log!(
+                &env,
+                "Deposit for user {} was valued at {} on {} (ledger {})",
+                user,
+                deposit_price.price,
+                deposit_price.feed,
+                deposit_price.ledger
+            );
+        }
 
-        gold_amount_to_return
+        amount_paid
     }
 
-    /// Get USD value of gold amount using oracle
-    pub fn get_usd_value(env: Env, gold_asset: Address, gold_amount: u128) -> u128 {
-        let _oracle_contract: Address = env.storage().instance().get(&ORACLE).unwrap();
+    fn store_recovery(env: &Env, user: &Address, plan: &RecoveryPlan) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Recovery(user.clone()), plan);
+        extend_persistent_ttl(env, &DataKey::Recovery(user.clone()));
+    }
 
-        // Determine price feed symbol based on asset
-        let _price_symbol = if Self::is_paxg_asset(&env, &gold_asset) {
-            "PAXG/USD"
-        } else {
-            "XAU/USD" // Generic gold price for Wisdom Tree or other gold tokens
-        };
+    fn designate_beneficiary(env: &Env, user: &Address, beneficiary: &Address) {
+        if Self::get_beneficiary(env.clone(), user.clone()).is_some() {
+            if Self::payout_address(env, user) != *beneficiary {
+                panic!("Beneficiary can only be changed through change_beneficiary");
+            }
+            return;
+        }
 
-        // Call oracle for current price (this is a placeholder - actual oracle integration needed)
-        // For now, using a mock price
-        let gold_price_usd: u128 = 2000_000000; // $2000 per ounce with 6 decimals
+        let designation = Beneficiary {
+            address: beneficiary.clone(),
+            pending: None,
+            effective_at: env.ledger().timestamp(),
+        };
+        Self::store_beneficiary(env, user, &designation);
 
-        // TODO: Replace with actual oracle call:
-        // let gold_price_usd: u128 = env.invoke_contract(
-        //     &oracle_contract,
-        //     &Symbol::new(env, "get_price"),
-        //     (price_symbol,).into_val(env),
-        // );
+        log!(env, "User {} named beneficiary {}", user, beneficiary);
+    }
 
-        (gold_amount * gold_price_usd) / 1_000_000 // Assuming 6 decimal places
+    fn payout_address(env: &Env, user: &Address) -> Address {
+        let Some(designation) = Self::get_beneficiary(env.clone(), user.clone()) else {
+            return user.clone();
+        };
+        // A requested change takes over once its delay has passed
+        match designation.pending {
+            Some(pending) if deadline_time(env) >= designation.effective_at => pending,
+            _ => designation.address,
+        }
     }
 
-    /// Get user's deposit information
-    pub fn get_deposit_info(env: Env, user: Address) -> Option<DepositInfo> {
+    fn store_beneficiary(env: &Env, user: &Address, designation: &Beneficiary) {
         env.storage()
             .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
+            .set(&DataKey::Beneficiary(user.clone()), designation);
+        extend_persistent_ttl(env, &DataKey::Beneficiary(user.clone()));
     }
 
-    /// Get lock expiry time for a user
-    pub fn get_lock_expiry(env: Env, user: Address) -> u64 {
-        let deposit_info: DepositInfo = env
-            .storage()
-            .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
-            .unwrap_or_else(|| panic!("No deposit found for user"));
-
-        deposit_info.unlock_time
+    fn remove_reward_stake(env: &Env, user: &Address) {
+        let rewards_contract: Option<Address> = env.storage().instance().get(&DataKey::Rewards);
+        if let Some(rewards_contract) = rewards_contract {
+            // Withdrawals always go through; a stale stake only over-weights future emissions
+            let removed = try_invoke_peer::<()>(
+                env,
+                &rewards_contract,
+                "remove_stake",
+                (env.current_contract_address(), user.clone()).into_val(env),
+            );
+            if removed.is_none() {
+                log!(env, "Reward stake removal failed for user {}", user);
+            }
+        }
     }
 
-    /// Calculate yield rate based on lock period (same as USDC vault)
-    pub fn calculate_yield_rate(env: Env, lock_period: LockPeriod) -> u128 {
-        let base_rate = 500u128; // 5% base annual rate in basis points
+    fn record_shortfall(env: &Env, user: &Address, shortfall_usd: u128) {
+        if shortfall_usd == 0 {
+            return;
+        }
 
-        match lock_period {
-            LockPeriod::ThreeMonths => base_rate,           // 5% APY
-            LockPeriod::SixMonths => (base_rate * 15) / 10, // 7.5% APY (1.5x)
-            LockPeriod::TwelveMonths => base_rate * 2,      // 10% APY (2x)
+        // Without a configured ledger the shortfall is only logged
+        if env.storage().instance().has(&DataKey::Governance) {
+            let governance = resolve_peer(
+                env,
+                &DataKey::Registry,
+                REGISTRY_GOVERNANCE,
+                &DataKey::Governance,
+            );
+            let reference: BytesN<32> = env.crypto().sha256(&user.clone().to_xdr(env)).into();
+            let record_id = try_invoke_peer::<u64>(
+                env,
+                &governance,
+                "record_bad_debt",
+                (
+                    env.current_contract_address(),
+                    BadDebtSource::GoldShortfall,
+                    reference,
+                    env.current_contract_address(),
+                    shortfall_usd,
+                )
+                    .into_val(env),
+            );
+
+            // Keep the withdrawal going and hold the amount until it can be recorded
+            if record_id.is_none() {
+                let unrecorded: u128 = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::UnrecordedShortfall)
+                    .unwrap_or(0);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::UnrecordedShortfall, &(unrecorded + shortfall_usd));
+                log!(
+                    env,
+                    "Bad debt ledger unavailable; shortfall held as unrecorded"
+                );
+            }
         }
-    }
 
-    /// Get current vault balance in USD terms
-    pub fn get_vault_balance(env: Env) -> u128 {
-        env.storage().instance().get(&VAULT_BALANCE).unwrap_or(0)
+        log!(
+            env,
+            "Gold shortfall of ${} on withdrawal for user {}",
+            shortfall_usd,
+            user
+        );
     }
 
-    /// Add supported gold asset (admin only)
-    pub fn add_supported_asset(env: Env, admin: Address, new_asset: Address) {
-        admin.require_auth();
-
-        let stored_admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if admin != stored_admin {
-            panic!("Only admin can add supported assets");
+    fn track_depositor(env: &Env, user: &Address) {
+        // Append-only index walked by exports; closed positions are skipped there
+        let slot_key = DataKey::DepositorSlot(user.clone());
+        if env.storage().persistent().has(&slot_key) {
+            return;
         }
 
-        let mut supported_assets: Vec<Address> = env
+        let depositor_count: u64 = env
             .storage()
             .instance()
-            .get(&SUPPORTED_ASSETS)
-            .unwrap_or(Vec::new(&env));
-
-        supported_assets.push_back(new_asset.clone());
+            .get(&DataKey::DepositorCount)
+            .unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Depositor(depositor_count), user);
+        env.storage().persistent().set(&slot_key, &depositor_count);
+        extend_persistent_ttl(env, &DataKey::Depositor(depositor_count));
+        extend_persistent_ttl(env, &slot_key);
         env.storage()
             .instance()
-            .set(&SUPPORTED_ASSETS, &supported_assets);
+            .set(&DataKey::DepositorCount, &(depositor_count + 1));
+    }
 
-        log!(&env, "Added supported gold asset: {}", new_asset);
+    fn position_record(env: &Env, user: &Address) -> Option<PositionRecord> {
+        let deposit_info = Self::get_deposit_info(env.clone(), user.clone())?;
+        let receipt = Self::get_active_receipt(env.clone(), user.clone());
+
+        // Positions predating receipts accrue at the current rate for their tier
+        let yield_rate = match &receipt {
+            Some(receipt) => receipt.terms.yield_rate,
+            None => Self::calculate_yield_rate(env.clone(), deposit_info.lock_period.clone()),
+        };
+        let elapsed = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(deposit_info.deposit_time);
+        let grown = compound_daily(deposit_info.amount, yield_rate, elapsed)
+            .unwrap_or_else(|| panic!("Projected yield overflow"));
+        let position_id = receipt.map(|receipt| receipt.id);
+
+        Some(PositionRecord {
+            vault: env.current_contract_address(),
+            vault_type: deposit_info.vault_type,
+            owner: user.clone(),
+            position_id,
+            principal: deposit_info.amount,
+            accrued_yield: grown - deposit_info.amount,
+            lock_period: deposit_info.lock_period,
+            deposit_time: deposit_info.deposit_time,
+            unlock_time: deposit_info.unlock_time,
+            encumbrance: position_id.and_then(|position_id| {
+                Self::get_encumbrance(env.clone(), user.clone(), position_id)
+            }),
+        })
     }
 
-    /// Internal helper functions
-    fn verify_supported_asset(env: &Env, asset: &Address) {
-        let supported_assets: Vec<Address> = env
+    fn issue_receipt(
+        env: &Env,
+        user: &Address,
+        gold_asset: &Address,
+        gold_amount: u128,
+        deposit_info: &DepositInfo,
+        yield_rate: u128,
+    ) {
+        let receipt_id: u64 = env
             .storage()
             .instance()
-            .get(&SUPPORTED_ASSETS)
-            .unwrap_or(Vec::new(env));
+            .get(&DataKey::NextReceiptId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextReceiptId, &(receipt_id + 1));
 
-        for supported_asset in supported_assets.iter() {
-            if supported_asset == *asset {
-                return;
-            }
-        }
+        let terms = DepositTerms {
+            vault: env.current_contract_address(),
+            depositor: user.clone(),
+            asset: gold_asset.clone(),
+            amount: gold_amount,
+            usd_value: deposit_info.amount,
+            lock_period: deposit_info.lock_period.clone(),
+            yield_rate,
+            deposit_time: deposit_info.deposit_time,
+            unlock_time: deposit_info.unlock_time,
+            // Gold positions are charged no vault fees
+            fee_policy_version: 0,
+            compounding: CompoundingFrequency::Daily,
+        };
+        let receipt = DepositReceipt {
+            id: receipt_id,
+            terms_hash: hash_deposit_terms(env, &terms),
+            terms,
+            nft_id: None,
+            closed_at: None,
+        };
 
-        panic!("Unsupported gold asset");
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt_id), &receipt);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ActiveReceipt(user.clone()), &receipt_id);
+        extend_persistent_ttl(env, &DataKey::Receipt(receipt_id));
+        extend_persistent_ttl(env, &DataKey::ActiveReceipt(user.clone()));
     }
 
-    fn determine_vault_type(env: &Env, gold_asset: &Address) -> VaultType {
-        if Self::is_paxg_asset(env, gold_asset) {
-            VaultType::PAXG
-        } else {
-            VaultType::WisdomTreeGold
-        }
-    }
+    fn close_receipt(env: &Env, user: &Address) -> Option<u64> {
+        // Deposits made before receipts were issued have none to close
+        let mut receipt = Self::get_active_receipt(env.clone(), user.clone())?;
+        receipt.closed_at = Some(env.ledger().timestamp());
 
-    fn is_paxg_asset(env: &Env, asset: &Address) -> bool {
-        // TODO: Replace with actual PAXG asset address comparison
-        // For now, assume first supported asset is PAXG
-        let supported_assets: Vec<Address> = env
-            .storage()
-            .instance()
-            .get(&SUPPORTED_ASSETS)
-            .unwrap_or(Vec::new(env));
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt.id), &receipt);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ActiveReceipt(user.clone()));
 
-        if let Some(first_asset) = supported_assets.first() {
-            asset == first_asset.clone()
-        } else {
-            false
+        receipt.nft_id
+    }
+
+    fn settle_receipt_token(env: &Env, nft_id: Option<u64>) {
+        if let Some(nft_id) = nft_id {
+            // The vault's own receipt is already closed; a stale token must not block withdrawals
+            let receipts_contract: Address = resolve_peer(
+                env,
+                &DataKey::Registry,
+                REGISTRY_RECEIPTS,
+                &DataKey::Receipts,
+            );
+            let settled = try_invoke_peer::<()>(
+                env,
+                &receipts_contract,
+                "settle",
+                (env.current_contract_address(), nft_id).into_val(env),
+            );
+            if settled.is_none() {
+                log!(env, "Receipt token {} settlement failed", nft_id);
+            }
         }
     }
 
+    fn migrate_v1_instance(env: &Env) {
+        migrate_instance_entry(env, &v1::ADMIN, &DataKey::Admin);
+        migrate_instance_entry(env, &v1::YIELD_TOKEN, &DataKey::YieldToken);
+        migrate_instance_entry(env, &v1::ORACLE, &DataKey::Oracle);
+        migrate_instance_entry(env, &v1::SUPPORTED_ASSETS, &DataKey::SupportedAssets);
+        migrate_instance_entry(env, &v1::REGISTRY, &DataKey::Registry);
+        migrate_instance_entry(env, &v1::REWARDS, &DataKey::Rewards);
+        migrate_instance_entry(env, &v1::GOVERNANCE, &DataKey::Governance);
+        migrate_instance_entry(env, &v1::VAULT_BALANCE, &DataKey::VaultBalance);
+    }
+
     fn calculate_unlock_time(current_time: u64, lock_period: &LockPeriod) -> u64 {
         match lock_period {
             LockPeriod::ThreeMonths => current_time + (90 * 24 * 60 * 60), // 90 days
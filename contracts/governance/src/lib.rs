@@ -1,21 +1,181 @@
 #![no_std]
 use shared::{
-    CommitteeMember, ExpertiseArea, GovernanceProposal, LoanProposal, ProposalStatus,
-    ProtocolParameter, TradeParams, REQUIRED_COMMITTEE_APPROVALS, TOTAL_COMMITTEE_SIZE,
+    anchor_ledger_clock, begin_migration, deadline_time, derive_proposal_id, digest_accounting,
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    finish_migration, invoke_peer, is_initialized, ledger_clock, lookup_registry, mark_initialized,
+    migrate_instance_entry, migrate_persistent_entry, mul_div, mul_div_up, parameter_bounds,
+    require_role, resolve_peer, role_holder, set_paused, set_role_holder, stored_schema_version,
+    transfer_or_abort, try_invoke_peer, verify_migration_admin, BadDebtRecord, BadDebtSource,
+    ChangelogEntry, CollateralInfo, CollateralStatus, CombinedCollateral, CommitteeDelegate,
+    CommitteeMember, CompensationSchedule, ContractError, ContractInfo, CreditHistory,
+    EarlyRepayment, EpochSummary, ExpertiseArea, FeeSwitch, FeeSwitchChange, GoldSeizure,
+    GovernanceProposal, Installment, KeeperBounty, LedgerClock, LimitOrder, LoanCurrency,
+    LoanFreeze, LoanPreview, LoanProposal, LoanTranche, MemberStats, PrepaymentPolicy,
+    ProfitReport, ProfitReportProposal, ProposalKind, ProposalStatus, ProtocolParameter, RateLock,
+    RepaymentStructure, RevenueStream, Role, SessionKey, StateDigest, StipendAccount,
+    SyndicateShare, TradeFill, TradeParams, TradeRecord, TradeSweep, TreasuryTarget, VaultLien,
+    VoteTally, VoteType, VotingRules, WriteOffFunding, BASIS_POINTS, CHANGELOG_PAGE_SIZE,
+    COLLATERAL_RATIO_BASIS_POINTS, CREDIT_SCORE_MAX, CREDIT_SCORE_NEUTRAL, DAYS_PER_YEAR,
+    DEFAULT_VOTING_PERIOD, EPOCH_DURATION, EPOCH_SUMMARY_PAGE_SIZE, EXECUTION_PRICE_PRECISION,
+    LOAN_INSTALLMENT_DAYS, MAX_DELEGATION_DURATION, MAX_LIQUIDATION_GRACE_PERIOD,
+    MAX_PASS_THRESHOLD_BPS, MAX_PREPAYMENT_FEE_BPS, MAX_PROPOSAL_PREREQUISITES,
+    MAX_PROPOSAL_REVIEW_PERIOD, MAX_QA_HOLDBACK_BPS, MAX_SESSION_KEY_LEDGERS, MAX_VOTING_PERIOD,
+    MEMBERSHIP_ACCEPTANCE_WINDOW, MIN_VOTING_PERIOD, PROTOCOL_FEE_BASIS_POINTS, RATE_LOCK_WINDOW,
+    REGISTRY_COFFEE_COLLATERAL, REGISTRY_ESCROW, REGISTRY_GOLD_VAULT, REGISTRY_GOVERNANCE,
+    REGISTRY_ORACLE, REGISTRY_TIMELOCK, REGISTRY_TRADE_ROUTER, REGISTRY_TREASURY,
+    REGISTRY_USDC_VAULT, REGISTRY_YIELD_TOKEN, REQUIRED_COMMITTEE_APPROVALS, SECONDS_PER_DAY,
+    SIMPLE_MAJORITY_BPS, TOTAL_COMMITTEE_SIZE, TRADE_SWEEP_PAGE_SIZE, USDC_PEG_PRICE,
 };
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::xdr::ToXdr;
 use soroban_sdk::{
-    contract, contractimpl, log, symbol_short, Address, BytesN, Env, IntoVal, Symbol, Vec,
+    contract, contractimpl, contracttype, log, panic_with_error, symbol_short, vec, Address,
+    BytesN, Env, IntoVal, Map, String, Symbol, Val, Vec,
 };
 
 // Storage Keys
-const COMMITTEE_MEMBERS: Symbol = symbol_short!("MEMBERS");
-const LOAN_PROPOSALS: Symbol = symbol_short!("LOANS");
-const TRADE_PROPOSALS: Symbol = symbol_short!("TRADES");
-const GOVERNANCE_PROPOSALS: Symbol = symbol_short!("GOV");
-const PROPOSAL_COUNTER: Symbol = symbol_short!("COUNTER");
-const ADMIN: Symbol = symbol_short!("ADMIN");
-const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
-const MIN_PROPOSAL_TOKENS: Symbol = symbol_short!("MIN_TOK");
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config, counters and totals
+    Admin,
+    YieldToken,
+    Registry,
+    TradeRouter,
+    CommitteeMembers,
+    MinProposalTokens,
+    ProposalCounter,
+    ProfitReportCounter,
+    BadDebtCounter,
+    BadDebtTotal,
+    BadDebtReporters,
+    SyndicateLenders,
+    InsuranceFund,
+    FeeWriteOff,
+    ShutdownApprovals,
+    SettledAt,
+    ActiveLoans,
+    OutstandingPrincipal,
+    EpochCounter,
+    EpochStartedAt,
+    EpochYield,
+    EpochProtocolFees,
+    EpochPenaltyMark,
+    EpochYieldPaidMark(Address),
+    PrepaymentPolicy,
+    PrepaymentIncome,
+    InterestRebated,
+    LoanCurrency(Address),
+    ReviewPeriod,
+    Quorum,
+    VotingRules(ProtocolParameter),
+    TradeSweepBounty,
+    BaseLoanRate,
+    LiquidationGracePeriod,
+    Treasury,
+    TreasuryAllocation,
+    CompensationSchedule,
+    Escrow,
+    QaHoldbackBps,
+    ParameterValue(ProtocolParameter),
+    ChangelogCount,
+    ReportedEpoch,
+    // Persistent: proposals and their approvals or votes
+    LoanProposal(BytesN<32>),
+    LoanApproval(BytesN<32>, Address),
+    LoanRejection(BytesN<32>, Address),
+    LoanRejections(BytesN<32>),
+    LoanDenomination(BytesN<32>),
+    RateLock(BytesN<32>),
+    TradeProposal(BytesN<32>),
+    TradeProposer(BytesN<32>),
+    OpenTrades,
+    TradeFill(BytesN<32>),
+    TradeRecord(BytesN<32>),
+    TradeLimit(BytesN<32>),
+    LimitApproval(BytesN<32>, Address),
+    GovernanceProposal(BytesN<32>),
+    PassThreshold(BytesN<32>),
+    TreasurySwap(BytesN<32>),
+    FeeSwitchChange(BytesN<32>),
+    ProposalPrerequisites(BytesN<32>),
+    Vote(BytesN<32>, Address),
+    // Persistent: the append-only changelog of executed proposals
+    ChangelogEntry(u64),
+    // Persistent: profit and bad-debt ledgers, with the approvals for each
+    ProfitReport(u64),
+    ProfitReportProposal(u64),
+    ProfitReportApproval(u64, Address),
+    BadDebt(u64),
+    WriteOffApproval(u64, Address),
+    LoanBadDebt(BytesN<32>),
+    // Persistent: loan due dates and borrower credit history
+    LoanDueAt(BytesN<32>),
+    LoanOutstanding(BytesN<32>),
+    RepaymentSchedule(BytesN<32>),
+    LoanStructure(BytesN<32>),
+    InterestPaid(BytesN<32>),
+    EarlyRepayment(BytesN<32>),
+    CreditHistory(Address),
+    // Persistent: single-loan freezes and the approvals to lift them
+    LoanFreeze(BytesN<32>),
+    UnfreezeApproval(BytesN<32>, Address),
+    // Persistent: vault positions pledged to loans
+    VaultLiens(BytesN<32>),
+    GoldCollateral(BytesN<32>),
+    GoldSeizure(BytesN<32>),
+    // Persistent: escrows loans were disbursed into
+    DisbursementEscrow(BytesN<32>),
+    QaHoldback(BytesN<32>),
+    LoanSyndicate(BytesN<32>),
+    // Persistent: milestone tranches and their per-tranche approvals
+    LoanTranches(BytesN<32>),
+    TrancheApproval(BytesN<32>, u32, Address),
+    // Persistent: committee appointments awaiting acceptance
+    PendingMembership(Address),
+    // Persistent: committee delegations, both ways
+    CommitteeDelegate(Address),
+    DelegateOf(Address),
+    // Persistent: committee session keys, both ways
+    SessionKey(Address),
+    SessionKeyOf(Address),
+    // Persistent: per-member committee activity and stipends
+    MemberStats(Address),
+    StipendAccount(Address),
+    // Persistent: epoch summaries and state digests
+    EpochSummary(u64),
+    EpochDigests(u64),
+}
+
+// Schema v1 storage keys, only read when migrating
+mod v1 {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub const COMMITTEE_MEMBERS: Symbol = symbol_short!("MEMBERS");
+    pub const LOAN_PROPOSALS: Symbol = symbol_short!("LOANS");
+    pub const TRADE_PROPOSALS: Symbol = symbol_short!("TRADES");
+    pub const GOVERNANCE_PROPOSALS: Symbol = symbol_short!("GOV");
+    pub const PROPOSAL_COUNTER: Symbol = symbol_short!("COUNTER");
+    pub const ADMIN: Symbol = symbol_short!("ADMIN");
+    pub const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
+    pub const MIN_PROPOSAL_TOKENS: Symbol = symbol_short!("MIN_TOK");
+    pub const REGISTRY: Symbol = symbol_short!("REGISTRY");
+    pub const PROFIT_REPORTS: Symbol = symbol_short!("PROFITS");
+    pub const PROFIT_REPORT_COUNTER: Symbol = symbol_short!("PROFITCNT");
+    pub const BAD_DEBT: Symbol = symbol_short!("BADDEBT");
+    pub const BAD_DEBT_COUNTER: Symbol = symbol_short!("BADCNT");
+    pub const BAD_DEBT_TOTAL: Symbol = symbol_short!("BADTOTAL");
+    pub const BAD_DEBT_REPORTERS: Symbol = symbol_short!("BADRPTS");
+    pub const INSURANCE_FUND: Symbol = symbol_short!("INSURE");
+    pub const FEE_WRITE_OFF: Symbol = symbol_short!("FEEWO");
+    pub const LOAN_APPROVAL: &str = "approval";
+    pub const WRITE_OFF_APPROVAL: &str = "wo_approval";
+    pub const VOTE: &str = "vote";
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 2;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[contract]
 pub struct Governance;
@@ -30,6 +190,8 @@ impl Governance {
         initial_committee: Vec<CommitteeMember>,
         min_proposal_tokens: u128,
     ) {
+        ensure_uninitialized(&env, Some(v1::ADMIN));
+
         admin.require_auth();
 
         if initial_committee.len() != TOTAL_COMMITTEE_SIZE {
@@ -39,17 +201,25 @@ impl Governance {
             );
         }
 
-        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldToken, &yield_token_contract);
         env.storage()
             .instance()
-            .set(&YIELD_TOKEN, &yield_token_contract);
+            .set(&DataKey::CommitteeMembers, &initial_committee);
         env.storage()
             .instance()
-            .set(&COMMITTEE_MEMBERS, &initial_committee);
+            .set(&DataKey::MinProposalTokens, &min_proposal_tokens);
         env.storage()
             .instance()
-            .set(&MIN_PROPOSAL_TOKENS, &min_proposal_tokens);
-        env.storage().instance().set(&PROPOSAL_COUNTER, &0u64);
+            .set(&DataKey::ProposalCounter, &0u64);
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochStartedAt, &env.ledger().timestamp());
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
 
         log!(
             &env,
@@ -58,6 +228,156 @@ impl Governance {
         );
     }
 
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_GOVERNANCE),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Migrate storage to the schema version of the current WASM (admin only)
+    ///
+    /// Moves config and totals; proposals and ledgers are moved with `migrate_proposals` and `migrate_ledgers`.
+    ///
+    /// Storage from before versioning also needs the `deployer` contract that
+    /// created this one, whose record of the admin is trusted over the stored one.
+    pub fn migrate(env: Env, admin: Address, deployer: Option<Address>) {
+        admin.require_auth();
+
+        // Until the v2 migration has run the admin still sits under its v1 key
+        let stored_admin: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .or_else(|| env.storage().instance().get(&v1::ADMIN));
+        let stored_version = begin_migration(&env, STORAGE_VERSION);
+        verify_migration_admin(&env, stored_version, &admin, stored_admin, deployer);
+
+        match stored_version {
+            0 | 1 => Self::migrate_v1_instance(&env),
+            _ => panic_with_error!(&env, ContractError::InvalidMigration),
+        }
+        finish_migration(&env, stored_version, STORAGE_VERSION);
+    }
+
+    /// Move schema v1 proposals with their approvals and votes to `DataKey` entries (admin only)
+    ///
+    /// Proposal IDs are hashes, so open proposals and the addresses that voted
+    /// on them are supplied in batches from indexed events once `migrate` has run.
+    pub fn migrate_proposals(
+        env: Env,
+        admin: Address,
+        proposal_ids: Vec<BytesN<32>>,
+        voters: Vec<Address>,
+    ) -> u32 {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let committee = Self::get_committee_members(env.clone());
+        let approval = Symbol::new(&env, v1::LOAN_APPROVAL);
+        let vote = Symbol::new(&env, v1::VOTE);
+        let mut migrated = 0u32;
+        for proposal_id in proposal_ids.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::LOAN_PROPOSALS, proposal_id.clone()),
+                &DataKey::LoanProposal(proposal_id.clone()),
+            ) {
+                for member in committee.iter() {
+                    migrate_persistent_entry(
+                        &env,
+                        &(
+                            approval.clone(),
+                            proposal_id.clone(),
+                            member.address.clone(),
+                        ),
+                        &DataKey::LoanApproval(proposal_id.clone(), member.address.clone()),
+                    );
+                }
+                migrated += 1;
+            }
+
+            if migrate_persistent_entry(
+                &env,
+                &(v1::TRADE_PROPOSALS, proposal_id.clone()),
+                &DataKey::TradeProposal(proposal_id.clone()),
+            ) {
+                migrated += 1;
+            }
+
+            if migrate_persistent_entry(
+                &env,
+                &(v1::GOVERNANCE_PROPOSALS, proposal_id.clone()),
+                &DataKey::GovernanceProposal(proposal_id.clone()),
+            ) {
+                for voter in voters.iter() {
+                    migrate_persistent_entry(
+                        &env,
+                        &(vote.clone(), proposal_id.clone(), voter.clone()),
+                        &DataKey::Vote(proposal_id.clone(), voter.clone()),
+                    );
+                }
+                migrated += 1;
+            }
+        }
+
+        log!(&env, "Migrated {} proposals", migrated);
+
+        migrated
+    }
+
+    /// Move schema v1 profit reports and bad-debt records in an ID range to `DataKey` entries (admin only)
+    pub fn migrate_ledgers(env: Env, admin: Address, from_id: u64, to_id: u64) -> u32 {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let committee = Self::get_committee_members(env.clone());
+        let write_off_approval = Symbol::new(&env, v1::WRITE_OFF_APPROVAL);
+        let mut migrated = 0u32;
+        for id in from_id..=to_id {
+            if migrate_persistent_entry(&env, &(v1::PROFIT_REPORTS, id), &DataKey::ProfitReport(id))
+            {
+                migrated += 1;
+            }
+
+            if migrate_persistent_entry(&env, &(v1::BAD_DEBT, id), &DataKey::BadDebt(id)) {
+                for member in committee.iter() {
+                    migrate_persistent_entry(
+                        &env,
+                        &(write_off_approval.clone(), id, member.address.clone()),
+                        &DataKey::WriteOffApproval(id, member.address.clone()),
+                    );
+                }
+                migrated += 1;
+            }
+        }
+
+        log!(&env, "Migrated {} ledger records", migrated);
+
+        migrated
+    }
+
     /// Submit a loan proposal (committee members only)
     pub fn submit_loan_proposal(
         env: Env,
@@ -73,52 +393,401 @@ impl Governance {
         // Verify proposer is committee member
         Self::verify_committee_member(&env, &proposer);
 
-        // Generate proposal ID using a simpler approach
-        let mut proposal_bytes = soroban_sdk::Bytes::new(&env);
-        proposal_bytes.extend_from_array(&borrower.to_xdr(&env));
-        proposal_bytes.extend_from_array(&loan_amount.to_be_bytes());
-        proposal_bytes.extend_from_array(&env.ledger().timestamp().to_be_bytes());
-        let proposal_id = env.crypto().sha256(&proposal_bytes).into();
+        Self::open_loan_proposal(
+            &env,
+            &proposer,
+            borrower,
+            loan_amount,
+            None,
+            collateral_asset,
+            interest_rate,
+            duration_days,
+            RepaymentStructure::Amortizing,
+            0,
+        )
+    }
 
-        let proposal = LoanProposal {
-            id: proposal_id,
+    /// Submit a USDC loan proposal repaid under the given structure (committee members only)
+    ///
+    /// A `Balloon` loan pays only interest each installment and its whole principal
+    /// at maturity; `submit_loan_proposal` always amortizes.
+    pub fn submit_structured_loan_proposal(
+        env: Env,
+        proposer: Address,
+        borrower: Address,
+        loan_amount: u128,
+        collateral_asset: Address,
+        interest_rate: u128,
+        duration_days: u64,
+        structure: RepaymentStructure,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        Self::open_loan_proposal(
+            &env,
+            &proposer,
             borrower,
-            amount: loan_amount,
-            collateral: collateral_asset,
+            loan_amount,
+            None,
+            collateral_asset,
             interest_rate,
-            duration: duration_days,
-            approvals: 0,
-            status: ProposalStatus::Pending,
-            created_at: env.ledger().timestamp(),
-        };
+            duration_days,
+            structure,
+            0,
+        )
+    }
+
+    /// Get how a loan's principal is repaid; loans amortize unless proposed otherwise
+    pub fn get_loan_structure(env: Env, loan_id: BytesN<32>) -> RepaymentStructure {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanStructure(loan_id))
+            .unwrap_or(RepaymentStructure::Amortizing)
+    }
+
+    /// Get the interest recorded as paid on a balloon loan's interest-only installments
+    pub fn get_interest_paid(env: Env, loan_id: BytesN<32>) -> u128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::InterestPaid(loan_id))
+            .unwrap_or(0)
+    }
+
+    /// Submit a USDC loan backed by a coffee lot and a gold-vault position together (committee members only)
+    ///
+    /// The position owner must also authorize the pledge. The lot and the position's
+    /// live value must jointly cover the loan at the collateral ratio. The gold lien is
+    /// released with `release_vault_liens`, or the position is taken by
+    /// `seize_gold_collateral` if liquidating the lot leaves a shortfall.
+    pub fn submit_dual_collateral_loan_proposal(
+        env: Env,
+        proposer: Address,
+        borrower: Address,
+        loan_amount: u128,
+        collateral_asset: Address,
+        interest_rate: u128,
+        duration_days: u64,
+        gold_owner: Address,
+        gold_position_id: u64,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        let gold_vault = lookup_registry(&env, &DataKey::Registry, REGISTRY_GOLD_VAULT)
+            .unwrap_or_else(|| panic!("Gold vault not registered"));
+        let gold_value_usd: u128 = invoke_peer(
+            &env,
+            &gold_vault,
+            "get_position_value",
+            (gold_owner.clone(),).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        if gold_value_usd == 0 {
+            panic!("Gold position has no value");
+        }
+
+        let loan_id = Self::open_loan_proposal(
+            &env,
+            &proposer,
+            borrower,
+            loan_amount,
+            None,
+            collateral_asset,
+            interest_rate,
+            duration_days,
+            RepaymentStructure::Amortizing,
+            gold_value_usd,
+        );
 
+        invoke_peer::<()>(
+            &env,
+            &gold_vault,
+            "encumber_position",
+            (
+                env.current_contract_address(),
+                gold_owner.clone(),
+                gold_position_id,
+                loan_id.clone(),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        let lien = VaultLien {
+            vault: gold_vault,
+            owner: gold_owner,
+            position_id: gold_position_id,
+        };
         env.storage()
             .persistent()
-            .set(&(LOAN_PROPOSALS.clone(), proposal_id), &proposal);
+            .set(&DataKey::GoldCollateral(loan_id.clone()), &lien);
+        extend_persistent_ttl(&env, &DataKey::GoldCollateral(loan_id.clone()));
 
         log!(
             &env,
-            "Loan proposal {} submitted by {} for borrower {} amount: ${}",
-            proposal_id,
-            proposer,
-            proposal.borrower,
-            loan_amount
+            "Gold position {} of {} worth ${} pledged alongside the lot of loan {}",
+            lien.position_id,
+            lien.owner,
+            gold_value_usd,
+            loan_id
         );
 
-        proposal_id
+        loan_id
+    }
+
+    /// Submit a loan proposal denominated in a registered non-USDC currency (committee members only)
+    ///
+    /// `loan_amount` is in units of `currency`; collateral is checked against its
+    /// oracle USD value.
+    pub fn submit_loan_proposal_in(
+        env: Env,
+        proposer: Address,
+        borrower: Address,
+        loan_amount: u128,
+        currency: Address,
+        collateral_asset: Address,
+        interest_rate: u128,
+        duration_days: u64,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        if Self::get_loan_currency(env.clone(), currency.clone()).is_none() {
+            panic!("Loan currency is not supported");
+        }
+
+        Self::open_loan_proposal(
+            &env,
+            &proposer,
+            borrower,
+            loan_amount,
+            Some(currency),
+            collateral_asset,
+            interest_rate,
+            duration_days,
+            RepaymentStructure::Amortizing,
+            0,
+        )
+    }
+
+    /// Register a currency loans can be denominated in (admin only)
+    ///
+    /// Loans in `asset` are lent from `source_vault` and valued through the oracle's `price_feed`.
+    pub fn set_loan_currency(
+        env: Env,
+        admin: Address,
+        asset: Address,
+        source_vault: Address,
+        price_feed: Symbol,
+    ) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set loan currencies");
+        }
+
+        let currency = LoanCurrency {
+            asset: asset.clone(),
+            source_vault,
+            price_feed,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::LoanCurrency(asset.clone()), &currency);
+
+        log!(
+            &env,
+            "Loan currency {} set with source vault {}",
+            asset,
+            currency.source_vault
+        );
+    }
+
+    /// Get a registered loan currency
+    pub fn get_loan_currency(env: Env, asset: Address) -> Option<LoanCurrency> {
+        env.storage().instance().get(&DataKey::LoanCurrency(asset))
+    }
+
+    /// Get the currency a loan is denominated in, None for USDC
+    pub fn get_loan_denomination(env: Env, loan_id: BytesN<32>) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanDenomination(loan_id))
+    }
+
+    /// Get the oracle USD value of a loan's principal
+    pub fn get_loan_usd_value(env: Env, loan_id: BytesN<32>) -> u128 {
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        Self::loan_usd_value(&env, &loan_id, proposal.amount)
+    }
+
+    /// Set the base loan rate in basis points that proposals are priced against (admin only)
+    ///
+    /// Proposals lock the base rate in effect when they are submitted; changes
+    /// only reach loans executed after their rate lock has expired.
+    pub fn set_base_loan_rate(env: Env, admin: Address, rate: u128) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the base loan rate");
+        }
+
+        if rate > BASIS_POINTS {
+            panic!("Base loan rate exceeds maximum");
+        }
+
+        env.storage().instance().set(&DataKey::BaseLoanRate, &rate);
+
+        log!(&env, "Base loan rate set to {} bps", rate);
+    }
+
+    /// Get the base loan rate in basis points, 0 unless one is set
+    pub fn get_base_loan_rate(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::BaseLoanRate)
+            .unwrap_or(0)
+    }
+
+    /// Set how long an installment may be overdue before the collateral can be liquidated (admin only)
+    pub fn set_liquidation_grace_period(env: Env, admin: Address, grace_period: u64) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the liquidation grace period");
+        }
+
+        if grace_period > MAX_LIQUIDATION_GRACE_PERIOD {
+            panic!("Liquidation grace period exceeds maximum");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidationGracePeriod, &grace_period);
+
+        log!(&env, "Liquidation grace period set to {}s", grace_period);
+    }
+
+    /// Get the liquidation grace period in seconds, 0 unless one is set
+    pub fn get_liquidation_grace_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LiquidationGracePeriod)
+            .unwrap_or(0)
+    }
+
+    /// Cross-check voting deadlines and vault lock expiries against the ledger sequence (admin only)
+    ///
+    /// Enabling anchors the sequence clock at the current ledger here and in every
+    /// registered vault, so deadlines only pass once both the timestamp and the
+    /// ledgers closed since agree they have. Disabling returns to timestamps alone.
+    pub fn set_ledger_clock(env: Env, admin: Address, enabled: bool) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the ledger clock");
+        }
+
+        anchor_ledger_clock(&env, enabled);
+
+        // Vaults are only known through the registry; one not deployed keeps its own setting
+        let governance = env.current_contract_address();
+        for name in [REGISTRY_USDC_VAULT, REGISTRY_GOLD_VAULT] {
+            if let Some(vault) = lookup_registry(&env, &DataKey::Registry, name) {
+                invoke_peer::<()>(
+                    &env,
+                    &vault,
+                    "set_ledger_clock",
+                    (governance.clone(), enabled).into_val(&env),
+                    ContractError::PeerCallFailed,
+                );
+            }
+        }
+
+        log!(&env, "Ledger-sequence deadline checks set to {}", enabled);
+    }
+
+    /// Get the ledger clock voting deadlines are cross-checked against, if any
+    pub fn get_ledger_clock(env: Env) -> Option<LedgerClock> {
+        ledger_clock(&env)
+    }
+
+    /// Get the rate locked for a loan proposal when it was submitted
+    pub fn get_rate_lock(env: Env, proposal_id: BytesN<32>) -> Option<RateLock> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RateLock(proposal_id))
+    }
+
+    /// Preview the repayment schedule, interest and collateral of a USDC loan executed now
+    ///
+    /// Fails where `submit_structured_loan_proposal` would for the same terms, so
+    /// wallets can show the preview from a simulated call.
+    pub fn simulate_loan(
+        env: Env,
+        amount: u128,
+        interest_rate: u128,
+        duration_days: u64,
+        structure: RepaymentStructure,
+    ) -> LoanPreview {
+        Self::ensure_not_settled(&env);
+        if interest_rate < Self::get_base_loan_rate(env.clone()) {
+            panic!("Interest rate is below the base loan rate");
+        }
+
+        let now = env.ledger().timestamp();
+        let schedule = Self::build_repayment_schedule(
+            &env,
+            amount,
+            interest_rate,
+            duration_days,
+            &structure,
+            now,
+        );
+        let total_interest: u128 = schedule
+            .iter()
+            .map(|installment| installment.interest)
+            .sum();
+        let protocol_fee = mul_div(total_interest, PROTOCOL_FEE_BASIS_POINTS, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Protocol fee overflow"));
+        let required_collateral = mul_div_up(amount, COLLATERAL_RATIO_BASIS_POINTS, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Required collateral overflow"));
+
+        LoanPreview {
+            amount,
+            total_interest,
+            protocol_fee,
+            required_collateral,
+            due_at: now + duration_days * SECONDS_PER_DAY,
+            schedule,
+        }
     }
 
     /// Approve a loan proposal (committee members only)
     pub fn approve_loan(env: Env, proposal_id: BytesN<32>, approver: Address) {
         approver.require_auth();
 
-        // Verify approver is committee member
-        Self::verify_committee_member(&env, &approver);
+        // An active delegate's or session key's approval counts as the member's
+        let member = Self::loan_voting_member(&env, &approver);
 
         let mut proposal: LoanProposal = env
             .storage()
             .persistent()
-            .get(&(LOAN_PROPOSALS.clone(), proposal_id))
+            .get(&DataKey::LoanProposal(proposal_id.clone()))
             .unwrap_or_else(|| panic!("Loan proposal not found"));
 
         if proposal.status != ProposalStatus::Pending {
@@ -126,15 +795,27 @@ impl Governance {
         }
 
         // Check if already approved by this member
-        let approval_key = (Symbol::new(&env, "approval"), proposal_id, approver.clone());
+        let approval_key = DataKey::LoanApproval(proposal_id.clone(), member.clone());
         if env.storage().persistent().has(&approval_key) {
             panic!("Member has already approved this proposal");
         }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::LoanRejection(proposal_id.clone(), member.clone()))
+        {
+            panic!("Member has already rejected this proposal");
+        }
 
         // Record approval
         env.storage().persistent().set(&approval_key, &true);
         proposal.approvals += 1;
 
+        let mut stats = Self::load_member_stats(&env, &member);
+        stats.approvals += 1;
+        stats.total_response_time += env.ledger().timestamp() - proposal.created_at;
+        Self::store_member_stats(&env, &member, stats);
+
         // Check if enough approvals
         if proposal.approvals >= REQUIRED_COMMITTEE_APPROVALS {
             proposal.status = ProposalStatus::Approved;
@@ -142,7 +823,7 @@ impl Governance {
 
         env.storage()
             .persistent()
-            .set(&(LOAN_PROPOSALS.clone(), proposal_id), &proposal);
+            .set(&DataKey::LoanProposal(proposal_id.clone()), &proposal);
 
         log!(
             &env,
@@ -154,340 +835,4937 @@ impl Governance {
         );
     }
 
-    /// Execute an approved loan
-    pub fn execute_loan(env: Env, executor: Address, proposal_id: BytesN<32>) {
-        executor.require_auth();
+    /// Reject a pending loan proposal (committee members only)
+    ///
+    /// The proposal is marked rejected once enough members reject it that the
+    /// required approvals can no longer be reached.
+    pub fn reject_loan(env: Env, proposal_id: BytesN<32>, rejecter: Address) {
+        rejecter.require_auth();
 
-        // Verify executor is committee member
-        Self::verify_committee_member(&env, &executor);
+        // An active delegate's or session key's rejection counts as the member's
+        let member = Self::loan_voting_member(&env, &rejecter);
 
         let mut proposal: LoanProposal = env
             .storage()
             .persistent()
-            .get(&(LOAN_PROPOSALS.clone(), proposal_id))
+            .get(&DataKey::LoanProposal(proposal_id.clone()))
             .unwrap_or_else(|| panic!("Loan proposal not found"));
 
-        if proposal.status != ProposalStatus::Approved {
-            panic!("Proposal must be approved before execution");
+        if proposal.status != ProposalStatus::Pending {
+            panic!("Proposal is not in pending status");
         }
 
-        // TODO: Implement actual loan execution logic
-        // This would involve:
-        // 1. Verifying collateral with coffee collateral contract
-        // 2. Transferring funds from vault to borrower
-        // 3. Recording loan terms and repayment schedule
+        let rejection_key = DataKey::LoanRejection(proposal_id.clone(), member.clone());
+        if env.storage().persistent().has(&rejection_key) {
+            panic!("Member has already rejected this proposal");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::LoanApproval(proposal_id.clone(), member.clone()))
+        {
+            panic!("Member has already approved this proposal");
+        }
 
-        proposal.status = ProposalStatus::Executed;
+        env.storage().persistent().set(&rejection_key, &true);
+        let rejections: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanRejections(proposal_id.clone()))
+            .unwrap_or(0)
+            + 1;
         env.storage()
             .persistent()
-            .set(&(LOAN_PROPOSALS.clone(), proposal_id), &proposal);
+            .set(&DataKey::LoanRejections(proposal_id.clone()), &rejections);
+
+        let mut stats = Self::load_member_stats(&env, &member);
+        stats.rejections += 1;
+        stats.total_response_time += env.ledger().timestamp() - proposal.created_at;
+        Self::store_member_stats(&env, &member, stats);
+
+        if rejections > TOTAL_COMMITTEE_SIZE - REQUIRED_COMMITTEE_APPROVALS {
+            proposal.status = ProposalStatus::Rejected;
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanProposal(proposal_id.clone()), &proposal);
+        }
 
         log!(
             &env,
-            "Loan proposal {} executed by {}. Amount: ${} to borrower: {}",
+            "Loan proposal {} rejected by {}. Rejections: {}",
             proposal_id,
-            executor,
-            proposal.amount,
-            proposal.borrower
+            rejecter,
+            rejections
         );
     }
 
-    /// Submit a trade proposal (committee members only)
-    pub fn submit_trade_proposal(
+    /// Get a committee member's proposal and approval activity
+    pub fn get_member_stats(env: Env, member: Address) -> MemberStats {
+        let mut stats = Self::load_member_stats(&env, &member);
+        let responses = (stats.approvals + stats.rejections) as u64;
+        if responses > 0 {
+            stats.average_response_time = stats.total_response_time / responses;
+        }
+        stats
+    }
+
+    /// Set the committee stipend, paid in yield tokens from the treasury per period (admin only)
+    ///
+    /// Periods count from now; periods left unclaimed under a previous schedule are forfeited.
+    pub fn set_compensation_schedule(
         env: Env,
-        proposer: Address,
-        trade_params: TradeParams,
-    ) -> BytesN<32> {
-        proposer.require_auth();
+        admin: Address,
+        stipend: u128,
+        period: u64,
+        min_activity: u32,
+        max_average_response_time: u64,
+    ) {
+        admin.require_auth();
 
-        // Verify proposer is committee member
-        Self::verify_committee_member(&env, &proposer);
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the compensation schedule");
+        }
+        if period == 0 {
+            panic!("Stipend period must be greater than 0");
+        }
 
-        // Generate trade ID
-        let trade_id = env
-            .crypto()
-            .sha256(
-                &(
-                    trade_params.asset_in.clone(),
-                    trade_params.amount_in,
-                    env.ledger().timestamp(),
-                )
-                    .into_val(&env),
-            )
-            .into();
+        let schedule = CompensationSchedule {
+            stipend,
+            period,
+            min_activity,
+            max_average_response_time,
+            started_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::CompensationSchedule, &schedule);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Committee stipend set to {} yield tokens every {} seconds for {} actions",
+            stipend,
+            period,
+            min_activity
+        );
+    }
+
+    /// Get the committee stipend schedule, if one is set
+    pub fn get_compensation_schedule(env: Env) -> Option<CompensationSchedule> {
+        env.storage().instance().get(&DataKey::CompensationSchedule)
+    }
+
+    /// Get a member's stipend claims so far
+    pub fn get_stipend_account(env: Env, member: Address) -> StipendAccount {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StipendAccount(member))
+            .unwrap_or(StipendAccount {
+                claimed_through: 0,
+                activity_mark: 0,
+                total_claimed: 0,
+            })
+    }
+
+    /// Claim the stipend for every period ended since the last claim (committee members only)
+    ///
+    /// A period is paid only if the member's proposals, approvals and rejections
+    /// since the last claim cover `min_activity` for it; the rest are forfeited.
+    /// The treasury must authorize the yield token transfer. Returns the stipend paid.
+    pub fn claim_stipend(env: Env, member: Address) -> u128 {
+        member.require_auth();
+        Self::verify_committee_member(&env, &member);
+
+        let schedule = Self::get_compensation_schedule(env.clone())
+            .unwrap_or_else(|| panic!("No compensation schedule set"));
+        let stats = Self::get_member_stats(env.clone(), member.clone());
+        if schedule.max_average_response_time > 0
+            && stats.average_response_time > schedule.max_average_response_time
+        {
+            panic!("Average response time is above the stipend threshold");
+        }
 
+        let mut account = Self::get_stipend_account(env.clone(), member.clone());
+        let from = account.claimed_through.max(schedule.started_at);
+        let periods = (env.ledger().timestamp() - from) / schedule.period;
+        if periods == 0 {
+            panic!("No stipend period has ended since the last claim");
+        }
+
+        let activity = stats.proposals_submitted + stats.approvals + stats.rejections;
+        let paid_periods = match schedule.min_activity {
+            0 => periods,
+            min_activity => periods.min(((activity - account.activity_mark) / min_activity) as u64),
+        };
+        let stipend = schedule.stipend * paid_periods as u128;
+
+        account.claimed_through = from + periods * schedule.period;
+        account.activity_mark = activity;
+        account.total_claimed += stipend;
         env.storage()
             .persistent()
-            .set(&(TRADE_PROPOSALS.clone(), trade_id), &trade_params);
+            .set(&DataKey::StipendAccount(member.clone()), &account);
+        extend_persistent_ttl(&env, &DataKey::StipendAccount(member.clone()));
+
+        if stipend > 0 {
+            let treasury = lookup_registry(&env, &DataKey::Registry, REGISTRY_TREASURY)
+                .or_else(|| env.storage().instance().get(&DataKey::Treasury))
+                .unwrap_or_else(|| panic!("Treasury not configured"));
+            let yield_token_contract = resolve_peer(
+                &env,
+                &DataKey::Registry,
+                REGISTRY_YIELD_TOKEN,
+                &DataKey::YieldToken,
+            );
+            transfer_or_abort(
+                &env,
+                &yield_token_contract,
+                &treasury,
+                &member,
+                stipend as i128,
+            );
+        }
 
         log!(
             &env,
-            "Trade proposal {} submitted: {} {} for {} {}",
-            trade_id,
-            trade_params.amount_in,
-            trade_params.asset_in,
-            trade_params.min_amount_out,
-            trade_params.asset_out
+            "Member {} claimed {} yield tokens for {} of {} stipend periods",
+            member,
+            stipend,
+            paid_periods,
+            periods
         );
 
-        trade_id
+        stipend
     }
 
-    /// Execute a trade (committee members only)
-    pub fn execute_trade(env: Env, executor: Address, trade_id: BytesN<32>) {
+    /// Execute an approved loan
+    pub fn execute_loan(env: Env, executor: Address, proposal_id: BytesN<32>) {
         executor.require_auth();
+        ensure_not_paused(&env);
 
         // Verify executor is committee member
         Self::verify_committee_member(&env, &executor);
 
-        let trade_params: TradeParams = env
+        let mut proposal: LoanProposal = env
             .storage()
             .persistent()
-            .get(&(TRADE_PROPOSALS.clone(), trade_id))
-            .unwrap_or_else(|| panic!("Trade proposal not found"));
+            .get(&DataKey::LoanProposal(proposal_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
 
-        // Check deadline
-        if env.ledger().timestamp() > trade_params.deadline {
-            panic!("Trade proposal has expired");
+        if proposal.status != ProposalStatus::Approved {
+            panic!("Proposal must be approved before execution");
+        }
+        Self::ensure_not_settled(&env);
+
+        // Loans in other currencies are valued at today's oracle price before they go out
+        if let Some(currency) = Self::get_loan_denomination(env.clone(), proposal_id.clone()) {
+            let loan_value_usd = Self::loan_usd_value(&env, &proposal_id, proposal.amount);
+            let gold_value_usd = Self::gold_collateral_value(&env, &proposal_id);
+            Self::verify_collateral_coverage(
+                &env,
+                &proposal.collateral,
+                gold_value_usd,
+                loan_value_usd,
+            );
+            log!(
+                &env,
+                "Loan proposal {} is denominated in {} worth ${}",
+                proposal_id,
+                currency,
+                loan_value_usd
+            );
+        }
+
+        // Past its rate lock the loan keeps its spread over the base rate in effect today
+        if let Some(rate_lock) = Self::get_rate_lock(env.clone(), proposal_id.clone()) {
+            if env.ledger().timestamp() > rate_lock.expires_at {
+                let spread = rate_lock.locked_rate.saturating_sub(rate_lock.base_rate);
+                proposal.interest_rate = Self::get_base_loan_rate(env.clone()) + spread;
+                log!(
+                    &env,
+                    "Rate lock on loan proposal {} expired; repriced to {} bps",
+                    proposal_id,
+                    proposal.interest_rate
+                );
+            }
         }
 
-        // TODO: Implement actual trade execution logic
+        // TODO: Implement actual loan execution logic
         // This would involve:
-        // 1. Calling external DEX or trading platform
-        // 2. Verifying slippage constraints
-        // 3. Recording profit/loss for yield distribution
+        // 1. Verifying collateral with coffee collateral contract
+        // 2. Transferring funds from vault to borrower
+        // 3. Recording loan terms and repayment schedule
+
+        proposal.status = ProposalStatus::Executed;
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanProposal(proposal_id.clone()), &proposal);
 
-        // Remove executed trade
+        // Repayments after this time count as late in the borrower's credit history
+        let due_at = env.ledger().timestamp() + proposal.duration * SECONDS_PER_DAY;
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanDueAt(proposal_id.clone()), &due_at);
+        extend_persistent_ttl(&env, &DataKey::LoanDueAt(proposal_id.clone()));
+        let schedule = Self::build_repayment_schedule(
+            &env,
+            proposal.amount,
+            proposal.interest_rate,
+            proposal.duration,
+            &Self::get_loan_structure(env.clone(), proposal_id.clone()),
+            env.ledger().timestamp(),
+        );
         env.storage()
             .persistent()
-            .remove(&(TRADE_PROPOSALS.clone(), trade_id));
+            .set(&DataKey::RepaymentSchedule(proposal_id.clone()), &schedule);
+        extend_persistent_ttl(&env, &DataKey::RepaymentSchedule(proposal_id.clone()));
+
+        // The loan stays active until its principal is repaid or liquidated
+        env.storage().persistent().set(
+            &DataKey::LoanOutstanding(proposal_id.clone()),
+            &proposal.amount,
+        );
+        extend_persistent_ttl(&env, &DataKey::LoanOutstanding(proposal_id.clone()));
+        let active_loans = Self::get_active_loans(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::ActiveLoans, &(active_loans + 1));
+        let outstanding_principal = Self::get_outstanding_principal(env.clone());
+        env.storage().instance().set(
+            &DataKey::OutstandingPrincipal,
+            &(outstanding_principal + proposal.amount),
+        );
 
-        log!(&env, "Trade {} executed by {}", trade_id, executor);
+        let mut history = Self::get_credit_history(env.clone(), proposal.borrower.clone());
+        history.loans_executed += 1;
+        Self::store_credit_history(&env, &proposal.borrower, history);
+
+        log!(
+            &env,
+            "Loan proposal {} executed by {}. Amount: ${} to borrower: {}",
+            proposal_id,
+            executor,
+            proposal.amount,
+            proposal.borrower
+        );
     }
 
-    /// DAO Governance: Propose parameter change
-    pub fn propose_parameter_change(
+    /// Record a borrower repayment against an executed loan (committee members only)
+    ///
+    /// On a balloon loan the repayment settles scheduled interest in due order before
+    /// any principal. Returns whether the repayment was made by the loan's due date.
+    pub fn record_repayment(
         env: Env,
-        proposer: Address,
-        parameter: ProtocolParameter,
-        new_value: u128,
-    ) -> BytesN<32> {
-        proposer.require_auth();
+        reporter: Address,
+        loan_id: BytesN<32>,
+        amount: u128,
+    ) -> bool {
+        reporter.require_auth();
 
-        // Check minimum token requirement
-        let min_tokens: u128 = env.storage().instance().get(&MIN_PROPOSAL_TOKENS).unwrap();
-        let proposer_balance = Self::get_voting_power(&env, &proposer);
+        // Verify reporter is committee member
+        Self::verify_committee_member(&env, &reporter);
 
-        if proposer_balance < min_tokens {
-            panic!(
-                "Insufficient tokens to propose. Required: {}, Have: {}",
-                min_tokens, proposer_balance
-            );
+        if amount == 0 {
+            panic!("Repayment amount must be greater than 0");
         }
 
-        // Generate proposal ID
-        let proposal_id = env
-            .crypto()
-            .sha256(
-                &(
-                    proposer.clone(),
-                    parameter.clone(),
-                    new_value,
-                    env.ledger().timestamp(),
-                )
-                    .into_val(&env),
-            )
-            .into();
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Executed {
+            panic!("Repayments can only be recorded for executed loans");
+        }
 
-        let proposal = GovernanceProposal {
-            id: proposal_id,
-            proposer,
-            parameter,
-            new_value,
-            votes_for: 0,
-            votes_against: 0,
-            voting_deadline: env.ledger().timestamp() + (7 * 24 * 60 * 60), // 7 days
-            status: ProposalStatus::Pending,
+        // Late when the earliest unpaid installment was already past due
+        let due_at = Self::current_due_at(&env, &loan_id)
+            .unwrap_or_else(|| panic!("Loan has no recorded due date"));
+        let on_time = env.ledger().timestamp() <= due_at;
+
+        let mut history = Self::get_credit_history(env.clone(), proposal.borrower.clone());
+        if on_time {
+            history.on_time_repaid += amount;
+        } else {
+            history.late_repaid += amount;
+        }
+        Self::store_credit_history(&env, &proposal.borrower, history);
+        let principal = match Self::get_loan_structure(env.clone(), loan_id.clone()) {
+            RepaymentStructure::Amortizing => amount,
+            RepaymentStructure::Balloon => Self::apply_interest(&env, &loan_id, amount),
         };
+        Self::reduce_loan_outstanding(&env, &loan_id, principal);
+        Self::apply_to_schedule(&env, &loan_id, principal);
+        Self::credit_syndicate(&env, &loan_id, proposal.amount, amount);
+
+        log!(
+            &env,
+            "Repayment of ${} on loan {} recorded by {} (on time: {})",
+            amount,
+            loan_id,
+            reporter,
+            on_time
+        );
+
+        on_time
+    }
+
+    /// Split a loan into milestone tranches before it is executed (committee members only)
+    ///
+    /// Amounts must add up to the loan principal. Once the loan is executed each
+    /// tranche is released separately through `approve_tranche`.
+    pub fn define_loan_tranches(
+        env: Env,
+        proposer: Address,
+        loan_id: BytesN<32>,
+        milestones: Vec<String>,
+        amounts: Vec<u128>,
+    ) {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Pending && proposal.status != ProposalStatus::Approved
+        {
+            panic!("Tranches can only be defined before the loan is executed");
+        }
+        if milestones.is_empty() || milestones.len() != amounts.len() {
+            panic!("Each tranche needs one milestone and one amount");
+        }
+
+        let mut tranches: Vec<LoanTranche> = Vec::new(&env);
+        let mut total: u128 = 0;
+        for (milestone, amount) in milestones.iter().zip(amounts.iter()) {
+            if amount == 0 {
+                panic!("Tranche amount must be greater than 0");
+            }
+            total += amount;
+            tranches.push_back(LoanTranche {
+                milestone,
+                amount,
+                approvals: 0,
+                disbursed_at: None,
+            });
+        }
+        if total != proposal.amount {
+            panic!("Tranche amounts must add up to the loan amount");
+        }
 
         env.storage()
             .persistent()
-            .set(&(GOVERNANCE_PROPOSALS.clone(), proposal_id), &proposal);
+            .set(&DataKey::LoanTranches(loan_id.clone()), &tranches);
+        extend_persistent_ttl(&env, &DataKey::LoanTranches(loan_id.clone()));
 
         log!(
             &env,
-            "Governance proposal {} submitted by {} for parameter {:?}",
-            proposal_id,
-            proposal.proposer,
-            proposal.parameter
+            "Loan {} split into {} tranches by {}",
+            loan_id,
+            tranches.len(),
+            proposer
         );
-
-        proposal_id
     }
 
-    /// DAO Governance: Vote on parameter change
-    pub fn vote_on_proposal(env: Env, voter: Address, proposal_id: BytesN<32>, support: bool) {
-        voter.require_auth();
+    /// Approve the release of a loan tranche (committee members only)
+    ///
+    /// Tranches are released in order. The tranche is disbursed once it reaches the
+    /// required approvals, provided the collateral still covers everything released
+    /// so far at the collateral ratio. Returns whether this approval released it.
+    pub fn approve_tranche(env: Env, approver: Address, loan_id: BytesN<32>, index: u32) -> bool {
+        approver.require_auth();
 
-        let mut proposal: GovernanceProposal = env
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+        Self::ensure_not_settled(&env);
+
+        let proposal: LoanProposal = env
             .storage()
             .persistent()
-            .get(&(GOVERNANCE_PROPOSALS.clone(), proposal_id))
-            .unwrap_or_else(|| panic!("Governance proposal not found"));
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Executed {
+            panic!("Tranches can only be released for executed loans");
+        }
+        if Self::get_loan_freeze(env.clone(), loan_id.clone()).is_some() {
+            panic!("Loan is frozen");
+        }
 
-        if env.ledger().timestamp() > proposal.voting_deadline {
-            panic!("Voting period has ended");
+        let mut tranches = Self::get_loan_tranches(env.clone(), loan_id.clone());
+        let mut tranche = tranches
+            .get(index)
+            .unwrap_or_else(|| panic!("Tranche not found"));
+        if tranche.disbursed_at.is_some() {
+            panic!("Tranche has already been disbursed");
         }
 
-        if proposal.status != ProposalStatus::Pending {
-            panic!("Proposal is not active for voting");
+        let mut released: u128 = 0;
+        for i in 0..index {
+            let earlier = tranches.get(i).unwrap();
+            if earlier.disbursed_at.is_none() {
+                panic!("Earlier tranches must be disbursed first");
+            }
+            released += earlier.amount;
         }
 
-        // Check if already voted
-        let vote_key = (Symbol::new(&env, "vote"), proposal_id, voter.clone());
-        if env.storage().persistent().has(&vote_key) {
-            panic!("User has already voted on this proposal");
+        // Check if already approved by this member
+        let approval_key = DataKey::TrancheApproval(loan_id.clone(), index, member);
+        if env.storage().persistent().has(&approval_key) {
+            panic!("Member has already approved this tranche");
         }
 
-        let voting_power = Self::get_voting_power(&env, &voter);
+        // Record approval
+        env.storage().persistent().set(&approval_key, &true);
+        extend_persistent_ttl(&env, &approval_key);
+        tranche.approvals += 1;
+
+        let disbursed = tranche.approvals >= REQUIRED_COMMITTEE_APPROVALS;
+        if disbursed {
+            // Collateral may have been revalued since the loan was approved
+            let released_usd = Self::loan_usd_value(&env, &loan_id, released + tranche.amount);
+            let gold_value_usd = Self::gold_collateral_value(&env, &loan_id);
+            Self::verify_collateral_coverage(
+                &env,
+                &proposal.collateral,
+                gold_value_usd,
+                released_usd,
+            );
+            tranche.disbursed_at = Some(env.ledger().timestamp());
+        }
+
+        let amount = tranche.amount;
+        tranches.set(index, tranche);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanTranches(loan_id.clone()), &tranches);
+        extend_persistent_ttl(&env, &DataKey::LoanTranches(loan_id.clone()));
 
-        if support {
-            proposal.votes_for += voting_power;
+        if disbursed {
+            log!(
+                &env,
+                "Tranche {} of loan {} disbursed: ${} to borrower: {}",
+                index,
+                loan_id,
+                amount,
+                proposal.borrower
+            );
         } else {
-            proposal.votes_against += voting_power;
+            log!(
+                &env,
+                "Tranche {} of loan {} approved by {}",
+                index,
+                loan_id,
+                approver
+            );
         }
 
-        // Record vote
-        env.storage().persistent().set(&vote_key, &support);
+        disbursed
+    }
 
+    /// Get the milestone tranches of a loan, empty when it is paid out as a lump sum
+    pub fn get_loan_tranches(env: Env, loan_id: BytesN<32>) -> Vec<LoanTranche> {
         env.storage()
             .persistent()
-            .set(&(GOVERNANCE_PROPOSALS.clone(), proposal_id), &proposal);
-
-        log!(
-            &env,
-            "User {} voted {} on proposal {} with {} voting power",
-            voter,
-            if support { "FOR" } else { "AGAINST" },
-            proposal_id,
-            voting_power
-        );
+            .get(&DataKey::LoanTranches(loan_id))
+            .unwrap_or(Vec::new(&env))
     }
 
-    /// Execute approved governance proposal
-    pub fn execute_governance_proposal(env: Env, executor: Address, proposal_id: BytesN<32>) {
+    /// Pay an executed loan out through the escrow to a fiat ramp partner (committee members only)
+    ///
+    /// The principal is sent from governance's holdings to the escrow, where the partner
+    /// can claim it once the borrower confirms delivery. Unconfirmed funds go back to the
+    /// loan's source vault after `timeout` seconds. When a QA holdback is set, that share
+    /// of the principal is held apart until a lab samples the pledged lot. Returns the
+    /// escrow id.
+    pub fn disburse_to_escrow(
+        env: Env,
+        executor: Address,
+        loan_id: BytesN<32>,
+        token: Address,
+        partner: Address,
+        timeout: u64,
+    ) -> u64 {
         executor.require_auth();
+        ensure_not_paused(&env);
 
-        let mut proposal: GovernanceProposal = env
+        // Verify executor is committee member
+        Self::verify_committee_member(&env, &executor);
+        Self::ensure_not_settled(&env);
+
+        let proposal: LoanProposal = env
             .storage()
             .persistent()
-            .get(&(GOVERNANCE_PROPOSALS.clone(), proposal_id))
-            .unwrap_or_else(|| panic!("Governance proposal not found"));
-
-        if env.ledger().timestamp() <= proposal.voting_deadline {
-            panic!("Voting period has not ended");
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Executed {
+            panic!("Only executed loans can be disbursed");
+        }
+        if Self::get_loan_freeze(env.clone(), loan_id.clone()).is_some() {
+            panic!("Loan is frozen");
+        }
+        if !Self::get_loan_tranches(env.clone(), loan_id.clone()).is_empty() {
+            panic!("Tranched loans are released through tranche approvals");
+        }
+        if Self::get_loan_escrow(env.clone(), loan_id.clone()).is_some() {
+            panic!("Loan has already been disbursed");
         }
 
-        if proposal.votes_for <= proposal.votes_against {
-            proposal.status = ProposalStatus::Rejected;
+        // Unclaimed funds return to the vault the loan is drawn from
+        let refund_to = match Self::get_loan_denomination(env.clone(), loan_id.clone()) {
+            Some(currency) => {
+                if token != currency {
+                    panic!("Disbursement token does not match the loan currency");
+                }
+                Self::get_loan_currency(env.clone(), currency)
+                    .unwrap_or_else(|| panic!("Loan currency is not supported"))
+                    .source_vault
+            }
+            None => lookup_registry(&env, &DataKey::Registry, REGISTRY_USDC_VAULT)
+                .unwrap_or_else(|| panic!("USDC vault not registered")),
+        };
+        let escrow = lookup_registry(&env, &DataKey::Registry, REGISTRY_ESCROW)
+            .or_else(|| env.storage().instance().get(&DataKey::Escrow))
+            .unwrap_or_else(|| panic!("Escrow not configured"));
+
+        let this = env.current_contract_address();
+        transfer_or_abort(&env, &token, &this, &escrow, proposal.amount as i128);
+
+        // The holdback is paid to the borrower only once the pledged lot passes its sample
+        let holdback_bps = Self::get_qa_holdback(env.clone()) as u128;
+        let holdback = mul_div(proposal.amount, holdback_bps, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("QA holdback overflow"));
+        if holdback > 0 {
+            let holdback_id: u64 = invoke_peer(
+                &env,
+                &escrow,
+                "open_holdback",
+                (
+                    this.clone(),
+                    loan_id.clone(),
+                    proposal.collateral.clone(),
+                    token.clone(),
+                    holdback,
+                    proposal.borrower.clone(),
+                    refund_to.clone(),
+                )
+                    .into_val(&env),
+                ContractError::PeerCallFailed,
+            );
             env.storage()
                 .persistent()
-                .set(&(GOVERNANCE_PROPOSALS.clone(), proposal_id), &proposal);
-            panic!("Proposal was rejected by vote");
+                .set(&DataKey::QaHoldback(loan_id.clone()), &holdback_id);
+            extend_persistent_ttl(&env, &DataKey::QaHoldback(loan_id.clone()));
+
+            log!(
+                &env,
+                "${} of loan {} held back in {} until lot {} passes QA",
+                holdback,
+                loan_id,
+                holdback_id,
+                proposal.collateral
+            );
         }
 
-        // TODO: Implement actual parameter update logic
-        // This would involve updating the relevant protocol parameters
+        let escrow_id: u64 = invoke_peer(
+            &env,
+            &escrow,
+            "open",
+            (
+                this,
+                loan_id.clone(),
+                token,
+                proposal.amount - holdback,
+                proposal.borrower.clone(),
+                partner.clone(),
+                refund_to,
+                timeout,
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
 
-        proposal.status = ProposalStatus::Executed;
         env.storage()
             .persistent()
-            .set(&(GOVERNANCE_PROPOSALS.clone(), proposal_id), &proposal);
+            .set(&DataKey::DisbursementEscrow(loan_id.clone()), &escrow_id);
+        extend_persistent_ttl(&env, &DataKey::DisbursementEscrow(loan_id.clone()));
 
         log!(
             &env,
-            "Governance proposal {} executed. Parameter {:?} updated to {}",
-            proposal_id,
-            proposal.parameter,
-            proposal.new_value
+            "Loan {} disbursed into escrow {}: ${} for borrower {} via partner {}",
+            loan_id,
+            escrow_id,
+            proposal.amount - holdback,
+            proposal.borrower,
+            partner
         );
+
+        escrow_id
     }
 
-    /// Get loan proposal details
-    pub fn get_loan_proposal(env: Env, proposal_id: BytesN<32>) -> Option<LoanProposal> {
+    /// Get the QA holdback opened when a loan was disbursed, if any
+    pub fn get_loan_holdback(env: Env, loan_id: BytesN<32>) -> Option<u64> {
         env.storage()
             .persistent()
-            .get(&(LOAN_PROPOSALS.clone(), proposal_id))
+            .get(&DataKey::QaHoldback(loan_id))
     }
 
-    /// Get governance proposal details
-    pub fn get_governance_proposal(
-        env: Env,
-        proposal_id: BytesN<32>,
-    ) -> Option<GovernanceProposal> {
+    /// Get the escrow a loan was disbursed into, if any
+    pub fn get_loan_escrow(env: Env, loan_id: BytesN<32>) -> Option<u64> {
         env.storage()
             .persistent()
-            .get(&(GOVERNANCE_PROPOSALS.clone(), proposal_id))
+            .get(&DataKey::DisbursementEscrow(loan_id))
     }
 
-    /// Get committee members
-    pub fn get_committee_members(env: Env) -> Vec<CommitteeMember> {
-        env.storage()
-            .instance()
-            .get(&COMMITTEE_MEMBERS)
-            .unwrap_or(Vec::new(&env))
-    }
+    /// Allow or disallow an external lender to co-fund approved loans (admin only)
+    pub fn set_syndicate_lender(env: Env, admin: Address, lender: Address, allowed: bool) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can manage syndicate lenders");
+        }
+
+        let mut updated = Vec::new(&env);
+        for existing in Self::get_syndicate_lenders(env.clone()).iter() {
+            if existing != lender {
+                updated.push_back(existing);
+            }
+        }
+        if allowed {
+            updated.push_back(lender.clone());
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SyndicateLenders, &updated);
+
+        log!(&env, "Syndicate lender {} allowed: {}", lender, allowed);
+    }
+
+    /// Get the external lenders allowed to co-fund loans
+    pub fn get_syndicate_lenders(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::SyndicateLenders)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Put part of a loan's principal up from an external lender's own funds
+    ///
+    /// Lenders can commit while the committee reviews the loan and until it is executed.
+    /// The funds are held by governance and paid out with the rest of the principal, so
+    /// the vault only supplies what lenders do not. Every repayment recorded afterwards
+    /// is credited to the lenders in proportion to their share of the principal.
+    /// Returns the principal funded by lenders so far.
+    pub fn fund_loan(
+        env: Env,
+        lender: Address,
+        loan_id: BytesN<32>,
+        token: Address,
+        amount: u128,
+    ) -> u128 {
+        lender.require_auth();
+        ensure_not_paused(&env);
+        Self::ensure_not_settled(&env);
+
+        if !Self::get_syndicate_lenders(env.clone()).contains(&lender) {
+            panic!("Lender is not allowed to co-fund loans");
+        }
+        if amount == 0 {
+            panic!("Funding amount must be greater than 0");
+        }
+
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if !matches!(
+            proposal.status,
+            ProposalStatus::Pending | ProposalStatus::Approved
+        ) {
+            panic!("Only loans awaiting execution can be co-funded");
+        }
+
+        // Every lender funds in the loan's currency, fixed by the first lender for USDC loans
+        let mut syndicate = Self::get_loan_syndicate(env.clone(), loan_id.clone());
+        let expected_token = match Self::get_loan_denomination(env.clone(), loan_id.clone()) {
+            Some(currency) => Some(currency),
+            None => syndicate.first().map(|share| share.token),
+        };
+        if expected_token.is_some_and(|expected| expected != token) {
+            panic!("Funding token does not match the loan currency");
+        }
+
+        let syndicated = Self::get_syndicated_amount(env.clone(), loan_id.clone()) + amount;
+        if syndicated > proposal.amount {
+            panic!("Funding exceeds the loan principal");
+        }
+
+        transfer_or_abort(
+            &env,
+            &token,
+            &lender,
+            &env.current_contract_address(),
+            amount as i128,
+        );
+
+        let position = syndicate.iter().position(|share| share.lender == lender);
+        match position {
+            Some(index) => {
+                let mut share = syndicate.get(index as u32).unwrap();
+                share.principal += amount;
+                syndicate.set(index as u32, share);
+            }
+            None => syndicate.push_back(SyndicateShare {
+                lender: lender.clone(),
+                token,
+                principal: amount,
+                credited: 0,
+                claimed: 0,
+            }),
+        }
+        Self::store_loan_syndicate(&env, &loan_id, &syndicate);
+
+        log!(
+            &env,
+            "Lender {} co-funded ${} of loan {}; lenders now fund ${} of ${}",
+            lender,
+            amount,
+            loan_id,
+            syndicated,
+            proposal.amount
+        );
+
+        syndicated
+    }
+
+    /// Return a lender's funding once the loan it was put towards has been rejected
+    pub fn withdraw_loan_funding(env: Env, lender: Address, loan_id: BytesN<32>) -> u128 {
+        lender.require_auth();
+
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Rejected {
+            panic!("Funding can only be withdrawn from rejected loans");
+        }
+
+        let mut syndicate = Self::get_loan_syndicate(env.clone(), loan_id.clone());
+        let index = syndicate
+            .iter()
+            .position(|share| share.lender == lender)
+            .unwrap_or_else(|| panic!("Lender did not fund this loan"));
+        let share = syndicate.get(index as u32).unwrap();
+        syndicate.remove(index as u32);
+        Self::store_loan_syndicate(&env, &loan_id, &syndicate);
+
+        transfer_or_abort(
+            &env,
+            &share.token,
+            &env.current_contract_address(),
+            &lender,
+            share.principal as i128,
+        );
+
+        log!(
+            &env,
+            "Lender {} withdrew ${} from rejected loan {}",
+            lender,
+            share.principal,
+            loan_id
+        );
+
+        share.principal
+    }
+
+    /// Pay a lender the repayments credited to it on a co-funded loan
+    ///
+    /// The lenders' share of each recorded repayment must reach governance before it
+    /// can be claimed.
+    pub fn claim_syndicate_repayment(env: Env, lender: Address, loan_id: BytesN<32>) -> u128 {
+        lender.require_auth();
+        ensure_not_paused(&env);
+
+        let mut syndicate = Self::get_loan_syndicate(env.clone(), loan_id.clone());
+        let index = syndicate
+            .iter()
+            .position(|share| share.lender == lender)
+            .unwrap_or_else(|| panic!("Lender did not fund this loan"));
+        let mut share = syndicate.get(index as u32).unwrap();
+        let claimable = share.credited - share.claimed;
+        if claimable == 0 {
+            panic!("No repayments to claim");
+        }
+
+        share.claimed = share.credited;
+        syndicate.set(index as u32, share.clone());
+        Self::store_loan_syndicate(&env, &loan_id, &syndicate);
+
+        transfer_or_abort(
+            &env,
+            &share.token,
+            &env.current_contract_address(),
+            &lender,
+            claimable as i128,
+        );
+
+        log!(
+            &env,
+            "Lender {} claimed ${} of repayments on loan {}",
+            lender,
+            claimable,
+            loan_id
+        );
+
+        claimable
+    }
+
+    /// Get the external lenders' shares of a co-funded loan
+    pub fn get_loan_syndicate(env: Env, loan_id: BytesN<32>) -> Vec<SyndicateShare> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanSyndicate(loan_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the part of a loan's principal funded by external lenders
+    pub fn get_syndicated_amount(env: Env, loan_id: BytesN<32>) -> u128 {
+        Self::get_loan_syndicate(env, loan_id)
+            .iter()
+            .map(|share| share.principal)
+            .sum()
+    }
+
+    /// Get the principal still outstanding on an active loan
+    pub fn get_loan_outstanding(env: Env, loan_id: BytesN<32>) -> Option<u128> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanOutstanding(loan_id))
+    }
+
+    /// Get the number of executed loans with principal outstanding
+    pub fn get_active_loans(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ActiveLoans)
+            .unwrap_or(0)
+    }
+
+    /// Get the principal outstanding across active loans
+    ///
+    /// Counts loans executed since the total was introduced.
+    pub fn get_outstanding_principal(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::OutstandingPrincipal)
+            .unwrap_or(0)
+    }
+
+    /// Get when a loan is due; every loan is called at global settlement
+    pub fn get_loan_due_at(env: Env, loan_id: BytesN<32>) -> Option<u64> {
+        let due_at: Option<u64> = env.storage().persistent().get(&DataKey::LoanDueAt(loan_id));
+        let settled_at: Option<u64> = env.storage().instance().get(&DataKey::SettledAt);
+        match (due_at, settled_at) {
+            (Some(due_at), Some(settled_at)) => Some(due_at.min(settled_at)),
+            (due_at, _) => due_at,
+        }
+    }
+
+    /// Get the installment schedule generated when a loan was executed
+    pub fn get_repayment_schedule(env: Env, loan_id: BytesN<32>) -> Vec<Installment> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RepaymentSchedule(loan_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the earliest installment whose principal is not fully repaid
+    ///
+    /// A balloon loan's interest-only installments are due until their interest is
+    /// paid. Like the final due date, every installment falls due at global settlement.
+    pub fn get_next_payment(env: Env, loan_id: BytesN<32>) -> Option<Installment> {
+        let settled_at: Option<u64> = env.storage().instance().get(&DataKey::SettledAt);
+        let balloon =
+            Self::get_loan_structure(env.clone(), loan_id.clone()) == RepaymentStructure::Balloon;
+        let interest_paid = Self::get_interest_paid(env.clone(), loan_id.clone());
+        let schedule = Self::get_repayment_schedule(env, loan_id);
+        let mut interest_due: u128 = 0;
+        let mut installment = schedule.iter().find(|installment| {
+            interest_due += installment.interest;
+            installment.principal_repaid < installment.principal
+                || (balloon && interest_due > interest_paid)
+        })?;
+        if let Some(settled_at) = settled_at {
+            installment.due_at = installment.due_at.min(settled_at);
+        }
+        Some(installment)
+    }
+
+    /// Whether an active loan has an installment past its due date
+    pub fn is_loan_overdue(env: Env, loan_id: BytesN<32>) -> bool {
+        Self::overdue_beyond(&env, &loan_id, 0)
+    }
+
+    /// Whether an active loan has been overdue for longer than the liquidation grace period
+    ///
+    /// Collateral can only be liquidated for a loan in this state.
+    pub fn is_loan_liquidatable(env: Env, loan_id: BytesN<32>) -> bool {
+        let grace_period = Self::get_liquidation_grace_period(env.clone());
+        Self::overdue_beyond(&env, &loan_id, grace_period)
+    }
+
+    /// Freeze an active loan when fraud is suspected (committee members only)
+    ///
+    /// A single member can freeze a loan. While frozen no tranche is released, the
+    /// loan cannot be repaid early and its repayment clock is stopped. This is
+    /// separate from the emergency shutdown.
+    pub fn freeze_loan(env: Env, guardian: Address, loan_id: BytesN<32>) {
+        guardian.require_auth();
+
+        // An active delegate can freeze on the member's behalf
+        let member = Self::approving_member(&env, &guardian);
+
+        if Self::get_loan_outstanding(env.clone(), loan_id.clone()).is_none() {
+            panic!("Only active loans can be frozen");
+        }
+        if Self::get_loan_freeze(env.clone(), loan_id.clone()).is_some() {
+            panic!("Loan is already frozen");
+        }
+
+        let freeze = LoanFreeze {
+            frozen_by: member,
+            frozen_at: env.ledger().timestamp(),
+            unfreeze_approvals: 0,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanFreeze(loan_id.clone()), &freeze);
+        extend_persistent_ttl(&env, &DataKey::LoanFreeze(loan_id.clone()));
+
+        log!(&env, "Loan {} frozen by {}", loan_id, guardian);
+    }
+
+    /// Approve lifting a loan freeze (committee members only)
+    ///
+    /// Returns true once the required approvals are reached and the loan is
+    /// unfrozen. Its due dates move out by the time it spent frozen.
+    pub fn approve_unfreeze(env: Env, approver: Address, loan_id: BytesN<32>) -> bool {
+        approver.require_auth();
+
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+
+        let mut freeze = Self::get_loan_freeze(env.clone(), loan_id.clone())
+            .unwrap_or_else(|| panic!("Loan is not frozen"));
+
+        // Check if already approved by this member
+        let approval_key = DataKey::UnfreezeApproval(loan_id.clone(), member);
+        if env.storage().persistent().has(&approval_key) {
+            panic!("Member has already approved unfreezing this loan");
+        }
+
+        // Record approval
+        env.storage().persistent().set(&approval_key, &true);
+        extend_persistent_ttl(&env, &approval_key);
+        freeze.unfreeze_approvals += 1;
+
+        if freeze.unfreeze_approvals < REQUIRED_COMMITTEE_APPROVALS {
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanFreeze(loan_id.clone()), &freeze);
+
+            log!(
+                &env,
+                "Unfreeze of loan {} approved by {}. Approvals: {}/{}",
+                loan_id,
+                approver,
+                freeze.unfreeze_approvals,
+                REQUIRED_COMMITTEE_APPROVALS
+            );
+            return false;
+        }
+
+        let frozen_for = env.ledger().timestamp() - freeze.frozen_at;
+        Self::defer_loan_schedule(&env, &loan_id, frozen_for);
+
+        // Clear approvals so a later freeze starts from scratch
+        let committee: Vec<CommitteeMember> = env
+            .storage()
+            .instance()
+            .get(&DataKey::CommitteeMembers)
+            .unwrap_or(Vec::new(&env));
+        for committee_member in committee.iter() {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::UnfreezeApproval(
+                    loan_id.clone(),
+                    committee_member.address,
+                ));
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::LoanFreeze(loan_id.clone()));
+
+        log!(
+            &env,
+            "Loan {} unfrozen after {} seconds; due dates deferred",
+            loan_id,
+            frozen_for
+        );
+
+        true
+    }
+
+    /// Get the freeze on a loan, if any
+    pub fn get_loan_freeze(env: Env, loan_id: BytesN<32>) -> Option<LoanFreeze> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanFreeze(loan_id))
+    }
+
+    /// Pledge a vault position to a loan, blocking its withdrawal (committee members only)
+    ///
+    /// The position owner must also authorize the pledge. Liens are released
+    /// together with `release_vault_liens` once the loan is repaid or rejected.
+    pub fn pledge_vault_position(
+        env: Env,
+        member: Address,
+        loan_id: BytesN<32>,
+        vault: Address,
+        owner: Address,
+        position_id: u64,
+    ) {
+        member.require_auth();
+
+        // Verify member is committee member
+        Self::verify_committee_member(&env, &member);
+
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        let open = match proposal.status {
+            ProposalStatus::Pending | ProposalStatus::Approved => true,
+            ProposalStatus::Executed => {
+                Self::get_loan_outstanding(env.clone(), loan_id.clone()).is_some()
+            }
+            _ => false,
+        };
+        if !open {
+            panic!("Positions can only be pledged to open loans");
+        }
+
+        invoke_peer::<()>(
+            &env,
+            &vault,
+            "encumber_position",
+            (
+                env.current_contract_address(),
+                owner.clone(),
+                position_id,
+                loan_id.clone(),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+
+        let mut liens = Self::get_vault_liens(env.clone(), loan_id.clone());
+        liens.push_back(VaultLien {
+            vault: vault.clone(),
+            owner: owner.clone(),
+            position_id,
+        });
+        env.storage()
+            .persistent()
+            .set(&DataKey::VaultLiens(loan_id.clone()), &liens);
+        extend_persistent_ttl(&env, &DataKey::VaultLiens(loan_id.clone()));
+
+        log!(
+            &env,
+            "Position {} of {} in vault {} pledged to loan {}",
+            position_id,
+            owner,
+            vault,
+            loan_id
+        );
+    }
+
+    /// Release every vault lien of a repaid or rejected loan (permissionless)
+    ///
+    /// The loan's coffee lot, if still pledged, is released with them. Returns the
+    /// number of vault liens released.
+    pub fn release_vault_liens(env: Env, loan_id: BytesN<32>) -> u32 {
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        let closed = match proposal.status {
+            ProposalStatus::Rejected => true,
+            ProposalStatus::Executed => {
+                Self::get_loan_outstanding(env.clone(), loan_id.clone()).is_none()
+            }
+            _ => false,
+        };
+        if !closed {
+            panic!("Liens are held until the loan is repaid or rejected");
+        }
+
+        let mut liens = Self::get_vault_liens(env.clone(), loan_id.clone());
+        env.storage()
+            .persistent()
+            .remove(&DataKey::VaultLiens(loan_id.clone()));
+        // Gold collateral stays pledged while it still owes a liquidation shortfall
+        if let Some(lien) = Self::get_gold_collateral(env.clone(), loan_id.clone()) {
+            if Self::liquidation_shortfall(&env, &loan_id) == 0 {
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::GoldCollateral(loan_id.clone()));
+                liens.push_back(lien);
+            }
+        }
+        for lien in liens.iter() {
+            invoke_peer::<()>(
+                &env,
+                &lien.vault,
+                "release_lien",
+                (env.current_contract_address(), lien.owner, lien.position_id).into_val(&env),
+                ContractError::PeerCallFailed,
+            );
+        }
+
+        // A lot still active (never liquidated) is freed along with the liens
+        if let Some(coffee_collateral) =
+            lookup_registry(&env, &DataKey::Registry, REGISTRY_COFFEE_COLLATERAL)
+        {
+            let pledged: bool = invoke_peer(
+                &env,
+                &coffee_collateral,
+                "verify_collateral",
+                (loan_id.clone(),).into_val(&env),
+                ContractError::PeerCallFailed,
+            );
+            if pledged {
+                invoke_peer::<Address>(
+                    &env,
+                    &coffee_collateral,
+                    "release_collateral",
+                    (env.current_contract_address(), loan_id.clone()).into_val(&env),
+                    ContractError::PeerCallFailed,
+                );
+            }
+        }
+
+        log!(
+            &env,
+            "Released {} vault liens of loan {}",
+            liens.len(),
+            loan_id
+        );
+
+        liens.len()
+    }
+
+    /// Get the vault positions pledged to a loan
+    pub fn get_vault_liens(env: Env, loan_id: BytesN<32>) -> Vec<VaultLien> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::VaultLiens(loan_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the gold-vault position counted toward a dual-collateral loan's coverage
+    pub fn get_gold_collateral(env: Env, loan_id: BytesN<32>) -> Option<VaultLien> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::GoldCollateral(loan_id))
+    }
+
+    /// Value a loan against its coffee lot and any pledged gold-vault position together
+    pub fn get_combined_collateral(env: Env, loan_id: BytesN<32>) -> CombinedCollateral {
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+
+        let coffee_collateral =
+            lookup_registry(&env, &DataKey::Registry, REGISTRY_COFFEE_COLLATERAL)
+                .unwrap_or_else(|| panic!("Coffee collateral contract not registered"));
+        let info: Option<CollateralInfo> = invoke_peer(
+            &env,
+            &coffee_collateral,
+            "get_collateral_info",
+            (proposal.collateral.clone(),).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        let coffee_value_usd = match info {
+            Some(info) if info.status == CollateralStatus::Active => info.estimated_value_usd,
+            _ => 0,
+        };
+        let gold_value_usd = Self::gold_collateral_value(&env, &loan_id);
+
+        let principal = if proposal.status == ProposalStatus::Executed {
+            Self::get_loan_outstanding(env.clone(), loan_id.clone()).unwrap_or(0)
+        } else {
+            proposal.amount
+        };
+        let loan_value_usd = Self::loan_usd_value(&env, &loan_id, principal);
+        let collateral_value_usd = coffee_value_usd + gold_value_usd;
+        let ltv_bps = if collateral_value_usd == 0 {
+            u32::MAX
+        } else {
+            mul_div_up(loan_value_usd, BASIS_POINTS, collateral_value_usd)
+                .unwrap_or_else(|| panic!("Loan-to-value overflow"))
+                .min(u32::MAX as u128) as u32
+        };
+
+        CombinedCollateral {
+            coffee_value_usd,
+            gold_value_usd,
+            loan_value_usd,
+            ltv_bps,
+        }
+    }
+
+    /// Take the gold collateral of a loan whose coffee lot was liquidated at a shortfall (permissionless)
+    ///
+    /// The whole position is closed. Gold worth the shortfall goes to the loan's creditor,
+    /// the vault named on its bad-debt record or else the USDC vault, and the rest back to
+    /// the position owner. The recovery is taken off the loan's bad debt. Returns the
+    /// shortfall recovered in USD.
+    pub fn seize_gold_collateral(env: Env, caller: Address, loan_id: BytesN<32>) -> u128 {
+        caller.require_auth();
+
+        let lien = Self::get_gold_collateral(env.clone(), loan_id.clone())
+            .unwrap_or_else(|| panic!("Loan has no gold collateral"));
+        let shortfall = Self::liquidation_shortfall(&env, &loan_id);
+        if shortfall == 0 {
+            panic!("Coffee collateral has not been liquidated at a shortfall");
+        }
+        let creditor = Self::loan_creditor(&env, &loan_id);
+        let gold_asset: Address = invoke_peer::<Option<Address>>(
+            &env,
+            &lien.vault,
+            "get_position_asset",
+            (lien.owner.clone(),).into_val(&env),
+            ContractError::PeerCallFailed,
+        )
+        .unwrap_or_else(|| panic!("Gold position is already closed"));
+
+        // Drop the pledge before the vault pays out, so it can only be seized once
+        env.storage()
+            .persistent()
+            .remove(&DataKey::GoldCollateral(loan_id.clone()));
+        let this = env.current_contract_address();
+        let gold_seized: u128 = invoke_peer(
+            &env,
+            &lien.vault,
+            "seize_position",
+            (this.clone(), lien.owner.clone(), lien.position_id).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        let seized_usd: u128 = invoke_peer(
+            &env,
+            &lien.vault,
+            "get_usd_value",
+            (gold_asset.clone(), gold_seized).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+
+        let recovered_usd = shortfall.min(seized_usd);
+        let gold_to_creditor = if seized_usd == 0 {
+            gold_seized
+        } else {
+            mul_div_up(gold_seized, recovered_usd, seized_usd)
+                .unwrap_or_else(|| panic!("Seized gold overflow"))
+                .min(gold_seized)
+        };
+        if gold_to_creditor > 0 {
+            transfer_or_abort(
+                &env,
+                &gold_asset,
+                &this,
+                &creditor,
+                gold_to_creditor as i128,
+            );
+        }
+        if gold_seized > gold_to_creditor {
+            transfer_or_abort(
+                &env,
+                &gold_asset,
+                &this,
+                &lien.owner,
+                (gold_seized - gold_to_creditor) as i128,
+            );
+        }
+        Self::apply_shortfall_recovery(&env, &loan_id, recovered_usd);
+
+        let seizure = GoldSeizure {
+            gold_asset,
+            gold_seized,
+            creditor: creditor.clone(),
+            gold_to_creditor,
+            recovered_usd,
+            seized_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::GoldSeizure(loan_id.clone()), &seizure);
+        extend_persistent_ttl(&env, &DataKey::GoldSeizure(loan_id.clone()));
+
+        log!(
+            &env,
+            "Gold collateral of loan {} seized by {}: {} to creditor {} covering ${} of the shortfall",
+            loan_id,
+            caller,
+            gold_to_creditor,
+            creditor,
+            recovered_usd
+        );
+
+        recovered_usd
+    }
+
+    /// Get how a loan's gold collateral was seized, if it was
+    pub fn get_gold_seizure(env: Env, loan_id: BytesN<32>) -> Option<GoldSeizure> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::GoldSeizure(loan_id))
+    }
+
+    /// Set how interest is charged when a loan is repaid early (fee setter, or admin if none is set)
+    pub fn set_prepayment_policy(env: Env, caller: Address, policy: PrepaymentPolicy) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::FeeSetter, &caller, &admin);
+
+        if let PrepaymentPolicy::PrepaymentFee(fee_bps) = policy {
+            if fee_bps > MAX_PREPAYMENT_FEE_BPS {
+                panic!("Prepayment fee exceeds maximum");
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PrepaymentPolicy, &policy);
+
+        log!(&env, "Prepayment policy set to: {:?}", policy);
+    }
+
+    /// Get the early repayment policy; borrowers owe full interest unless one is set
+    pub fn get_prepayment_policy(env: Env) -> PrepaymentPolicy {
+        env.storage()
+            .instance()
+            .get(&DataKey::PrepaymentPolicy)
+            .unwrap_or(PrepaymentPolicy::FullInterest)
+    }
+
+    /// Quote what repaying a loan's outstanding principal now would cost under the current policy
+    ///
+    /// Interest of installments already past due is owed in full and not part of the quote.
+    pub fn quote_early_repayment(env: Env, loan_id: BytesN<32>) -> EarlyRepayment {
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        let principal = Self::get_loan_outstanding(env.clone(), loan_id.clone())
+            .unwrap_or_else(|| panic!("Loan has no outstanding principal"));
+        let due_at: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanDueAt(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan has no recorded due date"));
+
+        let current_time = env.ledger().timestamp();
+        let mut period_start = due_at - proposal.duration * SECONDS_PER_DAY;
+        let mut scheduled: u128 = 0;
+        let mut accrued: u128 = 0;
+        let mut interest_due: u128 = 0;
+        for installment in Self::get_repayment_schedule(env.clone(), loan_id.clone()).iter() {
+            if installment.due_at <= current_time {
+                interest_due += installment.interest;
+            } else {
+                scheduled += installment.interest;
+                // Only the current period has started accruing
+                if current_time > period_start {
+                    accrued += mul_div_up(
+                        installment.interest,
+                        (current_time - period_start) as u128,
+                        (installment.due_at - period_start) as u128,
+                    )
+                    .unwrap_or_else(|| panic!("Accrued interest overflow"));
+                }
+            }
+            period_start = installment.due_at;
+        }
+
+        let (interest_charged, prepayment_fee) = match Self::get_prepayment_policy(env.clone()) {
+            PrepaymentPolicy::FullInterest => (scheduled, 0),
+            PrepaymentPolicy::ProRated => (accrued, 0),
+            PrepaymentPolicy::PrepaymentFee(fee_bps) => (
+                accrued,
+                mul_div_up(principal, fee_bps as u128, BASIS_POINTS)
+                    .unwrap_or_else(|| panic!("Prepayment fee overflow")),
+            ),
+        };
+        // Interest a balloon borrower paid ahead of its due dates counts toward the charge
+        let prepaid =
+            Self::get_interest_paid(env.clone(), loan_id.clone()).saturating_sub(interest_due);
+
+        EarlyRepayment {
+            principal,
+            interest_charged: interest_charged.saturating_sub(prepaid),
+            interest_rebated: scheduled - interest_charged,
+            prepayment_fee,
+            repaid_at: current_time,
+        }
+    }
+
+    /// Record that a borrower repaid a loan in full ahead of schedule (committee members only)
+    ///
+    /// The charged interest and any prepayment fee are added to the lending profit
+    /// of the next profit report.
+    pub fn record_early_repayment(
+        env: Env,
+        reporter: Address,
+        loan_id: BytesN<32>,
+    ) -> EarlyRepayment {
+        reporter.require_auth();
+
+        // Verify reporter is committee member
+        Self::verify_committee_member(&env, &reporter);
+
+        let proposal: LoanProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()))
+            .unwrap_or_else(|| panic!("Loan proposal not found"));
+        if proposal.status != ProposalStatus::Executed {
+            panic!("Repayments can only be recorded for executed loans");
+        }
+        if Self::get_loan_freeze(env.clone(), loan_id.clone()).is_some() {
+            panic!("Loan is frozen");
+        }
+        if Self::is_loan_overdue(env.clone(), loan_id.clone()) {
+            panic!("Overdue loans cannot be repaid early");
+        }
+
+        let repayment = Self::quote_early_repayment(env.clone(), loan_id.clone());
+
+        let mut history = Self::get_credit_history(env.clone(), proposal.borrower.clone());
+        history.on_time_repaid += repayment.principal;
+        Self::store_credit_history(&env, &proposal.borrower, history);
+        Self::reduce_loan_outstanding(&env, &loan_id, repayment.principal);
+        Self::apply_to_schedule(&env, &loan_id, repayment.principal);
+        Self::credit_syndicate(
+            &env,
+            &loan_id,
+            proposal.amount,
+            repayment.principal + repayment.interest_charged,
+        );
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::EarlyRepayment(loan_id.clone()), &repayment);
+        extend_persistent_ttl(&env, &DataKey::EarlyRepayment(loan_id.clone()));
+
+        let income = Self::get_prepayment_income(env.clone());
+        env.storage().instance().set(
+            &DataKey::PrepaymentIncome,
+            &(income + repayment.interest_charged + repayment.prepayment_fee),
+        );
+        let rebated = Self::get_interest_rebated(env.clone());
+        env.storage().instance().set(
+            &DataKey::InterestRebated,
+            &(rebated + repayment.interest_rebated),
+        );
+
+        log!(
+            &env,
+            "Loan {} repaid early, recorded by {}: principal ${}, interest ${}, rebate ${}, fee ${}",
+            loan_id,
+            reporter,
+            repayment.principal,
+            repayment.interest_charged,
+            repayment.interest_rebated,
+            repayment.prepayment_fee
+        );
+
+        repayment
+    }
+
+    /// Get the settlement of a loan that was repaid early
+    pub fn get_early_repayment(env: Env, loan_id: BytesN<32>) -> Option<EarlyRepayment> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EarlyRepayment(loan_id))
+    }
+
+    /// Get early repayment interest and fees not yet included in a profit report
+    pub fn get_prepayment_income(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PrepaymentIncome)
+            .unwrap_or(0)
+    }
+
+    /// Get the total scheduled interest waived on early repayments
+    pub fn get_interest_rebated(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::InterestRebated)
+            .unwrap_or(0)
+    }
+
+    /// Approve protocol-wide emergency shutdown (committee members only)
+    ///
+    /// Once enough members approve, yield stops accruing, both vaults enter
+    /// pro-rata settlement with every lock waived, and all executed loans are called.
+    pub fn approve_shutdown(env: Env, approver: Address) {
+        approver.require_auth();
+
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+        Self::ensure_not_settled(&env);
+
+        let mut approvals: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::ShutdownApprovals)
+            .unwrap_or(Vec::new(&env));
+        if approvals.contains(&member) {
+            panic!("Member has already approved shutdown");
+        }
+        approvals.push_back(member);
+        env.storage()
+            .instance()
+            .set(&DataKey::ShutdownApprovals, &approvals);
+
+        log!(
+            &env,
+            "Shutdown approved by {}. Approvals: {}/{}",
+            approver,
+            approvals.len(),
+            REQUIRED_COMMITTEE_APPROVALS
+        );
+
+        if approvals.len() >= REQUIRED_COMMITTEE_APPROVALS {
+            Self::trigger_settlement(&env);
+        }
+    }
+
+    /// Get when global settlement started, if it has
+    pub fn get_settled_at(env: Env) -> Option<u64> {
+        env.storage().instance().get(&DataKey::SettledAt)
+    }
+
+    /// Get a borrower's repayment history
+    pub fn get_credit_history(env: Env, borrower: Address) -> CreditHistory {
+        env.storage()
+            .persistent()
+            .get(&DataKey::CreditHistory(borrower))
+            .unwrap_or(CreditHistory {
+                loans_executed: 0,
+                on_time_repaid: 0,
+                late_repaid: 0,
+                defaulted: 0,
+                last_updated: 0,
+            })
+    }
+
+    /// Get a borrower's credit score from 0 to `CREDIT_SCORE_MAX`
+    ///
+    /// The share of settled loan value repaid on time, with late repayments
+    /// counting half and defaults nothing; `CREDIT_SCORE_NEUTRAL` without history.
+    pub fn get_credit_score(env: Env, borrower: Address) -> u32 {
+        let history = Self::get_credit_history(env, borrower);
+
+        let settled = history.on_time_repaid + history.late_repaid + history.defaulted;
+        if settled == 0 {
+            return CREDIT_SCORE_NEUTRAL;
+        }
+
+        let weighted = history.on_time_repaid + history.late_repaid / 2;
+        mul_div(weighted, CREDIT_SCORE_MAX as u128, settled)
+            .unwrap_or_else(|| panic!("Credit score overflow")) as u32
+    }
+
+    /// Submit a trade proposal (committee members only)
+    pub fn submit_trade_proposal(
+        env: Env,
+        proposer: Address,
+        trade_params: TradeParams,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        Self::open_trade(&env, &proposer, &trade_params)
+    }
+
+    /// Submit a trade that fills only at or above a limit price (committee members only)
+    ///
+    /// `limit_price` is in asset_out per asset_in at oracle precision. Once the order
+    /// has the required committee approvals, the proposer's included, any keeper can
+    /// execute it through `execute_limit_order` while the price feed is at or above
+    /// the limit and the deadline has not passed.
+    pub fn submit_limit_order(
+        env: Env,
+        proposer: Address,
+        trade_params: TradeParams,
+        price_feed: Symbol,
+        limit_price: u128,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        if limit_price == 0 {
+            panic!("Limit price must be greater than 0");
+        }
+
+        let trade_id = Self::open_trade(&env, &proposer, &trade_params);
+        let order = LimitOrder {
+            price_feed,
+            limit_price,
+            approvals: 1,
+        };
+        Self::store_limit_order(&env, &trade_id, &order);
+        let approval_key = DataKey::LimitApproval(trade_id.clone(), proposer.clone());
+        env.storage().persistent().set(&approval_key, &true);
+        extend_persistent_ttl(&env, &approval_key);
+
+        log!(
+            &env,
+            "Trade {} set to fill at a limit of {} on {}",
+            trade_id,
+            order.limit_price,
+            order.price_feed
+        );
+
+        trade_id
+    }
+
+    /// Approve an open limit order (committee members only)
+    ///
+    /// Returns whether the order now has the approvals keepers need to execute it.
+    pub fn approve_limit_order(env: Env, approver: Address, trade_id: BytesN<32>) -> bool {
+        approver.require_auth();
+
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::TradeProposal(trade_id.clone()))
+        {
+            panic!("Trade proposal not found");
+        }
+        let mut order = Self::get_limit_order(env.clone(), trade_id.clone())
+            .unwrap_or_else(|| panic!("Trade is not a limit order"));
+
+        let approval_key = DataKey::LimitApproval(trade_id.clone(), member);
+        if env.storage().persistent().has(&approval_key) {
+            panic!("Member has already approved this limit order");
+        }
+        env.storage().persistent().set(&approval_key, &true);
+        extend_persistent_ttl(&env, &approval_key);
+        order.approvals += 1;
+        Self::store_limit_order(&env, &trade_id, &order);
+
+        log!(&env, "Limit order {} approved by {}", trade_id, approver);
+
+        order.approvals >= REQUIRED_COMMITTEE_APPROVALS
+    }
+
+    /// Execute what remains of an approved limit order once its price is reached (permissionless)
+    ///
+    /// The swap must still return the trade's minimum out, and at least the limit
+    /// price on the amount filled. Returns the amount out.
+    pub fn execute_limit_order(env: Env, keeper: Address, trade_id: BytesN<32>) -> u128 {
+        keeper.require_auth();
+        ensure_not_paused(&env);
+
+        let order = Self::get_limit_order(env.clone(), trade_id.clone())
+            .unwrap_or_else(|| panic!("Trade is not a limit order"));
+        if order.approvals < REQUIRED_COMMITTEE_APPROVALS {
+            panic!("Limit order needs more committee approvals");
+        }
+
+        let oracle = lookup_registry(&env, &DataKey::Registry, REGISTRY_ORACLE)
+            .unwrap_or_else(|| panic!("Oracle not registered"));
+        let price: u128 = invoke_peer(
+            &env,
+            &oracle,
+            "get_price",
+            (order.price_feed.clone(),).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        if price < order.limit_price {
+            panic!("Limit price has not been reached");
+        }
+
+        let remaining = Self::get_trade_remaining(env.clone(), trade_id.clone());
+        Self::fill_trade(&env, &keeper, &trade_id, remaining)
+    }
+
+    /// Execute a trade, or whatever remains of a partially filled one (committee members only)
+    pub fn execute_trade(env: Env, executor: Address, trade_id: BytesN<32>) {
+        let remaining = Self::get_trade_remaining(env.clone(), trade_id.clone());
+        Self::execute_trade_partial(env, executor, trade_id, remaining);
+    }
+
+    /// Execute a slice of a trade (committee members only)
+    ///
+    /// Each slice must return at least its pro-rata share of the trade's minimum out.
+    /// The trade stays open until fully filled, when its record is written at the
+    /// weighted price across slices. Returns the amount out of this slice.
+    pub fn execute_trade_partial(
+        env: Env,
+        executor: Address,
+        trade_id: BytesN<32>,
+        amount: u128,
+    ) -> u128 {
+        executor.require_auth();
+        ensure_not_paused(&env);
+
+        // Verify executor is committee member
+        Self::verify_committee_member(&env, &executor);
+
+        if Self::get_limit_order(env.clone(), trade_id.clone()).is_some() {
+            panic!("Limit orders execute through execute_limit_order");
+        }
+
+        Self::fill_trade(&env, &executor, &trade_id, amount)
+    }
+
+    /// Get how much of a trade has been filled so far, and at what weighted price
+    pub fn get_trade_fill(env: Env, trade_id: BytesN<32>) -> Option<TradeFill> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TradeFill(trade_id))
+    }
+
+    /// Get the input amount of an open trade still to be filled; zero once it is closed
+    pub fn get_trade_remaining(env: Env, trade_id: BytesN<32>) -> u128 {
+        let trade_params: Option<TradeParams> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TradeProposal(trade_id.clone()));
+        let Some(trade_params) = trade_params else {
+            return 0;
+        };
+        let filled_in = Self::get_trade_fill(env, trade_id).map_or(0, |fill| fill.filled_in);
+        trade_params.amount_in - filled_in
+    }
+
+    /// Withdraw a trade proposal that has not been executed (its proposer only)
+    pub fn cancel_trade(env: Env, proposer: Address, trade_id: BytesN<32>) {
+        proposer.require_auth();
+
+        let stored_proposer: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TradeProposer(trade_id.clone()))
+            .unwrap_or_else(|| panic!("Trade proposal not found"));
+        if proposer != stored_proposer {
+            panic!("Only the proposer can cancel a trade");
+        }
+
+        Self::remove_trade_proposal(&env, &trade_id);
+
+        log!(
+            &env,
+            "Trade proposal {} cancelled by {}",
+            trade_id,
+            proposer
+        );
+    }
+
+    /// Delete trade proposals past their deadline from a page of open trades (permissionless)
+    ///
+    /// Scans up to `limit` open trades, capped at `TRADE_SWEEP_PAGE_SIZE`, from position
+    /// `cursor`; continue from `next_cursor` until it is None. Pays the keeper the
+    /// configured bounty for each proposal removed.
+    pub fn sweep_expired_trades(env: Env, keeper: Address, cursor: u32, limit: u32) -> TradeSweep {
+        keeper.require_auth();
+
+        let current_time = env.ledger().timestamp();
+        let open_trades = Self::get_open_trades(env.clone());
+        let start = cursor.min(open_trades.len());
+        let end = (start + limit.min(TRADE_SWEEP_PAGE_SIZE)).min(open_trades.len());
+
+        let mut remaining = open_trades.slice(..start);
+        let mut swept: u32 = 0;
+        for trade_id in open_trades.slice(start..end).iter() {
+            let trade_params: Option<TradeParams> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::TradeProposal(trade_id.clone()));
+            let expired = match &trade_params {
+                Some(trade_params) => current_time > trade_params.deadline,
+                None => true,
+            };
+            if !expired {
+                remaining.push_back(trade_id);
+                continue;
+            }
+
+            env.storage()
+                .persistent()
+                .remove(&DataKey::TradeProposal(trade_id.clone()));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::TradeProposer(trade_id));
+            swept += 1;
+        }
+        // Positions after the page shift down by the proposals swept
+        let next_cursor = if end < open_trades.len() {
+            Some(remaining.len())
+        } else {
+            None
+        };
+        remaining.append(&open_trades.slice(end..));
+        Self::store_open_trades(&env, &remaining);
+
+        if let Some(bounty) = Self::get_trade_sweep_bounty(env.clone()) {
+            let payout = bounty.amount * swept as u128;
+            if payout > 0 {
+                transfer_or_abort(
+                    &env,
+                    &bounty.token,
+                    &env.current_contract_address(),
+                    &keeper,
+                    payout as i128,
+                );
+            }
+        }
+
+        log!(
+            &env,
+            "{} expired trade proposals swept by {}",
+            swept,
+            keeper
+        );
+
+        TradeSweep { swept, next_cursor }
+    }
+
+    /// Set the bounty paid from governance funds per expired trade swept (admin only)
+    pub fn set_trade_sweep_bounty(env: Env, admin: Address, token: Address, amount: u128) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the trade sweep bounty");
+        }
+
+        let bounty = KeeperBounty { token, amount };
+        env.storage()
+            .instance()
+            .set(&DataKey::TradeSweepBounty, &bounty);
+
+        log!(
+            &env,
+            "Trade sweep bounty set to {} of {}",
+            bounty.amount,
+            bounty.token
+        );
+    }
+
+    /// Get the bounty paid per expired trade swept, if one is set
+    pub fn get_trade_sweep_bounty(env: Env) -> Option<KeeperBounty> {
+        env.storage().instance().get(&DataKey::TradeSweepBounty)
+    }
+
+    /// Get the IDs of trade proposals not yet executed, cancelled or swept
+    pub fn get_open_trades(env: Env) -> Vec<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::OpenTrades)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the execution record of a completed trade
+    pub fn get_trade_record(env: Env, trade_id: BytesN<32>) -> Option<TradeRecord> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TradeRecord(trade_id))
+    }
+
+    /// Get the limit price and approvals of a limit order
+    pub fn get_limit_order(env: Env, trade_id: BytesN<32>) -> Option<LimitOrder> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TradeLimit(trade_id))
+    }
+
+    /// Propose a period's realized profits for distribution (committee members only)
+    ///
+    /// The reporter escrows the whole profit in USDC with governance. Nothing is
+    /// distributed until `REQUIRED_COMMITTEE_APPROVALS` members approve the report,
+    /// and only one report can be made per epoch.
+    pub fn submit_profit_report(
+        env: Env,
+        reporter: Address,
+        coffee_lending_profit: u128,
+        trading_profit: u128,
+    ) -> u64 {
+        reporter.require_auth();
+
+        // Verify reporter is committee member
+        Self::verify_committee_member(&env, &reporter);
+
+        let epoch = Self::get_epoch_count(env.clone());
+        let reported_epoch: Option<u64> = env.storage().instance().get(&DataKey::ReportedEpoch);
+        if reported_epoch == Some(epoch) {
+            panic!("A profit report was already made this epoch");
+        }
+
+        // Interest and fees settled by early repayments ride on the next report
+        let prepayment_income = Self::get_prepayment_income(env.clone());
+        let coffee_lending_profit = coffee_lending_profit + prepayment_income;
+
+        let total_profit = coffee_lending_profit + trading_profit;
+        if total_profit == 0 {
+            panic!("Profit report must carry some profit");
+        }
+        let protocol_fee = (total_profit * PROTOCOL_FEE_BASIS_POINTS) / 10000;
+
+        // The profit backs the yield it will distribute, so it is escrowed up front
+        let usdc = Self::usdc_contract(&env);
+        transfer_or_abort(
+            &env,
+            &usdc,
+            &reporter,
+            &env.current_contract_address(),
+            total_profit as i128,
+        );
+
+        let report = ProfitReport {
+            total_profit,
+            coffee_lending_profit,
+            trading_profit,
+            yield_distributed: total_profit - protocol_fee,
+            protocol_fee,
+            timestamp: env.ledger().timestamp(),
+        };
+
+        let report_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProfitReportCounter)
+            .unwrap_or(0);
+        let report_id = report_counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ProfitReportCounter, &report_id);
+        env.storage()
+            .instance()
+            .set(&DataKey::ReportedEpoch, &epoch);
+        extend_instance_ttl(&env);
+
+        let proposal = ProfitReportProposal {
+            report,
+            reporter: reporter.clone(),
+            epoch,
+            prepayment_income,
+            approvals: 0,
+            status: ProposalStatus::Pending,
+        };
+        Self::store_profit_report_proposal(&env, report_id, &proposal);
+
+        log!(
+            &env,
+            "Profit report {} submitted by {} for epoch {}: total ${}, protocol fee ${}",
+            report_id,
+            reporter,
+            epoch,
+            total_profit,
+            protocol_fee
+        );
+
+        report_id
+    }
+
+    /// Approve a proposed profit report; distributes it once enough committee members approve
+    ///
+    /// On the final approval the depositor share is paid into the USDC vault before
+    /// the yield index moves, and the protocol fee goes to the treasury when one is
+    /// configured.
+    pub fn approve_profit_report(env: Env, approver: Address, report_id: u64) {
+        approver.require_auth();
+
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+
+        let mut proposal = Self::get_profit_report_proposal(env.clone(), report_id)
+            .unwrap_or_else(|| panic!("Profit report not found"));
+        if proposal.status != ProposalStatus::Pending {
+            panic!("Profit report is no longer pending");
+        }
+
+        let approval_key = DataKey::ProfitReportApproval(report_id, member);
+        if env.storage().persistent().has(&approval_key) {
+            panic!("Member has already approved this profit report");
+        }
+        env.storage().persistent().set(&approval_key, &true);
+        extend_persistent_ttl(&env, &approval_key);
+        proposal.approvals += 1;
+
+        if proposal.approvals >= REQUIRED_COMMITTEE_APPROVALS {
+            Self::distribute_profit_report(&env, report_id, &proposal);
+            proposal.status = ProposalStatus::Executed;
+        }
+        Self::store_profit_report_proposal(&env, report_id, &proposal);
+
+        log!(
+            &env,
+            "Profit report {} approved by {}. Approvals: {}/{}",
+            report_id,
+            approver,
+            proposal.approvals,
+            REQUIRED_COMMITTEE_APPROVALS
+        );
+    }
+
+    /// Withdraw a pending profit report and reclaim its escrow (original reporter only)
+    ///
+    /// Frees the epoch for a corrected report.
+    pub fn withdraw_profit_report(env: Env, reporter: Address, report_id: u64) -> u128 {
+        reporter.require_auth();
+
+        let mut proposal = Self::get_profit_report_proposal(env.clone(), report_id)
+            .unwrap_or_else(|| panic!("Profit report not found"));
+        if proposal.reporter != reporter {
+            panic!("Only the reporter can withdraw a profit report");
+        }
+        if proposal.status != ProposalStatus::Pending {
+            panic!("Profit report is no longer pending");
+        }
+
+        proposal.status = ProposalStatus::Rejected;
+        Self::store_profit_report_proposal(&env, report_id, &proposal);
+        let reported_epoch: Option<u64> = env.storage().instance().get(&DataKey::ReportedEpoch);
+        if reported_epoch == Some(proposal.epoch) {
+            env.storage().instance().remove(&DataKey::ReportedEpoch);
+        }
+
+        let refund = proposal.report.total_profit;
+        transfer_or_abort(
+            &env,
+            &Self::usdc_contract(&env),
+            &env.current_contract_address(),
+            &reporter,
+            refund as i128,
+        );
+
+        log!(
+            &env,
+            "Profit report {} withdrawn by {}",
+            report_id,
+            reporter
+        );
+
+        refund
+    }
+
+    /// Get a profit report with its approval status, distributed or not
+    pub fn get_profit_report_proposal(env: Env, report_id: u64) -> Option<ProfitReportProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ProfitReportProposal(report_id))
+    }
+
+    /// Close the current epoch and publish its summary event (permissionless)
+    ///
+    /// Epochs follow the yield token's rebase interval. The single `epoch` event
+    /// carries vault TVL, yield distributed, fees collected and active loans, so
+    /// off-chain reports need not aggregate the individual events.
+    pub fn close_epoch(env: Env) -> EpochSummary {
+        let started_at: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochStartedAt)
+            .unwrap_or(0);
+        let current_time = env.ledger().timestamp();
+        if current_time < started_at + EPOCH_DURATION {
+            panic!("Epoch does not end until: {}", started_at + EPOCH_DURATION);
+        }
+
+        // Missing vaults report nothing rather than blocking the summary
+        let usdc_vault = lookup_registry(&env, &DataKey::Registry, REGISTRY_USDC_VAULT);
+        let gold_vault = lookup_registry(&env, &DataKey::Registry, REGISTRY_GOLD_VAULT);
+        let usdc_vault_tvl = Self::query_vault(&env, &usdc_vault, "get_vault_balance");
+        let gold_vault_tvl = Self::query_vault(&env, &gold_vault, "get_vault_balance");
+
+        // Vault penalties are cumulative, so the epoch's share is the change since the last mark
+        let penalty_total = Self::query_vault(&env, &usdc_vault, "get_fees_routed")
+            + Self::query_vault(&env, &usdc_vault, "get_unrouted_fees");
+        let penalty_mark: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochPenaltyMark)
+            .unwrap_or(0);
+        let usdc_yield_paid = Self::take_yield_paid(&env, &usdc_vault);
+        let gold_yield_paid = Self::take_yield_paid(&env, &gold_vault);
+
+        let epoch: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochCounter)
+            .unwrap_or(0)
+            + 1;
+        let summary = EpochSummary {
+            epoch,
+            started_at,
+            ended_at: current_time,
+            usdc_vault_tvl,
+            gold_vault_tvl,
+            yield_distributed: env
+                .storage()
+                .instance()
+                .get(&DataKey::EpochYield)
+                .unwrap_or(0),
+            usdc_yield_paid,
+            gold_yield_paid,
+            protocol_fees: env
+                .storage()
+                .instance()
+                .get(&DataKey::EpochProtocolFees)
+                .unwrap_or(0),
+            penalty_fees: penalty_total.saturating_sub(penalty_mark),
+            active_loans: Self::get_active_loans(env.clone()),
+        };
+
+        env.storage().instance().set(&DataKey::EpochCounter, &epoch);
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochStartedAt, &current_time);
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochPenaltyMark, &penalty_total);
+        env.storage().instance().remove(&DataKey::EpochYield);
+        env.storage().instance().remove(&DataKey::EpochProtocolFees);
+        env.storage()
+            .persistent()
+            .set(&DataKey::EpochSummary(epoch), &summary);
+        extend_persistent_ttl(&env, &DataKey::EpochSummary(epoch));
+        extend_instance_ttl(&env);
+
+        // Each contract hashes its own accounting state; unreachable ones are left out
+        let yield_token = lookup_registry(&env, &DataKey::Registry, REGISTRY_YIELD_TOKEN)
+            .or_else(|| env.storage().instance().get(&DataKey::YieldToken));
+        let mut digests = Vec::new(&env);
+        digests.push_back(StateDigest {
+            contract: env.current_contract_address(),
+            digest: Self::state_digest(env.clone()),
+        });
+        for contract in [usdc_vault, gold_vault, yield_token].into_iter().flatten() {
+            if let Some(digest) = try_invoke_peer(&env, &contract, "state_digest", Vec::new(&env)) {
+                digests.push_back(StateDigest { contract, digest });
+            }
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::EpochDigests(epoch), &digests);
+        extend_persistent_ttl(&env, &DataKey::EpochDigests(epoch));
+
+        env.events()
+            .publish((symbol_short!("digest"), epoch), digests);
+        env.events()
+            .publish((symbol_short!("epoch"), epoch), summary.clone());
+
+        log!(
+            &env,
+            "Epoch {} closed: USDC TVL ${}, gold TVL ${}, yield ${}, fees ${} + ${}, {} active loans",
+            epoch,
+            summary.usdc_vault_tvl,
+            summary.gold_vault_tvl,
+            summary.yield_distributed,
+            summary.protocol_fees,
+            summary.penalty_fees,
+            summary.active_loans
+        );
+
+        summary
+    }
+
+    /// Get a closed epoch's summary
+    pub fn get_epoch_summary(env: Env, epoch: u64) -> Option<EpochSummary> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EpochSummary(epoch))
+    }
+
+    /// Get the last `count` closed epoch summaries, oldest first
+    ///
+    /// `count` is capped at `EPOCH_SUMMARY_PAGE_SIZE`. The yield token's `current_apy`
+    /// reads its window from here.
+    pub fn get_recent_epoch_summaries(env: Env, count: u32) -> Vec<EpochSummary> {
+        let epoch_count = Self::get_epoch_count(env.clone());
+        let first = epoch_count.saturating_sub(count.min(EPOCH_SUMMARY_PAGE_SIZE) as u64) + 1;
+
+        let mut summaries = Vec::new(&env);
+        for epoch in first..=epoch_count {
+            if let Some(summary) = Self::get_epoch_summary(env.clone(), epoch) {
+                summaries.push_back(summary);
+            }
+        }
+        summaries
+    }
+
+    /// Get the state digests recorded when an epoch closed
+    pub fn get_epoch_digests(env: Env, epoch: u64) -> Vec<StateDigest> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EpochDigests(epoch))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the stored accounting figures the state digest is computed over
+    ///
+    /// Active loans, the principal outstanding across them and unrecovered bad debt.
+    pub fn accounting_state(env: Env) -> Map<Symbol, u128> {
+        let mut figures = Map::new(&env);
+        figures.set(
+            symbol_short!("loans"),
+            Self::get_active_loans(env.clone()) as u128,
+        );
+        figures.set(
+            symbol_short!("principal"),
+            Self::get_outstanding_principal(env.clone()),
+        );
+        figures.set(symbol_short!("bad_debt"), Self::total_bad_debt(env));
+        figures
+    }
+
+    /// Get the SHA-256 digest of `accounting_state()`, recorded here each epoch
+    pub fn state_digest(env: Env) -> BytesN<32> {
+        digest_accounting(&env, &Self::accounting_state(env.clone()))
+    }
+
+    /// Get the number of closed epochs
+    pub fn get_epoch_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::EpochCounter)
+            .unwrap_or(0)
+    }
+
+    /// Get a distributed profit report
+    pub fn get_profit_report(env: Env, report_id: u64) -> Option<ProfitReport> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ProfitReport(report_id))
+    }
+
+    /// Record a deficit from an under-recovered liquidation or gold shortfall
+    ///
+    /// Callable by committee members or by registered reporter contracts.
+    pub fn record_bad_debt(
+        env: Env,
+        reporter: Address,
+        source: BadDebtSource,
+        reference: BytesN<32>,
+        vault: Address,
+        amount: u128,
+    ) -> u64 {
+        reporter.require_auth();
+
+        let reporters: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::BadDebtReporters)
+            .unwrap_or(Vec::new(&env));
+        if !reporters.contains(&reporter) {
+            Self::verify_committee_member(&env, &reporter);
+        }
+
+        // Gold collateral seized before the shortfall was recorded has already covered part of it
+        let amount = match (
+            &source,
+            Self::get_gold_seizure(env.clone(), reference.clone()),
+        ) {
+            (BadDebtSource::LiquidationShortfall, Some(seizure)) => {
+                amount.saturating_sub(seizure.recovered_usd)
+            }
+            _ => amount,
+        };
+        if amount == 0 {
+            panic!("Bad debt amount must be greater than 0");
+        }
+
+        let debt_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::BadDebtCounter)
+            .unwrap_or(0);
+        let record_id = debt_counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::BadDebtCounter, &record_id);
+
+        let record = BadDebtRecord {
+            id: record_id,
+            source,
+            reference,
+            vault,
+            amount,
+            recorded_at: env.ledger().timestamp(),
+            write_off_funding: None,
+            approvals: 0,
+            status: ProposalStatus::Pending,
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BadDebt(record_id), &record);
+        extend_persistent_ttl(&env, &DataKey::BadDebt(record_id));
+        extend_instance_ttl(&env);
+
+        let total_bad_debt: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::BadDebtTotal)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::BadDebtTotal, &(total_bad_debt + amount));
+
+        // A liquidation shortfall is the defaulted part of the referenced loan
+        if record.source == BadDebtSource::LiquidationShortfall {
+            let loan: Option<LoanProposal> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::LoanProposal(record.reference.clone()));
+            if let Some(loan) = loan {
+                let mut history = Self::get_credit_history(env.clone(), loan.borrower.clone());
+                history.defaulted += amount;
+                Self::store_credit_history(&env, &loan.borrower, history);
+                // Liquidation closes the loan whatever principal remained
+                Self::reduce_loan_outstanding(&env, &record.reference, u128::MAX);
+                env.storage()
+                    .persistent()
+                    .set(&DataKey::LoanBadDebt(record.reference.clone()), &record_id);
+                extend_persistent_ttl(&env, &DataKey::LoanBadDebt(record.reference.clone()));
+            }
+        }
+
+        log!(
+            &env,
+            "Bad debt {} recorded by {}: ${} ({:?}) in vault {}",
+            record_id,
+            reporter,
+            amount,
+            record.source,
+            record.vault
+        );
+
+        record_id
+    }
+
+    /// Propose how a bad-debt record will be written off (committee members only)
+    pub fn propose_write_off(
+        env: Env,
+        proposer: Address,
+        record_id: u64,
+        funding: WriteOffFunding,
+    ) {
+        proposer.require_auth();
+
+        // Verify proposer is committee member
+        Self::verify_committee_member(&env, &proposer);
+
+        let mut record = Self::get_bad_debt_or_panic(&env, record_id);
+
+        if record.status != ProposalStatus::Pending {
+            panic!("Bad debt has already been written off");
+        }
+
+        if record.write_off_funding.is_some() {
+            panic!("Write-off already proposed for this bad debt");
+        }
+
+        record.write_off_funding = Some(funding.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::BadDebt(record_id), &record);
+
+        log!(
+            &env,
+            "Write-off of bad debt {} proposed by {} funded by {:?}",
+            record_id,
+            proposer,
+            funding
+        );
+    }
+
+    /// Approve a proposed write-off; executes once enough committee members approve
+    pub fn approve_write_off(env: Env, approver: Address, record_id: u64) {
+        approver.require_auth();
+
+        // An active delegate's approval counts as the delegating member's
+        let member = Self::approving_member(&env, &approver);
+
+        let mut record = Self::get_bad_debt_or_panic(&env, record_id);
+
+        if record.status != ProposalStatus::Pending {
+            panic!("Bad debt has already been written off");
+        }
+
+        let funding = record
+            .write_off_funding
+            .clone()
+            .unwrap_or_else(|| panic!("No write-off proposed for this bad debt"));
+
+        let approval_key = DataKey::WriteOffApproval(record_id, member);
+        if env.storage().persistent().has(&approval_key) {
+            panic!("Member has already approved this write-off");
+        }
+
+        env.storage().persistent().set(&approval_key, &true);
+        record.approvals += 1;
+
+        if record.approvals >= REQUIRED_COMMITTEE_APPROVALS {
+            match funding {
+                WriteOffFunding::InsuranceFund(token) => {
+                    // Make the affected vault whole; the insurance fund must authorize this transfer
+                    let insurance_fund: Address = env
+                        .storage()
+                        .instance()
+                        .get(&DataKey::InsuranceFund)
+                        .unwrap_or_else(|| panic!("Insurance fund not configured"));
+                    transfer_or_abort(
+                        &env,
+                        &token,
+                        &insurance_fund,
+                        &record.vault,
+                        record.amount as i128,
+                    );
+                }
+                WriteOffFunding::FutureFees => {
+                    let fee_write_off: u128 = env
+                        .storage()
+                        .instance()
+                        .get(&DataKey::FeeWriteOff)
+                        .unwrap_or(0);
+                    env.storage()
+                        .instance()
+                        .set(&DataKey::FeeWriteOff, &(fee_write_off + record.amount));
+                }
+            }
+
+            let total_bad_debt: u128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::BadDebtTotal)
+                .unwrap_or(0);
+            env.storage()
+                .instance()
+                .set(&DataKey::BadDebtTotal, &(total_bad_debt - record.amount));
+
+            record.status = ProposalStatus::Executed;
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::BadDebt(record_id), &record);
+
+        log!(
+            &env,
+            "Write-off of bad debt {} approved by {}. Approvals: {}/{}",
+            record_id,
+            approver,
+            record.approvals,
+            REQUIRED_COMMITTEE_APPROVALS
+        );
+    }
+
+    /// Get outstanding (not yet written off) bad debt
+    pub fn total_bad_debt(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::BadDebtTotal)
+            .unwrap_or(0)
+    }
+
+    /// Get written-off bad debt still to be covered by future protocol fees
+    pub fn get_pending_fee_write_off(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeeWriteOff)
+            .unwrap_or(0)
+    }
+
+    /// Get a bad-debt record
+    pub fn get_bad_debt(env: Env, record_id: u64) -> Option<BadDebtRecord> {
+        env.storage().persistent().get(&DataKey::BadDebt(record_id))
+    }
+
+    /// Allow or disallow a contract to record bad debt, e.g. the gold vault (admin only)
+    pub fn set_bad_debt_reporter(env: Env, admin: Address, reporter: Address, allowed: bool) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can manage bad debt reporters");
+        }
+
+        let reporters: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::BadDebtReporters)
+            .unwrap_or(Vec::new(&env));
+        let mut updated = Vec::new(&env);
+        for existing in reporters.iter() {
+            if existing != reporter {
+                updated.push_back(existing);
+            }
+        }
+        if allowed {
+            updated.push_back(reporter.clone());
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::BadDebtReporters, &updated);
+
+        log!(&env, "Bad debt reporter {} allowed: {}", reporter, allowed);
+    }
+
+    /// Set the treasury account that pays committee stipends (admin only)
+    ///
+    /// A treasury in the registry takes precedence.
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the treasury");
+        }
+
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+
+        log!(&env, "Treasury set to: {}", treasury);
+    }
+
+    /// Set the loan disbursement escrow used when the registry has none (admin only)
+    pub fn set_escrow(env: Env, admin: Address, escrow: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the escrow");
+        }
+
+        env.storage().instance().set(&DataKey::Escrow, &escrow);
+
+        log!(&env, "Loan escrow set to: {}", escrow);
+    }
+
+    /// Set the share of escrowed loan proceeds held until the pledged lot passes QA (admin only)
+    ///
+    /// Applies to loans disbursed afterwards, up to `MAX_QA_HOLDBACK_BPS`; zero turns it off.
+    pub fn set_qa_holdback(env: Env, admin: Address, holdback_bps: u32) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the QA holdback");
+        }
+        if holdback_bps > MAX_QA_HOLDBACK_BPS {
+            panic_with_error!(&env, ContractError::ParameterOutOfBounds);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::QaHoldbackBps, &holdback_bps);
+
+        log!(&env, "QA holdback set to {} bps", holdback_bps);
+    }
+
+    /// Get the QA holdback in basis points; nothing is held back by default
+    pub fn get_qa_holdback(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::QaHoldbackBps)
+            .unwrap_or(0)
+    }
+
+    /// Set the insurance fund account that pays write-offs (admin only)
+    pub fn set_insurance_fund(env: Env, admin: Address, insurance_fund: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the insurance fund");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::InsuranceFund, &insurance_fund);
+
+        log!(&env, "Insurance fund set to: {}", insurance_fund);
+    }
+
+    /// DAO Governance: Propose parameter change
+    pub fn propose_parameter_change(
+        env: Env,
+        proposer: Address,
+        parameter: ProtocolParameter,
+        new_value: u128,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        if parameter == ProtocolParameter::TreasurySwap {
+            panic!("Treasury swaps are proposed with their terms");
+        }
+        if parameter == ProtocolParameter::FeeSwitch {
+            panic!("Fee switches are proposed with their stream and switch");
+        }
+
+        Self::open_proposal(&env, proposer, parameter, new_value).id
+    }
+
+    /// DAO Governance: Propose a parameter change that may only execute after other proposals
+    ///
+    /// Each prerequisite must be an existing proposal that has not been rejected, and
+    /// must have been executed before this one can be, so a multi-step change such as
+    /// raising `MaximumYieldRate` before a higher vault rate cannot run out of order.
+    pub fn propose_dependent_change(
+        env: Env,
+        proposer: Address,
+        parameter: ProtocolParameter,
+        new_value: u128,
+        prerequisites: Vec<BytesN<32>>,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        if parameter == ProtocolParameter::TreasurySwap {
+            panic!("Treasury swaps are proposed with their terms");
+        }
+        if parameter == ProtocolParameter::FeeSwitch {
+            panic!("Fee switches are proposed with their stream and switch");
+        }
+        if prerequisites.is_empty() {
+            panic!("Dependent proposal needs at least one prerequisite");
+        }
+        if prerequisites.len() > MAX_PROPOSAL_PREREQUISITES {
+            panic!("Too many prerequisite proposals");
+        }
+        for (i, prerequisite_id) in prerequisites.iter().enumerate() {
+            if prerequisites
+                .iter()
+                .skip(i + 1)
+                .any(|other| other == prerequisite_id)
+            {
+                panic!("Duplicate prerequisite proposal");
+            }
+            let prerequisite: GovernanceProposal = env
+                .storage()
+                .persistent()
+                .get(&DataKey::GovernanceProposal(prerequisite_id))
+                .unwrap_or_else(|| panic!("Prerequisite proposal not found"));
+            if prerequisite.status == ProposalStatus::Rejected {
+                panic!("Prerequisite proposal was rejected");
+            }
+        }
+
+        let proposal = Self::open_proposal(&env, proposer, parameter, new_value);
+        let key = DataKey::ProposalPrerequisites(proposal.id.clone());
+        env.storage().persistent().set(&key, &prerequisites);
+        extend_persistent_ttl(&env, &key);
+
+        log!(
+            &env,
+            "Governance proposal {} waits on {} prerequisites",
+            proposal.id,
+            prerequisites.len()
+        );
+
+        proposal.id
+    }
+
+    /// Get the proposals that must be executed before a DAO proposal; empty when it has none
+    pub fn get_prerequisites(env: Env, proposal_id: BytesN<32>) -> Vec<BytesN<32>> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ProposalPrerequisites(proposal_id))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// DAO Governance: Propose that the treasury swap fee assets through the trade router
+    ///
+    /// Voted on like a parameter change under the `TreasurySwap` voting rules. The swap
+    /// must still be live when the vote ends and keep the treasury within its target
+    /// allocation, which is checked again at execution.
+    pub fn propose_treasury_swap(env: Env, proposer: Address, swap: TradeParams) -> BytesN<32> {
+        proposer.require_auth();
+
+        if swap.asset_in == swap.asset_out {
+            panic!("Treasury swap must change assets");
+        }
+        if swap.min_amount_out == 0 {
+            panic!("Treasury swap needs a minimum amount out");
+        }
+        Self::verify_treasury_allocation(&env, &swap);
+
+        let proposal = Self::open_proposal(
+            &env,
+            proposer,
+            ProtocolParameter::TreasurySwap,
+            swap.amount_in,
+        );
+        if swap.deadline <= proposal.voting_deadline {
+            panic!("Treasury swap expires before the vote ends");
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::TreasurySwap(proposal.id.clone()), &swap);
+        extend_persistent_ttl(&env, &DataKey::TreasurySwap(proposal.id.clone()));
+
+        proposal.id
+    }
+
+    /// Get the swap terms of a treasury swap proposal
+    pub fn get_treasury_swap(env: Env, proposal_id: BytesN<32>) -> Option<TradeParams> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::TreasurySwap(proposal_id))
+    }
+
+    /// DAO Governance: Propose turning a revenue stream on or off and setting its insurance share
+    ///
+    /// Voted on like a parameter change under the `FeeSwitch` voting rules. On execution
+    /// governance sets the switch on the registered USDC vault, which holds the only copy.
+    pub fn propose_fee_switch(
+        env: Env,
+        proposer: Address,
+        stream: RevenueStream,
+        switch: FeeSwitch,
+    ) -> BytesN<32> {
+        proposer.require_auth();
+
+        if lookup_registry(&env, &DataKey::Registry, REGISTRY_USDC_VAULT).is_none() {
+            panic!("No USDC vault is registered to apply the fee switch");
+        }
+
+        let proposal = Self::open_proposal(
+            &env,
+            proposer,
+            ProtocolParameter::FeeSwitch,
+            switch.insurance_bps as u128,
+        );
+        let key = DataKey::FeeSwitchChange(proposal.id.clone());
+        env.storage()
+            .persistent()
+            .set(&key, &FeeSwitchChange { stream, switch });
+        extend_persistent_ttl(&env, &key);
+
+        proposal.id
+    }
+
+    /// Get the stream and switch of a fee switch proposal
+    pub fn get_fee_switch_change(env: Env, proposal_id: BytesN<32>) -> Option<FeeSwitchChange> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::FeeSwitchChange(proposal_id))
+    }
+
+    /// Set the share of treasury value each asset should be held in (admin only)
+    ///
+    /// Targets must sum to 100%. Treasury swaps may only sell an asset down to its
+    /// target and buy one up to its target; assets outside the policy cannot be swapped.
+    pub fn set_treasury_allocation(env: Env, admin: Address, targets: Vec<TreasuryTarget>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the treasury allocation");
+        }
+
+        let mut total_bps: u32 = 0;
+        for (i, target) in targets.iter().enumerate() {
+            if targets
+                .iter()
+                .skip(i + 1)
+                .any(|other| other.asset == target.asset)
+            {
+                panic!("Duplicate treasury target asset");
+            }
+            total_bps += target.target_bps;
+        }
+        if total_bps as u128 != BASIS_POINTS {
+            panic!("Treasury targets must sum to 10000 basis points");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TreasuryAllocation, &targets);
+
+        log!(
+            &env,
+            "Treasury allocation set across {} assets",
+            targets.len()
+        );
+    }
+
+    /// Get the treasury's target allocation, empty until set
+    pub fn get_treasury_allocation(env: Env) -> Vec<TreasuryTarget> {
+        env.storage()
+            .instance()
+            .get(&DataKey::TreasuryAllocation)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Set the delay between submitting a DAO proposal and the opening of its vote (admin only)
+    pub fn set_review_period(env: Env, admin: Address, period: u64) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the review period");
+        }
+
+        if period > MAX_PROPOSAL_REVIEW_PERIOD {
+            panic!("Review period exceeds maximum");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ReviewPeriod, &period);
+
+        log!(&env, "Proposal review period set to {} seconds", period);
+    }
+
+    /// Get the review period before DAO votes open; votes open immediately by default
+    pub fn get_review_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ReviewPeriod)
+            .unwrap_or(0)
+    }
+
+    /// DAO Governance: Vote on parameter change
+    pub fn vote_on_proposal(env: Env, voter: Address, proposal_id: BytesN<32>, vote: VoteType) {
+        voter.require_auth();
+
+        let mut proposal: GovernanceProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GovernanceProposal(proposal_id.clone()))
+            .unwrap_or_else(|| panic!("Governance proposal not found"));
+
+        let current_time = deadline_time(&env);
+        if current_time < proposal.voting_start {
+            panic!("Voting has not opened yet");
+        }
+
+        if current_time > proposal.voting_deadline {
+            panic!("Voting period has ended");
+        }
+
+        if proposal.status != ProposalStatus::Pending {
+            panic!("Proposal is not active for voting");
+        }
+
+        // Check if already voted
+        let vote_key = DataKey::Vote(proposal_id.clone(), voter.clone());
+        if env.storage().persistent().has(&vote_key) {
+            panic!("User has already voted on this proposal");
+        }
+
+        let voting_power = Self::get_voting_power(&env, &voter);
+
+        match vote {
+            VoteType::For => proposal.votes_for += voting_power,
+            VoteType::Against => proposal.votes_against += voting_power,
+            VoteType::Abstain => proposal.votes_abstain += voting_power,
+        }
+
+        // Record vote
+        env.storage().persistent().set(&vote_key, &vote);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::GovernanceProposal(proposal_id.clone()), &proposal);
+
+        log!(
+            &env,
+            "User {} voted {:?} on proposal {} with {} voting power",
+            voter,
+            vote,
+            proposal_id,
+            voting_power
+        );
+    }
+
+    /// Execute approved governance proposal
+    ///
+    /// With a timelock in the registry, a passing proposal is queued there instead
+    /// and applied when the timelock executes it after its delay.
+    pub fn execute_governance_proposal(env: Env, executor: Address, proposal_id: BytesN<32>) {
+        executor.require_auth();
+
+        let mut proposal: GovernanceProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GovernanceProposal(proposal_id.clone()))
+            .unwrap_or_else(|| panic!("Governance proposal not found"));
+
+        if proposal.status != ProposalStatus::Pending {
+            panic!("Proposal is not pending");
+        }
+
+        if deadline_time(&env) <= proposal.voting_deadline {
+            panic!("Voting period has not ended");
+        }
+
+        // Left pending rather than rejected, so it can run once its prerequisites have
+        for prerequisite_id in Self::get_prerequisites(env.clone(), proposal_id.clone()).iter() {
+            let prerequisite: GovernanceProposal = env
+                .storage()
+                .persistent()
+                .get(&DataKey::GovernanceProposal(prerequisite_id))
+                .unwrap();
+            if prerequisite.status != ProposalStatus::Executed {
+                panic!("Prerequisite proposal has not been executed");
+            }
+        }
+
+        if !Self::tally(&env, &proposal).quorum_reached {
+            proposal.status = ProposalStatus::Rejected;
+            env.storage()
+                .persistent()
+                .set(&DataKey::GovernanceProposal(proposal_id.clone()), &proposal);
+            panic!("Proposal did not reach quorum");
+        }
+
+        let threshold_bps = Self::get_pass_threshold(env.clone(), proposal_id.clone()) as u128;
+        let decisive_votes = proposal.votes_for + proposal.votes_against;
+        if proposal.votes_for * BASIS_POINTS <= decisive_votes * threshold_bps {
+            proposal.status = ProposalStatus::Rejected;
+            env.storage()
+                .persistent()
+                .set(&DataKey::GovernanceProposal(proposal_id.clone()), &proposal);
+            panic!("Proposal was rejected by vote");
+        }
+
+        // Proposals submitted before the bounds existed are caught here
+        Self::verify_parameter_bounds(&env, &proposal.parameter, proposal.new_value);
+
+        if let Some(timelock) = lookup_registry(&env, &DataKey::Registry, REGISTRY_TIMELOCK) {
+            proposal.status = ProposalStatus::Approved;
+            env.storage()
+                .persistent()
+                .set(&DataKey::GovernanceProposal(proposal_id.clone()), &proposal);
+
+            let delay: u64 = invoke_peer(
+                &env,
+                &timelock,
+                "get_min_delay",
+                ().into_val(&env),
+                ContractError::PeerCallFailed,
+            );
+            let args: Vec<Val> = vec![
+                &env,
+                timelock.into_val(&env),
+                proposal_id.into_val(&env),
+                executor.into_val(&env),
+            ];
+            let operation_id: u64 = invoke_peer(
+                &env,
+                &timelock,
+                "schedule",
+                (
+                    env.current_contract_address(),
+                    env.current_contract_address(),
+                    Symbol::new(&env, "apply_governance_proposal"),
+                    args,
+                    delay,
+                )
+                    .into_val(&env),
+                ContractError::PeerCallFailed,
+            );
+
+            log!(
+                &env,
+                "Governance proposal {} queued as timelock operation {}",
+                proposal_id,
+                operation_id
+            );
+            return;
+        }
+
+        Self::apply_governance_change(&env, &mut proposal, &executor);
+    }
+
+    /// Apply a proposal queued by `execute_governance_proposal` (timelock only)
+    ///
+    /// Called by the timelock's `execute` once the delay has passed; `executor` is
+    /// whoever queued it and is recorded in the changelog.
+    pub fn apply_governance_proposal(
+        env: Env,
+        timelock: Address,
+        proposal_id: BytesN<32>,
+        executor: Address,
+    ) {
+        timelock.require_auth();
+
+        if lookup_registry(&env, &DataKey::Registry, REGISTRY_TIMELOCK) != Some(timelock) {
+            panic!("Only the timelock can apply queued proposals");
+        }
+
+        let mut proposal: GovernanceProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GovernanceProposal(proposal_id))
+            .unwrap_or_else(|| panic!("Governance proposal not found"));
+        if proposal.status != ProposalStatus::Approved {
+            panic!("Proposal is not queued");
+        }
+
+        Self::apply_governance_change(&env, &mut proposal, &executor);
+    }
+
+    /// Get executed DAO proposals, oldest first
+    ///
+    /// `limit` is capped at `CHANGELOG_PAGE_SIZE`.
+    pub fn get_changelog(env: Env, offset: u64, limit: u32) -> Vec<ChangelogEntry> {
+        let count = Self::get_changelog_count(env.clone());
+        let start = offset.min(count);
+        let end = (start + limit.min(CHANGELOG_PAGE_SIZE) as u64).min(count);
+
+        let mut changelog = Vec::new(&env);
+        for index in start..end {
+            if let Some(entry) = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ChangelogEntry(index))
+            {
+                changelog.push_back(entry);
+            }
+        }
+        changelog
+    }
+
+    /// Get the number of executed DAO proposals in the changelog
+    pub fn get_changelog_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::ChangelogCount)
+            .unwrap_or(0)
+    }
+
+    /// Get the value the last executed proposal set a parameter to, if any
+    ///
+    /// Treasury swaps are one-off amounts and never set a value.
+    pub fn get_parameter_value(env: Env, parameter: ProtocolParameter) -> Option<u128> {
+        env.storage()
+            .instance()
+            .get(&DataKey::ParameterValue(parameter))
+    }
+
+    /// Set or clear the voting period, threshold and proposal minimum for a parameter (admin only)
+    ///
+    /// Without rules a parameter is voted on for `DEFAULT_VOTING_PERIOD`, passes by
+    /// simple majority and needs the proposal minimum set at initialization.
+    pub fn set_voting_rules(
+        env: Env,
+        admin: Address,
+        parameter: ProtocolParameter,
+        rules: Option<VotingRules>,
+    ) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set voting rules");
+        }
+
+        let key = DataKey::VotingRules(parameter.clone());
+        match rules {
+            Some(rules) => {
+                if !(MIN_VOTING_PERIOD..=MAX_VOTING_PERIOD).contains(&rules.voting_period)
+                    || !(SIMPLE_MAJORITY_BPS..=MAX_PASS_THRESHOLD_BPS)
+                        .contains(&rules.pass_threshold_bps)
+                    || rules.min_proposal_tokens == 0
+                {
+                    panic_with_error!(&env, ContractError::ParameterOutOfBounds);
+                }
+                env.storage().instance().set(&key, &rules);
+
+                log!(
+                    &env,
+                    "Votes on {:?} run {}s, pass above {} bps and need {} tokens to propose",
+                    parameter,
+                    rules.voting_period,
+                    rules.pass_threshold_bps,
+                    rules.min_proposal_tokens
+                );
+            }
+            None => {
+                env.storage().instance().remove(&key);
+
+                log!(
+                    &env,
+                    "Voting rules for {:?} reset to the defaults",
+                    parameter
+                );
+            }
+        }
+    }
+
+    /// Get the voting rules a new proposal for the parameter would be held to
+    pub fn get_voting_rules(env: Env, parameter: ProtocolParameter) -> VotingRules {
+        env.storage()
+            .instance()
+            .get(&DataKey::VotingRules(parameter))
+            .unwrap_or_else(|| VotingRules {
+                voting_period: DEFAULT_VOTING_PERIOD,
+                pass_threshold_bps: SIMPLE_MAJORITY_BPS,
+                min_proposal_tokens: env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::MinProposalTokens)
+                    .unwrap(),
+            })
+    }
+
+    /// Get the pass threshold a DAO proposal was submitted under, in basis points
+    ///
+    /// Proposals submitted before thresholds were recorded pass by simple majority.
+    pub fn get_pass_threshold(env: Env, proposal_id: BytesN<32>) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PassThreshold(proposal_id))
+            .unwrap_or(SIMPLE_MAJORITY_BPS)
+    }
+
+    /// Set the voting power that must take part, abstentions included, for a DAO proposal to pass (admin only)
+    pub fn set_quorum(env: Env, admin: Address, quorum: u128) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the quorum");
+        }
+
+        env.storage().instance().set(&DataKey::Quorum, &quorum);
+
+        log!(&env, "Governance quorum set to {}", quorum);
+    }
+
+    /// Get the DAO quorum in voting power; zero when none is set
+    pub fn get_quorum(env: Env) -> u128 {
+        env.storage().instance().get(&DataKey::Quorum).unwrap_or(0)
+    }
+
+    /// Get the for/against/abstain breakdown of a DAO proposal and whether it has reached quorum
+    pub fn get_vote_tally(env: Env, proposal_id: BytesN<32>) -> VoteTally {
+        let proposal: GovernanceProposal = env
+            .storage()
+            .persistent()
+            .get(&DataKey::GovernanceProposal(proposal_id))
+            .unwrap_or_else(|| panic!("Governance proposal not found"));
+        Self::tally(&env, &proposal)
+    }
+
+    /// Get how a voter voted on a DAO proposal
+    pub fn get_vote(env: Env, proposal_id: BytesN<32>, voter: Address) -> Option<VoteType> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Vote(proposal_id, voter))
+    }
+
+    /// Get loan proposal details
+    pub fn get_loan_proposal(env: Env, proposal_id: BytesN<32>) -> Option<LoanProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(proposal_id.clone()))
+    }
+
+    /// Get governance proposal details
+    pub fn get_governance_proposal(
+        env: Env,
+        proposal_id: BytesN<32>,
+    ) -> Option<GovernanceProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::GovernanceProposal(proposal_id.clone()))
+    }
+
+    /// Get committee members
+    pub fn get_committee_members(env: Env) -> Vec<CommitteeMember> {
+        env.storage()
+            .instance()
+            .get(&DataKey::CommitteeMembers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Let a committee member's approvals be given by a delegate for a limited time
+    ///
+    /// Replaces any earlier delegate. The delegate cannot itself sit on the committee
+    /// or act for another member.
+    pub fn set_committee_delegate(env: Env, member: Address, delegate: Address, duration: u64) {
+        member.require_auth();
+
+        // Verify member is committee member
+        Self::verify_committee_member(&env, &member);
+
+        if duration == 0 || duration > MAX_DELEGATION_DURATION {
+            panic!("Delegation duration out of range");
+        }
+        if Self::holds_seat(&env, &delegate) {
+            panic!("Delegate cannot be a committee member");
+        }
+        let keyed: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SessionKeyOf(delegate.clone()));
+        if let Some(other) = keyed {
+            if Self::get_session_key(env.clone(), other).is_some() {
+                panic!("Delegate cannot be a session key");
+            }
+        }
+        let delegating: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::DelegateOf(delegate.clone()));
+        if let Some(other) = delegating {
+            if other != member && Self::get_committee_delegate(env.clone(), other.clone()).is_some()
+            {
+                panic!("Address is already a delegate for another member");
+            }
+        }
+
+        Self::clear_committee_delegate(&env, &member);
+        let delegation = CommitteeDelegate {
+            delegate: delegate.clone(),
+            expires_at: env.ledger().timestamp() + duration,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::CommitteeDelegate(member.clone()), &delegation);
+        env.storage()
+            .persistent()
+            .set(&DataKey::DelegateOf(delegate.clone()), &member);
+        extend_persistent_ttl(&env, &DataKey::CommitteeDelegate(member.clone()));
+        extend_persistent_ttl(&env, &DataKey::DelegateOf(delegate.clone()));
+
+        log!(
+            &env,
+            "Committee member {} delegated approvals to {} until {}",
+            member,
+            delegate,
+            delegation.expires_at
+        );
+    }
+
+    /// Revoke a committee member's delegate before it expires
+    pub fn revoke_committee_delegate(env: Env, member: Address) {
+        member.require_auth();
+
+        Self::clear_committee_delegate(&env, &member);
+
+        log!(&env, "Committee member {} revoked their delegate", member);
+    }
+
+    /// Get a committee member's delegate, None once it has expired
+    pub fn get_committee_delegate(env: Env, member: Address) -> Option<CommitteeDelegate> {
+        let delegation: CommitteeDelegate = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CommitteeDelegate(member))?;
+        if env.ledger().timestamp() >= delegation.expires_at {
+            return None;
+        }
+        Some(delegation)
+    }
+
+    /// Register a session key that can approve or reject loans for a member for `ledgers` ledgers
+    ///
+    /// Lets day-to-day loan votes be signed from a hot device while the member key stays
+    /// cold; the key can do nothing else. Replaces any earlier session key.
+    pub fn register_session_key(env: Env, member: Address, key: Address, ledgers: u32) {
+        member.require_auth();
+
+        // Verify member is committee member
+        Self::verify_committee_member(&env, &member);
+
+        if ledgers == 0 || ledgers > MAX_SESSION_KEY_LEDGERS {
+            panic!("Session key lifetime out of range");
+        }
+        if Self::holds_seat(&env, &key) {
+            panic!("Session key cannot be a committee member");
+        }
+        let delegating: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::DelegateOf(key.clone()));
+        if let Some(other) = delegating {
+            if Self::get_committee_delegate(env.clone(), other).is_some() {
+                panic!("Session key cannot be a committee delegate");
+            }
+        }
+        let keyed: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SessionKeyOf(key.clone()));
+        if let Some(other) = keyed {
+            if other != member && Self::get_session_key(env.clone(), other.clone()).is_some() {
+                panic!("Address is already a session key for another member");
+            }
+        }
+
+        Self::clear_session_key(&env, &member);
+        let session = SessionKey {
+            key: key.clone(),
+            expires_ledger: env.ledger().sequence() + ledgers,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::SessionKey(member.clone()), &session);
+        env.storage()
+            .persistent()
+            .set(&DataKey::SessionKeyOf(key.clone()), &member);
+        extend_persistent_ttl(&env, &DataKey::SessionKey(member.clone()));
+        extend_persistent_ttl(&env, &DataKey::SessionKeyOf(key.clone()));
+
+        log!(
+            &env,
+            "Committee member {} registered session key {} until ledger {}",
+            member,
+            key,
+            session.expires_ledger
+        );
+    }
+
+    /// Revoke a committee member's session key before it expires
+    pub fn revoke_session_key(env: Env, member: Address) {
+        member.require_auth();
+
+        Self::clear_session_key(&env, &member);
+
+        log!(
+            &env,
+            "Committee member {} revoked their session key",
+            member
+        );
+    }
+
+    /// Get a committee member's session key, None once it has expired
+    pub fn get_session_key(env: Env, member: Address) -> Option<SessionKey> {
+        let session: SessionKey = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SessionKey(member))?;
+        if env.ledger().sequence() >= session.expires_ledger {
+            return None;
+        }
+        Some(session)
+    }
+
+    /// Update committee (admin only)
+    ///
+    /// Members already active keep their standing. Every other address is appointed
+    /// pending: it holds the seat but cannot act until it calls `accept_membership`
+    /// within `MEMBERSHIP_ACCEPTANCE_WINDOW`.
+    pub fn update_committee(env: Env, admin: Address, new_committee: Vec<CommitteeMember>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can update committee");
+        }
+
+        if new_committee.len() != TOTAL_COMMITTEE_SIZE {
+            panic!(
+                "Committee must have exactly {} members",
+                TOTAL_COMMITTEE_SIZE
+            );
+        }
+
+        // Standing is read from the outgoing committee before it is replaced
+        let expires_at = env.ledger().timestamp() + MEMBERSHIP_ACCEPTANCE_WINDOW;
+        let mut appointed = Vec::new(&env);
+        for member in new_committee.iter() {
+            if !Self::is_committee_member(&env, &member.address) {
+                appointed.push_back(member.address.clone());
+            }
+        }
+        for member in Self::get_committee_members(env.clone()).iter() {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::PendingMembership(member.address));
+        }
+        for address in appointed.iter() {
+            let key = DataKey::PendingMembership(address.clone());
+            env.storage().persistent().set(&key, &expires_at);
+            extend_persistent_ttl(&env, &key);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::CommitteeMembers, &new_committee);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Committee updated by admin; {} appointments await acceptance until {}",
+            appointed.len(),
+            expires_at
+        );
+    }
+
+    /// Accept an appointment to the committee, proving control of the appointed key
+    pub fn accept_membership(env: Env, member: Address) {
+        member.require_auth();
+
+        let expires_at = Self::get_pending_membership(env.clone(), member.clone())
+            .unwrap_or_else(|| panic!("No pending committee appointment"));
+        if env.ledger().timestamp() > expires_at {
+            panic!("Committee appointment has expired");
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::PendingMembership(member.clone()));
+
+        log!(
+            &env,
+            "Committee member {} accepted their appointment",
+            member
+        );
+    }
 
-    /// Update committee (admin only)
-    pub fn update_committee(env: Env, admin: Address, new_committee: Vec<CommitteeMember>) {
+    /// Get when a pending committee appointment expires, None once accepted or never appointed
+    ///
+    /// An expired appointment stays pending and leaves its seat unable to act
+    /// until the admin appoints the address again with `update_committee`.
+    pub fn get_pending_membership(env: Env, member: Address) -> Option<u64> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingMembership(member))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
         admin.require_auth();
 
-        let stored_admin: Address = env.storage().instance().get(&ADMIN).unwrap();
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         if admin != stored_admin {
-            panic!("Only admin can update committee");
+            panic!("Only admin can assign roles");
+        }
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new loan proposals, loan executions and trades (pauser, or admin if no pauser is set)
+    ///
+    /// Repayments, votes and settlement stay open.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new loan proposals, loan executions and trades are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address, e.g. the multisig contract (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can transfer admin rights");
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
+
+    /// Set the address registry used to resolve peer contracts (admin only)
+    pub fn set_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the registry");
+        }
+
+        env.storage().instance().set(&DataKey::Registry, &registry);
+
+        log!(&env, "Address registry set to: {}", registry);
+    }
+
+    /// Set the DEX router trades execute through when the registry has none (admin only)
+    pub fn set_trade_router(env: Env, admin: Address, router: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the trade router");
+        }
+
+        env.storage().instance().set(&DataKey::TradeRouter, &router);
+
+        log!(&env, "Trade router set to: {}", router);
+    }
+
+    /// Internal helper functions
+    fn migrate_v1_instance(env: &Env) {
+        migrate_instance_entry(env, &v1::ADMIN, &DataKey::Admin);
+        migrate_instance_entry(env, &v1::YIELD_TOKEN, &DataKey::YieldToken);
+        migrate_instance_entry(env, &v1::REGISTRY, &DataKey::Registry);
+        migrate_instance_entry(env, &v1::COMMITTEE_MEMBERS, &DataKey::CommitteeMembers);
+        migrate_instance_entry(env, &v1::MIN_PROPOSAL_TOKENS, &DataKey::MinProposalTokens);
+        migrate_instance_entry(env, &v1::PROPOSAL_COUNTER, &DataKey::ProposalCounter);
+        migrate_instance_entry(
+            env,
+            &v1::PROFIT_REPORT_COUNTER,
+            &DataKey::ProfitReportCounter,
+        );
+        migrate_instance_entry(env, &v1::BAD_DEBT_COUNTER, &DataKey::BadDebtCounter);
+        migrate_instance_entry(env, &v1::BAD_DEBT_TOTAL, &DataKey::BadDebtTotal);
+        migrate_instance_entry(env, &v1::BAD_DEBT_REPORTERS, &DataKey::BadDebtReporters);
+        migrate_instance_entry(env, &v1::INSURANCE_FUND, &DataKey::InsuranceFund);
+        migrate_instance_entry(env, &v1::FEE_WRITE_OFF, &DataKey::FeeWriteOff);
+    }
+
+    fn open_proposal(
+        env: &Env,
+        proposer: Address,
+        parameter: ProtocolParameter,
+        new_value: u128,
+    ) -> GovernanceProposal {
+        // Check the parameter's minimum token requirement
+        let rules = Self::get_voting_rules(env.clone(), parameter.clone());
+        let min_tokens = rules.min_proposal_tokens;
+        let proposer_balance = Self::get_voting_power(env, &proposer);
+
+        if proposer_balance < min_tokens {
+            panic!(
+                "Insufficient tokens to propose. Required: {}, Have: {}",
+                min_tokens, proposer_balance
+            );
+        }
+        Self::verify_parameter_bounds(env, &parameter, new_value);
+
+        // Generate proposal ID
+        let mut payload = proposer.clone().to_xdr(env);
+        payload.append(&parameter.clone().to_xdr(env));
+        payload.extend_from_array(&new_value.to_be_bytes());
+        let proposal_id = derive_proposal_id(env, Self::next_proposal_counter(env), &payload);
+
+        // The community gets the review period to inspect the proposal before votes open
+        let voting_start = env.ledger().timestamp() + Self::get_review_period(env.clone());
+        let proposal = GovernanceProposal {
+            id: proposal_id.clone(),
+            proposer,
+            parameter,
+            new_value,
+            votes_for: 0,
+            votes_against: 0,
+            votes_abstain: 0,
+            voting_start,
+            voting_deadline: voting_start + rules.voting_period,
+            status: ProposalStatus::Pending,
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::GovernanceProposal(proposal_id.clone()), &proposal);
+        extend_persistent_ttl(env, &DataKey::GovernanceProposal(proposal_id.clone()));
+        // The threshold is fixed at submission so later rule changes cannot move it mid-vote
+        env.storage().persistent().set(
+            &DataKey::PassThreshold(proposal_id.clone()),
+            &rules.pass_threshold_bps,
+        );
+        extend_persistent_ttl(env, &DataKey::PassThreshold(proposal_id.clone()));
+        extend_instance_ttl(env);
+
+        log!(
+            env,
+            "Governance proposal {} submitted by {} for parameter {:?}",
+            proposal_id,
+            proposal.proposer,
+            proposal.parameter
+        );
+
+        proposal
+    }
+
+    fn execute_treasury_swap(env: &Env, executor: &Address, proposal_id: &BytesN<32>) {
+        let swap = Self::get_treasury_swap(env.clone(), proposal_id.clone())
+            .unwrap_or_else(|| panic!("Treasury swap not found"));
+        if env.ledger().timestamp() > swap.deadline {
+            panic!("Treasury swap has expired");
+        }
+        // Prices may have moved during the vote
+        Self::verify_treasury_allocation(env, &swap);
+
+        let treasury = lookup_registry(env, &DataKey::Registry, REGISTRY_TREASURY)
+            .or_else(|| env.storage().instance().get(&DataKey::Treasury))
+            .unwrap_or_else(|| panic!("Treasury not configured"));
+        let router = lookup_registry(env, &DataKey::Registry, REGISTRY_TRADE_ROUTER)
+            .or_else(|| env.storage().instance().get(&DataKey::TradeRouter))
+            .unwrap_or_else(|| panic!("Trade router not configured"));
+
+        // The treasury must authorize the input leaving it; the output is measured on arrival
+        let out_client = TokenClient::new(env, &swap.asset_out);
+        let balance_before = out_client.balance(&treasury);
+        transfer_or_abort(
+            env,
+            &swap.asset_in,
+            &treasury,
+            &router,
+            swap.amount_in as i128,
+        );
+        invoke_peer::<u128>(
+            env,
+            &router,
+            "swap",
+            (
+                swap.asset_in.clone(),
+                swap.asset_out.clone(),
+                swap.amount_in,
+                swap.min_amount_out,
+                treasury.clone(),
+            )
+                .into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        let amount_out = (out_client.balance(&treasury) - balance_before).max(0) as u128;
+        if amount_out < swap.min_amount_out {
+            panic_with_error!(env, ContractError::SlippageExceeded);
+        }
+
+        let execution_price = mul_div(amount_out, EXECUTION_PRICE_PRECISION, swap.amount_in)
+            .unwrap_or_else(|| panic!("Execution price overflow"));
+        let record = TradeRecord {
+            params: swap,
+            amount_out,
+            execution_price,
+            executor: executor.clone(),
+            executed_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeRecord(proposal_id.clone()), &record);
+        extend_persistent_ttl(env, &DataKey::TradeRecord(proposal_id.clone()));
+
+        log!(
+            env,
+            "Treasury swapped {} of {} for {} of {}",
+            record.params.amount_in,
+            record.params.asset_in,
+            record.amount_out,
+            record.params.asset_out
+        );
+    }
+
+    fn verify_treasury_allocation(env: &Env, swap: &TradeParams) {
+        let treasury = lookup_registry(env, &DataKey::Registry, REGISTRY_TREASURY)
+            .or_else(|| env.storage().instance().get(&DataKey::Treasury))
+            .unwrap_or_else(|| panic!("Treasury not configured"));
+        let oracle = lookup_registry(env, &DataKey::Registry, REGISTRY_ORACLE)
+            .unwrap_or_else(|| panic!("Oracle not registered"));
+
+        // Value every target asset the treasury holds, and the two legs at their proposed sizes
+        let mut total_value: u128 = 0;
+        let mut leg_in: Option<(u128, u128)> = None;
+        let mut leg_out: Option<(u128, u128)> = None;
+        for target in Self::get_treasury_allocation(env.clone()).iter() {
+            let price: u128 = invoke_peer(
+                env,
+                &oracle,
+                "get_price",
+                (target.price_feed.clone(),).into_val(env),
+                ContractError::PeerCallFailed,
+            );
+            let balance = TokenClient::new(env, &target.asset)
+                .balance(&treasury)
+                .max(0) as u128;
+            let value = |amount: u128| {
+                mul_div(amount, price, USDC_PEG_PRICE)
+                    .unwrap_or_else(|| panic!("Treasury value overflow"))
+            };
+            total_value += value(balance);
+            if target.asset == swap.asset_in {
+                if balance < swap.amount_in {
+                    panic!("Treasury holds too little to swap");
+                }
+                leg_in = Some((value(balance - swap.amount_in), target.target_bps as u128));
+            } else if target.asset == swap.asset_out {
+                leg_out = Some((
+                    value(balance + swap.min_amount_out),
+                    target.target_bps as u128,
+                ));
+            }
+        }
+
+        let (Some((value_in, target_in)), Some((value_out, target_out))) = (leg_in, leg_out) else {
+            panic!("Treasury swap assets are not in the allocation policy");
+        };
+        if value_in * BASIS_POINTS < total_value * target_in
+            || value_out * BASIS_POINTS > total_value * target_out
+        {
+            panic!("Treasury swap moves holdings away from the target allocation");
+        }
+    }
+
+    fn apply_governance_change(env: &Env, proposal: &mut GovernanceProposal, executor: &Address) {
+        proposal.status = ProposalStatus::Executed;
+        env.storage()
+            .persistent()
+            .set(&DataKey::GovernanceProposal(proposal.id.clone()), proposal);
+
+        if proposal.parameter == ProtocolParameter::TreasurySwap {
+            Self::execute_treasury_swap(env, executor, &proposal.id);
+        }
+        if proposal.parameter == ProtocolParameter::FeeSwitch {
+            Self::apply_fee_switch(env, &proposal.id);
+        }
+        Self::record_change(env, proposal, executor);
+
+        log!(
+            env,
+            "Governance proposal {} executed. Parameter {:?} updated to {}",
+            proposal.id,
+            proposal.parameter,
+            proposal.new_value
+        );
+    }
+
+    fn apply_fee_switch(env: &Env, proposal_id: &BytesN<32>) {
+        let change: FeeSwitchChange = env
+            .storage()
+            .persistent()
+            .get(&DataKey::FeeSwitchChange(proposal_id.clone()))
+            .unwrap();
+        let vault = lookup_registry(env, &DataKey::Registry, REGISTRY_USDC_VAULT)
+            .unwrap_or_else(|| panic!("No USDC vault is registered to apply the fee switch"));
+        invoke_peer::<()>(
+            env,
+            &vault,
+            "set_fee_switch",
+            (
+                env.current_contract_address(),
+                change.stream.clone(),
+                change.switch.clone(),
+            )
+                .into_val(env),
+            ContractError::PeerCallFailed,
+        );
+
+        log!(
+            env,
+            "Fee switch for {} set to {} with {} bps to insurance",
+            change.stream,
+            change.switch.enabled,
+            change.switch.insurance_bps
+        );
+    }
+
+    fn record_change(env: &Env, proposal: &GovernanceProposal, executor: &Address) {
+        let (kind, old_value) = if proposal.parameter == ProtocolParameter::TreasurySwap {
+            (ProposalKind::TreasurySwap, None)
+        } else if proposal.parameter == ProtocolParameter::FeeSwitch {
+            (ProposalKind::FeeSwitch, None)
+        } else {
+            let key = DataKey::ParameterValue(proposal.parameter.clone());
+            let old_value = env.storage().instance().get(&key);
+            env.storage().instance().set(&key, &proposal.new_value);
+            let dependent = env
+                .storage()
+                .persistent()
+                .has(&DataKey::ProposalPrerequisites(proposal.id.clone()));
+            if dependent {
+                (ProposalKind::DependentChange, old_value)
+            } else {
+                (ProposalKind::ParameterChange, old_value)
+            }
+        };
+
+        let index = Self::get_changelog_count(env.clone());
+        let entry = ChangelogEntry {
+            index,
+            proposal_id: proposal.id.clone(),
+            kind,
+            parameter: proposal.parameter.clone(),
+            old_value,
+            new_value: proposal.new_value,
+            executor: executor.clone(),
+            executed_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::ChangelogEntry(index), &entry);
+        extend_persistent_ttl(env, &DataKey::ChangelogEntry(index));
+        env.storage()
+            .instance()
+            .set(&DataKey::ChangelogCount, &(index + 1));
+    }
+
+    fn verify_parameter_bounds(env: &Env, parameter: &ProtocolParameter, value: u128) {
+        let (min, max) = parameter_bounds(parameter);
+        if value < min || value > max {
+            panic_with_error!(env, ContractError::ParameterOutOfBounds);
+        }
+    }
+
+    fn open_trade(env: &Env, proposer: &Address, trade_params: &TradeParams) -> BytesN<32> {
+        if trade_params.amount_in == 0 {
+            panic!("Trade amount must be greater than 0");
+        }
+
+        // Generate trade ID
+        let mut payload = trade_params.asset_in.clone().to_xdr(env);
+        payload.extend_from_array(&trade_params.amount_in.to_be_bytes());
+        let trade_id = derive_proposal_id(env, Self::next_proposal_counter(env), &payload);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeProposal(trade_id.clone()), trade_params);
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeProposer(trade_id.clone()), proposer);
+        extend_persistent_ttl(env, &DataKey::TradeProposal(trade_id.clone()));
+        extend_persistent_ttl(env, &DataKey::TradeProposer(trade_id.clone()));
+        let mut open_trades = Self::get_open_trades(env.clone());
+        open_trades.push_back(trade_id.clone());
+        Self::store_open_trades(env, &open_trades);
+        let mut stats = Self::load_member_stats(env, proposer);
+        stats.proposals_submitted += 1;
+        Self::store_member_stats(env, proposer, stats);
+        extend_instance_ttl(env);
+
+        log!(
+            env,
+            "Trade proposal {} submitted: {} {} for {} {}",
+            trade_id,
+            trade_params.amount_in,
+            trade_params.asset_in,
+            trade_params.min_amount_out,
+            trade_params.asset_out
+        );
+
+        trade_id
+    }
+
+    fn fill_trade(env: &Env, executor: &Address, trade_id: &BytesN<32>, amount: u128) -> u128 {
+        let trade_params: TradeParams = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TradeProposal(trade_id.clone()))
+            .unwrap_or_else(|| panic!("Trade proposal not found"));
+
+        // Check deadline
+        if env.ledger().timestamp() > trade_params.deadline {
+            panic!("Trade proposal has expired");
+        }
+
+        let mut fill = Self::get_trade_fill(env.clone(), trade_id.clone()).unwrap_or(TradeFill {
+            filled_in: 0,
+            filled_out: 0,
+            average_price: 0,
+            slices: 0,
+        });
+        if amount == 0 || amount > trade_params.amount_in - fill.filled_in {
+            panic!("Slice exceeds the unfilled trade amount");
+        }
+        let mut min_amount_out =
+            mul_div_up(trade_params.min_amount_out, amount, trade_params.amount_in)
+                .unwrap_or_else(|| panic!("Slice minimum overflow"));
+        // A limit order never fills below its limit, whatever the oracle read
+        if let Some(order) = Self::get_limit_order(env.clone(), trade_id.clone()) {
+            let limit_out = mul_div_up(amount, order.limit_price, USDC_PEG_PRICE)
+                .unwrap_or_else(|| panic!("Slice minimum overflow"));
+            min_amount_out = min_amount_out.max(limit_out);
+        }
+
+        let router = lookup_registry(env, &DataKey::Registry, REGISTRY_TRADE_ROUTER)
+            .or_else(|| env.storage().instance().get(&DataKey::TradeRouter))
+            .unwrap_or_else(|| panic!("Trade router not configured"));
+
+        // Claim the slice, and remove a completed trade, before any outbound call
+        fill.filled_in += amount;
+        fill.slices += 1;
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeFill(trade_id.clone()), &fill);
+        extend_persistent_ttl(env, &DataKey::TradeFill(trade_id.clone()));
+        let completed = fill.filled_in == trade_params.amount_in;
+        if completed {
+            Self::remove_trade_proposal(env, trade_id);
+        }
+
+        // Hand the input to the router and measure what actually arrives
+        let this = env.current_contract_address();
+        let out_client = TokenClient::new(env, &trade_params.asset_out);
+        let balance_before = out_client.balance(&this);
+        transfer_or_abort(env, &trade_params.asset_in, &this, &router, amount as i128);
+        invoke_peer::<u128>(
+            env,
+            &router,
+            "swap",
+            (
+                trade_params.asset_in.clone(),
+                trade_params.asset_out.clone(),
+                amount,
+                min_amount_out,
+                this.clone(),
+            )
+                .into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        let amount_out = (out_client.balance(&this) - balance_before).max(0) as u128;
+
+        // The committee's minimum is enforced against the on-chain receipt
+        if amount_out < min_amount_out {
+            panic_with_error!(env, ContractError::SlippageExceeded);
+        }
+
+        fill.filled_out += amount_out;
+        fill.average_price = mul_div(fill.filled_out, EXECUTION_PRICE_PRECISION, fill.filled_in)
+            .unwrap_or_else(|| panic!("Execution price overflow"));
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeFill(trade_id.clone()), &fill);
+
+        let total_in = trade_params.amount_in;
+        if completed {
+            let record = TradeRecord {
+                params: trade_params,
+                amount_out: fill.filled_out,
+                execution_price: fill.average_price,
+                executor: executor.clone(),
+                executed_at: env.ledger().timestamp(),
+            };
+            env.storage()
+                .persistent()
+                .set(&DataKey::TradeRecord(trade_id.clone()), &record);
+            extend_persistent_ttl(env, &DataKey::TradeRecord(trade_id.clone()));
+        }
+        extend_instance_ttl(env);
+
+        log!(
+            env,
+            "Trade {} slice executed by {}: {} in for {} out. Filled {} of {} at price {}",
+            trade_id,
+            executor,
+            amount,
+            amount_out,
+            fill.filled_in,
+            total_in,
+            fill.average_price
+        );
+
+        amount_out
+    }
+
+    // Pays out an approved profit report, then moves the yield index over the funded yield
+    fn distribute_profit_report(env: &Env, report_id: u64, proposal: &ProfitReportProposal) {
+        let report = proposal.report.clone();
+
+        // Early-repayment income folded into this report is no longer pending
+        let prepayment_income = Self::get_prepayment_income(env.clone());
+        env.storage().instance().set(
+            &DataKey::PrepaymentIncome,
+            &prepayment_income.saturating_sub(proposal.prepayment_income),
+        );
+
+        // Protocol fees first repay bad debt written off against future fees
+        let fee_write_off: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::FeeWriteOff)
+            .unwrap_or(0);
+        if fee_write_off > 0 && report.protocol_fee > 0 {
+            let fee_applied = if report.protocol_fee < fee_write_off {
+                report.protocol_fee
+            } else {
+                fee_write_off
+            };
+            env.storage()
+                .instance()
+                .set(&DataKey::FeeWriteOff, &(fee_write_off - fee_applied));
+
+            log!(
+                env,
+                "Applied ${} of protocol fees to written-off bad debt. Remaining: ${}",
+                fee_applied,
+                fee_write_off - fee_applied
+            );
+        }
+
+        // The depositor share reaches the vault before holders are credited with it
+        let usdc = Self::usdc_contract(env);
+        let this = env.current_contract_address();
+        let usdc_vault = lookup_registry(env, &DataKey::Registry, REGISTRY_USDC_VAULT)
+            .unwrap_or_else(|| panic!("USDC vault not registered"));
+        transfer_or_abort(
+            env,
+            &usdc,
+            &this,
+            &usdc_vault,
+            report.yield_distributed as i128,
+        );
+        let treasury = lookup_registry(env, &DataKey::Registry, REGISTRY_TREASURY)
+            .or_else(|| env.storage().instance().get(&DataKey::Treasury));
+        if let Some(treasury) = treasury {
+            transfer_or_abort(env, &usdc, &this, &treasury, report.protocol_fee as i128);
         }
 
-        if new_committee.len() != TOTAL_COMMITTEE_SIZE {
-            panic!(
-                "Committee must have exactly {} members",
-                TOTAL_COMMITTEE_SIZE
-            );
+        env.storage()
+            .persistent()
+            .set(&DataKey::ProfitReport(report_id), &report);
+        extend_persistent_ttl(env, &DataKey::ProfitReport(report_id));
+        let epoch_yield: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochYield)
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::EpochYield,
+            &(epoch_yield + report.yield_distributed),
+        );
+        let epoch_fees: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochProtocolFees)
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::EpochProtocolFees,
+            &(epoch_fees + report.protocol_fee),
+        );
+        extend_instance_ttl(env);
+
+        // Governance is the yield token's profit reporter
+        let yield_token_contract = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        invoke_peer::<()>(
+            env,
+            &yield_token_contract,
+            "distribute_profits",
+            (this, report.clone()).into_val(env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        log!(
+            env,
+            "Profit report {} distributed: total ${}, distributed ${}, protocol fee ${}",
+            report_id,
+            report.total_profit,
+            report.yield_distributed,
+            report.protocol_fee
+        );
+    }
+
+    fn store_profit_report_proposal(env: &Env, report_id: u64, proposal: &ProfitReportProposal) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::ProfitReportProposal(report_id), proposal);
+        extend_persistent_ttl(env, &DataKey::ProfitReportProposal(report_id));
+    }
+
+    // Profits are reported and escrowed in the USDC the vault pays yield from
+    fn usdc_contract(env: &Env) -> Address {
+        let usdc_vault = lookup_registry(env, &DataKey::Registry, REGISTRY_USDC_VAULT)
+            .unwrap_or_else(|| panic!("USDC vault not registered"));
+        invoke_peer(
+            env,
+            &usdc_vault,
+            "get_usdc_contract",
+            Vec::new(env),
+            ContractError::PeerCallFailed,
+        )
+    }
+
+    fn store_limit_order(env: &Env, trade_id: &BytesN<32>, order: &LimitOrder) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::TradeLimit(trade_id.clone()), order);
+        extend_persistent_ttl(env, &DataKey::TradeLimit(trade_id.clone()));
+    }
+
+    fn remove_trade_proposal(env: &Env, trade_id: &BytesN<32>) {
+        env.storage()
+            .persistent()
+            .remove(&DataKey::TradeProposal(trade_id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::TradeProposer(trade_id.clone()));
+
+        let mut open_trades = Self::get_open_trades(env.clone());
+        if let Some(index) = open_trades.first_index_of(trade_id) {
+            open_trades.remove(index);
+            Self::store_open_trades(env, &open_trades);
         }
+    }
 
+    fn store_open_trades(env: &Env, open_trades: &Vec<BytesN<32>>) {
         env.storage()
-            .instance()
-            .set(&COMMITTEE_MEMBERS, &new_committee);
+            .persistent()
+            .set(&DataKey::OpenTrades, open_trades);
+        extend_persistent_ttl(env, &DataKey::OpenTrades);
+    }
 
-        log!(&env, "Committee updated by admin");
+    fn tally(env: &Env, proposal: &GovernanceProposal) -> VoteTally {
+        let quorum = Self::get_quorum(env.clone());
+        VoteTally {
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            votes_abstain: proposal.votes_abstain,
+            quorum,
+            quorum_reached: proposal.votes_for + proposal.votes_against + proposal.votes_abstain
+                >= quorum,
+        }
     }
 
-    /// Internal helper functions
     fn verify_committee_member(env: &Env, member: &Address) {
+        if !Self::is_committee_member(env, member) {
+            panic!("Address is not a committee member");
+        }
+    }
+
+    fn is_committee_member(env: &Env, member: &Address) -> bool {
+        // Appointed members count only once they have accepted
+        Self::holds_seat(env, member)
+            && !env
+                .storage()
+                .persistent()
+                .has(&DataKey::PendingMembership(member.clone()))
+    }
+
+    fn holds_seat(env: &Env, member: &Address) -> bool {
         let committee: Vec<CommitteeMember> = env
             .storage()
             .instance()
-            .get(&COMMITTEE_MEMBERS)
+            .get(&DataKey::CommitteeMembers)
             .unwrap_or(Vec::new(env));
 
         for committee_member in committee.iter() {
             if committee_member.address == *member {
-                return;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn approving_member(env: &Env, approver: &Address) -> Address {
+        if Self::is_committee_member(env, approver) {
+            return approver.clone();
+        }
+
+        // Approvals are recorded under the member, so member and delegate cannot both approve
+        let member: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::DelegateOf(approver.clone()));
+        if let Some(member) = member {
+            let delegation = Self::get_committee_delegate(env.clone(), member.clone());
+            if delegation.map_or(false, |delegation| delegation.delegate == *approver)
+                && Self::is_committee_member(env, &member)
+            {
+                return member;
             }
         }
 
         panic!("Address is not a committee member");
     }
 
+    fn loan_voting_member(env: &Env, signer: &Address) -> Address {
+        // Session keys only ever reach here, so they cannot act on trades, freezes or shutdowns
+        let member: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SessionKeyOf(signer.clone()));
+        if let Some(member) = member {
+            let session = Self::get_session_key(env.clone(), member.clone());
+            if session.map_or(false, |session| session.key == *signer)
+                && Self::is_committee_member(env, &member)
+            {
+                return member;
+            }
+        }
+
+        Self::approving_member(env, signer)
+    }
+
+    fn clear_session_key(env: &Env, member: &Address) {
+        let session: Option<SessionKey> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::SessionKey(member.clone()));
+        if let Some(session) = session {
+            // The key may have been registered by someone else since this expired
+            let keyed: Option<Address> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::SessionKeyOf(session.key.clone()));
+            if keyed.as_ref() == Some(member) {
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::SessionKeyOf(session.key));
+            }
+            env.storage()
+                .persistent()
+                .remove(&DataKey::SessionKey(member.clone()));
+        }
+    }
+
+    fn clear_committee_delegate(env: &Env, member: &Address) {
+        let delegation: Option<CommitteeDelegate> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CommitteeDelegate(member.clone()));
+        if let Some(delegation) = delegation {
+            // The delegate may have moved on to act for someone else since this expired
+            let delegating: Option<Address> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::DelegateOf(delegation.delegate.clone()));
+            if delegating.as_ref() == Some(member) {
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::DelegateOf(delegation.delegate));
+            }
+            env.storage()
+                .persistent()
+                .remove(&DataKey::CommitteeDelegate(member.clone()));
+        }
+    }
+
+    fn next_proposal_counter(env: &Env) -> u64 {
+        // Shared by loan, trade and governance proposals so IDs never repeat across kinds
+        let proposal_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCounter)
+            .unwrap_or(0);
+        let next_counter = proposal_counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ProposalCounter, &next_counter);
+        next_counter
+    }
+
+    fn trigger_settlement(env: &Env) {
+        let settled_at = env.ledger().timestamp();
+        env.storage()
+            .instance()
+            .set(&DataKey::SettledAt, &settled_at);
+        extend_instance_ttl(env);
+
+        let governance = env.current_contract_address();
+        let yield_token_contract = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        invoke_peer::<()>(
+            env,
+            &yield_token_contract,
+            "enter_settlement",
+            (governance.clone(),).into_val(env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        // Vaults are only known through the registry; one not deployed has nothing to settle
+        for name in [REGISTRY_USDC_VAULT, REGISTRY_GOLD_VAULT] {
+            match lookup_registry(env, &DataKey::Registry, name) {
+                Some(vault) => invoke_peer::<()>(
+                    env,
+                    &vault,
+                    "enter_settlement",
+                    (governance.clone(),).into_val(env),
+                    ContractError::PeerCallFailed,
+                ),
+                None => log!(
+                    env,
+                    "No {} registered; skipped settlement",
+                    Symbol::new(env, name)
+                ),
+            }
+        }
+
+        log!(
+            env,
+            "Global settlement started at {}; all loans called",
+            settled_at
+        );
+    }
+
+    fn ensure_not_settled(env: &Env) {
+        if env.storage().instance().has(&DataKey::SettledAt) {
+            panic_with_error!(env, ContractError::GlobalSettlement);
+        }
+    }
+
+    fn store_credit_history(env: &Env, borrower: &Address, mut history: CreditHistory) {
+        history.last_updated = env.ledger().timestamp();
+        env.storage()
+            .persistent()
+            .set(&DataKey::CreditHistory(borrower.clone()), &history);
+        extend_persistent_ttl(env, &DataKey::CreditHistory(borrower.clone()));
+    }
+
+    fn load_member_stats(env: &Env, member: &Address) -> MemberStats {
+        env.storage()
+            .persistent()
+            .get(&DataKey::MemberStats(member.clone()))
+            .unwrap_or(MemberStats {
+                proposals_submitted: 0,
+                approvals: 0,
+                rejections: 0,
+                total_response_time: 0,
+                average_response_time: 0,
+            })
+    }
+
+    fn store_member_stats(env: &Env, member: &Address, stats: MemberStats) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::MemberStats(member.clone()), &stats);
+        extend_persistent_ttl(env, &DataKey::MemberStats(member.clone()));
+    }
+
+    fn get_bad_debt_or_panic(env: &Env, record_id: u64) -> BadDebtRecord {
+        env.storage()
+            .persistent()
+            .get(&DataKey::BadDebt(record_id))
+            .unwrap_or_else(|| panic!("Bad debt record not found"))
+    }
+
     fn get_voting_power(env: &Env, user: &Address) -> u128 {
-        let yield_token_contract: Address = env.storage().instance().get(&YIELD_TOKEN).unwrap();
+        let yield_token_contract = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
 
         // Get user's token balance as voting power
-        let balance: i128 = env.invoke_contract(
+        let balance: i128 = invoke_peer(
+            env,
             &yield_token_contract,
-            &Symbol::new(env, "balance"),
+            "balance",
             (user.clone(),).into_val(env),
+            ContractError::YieldTokenCallFailed,
         );
 
         balance as u128
     }
+
+    fn open_loan_proposal(
+        env: &Env,
+        proposer: &Address,
+        borrower: Address,
+        loan_amount: u128,
+        currency: Option<Address>,
+        collateral_asset: Address,
+        interest_rate: u128,
+        duration_days: u64,
+        structure: RepaymentStructure,
+        pledged_gold_usd: u128,
+    ) -> BytesN<32> {
+        ensure_not_paused(env);
+        Self::ensure_not_settled(env);
+
+        // Turn away loans the collateral could never back before the committee spends time on them
+        if let Some(coffee_collateral) =
+            lookup_registry(env, &DataKey::Registry, REGISTRY_COFFEE_COLLATERAL)
+        {
+            let pledged_to: Option<BytesN<32>> = invoke_peer(
+                env,
+                &coffee_collateral,
+                "get_pledged_loan",
+                (collateral_asset.clone(),).into_val(env),
+                ContractError::PeerCallFailed,
+            );
+            if pledged_to.is_some() {
+                panic!("Collateral is already pledged to another loan");
+            }
+            let loan_value_usd = Self::usd_value(env, &currency, loan_amount);
+            Self::verify_collateral_coverage(
+                env,
+                &collateral_asset,
+                pledged_gold_usd,
+                loan_value_usd,
+            );
+        }
+
+        let base_rate = Self::get_base_loan_rate(env.clone());
+        if interest_rate < base_rate {
+            panic!("Interest rate is below the base loan rate");
+        }
+
+        // Generate proposal ID
+        let mut payload = borrower.clone().to_xdr(env);
+        payload.extend_from_array(&loan_amount.to_be_bytes());
+        let proposal_id = derive_proposal_id(env, Self::next_proposal_counter(env), &payload);
+
+        let proposal = LoanProposal {
+            id: proposal_id.clone(),
+            borrower,
+            amount: loan_amount,
+            collateral: collateral_asset,
+            interest_rate,
+            duration: duration_days,
+            approvals: 0,
+            status: ProposalStatus::Pending,
+            created_at: env.ledger().timestamp(),
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::LoanProposal(proposal_id.clone()), &proposal);
+        extend_persistent_ttl(env, &DataKey::LoanProposal(proposal_id.clone()));
+        let mut stats = Self::load_member_stats(env, proposer);
+        stats.proposals_submitted += 1;
+        Self::store_member_stats(env, proposer, stats);
+        // The borrower keeps this rate if the loan is executed before the lock expires
+        let rate_lock = RateLock {
+            base_rate,
+            locked_rate: interest_rate,
+            locked_at: proposal.created_at,
+            expires_at: proposal.created_at + RATE_LOCK_WINDOW,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::RateLock(proposal_id.clone()), &rate_lock);
+        extend_persistent_ttl(env, &DataKey::RateLock(proposal_id.clone()));
+        if let Some(currency) = &currency {
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanDenomination(proposal_id.clone()), currency);
+            extend_persistent_ttl(env, &DataKey::LoanDenomination(proposal_id.clone()));
+        }
+        if structure != RepaymentStructure::Amortizing {
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanStructure(proposal_id.clone()), &structure);
+            extend_persistent_ttl(env, &DataKey::LoanStructure(proposal_id.clone()));
+        }
+        extend_instance_ttl(env);
+
+        log!(
+            env,
+            "Loan proposal {} submitted by {} for borrower {} amount: ${}",
+            proposal_id,
+            proposer,
+            proposal.borrower,
+            loan_amount
+        );
+
+        proposal_id
+    }
+
+    fn build_repayment_schedule(
+        env: &Env,
+        amount: u128,
+        interest_rate: u128,
+        duration_days: u64,
+        structure: &RepaymentStructure,
+        start: u64,
+    ) -> Vec<Installment> {
+        let count = duration_days.div_ceil(LOAN_INSTALLMENT_DAYS).max(1);
+        // Balloon loans carry their whole principal to the final installment
+        let principal_each = match structure {
+            RepaymentStructure::Amortizing => amount / count as u128,
+            RepaymentStructure::Balloon => 0,
+        };
+
+        let mut schedule = Vec::new(env);
+        let mut remaining = amount;
+        let mut elapsed_days = 0;
+        for i in 0..count {
+            let period_days = (duration_days - elapsed_days).min(LOAN_INSTALLMENT_DAYS);
+            elapsed_days += period_days;
+
+            // The final installment also carries the rounding remainder
+            let principal = if i + 1 == count {
+                remaining
+            } else {
+                principal_each
+            };
+            let interest = mul_div_up(
+                remaining,
+                interest_rate * period_days as u128,
+                DAYS_PER_YEAR * BASIS_POINTS,
+            )
+            .unwrap_or_else(|| panic!("Installment interest overflow"));
+
+            schedule.push_back(Installment {
+                due_at: start + elapsed_days * SECONDS_PER_DAY,
+                principal,
+                interest,
+                principal_repaid: 0,
+            });
+            remaining -= principal;
+        }
+        schedule
+    }
+
+    fn apply_to_schedule(env: &Env, loan_id: &BytesN<32>, amount: u128) {
+        let mut schedule = Self::get_repayment_schedule(env.clone(), loan_id.clone());
+        if schedule.is_empty() {
+            return;
+        }
+
+        let mut remaining = amount;
+        for i in 0..schedule.len() {
+            if remaining == 0 {
+                break;
+            }
+            let mut installment = schedule.get(i).unwrap();
+            let applied = remaining.min(installment.principal - installment.principal_repaid);
+            if applied == 0 {
+                continue;
+            }
+            installment.principal_repaid += applied;
+            remaining -= applied;
+            schedule.set(i, installment);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::RepaymentSchedule(loan_id.clone()), &schedule);
+        extend_persistent_ttl(env, &DataKey::RepaymentSchedule(loan_id.clone()));
+    }
+
+    /// Settle a balloon loan's unpaid scheduled interest, returning what is left for principal
+    fn apply_interest(env: &Env, loan_id: &BytesN<32>, amount: u128) -> u128 {
+        let scheduled: u128 = Self::get_repayment_schedule(env.clone(), loan_id.clone())
+            .iter()
+            .map(|installment| installment.interest)
+            .sum();
+        let paid = Self::get_interest_paid(env.clone(), loan_id.clone());
+        let applied = amount.min(scheduled - paid);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::InterestPaid(loan_id.clone()), &(paid + applied));
+        extend_persistent_ttl(env, &DataKey::InterestPaid(loan_id.clone()));
+
+        amount - applied
+    }
+
+    fn defer_loan_schedule(env: &Env, loan_id: &BytesN<32>, delay: u64) {
+        let due_at: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanDueAt(loan_id.clone()));
+        if let Some(due_at) = due_at {
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanDueAt(loan_id.clone()), &(due_at + delay));
+        }
+
+        let mut schedule = Self::get_repayment_schedule(env.clone(), loan_id.clone());
+        for i in 0..schedule.len() {
+            let mut installment = schedule.get(i).unwrap();
+            installment.due_at += delay;
+            schedule.set(i, installment);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::RepaymentSchedule(loan_id.clone()), &schedule);
+    }
+
+    fn overdue_beyond(env: &Env, loan_id: &BytesN<32>, grace_period: u64) -> bool {
+        if Self::get_loan_outstanding(env.clone(), loan_id.clone()).is_none() {
+            return false;
+        }
+        // A frozen loan's clock stops at the freeze
+        let now = Self::get_loan_freeze(env.clone(), loan_id.clone())
+            .map_or(env.ledger().timestamp(), |freeze| freeze.frozen_at);
+        Self::current_due_at(env, loan_id).map_or(false, |due_at| now > due_at + grace_period)
+    }
+
+    fn current_due_at(env: &Env, loan_id: &BytesN<32>) -> Option<u64> {
+        // Loans executed before schedules existed only have a final due date
+        match Self::get_next_payment(env.clone(), loan_id.clone()) {
+            Some(installment) => Some(installment.due_at),
+            None => Self::get_loan_due_at(env.clone(), loan_id.clone()),
+        }
+    }
+
+    fn store_loan_syndicate(env: &Env, loan_id: &BytesN<32>, syndicate: &Vec<SyndicateShare>) {
+        let key = DataKey::LoanSyndicate(loan_id.clone());
+        if syndicate.is_empty() {
+            env.storage().persistent().remove(&key);
+            return;
+        }
+        env.storage().persistent().set(&key, syndicate);
+        extend_persistent_ttl(env, &key);
+    }
+
+    /// Credit external lenders their pro-rata share of a recorded repayment
+    fn credit_syndicate(env: &Env, loan_id: &BytesN<32>, principal: u128, repaid: u128) {
+        let mut syndicate = Self::get_loan_syndicate(env.clone(), loan_id.clone());
+        if syndicate.is_empty() {
+            return;
+        }
+
+        for index in 0..syndicate.len() {
+            let mut share = syndicate.get(index).unwrap();
+            share.credited += mul_div(repaid, share.principal, principal)
+                .unwrap_or_else(|| panic!("Syndicate share overflow"));
+            syndicate.set(index, share);
+        }
+        Self::store_loan_syndicate(env, loan_id, &syndicate);
+    }
+
+    fn reduce_loan_outstanding(env: &Env, loan_id: &BytesN<32>, amount: u128) {
+        let Some(outstanding) = Self::get_loan_outstanding(env.clone(), loan_id.clone()) else {
+            return;
+        };
+
+        let remaining = outstanding.saturating_sub(amount);
+        let outstanding_principal = Self::get_outstanding_principal(env.clone());
+        env.storage().instance().set(
+            &DataKey::OutstandingPrincipal,
+            &outstanding_principal.saturating_sub(outstanding - remaining),
+        );
+        if remaining > 0 {
+            env.storage()
+                .persistent()
+                .set(&DataKey::LoanOutstanding(loan_id.clone()), &remaining);
+            extend_persistent_ttl(env, &DataKey::LoanOutstanding(loan_id.clone()));
+            return;
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::LoanOutstanding(loan_id.clone()));
+        let active_loans = Self::get_active_loans(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::ActiveLoans, &active_loans.saturating_sub(1));
+    }
+
+    fn verify_collateral_coverage(
+        env: &Env,
+        collateral: &Address,
+        gold_value_usd: u128,
+        loan_value_usd: u128,
+    ) {
+        let coffee_collateral =
+            lookup_registry(env, &DataKey::Registry, REGISTRY_COFFEE_COLLATERAL)
+                .unwrap_or_else(|| panic!("Coffee collateral contract not registered"));
+        let info: Option<CollateralInfo> = invoke_peer(
+            env,
+            &coffee_collateral,
+            "get_collateral_info",
+            (collateral.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        let info = info.unwrap_or_else(|| panic!("Collateral not found"));
+        if info.status != CollateralStatus::Active {
+            panic!("Collateral is no longer active");
+        }
+
+        let required = mul_div_up(loan_value_usd, COLLATERAL_RATIO_BASIS_POINTS, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Required collateral overflow"));
+        // A pledged gold-vault position makes up what the coffee lot alone does not
+        if info.estimated_value_usd + gold_value_usd < required {
+            panic!("Collateral does not cover the loan value");
+        }
+    }
+
+    fn gold_collateral_value(env: &Env, loan_id: &BytesN<32>) -> u128 {
+        let Some(lien) = Self::get_gold_collateral(env.clone(), loan_id.clone()) else {
+            return 0;
+        };
+        invoke_peer(
+            env,
+            &lien.vault,
+            "get_position_value",
+            (lien.owner,).into_val(env),
+            ContractError::PeerCallFailed,
+        )
+    }
+
+    fn loan_creditor(env: &Env, loan_id: &BytesN<32>) -> Address {
+        // The vault the shortfall was booked against, else the USDC vault that funds loans
+        let record_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanBadDebt(loan_id.clone()));
+        record_id
+            .and_then(|record_id| Self::get_bad_debt(env.clone(), record_id))
+            .map(|record| record.vault)
+            .or_else(|| lookup_registry(env, &DataKey::Registry, REGISTRY_USDC_VAULT))
+            .unwrap_or_else(|| panic!("Loan creditor not registered"))
+    }
+
+    fn apply_shortfall_recovery(env: &Env, loan_id: &BytesN<32>, recovered_usd: u128) {
+        let record_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanBadDebt(loan_id.clone()));
+        let Some(mut record) =
+            record_id.and_then(|record_id| Self::get_bad_debt(env.clone(), record_id))
+        else {
+            // Recorded later, the shortfall is netted against the seizure instead
+            return;
+        };
+        if record.status != ProposalStatus::Pending {
+            return;
+        }
+
+        let recovered = recovered_usd.min(record.amount);
+        record.amount -= recovered;
+        if record.amount == 0 {
+            record.status = ProposalStatus::Executed;
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::BadDebt(record.id), &record);
+        let total_bad_debt: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::BadDebtTotal)
+            .unwrap_or(0);
+        env.storage().instance().set(
+            &DataKey::BadDebtTotal,
+            &total_bad_debt.saturating_sub(recovered),
+        );
+
+        let loan: Option<LoanProposal> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::LoanProposal(loan_id.clone()));
+        if let Some(loan) = loan {
+            let mut history = Self::get_credit_history(env.clone(), loan.borrower.clone());
+            history.defaulted = history.defaulted.saturating_sub(recovered);
+            Self::store_credit_history(env, &loan.borrower, history);
+        }
+    }
+
+    fn liquidation_shortfall(env: &Env, loan_id: &BytesN<32>) -> u128 {
+        let coffee_collateral =
+            lookup_registry(env, &DataKey::Registry, REGISTRY_COFFEE_COLLATERAL)
+                .unwrap_or_else(|| panic!("Coffee collateral contract not registered"));
+        invoke_peer(
+            env,
+            &coffee_collateral,
+            "get_liquidation_shortfall",
+            (loan_id.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        )
+    }
+
+    fn loan_usd_value(env: &Env, loan_id: &BytesN<32>, amount: u128) -> u128 {
+        let currency = Self::get_loan_denomination(env.clone(), loan_id.clone());
+        Self::usd_value(env, &currency, amount)
+    }
+
+    fn usd_value(env: &Env, currency: &Option<Address>, amount: u128) -> u128 {
+        let Some(asset) = currency else {
+            return amount;
+        };
+
+        let loan_currency = Self::get_loan_currency(env.clone(), asset.clone())
+            .unwrap_or_else(|| panic!("Loan currency is not supported"));
+        let oracle = lookup_registry(env, &DataKey::Registry, REGISTRY_ORACLE)
+            .unwrap_or_else(|| panic!("Oracle not registered"));
+        let price: u128 = invoke_peer(
+            env,
+            &oracle,
+            "get_price",
+            (loan_currency.price_feed,).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        mul_div(amount, price, USDC_PEG_PRICE).unwrap_or_else(|| panic!("Loan value overflow"))
+    }
+
+    fn query_vault(env: &Env, vault: &Option<Address>, function: &str) -> u128 {
+        let Some(vault) = vault else {
+            return 0;
+        };
+        try_invoke_peer(env, vault, function, Vec::new(env)).unwrap_or(0)
+    }
+
+    fn take_yield_paid(env: &Env, vault: &Option<Address>) -> u128 {
+        let Some(vault_address) = vault else {
+            return 0;
+        };
+
+        // The vault's total is cumulative, so the epoch's share is the change since its mark
+        let yield_paid = Self::query_vault(env, vault, "get_yield_paid");
+        let key = DataKey::EpochYieldPaidMark(vault_address.clone());
+        let mark: u128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &yield_paid);
+        yield_paid.saturating_sub(mark)
+    }
 }
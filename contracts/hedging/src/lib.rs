@@ -0,0 +1,526 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    invoke_peer, is_initialized, mark_initialized, require_role, resolve_peer, role_holder,
+    set_paused, set_role_holder, stored_schema_version, try_resolve_peer, CoffeePrice,
+    CommitteeMember, ContractError, ContractInfo, HedgePosition, HedgeStatus, HedgeSummary, Role,
+    BASIS_POINTS, REGISTRY_COFFEE_COLLATERAL, REGISTRY_GOVERNANCE, REGISTRY_HEDGING,
+    REGISTRY_TRADE_ROUTER,
+};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env, String, Symbol, Vec};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config, peers and book totals
+    Admin,
+    Registry,
+    Governance,
+    CoffeeCollateral,
+    TradeRouter,
+    Partners,
+    NextHedgeId,
+    OpenHedges,
+    HedgedKg,
+    RealizedPnl,
+    // Persistent: per-hedge records
+    Hedge(u64),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Hedging;
+
+#[contractimpl]
+impl Hedging {
+    /// Initialize the coffee hedge book
+    pub fn initialize(env: Env, admin: Address, governance: Address, coffee_collateral: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Governance, &governance);
+        env.storage()
+            .instance()
+            .set(&DataKey::CoffeeCollateral, &coffee_collateral);
+        env.storage()
+            .instance()
+            .set(&DataKey::Partners, &Vec::<Address>::new(&env));
+        env.storage().instance().set(&DataKey::NextHedgeId, &1u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Hedge book initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_HEDGING),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Register an external hedging partner allowed to hold shorts (admin only)
+    pub fn add_partner(env: Env, admin: Address, partner: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mut partners = Self::get_partners(env.clone());
+        if partners.contains(&partner) {
+            panic!("Partner already registered");
+        }
+
+        partners.push_back(partner.clone());
+        env.storage().instance().set(&DataKey::Partners, &partners);
+
+        log!(&env, "Hedging partner registered: {}", partner);
+    }
+
+    /// Deregister a hedging partner; its open hedges can still be resized and closed (admin only)
+    pub fn remove_partner(env: Env, admin: Address, partner: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let partners = Self::get_partners(env.clone());
+        let mut remaining = Vec::new(&env);
+        for existing in partners.iter() {
+            if existing != partner {
+                remaining.push_back(existing);
+            }
+        }
+        if remaining.len() == partners.len() {
+            panic!("Partner not registered");
+        }
+        env.storage().instance().set(&DataKey::Partners, &remaining);
+
+        log!(&env, "Hedging partner removed: {}", partner);
+    }
+
+    /// Get every registered hedging partner
+    pub fn get_partners(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Partners)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Check whether a venue can hold new hedges: the trade router or a registered partner
+    pub fn is_venue(env: Env, venue: Address) -> bool {
+        let router = try_resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_TRADE_ROUTER,
+            &DataKey::TradeRouter,
+        );
+        router == Some(venue.clone()) || Self::get_partners(env).contains(&venue)
+    }
+
+    /// Record a short hedge against the collateral book (committee member only)
+    ///
+    /// Open hedges together can cover at most the coffee pledged as loan collateral.
+    pub fn open_hedge(
+        env: Env,
+        member: Address,
+        venue: Address,
+        quantity_kg: u128,
+        entry_price: u128,
+        expires_at: u64,
+    ) -> u64 {
+        member.require_auth();
+        ensure_not_paused(&env);
+        Self::verify_committee_member(&env, &member);
+
+        if !Self::is_venue(env.clone(), venue.clone()) {
+            panic!("Hedges can only be placed through the trade router or a registered partner");
+        }
+        if quantity_kg == 0 {
+            panic!("Hedge quantity must be greater than 0");
+        }
+        if entry_price == 0 {
+            panic!("Entry price must be greater than 0");
+        }
+        if expires_at <= env.ledger().timestamp() {
+            panic!("Hedge expiry must be in the future");
+        }
+
+        let hedged_kg = Self::get_hedged_quantity(env.clone()) + quantity_kg;
+        Self::ensure_within_book(&env, hedged_kg);
+
+        let hedge_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextHedgeId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextHedgeId, &(hedge_id + 1));
+        env.storage().instance().set(&DataKey::HedgedKg, &hedged_kg);
+
+        let mut open_hedges = Self::get_open_hedges(env.clone());
+        open_hedges.push_back(hedge_id);
+        env.storage()
+            .instance()
+            .set(&DataKey::OpenHedges, &open_hedges);
+
+        let hedge = HedgePosition {
+            id: hedge_id,
+            venue: venue.clone(),
+            quantity_kg,
+            entry_price,
+            expires_at,
+            opened_by: member,
+            opened_at: env.ledger().timestamp(),
+            exit_price: 0,
+            realized_pnl: 0,
+            status: HedgeStatus::Open,
+        };
+        Self::store_hedge(&env, &hedge);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Hedge {} opened with {}: short {} kg at {} per kg until {}",
+            hedge_id,
+            venue,
+            quantity_kg,
+            entry_price,
+            expires_at
+        );
+
+        hedge_id
+    }
+
+    /// Change the quantity of an open hedge, e.g. after a partial unwind (committee member only)
+    pub fn resize_hedge(env: Env, member: Address, hedge_id: u64, quantity_kg: u128) {
+        member.require_auth();
+        Self::verify_committee_member(&env, &member);
+
+        let mut hedge = Self::load_open_hedge(&env, hedge_id);
+        if quantity_kg == 0 {
+            panic!("Hedge quantity must be greater than 0; close the hedge instead");
+        }
+
+        let hedged_kg = Self::get_hedged_quantity(env.clone()) - hedge.quantity_kg + quantity_kg;
+        if quantity_kg > hedge.quantity_kg {
+            Self::ensure_within_book(&env, hedged_kg);
+        }
+        env.storage().instance().set(&DataKey::HedgedKg, &hedged_kg);
+
+        let previous_kg = hedge.quantity_kg;
+        hedge.quantity_kg = quantity_kg;
+        Self::store_hedge(&env, &hedge);
+
+        log!(
+            &env,
+            "Hedge {} resized from {} kg to {} kg by {}",
+            hedge_id,
+            previous_kg,
+            quantity_kg,
+            member
+        );
+    }
+
+    /// Close an open hedge at its exit price and book the result (committee member only)
+    ///
+    /// Returns the realized result of the short: positive when the price fell.
+    pub fn close_hedge(env: Env, member: Address, hedge_id: u64, exit_price: u128) -> i128 {
+        member.require_auth();
+        Self::verify_committee_member(&env, &member);
+
+        let mut hedge = Self::load_open_hedge(&env, hedge_id);
+        if exit_price == 0 {
+            panic!("Exit price must be greater than 0");
+        }
+
+        let pnl = Self::short_pnl(&hedge, exit_price);
+        hedge.exit_price = exit_price;
+        hedge.realized_pnl = pnl;
+        hedge.status = HedgeStatus::Closed;
+        Self::store_hedge(&env, &hedge);
+
+        let hedged_kg = Self::get_hedged_quantity(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::HedgedKg, &(hedged_kg - hedge.quantity_kg));
+        let realized = Self::get_realized_pnl(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::RealizedPnl, &(realized + pnl));
+
+        let open_hedges = Self::get_open_hedges(env.clone());
+        let mut remaining = Vec::new(&env);
+        for id in open_hedges.iter() {
+            if id != hedge_id {
+                remaining.push_back(id);
+            }
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::OpenHedges, &remaining);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Hedge {} closed at {} per kg by {} with result {}",
+            hedge_id,
+            exit_price,
+            member,
+            pnl
+        );
+
+        pnl
+    }
+
+    /// Get a hedge by id
+    pub fn get_hedge(env: Env, hedge_id: u64) -> Option<HedgePosition> {
+        env.storage().persistent().get(&DataKey::Hedge(hedge_id))
+    }
+
+    /// Get the ids of every open hedge
+    pub fn get_open_hedges(env: Env) -> Vec<u64> {
+        env.storage()
+            .instance()
+            .get(&DataKey::OpenHedges)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the kg of coffee sold short across open hedges
+    pub fn get_hedged_quantity(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::HedgedKg)
+            .unwrap_or(0)
+    }
+
+    /// Get the total result of closed hedges
+    pub fn get_realized_pnl(env: Env) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RealizedPnl)
+            .unwrap_or(0)
+    }
+
+    /// Get the share of the collateral book covered by open hedges, in bps
+    pub fn get_hedge_ratio(env: Env) -> u128 {
+        let book_kg = Self::book_quantity(&env);
+        if book_kg == 0 {
+            return 0;
+        }
+        Self::get_hedged_quantity(env) * BASIS_POINTS / book_kg
+    }
+
+    /// Get the hedge book against the collateral book for risk dashboards
+    pub fn get_hedge_summary(env: Env) -> HedgeSummary {
+        let book_kg = Self::book_quantity(&env);
+        let hedged_kg = Self::get_hedged_quantity(env.clone());
+        let open_hedges = Self::get_open_hedges(env.clone());
+
+        // Open shorts are marked to the collateral registry's base price, if it has one
+        let collateral = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_COFFEE_COLLATERAL,
+            &DataKey::CoffeeCollateral,
+        );
+        let base_price: Option<CoffeePrice> = invoke_peer(
+            &env,
+            &collateral,
+            "get_base_price",
+            Vec::new(&env),
+            ContractError::PeerCallFailed,
+        );
+        let mut unrealized_pnl = 0i128;
+        if let Some(base_price) = base_price {
+            for hedge_id in open_hedges.iter() {
+                let hedge = Self::load_open_hedge(&env, hedge_id);
+                unrealized_pnl += Self::short_pnl(&hedge, base_price.price_per_kg);
+            }
+        }
+
+        HedgeSummary {
+            book_kg,
+            hedged_kg,
+            hedge_ratio_bps: if book_kg == 0 {
+                0
+            } else {
+                hedged_kg * BASIS_POINTS / book_kg
+            },
+            open_hedges: open_hedges.len(),
+            unrealized_pnl,
+            realized_pnl: Self::get_realized_pnl(env),
+        }
+    }
+
+    /// Set the peer registry (admin only)
+    pub fn set_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Registry, &registry);
+
+        log!(&env, "Registry set to: {}", registry);
+    }
+
+    /// Set the trade router used as a venue when the registry has none (admin only)
+    pub fn set_trade_router(env: Env, admin: Address, router: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::TradeRouter, &router);
+
+        log!(&env, "Trade router set to: {}", router);
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause opening hedges (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether opening hedges is paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn short_pnl(hedge: &HedgePosition, price: u128) -> i128 {
+        (hedge.entry_price as i128 - price as i128) * hedge.quantity_kg as i128
+    }
+
+    fn book_quantity(env: &Env) -> u128 {
+        let collateral = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_COFFEE_COLLATERAL,
+            &DataKey::CoffeeCollateral,
+        );
+        invoke_peer(
+            env,
+            &collateral,
+            "get_pledged_quantity",
+            Vec::new(env),
+            ContractError::PeerCallFailed,
+        )
+    }
+
+    fn ensure_within_book(env: &Env, hedged_kg: u128) {
+        let book_kg = Self::book_quantity(env);
+        if hedged_kg > book_kg {
+            panic!(
+                "Hedges would cover {} kg against a collateral book of {} kg",
+                hedged_kg, book_kg
+            );
+        }
+    }
+
+    fn load_open_hedge(env: &Env, hedge_id: u64) -> HedgePosition {
+        let hedge =
+            Self::get_hedge(env.clone(), hedge_id).unwrap_or_else(|| panic!("Hedge not found"));
+        if hedge.status != HedgeStatus::Open {
+            panic!("Hedge is not open");
+        }
+        hedge
+    }
+
+    fn store_hedge(env: &Env, hedge: &HedgePosition) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Hedge(hedge.id), hedge);
+        extend_persistent_ttl(env, &DataKey::Hedge(hedge.id));
+    }
+
+    fn verify_committee_member(env: &Env, member: &Address) {
+        let governance = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_GOVERNANCE,
+            &DataKey::Governance,
+        );
+        let committee: Vec<CommitteeMember> = invoke_peer(
+            env,
+            &governance,
+            "get_committee_members",
+            Vec::new(env),
+            ContractError::PeerCallFailed,
+        );
+        if !committee.iter().any(|m| m.address == *member) {
+            panic!("Only committee members can manage hedges");
+        }
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage the hedge book");
+        }
+    }
+}
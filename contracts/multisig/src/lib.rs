@@ -0,0 +1,572 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, AdminAction, AdminOp, ContractInfo, ProposalStatus, Role,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, symbol_short, vec, Address, Env, IntoVal, String,
+    Symbol, TryFromVal, Val, Vec,
+};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: signer set and counters
+    Signers,
+    Threshold,
+    ActionCounter,
+    // Persistent: admin actions and their confirmations
+    Action(u64),
+    Confirmation(u64, Address),
+}
+
+// Function names recorded on actions the multisig applies to itself
+const MULTICALL: Symbol = symbol_short!("multicall");
+const UPDATE_SIGNERS: Symbol = symbol_short!("signers");
+const UPDATE_ROLE: Symbol = symbol_short!("role");
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Multisig;
+
+#[contractimpl]
+impl Multisig {
+    /// Initialize the N-of-M multisig with its signer set
+    ///
+    /// There is no admin to authorize this, so deploy the multisig through the
+    /// deployer's `deploy_protocol`, which initializes it in the same transaction.
+    pub fn initialize(env: Env, signers: Vec<Address>, threshold: u32) {
+        ensure_uninitialized(&env, None);
+
+        Self::validate_signer_set(&signers, threshold);
+
+        env.storage().instance().set(&DataKey::Signers, &signers);
+        env.storage()
+            .instance()
+            .set(&DataKey::Threshold, &threshold);
+        env.storage().instance().set(&DataKey::ActionCounter, &0u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Multisig initialized with {}-of-{} signers",
+            threshold,
+            signers.len()
+        );
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "multisig"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Propose an admin action against a protocol contract (signers only)
+    ///
+    /// The proposer's confirmation is recorded immediately.
+    pub fn propose_action(
+        env: Env,
+        proposer: Address,
+        target: Address,
+        function: Symbol,
+        args: Vec<Val>,
+    ) -> u64 {
+        proposer.require_auth();
+
+        // Verify proposer is a signer
+        Self::verify_signer(&env, &proposer);
+
+        let action_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ActionCounter)
+            .unwrap_or(0);
+        let action_id = action_counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ActionCounter, &action_id);
+
+        let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap();
+        let action = AdminAction {
+            id: action_id,
+            proposer: proposer.clone(),
+            target,
+            function,
+            args,
+            confirmations: 1,
+            status: if threshold <= 1 {
+                ProposalStatus::Approved
+            } else {
+                ProposalStatus::Pending
+            },
+            created_at: env.ledger().timestamp(),
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Action(action_id), &action);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Confirmation(action_id, proposer.clone()), &true);
+        extend_persistent_ttl(&env, &DataKey::Action(action_id));
+        extend_persistent_ttl(&env, &DataKey::Confirmation(action_id, proposer.clone()));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Admin action {} proposed by {}: {} on {}",
+            action_id,
+            proposer,
+            action.function,
+            action.target
+        );
+
+        action_id
+    }
+
+    /// Propose a batch of admin calls executed atomically as one action (signers only)
+    ///
+    /// Confirmed and executed like any other action; if any call fails, the whole
+    /// batch reverts, so a reconfiguration never lands half-applied.
+    pub fn propose_batch(env: Env, proposer: Address, ops: Vec<AdminOp>) -> u64 {
+        if ops.is_empty() {
+            panic!("Batch must contain at least one call");
+        }
+
+        let args = vec![&env, ops.into_val(&env)];
+        Self::propose_action(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            MULTICALL,
+            args,
+        )
+    }
+
+    /// Propose replacing the signer set and threshold (signers only)
+    ///
+    /// Confirmed and executed like any other action; `execute_action` applies it
+    /// directly, since a contract cannot call back into itself.
+    pub fn propose_signer_update(
+        env: Env,
+        proposer: Address,
+        new_signers: Vec<Address>,
+        new_threshold: u32,
+    ) -> u64 {
+        Self::validate_signer_set(&new_signers, new_threshold);
+
+        let args = vec![
+            &env,
+            new_signers.into_val(&env),
+            new_threshold.into_val(&env),
+        ];
+        Self::propose_action(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            UPDATE_SIGNERS,
+            args,
+        )
+    }
+
+    /// Propose granting a role, or revoking it with None (signers only)
+    ///
+    /// Applied by `execute_action` like a signer update. The multisig has no
+    /// admin, so a role nobody holds cannot be used until one is granted, and
+    /// revoking the pauser lifts any pause it left in place.
+    pub fn propose_role_update(
+        env: Env,
+        proposer: Address,
+        role: Role,
+        holder: Option<Address>,
+    ) -> u64 {
+        let args = vec![&env, role.into_val(&env), holder.into_val(&env)];
+        Self::propose_action(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            UPDATE_ROLE,
+            args,
+        )
+    }
+
+    /// Confirm a pending admin action (signers only)
+    pub fn confirm_action(env: Env, signer: Address, action_id: u64) {
+        signer.require_auth();
+
+        // Verify caller is a signer
+        Self::verify_signer(&env, &signer);
+
+        let mut action = Self::get_action_or_panic(&env, action_id);
+
+        if action.status != ProposalStatus::Pending && action.status != ProposalStatus::Approved {
+            panic!("Action is no longer open for confirmation");
+        }
+
+        let confirmation_key = DataKey::Confirmation(action_id, signer.clone());
+        if env.storage().persistent().has(&confirmation_key) {
+            panic!("Signer has already confirmed this action");
+        }
+
+        env.storage().persistent().set(&confirmation_key, &true);
+        action.confirmations = Self::count_confirmations(&env, action_id);
+
+        let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap();
+        if action.confirmations >= threshold {
+            action.status = ProposalStatus::Approved;
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Action(action_id), &action);
+
+        log!(
+            &env,
+            "Admin action {} confirmed by {}. Confirmations: {}/{}",
+            action_id,
+            signer,
+            action.confirmations,
+            threshold
+        );
+    }
+
+    /// Revoke a previously given confirmation before execution (signers only)
+    pub fn revoke_confirmation(env: Env, signer: Address, action_id: u64) {
+        signer.require_auth();
+
+        // Verify caller is a signer
+        Self::verify_signer(&env, &signer);
+
+        let mut action = Self::get_action_or_panic(&env, action_id);
+
+        if action.status != ProposalStatus::Pending && action.status != ProposalStatus::Approved {
+            panic!("Action is no longer open for confirmation");
+        }
+
+        let confirmation_key = DataKey::Confirmation(action_id, signer.clone());
+        if !env.storage().persistent().has(&confirmation_key) {
+            panic!("Signer has not confirmed this action");
+        }
+
+        env.storage().persistent().remove(&confirmation_key);
+        action.confirmations = Self::count_confirmations(&env, action_id);
+
+        let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap();
+        if action.confirmations < threshold {
+            action.status = ProposalStatus::Pending;
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Action(action_id), &action);
+
+        log!(
+            &env,
+            "Confirmation for admin action {} revoked by {}",
+            action_id,
+            signer
+        );
+    }
+
+    /// Execute an admin action once the confirmation threshold is met (signers only)
+    ///
+    /// Confirmations are recounted over the current signer set against the
+    /// current threshold, so a signer update cannot leave a stale approval behind.
+    pub fn execute_action(env: Env, executor: Address, action_id: u64) -> Val {
+        executor.require_auth();
+
+        // Verify executor is a signer
+        Self::verify_signer(&env, &executor);
+
+        let mut action = Self::get_action_or_panic(&env, action_id);
+
+        if action.status != ProposalStatus::Pending && action.status != ProposalStatus::Approved {
+            panic!("Action is no longer open for execution");
+        }
+
+        let threshold: u32 = env.storage().instance().get(&DataKey::Threshold).unwrap();
+        action.confirmations = Self::count_confirmations(&env, action_id);
+        if action.confirmations < threshold {
+            panic!("Action must reach the confirmation threshold before execution");
+        }
+
+        // Updates to the multisig itself still apply, so a pauser can always be revoked
+        if action.target != env.current_contract_address() || Self::is_batch(&env, &action) {
+            ensure_not_paused(&env);
+        }
+
+        // Mark executed before the external call
+        action.status = ProposalStatus::Executed;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Action(action_id), &action);
+
+        // This contract is the admin of the target, so its own address must be
+        // among the args; the direct-caller auth satisfies `admin.require_auth()`.
+        let result: Val = if Self::is_batch(&env, &action) {
+            Self::execute_batch(&env, &action).into_val(&env)
+        } else if Self::is_signer_update(&env, &action) {
+            Self::apply_signer_update(&env, &action);
+            ().into_val(&env)
+        } else if Self::is_role_update(&env, &action) {
+            Self::apply_role_update(&env, &action);
+            ().into_val(&env)
+        } else {
+            env.invoke_contract(&action.target, &action.function, action.args.clone())
+        };
+
+        log!(
+            &env,
+            "Admin action {} executed by {}: {} on {}",
+            action_id,
+            executor,
+            action.function,
+            action.target
+        );
+
+        result
+    }
+
+    /// Cancel a pending admin action (proposer only)
+    pub fn cancel_action(env: Env, proposer: Address, action_id: u64) {
+        proposer.require_auth();
+
+        let mut action = Self::get_action_or_panic(&env, action_id);
+
+        if action.proposer != proposer {
+            panic!("Only the proposer can cancel an action");
+        }
+
+        if action.status == ProposalStatus::Executed {
+            panic!("Action has already been executed");
+        }
+
+        action.status = ProposalStatus::Rejected;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Action(action_id), &action);
+
+        log!(&env, "Admin action {} cancelled by {}", action_id, proposer);
+    }
+
+    /// Get the calls of a batch action, if the action is a batch
+    pub fn get_batch(env: Env, action_id: u64) -> Option<Vec<AdminOp>> {
+        let action = Self::get_action(env.clone(), action_id)?;
+        if !Self::is_batch(&env, &action) {
+            return None;
+        }
+        Vec::try_from_val(&env, &action.args.get(0)?).ok()
+    }
+
+    /// Get admin action details
+    pub fn get_action(env: Env, action_id: u64) -> Option<AdminAction> {
+        env.storage().persistent().get(&DataKey::Action(action_id))
+    }
+
+    /// Check whether a signer has confirmed an action
+    pub fn has_confirmed(env: Env, signer: Address, action_id: u64) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Confirmation(action_id, signer))
+    }
+
+    /// Get current signers
+    pub fn get_signers(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Signers)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get current confirmation threshold
+    pub fn get_threshold(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Threshold)
+            .unwrap_or(0)
+    }
+
+    /// Get the address holding a role, if one has been granted
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Hold execution of admin calls to other contracts (pauser only)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        require_role(&env, Role::Pauser, &caller, &env.current_contract_address());
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser only)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        require_role(&env, Role::Pauser, &caller, &env.current_contract_address());
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether execution of admin calls to other contracts is paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Internal helper functions
+    fn count_confirmations(env: &Env, action_id: u64) -> u32 {
+        // Only seats in the current signer set count towards the threshold
+        let mut confirmations = 0u32;
+        for signer in Self::get_signers(env.clone()).iter() {
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Confirmation(action_id, signer))
+            {
+                confirmations += 1;
+            }
+        }
+        confirmations
+    }
+
+    fn is_batch(env: &Env, action: &AdminAction) -> bool {
+        action.target == env.current_contract_address() && action.function == MULTICALL
+    }
+
+    fn is_signer_update(env: &Env, action: &AdminAction) -> bool {
+        action.target == env.current_contract_address() && action.function == UPDATE_SIGNERS
+    }
+
+    fn apply_signer_update(env: &Env, action: &AdminAction) {
+        let new_signers: Vec<Address> = action
+            .args
+            .get(0)
+            .and_then(|signers| Vec::try_from_val(env, &signers).ok())
+            .unwrap_or_else(|| panic!("Malformed signer update"));
+        let new_threshold: u32 = action
+            .args
+            .get(1)
+            .and_then(|threshold| u32::try_from_val(env, &threshold).ok())
+            .unwrap_or_else(|| panic!("Malformed signer update"));
+        Self::validate_signer_set(&new_signers, new_threshold);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Signers, &new_signers);
+        env.storage()
+            .instance()
+            .set(&DataKey::Threshold, &new_threshold);
+
+        log!(
+            env,
+            "Multisig signers updated to {}-of-{}",
+            new_threshold,
+            new_signers.len()
+        );
+    }
+
+    fn is_role_update(env: &Env, action: &AdminAction) -> bool {
+        action.target == env.current_contract_address() && action.function == UPDATE_ROLE
+    }
+
+    fn apply_role_update(env: &Env, action: &AdminAction) {
+        let role: Role = action
+            .args
+            .get(0)
+            .and_then(|role| Role::try_from_val(env, &role).ok())
+            .unwrap_or_else(|| panic!("Malformed role update"));
+        let holder: Option<Address> = action
+            .args
+            .get(1)
+            .and_then(|holder| Option::try_from_val(env, &holder).ok())
+            .unwrap_or_else(|| panic!("Malformed role update"));
+        // Nobody else could lift a revoked pauser's pause
+        if role == Role::Pauser && holder.is_none() {
+            set_paused(env, false);
+        }
+        set_role_holder(env, role.clone(), holder.clone());
+
+        log!(env, "Multisig role {:?} assigned to {:?}", role, holder);
+    }
+
+    fn execute_batch(env: &Env, action: &AdminAction) -> Vec<Val> {
+        // Calls run in order within this transaction; a failure reverts the ones before it
+        let ops: Vec<AdminOp> = action
+            .args
+            .get(0)
+            .and_then(|ops| Vec::try_from_val(env, &ops).ok())
+            .unwrap_or_else(|| panic!("Malformed batch action"));
+
+        let mut results = Vec::new(env);
+        for op in ops.iter() {
+            results.push_back(env.invoke_contract::<Val>(&op.target, &op.function, op.args));
+        }
+        results
+    }
+
+    fn verify_signer(env: &Env, address: &Address) {
+        let signers: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Signers)
+            .unwrap_or(Vec::new(env));
+
+        for signer in signers.iter() {
+            if signer == *address {
+                return;
+            }
+        }
+
+        panic!("Address is not a multisig signer");
+    }
+
+    fn validate_signer_set(signers: &Vec<Address>, threshold: u32) {
+        if signers.is_empty() {
+            panic!("Multisig must have at least one signer");
+        }
+
+        if threshold == 0 || threshold > signers.len() {
+            panic!("Threshold must be between 1 and the number of signers");
+        }
+
+        for i in 0..signers.len() {
+            for j in (i + 1)..signers.len() {
+                if signers.get_unchecked(i) == signers.get_unchecked(j) {
+                    panic!("Duplicate multisig signer");
+                }
+            }
+        }
+    }
+
+    fn get_action_or_panic(env: &Env, action_id: u64) -> AdminAction {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Action(action_id))
+            .unwrap_or_else(|| panic!("Admin action not found"))
+    }
+}
@@ -0,0 +1,198 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, ContractInfo, Role,
+};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env, String, Symbol, Vec};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: admin and feed index
+    Admin,
+    Feeds,
+    // Persistent: latest price per feed
+    Price(Symbol),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeedPrice {
+    pub price: u128,     // Latest price, in the feed's own precision
+    pub updated_at: u64, // Ledger time the price was pushed
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Push price feed read by the vaults, coffee collateral and governance
+///
+/// The admin pushes each feed's price; readers call `get_price(feed)` and
+/// `last_updated(feed)` and judge staleness themselves.
+#[contract]
+pub struct PriceOracle;
+
+#[contractimpl]
+impl PriceOracle {
+    /// Initialize the price feed with the admin allowed to push prices
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Feeds, &Vec::<Symbol>::new(&env));
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Price oracle initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "oracle"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Push the current price of a feed (admin only)
+    pub fn set_price(env: Env, admin: Address, feed: Symbol, price: u128) {
+        admin.require_auth();
+        ensure_not_paused(&env);
+        Self::verify_admin(&env, &admin);
+
+        if price == 0 {
+            panic!("Price must be positive");
+        }
+
+        let key = DataKey::Price(feed.clone());
+        if !env.storage().persistent().has(&key) {
+            let mut feeds = Self::get_feeds(env.clone());
+            feeds.push_back(feed.clone());
+            env.storage().instance().set(&DataKey::Feeds, &feeds);
+        }
+
+        env.storage().persistent().set(
+            &key,
+            &FeedPrice {
+                price,
+                updated_at: env.ledger().timestamp(),
+            },
+        );
+        extend_persistent_ttl(&env, &key);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Price for {} set to {}", feed, price);
+    }
+
+    /// Get the latest price of a feed
+    pub fn get_price(env: Env, feed: Symbol) -> u128 {
+        Self::read_feed(&env, feed).price
+    }
+
+    /// Get the ledger time a feed's price was last pushed
+    pub fn last_updated(env: Env, feed: Symbol) -> u64 {
+        Self::read_feed(&env, feed).updated_at
+    }
+
+    /// List every feed that has been priced
+    pub fn get_feeds(env: Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Feeds)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause price updates (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether price updates are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn read_feed(env: &Env, feed: Symbol) -> FeedPrice {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Price(feed))
+            .unwrap_or_else(|| panic!("No price for feed"))
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can update prices");
+        }
+    }
+}
@@ -0,0 +1,479 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    hash_deposit_terms, is_initialized, mark_initialized, require_role, role_holder, set_paused,
+    set_role_holder, stored_schema_version, ContractInfo, DepositReceipt, LockPeriod, Role,
+    VaultType, REGISTRY_RECEIPTS,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, Address, Bytes, BytesN, Env, String, Symbol, Vec,
+};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and token counter
+    Admin,
+    Vaults,
+    NextTokenId,
+    AssetType(Address),
+    // Persistent: per-token records
+    Receipt(u64),
+    Owner(u64),
+    Balance(Address),
+}
+
+// Largest metadata document `token_uri` renders
+const METADATA_BUFFER: usize = 1024;
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Receipts;
+
+#[contractimpl]
+impl Receipts {
+    /// Initialize the deposit receipts collection
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Vaults, &Vec::<Address>::new(&env));
+        env.storage().instance().set(&DataKey::NextTokenId, &1u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Deposit receipts initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_RECEIPTS),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Register a vault allowed to export receipts (admin only)
+    pub fn add_vault(env: Env, admin: Address, vault: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mut vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(&env));
+
+        if vaults.contains(&vault) {
+            panic!("Vault already registered");
+        }
+
+        vaults.push_back(vault.clone());
+        env.storage().instance().set(&DataKey::Vaults, &vaults);
+
+        log!(&env, "Receipts vault registered: {}", vault);
+    }
+
+    /// Mint a transferable token for an open deposit receipt (vault only)
+    pub fn mint(env: Env, vault: Address, owner: Address, receipt: DepositReceipt) -> u64 {
+        vault.require_auth();
+        Self::verify_vault(&env, &vault);
+
+        if receipt.terms.vault != vault {
+            panic!("Receipt was not issued by this vault");
+        }
+        if receipt.nft_id.is_some() || receipt.closed_at.is_some() {
+            panic!("Receipt is already exported or closed");
+        }
+        if hash_deposit_terms(&env, &receipt.terms) != receipt.terms_hash {
+            panic!("Receipt terms hash mismatch");
+        }
+
+        let token_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextTokenId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextTokenId, &(token_id + 1));
+
+        let mut token_receipt = receipt;
+        token_receipt.nft_id = Some(token_id);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(token_id), &token_receipt);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Owner(token_id), &owner);
+        extend_persistent_ttl(&env, &DataKey::Receipt(token_id));
+        extend_persistent_ttl(&env, &DataKey::Owner(token_id));
+        Self::adjust_balance(&env, &owner, true);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Receipt token {} minted to {} for receipt {} of vault {}",
+            token_id,
+            owner,
+            token_receipt.id,
+            vault
+        );
+
+        token_id
+    }
+
+    /// Transfer a receipt token to a new owner
+    pub fn transfer(env: Env, from: Address, to: Address, token_id: u64) {
+        from.require_auth();
+        ensure_not_paused(&env);
+
+        let owner = Self::owner_of(env.clone(), token_id);
+        if owner != from {
+            panic!("Only the owner can transfer a receipt token");
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Owner(token_id), &to);
+        extend_persistent_ttl(&env, &DataKey::Owner(token_id));
+        Self::adjust_balance(&env, &from, false);
+        Self::adjust_balance(&env, &to, true);
+
+        log!(
+            &env,
+            "Receipt token {} transferred from {} to {}",
+            token_id,
+            from,
+            to
+        );
+    }
+
+    /// Mark a token's position as withdrawn (issuing vault only)
+    pub fn settle(env: Env, vault: Address, token_id: u64) {
+        vault.require_auth();
+
+        let mut receipt = Self::get_receipt(env.clone(), token_id);
+        if receipt.terms.vault != vault {
+            panic!("Only the issuing vault can settle a receipt token");
+        }
+        if receipt.closed_at.is_some() {
+            panic!("Receipt token already settled");
+        }
+
+        receipt.closed_at = Some(env.ledger().timestamp());
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(token_id), &receipt);
+        extend_persistent_ttl(&env, &DataKey::Receipt(token_id));
+
+        log!(
+            &env,
+            "Receipt token {} settled by vault {}",
+            token_id,
+            vault
+        );
+    }
+
+    /// Get the deposit receipt a token represents
+    pub fn get_receipt(env: Env, token_id: u64) -> DepositReceipt {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Receipt(token_id))
+            .unwrap_or_else(|| panic!("Receipt token not found"))
+    }
+
+    /// Get the current owner of a token
+    pub fn owner_of(env: Env, token_id: u64) -> Address {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Owner(token_id))
+            .unwrap_or_else(|| panic!("Receipt token not found"))
+    }
+
+    /// Get the number of tokens held by an address
+    pub fn balance(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Balance(owner))
+            .unwrap_or(0)
+    }
+
+    /// Check a token's recorded terms against their hash
+    pub fn verify_terms(env: Env, token_id: u64) -> bool {
+        let receipt = Self::get_receipt(env.clone(), token_id);
+        hash_deposit_terms(&env, &receipt.terms) == receipt.terms_hash
+    }
+
+    /// Label a deposit asset with its vault type for wallet metadata (admin only)
+    pub fn set_asset_type(env: Env, admin: Address, asset: Address, vault_type: VaultType) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::AssetType(asset.clone()), &vault_type);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Asset {} labelled as {:?}", asset, vault_type);
+    }
+
+    /// Get the vault type an asset is labelled with
+    pub fn get_asset_type(env: Env, asset: Address) -> Option<VaultType> {
+        env.storage().instance().get(&DataKey::AssetType(asset))
+    }
+
+    /// Render a token as a JSON data URI wallets can display as a term-deposit certificate
+    ///
+    /// Everything is read from the stored receipt, so no off-chain metadata server is
+    /// needed. Amounts are strings in underlying units and times are Unix timestamps.
+    pub fn token_uri(env: Env, token_id: u64) -> String {
+        let receipt = Self::get_receipt(env.clone(), token_id);
+        let terms = &receipt.terms;
+
+        let vault_type = match Self::get_asset_type(env.clone(), terms.asset.clone()) {
+            Some(VaultType::USDC) => "USDC",
+            Some(VaultType::PAXG) => "PAXG",
+            Some(VaultType::WisdomTreeGold) => "WisdomTree Gold",
+            None => "Unknown",
+        };
+        let lock_period = match terms.lock_period {
+            LockPeriod::ThreeMonths => "3 months",
+            LockPeriod::SixMonths => "6 months",
+            LockPeriod::TwelveMonths => "12 months",
+        };
+        let status = if receipt.closed_at.is_some() {
+            "Withdrawn"
+        } else if env.ledger().timestamp() >= terms.unlock_time {
+            "Matured"
+        } else {
+            "Locked"
+        };
+
+        let mut json = Bytes::new(&env);
+        json.extend_from_slice(b"data:application/json;utf8,{\"name\":\"Term Deposit #");
+        Self::push_number(&mut json, token_id as u128);
+        json.extend_from_slice(b"\",\"description\":\"");
+        json.extend_from_slice(lock_period.as_bytes());
+        json.extend_from_slice(b" ");
+        json.extend_from_slice(vault_type.as_bytes());
+        json.extend_from_slice(b" term deposit certificate\",\"attributes\":[");
+        Self::push_attribute(&mut json, "Vault Type", vault_type.as_bytes(), true);
+        Self::push_attribute(&mut json, "Lock Period", lock_period.as_bytes(), true);
+        Self::push_number_attribute(&mut json, "Amount", terms.amount, true);
+        Self::push_number_attribute(&mut json, "USD Value", terms.usd_value, true);
+        Self::push_number_attribute(&mut json, "Yield Rate (bps)", terms.yield_rate, false);
+        Self::push_date_attribute(&mut json, "Deposit Time", terms.deposit_time);
+        Self::push_date_attribute(&mut json, "Unlock Time", terms.unlock_time);
+        Self::push_attribute(&mut json, "Status", status.as_bytes(), true);
+        json.pop_back();
+        json.extend_from_slice(b"],\"vault\":\"");
+        Self::push_string(&mut json, &terms.vault.to_string());
+        json.extend_from_slice(b"\",\"receipt_id\":");
+        Self::push_number(&mut json, receipt.id as u128);
+        json.extend_from_slice(b",\"terms_hash\":\"");
+        for byte in receipt.terms_hash.to_array() {
+            json.extend_from_slice(&Self::hex_byte(byte));
+        }
+        json.extend_from_slice(b"\"}");
+
+        let len = json.len() as usize;
+        if len > METADATA_BUFFER {
+            panic!("Token metadata exceeds {} bytes", METADATA_BUFFER);
+        }
+        let mut buffer = [0u8; METADATA_BUFFER];
+        json.copy_into_slice(&mut buffer[..len]);
+        String::from_bytes(&env, &buffer[..len])
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause receipt transfers (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether receipt transfers are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
+
+    /// Internal helper functions
+    fn adjust_balance(env: &Env, owner: &Address, increase: bool) {
+        let balance = Self::balance(env.clone(), owner.clone());
+        let updated = if increase { balance + 1 } else { balance - 1 };
+
+        if updated == 0 {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Balance(owner.clone()));
+        } else {
+            env.storage()
+                .persistent()
+                .set(&DataKey::Balance(owner.clone()), &updated);
+            extend_persistent_ttl(env, &DataKey::Balance(owner.clone()));
+        }
+    }
+
+    fn push_attribute(json: &mut Bytes, trait_type: &str, value: &[u8], quoted: bool) {
+        // Each attribute ends with a comma; the caller drops the last one
+        json.extend_from_slice(b"{\"trait_type\":\"");
+        json.extend_from_slice(trait_type.as_bytes());
+        let (open, close): (&[u8], &[u8]) = if quoted {
+            (b"\",\"value\":\"", b"\"},")
+        } else {
+            (b"\",\"value\":", b"},")
+        };
+        json.extend_from_slice(open);
+        json.extend_from_slice(value);
+        json.extend_from_slice(close);
+    }
+
+    fn push_number_attribute(json: &mut Bytes, trait_type: &str, value: u128, quoted: bool) {
+        // Amounts are quoted, as they can exceed what wallets parse as exact numbers
+        let (digits, start) = Self::decimal_digits(value);
+        Self::push_attribute(json, trait_type, &digits[start..], quoted);
+    }
+
+    fn push_date_attribute(json: &mut Bytes, trait_type: &str, timestamp: u64) {
+        json.extend_from_slice(b"{\"display_type\":\"date\",");
+        json.extend_from_slice(b"\"trait_type\":\"");
+        json.extend_from_slice(trait_type.as_bytes());
+        json.extend_from_slice(b"\",\"value\":");
+        Self::push_number(json, timestamp as u128);
+        json.extend_from_slice(b"},");
+    }
+
+    fn push_number(json: &mut Bytes, value: u128) {
+        let (digits, start) = Self::decimal_digits(value);
+        json.extend_from_slice(&digits[start..]);
+    }
+
+    fn push_string(json: &mut Bytes, value: &String) {
+        // Strkeys are 56 characters
+        let mut buffer = [0u8; 64];
+        let len = value.len() as usize;
+        value.copy_into_slice(&mut buffer[..len]);
+        json.extend_from_slice(&buffer[..len]);
+    }
+
+    fn decimal_digits(mut value: u128) -> ([u8; 39], usize) {
+        // u128::MAX has 39 digits; returns the buffer and where the digits start
+        let mut digits = [0u8; 39];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                return (digits, start);
+            }
+        }
+    }
+
+    fn hex_byte(byte: u8) -> [u8; 2] {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        [HEX[(byte >> 4) as usize], HEX[(byte & 0x0f) as usize]]
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage receipts");
+        }
+    }
+
+    fn verify_vault(env: &Env, vault: &Address) {
+        let vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(env));
+
+        if !vaults.contains(vault) {
+            panic!("Caller is not a registered vault");
+        }
+    }
+}
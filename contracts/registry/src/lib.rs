@@ -0,0 +1,215 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, ContractInfo, Role,
+};
+use soroban_sdk::{contract, contractimpl, contracttype, log, Address, Env, String, Symbol, Vec};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: admin and name index
+    Admin,
+    Names,
+    // Persistent: name to address entries
+    Entry(Symbol),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Registry;
+
+#[contractimpl]
+impl Registry {
+    /// Initialize the address registry (admin is expected to be governance or the timelock)
+    pub fn initialize(env: Env, admin: Address) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::Names, &Vec::<Symbol>::new(&env));
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Address registry initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "registry"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Point a name at a contract address (admin only)
+    pub fn set_address(env: Env, admin: Address, name: Symbol, address: Address) {
+        admin.require_auth();
+        ensure_not_paused(&env);
+        Self::verify_admin(&env, &admin);
+
+        let previous: Option<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Entry(name.clone()));
+
+        if previous.is_none() {
+            let mut names: Vec<Symbol> = env
+                .storage()
+                .instance()
+                .get(&DataKey::Names)
+                .unwrap_or(Vec::new(&env));
+            names.push_back(name.clone());
+            env.storage().instance().set(&DataKey::Names, &names);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Entry(name.clone()), &address);
+        extend_persistent_ttl(&env, &DataKey::Entry(name.clone()));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Registry entry {} set to {} (previous: {:?})",
+            name,
+            address,
+            previous
+        );
+    }
+
+    /// Remove a name from the registry (admin only)
+    pub fn remove_address(env: Env, admin: Address, name: Symbol) {
+        admin.require_auth();
+        ensure_not_paused(&env);
+        Self::verify_admin(&env, &admin);
+
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Entry(name.clone()))
+        {
+            panic!("Registry entry not found");
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Entry(name.clone()));
+
+        let names: Vec<Symbol> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Names)
+            .unwrap_or(Vec::new(&env));
+        let mut remaining = Vec::new(&env);
+        for existing in names.iter() {
+            if existing != name {
+                remaining.push_back(existing);
+            }
+        }
+        env.storage().instance().set(&DataKey::Names, &remaining);
+
+        log!(&env, "Registry entry {} removed", name);
+    }
+
+    /// Resolve a name to its current address
+    pub fn get_address(env: Env, name: Symbol) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Entry(name))
+    }
+
+    /// List all registered names
+    pub fn get_names(env: Env) -> Vec<Symbol> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Names)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause registry entry changes (pauser, or admin if no pauser is set)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether registry entry changes are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can update the registry");
+        }
+    }
+}
@@ -0,0 +1,798 @@
+#![no_std]
+use shared::{
+    airdrop_leaf, ensure_not_paused, ensure_uninitialized, extend_instance_ttl,
+    extend_persistent_ttl, is_initialized, mark_initialized, require_role, role_holder, set_paused,
+    set_role_holder, stored_schema_version, verify_merkle_proof, Airdrop, ContractInfo,
+    EmissionSpan, LockPeriod, RewardEpoch, RewardStake, Role, MIN_REWARD_EPOCH_DURATION,
+    REWARD_PRECISION,
+};
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, Address, BytesN, Env, String, Symbol, Vec,
+};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and pool accumulator
+    Admin,
+    RewardToken,
+    Vaults,
+    EpochDuration,
+    EpochStart,
+    Emission,
+    TotalWeight,
+    AccReward,
+    LastUpdate,
+    Treasury,
+    AirdropCounter,
+    TotalEmitted,
+    EmissionSpanCount,
+    // Persistent: per-stake and per-epoch records
+    Stake(Address, Address),
+    Epoch(u64),
+    // Persistent: emission history that per-epoch totals are read from
+    EmissionSpan(u64),
+    // Persistent: snapshot airdrops and their claims
+    Airdrop(u32),
+    AirdropClaimed(u32, Address),
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Rewards;
+
+#[contractimpl]
+impl Rewards {
+    /// Initialize the liquidity-mining program
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        reward_token: Address,
+        epoch_duration: u64,
+        emission_per_epoch: u128,
+    ) {
+        ensure_uninitialized(&env, None);
+
+        admin.require_auth();
+
+        if epoch_duration < MIN_REWARD_EPOCH_DURATION {
+            panic!("Epoch duration must be at least a day");
+        }
+
+        let current_time = env.ledger().timestamp();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::RewardToken, &reward_token);
+        env.storage()
+            .instance()
+            .set(&DataKey::Vaults, &Vec::<Address>::new(&env));
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochDuration, &epoch_duration);
+        env.storage()
+            .instance()
+            .set(&DataKey::EpochStart, &current_time);
+        env.storage()
+            .instance()
+            .set(&DataKey::Emission, &emission_per_epoch);
+        env.storage().instance().set(&DataKey::TotalWeight, &0u128);
+        env.storage().instance().set(&DataKey::AccReward, &0u128);
+        env.storage()
+            .instance()
+            .set(&DataKey::LastUpdate, &current_time);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Rewards initialized with token: {}, {} per epoch of {} seconds",
+            reward_token,
+            emission_per_epoch,
+            epoch_duration
+        );
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "rewards"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Register a vault allowed to report stakes (admin only)
+    pub fn add_vault(env: Env, admin: Address, vault: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        let mut vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(&env));
+
+        if vaults.contains(&vault) {
+            panic!("Vault already registered");
+        }
+
+        vaults.push_back(vault.clone());
+        env.storage().instance().set(&DataKey::Vaults, &vaults);
+
+        log!(&env, "Rewards vault registered: {}", vault);
+    }
+
+    /// Set the reward amount streamed per epoch (admin only, expected to be governance)
+    pub fn set_emission(env: Env, admin: Address, emission_per_epoch: u128) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        // Settle accrual at the old rate before switching
+        Self::update_pool(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Emission, &emission_per_epoch);
+
+        log!(
+            &env,
+            "Reward emission set to {} per epoch",
+            emission_per_epoch
+        );
+    }
+
+    /// Record a depositor's locked position (vault only)
+    pub fn register_stake(
+        env: Env,
+        vault: Address,
+        user: Address,
+        usd_value: u128,
+        lock_period: LockPeriod,
+    ) {
+        vault.require_auth();
+        Self::verify_vault(&env, &vault);
+
+        let acc_reward = Self::update_pool(&env);
+        let mut stake = Self::get_stake_or_default(&env, &vault, &user);
+        Self::settle(&mut stake, acc_reward);
+
+        let added_weight = Self::tier_weight(usd_value, &lock_period);
+        stake.weight += added_weight;
+        stake.reward_debt = (stake.weight * acc_reward) / REWARD_PRECISION;
+
+        let total_weight: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalWeight)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalWeight, &(total_weight + added_weight));
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Stake(vault.clone(), user.clone()), &stake);
+        extend_persistent_ttl(&env, &DataKey::Stake(vault.clone(), user.clone()));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Stake registered for user {} in vault {}: ${} with {:?} lock (weight {})",
+            user,
+            vault,
+            usd_value,
+            lock_period,
+            added_weight
+        );
+    }
+
+    /// Remove a depositor's position when it is withdrawn (vault only)
+    ///
+    /// Rewards accrued so far stay claimable.
+    pub fn remove_stake(env: Env, vault: Address, user: Address) {
+        vault.require_auth();
+        Self::verify_vault(&env, &vault);
+
+        let acc_reward = Self::update_pool(&env);
+        let mut stake = Self::get_stake_or_default(&env, &vault, &user);
+        Self::settle(&mut stake, acc_reward);
+
+        let total_weight: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalWeight)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalWeight, &(total_weight - stake.weight));
+
+        stake.weight = 0;
+        stake.reward_debt = 0;
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Stake(vault.clone(), user.clone()), &stake);
+
+        log!(&env, "Stake removed for user {} in vault {}", user, vault);
+    }
+
+    /// Claim all accrued rewards across vaults
+    pub fn claim_rewards(env: Env, user: Address) -> u128 {
+        user.require_auth();
+        ensure_not_paused(&env);
+
+        let acc_reward = Self::update_pool(&env);
+        let vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(&env));
+
+        let mut total_claim = 0u128;
+        for vault in vaults.iter() {
+            let stake_key = DataKey::Stake(vault.clone(), user.clone());
+            let stake: Option<RewardStake> = env.storage().persistent().get(&stake_key);
+
+            if let Some(mut stake) = stake {
+                Self::settle(&mut stake, acc_reward);
+                total_claim += stake.pending_rewards;
+                stake.pending_rewards = 0;
+
+                if stake.weight == 0 {
+                    env.storage().persistent().remove(&stake_key);
+                } else {
+                    env.storage().persistent().set(&stake_key, &stake);
+                }
+            }
+        }
+
+        if total_claim == 0 {
+            panic!("No rewards to claim");
+        }
+
+        let mut epoch_info = Self::get_epoch_or_default(&env, Self::current_epoch(env.clone()));
+        epoch_info.claimed += total_claim;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Epoch(epoch_info.epoch), &epoch_info);
+
+        let reward_token: Address = env.storage().instance().get(&DataKey::RewardToken).unwrap();
+        let reward_client = TokenClient::new(&env, &reward_token);
+        reward_client.transfer(
+            &env.current_contract_address(),
+            &user,
+            &(total_claim as i128),
+        );
+
+        log!(&env, "User {} claimed {} reward tokens", user, total_claim);
+
+        total_claim
+    }
+
+    /// Get rewards claimable by a user right now
+    pub fn pending_rewards(env: Env, user: Address) -> u128 {
+        let acc_reward = Self::projected_acc_reward(&env);
+        let vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(&env));
+
+        let mut pending = 0u128;
+        for vault in vaults.iter() {
+            let stake: Option<RewardStake> = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Stake(vault, user.clone()));
+
+            if let Some(mut stake) = stake {
+                Self::settle(&mut stake, acc_reward);
+                pending += stake.pending_rewards;
+            }
+        }
+
+        pending
+    }
+
+    /// Get the index of the current epoch
+    pub fn current_epoch(env: Env) -> u64 {
+        let epoch_start: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochStart)
+            .unwrap_or(0);
+        let epoch_duration: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochDuration)
+            .unwrap_or(1);
+
+        (env.ledger().timestamp() - epoch_start) / epoch_duration
+    }
+
+    /// Get emitted and claimed totals for an epoch
+    ///
+    /// Emission is read from the recorded emission spans rather than stored per
+    /// epoch, so it covers accrual up to the last pool update.
+    pub fn get_epoch(env: Env, epoch: u64) -> RewardEpoch {
+        let epoch_start: u64 = env.storage().instance().get(&DataKey::EpochStart).unwrap();
+        let epoch_duration: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochDuration)
+            .unwrap();
+        let from = epoch_start + epoch * epoch_duration;
+
+        let mut epoch_info = Self::get_epoch_or_default(&env, epoch);
+        epoch_info.emitted +=
+            Self::emitted_at(&env, from + epoch_duration) - Self::emitted_at(&env, from);
+        epoch_info
+    }
+
+    /// Get a user's stake in a vault
+    pub fn get_stake(env: Env, vault: Address, user: Address) -> Option<RewardStake> {
+        env.storage().persistent().get(&DataKey::Stake(vault, user))
+    }
+
+    /// Get total staked weight across all vaults
+    pub fn get_total_weight(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalWeight)
+            .unwrap_or(0)
+    }
+
+    /// Get reward amount streamed per epoch
+    pub fn get_emission(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::Emission)
+            .unwrap_or(0)
+    }
+
+    /// Set the treasury allowed to fund airdrops (admin only)
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+
+        log!(&env, "Airdrop treasury set to: {}", treasury);
+    }
+
+    /// Get the treasury allowed to fund airdrops
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
+
+    /// Fund an airdrop to yield-token holders of a snapshot (treasury only)
+    ///
+    /// Allocations are computed off-chain from the snapshot's balances and
+    /// committed to as a merkle tree of `shared::airdrop_leaf` hashes. The whole
+    /// `total_amount` of `token` moves from the treasury into this contract.
+    pub fn create_airdrop(
+        env: Env,
+        treasury: Address,
+        token: Address,
+        snapshot_id: u32,
+        merkle_root: BytesN<32>,
+        total_amount: u128,
+    ) -> u32 {
+        treasury.require_auth();
+
+        if Self::get_treasury(env.clone()) != Some(treasury.clone()) {
+            panic!("Only the treasury can fund airdrops");
+        }
+        if total_amount == 0 {
+            panic!("Airdrop amount must be greater than 0");
+        }
+
+        let airdrop_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AirdropCounter)
+            .unwrap_or(0)
+            + 1;
+        let airdrop = Airdrop {
+            token: token.clone(),
+            snapshot_id,
+            merkle_root,
+            total_amount,
+            claimed: 0,
+            created_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::AirdropCounter, &airdrop_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Airdrop(airdrop_id), &airdrop);
+        extend_persistent_ttl(&env, &DataKey::Airdrop(airdrop_id));
+        extend_instance_ttl(&env);
+
+        TokenClient::new(&env, &token).transfer(
+            &treasury,
+            &env.current_contract_address(),
+            &(total_amount as i128),
+        );
+
+        log!(
+            &env,
+            "Airdrop {} of {} {} funded for snapshot {}",
+            airdrop_id,
+            total_amount,
+            token,
+            snapshot_id
+        );
+
+        airdrop_id
+    }
+
+    /// Claim an airdrop allocation by proving it is in the airdrop's merkle tree
+    pub fn claim_airdrop(
+        env: Env,
+        user: Address,
+        airdrop_id: u32,
+        amount: u128,
+        proof: Vec<BytesN<32>>,
+    ) -> u128 {
+        user.require_auth();
+        ensure_not_paused(&env);
+
+        let mut airdrop = Self::get_airdrop(env.clone(), airdrop_id)
+            .unwrap_or_else(|| panic!("Airdrop not found"));
+
+        let claimed_key = DataKey::AirdropClaimed(airdrop_id, user.clone());
+        if env.storage().persistent().has(&claimed_key) {
+            panic!("Airdrop already claimed");
+        }
+
+        let leaf = airdrop_leaf(&env, &user, amount);
+        if !verify_merkle_proof(&env, &airdrop.merkle_root, leaf, &proof) {
+            panic!("Invalid airdrop proof");
+        }
+        if airdrop.claimed + amount > airdrop.total_amount {
+            panic!("Airdrop is exhausted");
+        }
+
+        env.storage().persistent().set(&claimed_key, &true);
+        extend_persistent_ttl(&env, &claimed_key);
+        airdrop.claimed += amount;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Airdrop(airdrop_id), &airdrop);
+
+        TokenClient::new(&env, &airdrop.token).transfer(
+            &env.current_contract_address(),
+            &user,
+            &(amount as i128),
+        );
+
+        log!(
+            &env,
+            "User {} claimed {} from airdrop {}",
+            user,
+            amount,
+            airdrop_id
+        );
+
+        amount
+    }
+
+    /// Get an airdrop by ID
+    pub fn get_airdrop(env: Env, airdrop_id: u32) -> Option<Airdrop> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Airdrop(airdrop_id))
+    }
+
+    /// Whether a user has claimed their allocation of an airdrop
+    pub fn has_claimed_airdrop(env: Env, airdrop_id: u32, user: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::AirdropClaimed(airdrop_id, user))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause reward and airdrop claims (pauser, or admin if no pauser is set)
+    ///
+    /// Stakes keep accruing while claims are paused.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether reward and airdrop claims are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+        Self::verify_admin(&env, &admin);
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Internal helper functions
+    fn update_pool(env: &Env) -> u128 {
+        let current_time = env.ledger().timestamp();
+        let last_update: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::LastUpdate)
+            .unwrap_or(current_time);
+        let mut acc_reward: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AccReward)
+            .unwrap_or(0);
+
+        if current_time <= last_update {
+            return acc_reward;
+        }
+
+        let total_weight: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalWeight)
+            .unwrap_or(0);
+        if total_weight > 0 {
+            let emission: u128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::Emission)
+                .unwrap_or(0);
+            let epoch_duration: u64 = env
+                .storage()
+                .instance()
+                .get(&DataKey::EpochDuration)
+                .unwrap();
+
+            // The rate is constant since the last update, so the whole window accrues at once
+            let emitted =
+                (emission * (current_time - last_update) as u128) / epoch_duration as u128;
+            if emitted > 0 {
+                acc_reward += (emitted * REWARD_PRECISION) / total_weight;
+                env.storage()
+                    .instance()
+                    .set(&DataKey::AccReward, &acc_reward);
+                Self::record_emission_span(env, last_update, current_time, emission, emitted);
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::LastUpdate, &current_time);
+
+        acc_reward
+    }
+
+    fn record_emission_span(env: &Env, start: u64, end: u64, emission: u128, emitted: u128) {
+        let emitted_before: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalEmitted)
+            .unwrap_or(0);
+        let span_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EmissionSpanCount)
+            .unwrap_or(0);
+
+        let key = DataKey::EmissionSpan(span_count);
+        env.storage().persistent().set(
+            &key,
+            &EmissionSpan {
+                start,
+                end,
+                emitted_before,
+                emission,
+            },
+        );
+        extend_persistent_ttl(env, &key);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalEmitted, &(emitted_before + emitted));
+        env.storage()
+            .instance()
+            .set(&DataKey::EmissionSpanCount, &(span_count + 1));
+    }
+
+    fn emitted_at(env: &Env, time: u64) -> u128 {
+        // Spans are stored in time order, so find the first one ending at or after `time`
+        let span_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EmissionSpanCount)
+            .unwrap_or(0);
+        let (mut low, mut high) = (0, span_count);
+        while low < high {
+            let mid = (low + high) / 2;
+            if Self::get_emission_span(env, mid).end < time {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        if low == span_count {
+            // Past the last recorded span
+            return env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalEmitted)
+                .unwrap_or(0);
+        }
+
+        let span = Self::get_emission_span(env, low);
+        if time <= span.start {
+            return span.emitted_before;
+        }
+        let epoch_duration: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochDuration)
+            .unwrap();
+        span.emitted_before + (span.emission * (time - span.start) as u128) / epoch_duration as u128
+    }
+
+    fn get_emission_span(env: &Env, index: u64) -> EmissionSpan {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EmissionSpan(index))
+            .unwrap()
+    }
+
+    fn projected_acc_reward(env: &Env) -> u128 {
+        let current_time = env.ledger().timestamp();
+        let last_update: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::LastUpdate)
+            .unwrap_or(current_time);
+        let acc_reward: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::AccReward)
+            .unwrap_or(0);
+        let total_weight: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalWeight)
+            .unwrap_or(0);
+
+        if current_time <= last_update || total_weight == 0 {
+            return acc_reward;
+        }
+
+        let emission: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::Emission)
+            .unwrap_or(0);
+        let epoch_duration: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::EpochDuration)
+            .unwrap();
+        let emitted = (emission * (current_time - last_update) as u128) / epoch_duration as u128;
+
+        acc_reward + (emitted * REWARD_PRECISION) / total_weight
+    }
+
+    fn settle(stake: &mut RewardStake, acc_reward: u128) {
+        let accrued = (stake.weight * acc_reward) / REWARD_PRECISION;
+        stake.pending_rewards += accrued - stake.reward_debt;
+        stake.reward_debt = accrued;
+    }
+
+    fn tier_weight(usd_value: u128, lock_period: &LockPeriod) -> u128 {
+        // Same multipliers as the vault yield schedule
+        match lock_period {
+            LockPeriod::ThreeMonths => usd_value,
+            LockPeriod::SixMonths => (usd_value * 15) / 10,
+            LockPeriod::TwelveMonths => usd_value * 2,
+        }
+    }
+
+    fn get_stake_or_default(env: &Env, vault: &Address, user: &Address) -> RewardStake {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stake(vault.clone(), user.clone()))
+            .unwrap_or(RewardStake {
+                weight: 0,
+                reward_debt: 0,
+                pending_rewards: 0,
+            })
+    }
+
+    fn get_epoch_or_default(env: &Env, epoch: u64) -> RewardEpoch {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Epoch(epoch))
+            .unwrap_or(RewardEpoch {
+                epoch,
+                emitted: 0,
+                claimed: 0,
+            })
+    }
+
+    fn verify_admin(env: &Env, admin: &Address) {
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if *admin != stored_admin {
+            panic!("Only admin can manage rewards");
+        }
+    }
+
+    fn verify_vault(env: &Env, vault: &Address) {
+        let vaults: Vec<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vaults)
+            .unwrap_or(Vec::new(env));
+
+        if !vaults.contains(vault) {
+            panic!("Caller is not a registered vault");
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Role-based access control for contracts with an admin
+//!
+//! Roles split the admin's powers so each can sit with a different key: the
+//! upgrader replaces the contract WASM, the pauser halts new business, the
+//! fee setter changes fees and the reporter exports position data for
+//! regulatory reporting. A role nobody holds falls back to the contract's
+//! own check, so behaviour is unchanged until a role is granted.
+
+use crate::ContractError;
+use soroban_sdk::{contracttype, panic_with_error, symbol_short, Address, Env, Symbol};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    Pauser,
+    Upgrader,
+    FeeSetter,
+    Reporter,
+}
+
+// Fixed symbol keys, clear of every contract's own `DataKey`
+const ROLE: Symbol = symbol_short!("ROLE");
+const PAUSED: Symbol = symbol_short!("PAUSED");
+
+/// Get the address holding a role, if one has been granted
+pub fn role_holder(env: &Env, role: Role) -> Option<Address> {
+    env.storage().instance().get(&(ROLE, role))
+}
+
+/// Grant a role to an address, or revoke it with None
+pub fn set_role_holder(env: &Env, role: Role, holder: Option<Address>) {
+    match holder {
+        Some(holder) => env.storage().instance().set(&(ROLE, role), &holder),
+        None => env.storage().instance().remove(&(ROLE, role)),
+    }
+}
+
+/// Check whether the caller holds a role, or is the admin while nobody holds it
+pub fn has_role(env: &Env, role: Role, caller: &Address, admin: &Address) -> bool {
+    match role_holder(env, role) {
+        Some(holder) => holder == *caller,
+        None => caller == admin,
+    }
+}
+
+/// Abort with `MissingRole` unless `has_role` passes; the caller must already be authorized
+pub fn require_role(env: &Env, role: Role, caller: &Address, admin: &Address) {
+    if !has_role(env, role, caller, admin) {
+        panic_with_error!(env, ContractError::MissingRole);
+    }
+}
+
+/// Check whether the pauser has halted the contract
+pub fn is_paused(env: &Env) -> bool {
+    env.storage().instance().has(&PAUSED)
+}
+
+/// Halt or resume the entrypoints guarded by `ensure_not_paused`
+pub fn set_paused(env: &Env, paused: bool) {
+    if paused {
+        env.storage().instance().set(&PAUSED, &true);
+    } else {
+        env.storage().instance().remove(&PAUSED);
+    }
+}
+
+/// Abort with `Paused` while the contract is paused
+pub fn ensure_not_paused(env: &Env) {
+    if is_paused(env) {
+        panic_with_error!(env, ContractError::Paused);
+    }
+}
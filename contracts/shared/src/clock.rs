@@ -0,0 +1,58 @@
+//! Deadline time with an optional ledger-sequence cross-check
+//!
+//! Lock expiries and voting deadlines are stored as timestamps. Where validator
+//! timestamp drift is a concern, a ledger clock can be anchored: time is then
+//! also read from the ledger sequence at the nominal close rate, and deadline
+//! checks use the earlier of the two readings, so a deadline only passes once
+//! both the timestamp and the sequence agree it has.
+
+use crate::LEDGER_CLOSE_SECONDS;
+use soroban_sdk::{contracttype, symbol_short, Env, Symbol};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LedgerClock {
+    pub anchor_time: u64,     // Ledger timestamp when the clock was anchored
+    pub anchor_sequence: u32, // Ledger sequence at that timestamp
+}
+
+// Fixed symbol key, clear of every contract's own `DataKey`
+const CLOCK: Symbol = symbol_short!("CLOCK");
+
+/// Get the anchored ledger clock, if sequence cross-checks are on
+pub fn ledger_clock(env: &Env) -> Option<LedgerClock> {
+    env.storage().instance().get(&CLOCK)
+}
+
+/// Anchor the ledger clock at the current ledger, or remove it
+pub fn anchor_ledger_clock(env: &Env, enabled: bool) -> Option<LedgerClock> {
+    if !enabled {
+        env.storage().instance().remove(&CLOCK);
+        return None;
+    }
+
+    let clock = LedgerClock {
+        anchor_time: env.ledger().timestamp(),
+        anchor_sequence: env.ledger().sequence(),
+    };
+    env.storage().instance().set(&CLOCK, &clock);
+    Some(clock)
+}
+
+/// Get the time deadlines are checked against
+///
+/// The ledger timestamp, or with a ledger clock anchored the earlier of the
+/// timestamp and the time implied by the sequences closed since the anchor.
+pub fn deadline_time(env: &Env) -> u64 {
+    let timestamp = env.ledger().timestamp();
+    match ledger_clock(env) {
+        Some(clock) => {
+            let closed = env
+                .ledger()
+                .sequence()
+                .saturating_sub(clock.anchor_sequence) as u64;
+            timestamp.min(clock.anchor_time + closed * LEDGER_CLOSE_SECONDS)
+        }
+        None => timestamp,
+    }
+}
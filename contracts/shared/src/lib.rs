@@ -1,4 +1,64 @@
-use soroban_sdk::{contracttype, Address, BytesN};
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    contracterror, contracttype, panic_with_error, symbol_short, vec, Address, Bytes, BytesN, Env,
+    IntoVal, InvokeError, Map, String, Symbol, TryFromVal, Val, Vec,
+};
+
+mod access;
+pub use access::{
+    ensure_not_paused, has_role, is_paused, require_role, role_holder, set_paused, set_role_holder,
+    Role,
+};
+
+mod clock;
+pub use clock::{anchor_ledger_clock, deadline_time, ledger_clock, LedgerClock};
+
+mod math;
+pub use math::{
+    compound_at, compound_daily, gold_usd_value, mul_div, mul_div_up, mul_div_with_dust,
+    realized_apy, stream_yield,
+};
+
+mod schema;
+pub use schema::{
+    begin_migration, ensure_uninitialized, finish_migration, is_initialized, mark_initialized,
+    stored_schema_version, verify_migration_admin,
+};
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum ContractError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidMigration = 3,
+    YieldTokenCallFailed = 4, // Mint, burn, compound or balance call into the yield token failed
+    TokenTransferFailed = 5,  // Underlying asset transfer was rejected
+    PeerCallFailed = 6,       // Registry or other required peer contract call failed
+    Reentrancy = 7,           // Guarded entrypoint called while another is in progress
+    TierCapExceeded = 8,      // Deposit would push a lock tier past its share of vault TVL
+    InvalidTierCap = 9,       // Tier cap share above 100%
+    UsdcDepegged = 10,        // USDC oracle price outside the governance-set band
+    GlobalSettlement = 11,    // Entrypoint disabled once global settlement has started
+    SlippageExceeded = 12,    // Trade received less than its minimum amount out
+    ParameterOutOfBounds = 13, // Governance parameter value outside its hard bounds
+    StrategyCallFailed = 14,  // Deposit, withdraw or harvest call into a yield strategy failed
+    MissingRole = 15,         // Caller does not hold the role the entrypoint requires
+    Paused = 16,              // Entrypoint disabled while the pauser has halted the contract
+    NotAllowlisted = 17,      // Depositor not on the allowlist during a vault's launch phase
+    StalePrice = 18,          // Oracle price missing, zero or older than MAX_ORACLE_PRICE_AGE
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ContractInfo {
+    pub name: Symbol,
+    pub version: String,
+    pub schema_version: u32,        // Schema written by the deployed WASM
+    pub stored_schema_version: u32, // Schema currently in storage (lower means migrate pending)
+}
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
@@ -8,6 +68,21 @@ pub enum LockPeriod {
     TwelveMonths, // 2x base rate
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum CompoundingFrequency {
+    Daily,      // Compounded every day; the default
+    Weekly,     // Compounded once per whole week
+    AtMaturity, // Simple interest, compounded once when the lock expires
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CompoundingPlan {
+    pub frequency: CompoundingFrequency,
+    pub matures_at: u64, // Unlock time of the deposit; at-maturity yield is credited from then
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum VaultType {
@@ -16,6 +91,13 @@ pub enum VaultType {
     WisdomTreeGold,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LockTierCap {
+    pub max_share_bps: u32, // Max share of vault TVL held in this lock tier
+    pub min_tvl: u128,      // Cap is only enforced once vault TVL exceeds this
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct DepositInfo {
@@ -26,6 +108,188 @@ pub struct DepositInfo {
     pub vault_type: VaultType,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositTag {
+    pub integrator: Address, // Exchange or custodian pooling customer funds
+    pub customer_id: u64,    // Integrator's customer reference, e.g. a muxed account ID or memo
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositPreview {
+    pub amount: u128,
+    pub yield_rate: u128,
+    pub tokens_minted: u128,
+    pub projected_yield: u128, // Compounded daily until unlock at the quoted rate
+    pub early_exit_fee: u128,  // Penalty for an emergency exit right after depositing
+    pub unlock_time: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum WithdrawalCap {
+    Absolute(u128), // Underlying paid out per epoch
+    TvlShare(u32), // Bps of recorded liquidity plus queued withdrawals, set at the epoch's first withdrawal
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct WithdrawalEpoch {
+    pub epoch: u64,
+    pub capacity: u128,
+    pub withdrawn: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct QueuedClaim {
+    pub user: Address,
+    pub amount: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LiquidityFacility {
+    pub cap: u128,        // Most the borrowing vault may owe at once
+    pub rate_bps: u32,    // Simple annual interest on each draw
+    pub max_term: u64,    // Longest a draw may run, in seconds
+    pub max_ltv_bps: u32, // Owed amount against the borrower's recorded holdings
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FacilityDraw {
+    pub asset: Address, // Token lent, repaid in the same token
+    pub principal: u128,
+    pub rate_bps: u32, // The facility rate when drawn
+    pub drawn_at: u64,
+    pub maturity: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StrategySlot {
+    pub strategy: Address,
+    pub max_share_bps: u32, // Most of the vault's holdings of the asset the strategy may hold
+    pub allocated: u128,    // Principal currently deployed in the strategy
+    pub harvested: u128,    // Yield the strategy has paid back to the vault
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ExitFeeSchedule {
+    pub start_bps: u32, // Fee for an exit right after deposit
+    pub end_bps: u32,   // Fee once the lock has run out, reached linearly
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeRebateTier {
+    pub min_amount: u128, // Smallest principal the tier applies to
+    pub rebate_bps: u32,  // Share of the yield fee waived
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct YieldFeeSchedule {
+    pub fee_bps: u32, // Protocol share of the yield paid out at withdrawal
+    pub size_rebates: Vec<FeeRebateTier>, // Rebates by principal, thresholds ascending
+    pub long_lock_rebate_bps: u32, // Rebate for 12-month locks
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RevenueStream {
+    YieldSkim,        // Yield fee taken at withdrawal
+    EarlyExitPenalty, // Emergency exit penalty
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeSwitch {
+    pub enabled: bool,      // A disabled stream charges nothing
+    pub insurance_bps: u32, // Share routed to the insurance fund, the rest to the treasury
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeSwitchChange {
+    pub stream: RevenueStream,
+    pub switch: FeeSwitch,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StandingOrder {
+    pub amount: u128,
+    pub interval: u64, // Seconds between executions
+    pub lock_period: LockPeriod,
+    pub next_execution: u64,
+    pub executions: u32, // Deposits opened by keepers
+    pub skipped: u32,    // Periods skipped by the user or while a position was open
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum VoucherStatus {
+    Open,
+    Claimed,
+    Cancelled,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositVoucher {
+    pub id: u64,
+    pub funder: Address,
+    pub amount: u128,
+    pub lock_period: LockPeriod,
+    pub recipient: Option<Address>, // Only this address may claim, if set
+    pub code_hash: Option<BytesN<32>>, // SHA-256 of the claim code, if one is required
+    pub created_at: u64,
+    pub claimed_by: Option<Address>,
+    pub status: VoucherStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Beneficiary {
+    pub address: Address, // Only address the depositor's positions are paid out to
+    pub pending: Option<Address>, // Replacement requested by the depositor, if any
+    pub effective_at: u64, // When the pending replacement takes over
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RecoveryPlan {
+    pub recovery: Address, // May claim the matured position once the depositor goes quiet
+    pub inactivity_period: u64, // Seconds of silence after unlock or the last check-in
+    pub last_active: u64,  // Last time the depositor set the plan or checked in
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DormancyPolicy {
+    pub max_amount: u128,  // Only positions of at most this principal can go dormant
+    pub idle_period: u64,  // Seconds after unlock before a keeper may flag the position
+    pub grace_period: u64, // Seconds after flagging that yield still accrues before consolidation
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum YieldMode {
+    Fixed,        // 5/7.5/10% schedule by lock period
+    ProfitDriven, // Reported protocol profits distributed via the yield index
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RateTranche {
+    Fixed,    // Lock-period schedule whatever the yield mode
+    Variable, // Realized protocol profits via the yield index, with no fixed floor
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct UserYieldInfo {
@@ -35,12 +299,65 @@ pub struct UserYieldInfo {
     pub total_yield_earned: u128,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RateChange {
+    pub changed_at: u64,
+    pub holder: Option<Address>, // None for the global rate
+    pub old_rate: u128,          // Basis points (e.g., 500 = 5%)
+    pub new_rate: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TokenStats {
+    pub total_minted: u128,            // Minted for deposits since launch
+    pub total_burned: u128,            // Burned for withdrawals and by holders since launch
+    pub total_yield_distributed: u128, // Yield credited to balances or streams since launch
+    pub total_supply: u128,
+    pub holder_count: u64,
+    pub yield_index: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HolderStats {
+    pub minted: u128,
+    pub burned: u128,
+    pub yield_earned: u128, // Lifetime yield, unlike `UserYieldInfo` which resets on each deposit
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrancheStats {
+    pub supply: u128,       // Current balances of the tranche's holders
+    pub holders: u32,       // Holders who chose the tranche
+    pub yield_earned: u128, // Yield credited to the tranche since launch
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct YieldStream {
+    pub claimable: u128, // Streamed since the last claim
+    pub total_claimed: u128,
+    pub started_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AccrualBatch {
+    pub accrued: u128,            // Yield newly compounded across the batch
+    pub processed: u32,           // Holders compounded
+    pub next_cursor: Option<u64>, // Slot to continue from, None once every holder is done
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum CollateralStatus {
     Active,
     Liquidated,
     Expired,
+    Retired, // Physically delivered or exported; the lot no longer exists
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -54,6 +371,82 @@ pub struct CollateralInfo {
     pub status: CollateralStatus,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CollateralSummary {
+    pub active: u32,
+    pub liquidated: u32,
+    pub expired: u32,
+    pub retired: u32,
+    pub active_value_usd: u128, // Latest valuation of all active lots
+    pub average_grade: u32,     // Mean quality grade of active lots, 0 without any
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LotListing {
+    pub seller: Address,
+    pub price: u128, // Asking price in the market token
+    pub listed_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LotOffer {
+    pub amount: u128,
+    pub token: Address, // Token escrowed, which the offer is paid out in
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LotSale {
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: u128,    // Settled price in `token`
+    pub token: Address, // Market token of a listing, or the token an offer was escrowed in
+    pub sold_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum AlertTier {
+    Healthy,
+    Watch,
+    Warning,
+    Critical,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AlertThresholds {
+    pub watch_bps: u128,    // Health factors below this enter the watch tier
+    pub warning_bps: u128,  // ...below this the warning tier
+    pub critical_bps: u128, // ...and below this the critical tier
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PriceSnapshot {
+    pub feed: String, // Oracle feed the price came from, e.g. PAXG/USD
+    pub price: u128,  // At GOLD_PRICE_PRECISION
+    pub ledger: u32,  // Ledger sequence of the read; the oracle has no round ids
+    pub observed_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CoffeePrice {
+    pub price_per_kg: u128, // Base (grade-neutral) price of one kg, in collateral value units
+    pub updated_at: u64,    // When the price was last pulled from the oracle
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GradePoint {
+    pub grade: u32,          // Quality grade, 1-100
+    pub multiplier_bps: u32, // Share of the base price a kg of this grade is worth
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum ProposalStatus {
@@ -77,6 +470,16 @@ pub struct LoanProposal {
     pub created_at: u64,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CreditHistory {
+    pub loans_executed: u32,
+    pub on_time_repaid: u128, // Repaid by the loan's due date
+    pub late_repaid: u128,    // Repaid after the due date
+    pub defaulted: u128,      // Liquidation shortfalls recorded as bad debt
+    pub last_updated: u64,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum ExpertiseArea {
@@ -94,6 +497,65 @@ pub struct CommitteeMember {
     pub vote_weight: u32,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CommitteeDelegate {
+    pub delegate: Address,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SessionKey {
+    pub key: Address,
+    pub expires_ledger: u32, // First ledger the key no longer signs in
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RateLock {
+    pub base_rate: u128,   // Base loan rate when the loan was proposed
+    pub locked_rate: u128, // Rate the borrower gets if the loan executes before expiry
+    pub locked_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LoanFreeze {
+    pub frozen_by: Address,
+    pub frozen_at: u64,
+    pub unfreeze_approvals: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct MemberStats {
+    pub proposals_submitted: u32,   // Loan and trade proposals
+    pub approvals: u32, // Loan approvals, including those cast by a delegate or session key
+    pub rejections: u32, // Loan rejections, likewise
+    pub total_response_time: u64, // Seconds from submission to each approval or rejection
+    pub average_response_time: u64, // Derived from the total when read
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CompensationSchedule {
+    pub stipend: u128,                  // Yield tokens paid per period
+    pub period: u64,                    // Seconds per stipend period
+    pub min_activity: u32, // Proposals, approvals and rejections needed per period paid
+    pub max_average_response_time: u64, // Claims blocked above this average, 0 for no limit
+    pub started_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StipendAccount {
+    pub claimed_through: u64, // End of the last period settled, paid or forfeited
+    pub activity_mark: u32,   // Activity count at the last claim
+    pub total_claimed: u128,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub enum ProtocolParameter {
@@ -102,6 +564,34 @@ pub enum ProtocolParameter {
     CollateralRatio,
     ProtocolFeeRate,
     EmergencyWithdrawFee,
+    TreasurySwap, // Amount in of a treasury swap; the swap terms are stored with the proposal
+    FeeSwitch, // Insurance share of a fee switch; the stream and switch are stored with the proposal
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VotingRules {
+    pub voting_period: u64, // Seconds votes stay open once the review period ends
+    pub pass_threshold_bps: u32, // Share of for and against votes that must be for, exceeded strictly
+    pub min_proposal_tokens: u128, // Voting power needed to propose a change
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum VoteType {
+    For,
+    Against,
+    Abstain,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VoteTally {
+    pub votes_for: u128,
+    pub votes_against: u128,
+    pub votes_abstain: u128,
+    pub quorum: u128,
+    pub quorum_reached: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -113,10 +603,34 @@ pub struct GovernanceProposal {
     pub new_value: u128,
     pub votes_for: u128,
     pub votes_against: u128,
+    pub votes_abstain: u128, // Counts toward quorum but not toward the outcome
+    pub voting_start: u64,   // Votes open once the review period after submission has passed
     pub voting_deadline: u64,
     pub status: ProposalStatus,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ProposalKind {
+    ParameterChange,
+    DependentChange, // A parameter change gated on prerequisite proposals
+    TreasurySwap,
+    FeeSwitch,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ChangelogEntry {
+    pub index: u64,
+    pub proposal_id: BytesN<32>,
+    pub kind: ProposalKind,
+    pub parameter: ProtocolParameter,
+    pub old_value: Option<u128>, // None before the parameter's first change, and for treasury swaps
+    pub new_value: u128,
+    pub executor: Address,
+    pub executed_at: u64,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct ProfitReport {
@@ -128,6 +642,17 @@ pub struct ProfitReport {
     pub timestamp: u64,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ProfitReportProposal {
+    pub report: ProfitReport,
+    pub reporter: Address,
+    pub epoch: u64, // Epoch the report was submitted in; one report per epoch
+    pub prepayment_income: u128, // Early-repayment income folded into the lending profit
+    pub approvals: u32,
+    pub status: ProposalStatus, // Pending until approved and distributed, Rejected if withdrawn
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct TradeParams {
@@ -138,18 +663,1013 @@ pub struct TradeParams {
     pub deadline: u64,
 }
 
-// Storage keys
-pub const STORAGE_INSTANCE_PERSISTENT: u64 = 86400 * 365; // 1 year
-pub const REBASE_INTERVAL: u64 = 86400; // 24 hours in seconds
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LimitOrder {
+    pub price_feed: Symbol, // Oracle feed pricing asset_in in asset_out at 6 decimals
+    pub limit_price: u128,  // Lowest asset_out per asset_in the order fills at, same precision
+    pub approvals: u32,     // The proposer's included
+}
 
-// Protocol constants
-pub const REQUIRED_COMMITTEE_APPROVALS: u32 = 3;
-pub const TOTAL_COMMITTEE_SIZE: u32 = 5;
-pub const PROTOCOL_FEE_BASIS_POINTS: u128 = 2000; // 20%
-pub const YIELD_DISTRIBUTION_BASIS_POINTS: u128 = 8000; // 80%
-pub const COLLATERAL_RATIO_BASIS_POINTS: u128 = 15000; // 150%
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TreasuryTarget {
+    pub asset: Address,
+    pub price_feed: Symbol, // Oracle feed giving the USD price at 6 decimals
+    pub target_bps: u32,    // Share of treasury value to hold in the asset
+}
 
-// Asset addresses (placeholders - will need to be updated with actual addresses)
-pub const USDC_ASSET: &str = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct KeeperBounty {
+    pub token: Address,
+    pub amount: u128, // Paid per entry swept
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TradeSweep {
+    pub swept: u32,               // Expired trade proposals removed from the page scanned
+    pub next_cursor: Option<u32>, // Open-trade position to continue from, None once the list is done
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TradeFill {
+    pub filled_in: u128,
+    pub filled_out: u128, // Measured from the governance balance, slice by slice
+    pub average_price: u128, // filled_out per filled_in, scaled by EXECUTION_PRICE_PRECISION
+    pub slices: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TradeRecord {
+    pub params: TradeParams,
+    pub amount_out: u128, // Measured from the governance balance, not the router's reply
+    pub execution_price: u128, // amount_out per amount_in, scaled by EXECUTION_PRICE_PRECISION
+    pub executor: Address,
+    pub executed_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LoanCurrency {
+    pub asset: Address,
+    pub source_vault: Address, // Vault the loan is disbursed from
+    pub price_feed: Symbol,    // Oracle feed giving the USD price at 6 decimals
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowStatus {
+    Open,      // Funded, waiting for the recipient to confirm delivery
+    Confirmed, // Delivery confirmed, the partner can claim
+    Claimed,
+    Refunded,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LoanEscrow {
+    pub id: u64,
+    pub loan_id: BytesN<32>,
+    pub token: Address,
+    pub amount: u128,
+    pub funder: Address,
+    pub recipient: Address, // Borrower who confirms the partner delivered the funds
+    pub partner: Address,   // Fiat on/off-ramp partner paid on confirmation
+    pub refund_to: Address, // Vault the funds return to if delivery is not confirmed in time
+    pub opened_at: u64,
+    pub expires_at: u64,
+    pub delivery_ref: Option<BytesN<32>>, // Partner's delivery reference, set on confirmation
+    pub status: EscrowStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HoldbackStatus {
+    Held,       // Waiting for a QA lab to sample the pledged lot
+    Released,   // The sample passed and the borrower was paid
+    ClawedBack, // The sample failed or was never posted; returned to the vault
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct QaHoldback {
+    pub id: u64,
+    pub loan_id: BytesN<32>,
+    pub lot: Address, // Pledged coffee lot the sample is taken from
+    pub token: Address,
+    pub amount: u128,
+    pub recipient: Address, // Borrower paid when the sample passes
+    pub refund_to: Address, // Vault the holdback returns to when it fails
+    pub opened_at: u64,
+    pub expires_at: u64,
+    pub lab: Option<Address>,            // QA lab that posted the result
+    pub sample_hash: Option<BytesN<32>>, // Hash of the lab's sample report
+    pub status: HoldbackStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HedgeStatus {
+    Open,
+    Closed,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HedgePosition {
+    pub id: u64,
+    pub venue: Address, // Trade router or registered hedging partner holding the short
+    pub quantity_kg: u128, // Coffee sold short
+    pub entry_price: u128, // Price per kg the short was opened at, in collateral value units
+    pub expires_at: u64, // Expiry of the underlying futures contract
+    pub opened_by: Address,
+    pub opened_at: u64,
+    pub exit_price: u128,   // Price per kg the short was closed at, 0 while open
+    pub realized_pnl: i128, // (entry - exit) * quantity, set when closed
+    pub status: HedgeStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HedgeSummary {
+    pub book_kg: u128,         // Coffee pledged as loan collateral
+    pub hedged_kg: u128,       // Coffee sold short across open hedges
+    pub hedge_ratio_bps: u128, // hedged_kg in bps of book_kg
+    pub open_hedges: u32,
+    pub unrealized_pnl: i128, // Open hedges marked to the coffee base price, 0 without one
+    pub realized_pnl: i128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LoanTranche {
+    pub milestone: String, // e.g. planting, harvest, export
+    pub amount: u128,
+    pub approvals: u32,
+    pub disbursed_at: Option<u64>, // None until the committee releases the tranche
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RepaymentStructure {
+    Amortizing, // Principal repaid in equal installments alongside the interest
+    Balloon,    // Interest-only installments with all principal due at maturity
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Installment {
+    pub due_at: u64,
+    pub principal: u128,
+    pub interest: u128, // Simple interest on the principal outstanding over the period
+    pub principal_repaid: u128, // Recorded repayments are applied to installments in due order
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PrepaymentPolicy {
+    FullInterest,       // Borrower pays the interest of every installment not yet due
+    ProRated,           // Interest accrued to date on the current installment only
+    PrepaymentFee(u32), // Pro-rated interest plus a fee in bps of the prepaid principal
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct EarlyRepayment {
+    pub principal: u128,
+    pub interest_charged: u128,
+    pub interest_rebated: u128, // Scheduled interest waived by the policy
+    pub prepayment_fee: u128,
+    pub repaid_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LoanPreview {
+    pub amount: u128,
+    pub total_interest: u128,
+    pub protocol_fee: u128, // Protocol share of the interest once reported as profit
+    pub required_collateral: u128, // USD collateral value needed at the collateral ratio
+    pub due_at: u64,
+    pub schedule: Vec<Installment>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AdminAction {
+    pub id: u64,
+    pub proposer: Address,
+    pub target: Address,
+    pub function: Symbol,
+    pub args: Vec<Val>,
+    pub confirmations: u32,
+    pub status: ProposalStatus,
+    pub created_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct AdminOp {
+    pub target: Address,
+    pub function: Symbol,
+    pub args: Vec<Val>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TimelockOperation {
+    pub id: u64,
+    pub target: Address,
+    pub function: Symbol,
+    pub args: Vec<Val>,
+    pub eta: u64,
+    pub status: ProposalStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ProtocolWasmHashes {
+    pub yield_token: BytesN<32>,
+    pub usdc_vault: BytesN<32>,
+    pub gold_vault: BytesN<32>,
+    pub governance: BytesN<32>,
+    pub coffee_collateral: BytesN<32>,
+    pub oracle: BytesN<32>,
+    pub multisig: BytesN<32>,
+    pub registry: BytesN<32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ProtocolConfig {
+    pub usdc_contract: Address,
+    pub supported_gold_assets: Vec<Address>,
+    pub initial_committee: Vec<CommitteeMember>,
+    pub min_proposal_tokens: u128,
+    pub multisig_signers: Vec<Address>,
+    pub multisig_threshold: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct ProtocolAddresses {
+    pub yield_token: Address,
+    pub usdc_vault: Address,
+    pub gold_vault: Address,
+    pub governance: Address,
+    pub coffee_collateral: Address,
+    pub oracle: Address,
+    pub multisig: Address,
+    pub registry: Address,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DeployedContract {
+    pub admin: Address,   // Admin the deployer handed the contract to
+    pub salt: BytesN<32>, // Salt the deployer created the contract with
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RewardStake {
+    pub weight: u128, // USD value scaled by lock tier multiplier
+    pub reward_debt: u128,
+    pub pending_rewards: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct RewardEpoch {
+    pub epoch: u64,
+    pub emitted: u128,
+    pub claimed: u128,
+}
+
+/// A stretch of constant-rate reward emission between two pool updates
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct EmissionSpan {
+    pub start: u64,
+    pub end: u64,
+    pub emitted_before: u128, // Cumulative emission up to `start`
+    pub emission: u128,       // Per-epoch emission rate over the span
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BalanceSnapshot {
+    pub id: u32,
+    pub taken_at: u64,
+    pub total_supply: u128,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BalanceCheckpoint {
+    pub snapshot_id: u32, // First snapshot taken before this balance changed
+    pub balance: u128,    // Balance held when that snapshot was taken
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Airdrop {
+    pub token: Address,
+    pub snapshot_id: u32, // Yield-token snapshot the allocations were computed from
+    pub merkle_root: BytesN<32>,
+    pub total_amount: u128,
+    pub claimed: u128,
+    pub created_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BadDebtSource {
+    LiquidationShortfall, // Liquidation recovered less than the loan amount
+    GoldShortfall,        // Gold vault could not cover a withdrawal
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum WriteOffFunding {
+    InsuranceFund(Address), // Token paid from the insurance fund to the affected vault
+    FutureFees,             // Offset against future protocol fees
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BadDebtRecord {
+    pub id: u64,
+    pub source: BadDebtSource,
+    pub reference: BytesN<32>, // Loan ID or hashed depositor address
+    pub vault: Address,
+    pub amount: u128,
+    pub recorded_at: u64,
+    pub write_off_funding: Option<WriteOffFunding>,
+    pub approvals: u32,
+    pub status: ProposalStatus, // Pending until written off, then Executed
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VaultHealthReport {
+    pub held_value: u128,         // Tokens held by the vault (USD value for gold)
+    pub recorded_liquidity: u128, // Vault balance per its own accounting
+    pub yield_token_supply: u128,
+    pub liquidity_covered: bool, // held_value >= recorded_liquidity, false without fresh prices
+    pub supply_consistent: bool, // Shared yield-token supply covers what every vault minted
+    pub accounting_non_negative: bool, // No negative token balance or supply reported
+    pub price_fresh: bool, // Every held asset was valued at a fresh price; always true for USDC
+    pub healthy: bool,     // All invariants hold
+    pub checked_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositTerms {
+    pub vault: Address,
+    pub depositor: Address,
+    pub asset: Address,
+    pub amount: u128,    // Underlying units deposited
+    pub usd_value: u128, // Value credited in yield tokens
+    pub lock_period: LockPeriod,
+    pub yield_rate: u128, // Annual rate in basis points at deposit
+    pub deposit_time: u64,
+    pub unlock_time: u64,
+    pub fee_policy_version: u32, // Vault fee policy in force at deposit
+    pub compounding: CompoundingFrequency,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositReceipt {
+    pub id: u64, // Per-vault receipt number
+    pub terms: DepositTerms,
+    pub terms_hash: BytesN<32>, // SHA-256 of the XDR-encoded terms
+    pub nft_id: Option<u64>,    // Receipts contract token, once exported
+    pub closed_at: Option<u64>, // Set when the position is withdrawn
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Encumbrance {
+    pub loan_id: BytesN<32>,
+    pub lien_holder: Address, // Loan manager that placed the lien and alone can release it
+    pub amount: u128,         // Principal (USD for gold) of the position when pledged
+    pub encumbered_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PositionRecord {
+    pub vault: Address,
+    pub vault_type: VaultType,
+    pub owner: Address,
+    pub position_id: Option<u64>, // Deposit receipt ID, None for positions predating receipts
+    pub principal: u128,          // USD value credited at deposit (1:1 for USDC)
+    pub accrued_yield: u128,      // Yield projected at the position's rate to the export time
+    pub lock_period: LockPeriod,
+    pub deposit_time: u64,
+    pub unlock_time: u64,
+    pub encumbrance: Option<Encumbrance>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PositionPage {
+    pub records: Vec<PositionRecord>, // Open positions among the depositor slots scanned
+    pub next_cursor: Option<u64>,     // Slot to continue from, None once every depositor is done
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VaultLien {
+    pub vault: Address,
+    pub owner: Address,
+    pub position_id: u64, // Deposit receipt ID of the pledged position
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CombinedCollateral {
+    pub coffee_value_usd: u128, // Estimated value of the coffee lot, 0 once it is no longer active
+    pub gold_value_usd: u128,   // Live value of the pledged gold-vault position, 0 without one
+    pub loan_value_usd: u128,   // Outstanding principal once executed, the full amount before
+    pub ltv_bps: u32,           // Loan value over both collateral values
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GoldSeizure {
+    pub gold_asset: Address,
+    pub gold_seized: u128,      // The whole position, yield included
+    pub creditor: Address,      // The vault that bore the shortfall
+    pub gold_to_creditor: u128, // Enough to cover the coffee liquidation shortfall
+    pub recovered_usd: u128,
+    pub seized_at: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SyndicateShare {
+    pub lender: Address,
+    pub token: Address,  // Loan currency the lender funded in
+    pub principal: u128, // Amount the lender put towards the loan
+    pub credited: u128,  // Pro-rata share of recorded repayments
+    pub claimed: u128,   // Repayments already paid out to the lender
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SettlementSnapshot {
+    pub settled_at: u64,
+    pub recorded_liquidity: u128, // Depositor principal (USD for gold) at settlement
+    pub held_value: u128,         // Assets held at settlement (USD value for gold)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct EpochSummary {
+    pub epoch: u64,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub usdc_vault_tvl: u128,    // Recorded USDC principal at the boundary
+    pub gold_vault_tvl: u128,    // Recorded USD value at the boundary
+    pub yield_distributed: u128, // Profit-report yield distributed during the epoch
+    pub usdc_yield_paid: u128, // Fixed-rate yield paid and strategy yield harvested by the USDC vault
+    pub gold_yield_paid: u128, // Fixed-rate yield paid by the gold vault, in USD
+    pub protocol_fees: u128,   // Profit-report protocol fees during the epoch
+    pub penalty_fees: u128,    // Vault penalties collected during the epoch
+    pub active_loans: u32,     // Executed loans with principal outstanding
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct StateDigest {
+    pub contract: Address,
+    pub digest: BytesN<32>, // SHA-256 of the XDR of the contract's `accounting_state()`
+}
+
+// Storage TTL policy, in ledgers (~5 seconds each)
+pub const DAY_IN_LEDGERS: u32 = 17280;
+pub const INSTANCE_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS; // Config and protocol totals
+pub const INSTANCE_LIFETIME_THRESHOLD: u32 = INSTANCE_BUMP_AMOUNT - DAY_IN_LEDGERS;
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 120 * DAY_IN_LEDGERS; // Positions, proposals and records
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+pub const REBASE_INTERVAL: u64 = 86400; // 24 hours in seconds
+pub const REBASE_BATCH_SIZE: u64 = 25; // Holders compounded per rebase call
+pub const RATE_HISTORY_PAGE_SIZE: u32 = 50; // Rate changes returned per history page
+pub const COLLATERAL_PAGE_SIZE: u32 = 50; // Lots or loans returned per collateral index page
+pub const POSITION_EXPORT_PAGE_SIZE: u32 = 25; // Depositor slots scanned per position export page
+pub const CHANGELOG_PAGE_SIZE: u32 = 50; // Executed proposals returned per changelog page
+pub const EPOCH_SUMMARY_PAGE_SIZE: u32 = 50; // Closed epoch summaries returned per request
+pub const TRADE_SWEEP_PAGE_SIZE: u32 = 50; // Open trade proposals scanned per expiry sweep
+pub const WITHDRAWAL_QUEUE_BATCH: u32 = 25; // Queued withdrawals paid per queue call
+pub const EPOCH_DURATION: u64 = REBASE_INTERVAL; // Summaries follow the rebase cadence
+pub const APY_WINDOW_EPOCHS: u64 = 30; // Realized APY looks back over the last 30 closed epochs
+pub const MIN_STANDING_ORDER_INTERVAL: u64 = 86400; // Recurring deposits at most daily
+
+// Protocol constants
+pub const REQUIRED_COMMITTEE_APPROVALS: u32 = 3;
+pub const TOTAL_COMMITTEE_SIZE: u32 = 5;
+pub const MAX_DELEGATION_DURATION: u64 = 30 * 86400; // Committee delegates serve at most 30 days
+pub const MEMBERSHIP_ACCEPTANCE_WINDOW: u64 = 7 * 86400; // Appointed committee members have a week to accept
+pub const MAX_SESSION_KEY_LEDGERS: u32 = 7 * DAY_IN_LEDGERS; // Hot-device session keys last at most a week
+pub const MAX_PROPOSAL_REVIEW_PERIOD: u64 = 7 * 86400; // DAO votes open at most 7 days after submission
+pub const DEFAULT_VOTING_PERIOD: u64 = 7 * 86400; // DAO votes run a week unless a parameter's rules say otherwise
+pub const MIN_VOTING_PERIOD: u64 = 86400; // ...and at least a day
+pub const MAX_VOTING_PERIOD: u64 = 30 * 86400; // ...and at most 30 days
+pub const SIMPLE_MAJORITY_BPS: u32 = 5_000; // More for than against votes; the lowest pass threshold
+pub const MAX_PASS_THRESHOLD_BPS: u32 = 9_000; // Super-majorities above 90% would hand a veto to a few holders
+pub const MAX_PROPOSAL_PREREQUISITES: u32 = 5; // A DAO proposal waits on at most five earlier ones
+pub const PROTOCOL_FEE_BASIS_POINTS: u128 = 2000; // 20%
+pub const YIELD_DISTRIBUTION_BASIS_POINTS: u128 = 8000; // 80%
+pub const COLLATERAL_RATIO_BASIS_POINTS: u128 = 15000; // 150%
+pub const MIN_COLLATERAL_RATIO_BPS: u128 = 11_000; // Parameter votes keep loans at least 110% collateralised
+pub const MAX_COLLATERAL_RATIO_BPS: u128 = 50_000; // ...and cannot demand more than 500%
+pub const MAX_FEE_PARAMETER_BPS: u128 = 5_000; // Protocol and emergency-withdraw fees stay at or below 50%
+pub const MAX_YIELD_RATE_BPS: u128 = 5_000; // MaximumYieldRate can be voted up to 50% APY
+pub const MAX_MINIMUM_LOCK_PERIOD: u128 = 365 * 86400; // MinimumLockPeriod, in seconds, at most a year
+pub const YIELD_INDEX_PRECISION: u128 = 1_000_000_000_000; // 1e12 = index of 1.0
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000; // 1e12 scaling for reward-per-weight
+pub const MIN_REWARD_EPOCH_DURATION: u64 = 86400; // Reward epochs last at least a day
+pub const MAX_COMPOUND_TIP_BPS: u32 = 1000; // Keeper tips take at most 10% of newly accrued yield
+pub const CREDIT_SCORE_MAX: u32 = 1000; // Only on-time repayments
+pub const CREDIT_SCORE_NEUTRAL: u32 = 500; // Borrowers without repayment history
+pub const TIMELOCK_MIN_DELAY: u64 = 2 * 86400; // 48 hours
+pub const TIMELOCK_GRACE_PERIOD: u64 = 14 * 86400; // 14 days to execute once ready
+pub const BASIS_POINTS: u128 = 10_000;
+pub const SECONDS_PER_DAY: u64 = 86_400;
+pub const LEDGER_CLOSE_SECONDS: u64 = 5; // Nominal ledger close time, used to read time from ledger sequence
+pub const LOAN_INSTALLMENT_DAYS: u64 = 30; // Loans are repaid in monthly installments
+pub const RATE_LOCK_WINDOW: u64 = 30 * 86400; // Proposed loan rates are held for 30 days
+pub const MAX_PREPAYMENT_FEE_BPS: u32 = 500; // Early repayment fees are capped at 5% of the prepaid principal
+pub const DAYS_PER_YEAR: u128 = 365;
+pub const GOLD_PRICE_PRECISION: u128 = 1_000_000; // Oracle prices carry 6 decimals
+pub const USDC_PEG_PRICE: u128 = 1_000_000; // $1.00 at oracle precision
+pub const USDC_PRICE_FEED: &str = "USDC/USD";
+pub const MAX_ORACLE_PRICE_AGE: u64 = 3600; // Vaults value assets only at prices updated in the last hour
+pub const COFFEE_PRICE_FEED: &str = "COFFEE/USD"; // Quotes one kg in collateral value units
+pub const COFFEE_PRICE_MAX_AGE: u64 = 86400; // Valuations refresh only from a base price pulled in the last day
+pub const MAX_GRADE_MULTIPLIER_BPS: u32 = 30_000; // No grade is valued above 3x the base price
+pub const MAX_LIQUIDATION_BOUNTY_BPS: u32 = 1_000; // Liquidators keep at most 10% of a lot's recovered value
+pub const MAX_LIQUIDATION_GRACE_PERIOD: u64 = 30 * 86400; // Borrowers get at most 30 overdue days before liquidation
+pub const MIN_ESCROW_TIMEOUT: u64 = 86400; // Partners get at least a day to deliver escrowed loan funds
+pub const MAX_ESCROW_TIMEOUT: u64 = 30 * 86400; // ...and unconfirmed funds return to the vault within 30 days
+pub const MAX_QA_HOLDBACK_BPS: u32 = 1_000; // At most 10% of a loan waits on the lot's sample result
+pub const QA_SAMPLE_WINDOW: u64 = 30 * 86400; // Labs have 30 days to sample before the holdback is clawed back
+pub const MIN_RECOVERY_INACTIVITY: u64 = 365 * 86400; // Recovery addresses wait at least a year of silence
+pub const MAX_RECOVERY_INACTIVITY: u64 = 10 * 365 * 86400; // ...and at most ten years
+pub const BENEFICIARY_CHANGE_DELAY: u64 = 7 * 86400; // A new beneficiary takes over a week after the request
+pub const MAX_GOLD_HAIRCUT_BPS: u32 = 500; // Gold deposits mint on at least 95% of their USD value
+pub const MAX_COMPOUNDING_BONUS_BPS: u32 = 200; // Less frequent compounding earns at most 2% more a year
+pub const MAX_STRATEGY_SHARE_BPS: u32 = 5_000; // Yield strategies hold at most half of a vault's holdings
+pub const MAX_FACILITY_TERM: u64 = 30 * 86400; // Inter-vault liquidity draws are repaid within 30 days
+pub const FACILITY_LIQUIDATION_BONUS_BPS: u128 = 500; // Liquidating an overdue draw earns 5% over its value in collateral
+pub const SHARE_PRICE_PRECISION: u128 = 1_000_000_000_000; // 1e12 = one underlying unit per yield token
+pub const EXECUTION_PRICE_PRECISION: u128 = 1_000_000_000_000; // 1e12 = one unit out per unit in
+pub const DUST_PRECISION: u128 = 1_000_000_000_000; // Rounding dust is booked in 1e12ths of a unit
+
+// Fixed instance key for the reentrancy guard, shared by every guarded contract
+const REENTRANCY_GUARD: Symbol = symbol_short!("GUARD");
+
+// Address registry names
+pub const REGISTRY_YIELD_TOKEN: &str = "yield_token";
+pub const REGISTRY_USDC_VAULT: &str = "usdc_vault";
+pub const REGISTRY_GOLD_VAULT: &str = "gold_vault";
+pub const REGISTRY_GOVERNANCE: &str = "governance";
+pub const REGISTRY_COFFEE_COLLATERAL: &str = "coffee_collateral";
+pub const REGISTRY_ORACLE: &str = "oracle";
+pub const REGISTRY_TREASURY: &str = "treasury";
+pub const REGISTRY_INSURANCE_FUND: &str = "insurance_fund";
+pub const REGISTRY_RECEIPTS: &str = "receipts";
+pub const REGISTRY_TRADE_ROUTER: &str = "trade_router";
+pub const REGISTRY_ESCROW: &str = "escrow";
+pub const REGISTRY_ALLOWLIST: &str = "allowlist";
+pub const REGISTRY_HEDGING: &str = "hedging";
+pub const REGISTRY_TIMELOCK: &str = "timelock";
+
+// Asset addresses (placeholders - will need to be updated with actual addresses)
+pub const USDC_ASSET: &str = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
 pub const PAXG_ASSET: &str = "PAXG:PLACEHOLDER_ADDRESS_FOR_PAXG";
 pub const WISDOMTREE_GOLD: &str = "WTGOLD:PLACEHOLDER_ADDRESS_FOR_WISDOMTREE";
+
+/// Extend the contract instance (config and totals) per the storage TTL policy
+pub fn extend_instance_ttl(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(INSTANCE_LIFETIME_THRESHOLD, INSTANCE_BUMP_AMOUNT);
+}
+
+/// Extend an existing persistent entry per the storage TTL policy
+pub fn extend_persistent_ttl<K: IntoVal<Env, Val>>(env: &Env, key: &K) {
+    env.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
+
+/// Move an instance entry to a new key during a schema migration
+pub fn migrate_instance_entry<K, N>(env: &Env, legacy_key: &K, new_key: &N)
+where
+    K: IntoVal<Env, Val>,
+    N: IntoVal<Env, Val>,
+{
+    let value: Option<Val> = env.storage().instance().get(legacy_key);
+    if let Some(value) = value {
+        env.storage().instance().set(new_key, &value);
+        env.storage().instance().remove(legacy_key);
+    }
+}
+
+/// Move a persistent entry to a new key during a schema migration
+///
+/// Returns false when there was nothing stored under the legacy key, and aborts
+/// with `InvalidMigration` rather than overwrite an entry already at the new key.
+pub fn migrate_persistent_entry<K, N>(env: &Env, legacy_key: &K, new_key: &N) -> bool
+where
+    K: IntoVal<Env, Val>,
+    N: IntoVal<Env, Val>,
+{
+    let value: Option<Val> = env.storage().persistent().get(legacy_key);
+    match value {
+        Some(value) => {
+            if env.storage().persistent().has(new_key) {
+                panic_with_error!(env, ContractError::InvalidMigration);
+            }
+            env.storage().persistent().set(new_key, &value);
+            env.storage().persistent().remove(legacy_key);
+            extend_persistent_ttl(env, new_key);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Derive a unique 32-byte proposal ID from a contract-local counter and payload
+///
+/// The contract address, counter and ledger sequence are appended in bulk ahead
+/// of the payload, so two proposals with identical fields in the same ledger
+/// still hash to different IDs.
+pub fn derive_proposal_id(env: &Env, counter: u64, payload: &Bytes) -> BytesN<32> {
+    let mut preimage = env.current_contract_address().to_xdr(env);
+    preimage.extend_from_array(&counter.to_be_bytes());
+    preimage.extend_from_array(&env.ledger().sequence().to_be_bytes());
+    preimage.append(payload);
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Hash an airdrop allocation into the leaf its merkle proof starts from
+pub fn airdrop_leaf(env: &Env, account: &Address, amount: u128) -> BytesN<32> {
+    let mut preimage = account.clone().to_xdr(env);
+    preimage.extend_from_array(&amount.to_be_bytes());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Hash two merkle nodes into their parent, ordering the pair so proofs need no side flags
+pub fn merkle_parent(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (left, right) = if a.to_array() <= b.to_array() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+    let mut preimage = Bytes::from_array(env, &left.to_array());
+    preimage.extend_from_array(&right.to_array());
+    env.crypto().sha256(&preimage).into()
+}
+
+/// Check that `proof` climbs from `leaf` to `root`
+pub fn verify_merkle_proof(
+    env: &Env,
+    root: &BytesN<32>,
+    leaf: BytesN<32>,
+    proof: &Vec<BytesN<32>>,
+) -> bool {
+    let mut node = leaf;
+    for sibling in proof.iter() {
+        node = merkle_parent(env, &node, &sibling);
+    }
+    node == *root
+}
+
+/// Hash deposit terms so a receipt can be checked against its recorded parameters
+pub fn hash_deposit_terms(env: &Env, terms: &DepositTerms) -> BytesN<32> {
+    env.crypto().sha256(&terms.clone().to_xdr(env)).into()
+}
+
+/// Hash a contract's named accounting figures into its state digest
+///
+/// A `Map` keeps its keys sorted, so the XDR preimage is canonical: figures rebuilt
+/// off-chain under the same names hash to the same digest.
+pub fn digest_accounting(env: &Env, figures: &Map<Symbol, u128>) -> BytesN<32> {
+    env.crypto().sha256(&figures.clone().to_xdr(env)).into()
+}
+
+/// Hard bounds, inclusive, that a governance vote can set `parameter` to
+pub fn parameter_bounds(parameter: &ProtocolParameter) -> (u128, u128) {
+    match parameter {
+        ProtocolParameter::MinimumLockPeriod => (0, MAX_MINIMUM_LOCK_PERIOD),
+        ProtocolParameter::MaximumYieldRate => (0, MAX_YIELD_RATE_BPS),
+        ProtocolParameter::CollateralRatio => (MIN_COLLATERAL_RATIO_BPS, MAX_COLLATERAL_RATIO_BPS),
+        ProtocolParameter::ProtocolFeeRate => (0, MAX_FEE_PARAMETER_BPS),
+        ProtocolParameter::EmergencyWithdrawFee => (0, MAX_FEE_PARAMETER_BPS),
+        ProtocolParameter::TreasurySwap => (1, u128::MAX),
+        ProtocolParameter::FeeSwitch => (0, BASIS_POINTS),
+    }
+}
+
+/// Call a peer contract, aborting with `error` if the call fails
+///
+/// The whole transaction reverts, so no compensation is needed on this path.
+pub fn invoke_peer<T>(
+    env: &Env,
+    contract: &Address,
+    func: &str,
+    args: Vec<Val>,
+    error: ContractError,
+) -> T
+where
+    T: TryFromVal<Env, Val>,
+{
+    match env.try_invoke_contract::<T, InvokeError>(contract, &Symbol::new(env, func), args) {
+        Ok(Ok(value)) => value,
+        _ => panic_with_error!(env, error),
+    }
+}
+
+/// Call an optional peer contract, returning None instead of aborting if the call fails
+///
+/// Callers must compensate (record or log the skipped effect) when this returns None.
+pub fn try_invoke_peer<T>(env: &Env, contract: &Address, func: &str, args: Vec<Val>) -> Option<T>
+where
+    T: TryFromVal<Env, Val>,
+{
+    match env.try_invoke_contract::<T, InvokeError>(contract, &Symbol::new(env, func), args) {
+        Ok(Ok(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// Look up `name` in the address registry stored under `registry_key`, if one is set
+pub fn lookup_registry<K>(env: &Env, registry_key: &K, name: &str) -> Option<Address>
+where
+    K: IntoVal<Env, Val>,
+{
+    let registry: Address = env.storage().instance().get(registry_key)?;
+    invoke_peer(
+        env,
+        &registry,
+        "get_address",
+        (Symbol::new(env, name),).into_val(env),
+        ContractError::PeerCallFailed,
+    )
+}
+
+/// Resolve the peer registered as `name`, or else the address stored under `fallback_key`
+///
+/// Prefer the registry so address rotations only touch one contract.
+pub fn try_resolve_peer<K>(
+    env: &Env,
+    registry_key: &K,
+    name: &str,
+    fallback_key: &K,
+) -> Option<Address>
+where
+    K: IntoVal<Env, Val>,
+{
+    lookup_registry(env, registry_key, name).or_else(|| env.storage().instance().get(fallback_key))
+}
+
+/// Resolve a peer as `try_resolve_peer` does, aborting if neither source has it
+pub fn resolve_peer<K>(env: &Env, registry_key: &K, name: &str, fallback_key: &K) -> Address
+where
+    K: IntoVal<Env, Val>,
+{
+    try_resolve_peer(env, registry_key, name, fallback_key)
+        .unwrap_or_else(|| panic!("Peer contract {} not configured", name))
+}
+
+/// Read `feed` from a price oracle, or None if the oracle fails, quotes zero or has not
+/// updated the feed within MAX_ORACLE_PRICE_AGE
+pub fn read_oracle_price(env: &Env, oracle: &Address, feed: &str) -> Option<u128> {
+    let feed = Symbol::new(env, feed);
+    let price: u128 = try_invoke_peer(env, oracle, "get_price", (feed.clone(),).into_val(env))?;
+    let updated_at: u64 = try_invoke_peer(env, oracle, "last_updated", (feed,).into_val(env))?;
+    if price == 0 || env.ledger().timestamp().saturating_sub(updated_at) > MAX_ORACLE_PRICE_AGE {
+        return None;
+    }
+    Some(price)
+}
+
+/// Transfer tokens, aborting with `TokenTransferFailed` if the token contract rejects it
+pub fn transfer_or_abort(env: &Env, token: &Address, from: &Address, to: &Address, amount: i128) {
+    let token_client = TokenClient::new(env, token);
+    if !matches!(token_client.try_transfer(from, to, &amount), Ok(Ok(()))) {
+        panic_with_error!(env, ContractError::TokenTransferFailed);
+    }
+}
+
+/// Pull tokens under an allowance, aborting with `TokenTransferFailed` if the token contract rejects it
+pub fn transfer_from_or_abort(
+    env: &Env,
+    token: &Address,
+    spender: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) {
+    let token_client = TokenClient::new(env, token);
+    if !matches!(
+        token_client.try_transfer_from(spender, from, to, &amount),
+        Ok(Ok(()))
+    ) {
+        panic_with_error!(env, ContractError::TokenTransferFailed);
+    }
+}
+
+/// Let the next call this contract makes pull `amount` of `token` from it to `to`
+///
+/// Needed when a peer contract, rather than this one, calls the token's `transfer`.
+pub fn authorize_transfer(env: &Env, token: &Address, to: &Address, amount: i128) {
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token.clone(),
+                fn_name: symbol_short!("transfer"),
+                args: (env.current_contract_address(), to.clone(), amount).into_val(env),
+            },
+            sub_invocations: Vec::new(env),
+        }),
+    ]);
+}
+
+/// Swap `amount_in` of `asset_in` for `asset_out` through a trade router, returning what arrived
+///
+/// Routers implement `swap(asset_in, asset_out, amount_in, min_amount_out, to)`. The minimum
+/// is enforced against the measured receipt, aborting with `SlippageExceeded`.
+pub fn swap_via_router(
+    env: &Env,
+    router: &Address,
+    asset_in: &Address,
+    asset_out: &Address,
+    amount_in: u128,
+    min_amount_out: u128,
+) -> u128 {
+    let this = env.current_contract_address();
+    let out_client = TokenClient::new(env, asset_out);
+    let balance_before = out_client.balance(&this);
+    transfer_or_abort(env, asset_in, &this, router, amount_in as i128);
+    invoke_peer::<u128>(
+        env,
+        router,
+        "swap",
+        (
+            asset_in.clone(),
+            asset_out.clone(),
+            amount_in,
+            min_amount_out,
+            this.clone(),
+        )
+            .into_val(env),
+        ContractError::PeerCallFailed,
+    );
+    let amount_out = (out_client.balance(&this) - balance_before).max(0) as u128;
+    if amount_out < min_amount_out {
+        panic_with_error!(env, ContractError::SlippageExceeded);
+    }
+    amount_out
+}
+
+/// Check a liquidity facility's terms, panicking if any is out of range
+pub fn validate_liquidity_facility(facility: &LiquidityFacility) {
+    if facility.cap == 0
+        || facility.max_term == 0
+        || facility.max_term > MAX_FACILITY_TERM
+        || facility.max_ltv_bps == 0
+        || facility.max_ltv_bps as u128 > BASIS_POINTS
+    {
+        panic!("Invalid liquidity facility terms");
+    }
+}
+
+/// Simple interest accrued on a facility draw up to now, rounded up in the lender's favour
+pub fn facility_interest(env: &Env, draw: &FacilityDraw) -> u128 {
+    let elapsed = env.ledger().timestamp().saturating_sub(draw.drawn_at) as u128;
+    mul_div_up(
+        draw.principal,
+        draw.rate_bps as u128 * elapsed,
+        BASIS_POINTS * DAYS_PER_YEAR * SECONDS_PER_DAY as u128,
+    )
+    .unwrap_or_else(|| panic!("Facility interest overflow"))
+}
+
+/// Get this vault's open draw from a lending vault, if the lender reports one
+pub fn drawn_liquidity(env: &Env, lender: &Address) -> Option<FacilityDraw> {
+    invoke_peer(
+        env,
+        lender,
+        "get_facility_draw",
+        (env.current_contract_address(),).into_val(env),
+        ContractError::PeerCallFailed,
+    )
+}
+
+/// Repay this vault's open draw from a lending vault out of the drawn token it holds
+///
+/// The lender pulls the principal and interest itself, so the transfer is authorized
+/// up front. Returns the amount repaid.
+pub fn repay_drawn_liquidity(env: &Env, lender: &Address) -> u128 {
+    let this = env.current_contract_address();
+    let draw = drawn_liquidity(env, lender).unwrap_or_else(|| panic!("No open liquidity draw"));
+    let interest: u128 = invoke_peer(
+        env,
+        lender,
+        "get_facility_interest",
+        (this.clone(),).into_val(env),
+        ContractError::PeerCallFailed,
+    );
+    let owed = draw.principal + interest;
+    let held = TokenClient::new(env, &draw.asset).balance(&this).max(0) as u128;
+    if held < owed {
+        panic!("Not enough drawn liquidity held to repay");
+    }
+
+    authorize_transfer(env, &draw.asset, lender, owed as i128);
+    invoke_peer(
+        env,
+        lender,
+        "repay_facility",
+        (this.clone(), this).into_val(env),
+        ContractError::PeerCallFailed,
+    )
+}
+
+/// Hand `amount` of `asset` to a yield strategy and have it deploy the funds
+///
+/// Strategies implement `deposit(vault, asset, amount)`, `withdraw(vault, asset, amount) -> u128`
+/// and `harvest(vault, asset) -> u128`, paying withdrawals and harvests back to the vault.
+pub fn deposit_to_strategy(env: &Env, strategy: &Address, asset: &Address, amount: u128) {
+    let vault = env.current_contract_address();
+    transfer_or_abort(env, asset, &vault, strategy, amount as i128);
+    invoke_peer::<()>(
+        env,
+        strategy,
+        "deposit",
+        (vault, asset.clone(), amount).into_val(env),
+        ContractError::StrategyCallFailed,
+    );
+}
+
+/// Ask a strategy to return `amount` of `asset`, returning what the vault received or None on failure
+pub fn withdraw_from_strategy(
+    env: &Env,
+    strategy: &Address,
+    asset: &Address,
+    amount: u128,
+) -> Option<u128> {
+    let args = (env.current_contract_address(), asset.clone(), amount).into_val(env);
+    pull_from_strategy(env, strategy, asset, "withdraw", args)
+}
+
+/// Collect a strategy's earned yield in `asset`, returning what the vault received or None on failure
+pub fn harvest_from_strategy(env: &Env, strategy: &Address, asset: &Address) -> Option<u128> {
+    let args = (env.current_contract_address(), asset.clone()).into_val(env);
+    pull_from_strategy(env, strategy, asset, "harvest", args)
+}
+
+fn pull_from_strategy(
+    env: &Env,
+    strategy: &Address,
+    asset: &Address,
+    func: &str,
+    args: Vec<Val>,
+) -> Option<u128> {
+    // Count what actually arrived rather than what the strategy reports
+    let token_client = TokenClient::new(env, asset);
+    let before = token_client.balance(&env.current_contract_address());
+    try_invoke_peer::<u128>(env, strategy, func, args)?;
+    let after = token_client.balance(&env.current_contract_address());
+    Some(after.saturating_sub(before).max(0) as u128)
+}
+
+/// Mark a guarded entrypoint as in progress, aborting with `Reentrancy` if one already is
+pub fn enter_guard(env: &Env) {
+    if env.storage().instance().has(&REENTRANCY_GUARD) {
+        panic_with_error!(env, ContractError::Reentrancy);
+    }
+    env.storage().instance().set(&REENTRANCY_GUARD, &true);
+}
+
+/// Clear the reentrancy guard once a guarded entrypoint has finished its calls
+///
+/// A panic reverts the guard along with the rest of the call's state.
+pub fn exit_guard(env: &Env) {
+    env.storage().instance().remove(&REENTRANCY_GUARD);
+}
@@ -0,0 +1,157 @@
+//! Fixed-point arithmetic and the protocol's rounding policy
+//!
+//! Rounding always favours the protocol: amounts paid out round down and
+//! amounts owed round up. Payouts that drop a fraction report it through
+//! `mul_div_with_dust` so the paying contract can book it as rounding dust.
+
+use crate::{
+    CompoundingFrequency, EpochSummary, VaultType, BASIS_POINTS, DAYS_PER_YEAR, DUST_PRECISION,
+    GOLD_PRICE_PRECISION, SECONDS_PER_DAY,
+};
+use soroban_sdk::Vec;
+
+/// Compute `a * b / denominator`, rounding down; used for payouts
+///
+/// Returns None on overflow or a zero denominator instead of wrapping or panicking.
+pub fn mul_div(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    a.checked_mul(b).map(|product| product / denominator)
+}
+
+/// Compute `a * b / denominator`, rounding up; used for obligations
+///
+/// Returns None on overflow or a zero denominator.
+pub fn mul_div_up(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    a.checked_mul(b)
+        .map(|product| product.div_ceil(denominator))
+}
+
+/// Compute `a * b / denominator` rounded down, together with the fraction dropped
+///
+/// The fraction is in `DUST_PRECISION` parts of one unit, itself rounded down.
+pub fn mul_div_with_dust(a: u128, b: u128, denominator: u128) -> Option<(u128, u128)> {
+    if denominator == 0 {
+        return None;
+    }
+    let product = a.checked_mul(b)?;
+    let dust = mul_div(product % denominator, DUST_PRECISION, denominator)?;
+    Some((product / denominator, dust))
+}
+
+/// Grow `principal` by `annual_rate` basis points, compounded once per whole day elapsed
+///
+/// Each day rounds down, so the result undershoots exact compounding by at most
+/// one unit per day. Returns None if the balance would overflow.
+pub fn compound_daily(principal: u128, annual_rate: u128, time_elapsed: u64) -> Option<u128> {
+    compound_periods(principal, annual_rate, time_elapsed / SECONDS_PER_DAY, 1)
+}
+
+/// Grow `principal` by `annual_rate` basis points under a deposit's compounding frequency
+///
+/// Daily and weekly growth compound once per whole period elapsed. At-maturity
+/// growth is simple interest over whole days, compounded once when credited.
+/// Returns None if the balance would overflow.
+pub fn compound_at(
+    principal: u128,
+    annual_rate: u128,
+    time_elapsed: u64,
+    frequency: &CompoundingFrequency,
+) -> Option<u128> {
+    match frequency {
+        CompoundingFrequency::Daily => compound_daily(principal, annual_rate, time_elapsed),
+        CompoundingFrequency::Weekly => {
+            let weeks = time_elapsed / (7 * SECONDS_PER_DAY);
+            compound_periods(principal, annual_rate, weeks, 7)
+        }
+        CompoundingFrequency::AtMaturity => {
+            let days = (time_elapsed / SECONDS_PER_DAY) as u128;
+            let per_year = DAYS_PER_YEAR * BASIS_POINTS;
+            let interest = mul_div(principal, annual_rate.checked_mul(days)?, per_year)?;
+            principal.checked_add(interest)
+        }
+    }
+}
+
+fn compound_periods(
+    principal: u128,
+    annual_rate: u128,
+    periods: u64,
+    days_per_period: u128,
+) -> Option<u128> {
+    let period_rate = annual_rate.checked_mul(days_per_period)?;
+    let period_denominator = DAYS_PER_YEAR * BASIS_POINTS;
+
+    let mut result = principal;
+    for _ in 0..periods {
+        let interest = mul_div(result, period_rate, period_denominator)?;
+        if interest == 0 {
+            // Too small to ever accrue again at this rate
+            break;
+        }
+        result = result.checked_add(interest)?;
+    }
+
+    Some(result)
+}
+
+/// Simple interest on `principal` at `annual_rate` bps, accrued per second
+pub fn stream_yield(principal: u128, annual_rate: u128, time_elapsed: u64) -> Option<u128> {
+    let per_year = DAYS_PER_YEAR * SECONDS_PER_DAY as u128 * BASIS_POINTS;
+    mul_div(
+        principal,
+        annual_rate.checked_mul(time_elapsed as u128)?,
+        per_year,
+    )
+}
+
+/// Convert a gold amount to USD at `price_usd` (6-decimal oracle price per unit)
+pub fn gold_usd_value(gold_amount: u128, price_usd: u128) -> Option<u128> {
+    mul_div(gold_amount, price_usd, GOLD_PRICE_PRECISION)
+}
+
+/// Annualize the yield realized over closed epochs, in basis points of TVL, rounding down
+///
+/// A vault is credited the fixed-rate yield it paid and the strategy yield it harvested,
+/// plus the share of profit-report yield its TVL earned through the shared yield index;
+/// `None` covers all vaults. Both gold vault types read the gold vault. Epochs without
+/// TVL in the vault are skipped, so the result is zero until one has it. Returns None on
+/// overflow.
+pub fn realized_apy(summaries: &Vec<EpochSummary>, vault_type: &Option<VaultType>) -> Option<u128> {
+    let mut yield_earned: u128 = 0;
+    let mut tvl_seconds: u128 = 0;
+    for summary in summaries.iter() {
+        let total_tvl = summary.usdc_vault_tvl.checked_add(summary.gold_vault_tvl)?;
+        let (vault_tvl, yield_paid) = match vault_type {
+            None => (
+                total_tvl,
+                summary
+                    .usdc_yield_paid
+                    .checked_add(summary.gold_yield_paid)?,
+            ),
+            Some(VaultType::USDC) => (summary.usdc_vault_tvl, summary.usdc_yield_paid),
+            Some(VaultType::PAXG) | Some(VaultType::WisdomTreeGold) => {
+                (summary.gold_vault_tvl, summary.gold_yield_paid)
+            }
+        };
+        if vault_tvl == 0 {
+            continue;
+        }
+        let distributed = mul_div(summary.yield_distributed, vault_tvl, total_tvl)?;
+        yield_earned = yield_earned
+            .checked_add(yield_paid)?
+            .checked_add(distributed)?;
+        let duration = (summary.ended_at - summary.started_at) as u128;
+        tvl_seconds = tvl_seconds.checked_add(vault_tvl.checked_mul(duration)?)?;
+    }
+    if tvl_seconds == 0 {
+        return Some(0);
+    }
+
+    let seconds_per_year = DAYS_PER_YEAR * SECONDS_PER_DAY as u128;
+    mul_div(yield_earned, BASIS_POINTS * seconds_per_year, tvl_seconds)
+}
@@ -0,0 +1,107 @@
+//! Initialization flag and storage schema version kept by every contract
+//!
+//! Both markers sit under fixed symbol keys, clear of every contract's own
+//! `DataKey`, so any schema can find them. Storage written before versioning
+//! (schema v0) has neither marker, and its unguarded `initialize` let anyone
+//! replace the admin, so a v0 migration trusts the protocol deployer's record
+//! of who the contract was deployed for rather than the stored admin.
+
+use crate::{extend_instance_ttl, invoke_peer, ContractError, DeployedContract};
+use soroban_sdk::{log, panic_with_error, symbol_short, Address, Env, IntoVal, Symbol};
+
+const INITIALIZED: Symbol = symbol_short!("INIT");
+const SCHEMA_VERSION: Symbol = symbol_short!("SCHEMA");
+
+/// Check whether the contract has been initialized
+pub fn is_initialized(env: &Env) -> bool {
+    env.storage().instance().has(&INITIALIZED)
+}
+
+/// Get the schema version storage was last written at, or 0 before versioning
+pub fn stored_schema_version(env: &Env) -> u32 {
+    env.storage().instance().get(&SCHEMA_VERSION).unwrap_or(0)
+}
+
+/// Abort with `AlreadyInitialized` once the contract holds state
+///
+/// `legacy_key` is an instance key every deployment from before versioning
+/// set, so upgraded v0 storage cannot be initialized over before it migrates.
+pub fn ensure_uninitialized(env: &Env, legacy_key: Option<Symbol>) {
+    let legacy = legacy_key.map_or(false, |key| env.storage().instance().has(&key));
+    if is_initialized(env) || legacy {
+        panic_with_error!(env, ContractError::AlreadyInitialized);
+    }
+}
+
+/// Record the contract as initialized with storage at `schema_version`
+pub fn mark_initialized(env: &Env, schema_version: u32) {
+    env.storage().instance().set(&INITIALIZED, &true);
+    env.storage()
+        .instance()
+        .set(&SCHEMA_VERSION, &schema_version);
+}
+
+/// Start a migration to `schema_version`, returning the version storage is at
+///
+/// Aborts with `InvalidMigration` when storage is already at or past it.
+pub fn begin_migration(env: &Env, schema_version: u32) -> u32 {
+    let stored_version = stored_schema_version(env);
+    if stored_version >= schema_version {
+        panic_with_error!(env, ContractError::InvalidMigration);
+    }
+    stored_version
+}
+
+/// Check that `admin` may migrate storage at `stored_version`; `admin` must already be authorized
+///
+/// From v1 on the stored admin decides. At v0 `deployer` must be the deployer
+/// contract that created this one, shown by its recorded salt deriving this
+/// contract's address, and `admin` must be the admin it deployed the contract for.
+pub fn verify_migration_admin(
+    env: &Env,
+    stored_version: u32,
+    admin: &Address,
+    stored_admin: Option<Address>,
+    deployer: Option<Address>,
+) {
+    let trusted_admin = if stored_version == 0 {
+        let deployer =
+            deployer.unwrap_or_else(|| panic!("Storage from before versioning needs its deployer"));
+        let current = env.current_contract_address();
+        let record: Option<DeployedContract> = invoke_peer(
+            env,
+            &deployer,
+            "get_deployed_contract",
+            (current.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        record
+            .filter(|record| {
+                env.deployer()
+                    .with_address(deployer.clone(), record.salt.clone())
+                    .deployed_address()
+                    == current
+            })
+            .map(|record| record.admin)
+    } else {
+        stored_admin
+    };
+
+    if trusted_admin.as_ref() != Some(admin) {
+        panic!("Only admin can migrate storage");
+    }
+}
+
+/// Record storage as migrated from `stored_version` to `schema_version`
+pub fn finish_migration(env: &Env, stored_version: u32, schema_version: u32) {
+    // Deployments predating versioning also lack the initialization flag
+    mark_initialized(env, schema_version);
+    extend_instance_ttl(env);
+
+    log!(
+        env,
+        "Storage migrated from version {} to {}",
+        stored_version,
+        schema_version
+    );
+}
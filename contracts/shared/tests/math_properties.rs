@@ -0,0 +1,161 @@
+use proptest::prelude::*;
+use shared::{
+    compound_daily, gold_usd_value, mul_div, mul_div_up, mul_div_with_dust, BASIS_POINTS,
+    DAYS_PER_YEAR, DUST_PRECISION, GOLD_PRICE_PRECISION, SECONDS_PER_DAY,
+};
+
+// Bounds the protocol is expected to handle without overflow
+const MAX_PRINCIPAL: u128 = 10_000_000_000_000_000_000_000_000_000; // 1e28
+const MAX_RATE: u128 = 10_000; // 100% APY
+const MAX_DURATION: u64 = 10 * 365 * SECONDS_PER_DAY;
+const MAX_GOLD_AMOUNT: u128 = 1_000_000_000_000_000_000_000_000; // 1e24
+const MAX_GOLD_PRICE: u128 = 1_000_000 * GOLD_PRICE_PRECISION; // $1M per unit
+
+/// Exact daily compounding in floating point, used as the rounding reference
+fn reference_compound(principal: u128, annual_rate: u128, days: u64) -> f64 {
+    let daily = annual_rate as f64 / (DAYS_PER_YEAR * BASIS_POINTS) as f64;
+    principal as f64 * (1.0 + daily).powi(days as i32)
+}
+
+proptest! {
+    #[test]
+    fn compound_never_overflows_within_bounds(
+        principal in 0..=MAX_PRINCIPAL,
+        rate in 0..=MAX_RATE,
+        elapsed in 0..=MAX_DURATION,
+    ) {
+        prop_assert!(compound_daily(principal, rate, elapsed).is_some());
+    }
+
+    #[test]
+    fn compound_reports_overflow_instead_of_wrapping(
+        principal in (u128::MAX / 2)..=u128::MAX,
+        rate in 1..=MAX_RATE,
+    ) {
+        let result = compound_daily(principal, rate, 365 * SECONDS_PER_DAY);
+        if let Some(value) = result {
+            prop_assert!(value >= principal);
+        }
+    }
+
+    #[test]
+    fn compound_is_monotonic_in_time(
+        principal in 0..=MAX_PRINCIPAL,
+        rate in 0..=MAX_RATE,
+        earlier in 0..=MAX_DURATION / 2,
+        extra in 0..=MAX_DURATION / 2,
+    ) {
+        let first = compound_daily(principal, rate, earlier).unwrap();
+        let later = compound_daily(principal, rate, earlier + extra).unwrap();
+        prop_assert!(first >= principal);
+        prop_assert!(later >= first);
+    }
+
+    #[test]
+    fn compound_is_monotonic_in_principal_and_rate(
+        principal in 0..=MAX_PRINCIPAL / 2,
+        extra_principal in 0..=MAX_PRINCIPAL / 2,
+        rate in 0..=MAX_RATE / 2,
+        extra_rate in 0..=MAX_RATE / 2,
+        elapsed in 0..=MAX_DURATION,
+    ) {
+        let base = compound_daily(principal, rate, elapsed).unwrap();
+        prop_assert!(compound_daily(principal + extra_principal, rate, elapsed).unwrap() >= base);
+        prop_assert!(compound_daily(principal, rate + extra_rate, elapsed).unwrap() >= base);
+    }
+
+    #[test]
+    fn compound_rounding_error_is_bounded(
+        principal in 1_000_000u128..=1_000_000_000_000_000_000,
+        rate in 0..=MAX_RATE,
+        days in 0u64..=(MAX_DURATION / SECONDS_PER_DAY),
+    ) {
+        let result = compound_daily(principal, rate, days * SECONDS_PER_DAY).unwrap() as f64;
+        let exact = reference_compound(principal, rate, days);
+
+        // Each day rounds down by under one unit, and later days compound that loss
+        // by at most the overall growth factor; allow float noise on top
+        let growth = exact / principal as f64;
+        prop_assert!(result <= exact * (1.0 + 1e-9) + 1.0);
+        prop_assert!(result >= exact * (1.0 - 1e-9) - days as f64 * growth - 1.0);
+    }
+
+    #[test]
+    fn partial_days_do_not_accrue(
+        principal in 0..=MAX_PRINCIPAL,
+        rate in 0..=MAX_RATE,
+        elapsed in 0..SECONDS_PER_DAY,
+    ) {
+        prop_assert_eq!(compound_daily(principal, rate, elapsed), Some(principal));
+    }
+
+    #[test]
+    fn gold_conversion_is_monotonic_and_round_trips(
+        amount in 0..=MAX_GOLD_AMOUNT,
+        extra in 0..=MAX_GOLD_AMOUNT,
+        price in 1..=MAX_GOLD_PRICE,
+    ) {
+        let value = gold_usd_value(amount, price).unwrap();
+        prop_assert!(gold_usd_value(amount + extra, price).unwrap() >= value);
+
+        // Converting back never returns more gold than went in
+        let back = mul_div(value, GOLD_PRICE_PRECISION, price).unwrap();
+        prop_assert!(back <= amount);
+        prop_assert!(amount - back <= GOLD_PRICE_PRECISION / price + 1);
+    }
+
+    #[test]
+    fn withdrawal_proportioning_is_bounded(
+        gold in 0..=MAX_PRINCIPAL,
+        current_value in 1..=MAX_PRINCIPAL,
+        share in 0u128..=BASIS_POINTS,
+    ) {
+        // Withdraw a fraction (up to all) of the deposit's current USD value
+        let withdrawal_value = mul_div(current_value, share, BASIS_POINTS).unwrap();
+        let owed = mul_div(gold, withdrawal_value, current_value);
+
+        // Products beyond u128 are reported rather than wrapped
+        if let Some(owed) = owed {
+            prop_assert!(owed <= gold);
+            if withdrawal_value == current_value {
+                prop_assert_eq!(owed, gold);
+            }
+        } else {
+            prop_assert!(gold.checked_mul(withdrawal_value).is_none());
+        }
+    }
+
+    #[test]
+    fn mul_div_rejects_zero_denominator(a in any::<u128>(), b in any::<u128>()) {
+        prop_assert_eq!(mul_div(a, b, 0), None);
+    }
+
+    #[test]
+    fn obligations_round_up_by_less_than_one_unit(
+        a in 0..=MAX_PRINCIPAL,
+        b in 0..=MAX_RATE,
+        denominator in 1..=MAX_PRINCIPAL,
+    ) {
+        let down = mul_div(a, b, denominator).unwrap();
+        let up = mul_div_up(a, b, denominator).unwrap();
+        prop_assert!(up >= down && up - down <= 1);
+        prop_assert_eq!(up == down, (a * b) % denominator == 0);
+        prop_assert_eq!(mul_div_up(a, b, 0), None);
+    }
+
+    #[test]
+    fn payout_dust_is_the_fraction_rounded_off(
+        a in 0..=MAX_GOLD_AMOUNT,
+        b in 0..=MAX_GOLD_PRICE,
+        denominator in 1..=MAX_GOLD_PRICE,
+    ) {
+        let (payout, dust) = mul_div_with_dust(a, b, denominator).unwrap();
+        prop_assert_eq!(Some(payout), mul_div(a, b, denominator));
+        prop_assert!(dust < DUST_PRECISION);
+
+        // The dust, scaled back to units, accounts for the remainder to within one part
+        let remainder = (a * b) % denominator;
+        prop_assert!(dust * denominator <= remainder * DUST_PRECISION);
+        prop_assert!(remainder * DUST_PRECISION < (dust + 1) * denominator);
+    }
+}
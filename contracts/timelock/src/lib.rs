@@ -0,0 +1,392 @@
+#![no_std]
+use shared::{
+    ensure_not_paused, ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl,
+    is_initialized, mark_initialized, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, ContractInfo, ProposalStatus, Role, TimelockOperation,
+    TIMELOCK_GRACE_PERIOD, TIMELOCK_MIN_DELAY,
+};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, symbol_short, vec, Address, Env, IntoVal, String,
+    Symbol, TryFromVal, Val, Vec,
+};
+
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and counters
+    Proposer,
+    MinDelay,
+    OperationCounter,
+    // Persistent: queued operations
+    Operation(u64),
+}
+
+// Function names recorded on operations the timelock applies to itself
+const UPDATE_DELAY: Symbol = symbol_short!("delay");
+const UPDATE_PROPOSER: Symbol = symbol_short!("proposer");
+const UPDATE_ROLE: Symbol = symbol_short!("role");
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 1;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct Timelock;
+
+#[contractimpl]
+impl Timelock {
+    /// Initialize the timelock controller
+    ///
+    /// The proposer is expected to be the governance or multisig contract, so
+    /// every queued call has already passed a vote before the delay starts.
+    pub fn initialize(env: Env, proposer: Address, min_delay: u64) {
+        ensure_uninitialized(&env, None);
+
+        if min_delay < TIMELOCK_MIN_DELAY {
+            panic!("Delay must be at least {} seconds", TIMELOCK_MIN_DELAY);
+        }
+
+        env.storage().instance().set(&DataKey::Proposer, &proposer);
+        env.storage().instance().set(&DataKey::MinDelay, &min_delay);
+        env.storage()
+            .instance()
+            .set(&DataKey::OperationCounter, &0u64);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Timelock initialized with proposer: {} and delay: {}",
+            proposer,
+            min_delay
+        );
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, "timelock"),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Queue a change of the minimum delay behind the current one (proposer only)
+    pub fn schedule_delay_update(env: Env, proposer: Address, new_delay: u64, delay: u64) -> u64 {
+        if new_delay < TIMELOCK_MIN_DELAY {
+            panic!("Delay must be at least {} seconds", TIMELOCK_MIN_DELAY);
+        }
+
+        let args = vec![&env, new_delay.into_val(&env)];
+        Self::schedule(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            UPDATE_DELAY,
+            args,
+            delay,
+        )
+    }
+
+    /// Queue a change of proposer behind the current delay (proposer only)
+    pub fn schedule_proposer_update(
+        env: Env,
+        proposer: Address,
+        new_proposer: Address,
+        delay: u64,
+    ) -> u64 {
+        let args = vec![&env, new_proposer.into_val(&env)];
+        Self::schedule(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            UPDATE_PROPOSER,
+            args,
+            delay,
+        )
+    }
+
+    /// Queue granting a role, or revoking it with None, behind the current delay (proposer only)
+    ///
+    /// The timelock has no admin, so a role nobody holds cannot be used until
+    /// one is granted, and revoking the pauser lifts any pause it left in place.
+    pub fn schedule_role_update(
+        env: Env,
+        proposer: Address,
+        role: Role,
+        holder: Option<Address>,
+        delay: u64,
+    ) -> u64 {
+        let args = vec![&env, role.into_val(&env), holder.into_val(&env)];
+        Self::schedule(
+            env.clone(),
+            proposer,
+            env.current_contract_address(),
+            UPDATE_ROLE,
+            args,
+            delay,
+        )
+    }
+
+    /// Queue a cross-contract call to run no earlier than `delay` seconds from now (proposer only)
+    pub fn schedule(
+        env: Env,
+        proposer: Address,
+        target: Address,
+        function: Symbol,
+        args: Vec<Val>,
+        delay: u64,
+    ) -> u64 {
+        proposer.require_auth();
+        Self::verify_proposer(&env, &proposer);
+
+        let min_delay: u64 = env.storage().instance().get(&DataKey::MinDelay).unwrap();
+        if delay < min_delay {
+            panic!("Delay must be at least {} seconds", min_delay);
+        }
+
+        let operation_counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::OperationCounter)
+            .unwrap_or(0);
+        let operation_id = operation_counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::OperationCounter, &operation_id);
+
+        let operation = TimelockOperation {
+            id: operation_id,
+            target,
+            function,
+            args,
+            eta: env.ledger().timestamp() + delay,
+            status: ProposalStatus::Pending,
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Operation(operation_id), &operation);
+        extend_persistent_ttl(&env, &DataKey::Operation(operation_id));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Timelock operation {} scheduled: {} on {}, executable at {}",
+            operation_id,
+            operation.function,
+            operation.target,
+            operation.eta
+        );
+
+        operation_id
+    }
+
+    /// Cancel a queued operation before it runs (proposer only)
+    pub fn cancel(env: Env, proposer: Address, operation_id: u64) {
+        proposer.require_auth();
+        Self::verify_proposer(&env, &proposer);
+
+        let mut operation = Self::get_operation_or_panic(&env, operation_id);
+
+        if operation.status != ProposalStatus::Pending {
+            panic!("Operation is not pending");
+        }
+
+        operation.status = ProposalStatus::Rejected;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Operation(operation_id), &operation);
+
+        log!(&env, "Timelock operation {} cancelled", operation_id);
+    }
+
+    /// Execute a queued operation once its delay has passed (callable by anyone)
+    pub fn execute(env: Env, operation_id: u64) -> Val {
+        let mut operation = Self::get_operation_or_panic(&env, operation_id);
+
+        if operation.status != ProposalStatus::Pending {
+            panic!("Operation is not pending");
+        }
+
+        let current_time = env.ledger().timestamp();
+        if current_time < operation.eta {
+            panic!("Operation is not ready. Executable at: {}", operation.eta);
+        }
+
+        if current_time > operation.eta + TIMELOCK_GRACE_PERIOD {
+            panic!("Operation has expired");
+        }
+
+        // Updates to the timelock itself still apply, so a pauser can always be revoked
+        let own_update = operation.target == env.current_contract_address();
+        if !own_update {
+            ensure_not_paused(&env);
+        }
+
+        // Mark executed before the external call
+        operation.status = ProposalStatus::Executed;
+        env.storage()
+            .persistent()
+            .set(&DataKey::Operation(operation_id), &operation);
+
+        // The timelock is the admin of the target, so the direct-caller auth
+        // satisfies `admin.require_auth()` when its address is passed as admin.
+        // Changes to the timelock itself are applied here, as it cannot call itself.
+        let result: Val = if own_update {
+            Self::apply_own_update(&env, &operation);
+            ().into_val(&env)
+        } else {
+            env.invoke_contract(
+                &operation.target,
+                &operation.function,
+                operation.args.clone(),
+            )
+        };
+
+        log!(
+            &env,
+            "Timelock operation {} executed: {} on {}",
+            operation_id,
+            operation.function,
+            operation.target
+        );
+
+        result
+    }
+
+    /// Get queued operation details
+    pub fn get_operation(env: Env, operation_id: u64) -> Option<TimelockOperation> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Operation(operation_id))
+    }
+
+    /// Check whether an operation can be executed now
+    pub fn is_ready(env: Env, operation_id: u64) -> bool {
+        match Self::get_operation(env.clone(), operation_id) {
+            Some(operation) => {
+                let current_time = env.ledger().timestamp();
+                operation.status == ProposalStatus::Pending
+                    && current_time >= operation.eta
+                    && current_time <= operation.eta + TIMELOCK_GRACE_PERIOD
+            }
+            None => false,
+        }
+    }
+
+    /// Get current minimum delay
+    pub fn get_min_delay(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinDelay)
+            .unwrap_or(0)
+    }
+
+    /// Get current proposer
+    pub fn get_proposer(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Proposer).unwrap()
+    }
+
+    /// Get the address holding a role, if one has been granted
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Hold execution of queued calls to other contracts (pauser only)
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        require_role(&env, Role::Pauser, &caller, &env.current_contract_address());
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser only)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        require_role(&env, Role::Pauser, &caller, &env.current_contract_address());
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether execution of calls to other contracts is paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Internal helper functions
+    fn apply_own_update(env: &Env, operation: &TimelockOperation) {
+        let value = operation
+            .args
+            .get(0)
+            .unwrap_or_else(|| panic!("Malformed timelock update"));
+
+        if operation.function == UPDATE_DELAY {
+            let new_delay = u64::try_from_val(env, &value)
+                .unwrap_or_else(|_| panic!("Malformed timelock update"));
+            if new_delay < TIMELOCK_MIN_DELAY {
+                panic!("Delay must be at least {} seconds", TIMELOCK_MIN_DELAY);
+            }
+            env.storage().instance().set(&DataKey::MinDelay, &new_delay);
+
+            log!(env, "Timelock delay updated to {}", new_delay);
+        } else if operation.function == UPDATE_PROPOSER {
+            let new_proposer = Address::try_from_val(env, &value)
+                .unwrap_or_else(|_| panic!("Malformed timelock update"));
+            env.storage()
+                .instance()
+                .set(&DataKey::Proposer, &new_proposer);
+
+            log!(env, "Timelock proposer updated to {}", new_proposer);
+        } else if operation.function == UPDATE_ROLE {
+            let role = Role::try_from_val(env, &value)
+                .unwrap_or_else(|_| panic!("Malformed timelock update"));
+            let holder: Option<Address> = operation
+                .args
+                .get(1)
+                .and_then(|holder| Option::try_from_val(env, &holder).ok())
+                .unwrap_or_else(|| panic!("Malformed timelock update"));
+            // Nobody else could lift a revoked pauser's pause
+            if role == Role::Pauser && holder.is_none() {
+                set_paused(env, false);
+            }
+            set_role_holder(env, role.clone(), holder.clone());
+
+            log!(env, "Timelock role {:?} assigned to {:?}", role, holder);
+        } else {
+            panic!("Unknown timelock update");
+        }
+    }
+
+    fn verify_proposer(env: &Env, proposer: &Address) {
+        let stored_proposer: Address = env.storage().instance().get(&DataKey::Proposer).unwrap();
+        if *proposer != stored_proposer {
+            panic!("Only the timelock proposer can queue or cancel operations");
+        }
+    }
+
+    fn get_operation_or_panic(env: &Env, operation_id: u64) -> TimelockOperation {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Operation(operation_id))
+            .unwrap_or_else(|| panic!("Timelock operation not found"))
+    }
+}
@@ -1,16 +1,144 @@
 #![no_std]
-use soroban_sdk::{
-    contract, contractimpl, log, symbol_short, Address, Env, Symbol, IntoVal
+use shared::{
+    anchor_ledger_clock, begin_migration, compound_at, compound_daily, deadline_time,
+    deposit_to_strategy, digest_accounting, drawn_liquidity, ensure_not_paused,
+    ensure_uninitialized, enter_guard, exit_guard, extend_instance_ttl, extend_persistent_ttl,
+    facility_interest, finish_migration, harvest_from_strategy, hash_deposit_terms, invoke_peer,
+    is_initialized, ledger_clock, lookup_registry, mark_initialized, migrate_instance_entry,
+    migrate_persistent_entry, mul_div, mul_div_up, mul_div_with_dust, read_oracle_price,
+    repay_drawn_liquidity, require_role, resolve_peer, role_holder, set_paused, set_role_holder,
+    stored_schema_version, swap_via_router, transfer_from_or_abort, transfer_or_abort,
+    try_invoke_peer, try_resolve_peer, validate_liquidity_facility, verify_migration_admin,
+    withdraw_from_strategy, Beneficiary, CompoundingFrequency, ContractError, ContractInfo,
+    DepositInfo, DepositPreview, DepositReceipt, DepositTag, DepositTerms, DepositVoucher,
+    DormancyPolicy, Encumbrance, ExitFeeSchedule, FacilityDraw, FeeSwitch, LedgerClock,
+    LiquidityFacility, LockPeriod, LockTierCap, PositionPage, PositionRecord, QueuedClaim,
+    RecoveryPlan, RevenueStream, Role, SettlementSnapshot, StandingOrder, StrategySlot,
+    VaultHealthReport, VaultType, VoucherStatus, WithdrawalCap, WithdrawalEpoch, YieldFeeSchedule,
+    BASIS_POINTS, BENEFICIARY_CHANGE_DELAY, EPOCH_DURATION, FACILITY_LIQUIDATION_BONUS_BPS,
+    GOLD_PRICE_PRECISION, MAX_COMPOUNDING_BONUS_BPS, MAX_RECOVERY_INACTIVITY,
+    MAX_STRATEGY_SHARE_BPS, MIN_RECOVERY_INACTIVITY, MIN_STANDING_ORDER_INTERVAL,
+    POSITION_EXPORT_PAGE_SIZE, PROTOCOL_FEE_BASIS_POINTS, REGISTRY_ALLOWLIST, REGISTRY_GOLD_VAULT,
+    REGISTRY_INSURANCE_FUND, REGISTRY_ORACLE, REGISTRY_RECEIPTS, REGISTRY_TRADE_ROUTER,
+    REGISTRY_TREASURY, REGISTRY_USDC_VAULT, REGISTRY_YIELD_TOKEN, SHARE_PRICE_PRECISION,
+    USDC_ASSET, USDC_PEG_PRICE, USDC_PRICE_FEED, WITHDRAWAL_QUEUE_BATCH,
 };
 use soroban_sdk::token::TokenClient;
-use shared::{DepositInfo, LockPeriod, VaultType, USDC_ASSET, STORAGE_INSTANCE_PERSISTENT};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, panic_with_error, symbol_short, Address, Bytes,
+    BytesN, Env, IntoVal, Map, String, Symbol, Vec,
+};
 
 // Storage Keys
-const DEPOSIT: Symbol = symbol_short!("DEPOSIT");
-const VAULT_BALANCE: Symbol = symbol_short!("BALANCE");
-const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
-const ADMIN: Symbol = symbol_short!("ADMIN");
-const USDC_CONTRACT: Symbol = symbol_short!("USDC");
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and totals
+    Admin,
+    UsdcContract,
+    YieldToken,
+    Registry,
+    Rewards,
+    Governance,
+    Receipts,
+    Oracle,
+    Treasury,
+    InsuranceFund,
+    VaultBalance,
+    NextReceiptId,
+    DepegBand,
+    DepegWithdrawn,
+    Settlement,
+    ExitFeeSchedule,
+    YieldFeeSchedule,
+    FeeSwitch(RevenueStream),
+    FeesRouted,
+    UnroutedFees,
+    YieldPaid,
+    RoundingDust,
+    TierCap(LockPeriod),
+    TierBalance(LockPeriod),
+    WithdrawalCap,
+    WithdrawalEpoch,
+    QueuedTotal,
+    QueueHead,
+    QueueTail,
+    Strategies,
+    Strategy(Address),
+    TotalAllocated,
+    Facility(Address),
+    FacilityDraw(Address),
+    FacilityLent,
+    LiquidityLender,
+    LiquidityAsset,
+    Allowlist,
+    LaunchPhase,
+    NextVoucherId,
+    VoucherReserve,
+    DormancyPolicy,
+    FeePolicyVersion,
+    DepositorCount,
+    LadderBalance,
+    CompoundingBonus(LockPeriod, CompoundingFrequency),
+    // Persistent: per-user positions, deposit operators, standing orders and deposit receipts
+    Deposit(Address),
+    DepositOperator(Address, Address),
+    StandingOrder(Address),
+    ActiveReceipt(Address),
+    Receipt(u64),
+    Encumbrance(Address, u64),
+    QueuedWithdrawal(Address),
+    QueuedClaim(u64),
+    DepositTag(Address),
+    Recovery(Address),
+    Beneficiary(Address),
+    Dormant(Address),
+    LadderRung(Address, LockPeriod),
+    // Persistent: append-only depositor index walked by position exports
+    Depositor(u64),
+    DepositorSlot(Address),
+    // Persistent: deposit vouchers
+    Voucher(u64),
+}
+
+// Schema v1 storage keys, only read when migrating
+mod v1 {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub const DEPOSIT: Symbol = symbol_short!("DEPOSIT");
+    pub const VAULT_BALANCE: Symbol = symbol_short!("BALANCE");
+    pub const YIELD_TOKEN: Symbol = symbol_short!("YIELD");
+    pub const ADMIN: Symbol = symbol_short!("ADMIN");
+    pub const USDC_CONTRACT: Symbol = symbol_short!("USDC");
+    pub const REGISTRY: Symbol = symbol_short!("REGISTRY");
+    pub const REWARDS: Symbol = symbol_short!("REWARDS");
+}
+
+// Where the USDC for a new position comes from
+enum Funding {
+    Signed,    // The user signed and transfers it
+    Allowance, // Pulled under the user's allowance
+    Voucher,   // Already held by the vault for a claimed voucher
+}
+
+// How a closed position's USDC reaches its owner
+#[derive(Clone, Copy)]
+enum Payout {
+    Direct,            // Paid now, with anything beyond the epoch cap queued
+    Consolidated(u64), // Queued in full, with yield counted only until the given time
+    Liquidity(u128),   // Paid now in drawn facility gold, with the minimum gold out
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 2;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Lock tiers a laddered deposit is split across, shortest first
+const LADDER_TIERS: [LockPeriod; 3] = [
+    LockPeriod::ThreeMonths,
+    LockPeriod::SixMonths,
+    LockPeriod::TwelveMonths,
+];
 
 #[contract]
 pub struct USDCVault;
@@ -24,250 +152,4579 @@ impl USDCVault {
         usdc_contract: Address,
         yield_token_contract: Address,
     ) {
+        ensure_uninitialized(&env, Some(v1::ADMIN));
+
         admin.require_auth();
-        
-        env.storage().instance().set(&ADMIN, &admin);
-        env.storage().instance().set(&USDC_CONTRACT, &usdc_contract);
-        env.storage().instance().set(&YIELD_TOKEN, &yield_token_contract);
-        env.storage().instance().set(&VAULT_BALANCE, &0u128);
-        
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage()
+            .instance()
+            .set(&DataKey::UsdcContract, &usdc_contract);
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldToken, &yield_token_contract);
+        env.storage().instance().set(&DataKey::VaultBalance, &0u128);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
         log!(&env, "USDC Vault initialized with admin: {}", admin);
     }
 
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_USDC_VAULT),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Migrate storage to the schema version of the current WASM (admin only)
+    ///
+    /// Moves config and totals; deposits are moved with `migrate_deposits`.
+    ///
+    /// Storage from before versioning also needs the `deployer` contract that
+    /// created this one, whose record of the admin is trusted over the stored one.
+    pub fn migrate(env: Env, admin: Address, deployer: Option<Address>) {
+        admin.require_auth();
+
+        // Until the v2 migration has run the admin still sits under its v1 key
+        let stored_admin: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .or_else(|| env.storage().instance().get(&v1::ADMIN));
+        let stored_version = begin_migration(&env, STORAGE_VERSION);
+        verify_migration_admin(&env, stored_version, &admin, stored_admin, deployer);
+
+        match stored_version {
+            0 | 1 => Self::migrate_v1_instance(&env),
+            _ => panic_with_error!(&env, ContractError::InvalidMigration),
+        }
+        finish_migration(&env, stored_version, STORAGE_VERSION);
+    }
+
+    /// Move schema v1 deposit records to their `DataKey` entries (admin only)
+    ///
+    /// Persistent storage cannot be enumerated, so the depositors are supplied
+    /// in batches from indexed deposit events once `migrate` has run.
+    pub fn migrate_deposits(env: Env, admin: Address, users: Vec<Address>) -> u32 {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let mut migrated = 0u32;
+        for user in users.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::DEPOSIT, user.clone()),
+                &DataKey::Deposit(user.clone()),
+            ) {
+                migrated += 1;
+            }
+
+            // Index every supplied depositor so exports reach positions opened before the index
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Deposit(user.clone()))
+            {
+                Self::track_depositor(&env, &user);
+            }
+        }
+
+        log!(&env, "Migrated {} deposit records", migrated);
+
+        migrated
+    }
+
     /// Deposit USDC into the vault with time lock
-    pub fn deposit(env: Env, user: Address, amount: u128, lock_period: LockPeriod) {
+    ///
+    /// Returns the hash of the position's term sheet, which `verify_certificate`
+    /// checks so an off-chain agreement can reference the position.
+    pub fn deposit(env: Env, user: Address, amount: u128, lock_period: LockPeriod) -> BytesN<32> {
+        user.require_auth();
+        Self::open_deposit(
+            &env,
+            &user,
+            amount,
+            lock_period,
+            Funding::Signed,
+            None,
+            CompoundingFrequency::Daily,
+        )
+    }
+
+    /// Deposit USDC choosing how often its yield compounds
+    ///
+    /// Weekly and at-maturity compounding must be offered for the lock tier with
+    /// `set_compounding_option`, and add that option's rate adjustment to the tier rate.
+    pub fn deposit_compounding(
+        env: Env,
+        user: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        compounding: CompoundingFrequency,
+    ) -> BytesN<32> {
+        user.require_auth();
+        Self::open_deposit(
+            &env,
+            &user,
+            amount,
+            lock_period,
+            Funding::Signed,
+            None,
+            compounding,
+        )
+    }
+
+    /// Deposit USDC naming the only address the user's positions may be paid out to
+    ///
+    /// Meant for a cold wallet, so a compromised depositor key cannot withdraw to
+    /// itself. The first designation takes effect at once; an existing beneficiary
+    /// can only be replaced through `change_beneficiary` and its delay.
+    pub fn deposit_to_beneficiary(
+        env: Env,
+        user: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        beneficiary: Address,
+    ) -> BytesN<32> {
+        user.require_auth();
+        Self::designate_beneficiary(&env, &user, &beneficiary);
+        Self::open_deposit(
+            &env,
+            &user,
+            amount,
+            lock_period,
+            Funding::Signed,
+            None,
+            CompoundingFrequency::Daily,
+        )
+    }
+
+    /// Deposit USDC the user approved the vault to spend, on their behalf
+    ///
+    /// The operator signs instead of the user and must be the user or one of their
+    /// approved deposit operators; the vault pulls the USDC with `transfer_from`.
+    pub fn deposit_from(
+        env: Env,
+        operator: Address,
+        user: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+    ) -> BytesN<32> {
+        operator.require_auth();
+        Self::verify_deposit_operator(&env, &user, &operator);
+        Self::open_deposit(
+            &env,
+            &user,
+            amount,
+            lock_period,
+            Funding::Allowance,
+            None,
+            CompoundingFrequency::Daily,
+        )
+    }
+
+    /// Deposit USDC attributed to a customer of an integrator such as an exchange or custodian
+    ///
+    /// The tag is kept with the position and published in the deposit event so
+    /// pooled funds can be reconciled per customer off-chain.
+    pub fn deposit_tagged(
+        env: Env,
+        user: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        integrator: Address,
+        customer_id: u64,
+    ) -> BytesN<32> {
+        user.require_auth();
+        let tag = DepositTag {
+            integrator,
+            customer_id,
+        };
+        Self::open_deposit(
+            &env,
+            &user,
+            amount,
+            lock_period,
+            Funding::Signed,
+            Some(tag),
+            CompoundingFrequency::Daily,
+        )
+    }
+
+    /// Split one deposit across the 3, 6 and 12 month tiers as separate ladder rungs
+    ///
+    /// `allocation_bps` gives each tier's share in that order and must add up to 100%.
+    /// Rungs sit beside the user's regular deposit, one per tier, and earn their tier's
+    /// rate straight from the vault without minting yield tokens. Each is withdrawn on
+    /// its own with `withdraw_rung` once its lock expires, or with the regular deposit
+    /// through `redeem_settlement`. Rungs appear in `export_positions` without a position ID.
+    pub fn deposit_laddered(env: Env, user: Address, amount: u128, allocation_bps: Vec<u32>) {
         user.require_auth();
-        
+        ensure_not_paused(&env);
+        Self::ensure_not_settled(&env);
+        Self::ensure_allowlisted(&env, &user);
+
         if amount == 0 {
             panic!("Deposit amount must be greater than 0");
         }
+        if allocation_bps.len() != LADDER_TIERS.len() as u32 {
+            panic!("Allocation must give a share for each of the 3 lock tiers");
+        }
+        if allocation_bps.iter().map(|bps| bps as u128).sum::<u128>() != BASIS_POINTS {
+            panic!("Allocation must add up to 10000 basis points");
+        }
+        if !Self::is_usdc_pegged(env.clone()) {
+            panic_with_error!(&env, ContractError::UsdcDepegged);
+        }
+
+        enter_guard(&env);
 
+        // The longest funded tier takes the rounding remainder
         let current_time = env.ledger().timestamp();
-        let unlock_time = Self::calculate_unlock_time(current_time, &lock_period);
-        
-        // Check if user already has a deposit (for now, one deposit per user)
-        if env.storage().persistent().has(&(DEPOSIT.clone(), user.clone())) {
-            panic!("User already has an active deposit. Withdraw first to make a new deposit.");
-        }
+        let mut remaining = amount;
+        for (index, lock_period) in LADDER_TIERS.iter().enumerate() {
+            let bps = allocation_bps.get(index as u32).unwrap();
+            if bps == 0 {
+                continue;
+            }
+            let funds_later_tier = allocation_bps.iter().skip(index + 1).any(|later| later > 0);
+            let rung_amount = if funds_later_tier {
+                mul_div(amount, bps as u128, BASIS_POINTS)
+                    .unwrap_or_else(|| panic!("Rung amount overflow"))
+            } else {
+                remaining
+            };
+            remaining -= rung_amount;
+            if rung_amount == 0 {
+                continue;
+            }
 
-        // Transfer USDC from user to vault
-        let usdc_contract: Address = env.storage().instance().get(&USDC_CONTRACT).unwrap();
-        let usdc_client = TokenClient::new(&env, &usdc_contract);
-        
-        usdc_client.transfer(&user, &env.current_contract_address(), &(amount as i128));
+            let key = DataKey::LadderRung(user.clone(), lock_period.clone());
+            if env.storage().persistent().has(&key) {
+                panic!("User already has a ladder rung in this tier. Withdraw it first.");
+            }
+            Self::enforce_tier_cap(&env, lock_period, rung_amount);
 
-        // Update vault balance
-        let vault_balance: u128 = env.storage().instance().get(&VAULT_BALANCE).unwrap_or(0);
-        env.storage().instance().set(&VAULT_BALANCE, &(vault_balance + amount));
+            let vault_balance: u128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::VaultBalance)
+                .unwrap_or(0);
+            env.storage()
+                .instance()
+                .set(&DataKey::VaultBalance, &(vault_balance + rung_amount));
+            Self::add_tier_balance(&env, lock_period, rung_amount);
+            let ladder_balance = Self::get_ladder_balance(env.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::LadderBalance, &(ladder_balance + rung_amount));
 
-        // Create deposit info
-        let deposit_info = DepositInfo {
-            amount,
-            deposit_time: current_time,
-            unlock_time,
-            lock_period: lock_period.clone(),
-            vault_type: VaultType::USDC,
-        };
+            let unlock_time = Self::calculate_unlock_time(current_time, lock_period);
+            let rung = DepositInfo {
+                amount: rung_amount,
+                deposit_time: current_time,
+                unlock_time,
+                lock_period: lock_period.clone(),
+                vault_type: VaultType::USDC,
+            };
+            env.storage().persistent().set(&key, &rung);
+            extend_persistent_ttl(&env, &key);
 
-        // Store deposit info
-        env.storage()
-            .persistent()
-            .set(&(DEPOSIT.clone(), user.clone()), &deposit_info);
+            env.events().publish(
+                (symbol_short!("deposit"), user.clone()),
+                (
+                    rung_amount,
+                    lock_period.clone(),
+                    unlock_time,
+                    None::<DepositTag>,
+                ),
+            );
+        }
+        Self::track_depositor(&env, &user);
+        extend_instance_ttl(&env);
 
-        // Calculate yield rate and mint yield tokens
-        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period.clone());
-        let yield_token_contract: Address = env.storage().instance().get(&YIELD_TOKEN).unwrap();
-        
-        // Call yield token contract to mint tokens
-        env.invoke_contract(
-            &yield_token_contract,
-            &Symbol::new(&env, "mint_for_deposit"),
-            (
-                env.current_contract_address(),
-                user.clone(),
-                amount,
-                VaultType::USDC,
-                yield_rate,
-            ).into_val(&env),
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &user,
+            &env.current_contract_address(),
+            amount as i128,
         );
+        exit_guard(&env);
 
         log!(
             &env,
-            "User {} deposited {} USDC with {:?} lock period. Unlock time: {}",
+            "User {} laddered {} USDC across lock tiers: {:?}",
             user,
             amount,
-            lock_period,
-            unlock_time
+            allocation_bps
         );
     }
 
-    /// Withdraw USDC from the vault (only after lock period expires)
-    pub fn withdraw(env: Env, user: Address) -> u128 {
+    /// Approve or revoke an operator allowed to deposit the user's pre-approved USDC
+    pub fn set_deposit_operator(env: Env, user: Address, operator: Address, approved: bool) {
         user.require_auth();
 
-        let deposit_info: DepositInfo = env
-            .storage()
+        let key = DataKey::DepositOperator(user.clone(), operator.clone());
+        if approved {
+            env.storage().persistent().set(&key, &true);
+            extend_persistent_ttl(&env, &key);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        log!(
+            &env,
+            "Deposit operator {} for {} set to {}",
+            operator,
+            user,
+            approved
+        );
+    }
+
+    /// Check whether an operator may deposit on a user's behalf
+    pub fn is_deposit_operator(env: Env, user: Address, operator: Address) -> bool {
+        env.storage()
             .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
-            .unwrap_or_else(|| panic!("No deposit found for user"));
+            .has(&DataKey::DepositOperator(user, operator))
+    }
 
-        let current_time = env.ledger().timestamp();
-        
-        if current_time < deposit_info.unlock_time {
-            panic!(
-                "Withdrawal not allowed. Lock period expires at: {}",
-                deposit_info.unlock_time
-            );
+    /// Set up a recurring deposit that keepers execute from the user's USDC allowance
+    ///
+    /// The first execution is due immediately. The user must keep the vault approved
+    /// to spend `amount` per period.
+    pub fn create_standing_order(
+        env: Env,
+        user: Address,
+        amount: u128,
+        interval: u64,
+        lock_period: LockPeriod,
+    ) {
+        user.require_auth();
+
+        if amount == 0 {
+            panic!("Standing order amount must be greater than 0");
+        }
+        if interval < MIN_STANDING_ORDER_INTERVAL {
+            panic!("Standing order interval is too short");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::StandingOrder(user.clone()))
+        {
+            panic!("User already has a standing order. Cancel it first.");
         }
 
-        // Calculate final amount including yield
-        let yield_token_contract: Address = env.storage().instance().get(&YIELD_TOKEN).unwrap();
-        
-        // Compound interest first
-        env.invoke_contract(
-            &yield_token_contract,
-            &Symbol::new(&env, "compound_interest"),
-            (user.clone(),).into_val(&env),
-        );
+        let order = StandingOrder {
+            amount,
+            interval,
+            lock_period: lock_period.clone(),
+            next_execution: env.ledger().timestamp(),
+            executions: 0,
+            skipped: 0,
+        };
+        Self::store_standing_order(&env, &user, &order);
 
-        // Get final balance from yield token
-        let final_amount: i128 = env.invoke_contract(
-            &yield_token_contract,
-            &Symbol::new(&env, "balance"),
-            (user.clone(),).into_val(&env),
+        log!(
+            &env,
+            "Standing order for {}: {} USDC every {} seconds with {:?} lock period",
+            user,
+            amount,
+            interval,
+            lock_period
         );
+    }
 
-        let withdrawal_amount = final_amount as u128;
+    /// Execute a due standing order (permissionless keeper call)
+    ///
+    /// Returns false when the period is skipped because the user still holds a
+    /// position; the order then waits for the next period.
+    pub fn execute_standing_order(env: Env, keeper: Address, user: Address) -> bool {
+        keeper.require_auth();
 
-        // Burn yield tokens
-        env.invoke_contract(
-            &yield_token_contract,
-            &Symbol::new(&env, "burn_for_withdrawal"),
-            (
-                env.current_contract_address(),
-                user.clone(),
-                withdrawal_amount,
-            ).into_val(&env),
-        );
+        let mut order = Self::get_standing_order(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No standing order found for user"));
 
-        // Transfer USDC back to user
-        let usdc_contract: Address = env.storage().instance().get(&USDC_CONTRACT).unwrap();
-        let usdc_client = TokenClient::new(&env, &usdc_contract);
-        
-        usdc_client.transfer(
-            &env.current_contract_address(),
-            &user,
-            &(withdrawal_amount as i128),
-        );
+        let current_time = env.ledger().timestamp();
+        if current_time < order.next_execution {
+            panic!("Standing order is not due until: {}", order.next_execution);
+        }
 
-        // Update vault balance
-        let vault_balance: u128 = env.storage().instance().get(&VAULT_BALANCE).unwrap();
-        env.storage().instance().set(&VAULT_BALANCE, &(vault_balance - withdrawal_amount));
+        // At most one execution per interval, however late the keeper runs
+        order.next_execution = current_time + order.interval;
 
-        // Remove deposit info
-        env.storage()
+        if env
+            .storage()
             .persistent()
-            .remove(&(DEPOSIT.clone(), user.clone()));
+            .has(&DataKey::Deposit(user.clone()))
+        {
+            order.skipped += 1;
+            Self::store_standing_order(&env, &user, &order);
+
+            log!(
+                &env,
+                "Standing order for {} skipped by keeper {}: position still open. Next execution: {}",
+                user,
+                keeper,
+                order.next_execution
+            );
+            return false;
+        }
+
+        order.executions += 1;
+        Self::store_standing_order(&env, &user, &order);
+        Self::open_deposit(
+            &env,
+            &user,
+            order.amount,
+            order.lock_period.clone(),
+            Funding::Allowance,
+            None,
+            CompoundingFrequency::Daily,
+        );
 
         log!(
             &env,
-            "User {} withdrew {} USDC (including yield)",
+            "Standing order for {} executed by keeper {}: execution {}. Next execution: {}",
             user,
-            withdrawal_amount
+            keeper,
+            order.executions,
+            order.next_execution
         );
 
-        withdrawal_amount
+        true
     }
 
-    /// Get user's deposit information
-    pub fn get_deposit_info(env: Env, user: Address) -> Option<DepositInfo> {
-        env.storage()
-            .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
+    /// Skip the next due period of a standing order
+    pub fn skip_standing_order(env: Env, user: Address) {
+        user.require_auth();
+
+        let mut order = Self::get_standing_order(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No standing order found for user"));
+
+        order.next_execution += order.interval;
+        order.skipped += 1;
+        Self::store_standing_order(&env, &user, &order);
+
+        log!(
+            &env,
+            "Standing order for {} skipped by user. Next execution: {}",
+            user,
+            order.next_execution
+        );
     }
 
-    /// Get lock expiry time for a user
-    pub fn get_lock_expiry(env: Env, user: Address) -> u64 {
-        let deposit_info: DepositInfo = env
+    /// Cancel a standing order; open positions are unaffected
+    pub fn cancel_standing_order(env: Env, user: Address) {
+        user.require_auth();
+
+        if !env
             .storage()
             .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
-            .unwrap_or_else(|| panic!("No deposit found for user"));
-        
-        deposit_info.unlock_time
-    }
-
-    /// Calculate yield rate based on lock period
-    pub fn calculate_yield_rate(env: Env, lock_period: LockPeriod) -> u128 {
-        let base_rate = 500u128; // 5% base annual rate in basis points
-        
-        match lock_period {
-            LockPeriod::ThreeMonths => base_rate,                    // 5% APY
-            LockPeriod::SixMonths => (base_rate * 15) / 10,         // 7.5% APY (1.5x)
-            LockPeriod::TwelveMonths => base_rate * 2,              // 10% APY (2x)
+            .has(&DataKey::StandingOrder(user.clone()))
+        {
+            panic!("No standing order found for user");
         }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::StandingOrder(user.clone()));
+
+        log!(&env, "Standing order for {} cancelled", user);
     }
 
-    /// Get current vault USDC balance
-    pub fn get_vault_balance(env: Env) -> u128 {
-        env.storage().instance().get(&VAULT_BALANCE).unwrap_or(0)
+    /// Get a user's standing order
+    pub fn get_standing_order(env: Env, user: Address) -> Option<StandingOrder> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StandingOrder(user))
     }
 
-    /// Emergency withdraw with penalty (admin only, for emergencies)
-    pub fn emergency_withdraw(env: Env, admin: Address, user: Address) -> u128 {
-        admin.require_auth();
-        
-        let stored_admin: Address = env.storage().instance().get(&ADMIN).unwrap();
-        if admin != stored_admin {
-            panic!("Only admin can perform emergency withdrawal");
+    /// Fund a voucher that opens a deposit in the claimant's name, e.g. for onboarding programs
+    ///
+    /// The voucher can be limited to a recipient, to holders of a claim code given
+    /// as its SHA-256 hash, or both. A code alone is revealed when claimed, so a
+    /// recipient should be set wherever the claimant is known.
+    pub fn create_voucher(
+        env: Env,
+        funder: Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        recipient: Option<Address>,
+        code_hash: Option<BytesN<32>>,
+    ) -> u64 {
+        funder.require_auth();
+        ensure_not_paused(&env);
+        Self::ensure_not_settled(&env);
+
+        if amount == 0 {
+            panic!("Voucher amount must be greater than 0");
+        }
+        if recipient.is_none() && code_hash.is_none() {
+            panic!("A voucher needs a recipient or a claim code");
         }
 
-        let deposit_info: DepositInfo = env
+        let voucher_id: u64 = env
             .storage()
-            .persistent()
-            .get(&(DEPOSIT.clone(), user.clone()))
-            .unwrap_or_else(|| panic!("No deposit found for user"));
+            .instance()
+            .get(&DataKey::NextVoucherId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextVoucherId, &(voucher_id + 1));
+        let reserve = Self::get_voucher_reserve(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::VoucherReserve, &(reserve + amount));
 
-        // Apply 10% penalty for early withdrawal
-        let penalty_rate = 1000u128; // 10% in basis points
-        let penalty = (deposit_info.amount * penalty_rate) / 10000;
-        let withdrawal_amount = deposit_info.amount - penalty;
+        let voucher = DepositVoucher {
+            id: voucher_id,
+            funder: funder.clone(),
+            amount,
+            lock_period: lock_period.clone(),
+            recipient,
+            code_hash,
+            created_at: env.ledger().timestamp(),
+            claimed_by: None,
+            status: VoucherStatus::Open,
+        };
+        Self::store_voucher(&env, &voucher);
+        extend_instance_ttl(&env);
 
-        // Transfer USDC back to user (minus penalty)
-        let usdc_contract: Address = env.storage().instance().get(&USDC_CONTRACT).unwrap();
-        let usdc_client = TokenClient::new(&env, &usdc_contract);
-        
-        usdc_client.transfer(
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &funder,
             &env.current_contract_address(),
-            &user,
-            &(withdrawal_amount as i128),
+            amount as i128,
         );
 
-        // Update vault balance
-        let vault_balance: u128 = env.storage().instance().get(&VAULT_BALANCE).unwrap();
-        env.storage().instance().set(&VAULT_BALANCE, &(vault_balance - deposit_info.amount));
-
-        // Remove deposit info
-        env.storage()
-            .persistent()
-            .remove(&(DEPOSIT.clone(), user.clone()));
-
         log!(
             &env,
-            "Emergency withdrawal: User {} withdrew {} USDC with {} penalty",
-            user,
-            withdrawal_amount,
-            penalty
+            "Voucher {} funded by {}: {} USDC with {:?} lock period",
+            voucher_id,
+            funder,
+            amount,
+            lock_period
         );
 
-        withdrawal_amount
+        voucher_id
+    }
+
+    /// Claim a voucher, opening its deposit in the claimant's name
+    pub fn claim_voucher(env: Env, claimant: Address, voucher_id: u64, code: Option<Bytes>) {
+        claimant.require_auth();
+
+        let mut voucher = Self::get_voucher(env.clone(), voucher_id)
+            .unwrap_or_else(|| panic!("Voucher not found"));
+        if voucher.status != VoucherStatus::Open {
+            panic!("Voucher is not open");
+        }
+        if voucher
+            .recipient
+            .as_ref()
+            .is_some_and(|recipient| *recipient != claimant)
+        {
+            panic!("Voucher is reserved for another recipient");
+        }
+        if let Some(code_hash) = &voucher.code_hash {
+            let code = code.unwrap_or_else(|| panic!("Voucher requires a claim code"));
+            let hash: BytesN<32> = env.crypto().sha256(&code).into();
+            if hash != *code_hash {
+                panic!("Invalid claim code");
+            }
+        }
+
+        // Settle the voucher before its funds become the claimant's deposit
+        voucher.status = VoucherStatus::Claimed;
+        voucher.claimed_by = Some(claimant.clone());
+        Self::store_voucher(&env, &voucher);
+        let reserve = Self::get_voucher_reserve(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::VoucherReserve, &(reserve - voucher.amount));
+
+        Self::open_deposit(
+            &env,
+            &claimant,
+            voucher.amount,
+            voucher.lock_period.clone(),
+            Funding::Voucher,
+            None,
+            CompoundingFrequency::Daily,
+        );
+
+        log!(&env, "Voucher {} claimed by {}", voucher_id, claimant);
+    }
+
+    /// Cancel an unclaimed voucher and return its USDC to the funder
+    pub fn cancel_voucher(env: Env, funder: Address, voucher_id: u64) -> u128 {
+        funder.require_auth();
+
+        let mut voucher = Self::get_voucher(env.clone(), voucher_id)
+            .unwrap_or_else(|| panic!("Voucher not found"));
+        if voucher.funder != funder {
+            panic!("Only the funder can cancel a voucher");
+        }
+        if voucher.status != VoucherStatus::Open {
+            panic!("Voucher is not open");
+        }
+
+        voucher.status = VoucherStatus::Cancelled;
+        Self::store_voucher(&env, &voucher);
+        let reserve = Self::get_voucher_reserve(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::VoucherReserve, &(reserve - voucher.amount));
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &funder,
+            voucher.amount as i128,
+        );
+
+        log!(
+            &env,
+            "Voucher {} cancelled; {} USDC returned to {}",
+            voucher_id,
+            voucher.amount,
+            funder
+        );
+
+        voucher.amount
+    }
+
+    /// Get a deposit voucher by id
+    pub fn get_voucher(env: Env, voucher_id: u64) -> Option<DepositVoucher> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Voucher(voucher_id))
+    }
+
+    /// Get the USDC held for unclaimed vouchers
+    pub fn get_voucher_reserve(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::VoucherReserve)
+            .unwrap_or(0)
+    }
+
+    /// Withdraw USDC from the vault (only after lock period expires)
+    ///
+    /// Paid to the user's beneficiary when one is named. Under a withdrawal cap,
+    /// whatever exceeds this epoch's remaining capacity is queued for
+    /// `claim_queued_withdrawal`. Returns the USDC paid now.
+    pub fn withdraw(env: Env, user: Address) -> u128 {
+        user.require_auth();
+        let recipient = Self::payout_address(&env, &user);
+        Self::withdraw_to(env, user, recipient, Payout::Direct)
+    }
+
+    /// Withdraw a matured ladder rung with the yield its tier earned, less the yield fee
+    ///
+    /// Yield accrues at the tier's rate until the withdrawal. Payouts beyond the current
+    /// epoch's withdrawal capacity are queued like regular withdrawals.
+    pub fn withdraw_rung(env: Env, user: Address, lock_period: LockPeriod) -> u128 {
+        user.require_auth();
+        Self::ensure_not_settled(&env);
+
+        let key = DataKey::LadderRung(user.clone(), lock_period.clone());
+        let rung: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or_else(|| panic!("No ladder rung found in this tier"));
+
+        let current_time = deadline_time(&env);
+        if current_time < rung.unlock_time {
+            panic!(
+                "Withdrawal not allowed. Lock period expires at: {}",
+                rung.unlock_time
+            );
+        }
+
+        enter_guard(&env);
+
+        env.storage().persistent().remove(&key);
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap();
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultBalance, &(vault_balance - rung.amount));
+        Self::release_tier_balance(&env, &lock_period, rung.amount);
+        Self::release_ladder_balance(&env, rung.amount);
+        Self::flag_depeg_withdrawal(&env, &user, rung.amount);
+        extend_instance_ttl(&env);
+
+        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period.clone());
+        let credited = compound_daily(
+            rung.amount,
+            yield_rate,
+            current_time.saturating_sub(rung.deposit_time),
+        )
+        .unwrap_or_else(|| panic!("Rung yield overflow"));
+        let yield_fee = Self::yield_fee(&env, &rung, credited);
+        let owed = credited - yield_fee;
+        Self::record_yield_paid(&env, owed.saturating_sub(rung.amount));
+        let paid = Self::take_withdrawal_capacity(&env, owed);
+        let recipient = Self::payout_address(&env, &user);
+        Self::queue_withdrawal(&env, &recipient, owed - paid);
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        if paid + yield_fee > 0 {
+            Self::ensure_liquidity(&env, &usdc_contract, paid + yield_fee);
+        }
+        if paid > 0 {
+            transfer_or_abort(
+                &env,
+                &usdc_contract,
+                &env.current_contract_address(),
+                &recipient,
+                paid as i128,
+            );
+        }
+        Self::route_fee(&env, &usdc_contract, yield_fee, RevenueStream::YieldSkim);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} withdrew {:?} ladder rung: {} USDC paid to {}, {} queued, {} yield fee",
+            user,
+            lock_period,
+            paid,
+            recipient,
+            owed - paid,
+            yield_fee
+        );
+
+        paid
+    }
+
+    /// Get a user's ladder rung in a lock tier, if any
+    pub fn get_ladder_rung(
+        env: Env,
+        user: Address,
+        lock_period: LockPeriod,
+    ) -> Option<DepositInfo> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LadderRung(user, lock_period))
+    }
+
+    /// Get the recorded principal that was minted as yield tokens, i.e. outside ladder rungs
+    pub fn get_minted_liquidity(env: Env) -> u128 {
+        Self::get_vault_balance(env.clone()).saturating_sub(Self::get_ladder_balance(env))
+    }
+
+    /// Get the principal held in ladder rungs across all depositors
+    pub fn get_ladder_balance(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::LadderBalance)
+            .unwrap_or(0)
+    }
+
+    /// Name a recovery address that may claim the matured position after a long silence
+    ///
+    /// The recovery address can `claim_recovery` once `inactivity_period` has passed
+    /// since the later of the lock expiry and the depositor's last check-in.
+    pub fn set_recovery(env: Env, user: Address, recovery: Address, inactivity_period: u64) {
+        user.require_auth();
+
+        if !(MIN_RECOVERY_INACTIVITY..=MAX_RECOVERY_INACTIVITY).contains(&inactivity_period) {
+            panic!("Inactivity period must be between one and ten years");
+        }
+        if recovery == user {
+            panic!("Recovery address must differ from the depositor");
+        }
+
+        let plan = RecoveryPlan {
+            recovery: recovery.clone(),
+            inactivity_period,
+            last_active: env.ledger().timestamp(),
+        };
+        Self::store_recovery(&env, &user, &plan);
+
+        log!(
+            &env,
+            "User {} named recovery address {} after {}s of inactivity",
+            user,
+            recovery,
+            inactivity_period
+        );
+    }
+
+    /// Remove the depositor's recovery address
+    pub fn remove_recovery(env: Env, user: Address) {
+        user.require_auth();
+
+        if Self::get_recovery(env.clone(), user.clone()).is_none() {
+            panic!("No recovery address set");
+        }
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Recovery(user.clone()));
+
+        log!(&env, "User {} removed their recovery address", user);
+    }
+
+    /// Prove the depositor still holds their key, restarting the inactivity period
+    pub fn check_in(env: Env, user: Address) {
+        user.require_auth();
+
+        let mut plan = Self::get_recovery(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No recovery address set"));
+        plan.last_active = env.ledger().timestamp();
+        Self::store_recovery(&env, &user, &plan);
+    }
+
+    /// Get the depositor's recovery plan, if any
+    pub fn get_recovery(env: Env, user: Address) -> Option<RecoveryPlan> {
+        env.storage().persistent().get(&DataKey::Recovery(user))
+    }
+
+    /// Get when the recovery address may claim the depositor's position, if both exist
+    pub fn get_recovery_time(env: Env, user: Address) -> Option<u64> {
+        let plan = Self::get_recovery(env.clone(), user.clone())?;
+        let deposit_info: DepositInfo = env.storage().persistent().get(&DataKey::Deposit(user))?;
+        Some(deposit_info.unlock_time.max(plan.last_active) + plan.inactivity_period)
+    }
+
+    /// Withdraw an inactive depositor's matured position to their recovery address
+    ///
+    /// Pays out exactly as `withdraw` would, with any queued remainder claimable by
+    /// the recovery address. A named beneficiary is paid instead of the recovery
+    /// address. The plan is spent once claimed.
+    pub fn claim_recovery(env: Env, recovery: Address, user: Address) -> u128 {
+        recovery.require_auth();
+
+        let plan = Self::get_recovery(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No recovery address set"));
+        if plan.recovery != recovery {
+            panic!("Only the recovery address can claim the position");
+        }
+        let recovery_time = Self::get_recovery_time(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+        if deadline_time(&env) < recovery_time {
+            panic!("Recovery not allowed before: {}", recovery_time);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Recovery(user.clone()));
+        let recipient = match Self::get_beneficiary(env.clone(), user.clone()) {
+            Some(_) => Self::payout_address(&env, &user),
+            None => recovery.clone(),
+        };
+        let paid = Self::withdraw_to(env.clone(), user.clone(), recipient, Payout::Direct);
+
+        log!(
+            &env,
+            "Recovery address {} claimed the position of {}",
+            recovery,
+            user
+        );
+
+        paid
+    }
+
+    /// Request that payouts go to a new beneficiary once `BENEFICIARY_CHANGE_DELAY` has passed
+    ///
+    /// Until then the current beneficiary keeps receiving payouts and can cancel the
+    /// request with `cancel_beneficiary_change`. Naming the user themselves lifts the
+    /// designation once the delay has passed.
+    pub fn change_beneficiary(env: Env, user: Address, beneficiary: Address) {
+        user.require_auth();
+
+        let mut designation = Self::get_beneficiary(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No beneficiary set"));
+        designation.address = Self::payout_address(&env, &user);
+        designation.pending = Some(beneficiary.clone());
+        designation.effective_at = deadline_time(&env) + BENEFICIARY_CHANGE_DELAY;
+        Self::store_beneficiary(&env, &user, &designation);
+
+        log!(
+            &env,
+            "User {} asked to pay {} instead of {} from {}",
+            user,
+            beneficiary,
+            designation.address,
+            designation.effective_at
+        );
+    }
+
+    /// Cancel a beneficiary change that has not taken effect (the user or current beneficiary)
+    pub fn cancel_beneficiary_change(env: Env, caller: Address, user: Address) {
+        caller.require_auth();
+
+        let mut designation = Self::get_beneficiary(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No beneficiary set"));
+        if designation.pending.is_none() || deadline_time(&env) >= designation.effective_at {
+            panic!("No beneficiary change pending");
+        }
+        if caller != user && caller != designation.address {
+            panic!("Only the user or beneficiary can cancel the change");
+        }
+        designation.pending = None;
+        Self::store_beneficiary(&env, &user, &designation);
+
+        log!(
+            &env,
+            "Beneficiary change of {} cancelled by {}",
+            user,
+            caller
+        );
+    }
+
+    /// Get the user's beneficiary designation, including any change requested
+    pub fn get_beneficiary(env: Env, user: Address) -> Option<Beneficiary> {
+        env.storage().persistent().get(&DataKey::Beneficiary(user))
+    }
+
+    /// Get the address the user's positions are paid out to now
+    pub fn get_payout_address(env: Env, user: Address) -> Address {
+        Self::payout_address(&env, &user)
+    }
+
+    /// Set or clear the policy for sweeping small matured positions left unclaimed (governance or admin)
+    pub fn set_dormancy_policy(env: Env, caller: Address, policy: Option<DormancyPolicy>) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        match policy {
+            Some(policy) => {
+                if policy.max_amount == 0 {
+                    panic!("Dormancy threshold must be greater than 0");
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::DormancyPolicy, &policy);
+
+                log!(
+                    &env,
+                    "Positions up to {} USDC go dormant {}s after unlock, consolidated {}s later",
+                    policy.max_amount,
+                    policy.idle_period,
+                    policy.grace_period
+                );
+            }
+            None => {
+                env.storage().instance().remove(&DataKey::DormancyPolicy);
+
+                log!(&env, "Dormancy policy cleared");
+            }
+        }
+    }
+
+    /// Get the governance-set dormancy policy, if any
+    pub fn get_dormancy_policy(env: Env) -> Option<DormancyPolicy> {
+        env.storage().instance().get(&DataKey::DormancyPolicy)
+    }
+
+    /// Get when a position was flagged dormant, if it has been
+    pub fn get_dormant_since(env: Env, user: Address) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::Dormant(user))
+    }
+
+    /// Flag a small position left unclaimed past the idle period as dormant (permissionless)
+    ///
+    /// The depositor can still withdraw as usual during the grace period that follows.
+    pub fn flag_dormant(env: Env, keeper: Address, user: Address) {
+        keeper.require_auth();
+
+        let policy = Self::get_dormancy_policy(env.clone())
+            .unwrap_or_else(|| panic!("No dormancy policy set"));
+        if Self::get_dormant_since(env.clone(), user.clone()).is_some() {
+            panic!("Position is already dormant");
+        }
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+        if deposit_info.amount > policy.max_amount {
+            panic!("Position is above the dormancy threshold");
+        }
+        let idle_until = deposit_info.unlock_time + policy.idle_period;
+        let current_time = deadline_time(&env);
+        if current_time < idle_until {
+            panic!("Position cannot go dormant before: {}", idle_until);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Dormant(user.clone()), &current_time);
+        extend_persistent_ttl(&env, &DataKey::Dormant(user.clone()));
+
+        env.events()
+            .publish((symbol_short!("dormant"), user.clone()), current_time);
+
+        log!(&env, "Position of {} flagged dormant by {}", user, keeper);
+    }
+
+    /// Close a dormant position once its grace period is over (permissionless)
+    ///
+    /// The position's USDC, with yield counted only until the grace period ended, is
+    /// recorded as a single queued withdrawal the depositor collects with
+    /// `claim_queued_withdrawal`. The position's other entries are deleted, so it no
+    /// longer needs rent. Returns the USDC queued.
+    pub fn consolidate_dormant(env: Env, keeper: Address, user: Address) -> u128 {
+        keeper.require_auth();
+
+        let policy = Self::get_dormancy_policy(env.clone())
+            .unwrap_or_else(|| panic!("No dormancy policy set"));
+        let flagged_at = Self::get_dormant_since(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("Position is not dormant"));
+        let accrual_end = flagged_at + policy.grace_period;
+        if deadline_time(&env) < accrual_end {
+            panic!(
+                "Dormant position cannot be consolidated before: {}",
+                accrual_end
+            );
+        }
+
+        let recipient = Self::payout_address(&env, &user);
+        let queued_before = Self::get_queued_withdrawal(env.clone(), recipient.clone());
+        Self::withdraw_to(
+            env.clone(),
+            user.clone(),
+            recipient.clone(),
+            Payout::Consolidated(accrual_end),
+        );
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Recovery(user.clone()));
+        let consolidated = Self::get_queued_withdrawal(env.clone(), recipient) - queued_before;
+
+        log!(
+            &env,
+            "Dormant position of {} consolidated by {}: {} USDC queued",
+            user,
+            keeper,
+            consolidated
+        );
+
+        consolidated
+    }
+
+    /// Pay out a queued withdrawal, up to this epoch's remaining capacity
+    ///
+    /// Queued withdrawals are paid in the order they were queued, so this first pays
+    /// any claims ahead of the user's, up to `WITHDRAWAL_QUEUE_BATCH` of them. Returns
+    /// the USDC paid to the user, which is zero if capacity ran out on earlier claims.
+    /// In global settlement the cap no longer applies and the user's whole queue is paid.
+    pub fn claim_queued_withdrawal(env: Env, user: Address) -> u128 {
+        user.require_auth();
+
+        let queued = Self::get_queued_withdrawal(env.clone(), user.clone());
+        if queued == 0 {
+            panic!("No queued withdrawal found for user");
+        }
+
+        enter_guard(&env);
+        let paid = if Self::get_settlement(env.clone()).is_some() {
+            Self::pay_queued(&env, &user, queued);
+            extend_instance_ttl(&env);
+            queued
+        } else {
+            let (served, paid) = Self::serve_withdrawal_queue(&env, Some(&user));
+            if served == 0 {
+                panic!("No withdrawal capacity left this epoch");
+            }
+            paid
+        };
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} claimed {} USDC of queued withdrawals, {} still queued",
+            user,
+            paid,
+            queued - paid
+        );
+
+        paid
+    }
+
+    /// Pay queued withdrawals in the order they were queued, up to this epoch's capacity
+    ///
+    /// Anyone can call this to move the queue along. Pays at most
+    /// `WITHDRAWAL_QUEUE_BATCH` claims and returns the USDC paid.
+    pub fn process_withdrawal_queue(env: Env) -> u128 {
+        enter_guard(&env);
+        let (served, _) = Self::serve_withdrawal_queue(&env, None);
+        exit_guard(&env);
+
+        log!(&env, "Withdrawal queue paid {} USDC", served);
+
+        served
+    }
+
+    /// Get the next queued withdrawal to be paid, if any
+    pub fn get_queue_head(env: Env) -> Option<QueuedClaim> {
+        let head: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::QueueHead)
+            .unwrap_or(0);
+        env.storage().persistent().get(&DataKey::QueuedClaim(head))
+    }
+
+    /// Pay out yield streamed to the user since their last claim, whatever their lock
+    ///
+    /// The user must have switched to streaming with the yield token's `set_yield_stream`.
+    /// Like withdrawal yield, the payout comes from vault reserves.
+    pub fn claim_stream(env: Env, user: Address) -> u128 {
+        user.require_auth();
+        Self::ensure_not_settled(&env);
+
+        enter_guard(&env);
+
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        let claimed: u128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "take_streamed_yield",
+            (env.current_contract_address(), user.clone()).into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        if claimed > 0 {
+            let usdc_contract: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::UsdcContract)
+                .unwrap();
+            Self::ensure_liquidity(&env, &usdc_contract, claimed);
+            transfer_or_abort(
+                &env,
+                &usdc_contract,
+                &env.current_contract_address(),
+                &Self::payout_address(&env, &user),
+                claimed as i128,
+            );
+        }
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} claimed {} USDC of streamed yield",
+            user,
+            claimed
+        );
+
+        claimed
+    }
+
+    /// Pay out the tips a keeper has earned from the yield token's `compound_for`
+    ///
+    /// Tips are burned from the compounded holders' yield, so like streamed yield
+    /// they are paid in USDC from vault reserves.
+    pub fn claim_keeper_tips(env: Env, keeper: Address) -> u128 {
+        keeper.require_auth();
+        Self::ensure_not_settled(&env);
+
+        enter_guard(&env);
+
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        let claimed: u128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "take_keeper_tips",
+            (env.current_contract_address(), keeper.clone()).into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        if claimed > 0 {
+            let usdc_contract: Address = env
+                .storage()
+                .instance()
+                .get(&DataKey::UsdcContract)
+                .unwrap();
+            Self::ensure_liquidity(&env, &usdc_contract, claimed);
+            transfer_or_abort(
+                &env,
+                &usdc_contract,
+                &env.current_contract_address(),
+                &keeper,
+                claimed as i128,
+            );
+        }
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Keeper {} claimed {} USDC of compound tips",
+            keeper,
+            claimed
+        );
+
+        claimed
+    }
+
+    /// Enter global settlement: locks are waived and deposits close (governance only)
+    ///
+    /// Governance calls this once the committee approves a shutdown. Snapshots the
+    /// USDC held against recorded principal so each depositor redeems the same
+    /// fraction regardless of redemption order.
+    pub fn enter_settlement(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::verify_governance(&env, &caller);
+        Self::ensure_not_settled(&env);
+
+        let snapshot = Self::snapshot_settlement(&env, env.ledger().timestamp());
+
+        log!(
+            &env,
+            "Global settlement entered by {}: {} USDC held against {} recorded",
+            caller,
+            snapshot.held_value,
+            snapshot.recorded_liquidity
+        );
+    }
+
+    /// Re-take the settlement snapshot from the USDC held now (permissionless)
+    ///
+    /// Retries strategies that failed to return their USDC and picks up facility draws
+    /// repaid since, sharing them among the depositors yet to redeem.
+    pub fn refresh_settlement(env: Env) -> SettlementSnapshot {
+        let settlement = Self::get_settlement(env.clone())
+            .unwrap_or_else(|| panic!("Vault is not in global settlement"));
+
+        let snapshot = Self::snapshot_settlement(&env, settlement.settled_at);
+
+        log!(
+            &env,
+            "Settlement snapshot refreshed: {} USDC held against {} recorded",
+            snapshot.held_value,
+            snapshot.recorded_liquidity
+        );
+
+        snapshot
+    }
+
+    /// Redeem a pro-rata share of the USDC held at settlement, ignoring the lock
+    ///
+    /// Redeems the regular deposit and every ladder rung together. The holder's yield
+    /// tokens are burned; yield stopped accruing at settlement. A position pledged to
+    /// a loan can only redeem once the lien is released.
+    pub fn redeem_settlement(env: Env, user: Address) -> u128 {
+        user.require_auth();
+
+        let snapshot = Self::get_settlement(env.clone())
+            .unwrap_or_else(|| panic!("Vault is not in global settlement"));
+        let deposit_info: Option<DepositInfo> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()));
+        let rungs = Self::ladder_rungs(&env, &user);
+        if deposit_info.is_none() && rungs.is_empty() {
+            panic!("No deposit found for user");
+        }
+        Self::ensure_unencumbered(&env, &user);
+
+        let rung_principal: u128 = rungs.iter().map(|rung| rung.amount).sum();
+        let principal = deposit_info.as_ref().map_or(0, |info| info.amount) + rung_principal;
+        let (claim, dust) =
+            mul_div_with_dust(principal, snapshot.held_value, snapshot.recorded_liquidity)
+                .unwrap_or_else(|| panic!("Settlement claim overflow"));
+
+        enter_guard(&env);
+
+        // Effects: close the positions before any outbound call
+        let mut exported_receipt = None;
+        if let Some(deposit_info) = &deposit_info {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Deposit(user.clone()));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::DepositTag(user.clone()));
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Dormant(user.clone()));
+            Self::release_tier_balance(&env, &deposit_info.lock_period, deposit_info.amount);
+            exported_receipt = Self::close_receipt(&env, &user);
+        }
+        for rung in rungs.iter() {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::LadderRung(user.clone(), rung.lock_period.clone()));
+            Self::release_tier_balance(&env, &rung.lock_period, rung.amount);
+        }
+        Self::release_ladder_balance(&env, rung_principal);
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap();
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultBalance, &(vault_balance - principal));
+        Self::book_rounding_dust(&env, dust);
+        extend_instance_ttl(&env);
+
+        // Interactions: burn the frozen yield-token balance, then pay the claim
+        if deposit_info.is_some() {
+            let yield_token_contract: Address = resolve_peer(
+                &env,
+                &DataKey::Registry,
+                REGISTRY_YIELD_TOKEN,
+                &DataKey::YieldToken,
+            );
+            let token_balance: i128 = invoke_peer(
+                &env,
+                &yield_token_contract,
+                "balance",
+                (user.clone(),).into_val(&env),
+                ContractError::YieldTokenCallFailed,
+            );
+            if token_balance > 0 {
+                invoke_peer::<()>(
+                    &env,
+                    &yield_token_contract,
+                    "burn_for_withdrawal",
+                    (
+                        env.current_contract_address(),
+                        user.clone(),
+                        token_balance as u128,
+                    )
+                        .into_val(&env),
+                    ContractError::YieldTokenCallFailed,
+                );
+            }
+        }
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &Self::payout_address(&env, &user),
+            claim as i128,
+        );
+
+        if deposit_info.is_some() {
+            Self::remove_reward_stake(&env, &user);
+            Self::settle_receipt_token(&env, exported_receipt);
+        }
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} redeemed {} USDC in global settlement for {} principal, {} of it in ladder rungs",
+            user,
+            claim,
+            principal,
+            rung_principal
+        );
+
+        claim
+    }
+
+    /// Get the global settlement snapshot, if settlement has started
+    pub fn get_settlement(env: Env) -> Option<SettlementSnapshot> {
+        env.storage().instance().get(&DataKey::Settlement)
+    }
+
+    /// Get user's deposit information
+    pub fn get_deposit_info(env: Env, user: Address) -> Option<DepositInfo> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+    }
+
+    /// Get the integrator and customer a position was tagged with at deposit
+    pub fn get_deposit_tag(env: Env, user: Address) -> Option<DepositTag> {
+        env.storage().persistent().get(&DataKey::DepositTag(user))
+    }
+
+    /// Get lock expiry time for a user
+    pub fn get_lock_expiry(env: Env, user: Address) -> u64 {
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        deposit_info.unlock_time
+    }
+
+    /// Get a deposit receipt by its ID, including closed ones
+    pub fn get_receipt(env: Env, receipt_id: u64) -> Option<DepositReceipt> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Receipt(receipt_id))
+    }
+
+    /// Get the receipt for a user's open deposit
+    pub fn get_active_receipt(env: Env, user: Address) -> Option<DepositReceipt> {
+        let receipt_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ActiveReceipt(user));
+        receipt_id.and_then(|receipt_id| Self::get_receipt(env.clone(), receipt_id))
+    }
+
+    /// Check a term sheet hash against a user's position, open or closed
+    ///
+    /// `position_id` is the position's deposit receipt ID. The hash covers the amount,
+    /// yield rate, lock period and dates, and the fee policy version at deposit, and
+    /// matches only if the stored terms still hash to it.
+    pub fn verify_certificate(env: Env, user: Address, position_id: u64, hash: BytesN<32>) -> bool {
+        match Self::get_receipt(env.clone(), position_id) {
+            Some(receipt) => {
+                receipt.terms.depositor == user
+                    && receipt.terms_hash == hash
+                    && hash_deposit_terms(&env, &receipt.terms) == hash
+            }
+            None => false,
+        }
+    }
+
+    /// Pledge a user's open position as loan collateral (loan manager only)
+    ///
+    /// `position_id` is the position's deposit receipt ID. Withdrawal is blocked
+    /// until the loan manager calls `release_lien`.
+    pub fn encumber_position(
+        env: Env,
+        lien_holder: Address,
+        user: Address,
+        position_id: u64,
+        loan_id: BytesN<32>,
+    ) {
+        lien_holder.require_auth();
+        user.require_auth();
+        Self::verify_loan_manager(&env, &lien_holder);
+
+        let receipt = Self::get_active_receipt(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No open position found for user"));
+        if receipt.id != position_id {
+            panic!("Position is not the user's open position");
+        }
+
+        let encumbrance_key = DataKey::Encumbrance(user.clone(), position_id);
+        if env.storage().persistent().has(&encumbrance_key) {
+            panic!("Position is already encumbered");
+        }
+
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap();
+        let encumbrance = Encumbrance {
+            loan_id: loan_id.clone(),
+            lien_holder,
+            amount: deposit_info.amount,
+            encumbered_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&encumbrance_key, &encumbrance);
+        extend_persistent_ttl(&env, &encumbrance_key);
+
+        log!(
+            &env,
+            "Position {} of user {} encumbered for loan {}",
+            position_id,
+            user,
+            loan_id
+        );
+    }
+
+    /// Release the lien on a position once its loan no longer needs it (lien holder only)
+    pub fn release_lien(env: Env, lien_holder: Address, user: Address, position_id: u64) {
+        lien_holder.require_auth();
+
+        let encumbrance = Self::get_encumbrance(env.clone(), user.clone(), position_id)
+            .unwrap_or_else(|| panic!("Position is not encumbered"));
+        if encumbrance.lien_holder != lien_holder {
+            panic!("Only the lien holder can release the lien");
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Encumbrance(user.clone(), position_id));
+
+        log!(
+            &env,
+            "Lien on position {} of user {} released",
+            position_id,
+            user
+        );
+    }
+
+    /// Get the lien on a position, if it backs a loan
+    pub fn get_encumbrance(env: Env, user: Address, position_id: u64) -> Option<Encumbrance> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Encumbrance(user, position_id))
+    }
+
+    /// Export the open deposit receipt as a transferable token in the receipts contract
+    ///
+    /// The token is an auditable record of the position; withdrawal stays with the depositor.
+    pub fn export_receipt(env: Env, user: Address) -> u64 {
+        user.require_auth();
+
+        let mut receipt = Self::get_active_receipt(env.clone(), user.clone())
+            .unwrap_or_else(|| panic!("No open receipt found for user"));
+        if receipt.nft_id.is_some() {
+            panic!("Receipt already exported");
+        }
+
+        let receipts_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_RECEIPTS,
+            &DataKey::Receipts,
+        );
+        let nft_id: u64 = invoke_peer(
+            &env,
+            &receipts_contract,
+            "mint",
+            (
+                env.current_contract_address(),
+                user.clone(),
+                receipt.clone(),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+
+        receipt.nft_id = Some(nft_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt.id), &receipt);
+        extend_persistent_ttl(&env, &DataKey::Receipt(receipt.id));
+
+        log!(
+            &env,
+            "Receipt {} exported as token {} to {}",
+            receipt.id,
+            nft_id,
+            user
+        );
+
+        nft_id
+    }
+
+    /// Export normalized records of open positions for regulatory reporting (reporter, or admin if none is set)
+    ///
+    /// Scans up to `limit` depositor slots, capped at `POSITION_EXPORT_PAGE_SIZE`, from
+    /// slot `cursor`; continue from `next_cursor` until it is None. Depositors whose
+    /// position has closed are skipped, so a page can hold fewer records than slots.
+    /// Records share their shape with the gold vault's so reports can merge vaults.
+    pub fn export_positions(env: Env, caller: Address, cursor: u64, limit: u32) -> PositionPage {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Reporter, &caller, &admin);
+
+        let depositor_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DepositorCount)
+            .unwrap_or(0);
+        let start = cursor.min(depositor_count);
+        let end = (start + limit.min(POSITION_EXPORT_PAGE_SIZE) as u64).min(depositor_count);
+
+        let mut records = Vec::new(&env);
+        for slot in start..end {
+            let user: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Depositor(slot))
+                .unwrap();
+            if let Some(record) = Self::position_record(&env, &user) {
+                records.push_back(record);
+            }
+            for rung in Self::ladder_rungs(&env, &user).iter() {
+                records.push_back(Self::rung_record(&env, &user, rung));
+            }
+        }
+
+        PositionPage {
+            records,
+            next_cursor: if end < depositor_count {
+                Some(end)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Calculate yield rate based on lock period
+    pub fn calculate_yield_rate(env: Env, lock_period: LockPeriod) -> u128 {
+        let base_rate = 500u128; // 5% base annual rate in basis points
+
+        match lock_period {
+            LockPeriod::ThreeMonths => base_rate,           // 5% APY
+            LockPeriod::SixMonths => (base_rate * 15) / 10, // 7.5% APY (1.5x)
+            LockPeriod::TwelveMonths => base_rate * 2,      // 10% APY (2x)
+        }
+    }
+
+    /// Preview the tokens, yield to unlock and early exit fee of a deposit made now
+    ///
+    /// Fails where `deposit` would for the same amount and lock, so wallets can show
+    /// the preview from a simulated call.
+    pub fn simulate_deposit(env: Env, amount: u128, lock_period: LockPeriod) -> DepositPreview {
+        Self::ensure_not_settled(&env);
+        if amount == 0 {
+            panic!("Deposit amount must be greater than 0");
+        }
+        Self::enforce_tier_cap(&env, &lock_period, amount);
+        if !Self::is_usdc_pegged(env.clone()) {
+            panic_with_error!(&env, ContractError::UsdcDepegged);
+        }
+
+        let current_time = env.ledger().timestamp();
+        let deposit_info = DepositInfo {
+            amount,
+            deposit_time: current_time,
+            unlock_time: Self::calculate_unlock_time(current_time, &lock_period),
+            lock_period: lock_period.clone(),
+            vault_type: VaultType::USDC,
+        };
+        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period);
+        let grown = compound_daily(amount, yield_rate, deposit_info.unlock_time - current_time)
+            .unwrap_or_else(|| panic!("Projected yield overflow"));
+        let early_exit_fee = mul_div_up(
+            amount,
+            Self::exit_fee_bps(&env, &deposit_info),
+            BASIS_POINTS,
+        )
+        .unwrap_or_else(|| panic!("Exit fee overflow"));
+
+        DepositPreview {
+            amount,
+            yield_rate,
+            tokens_minted: amount,
+            projected_yield: grown - amount,
+            early_exit_fee,
+            unlock_time: deposit_info.unlock_time,
+        }
+    }
+
+    /// Get current vault USDC balance
+    pub fn get_vault_balance(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap_or(0)
+    }
+
+    /// Get USDC redeemable per yield token, scaled by `SHARE_PRICE_PRECISION`
+    ///
+    /// Yield tokens redeem 1:1 for USDC, so this only drops below par when the
+    /// vault's USDC holdings no longer cover its recorded deposits. Multiply by the
+    /// yield token's `accrued_balance` to value a position at current accrual.
+    pub fn get_share_price(env: Env) -> u128 {
+        let recorded_liquidity = Self::get_vault_balance(env.clone());
+        if recorded_liquidity == 0 {
+            return SHARE_PRICE_PRECISION;
+        }
+
+        let held_value = Self::depositor_held_value(&env);
+
+        if held_value >= recorded_liquidity {
+            SHARE_PRICE_PRECISION
+        } else {
+            mul_div(held_value, SHARE_PRICE_PRECISION, recorded_liquidity)
+                .unwrap_or_else(|| panic!("Share price overflow"))
+        }
+    }
+
+    /// Get USDC deposited in a lock tier
+    pub fn get_tier_balance(env: Env, lock_period: LockPeriod) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TierBalance(lock_period))
+            .unwrap_or(0)
+    }
+
+    /// Get the TVL cap for a lock tier, if one is set
+    pub fn get_tier_cap(env: Env, lock_period: LockPeriod) -> Option<LockTierCap> {
+        env.storage().instance().get(&DataKey::TierCap(lock_period))
+    }
+
+    /// Get the USDC token the vault holds
+    pub fn get_usdc_contract(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap()
+    }
+
+    /// Get the oracle USDC/USD price at 6 decimals, if an oracle is configured and quotes a fresh one
+    pub fn get_usdc_price(env: Env) -> Option<u128> {
+        let oracle = try_resolve_peer(&env, &DataKey::Registry, REGISTRY_ORACLE, &DataKey::Oracle)?;
+        read_oracle_price(&env, &oracle, USDC_PRICE_FEED)
+    }
+
+    /// Check whether the USDC price is within the depeg band
+    ///
+    /// Always true without a band; with one, a missing price counts as depegged.
+    pub fn is_usdc_pegged(env: Env) -> bool {
+        let band: Option<u32> = env.storage().instance().get(&DataKey::DepegBand);
+        let Some(band) = band else {
+            return true;
+        };
+
+        match Self::get_usdc_price(env) {
+            Some(price) => {
+                price.abs_diff(USDC_PEG_PRICE) * BASIS_POINTS <= USDC_PEG_PRICE * band as u128
+            }
+            None => false,
+        }
+    }
+
+    /// Get the allowed USDC price deviation from $1 in basis points, if set
+    pub fn get_depeg_band(env: Env) -> Option<u32> {
+        env.storage().instance().get(&DataKey::DepegBand)
+    }
+
+    /// Get principal withdrawn while USDC was outside the depeg band
+    pub fn get_depeg_withdrawn(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::DepegWithdrawn)
+            .unwrap_or(0)
+    }
+
+    /// Check vault accounting invariants (read-only, for monitoring and pre-upgrade checks)
+    pub fn health_check(env: Env) -> VaultHealthReport {
+        let recorded_liquidity = Self::get_vault_balance(env.clone());
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let usdc_client = TokenClient::new(&env, &usdc_contract);
+        let token_balance = usdc_client.balance(&env.current_contract_address());
+
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+        let yield_token_supply: i128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "total_supply",
+            Vec::new(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        // Penalties awaiting the treasury, queued withdrawals and unclaimed vouchers are not depositor liquidity
+        let accounting_non_negative = token_balance >= 0 && yield_token_supply >= 0;
+        let held_value = (token_balance.max(0) as u128
+            + Self::get_total_allocated(env.clone())
+            + Self::get_facility_lent(env.clone()))
+        .saturating_sub(Self::get_unrouted_fees(env.clone()))
+        .saturating_sub(Self::get_queued_total(env.clone()))
+        .saturating_sub(Self::get_voucher_reserve(env.clone()));
+        let yield_token_supply = yield_token_supply.max(0) as u128;
+
+        // Idle, allocated and lent USDC must back the recorded balance. The yield token is
+        // shared, so its supply must cover what both vaults minted, not this vault alone
+        let gold_vault_minted = lookup_registry(&env, &DataKey::Registry, REGISTRY_GOLD_VAULT)
+            .map_or(0, |gold_vault| {
+                invoke_peer(
+                    &env,
+                    &gold_vault,
+                    "get_minted_liquidity",
+                    Vec::new(&env),
+                    ContractError::PeerCallFailed,
+                )
+            });
+        let liquidity_covered = held_value >= recorded_liquidity;
+        let supply_consistent =
+            yield_token_supply >= Self::get_minted_liquidity(env.clone()) + gold_vault_minted;
+
+        VaultHealthReport {
+            held_value,
+            recorded_liquidity,
+            yield_token_supply,
+            liquidity_covered,
+            supply_consistent,
+            accounting_non_negative,
+            price_fresh: true,
+            healthy: liquidity_covered && supply_consistent && accounting_non_negative,
+            checked_at: env.ledger().timestamp(),
+        }
+    }
+
+    /// Get the stored accounting figures the state digest is computed over
+    ///
+    /// Recorded principal (`tvl`), strategy allocations, queued withdrawals, fees
+    /// routed and held for the treasury, and the unclaimed voucher reserve.
+    pub fn accounting_state(env: Env) -> Map<Symbol, u128> {
+        let mut figures = Map::new(&env);
+        figures.set(symbol_short!("tvl"), Self::get_vault_balance(env.clone()));
+        figures.set(
+            symbol_short!("allocated"),
+            Self::get_total_allocated(env.clone()),
+        );
+        figures.set(symbol_short!("lent"), Self::get_facility_lent(env.clone()));
+        figures.set(symbol_short!("queued"), Self::get_queued_total(env.clone()));
+        figures.set(
+            symbol_short!("fees_out"),
+            Self::get_fees_routed(env.clone()),
+        );
+        figures.set(
+            symbol_short!("fees_held"),
+            Self::get_unrouted_fees(env.clone()),
+        );
+        figures.set(symbol_short!("vouchers"), Self::get_voucher_reserve(env));
+        figures
+    }
+
+    /// Get the SHA-256 digest of `accounting_state()`, recorded by governance each epoch
+    pub fn state_digest(env: Env) -> BytesN<32> {
+        digest_accounting(&env, &Self::accounting_state(env.clone()))
+    }
+
+    /// Get the governance-set per-epoch withdrawal cap, if any
+    pub fn get_withdrawal_cap(env: Env) -> Option<WithdrawalCap> {
+        env.storage().instance().get(&DataKey::WithdrawalCap)
+    }
+
+    /// Get the USDC that can still be withdrawn this epoch, None when uncapped
+    pub fn get_withdrawal_capacity(env: Env) -> Option<u128> {
+        Self::current_withdrawal_epoch(&env).map(|epoch| epoch.capacity - epoch.withdrawn)
+    }
+
+    /// Get the USDC owed to a user from withdrawals beyond the cap
+    pub fn get_queued_withdrawal(env: Env, user: Address) -> u128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::QueuedWithdrawal(user))
+            .unwrap_or(0)
+    }
+
+    /// Get the USDC owed across all queued withdrawals
+    pub fn get_queued_total(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::QueuedTotal)
+            .unwrap_or(0)
+    }
+
+    /// Get the governance-set early exit fee schedule, if any
+    pub fn get_exit_fee_schedule(env: Env) -> Option<ExitFeeSchedule> {
+        env.storage().instance().get(&DataKey::ExitFeeSchedule)
+    }
+
+    /// Preview the penalty an emergency exit of the user's position would pay now
+    pub fn get_exit_fee(env: Env, user: Address) -> u128 {
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        mul_div_up(
+            deposit_info.amount,
+            Self::exit_fee_bps(&env, &deposit_info),
+            BASIS_POINTS,
+        )
+        .unwrap_or_else(|| panic!("Exit fee overflow"))
+    }
+
+    /// Get the yield fee schedule and its rebates, if any
+    pub fn get_yield_fee_schedule(env: Env) -> Option<YieldFeeSchedule> {
+        env.storage().instance().get(&DataKey::YieldFeeSchedule)
+    }
+
+    /// Get the share of the yield fee waived for the user's position, in basis points
+    pub fn get_fee_rebate(env: Env, user: Address) -> u32 {
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        Self::get_yield_fee_schedule(env)
+            .map_or(0, |schedule| Self::fee_rebate_bps(&schedule, &deposit_info))
+    }
+
+    /// Get penalties and fees sent to the treasury and insurance fund so far
+    pub fn get_fees_routed(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeesRouted)
+            .unwrap_or(0)
+    }
+
+    /// Get the fixed-rate yield paid on exits and the strategy yield harvested since launch
+    ///
+    /// Governance reads it each epoch to credit realized yield to this vault.
+    pub fn get_yield_paid(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::YieldPaid)
+            .unwrap_or(0)
+    }
+
+    /// Get penalties and fees held for the treasury because none was configured
+    pub fn get_unrouted_fees(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::UnroutedFees)
+            .unwrap_or(0)
+    }
+
+    /// Get the fractions of a unit kept by rounding payouts down, in `DUST_PRECISION` parts of one unit
+    pub fn get_rounding_dust(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RoundingDust)
+            .unwrap_or(0)
+    }
+
+    /// Send fees held for the treasury once one is configured (permissionless)
+    pub fn sweep_fees(env: Env) -> u128 {
+        let treasury = try_resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_TREASURY,
+            &DataKey::Treasury,
+        )
+        .unwrap_or_else(|| panic!("Treasury not configured"));
+        let unrouted = Self::get_unrouted_fees(env.clone());
+        if unrouted == 0 {
+            return 0;
+        }
+
+        enter_guard(&env);
+
+        env.storage().instance().remove(&DataKey::UnroutedFees);
+        let fees_routed = Self::get_fees_routed(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::FeesRouted, &(fees_routed + unrouted));
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &treasury,
+            unrouted as i128,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Swept {} USDC of held fees to treasury {}",
+            unrouted,
+            treasury
+        );
+
+        unrouted
+    }
+
+    /// Emergency withdraw with penalty (admin only, for emergencies)
+    ///
+    /// The penalty goes to the treasury, or is held for it outside depositor TVL.
+    pub fn emergency_withdraw(env: Env, admin: Address, user: Address) -> u128 {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can perform emergency withdrawal");
+        }
+        Self::ensure_not_settled(&env);
+        Self::ensure_unencumbered(&env, &user);
+
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        // Apply the early exit penalty, decaying over the lock when a schedule is set
+        let penalty_rate = Self::exit_fee_bps(&env, &deposit_info);
+        let penalty = mul_div_up(deposit_info.amount, penalty_rate, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Exit fee overflow"));
+        let withdrawal_amount = deposit_info.amount - penalty;
+
+        enter_guard(&env);
+
+        // Update vault balance and remove deposit info before paying out
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap();
+        env.storage().instance().set(
+            &DataKey::VaultBalance,
+            &(vault_balance - deposit_info.amount),
+        );
+        Self::release_tier_balance(&env, &deposit_info.lock_period, deposit_info.amount);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Deposit(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DepositTag(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Dormant(user.clone()));
+        let exported_receipt = Self::close_receipt(&env, &user);
+        Self::flag_depeg_withdrawal(&env, &user, deposit_info.amount);
+
+        // Transfer USDC back to user, or their beneficiary (minus penalty)
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        Self::ensure_liquidity(&env, &usdc_contract, deposit_info.amount);
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &Self::payout_address(&env, &user),
+            withdrawal_amount as i128,
+        );
+
+        Self::route_fee(
+            &env,
+            &usdc_contract,
+            penalty,
+            RevenueStream::EarlyExitPenalty,
+        );
+
+        Self::remove_reward_stake(&env, &user);
+        Self::settle_receipt_token(&env, exported_receipt);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Emergency withdrawal: User {} withdrew {} USDC with {} penalty",
+            user,
+            withdrawal_amount,
+            penalty
+        );
+
+        withdrawal_amount
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can assign roles");
+        }
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause new deposits and strategy allocations (pauser, or admin if no pauser is set)
+    ///
+    /// Withdrawals and other exits stay open so depositors can always leave.
+    pub fn pause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
+    }
+
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
+
+        set_paused(&env, false);
+
+        log!(&env, "Unpaused by {}", caller);
+    }
+
+    /// Check whether new deposits and strategy allocations are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
+
+    /// Transfer admin rights to a new address, e.g. the multisig contract (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can transfer admin rights");
+        }
+
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
+    }
+
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
+
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
+
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
+
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
+
+    /// Set the address registry used to resolve peer contracts (admin only)
+    pub fn set_registry(env: Env, admin: Address, registry: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the registry");
+        }
+
+        env.storage().instance().set(&DataKey::Registry, &registry);
+
+        log!(&env, "Address registry set to: {}", registry);
+    }
+
+    /// Set the liquidity-mining rewards contract notified of deposits (admin only)
+    pub fn set_rewards(env: Env, admin: Address, rewards_contract: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the rewards contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Rewards, &rewards_contract);
+
+        log!(&env, "Rewards contract set to: {}", rewards_contract);
+    }
+
+    /// Set the receipts contract that exported deposit receipts are minted in (admin only)
+    pub fn set_receipts(env: Env, admin: Address, receipts_contract: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the receipts contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Receipts, &receipts_contract);
+
+        log!(&env, "Receipts contract set to: {}", receipts_contract);
+    }
+
+    /// Set the price oracle consulted for the USDC peg (admin only)
+    pub fn set_oracle(env: Env, admin: Address, oracle: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the oracle");
+        }
+
+        env.storage().instance().set(&DataKey::Oracle, &oracle);
+
+        log!(&env, "Oracle set to: {}", oracle);
+    }
+
+    /// Set the treasury that receives penalties and fees when the registry has none (admin only)
+    pub fn set_treasury(env: Env, admin: Address, treasury: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the treasury");
+        }
+
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+
+        log!(&env, "Treasury set to: {}", treasury);
+    }
+
+    /// Set the insurance fund that receives its share of fees when the registry has none (admin only)
+    pub fn set_insurance_fund(env: Env, admin: Address, insurance_fund: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the insurance fund");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::InsuranceFund, &insurance_fund);
+
+        log!(&env, "Insurance fund set to: {}", insurance_fund);
+    }
+
+    /// Set the governance contract allowed to manage lock tier caps and the depeg band (admin only)
+    pub fn set_governance(env: Env, admin: Address, governance: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the governance contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Governance, &governance);
+
+        log!(&env, "Governance contract set to: {}", governance);
+    }
+
+    /// Cap the share of vault TVL a lock tier may hold (governance or admin)
+    pub fn set_tier_cap(env: Env, caller: Address, lock_period: LockPeriod, cap: LockTierCap) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if cap.max_share_bps as u128 > BASIS_POINTS {
+            panic_with_error!(&env, ContractError::InvalidTierCap);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::TierCap(lock_period.clone()), &cap);
+
+        log!(
+            &env,
+            "Tier cap for {:?} set to {} bps above ${} TVL",
+            lock_period,
+            cap.max_share_bps,
+            cap.min_tvl
+        );
+    }
+
+    /// Offer or withdraw a compounding frequency for a lock tier (governance or admin)
+    ///
+    /// `bonus_bps` is added to the tier rate for deposits choosing that frequency,
+    /// up to `MAX_COMPOUNDING_BONUS_BPS`. Daily compounding is always offered at the
+    /// tier rate; None stops new deposits choosing the frequency.
+    pub fn set_compounding_option(
+        env: Env,
+        caller: Address,
+        lock_period: LockPeriod,
+        frequency: CompoundingFrequency,
+        bonus_bps: Option<u32>,
+    ) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if frequency == CompoundingFrequency::Daily {
+            panic!("Daily compounding is always offered at the tier rate");
+        }
+        let key = DataKey::CompoundingBonus(lock_period.clone(), frequency.clone());
+        match bonus_bps {
+            Some(bonus_bps) => {
+                if bonus_bps > MAX_COMPOUNDING_BONUS_BPS {
+                    panic_with_error!(&env, ContractError::ParameterOutOfBounds);
+                }
+                env.storage().instance().set(&key, &bonus_bps);
+
+                log!(
+                    &env,
+                    "{:?} compounding offered for {:?} at {} bps over the tier rate",
+                    frequency,
+                    lock_period,
+                    bonus_bps
+                );
+            }
+            None => {
+                env.storage().instance().remove(&key);
+
+                log!(
+                    &env,
+                    "{:?} compounding withdrawn for {:?}",
+                    frequency,
+                    lock_period
+                );
+            }
+        }
+    }
+
+    /// Get the rate adjustment for a compounding frequency on a lock tier, None if not offered
+    pub fn get_compounding_option(
+        env: Env,
+        lock_period: LockPeriod,
+        frequency: CompoundingFrequency,
+    ) -> Option<u32> {
+        if frequency == CompoundingFrequency::Daily {
+            return Some(0);
+        }
+        env.storage()
+            .instance()
+            .get(&DataKey::CompoundingBonus(lock_period, frequency))
+    }
+
+    /// Start a launch phase in which only allowlisted addresses may deposit (admin only)
+    pub fn start_launch_phase(env: Env, admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can start a launch phase");
+        }
+
+        env.storage().instance().set(&DataKey::LaunchPhase, &true);
+
+        log!(
+            &env,
+            "Launch phase started: deposits limited to the allowlist"
+        );
+    }
+
+    /// End the launch phase and open deposits to everyone (governance or admin)
+    pub fn end_launch_phase(env: Env, caller: Address) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if !Self::is_launch_phase(env.clone()) {
+            panic!("No launch phase in progress");
+        }
+        env.storage().instance().remove(&DataKey::LaunchPhase);
+
+        log!(
+            &env,
+            "Launch phase ended by {}: deposits open to all",
+            caller
+        );
+    }
+
+    /// Check whether deposits are currently limited to the allowlist
+    pub fn is_launch_phase(env: Env) -> bool {
+        env.storage().instance().has(&DataKey::LaunchPhase)
+    }
+
+    /// Set the allowlist consulted during the launch phase when the registry has none (admin only)
+    pub fn set_allowlist(env: Env, admin: Address, allowlist: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the allowlist");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Allowlist, &allowlist);
+
+        log!(&env, "Allowlist set to: {}", allowlist);
+    }
+
+    /// Cross-check lock expiries against the ledger sequence, or stop doing so (governance or admin)
+    ///
+    /// Enabling anchors the sequence clock at the current ledger. A lock then only
+    /// expires once both the timestamp and the ledgers closed since agree it has.
+    pub fn set_ledger_clock(env: Env, caller: Address, enabled: bool) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        anchor_ledger_clock(&env, enabled);
+
+        log!(
+            &env,
+            "Ledger-sequence lock checks set to {} by {}",
+            enabled,
+            caller
+        );
+    }
+
+    /// Get the ledger clock lock expiries are cross-checked against, if any
+    pub fn get_ledger_clock(env: Env) -> Option<LedgerClock> {
+        ledger_clock(&env)
+    }
+
+    /// Turn a revenue stream on or off and set its insurance fund share (governance or fee setter, or admin if none is set)
+    ///
+    /// The vault holds the only copy of each switch; governance sets it when a
+    /// `propose_fee_switch` proposal executes. A disabled yield skim or exit penalty is
+    /// waived rather than charged. Without a resolvable insurance fund its share goes to
+    /// the treasury with the rest.
+    pub fn set_fee_switch(env: Env, caller: Address, stream: RevenueStream, switch: FeeSwitch) {
+        caller.require_auth();
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if governance.as_ref() != Some(&caller) {
+            Self::verify_fee_setter(&env, &caller);
+        }
+
+        if switch.insurance_bps as u128 > BASIS_POINTS {
+            panic!("Insurance share must be at most 100%");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::FeeSwitch(stream.clone()), &switch);
+        Self::bump_fee_policy_version(&env);
+
+        log!(
+            &env,
+            "Fee switch for {} set to {} with {} bps to insurance",
+            stream,
+            switch.enabled,
+            switch.insurance_bps
+        );
+    }
+
+    /// Get a revenue stream's fee switch, enabled and all to the treasury unless set
+    pub fn get_fee_switch(env: Env, stream: RevenueStream) -> FeeSwitch {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeeSwitch(stream))
+            .unwrap_or(FeeSwitch {
+                enabled: true,
+                insurance_bps: 0,
+            })
+    }
+
+    /// Get the version of the fee policy, bumped whenever a fee schedule or switch changes
+    ///
+    /// New positions record it in their terms so a certificate names the fees it was opened under.
+    pub fn get_fee_policy_version(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeePolicyVersion)
+            .unwrap_or(0)
+    }
+
+    /// Set or clear the decaying early exit fee schedule (fee setter, or governance or admin if none is set)
+    ///
+    /// Without a schedule emergency exits pay the flat 10% penalty.
+    pub fn set_exit_fee_schedule(env: Env, caller: Address, schedule: Option<ExitFeeSchedule>) {
+        caller.require_auth();
+        Self::verify_fee_setter(&env, &caller);
+
+        match schedule {
+            Some(schedule) => {
+                if schedule.start_bps as u128 > BASIS_POINTS
+                    || schedule.end_bps > schedule.start_bps
+                {
+                    panic!("Exit fee must decay from at most 100%");
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::ExitFeeSchedule, &schedule);
+
+                log!(
+                    &env,
+                    "Exit fee schedule set from {} bps to {} bps over the lock",
+                    schedule.start_bps,
+                    schedule.end_bps
+                );
+            }
+            None => {
+                env.storage().instance().remove(&DataKey::ExitFeeSchedule);
+
+                log!(&env, "Exit fee schedule cleared");
+            }
+        }
+        Self::bump_fee_policy_version(&env);
+    }
+
+    /// Set or clear the protocol fee on withdrawn yield and its rebates (fee setter, or governance or admin if none is set)
+    ///
+    /// The fee is at most `PROTOCOL_FEE_BASIS_POINTS` of the yield a position earned.
+    /// A position gets the rebate of the highest size tier its principal reaches, or
+    /// the long-lock rebate for a 12-month lock if that is larger.
+    pub fn set_yield_fee_schedule(env: Env, caller: Address, schedule: Option<YieldFeeSchedule>) {
+        caller.require_auth();
+        Self::verify_fee_setter(&env, &caller);
+
+        match schedule {
+            Some(schedule) => {
+                if schedule.fee_bps as u128 > PROTOCOL_FEE_BASIS_POINTS {
+                    panic_with_error!(&env, ContractError::ParameterOutOfBounds);
+                }
+                if schedule.long_lock_rebate_bps as u128 > BASIS_POINTS {
+                    panic!("Rebates must be at most 100% of the fee");
+                }
+                let mut previous_min: Option<u128> = None;
+                for tier in schedule.size_rebates.iter() {
+                    if tier.rebate_bps as u128 > BASIS_POINTS {
+                        panic!("Rebates must be at most 100% of the fee");
+                    }
+                    if previous_min.is_some_and(|previous_min| tier.min_amount <= previous_min) {
+                        panic!("Rebate thresholds must be strictly increasing");
+                    }
+                    previous_min = Some(tier.min_amount);
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::YieldFeeSchedule, &schedule);
+
+                log!(
+                    &env,
+                    "Yield fee set to {} bps with {} size rebate tiers",
+                    schedule.fee_bps,
+                    schedule.size_rebates.len()
+                );
+            }
+            None => {
+                env.storage().instance().remove(&DataKey::YieldFeeSchedule);
+
+                log!(&env, "Yield fee schedule cleared");
+            }
+        }
+        Self::bump_fee_policy_version(&env);
+    }
+
+    /// Cap the USDC paid out by withdrawals each epoch, or remove the cap (governance or admin)
+    pub fn set_withdrawal_cap(env: Env, caller: Address, cap: Option<WithdrawalCap>) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        // A changed cap recomputes this epoch's capacity but keeps what was already withdrawn
+        match cap {
+            Some(cap) => {
+                if let WithdrawalCap::TvlShare(share_bps) = cap {
+                    if share_bps == 0 || share_bps as u128 > BASIS_POINTS {
+                        panic!("Withdrawal cap must be between 1 and 10000 bps of TVL");
+                    }
+                }
+                env.storage().instance().set(&DataKey::WithdrawalCap, &cap);
+
+                let stored: Option<WithdrawalEpoch> =
+                    env.storage().instance().get(&DataKey::WithdrawalEpoch);
+                env.storage().instance().remove(&DataKey::WithdrawalEpoch);
+                let mut epoch = Self::current_withdrawal_epoch(&env).unwrap();
+                if let Some(stored) = stored.filter(|stored| stored.epoch == epoch.epoch) {
+                    epoch.withdrawn = stored.withdrawn;
+                    epoch.capacity = epoch.capacity.max(stored.withdrawn);
+                }
+                env.storage()
+                    .instance()
+                    .set(&DataKey::WithdrawalEpoch, &epoch);
+
+                log!(&env, "Withdrawal cap set to {:?} per epoch", cap);
+            }
+            None => {
+                env.storage().instance().remove(&DataKey::WithdrawalCap);
+
+                log!(&env, "Withdrawal cap cleared");
+            }
+        }
+    }
+
+    /// Set the allowed USDC price deviation from $1 before deposits pause (governance or admin)
+    pub fn set_depeg_band(env: Env, caller: Address, band_bps: u32) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        if band_bps as u128 > BASIS_POINTS {
+            panic!("Depeg band cannot exceed 100%");
+        }
+
+        env.storage().instance().set(&DataKey::DepegBand, &band_bps);
+
+        log!(&env, "USDC depeg band set to {} bps", band_bps);
+    }
+
+    /// Register a strategy for idle USDC with its share cap (admin only)
+    ///
+    /// Strategies implement `deposit`, `withdraw` and `harvest` and pay back to the vault.
+    pub fn register_strategy(env: Env, admin: Address, strategy: Address, max_share_bps: u32) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can register strategies");
+        }
+        if Self::get_strategy(env.clone(), strategy.clone()).is_some() {
+            panic!("Strategy is already registered");
+        }
+        if max_share_bps > MAX_STRATEGY_SHARE_BPS {
+            panic!(
+                "Strategy share cannot exceed {} bps",
+                MAX_STRATEGY_SHARE_BPS
+            );
+        }
+
+        let slot = StrategySlot {
+            strategy: strategy.clone(),
+            max_share_bps,
+            allocated: 0,
+            harvested: 0,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(strategy.clone()), &slot);
+        let mut strategies = Self::get_strategies(env.clone());
+        strategies.push_back(strategy.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategies, &strategies);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Strategy {} registered capped at {} bps",
+            strategy,
+            max_share_bps
+        );
+    }
+
+    /// Change a registered strategy's share cap (governance or admin)
+    ///
+    /// Lowering the cap below the current allocation only blocks further allocation.
+    pub fn set_strategy_cap(env: Env, caller: Address, strategy: Address, max_share_bps: u32) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        let mut slot = Self::get_strategy(env.clone(), strategy.clone())
+            .unwrap_or_else(|| panic!("Strategy is not registered"));
+        if max_share_bps > MAX_STRATEGY_SHARE_BPS {
+            panic!(
+                "Strategy share cannot exceed {} bps",
+                MAX_STRATEGY_SHARE_BPS
+            );
+        }
+
+        slot.max_share_bps = max_share_bps;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(strategy.clone()), &slot);
+
+        log!(
+            &env,
+            "Strategy {} capped at {} bps",
+            strategy,
+            max_share_bps
+        );
+    }
+
+    /// Get a registered strategy's slot
+    pub fn get_strategy(env: Env, strategy: Address) -> Option<StrategySlot> {
+        env.storage().instance().get(&DataKey::Strategy(strategy))
+    }
+
+    /// Get every registered strategy, in the order payouts recall from them
+    pub fn get_strategies(env: Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Strategies)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    /// Get the USDC allocated across all strategies
+    pub fn get_total_allocated(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::TotalAllocated)
+            .unwrap_or(0)
+    }
+
+    /// Lend idle USDC to a registered strategy, within its cap (governance or admin)
+    ///
+    /// Each strategy is capped at its share of depositor USDC, and all strategies
+    /// together at `MAX_STRATEGY_SHARE_BPS`, so withdrawals are mostly paid from idle funds.
+    pub fn allocate(env: Env, caller: Address, strategy: Address, amount: u128) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+
+        let mut slot = Self::get_strategy(env.clone(), strategy.clone())
+            .unwrap_or_else(|| panic!("Strategy is not registered"));
+        if amount == 0 {
+            panic!("Allocation must be greater than 0");
+        }
+
+        // allocated / managed <= cap, cross-multiplied
+        let managed = Self::depositor_held_value(&env);
+        let total_allocated = Self::get_total_allocated(env.clone());
+        if amount > managed.saturating_sub(total_allocated + Self::get_facility_lent(env.clone())) {
+            panic!("Not enough idle USDC to allocate");
+        }
+        let allocated = slot.allocated + amount;
+        if allocated * BASIS_POINTS > managed * slot.max_share_bps as u128 {
+            panic!("Allocation exceeds the strategy cap");
+        }
+        if (total_allocated + amount) * BASIS_POINTS > managed * MAX_STRATEGY_SHARE_BPS as u128 {
+            panic!("Allocation exceeds the total strategy cap");
+        }
+
+        enter_guard(&env);
+
+        slot.allocated = allocated;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(strategy.clone()), &slot);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalAllocated, &(total_allocated + amount));
+        extend_instance_ttl(&env);
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        deposit_to_strategy(&env, &strategy, &usdc_contract, amount);
+        exit_guard(&env);
+
+        log!(&env, "Allocated {} USDC to strategy {}", amount, strategy);
+    }
+
+    /// Bring USDC back from a strategy (governance or admin)
+    ///
+    /// Returns the USDC actually received, which is also what the allocation drops by.
+    pub fn deallocate(env: Env, caller: Address, strategy: Address, amount: u128) -> u128 {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        if Self::get_strategy(env.clone(), strategy.clone()).is_none() {
+            panic!("Strategy is not registered");
+        }
+
+        enter_guard(&env);
+        let returned = Self::recall_from_strategy(&env, &strategy, amount)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::StrategyCallFailed));
+        extend_instance_ttl(&env);
+        exit_guard(&env);
+
+        returned
+    }
+
+    /// Collect a strategy's earned yield and distribute it to holders via the yield index (permissionless)
+    ///
+    /// The harvested USDC stays in the vault to pay the yield out. Returns the USDC received.
+    pub fn harvest(env: Env, strategy: Address) -> u128 {
+        Self::ensure_not_settled(&env);
+        let mut slot = Self::get_strategy(env.clone(), strategy.clone())
+            .unwrap_or_else(|| panic!("Strategy is not registered"));
+
+        enter_guard(&env);
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let harvested = harvest_from_strategy(&env, &strategy, &usdc_contract)
+            .unwrap_or_else(|| panic_with_error!(&env, ContractError::StrategyCallFailed));
+        slot.harvested += harvested;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(strategy.clone()), &slot);
+        Self::record_yield_paid(&env, harvested);
+        extend_instance_ttl(&env);
+
+        if harvested > 0 {
+            let yield_token_contract: Address = resolve_peer(
+                &env,
+                &DataKey::Registry,
+                REGISTRY_YIELD_TOKEN,
+                &DataKey::YieldToken,
+            );
+            invoke_peer::<()>(
+                &env,
+                &yield_token_contract,
+                "distribute_strategy_yield",
+                (env.current_contract_address(), harvested).into_val(&env),
+                ContractError::YieldTokenCallFailed,
+            );
+        }
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Harvested {} USDC from strategy {}",
+            harvested,
+            strategy
+        );
+
+        harvested
+    }
+
+    /// Grant another vault a short-term USDC borrowing facility, or withdraw it (governance or admin)
+    ///
+    /// Lets the gold vault cover withdrawal spikes with USDC through its `draw_liquidity`.
+    /// Withdrawing a facility blocks new draws but leaves an open draw to be repaid.
+    pub fn set_liquidity_facility(
+        env: Env,
+        caller: Address,
+        borrower: Address,
+        facility: Option<LiquidityFacility>,
+    ) {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        match facility {
+            Some(facility) => {
+                validate_liquidity_facility(&facility);
+                env.storage()
+                    .instance()
+                    .set(&DataKey::Facility(borrower.clone()), &facility);
+
+                log!(
+                    &env,
+                    "Liquidity facility for {}: {} USDC cap at {} bps for up to {}s",
+                    borrower,
+                    facility.cap,
+                    facility.rate_bps,
+                    facility.max_term
+                );
+            }
+            None => {
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::Facility(borrower.clone()));
+
+                log!(&env, "Liquidity facility for {} withdrawn", borrower);
+            }
+        }
+        extend_instance_ttl(&env);
+    }
+
+    /// Borrow USDC under a granted facility, repayable by `term` seconds from now (borrowing vault)
+    ///
+    /// One draw may be open at a time. It is limited by the facility cap, the
+    /// borrower's recorded holdings (`holdings`, its `get_vault_balance`) at the
+    /// facility LTV and the vault's idle USDC.
+    pub fn draw_facility(
+        env: Env,
+        borrower: Address,
+        amount: u128,
+        term: u64,
+        holdings: u128,
+    ) -> FacilityDraw {
+        borrower.require_auth();
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+
+        let facility = Self::get_liquidity_facility(env.clone(), borrower.clone())
+            .unwrap_or_else(|| panic!("No liquidity facility for borrower"));
+        if Self::get_facility_draw(env.clone(), borrower.clone()).is_some() {
+            panic!("Repay the open draw first");
+        }
+        if amount == 0 || amount > facility.cap {
+            panic!("Draw must be between 1 and the facility cap");
+        }
+        if term == 0 || term > facility.max_term {
+            panic!("Draw term exceeds the facility maximum");
+        }
+
+        // The borrower's recorded holdings back the draw. The borrowing vault reports them
+        // itself, as the lender cannot call back into a vault that is calling it
+        if amount * BASIS_POINTS > holdings * facility.max_ltv_bps as u128 {
+            panic!("Draw exceeds the facility LTV against borrower holdings");
+        }
+        let lent = Self::get_facility_lent(env.clone());
+        let idle = Self::depositor_held_value(&env)
+            .saturating_sub(Self::get_total_allocated(env.clone()) + lent);
+        if amount > idle {
+            panic!("Not enough idle USDC to lend");
+        }
+
+        enter_guard(&env);
+
+        let now = env.ledger().timestamp();
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let draw = FacilityDraw {
+            asset: usdc_contract.clone(),
+            principal: amount,
+            rate_bps: facility.rate_bps,
+            drawn_at: now,
+            maturity: now + term,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::FacilityDraw(borrower.clone()), &draw);
+        env.storage()
+            .instance()
+            .set(&DataKey::FacilityLent, &(lent + amount));
+        extend_instance_ttl(&env);
+
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &borrower,
+            amount as i128,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Vault {} drew {} USDC until {}",
+            borrower,
+            amount,
+            draw.maturity
+        );
+
+        draw
+    }
+
+    /// Repay a vault's open draw with its interest, paid by `payer` (permissionless)
+    ///
+    /// Interest keeps accruing past maturity. It is distributed to holders through the
+    /// yield index. Returns the USDC repaid.
+    pub fn repay_facility(env: Env, payer: Address, borrower: Address) -> u128 {
+        payer.require_auth();
+
+        enter_guard(&env);
+        let (repaid, interest) = Self::settle_facility_draw(&env, &payer, &borrower);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Vault {} repaid {} USDC including {} interest",
+            borrower,
+            repaid,
+            interest
+        );
+
+        repaid
+    }
+
+    /// Repay an overdue draw in the borrower's place and take its holdings as collateral (permissionless)
+    ///
+    /// The liquidator pays the principal and interest. The borrowing vault then surrenders
+    /// holdings worth that much plus `FACILITY_LIQUIDATION_BONUS_BPS` to the liquidator, or
+    /// the whole call fails. Returns the USDC repaid.
+    pub fn liquidate_facility(env: Env, liquidator: Address, borrower: Address) -> u128 {
+        liquidator.require_auth();
+        if !Self::is_facility_overdue(env.clone(), borrower.clone()) {
+            panic!("Only an overdue draw can be liquidated");
+        }
+
+        enter_guard(&env);
+        let (repaid, _) = Self::settle_facility_draw(&env, &liquidator, &borrower);
+        let collateral_value = mul_div(
+            repaid,
+            BASIS_POINTS + FACILITY_LIQUIDATION_BONUS_BPS,
+            BASIS_POINTS,
+        )
+        .unwrap_or_else(|| panic!("Collateral value overflow"));
+        invoke_peer::<u128>(
+            &env,
+            &borrower,
+            "surrender_facility_collateral",
+            (
+                env.current_contract_address(),
+                liquidator.clone(),
+                collateral_value,
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Liquidator {} repaid {} USDC for vault {} and took collateral worth ${}",
+            liquidator,
+            repaid,
+            borrower,
+            collateral_value
+        );
+
+        repaid
+    }
+
+    /// Get the facility granted to a borrowing vault
+    pub fn get_liquidity_facility(env: Env, borrower: Address) -> Option<LiquidityFacility> {
+        env.storage().instance().get(&DataKey::Facility(borrower))
+    }
+
+    /// Get a borrowing vault's open draw
+    pub fn get_facility_draw(env: Env, borrower: Address) -> Option<FacilityDraw> {
+        env.storage()
+            .instance()
+            .get(&DataKey::FacilityDraw(borrower))
+    }
+
+    /// Get the interest accrued so far on a borrowing vault's open draw, rounded up
+    pub fn get_facility_interest(env: Env, borrower: Address) -> u128 {
+        Self::get_facility_draw(env.clone(), borrower)
+            .map_or(0, |draw| facility_interest(&env, &draw))
+    }
+
+    /// Check whether a borrowing vault's open draw is past maturity
+    pub fn is_facility_overdue(env: Env, borrower: Address) -> bool {
+        Self::get_facility_draw(env.clone(), borrower)
+            .map_or(false, |draw| env.ledger().timestamp() > draw.maturity)
+    }
+
+    /// Get the USDC lent out across all open facility draws
+    pub fn get_facility_lent(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::FacilityLent)
+            .unwrap_or(0)
+    }
+
+    /// Borrow gold from the gold vault's facility to cover a USDC withdrawal spike (governance or admin)
+    ///
+    /// The gold is paid out to depositors through `withdraw_in_liquidity` and bought back
+    /// with `swap_for_repayment` before `repay_liquidity`.
+    pub fn draw_liquidity(
+        env: Env,
+        caller: Address,
+        lender: Address,
+        gold_asset: Address,
+        amount: u128,
+        term: u64,
+    ) -> FacilityDraw {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+        Self::ensure_not_settled(&env);
+        ensure_not_paused(&env);
+
+        if let Some(current) = Self::get_liquidity_lender(env.clone()) {
+            if drawn_liquidity(&env, &current).is_some() {
+                panic!("Repay the open liquidity draw first");
+            }
+        }
+
+        let draw: FacilityDraw = invoke_peer(
+            &env,
+            &lender,
+            "draw_facility",
+            (
+                env.current_contract_address(),
+                gold_asset,
+                amount,
+                term,
+                Self::get_vault_balance(env.clone()),
+            )
+                .into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidityLender, &lender);
+        env.storage()
+            .instance()
+            .set(&DataKey::LiquidityAsset, &draw.asset);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Drew {} of gold token {} from vault {} until {}",
+            amount,
+            draw.asset,
+            lender,
+            draw.maturity
+        );
+
+        draw
+    }
+
+    /// Repay the open liquidity draw from the gold the vault holds (permissionless)
+    ///
+    /// Returns the gold repaid, principal and interest.
+    pub fn repay_liquidity(env: Env) -> u128 {
+        let lender = Self::get_liquidity_lender(env.clone())
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+
+        enter_guard(&env);
+        let repaid = repay_drawn_liquidity(&env, &lender);
+        exit_guard(&env);
+
+        log!(&env, "Repaid {} of drawn gold to vault {}", repaid, lender);
+
+        repaid
+    }
+
+    /// Buy back the drawn gold with idle USDC through the trade router (governance or admin)
+    ///
+    /// Aborts with `SlippageExceeded` if less than `min_amount_out` arrives. Returns the gold bought.
+    pub fn swap_for_repayment(
+        env: Env,
+        caller: Address,
+        amount_in: u128,
+        min_amount_out: u128,
+    ) -> u128 {
+        caller.require_auth();
+        Self::verify_governance_or_admin(&env, &caller);
+
+        let liquidity_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiquidityAsset)
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+        let idle = Self::depositor_held_value(&env).saturating_sub(
+            Self::get_total_allocated(env.clone()) + Self::get_facility_lent(env.clone()),
+        );
+        if amount_in == 0 || amount_in > idle {
+            panic!("Swap must be between 1 and the vault's idle USDC");
+        }
+
+        enter_guard(&env);
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let router = lookup_registry(&env, &DataKey::Registry, REGISTRY_TRADE_ROUTER)
+            .unwrap_or_else(|| panic!("Trade router not configured"));
+        let bought = swap_via_router(
+            &env,
+            &router,
+            &usdc_contract,
+            &liquidity_asset,
+            amount_in,
+            min_amount_out,
+        );
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "Swapped {} USDC for {} of gold token {}",
+            amount_in,
+            bought,
+            liquidity_asset
+        );
+
+        bought
+    }
+
+    /// Withdraw a matured position in drawn gold instead of USDC, at the lender's oracle price
+    ///
+    /// Pays the same value as `withdraw`, less the yield fee, and is not limited by the
+    /// withdrawal cap. Aborts with `SlippageExceeded` below `min_gold_out`. Returns the gold paid.
+    pub fn withdraw_in_liquidity(env: Env, user: Address, min_gold_out: u128) -> u128 {
+        user.require_auth();
+        let recipient = Self::payout_address(&env, &user);
+        Self::withdraw_to(env, user, recipient, Payout::Liquidity(min_gold_out))
+    }
+
+    /// Hand USDC worth `value` to a liquidator of an overdue liquidity draw (lending vault only)
+    ///
+    /// Called by the lender's `liquidate_facility` once the liquidator has repaid it.
+    /// Returns the USDC surrendered.
+    pub fn surrender_facility_collateral(
+        env: Env,
+        lender: Address,
+        recipient: Address,
+        value: u128,
+    ) -> u128 {
+        lender.require_auth();
+        if Self::get_liquidity_lender(env.clone()) != Some(lender.clone()) {
+            panic!("Only the liquidity lender can claim collateral");
+        }
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        Self::ensure_liquidity(&env, &usdc_contract, value);
+        transfer_or_abort(
+            &env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            &recipient,
+            value as i128,
+        );
+
+        log!(
+            &env,
+            "Surrendered {} USDC to {} for an overdue draw from vault {}",
+            value,
+            recipient,
+            lender
+        );
+
+        value
+    }
+
+    /// Get the vault the last liquidity draw was taken from, if any
+    pub fn get_liquidity_lender(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::LiquidityLender)
+    }
+
+    /// Internal helper functions
+    fn ensure_allowlisted(env: &Env, user: &Address) {
+        if !env.storage().instance().has(&DataKey::LaunchPhase) {
+            return;
+        }
+
+        // Fails closed: a launch phase with no resolvable allowlist admits nobody
+        let allowlist = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_ALLOWLIST,
+            &DataKey::Allowlist,
+        );
+        let allowed: bool = invoke_peer(
+            env,
+            &allowlist,
+            "is_allowed",
+            (user.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        if !allowed {
+            panic_with_error!(env, ContractError::NotAllowlisted);
+        }
+    }
+
+    fn verify_governance_or_admin(env: &Env, caller: &Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if *caller != admin && governance.as_ref() != Some(caller) {
+            panic!("Only governance or admin can manage vault risk parameters");
+        }
+    }
+
+    fn verify_fee_setter(env: &Env, caller: &Address) {
+        // Once assigned, the fee setter alone changes fees
+        match role_holder(env, Role::FeeSetter) {
+            Some(fee_setter) if fee_setter == *caller => {}
+            Some(_) => panic_with_error!(env, ContractError::MissingRole),
+            None => Self::verify_governance_or_admin(env, caller),
+        }
+    }
+
+    fn verify_governance(env: &Env, caller: &Address) {
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if governance.as_ref() != Some(caller) {
+            panic!("Only governance can enter global settlement");
+        }
+    }
+
+    fn verify_loan_manager(env: &Env, caller: &Address) {
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if governance.as_ref() != Some(caller) {
+            panic!("Only the loan manager can place liens");
+        }
+    }
+
+    fn ensure_unencumbered(env: &Env, user: &Address) {
+        let receipt_id: Option<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ActiveReceipt(user.clone()));
+        if let Some(receipt_id) = receipt_id {
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Encumbrance(user.clone(), receipt_id))
+            {
+                panic!("Position is encumbered by a loan");
+            }
+        }
+    }
+
+    fn ensure_not_settled(env: &Env) {
+        if env.storage().instance().has(&DataKey::Settlement) {
+            panic_with_error!(env, ContractError::GlobalSettlement);
+        }
+    }
+
+    fn exit_fee_bps(env: &Env, deposit_info: &DepositInfo) -> u128 {
+        if !Self::get_fee_switch(env.clone(), RevenueStream::EarlyExitPenalty).enabled {
+            return 0;
+        }
+        let Some(schedule) = Self::get_exit_fee_schedule(env.clone()) else {
+            return 1000; // 10% in basis points
+        };
+
+        // Linear decay from start to end across the lock, flat at the end once matured
+        let lock_duration = (deposit_info.unlock_time - deposit_info.deposit_time) as u128;
+        let held = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(deposit_info.deposit_time) as u128;
+        let start = schedule.start_bps as u128;
+        let end = schedule.end_bps as u128;
+        start - (start - end) * held.min(lock_duration) / lock_duration
+    }
+
+    fn fee_rebate_bps(schedule: &YieldFeeSchedule, deposit_info: &DepositInfo) -> u32 {
+        let mut rebate_bps = 0;
+        for tier in schedule.size_rebates.iter() {
+            if deposit_info.amount >= tier.min_amount {
+                rebate_bps = tier.rebate_bps;
+            }
+        }
+        if deposit_info.lock_period == LockPeriod::TwelveMonths {
+            rebate_bps = rebate_bps.max(schedule.long_lock_rebate_bps);
+        }
+        rebate_bps
+    }
+
+    fn yield_fee(env: &Env, deposit_info: &DepositInfo, withdrawal_amount: u128) -> u128 {
+        if !Self::get_fee_switch(env.clone(), RevenueStream::YieldSkim).enabled {
+            return 0;
+        }
+        let Some(schedule) = Self::get_yield_fee_schedule(env.clone()) else {
+            return 0;
+        };
+
+        // Rounds down, in the depositor's favour
+        let earned = withdrawal_amount.saturating_sub(deposit_info.amount);
+        let rebate_bps = Self::fee_rebate_bps(&schedule, deposit_info) as u128;
+        let fee_share = schedule.fee_bps as u128 * (BASIS_POINTS - rebate_bps);
+        mul_div(earned, fee_share, BASIS_POINTS * BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Yield fee overflow"))
+    }
+
+    fn depositor_held_value(env: &Env) -> u128 {
+        // USDC held, allocated or lent for depositors, excluding fees still owed to the treasury,
+        // queued withdrawals and unclaimed vouchers
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let usdc_client = TokenClient::new(env, &usdc_contract);
+        let token_balance = usdc_client.balance(&env.current_contract_address()).max(0) as u128;
+        (token_balance
+            + Self::get_total_allocated(env.clone())
+            + Self::get_facility_lent(env.clone()))
+        .saturating_sub(Self::get_unrouted_fees(env.clone()))
+        .saturating_sub(Self::get_queued_total(env.clone()))
+        .saturating_sub(Self::get_voucher_reserve(env.clone()))
+    }
+
+    fn current_withdrawal_epoch(env: &Env) -> Option<WithdrawalEpoch> {
+        let cap = Self::get_withdrawal_cap(env.clone())?;
+        let epoch = env.ledger().timestamp() / EPOCH_DURATION;
+
+        let stored: Option<WithdrawalEpoch> =
+            env.storage().instance().get(&DataKey::WithdrawalEpoch);
+        if let Some(stored) = stored.filter(|stored| stored.epoch == epoch) {
+            return Some(stored);
+        }
+
+        let capacity = match cap {
+            WithdrawalCap::Absolute(amount) => amount,
+            // Queued withdrawals still count, so a drained vault can keep paying its queue
+            WithdrawalCap::TvlShare(share_bps) => mul_div(
+                Self::get_vault_balance(env.clone()) + Self::get_queued_total(env.clone()),
+                share_bps as u128,
+                BASIS_POINTS,
+            )
+            .unwrap_or_else(|| panic!("Withdrawal capacity overflow")),
+        };
+        Some(WithdrawalEpoch {
+            epoch,
+            capacity,
+            withdrawn: 0,
+        })
+    }
+
+    fn take_withdrawal_capacity(env: &Env, amount: u128) -> u128 {
+        // Queued withdrawals are paid first, so new ones only get what the queue leaves
+        Self::draw_withdrawal_capacity(env, amount, Self::get_queued_total(env.clone()))
+    }
+
+    fn draw_withdrawal_capacity(env: &Env, amount: u128, reserved: u128) -> u128 {
+        let Some(mut epoch) = Self::current_withdrawal_epoch(env) else {
+            return amount;
+        };
+
+        let allowed = amount.min((epoch.capacity - epoch.withdrawn).saturating_sub(reserved));
+        epoch.withdrawn += allowed;
+        env.storage()
+            .instance()
+            .set(&DataKey::WithdrawalEpoch, &epoch);
+
+        allowed
+    }
+
+    // Pays queued claims from the head of the queue until capacity or the batch runs out,
+    // or `claimant` is paid in full, returning the USDC paid in total and to `claimant`
+    fn serve_withdrawal_queue(env: &Env, claimant: Option<&Address>) -> (u128, u128) {
+        let mut head: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::QueueHead)
+            .unwrap_or(0);
+        let tail: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::QueueTail)
+            .unwrap_or(0);
+
+        let mut served = 0;
+        let mut to_claimant = 0;
+        let mut claims = 0;
+        while head < tail && claims < WITHDRAWAL_QUEUE_BATCH {
+            let key = DataKey::QueuedClaim(head);
+            let mut claim: QueuedClaim = env.storage().persistent().get(&key).unwrap();
+            // Claims already paid out in settlement leave nothing owed
+            let owed = claim
+                .amount
+                .min(Self::get_queued_withdrawal(env.clone(), claim.user.clone()));
+            if owed > 0 {
+                let paid = Self::draw_withdrawal_capacity(env, owed, 0);
+                if paid == 0 {
+                    break;
+                }
+                Self::pay_queued(env, &claim.user, paid);
+                served += paid;
+                if claimant == Some(&claim.user) {
+                    to_claimant += paid;
+                }
+                if paid < owed {
+                    claim.amount = owed - paid;
+                    env.storage().persistent().set(&key, &claim);
+                    break;
+                }
+            }
+            env.storage().persistent().remove(&key);
+            head += 1;
+            claims += 1;
+
+            // A claimant stops once their own claims are paid, leaving the rest to their owners
+            if claimant == Some(&claim.user)
+                && Self::get_queued_withdrawal(env.clone(), claim.user.clone()) == 0
+            {
+                break;
+            }
+        }
+        env.storage().instance().set(&DataKey::QueueHead, &head);
+        extend_instance_ttl(env);
+
+        (served, to_claimant)
+    }
+
+    fn pay_queued(env: &Env, user: &Address, amount: u128) {
+        let remaining = Self::get_queued_withdrawal(env.clone(), user.clone()) - amount;
+        if remaining > 0 {
+            env.storage()
+                .persistent()
+                .set(&DataKey::QueuedWithdrawal(user.clone()), &remaining);
+        } else {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::QueuedWithdrawal(user.clone()));
+        }
+        let queued_total = Self::get_queued_total(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::QueuedTotal, &(queued_total - amount));
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        Self::ensure_liquidity(env, &usdc_contract, amount);
+        transfer_or_abort(
+            env,
+            &usdc_contract,
+            &env.current_contract_address(),
+            user,
+            amount as i128,
+        );
+    }
+
+    fn queue_withdrawal(env: &Env, user: &Address, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+
+        let queued = Self::get_queued_withdrawal(env.clone(), user.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::QueuedWithdrawal(user.clone()), &(queued + amount));
+        extend_persistent_ttl(env, &DataKey::QueuedWithdrawal(user.clone()));
+        let queued_total = Self::get_queued_total(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::QueuedTotal, &(queued_total + amount));
+
+        // Claims are paid in queue order before any new withdrawal
+        let tail: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::QueueTail)
+            .unwrap_or(0);
+        let key = DataKey::QueuedClaim(tail);
+        env.storage().persistent().set(
+            &key,
+            &QueuedClaim {
+                user: user.clone(),
+                amount,
+            },
+        );
+        extend_persistent_ttl(env, &key);
+        env.storage()
+            .instance()
+            .set(&DataKey::QueueTail, &(tail + 1));
+    }
+
+    fn snapshot_settlement(env: &Env, settled_at: u64) -> SettlementSnapshot {
+        // Strategies return everything they hold so settlement can pay it out;
+        // a failing strategy must not block settlement, so its USDC is left out
+        for strategy in Self::get_strategies(env.clone()).iter() {
+            let allocated =
+                Self::get_strategy(env.clone(), strategy.clone()).map_or(0, |slot| slot.allocated);
+            if Self::recall_from_strategy(env, &strategy, allocated).is_none() {
+                log!(
+                    env,
+                    "Strategy {} failed to return its USDC at settlement",
+                    strategy
+                );
+            }
+        }
+
+        let snapshot = SettlementSnapshot {
+            settled_at,
+            recorded_liquidity: Self::get_vault_balance(env.clone()),
+            held_value: Self::depositor_held_value(env).saturating_sub(
+                Self::get_total_allocated(env.clone()) + Self::get_facility_lent(env.clone()),
+            ),
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Settlement, &snapshot);
+        extend_instance_ttl(env);
+
+        snapshot
+    }
+
+    fn settle_facility_draw(env: &Env, payer: &Address, borrower: &Address) -> (u128, u128) {
+        let draw = Self::get_facility_draw(env.clone(), borrower.clone())
+            .unwrap_or_else(|| panic!("No open draw for borrower"));
+        let interest = facility_interest(env, &draw);
+
+        env.storage()
+            .instance()
+            .remove(&DataKey::FacilityDraw(borrower.clone()));
+        let lent = Self::get_facility_lent(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::FacilityLent, &lent.saturating_sub(draw.principal));
+        extend_instance_ttl(env);
+
+        let repaid = draw.principal + interest;
+        transfer_or_abort(
+            env,
+            &draw.asset,
+            payer,
+            &env.current_contract_address(),
+            repaid as i128,
+        );
+        if interest > 0 {
+            let yield_token_contract: Address = resolve_peer(
+                env,
+                &DataKey::Registry,
+                REGISTRY_YIELD_TOKEN,
+                &DataKey::YieldToken,
+            );
+            invoke_peer::<()>(
+                env,
+                &yield_token_contract,
+                "distribute_strategy_yield",
+                (env.current_contract_address(), interest).into_val(env),
+                ContractError::YieldTokenCallFailed,
+            );
+        }
+
+        (repaid, interest)
+    }
+
+    fn pay_in_liquidity(env: &Env, recipient: &Address, owed: u128, min_gold_out: u128) -> u128 {
+        let lender = Self::get_liquidity_lender(env.clone())
+            .unwrap_or_else(|| panic!("No liquidity has been drawn"));
+        let gold_asset: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::LiquidityAsset)
+            .unwrap();
+        let gold_price: u128 = invoke_peer(
+            env,
+            &lender,
+            "get_gold_price",
+            (gold_asset.clone(),).into_val(env),
+            ContractError::PeerCallFailed,
+        );
+        let gold_amount = mul_div(owed, GOLD_PRICE_PRECISION, gold_price)
+            .unwrap_or_else(|| panic!("Gold payout overflow"));
+        if gold_amount < min_gold_out {
+            panic_with_error!(env, ContractError::SlippageExceeded);
+        }
+        let held = TokenClient::new(env, &gold_asset)
+            .balance(&env.current_contract_address())
+            .max(0) as u128;
+        if gold_amount > held {
+            panic!("Not enough drawn liquidity to pay out");
+        }
+        transfer_or_abort(
+            env,
+            &gold_asset,
+            &env.current_contract_address(),
+            recipient,
+            gold_amount as i128,
+        );
+
+        gold_amount
+    }
+
+    fn ensure_liquidity(env: &Env, usdc_contract: &Address, amount: u128) {
+        // Recall from strategies in registration order until the payout is covered
+        // without touching USDC set aside for fees, queued withdrawals and vouchers
+        let usdc_client = TokenClient::new(env, usdc_contract);
+        let balance = usdc_client.balance(&env.current_contract_address()).max(0) as u128;
+        let reserved = Self::get_unrouted_fees(env.clone())
+            + Self::get_queued_total(env.clone())
+            + Self::get_voucher_reserve(env.clone());
+        let mut missing = (amount + reserved).saturating_sub(balance);
+        for strategy in Self::get_strategies(env.clone()).iter() {
+            if missing == 0 {
+                break;
+            }
+            if let Some(returned) = Self::recall_from_strategy(env, &strategy, missing) {
+                missing = missing.saturating_sub(returned);
+            }
+        }
+    }
+
+    fn recall_from_strategy(env: &Env, strategy: &Address, amount: u128) -> Option<u128> {
+        let Some(mut slot) = Self::get_strategy(env.clone(), strategy.clone()) else {
+            return Some(0);
+        };
+        let requested = amount.min(slot.allocated);
+        if requested == 0 {
+            return Some(0);
+        }
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let returned = withdraw_from_strategy(env, strategy, &usdc_contract, requested)?;
+        let released = returned.min(slot.allocated);
+        slot.allocated -= released;
+        env.storage()
+            .instance()
+            .set(&DataKey::Strategy(strategy.clone()), &slot);
+        let total_allocated = Self::get_total_allocated(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalAllocated, &(total_allocated - released));
+
+        log!(env, "Recalled {} USDC from strategy {}", returned, strategy);
+
+        Some(returned)
+    }
+
+    fn book_rounding_dust(env: &Env, dust: u128) {
+        if dust == 0 {
+            return;
+        }
+        let booked = Self::get_rounding_dust(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::RoundingDust, &(booked + dust));
+    }
+
+    fn route_fee(env: &Env, usdc_contract: &Address, amount: u128, stream: RevenueStream) {
+        if amount == 0 {
+            return;
+        }
+
+        // The insurance fund takes its share first, rounded down
+        let switch = Self::get_fee_switch(env.clone(), stream.clone());
+        let insured = mul_div(amount, switch.insurance_bps as u128, BASIS_POINTS)
+            .unwrap_or_else(|| panic!("Fee split overflow"));
+        let insurance_fund = if insured > 0 {
+            try_resolve_peer(
+                env,
+                &DataKey::Registry,
+                REGISTRY_INSURANCE_FUND,
+                &DataKey::InsuranceFund,
+            )
+        } else {
+            None
+        };
+        let amount = match insurance_fund {
+            Some(insurance_fund) => {
+                let fees_routed = Self::get_fees_routed(env.clone());
+                env.storage()
+                    .instance()
+                    .set(&DataKey::FeesRouted, &(fees_routed + insured));
+                transfer_or_abort(
+                    env,
+                    usdc_contract,
+                    &env.current_contract_address(),
+                    &insurance_fund,
+                    insured as i128,
+                );
+
+                log!(
+                    env,
+                    "Fee of {} USDC from {} routed to insurance fund {}",
+                    insured,
+                    stream,
+                    insurance_fund
+                );
+                amount - insured
+            }
+            None => amount,
+        };
+        if amount == 0 {
+            return;
+        }
+
+        // Without a treasury the fee stays in the vault, tracked outside depositor TVL
+        let Some(treasury) = try_resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_TREASURY,
+            &DataKey::Treasury,
+        ) else {
+            let unrouted = Self::get_unrouted_fees(env.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::UnroutedFees, &(unrouted + amount));
+
+            log!(
+                env,
+                "Fee of {} USDC from {} held for treasury",
+                amount,
+                stream
+            );
+            return;
+        };
+
+        let fees_routed = Self::get_fees_routed(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::FeesRouted, &(fees_routed + amount));
+        transfer_or_abort(
+            env,
+            usdc_contract,
+            &env.current_contract_address(),
+            &treasury,
+            amount as i128,
+        );
+
+        log!(
+            env,
+            "Fee of {} USDC from {} routed to treasury {}",
+            amount,
+            stream,
+            treasury
+        );
+    }
+
+    fn withdraw_to(env: Env, user: Address, recipient: Address, payout: Payout) -> u128 {
+        Self::ensure_not_settled(&env);
+        Self::ensure_unencumbered(&env, &user);
+
+        let deposit_info: DepositInfo = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Deposit(user.clone()))
+            .unwrap_or_else(|| panic!("No deposit found for user"));
+
+        let current_time = deadline_time(&env);
+
+        if current_time < deposit_info.unlock_time {
+            panic!(
+                "Withdrawal not allowed. Lock period expires at: {}",
+                deposit_info.unlock_time
+            );
+        }
+
+        enter_guard(&env);
+
+        // Effects: close the position before any outbound call
+        // (only the principal was recorded; yield is paid from reserves)
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Deposit(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::DepositTag(user.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Dormant(user.clone()));
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap();
+        env.storage().instance().set(
+            &DataKey::VaultBalance,
+            &(vault_balance - deposit_info.amount),
+        );
+        Self::release_tier_balance(&env, &deposit_info.lock_period, deposit_info.amount);
+        let exported_receipt = Self::close_receipt(&env, &user);
+        Self::flag_depeg_withdrawal(&env, &user, deposit_info.amount);
+        extend_instance_ttl(&env);
+
+        // Interactions: settle yield, burn, then pay out
+        let yield_token_contract: Address = resolve_peer(
+            &env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+
+        invoke_peer::<u128>(
+            &env,
+            &yield_token_contract,
+            "compound_interest",
+            (user.clone(),).into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        // Burn exactly the balance just read, with no other call in between
+        let final_amount: i128 = invoke_peer(
+            &env,
+            &yield_token_contract,
+            "balance",
+            (user.clone(),).into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+        let withdrawal_amount = final_amount as u128;
+
+        // A consolidated position's yield stops at the end of its grace period
+        let accrual_end = match payout {
+            Payout::Consolidated(accrual_end) => accrual_end,
+            Payout::Direct | Payout::Liquidity(_) => current_time,
+        };
+        let yield_rate = Self::calculate_yield_rate(env.clone(), deposit_info.lock_period.clone());
+        let scheduled = compound_daily(
+            deposit_info.amount,
+            yield_rate,
+            accrual_end.saturating_sub(deposit_info.deposit_time),
+        )
+        .unwrap_or_else(|| panic!("Projected yield overflow"));
+        let (credited, queue_all) = match payout {
+            Payout::Direct | Payout::Liquidity(_) => (withdrawal_amount, false),
+            Payout::Consolidated(_) => (withdrawal_amount.min(scheduled), true),
+        };
+        let yield_fee = Self::yield_fee(&env, &deposit_info, credited);
+        let owed = credited - yield_fee;
+        // Only the rate schedule's share is counted; index yield is counted when distributed
+        Self::record_yield_paid(
+            &env,
+            owed.min(scheduled).saturating_sub(deposit_info.amount),
+        );
+        // Drawn gold is outside the USDC withdrawal cap, so nothing is queued
+        let in_liquidity = matches!(payout, Payout::Liquidity(_));
+        let paid = if queue_all || in_liquidity {
+            0
+        } else {
+            Self::take_withdrawal_capacity(&env, owed)
+        };
+        if !in_liquidity {
+            Self::queue_withdrawal(&env, &recipient, owed - paid);
+        }
+
+        invoke_peer::<()>(
+            &env,
+            &yield_token_contract,
+            "burn_for_withdrawal",
+            (
+                env.current_contract_address(),
+                user.clone(),
+                withdrawal_amount,
+            )
+                .into_val(&env),
+            ContractError::YieldTokenCallFailed,
+        );
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        if paid + yield_fee > 0 {
+            Self::ensure_liquidity(&env, &usdc_contract, paid + yield_fee);
+        }
+        if paid > 0 {
+            transfer_or_abort(
+                &env,
+                &usdc_contract,
+                &env.current_contract_address(),
+                &recipient,
+                paid as i128,
+            );
+        }
+        Self::route_fee(&env, &usdc_contract, yield_fee, RevenueStream::YieldSkim);
+        if let Payout::Liquidity(min_gold_out) = payout {
+            let gold_paid = Self::pay_in_liquidity(&env, &recipient, owed, min_gold_out);
+            Self::remove_reward_stake(&env, &user);
+            Self::settle_receipt_token(&env, exported_receipt);
+            exit_guard(&env);
+
+            log!(
+                &env,
+                "User {} withdrew {} USDC (including yield) as {} drawn gold to {}, {} yield fee",
+                user,
+                owed,
+                gold_paid,
+                recipient,
+                yield_fee
+            );
+
+            return gold_paid;
+        }
+
+        Self::remove_reward_stake(&env, &user);
+        Self::settle_receipt_token(&env, exported_receipt);
+        exit_guard(&env);
+
+        log!(
+            &env,
+            "User {} withdrew {} USDC (including yield) to {}, {} queued, {} yield fee",
+            user,
+            paid,
+            recipient,
+            owed - paid,
+            yield_fee
+        );
+
+        paid
+    }
+
+    fn store_recovery(env: &Env, user: &Address, plan: &RecoveryPlan) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Recovery(user.clone()), plan);
+        extend_persistent_ttl(env, &DataKey::Recovery(user.clone()));
+    }
+
+    fn designate_beneficiary(env: &Env, user: &Address, beneficiary: &Address) {
+        if Self::get_beneficiary(env.clone(), user.clone()).is_some() {
+            if Self::payout_address(env, user) != *beneficiary {
+                panic!("Beneficiary can only be changed through change_beneficiary");
+            }
+            return;
+        }
+
+        let designation = Beneficiary {
+            address: beneficiary.clone(),
+            pending: None,
+            effective_at: env.ledger().timestamp(),
+        };
+        Self::store_beneficiary(env, user, &designation);
+
+        log!(env, "User {} named beneficiary {}", user, beneficiary);
+    }
+
+    fn payout_address(env: &Env, user: &Address) -> Address {
+        let Some(designation) = Self::get_beneficiary(env.clone(), user.clone()) else {
+            return user.clone();
+        };
+        // A requested change takes over once its delay has passed
+        match designation.pending {
+            Some(pending) if deadline_time(env) >= designation.effective_at => pending,
+            _ => designation.address,
+        }
+    }
+
+    fn store_beneficiary(env: &Env, user: &Address, designation: &Beneficiary) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Beneficiary(user.clone()), designation);
+        extend_persistent_ttl(env, &DataKey::Beneficiary(user.clone()));
+    }
+
+    fn store_standing_order(env: &Env, user: &Address, order: &StandingOrder) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::StandingOrder(user.clone()), order);
+        extend_persistent_ttl(env, &DataKey::StandingOrder(user.clone()));
+    }
+
+    fn store_voucher(env: &Env, voucher: &DepositVoucher) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Voucher(voucher.id), voucher);
+        extend_persistent_ttl(env, &DataKey::Voucher(voucher.id));
+    }
+
+    fn verify_deposit_operator(env: &Env, user: &Address, operator: &Address) {
+        if operator != user
+            && !Self::is_deposit_operator(env.clone(), user.clone(), operator.clone())
+        {
+            panic!("Operator is not approved to deposit for this user");
+        }
+    }
+
+    fn flag_depeg_withdrawal(env: &Env, user: &Address, principal: u128) {
+        // Withdrawals stay open during a depeg but are tallied for review
+        if Self::is_usdc_pegged(env.clone()) {
+            return;
+        }
+
+        let depeg_withdrawn = Self::get_depeg_withdrawn(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::DepegWithdrawn, &(depeg_withdrawn + principal));
+
+        log!(
+            env,
+            "Withdrawal of {} USDC by {} flagged: USDC outside depeg band",
+            principal,
+            user
+        );
+    }
+
+    fn open_deposit(
+        env: &Env,
+        user: &Address,
+        amount: u128,
+        lock_period: LockPeriod,
+        funding: Funding,
+        tag: Option<DepositTag>,
+        compounding: CompoundingFrequency,
+    ) -> BytesN<32> {
+        ensure_not_paused(env);
+        Self::ensure_not_settled(env);
+        Self::ensure_allowlisted(env, user);
+
+        if amount == 0 {
+            panic!("Deposit amount must be greater than 0");
+        }
+
+        let current_time = env.ledger().timestamp();
+        let unlock_time = Self::calculate_unlock_time(current_time, &lock_period);
+
+        // Check if user already has a deposit (for now, one deposit per user)
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Deposit(user.clone()))
+        {
+            panic!("User already has an active deposit. Withdraw first to make a new deposit.");
+        }
+
+        // A schema v1 position must be migrated before it can be replaced
+        if env.storage().persistent().has(&(v1::DEPOSIT, user.clone())) {
+            panic!("User has an unmigrated deposit. It must be migrated first.");
+        }
+
+        Self::enforce_tier_cap(env, &lock_period, amount);
+
+        if !Self::is_usdc_pegged(env.clone()) {
+            panic_with_error!(env, ContractError::UsdcDepegged);
+        }
+
+        enter_guard(env);
+
+        // Update vault and lock tier balances
+        let vault_balance: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::VaultBalance)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::VaultBalance, &(vault_balance + amount));
+        Self::add_tier_balance(env, &lock_period, amount);
+
+        // Create deposit info and its receipt
+        let deposit_info = DepositInfo {
+            amount,
+            deposit_time: current_time,
+            unlock_time,
+            lock_period: lock_period.clone(),
+            vault_type: VaultType::USDC,
+        };
+
+        let usdc_contract: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::UsdcContract)
+            .unwrap();
+        let compounding_bonus =
+            Self::get_compounding_option(env.clone(), lock_period.clone(), compounding.clone())
+                .unwrap_or_else(|| {
+                    panic!("Compounding frequency is not offered for this lock tier")
+                });
+        let yield_rate = Self::calculate_yield_rate(env.clone(), lock_period.clone())
+            + compounding_bonus as u128;
+
+        // Store deposit info
+        env.storage()
+            .persistent()
+            .set(&DataKey::Deposit(user.clone()), &deposit_info);
+        extend_persistent_ttl(env, &DataKey::Deposit(user.clone()));
+        if let Some(tag) = &tag {
+            env.storage()
+                .persistent()
+                .set(&DataKey::DepositTag(user.clone()), tag);
+            extend_persistent_ttl(env, &DataKey::DepositTag(user.clone()));
+        }
+        let terms_hash = Self::issue_receipt(
+            env,
+            user,
+            &usdc_contract,
+            amount,
+            &deposit_info,
+            yield_rate,
+            &compounding,
+        );
+        Self::track_depositor(env, user);
+        extend_instance_ttl(env);
+
+        // Transfer USDC from user to vault, pulling it under the user's allowance when they did not sign
+        match funding {
+            Funding::Signed => transfer_or_abort(
+                env,
+                &usdc_contract,
+                user,
+                &env.current_contract_address(),
+                amount as i128,
+            ),
+            Funding::Allowance => transfer_from_or_abort(
+                env,
+                &usdc_contract,
+                &env.current_contract_address(),
+                user,
+                &env.current_contract_address(),
+                amount as i128,
+            ),
+            Funding::Voucher => {}
+        }
+
+        // Mint yield tokens at the rate recorded on the receipt
+        let yield_token_contract: Address = resolve_peer(
+            env,
+            &DataKey::Registry,
+            REGISTRY_YIELD_TOKEN,
+            &DataKey::YieldToken,
+        );
+
+        // Call yield token contract to mint tokens; a failed mint reverts the deposit
+        invoke_peer::<()>(
+            env,
+            &yield_token_contract,
+            "mint_for_deposit",
+            (
+                env.current_contract_address(),
+                user.clone(),
+                amount,
+                VaultType::USDC,
+                yield_rate,
+            )
+                .into_val(env),
+            ContractError::YieldTokenCallFailed,
+        );
+        if compounding != CompoundingFrequency::Daily {
+            invoke_peer::<()>(
+                env,
+                &yield_token_contract,
+                "set_compounding",
+                (
+                    env.current_contract_address(),
+                    user.clone(),
+                    compounding,
+                    unlock_time,
+                )
+                    .into_val(env),
+                ContractError::YieldTokenCallFailed,
+            );
+        }
+
+        Self::register_reward_stake(env, user, amount, &lock_period);
+        exit_guard(env);
+
+        // Integrators reconcile customer positions from this event
+        env.events().publish(
+            (symbol_short!("deposit"), user.clone()),
+            (amount, lock_period.clone(), unlock_time, tag),
+        );
+
+        log!(
+            env,
+            "User {} deposited {} USDC with {:?} lock period. Unlock time: {}",
+            user,
+            amount,
+            lock_period,
+            unlock_time
+        );
+
+        terms_hash
+    }
+
+    fn enforce_tier_cap(env: &Env, lock_period: &LockPeriod, amount: u128) {
+        let cap: Option<LockTierCap> = env
+            .storage()
+            .instance()
+            .get(&DataKey::TierCap(lock_period.clone()));
+        if let Some(cap) = cap {
+            let vault_balance = Self::get_vault_balance(env.clone()) + amount;
+            if vault_balance <= cap.min_tvl {
+                return;
+            }
+
+            // tier / vault > cap, cross-multiplied to avoid rounding
+            let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone()) + amount;
+            if tier_balance * BASIS_POINTS > vault_balance * cap.max_share_bps as u128 {
+                panic_with_error!(env, ContractError::TierCapExceeded);
+            }
+        }
+    }
+
+    fn add_tier_balance(env: &Env, lock_period: &LockPeriod, amount: u128) {
+        let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone());
+        env.storage().instance().set(
+            &DataKey::TierBalance(lock_period.clone()),
+            &(tier_balance + amount),
+        );
+    }
+
+    fn release_tier_balance(env: &Env, lock_period: &LockPeriod, amount: u128) {
+        // Positions opened before tiers were tracked were never added
+        let tier_balance = Self::get_tier_balance(env.clone(), lock_period.clone());
+        env.storage().instance().set(
+            &DataKey::TierBalance(lock_period.clone()),
+            &tier_balance.saturating_sub(amount),
+        );
+    }
+
+    fn record_yield_paid(env: &Env, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+        let yield_paid = Self::get_yield_paid(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldPaid, &(yield_paid + amount));
+    }
+
+    fn release_ladder_balance(env: &Env, amount: u128) {
+        let ladder_balance = Self::get_ladder_balance(env.clone());
+        env.storage().instance().set(
+            &DataKey::LadderBalance,
+            &ladder_balance.saturating_sub(amount),
+        );
+    }
+
+    fn ladder_rungs(env: &Env, user: &Address) -> Vec<DepositInfo> {
+        let mut rungs = Vec::new(env);
+        for lock_period in LADDER_TIERS.iter() {
+            if let Some(rung) =
+                Self::get_ladder_rung(env.clone(), user.clone(), lock_period.clone())
+            {
+                rungs.push_back(rung);
+            }
+        }
+        rungs
+    }
+
+    fn register_reward_stake(env: &Env, user: &Address, usd_value: u128, lock_period: &LockPeriod) {
+        // Liquidity mining is optional
+        let rewards_contract: Option<Address> = env.storage().instance().get(&DataKey::Rewards);
+        if let Some(rewards_contract) = rewards_contract {
+            // A rewards outage must not block deposits; the stake can be re-registered later
+            let registered = try_invoke_peer::<()>(
+                env,
+                &rewards_contract,
+                "register_stake",
+                (
+                    env.current_contract_address(),
+                    user.clone(),
+                    usd_value,
+                    lock_period.clone(),
+                )
+                    .into_val(env),
+            );
+            if registered.is_none() {
+                log!(env, "Reward stake registration failed for user {}", user);
+            }
+        }
+    }
+
+    fn remove_reward_stake(env: &Env, user: &Address) {
+        let rewards_contract: Option<Address> = env.storage().instance().get(&DataKey::Rewards);
+        if let Some(rewards_contract) = rewards_contract {
+            // Withdrawals always go through; a stale stake only over-weights future emissions
+            let removed = try_invoke_peer::<()>(
+                env,
+                &rewards_contract,
+                "remove_stake",
+                (env.current_contract_address(), user.clone()).into_val(env),
+            );
+            if removed.is_none() {
+                log!(env, "Reward stake removal failed for user {}", user);
+            }
+        }
+    }
+
+    fn issue_receipt(
+        env: &Env,
+        user: &Address,
+        usdc_contract: &Address,
+        amount: u128,
+        deposit_info: &DepositInfo,
+        yield_rate: u128,
+        compounding: &CompoundingFrequency,
+    ) -> BytesN<32> {
+        let receipt_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextReceiptId)
+            .unwrap_or(1);
+        env.storage()
+            .instance()
+            .set(&DataKey::NextReceiptId, &(receipt_id + 1));
+
+        // USDC is deposited and credited 1:1
+        let terms = DepositTerms {
+            vault: env.current_contract_address(),
+            depositor: user.clone(),
+            asset: usdc_contract.clone(),
+            amount,
+            usd_value: amount,
+            lock_period: deposit_info.lock_period.clone(),
+            yield_rate,
+            deposit_time: deposit_info.deposit_time,
+            unlock_time: deposit_info.unlock_time,
+            fee_policy_version: Self::get_fee_policy_version(env.clone()),
+            compounding: compounding.clone(),
+        };
+        let receipt = DepositReceipt {
+            id: receipt_id,
+            terms_hash: hash_deposit_terms(env, &terms),
+            terms,
+            nft_id: None,
+            closed_at: None,
+        };
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt_id), &receipt);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ActiveReceipt(user.clone()), &receipt_id);
+        extend_persistent_ttl(env, &DataKey::Receipt(receipt_id));
+        extend_persistent_ttl(env, &DataKey::ActiveReceipt(user.clone()));
+
+        receipt.terms_hash
+    }
+
+    fn track_depositor(env: &Env, user: &Address) {
+        // Append-only index walked by exports; closed positions are skipped there
+        let slot_key = DataKey::DepositorSlot(user.clone());
+        if env.storage().persistent().has(&slot_key) {
+            return;
+        }
+
+        let depositor_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::DepositorCount)
+            .unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Depositor(depositor_count), user);
+        env.storage().persistent().set(&slot_key, &depositor_count);
+        extend_persistent_ttl(env, &DataKey::Depositor(depositor_count));
+        extend_persistent_ttl(env, &slot_key);
+        env.storage()
+            .instance()
+            .set(&DataKey::DepositorCount, &(depositor_count + 1));
+    }
+
+    fn position_record(env: &Env, user: &Address) -> Option<PositionRecord> {
+        let deposit_info = Self::get_deposit_info(env.clone(), user.clone())?;
+        let receipt = Self::get_active_receipt(env.clone(), user.clone());
+
+        // Positions predating receipts accrue daily at the current rate for their tier
+        let (yield_rate, compounding) = match &receipt {
+            Some(receipt) => (receipt.terms.yield_rate, receipt.terms.compounding.clone()),
+            None => (
+                Self::calculate_yield_rate(env.clone(), deposit_info.lock_period.clone()),
+                CompoundingFrequency::Daily,
+            ),
+        };
+        let elapsed = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(deposit_info.deposit_time);
+        let grown = compound_at(deposit_info.amount, yield_rate, elapsed, &compounding)
+            .unwrap_or_else(|| panic!("Projected yield overflow"));
+        let position_id = receipt.map(|receipt| receipt.id);
+
+        Some(PositionRecord {
+            vault: env.current_contract_address(),
+            vault_type: deposit_info.vault_type,
+            owner: user.clone(),
+            position_id,
+            principal: deposit_info.amount,
+            accrued_yield: grown - deposit_info.amount,
+            lock_period: deposit_info.lock_period,
+            deposit_time: deposit_info.deposit_time,
+            unlock_time: deposit_info.unlock_time,
+            encumbrance: position_id.and_then(|position_id| {
+                Self::get_encumbrance(env.clone(), user.clone(), position_id)
+            }),
+        })
+    }
+
+    fn rung_record(env: &Env, user: &Address, rung: DepositInfo) -> PositionRecord {
+        // Rungs accrue daily at their tier's current rate, as `withdraw_rung` pays them
+        let yield_rate = Self::calculate_yield_rate(env.clone(), rung.lock_period.clone());
+        let elapsed = env.ledger().timestamp().saturating_sub(rung.deposit_time);
+        let grown = compound_daily(rung.amount, yield_rate, elapsed)
+            .unwrap_or_else(|| panic!("Projected yield overflow"));
+
+        PositionRecord {
+            vault: env.current_contract_address(),
+            vault_type: rung.vault_type,
+            owner: user.clone(),
+            position_id: None,
+            principal: rung.amount,
+            accrued_yield: grown - rung.amount,
+            lock_period: rung.lock_period,
+            deposit_time: rung.deposit_time,
+            unlock_time: rung.unlock_time,
+            encumbrance: None,
+        }
+    }
+
+    fn bump_fee_policy_version(env: &Env) {
+        let version = Self::get_fee_policy_version(env.clone());
+        env.storage()
+            .instance()
+            .set(&DataKey::FeePolicyVersion, &(version + 1));
+    }
+
+    fn close_receipt(env: &Env, user: &Address) -> Option<u64> {
+        // Deposits made before receipts were issued have none to close
+        let mut receipt = Self::get_active_receipt(env.clone(), user.clone())?;
+        receipt.closed_at = Some(env.ledger().timestamp());
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Receipt(receipt.id), &receipt);
+        env.storage()
+            .persistent()
+            .remove(&DataKey::ActiveReceipt(user.clone()));
+
+        receipt.nft_id
+    }
+
+    fn settle_receipt_token(env: &Env, nft_id: Option<u64>) {
+        if let Some(nft_id) = nft_id {
+            // The vault's own receipt is already closed; a stale token must not block withdrawals
+            let receipts_contract: Address = resolve_peer(
+                env,
+                &DataKey::Registry,
+                REGISTRY_RECEIPTS,
+                &DataKey::Receipts,
+            );
+            let settled = try_invoke_peer::<()>(
+                env,
+                &receipts_contract,
+                "settle",
+                (env.current_contract_address(), nft_id).into_val(env),
+            );
+            if settled.is_none() {
+                log!(env, "Receipt token {} settlement failed", nft_id);
+            }
+        }
+    }
+
+    fn migrate_v1_instance(env: &Env) {
+        migrate_instance_entry(env, &v1::ADMIN, &DataKey::Admin);
+        migrate_instance_entry(env, &v1::USDC_CONTRACT, &DataKey::UsdcContract);
+        migrate_instance_entry(env, &v1::YIELD_TOKEN, &DataKey::YieldToken);
+        migrate_instance_entry(env, &v1::REGISTRY, &DataKey::Registry);
+        migrate_instance_entry(env, &v1::REWARDS, &DataKey::Rewards);
+        migrate_instance_entry(env, &v1::VAULT_BALANCE, &DataKey::VaultBalance);
     }
 
-    /// Internal helper functions
     fn calculate_unlock_time(current_time: u64, lock_period: &LockPeriod) -> u64 {
         match lock_period {
-            LockPeriod::ThreeMonths => current_time + (90 * 24 * 60 * 60),   // 90 days
-            LockPeriod::SixMonths => current_time + (180 * 24 * 60 * 60),    // 180 days  
+            LockPeriod::ThreeMonths => current_time + (90 * 24 * 60 * 60), // 90 days
+            LockPeriod::SixMonths => current_time + (180 * 24 * 60 * 60),  // 180 days
             LockPeriod::TwelveMonths => current_time + (365 * 24 * 60 * 60), // 365 days
         }
     }
@@ -1,201 +1,1480 @@
 #![no_std]
-use shared::{UserYieldInfo, VaultType, REBASE_INTERVAL, STORAGE_INSTANCE_PERSISTENT};
+use shared::{
+    begin_migration, compound_at, compound_daily, digest_accounting, ensure_not_paused,
+    ensure_uninitialized, extend_instance_ttl, extend_persistent_ttl, finish_migration,
+    invoke_peer, is_initialized, mark_initialized, migrate_instance_entry,
+    migrate_persistent_entry, realized_apy, require_role, role_holder, set_paused, set_role_holder,
+    stored_schema_version, stream_yield, verify_migration_admin, AccrualBatch, BalanceCheckpoint,
+    BalanceSnapshot, CompoundingFrequency, CompoundingPlan, ContractError, ContractInfo,
+    EpochSummary, HolderStats, ProfitReport, RateChange, RateTranche, Role, TokenStats,
+    TrancheStats, UserYieldInfo, VaultType, YieldMode, YieldStream, APY_WINDOW_EPOCHS,
+    BASIS_POINTS, MAX_COMPOUND_TIP_BPS, RATE_HISTORY_PAGE_SIZE, REBASE_BATCH_SIZE, REBASE_INTERVAL,
+    REGISTRY_YIELD_TOKEN, SECONDS_PER_DAY, YIELD_INDEX_PRECISION,
+};
 use soroban_sdk::token::TokenInterface;
 use soroban_sdk::{
-    contract, contractimpl, log, symbol_short, Address, Env, IntoVal, String, Symbol,
+    contract, contractimpl, contracttype, log, panic_with_error, symbol_short, Address, BytesN,
+    Env, IntoVal, Map, String, Symbol, Vec,
 };
 use soroban_token_sdk::metadata::TokenMetadata;
 
-// Storage Keys
-const BALANCE: Symbol = symbol_short!("BALANCE");
-const USER_YIELD: Symbol = symbol_short!("YIELD");
-const TOTAL_SUPPLY: Symbol = symbol_short!("SUPPLY");
-const GLOBAL_YIELD_RATE: Symbol = symbol_short!("RATE");
-const LAST_REBASE: Symbol = symbol_short!("REBASE");
-const METADATA: Symbol = symbol_short!("METADATA");
+// Storage Keys
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    // Instance: config and totals
+    Admin,
+    Metadata,
+    TotalSupply,
+    GlobalYieldRate,
+    LastRebase,
+    YieldMode,
+    FixedFloor,
+    YieldIndex,
+    ProfitReporter,
+    LastProfitReport,
+    HolderCount,
+    RebaseCursor,
+    Governance,
+    SettledAt,
+    CompoundTip,
+    SnapshotCounter,
+    StrategyVault,
+    TotalMinted,
+    TotalBurned,
+    TotalYieldDistributed,
+    RateChangeCount,
+    TrancheStats(RateTranche),
+    // Persistent: per-holder accounts
+    Balance(Address),
+    UserYield(Address),
+    UserIndex(Address),
+    Holder(u64),
+    HolderSlot(Address),
+    YieldStream(Address),
+    Tranche(Address),
+    Compounding(Address),
+    KeeperTips(Address),
+    // Persistent: vaults allowed to mint, burn and manage holder yield
+    AuthorizedVault(Address),
+    // Persistent: balance snapshots and per-holder checkpoints
+    Snapshot(u32),
+    Checkpoint(Address, u32),
+    CheckpointCount(Address),
+    RateChange(u64),
+    // Persistent: per-holder lifetime figures
+    HolderStats(Address),
+}
+
+// Schema v1 storage keys, only read when migrating
+mod v1 {
+    use soroban_sdk::{symbol_short, Symbol};
+
+    pub const BALANCE: Symbol = symbol_short!("BALANCE");
+    pub const USER_YIELD: Symbol = symbol_short!("YIELD");
+    pub const TOTAL_SUPPLY: Symbol = symbol_short!("SUPPLY");
+    pub const GLOBAL_YIELD_RATE: Symbol = symbol_short!("RATE");
+    pub const LAST_REBASE: Symbol = symbol_short!("REBASE");
+    pub const METADATA: Symbol = symbol_short!("METADATA");
+    pub const ADMIN: Symbol = symbol_short!("ADMIN");
+    pub const YIELD_MODE: Symbol = symbol_short!("MODE");
+    pub const FIXED_FLOOR: Symbol = symbol_short!("FLOOR");
+    pub const YIELD_INDEX: Symbol = symbol_short!("INDEX");
+    pub const USER_INDEX: Symbol = symbol_short!("UINDEX");
+    pub const PROFIT_REPORTER: Symbol = symbol_short!("REPORTER");
+    pub const LAST_PROFIT_REPORT: Symbol = symbol_short!("PROFIT");
+}
+
+// Storage schema version written by this build
+const STORAGE_VERSION: u32 = 2;
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[contract]
+pub struct YieldToken;
+
+#[contractimpl]
+impl YieldToken {
+    /// Initialize the yield token contract
+    pub fn initialize(env: Env, admin: Address, metadata: TokenMetadata) {
+        ensure_uninitialized(&env, Some(v1::ADMIN));
+
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+
+        // Set initial metadata
+        env.storage().instance().set(&DataKey::Metadata, &metadata);
+
+        // Initialize global state
+        env.storage().instance().set(&DataKey::TotalSupply, &0u128);
+        env.storage()
+            .instance()
+            .set(&DataKey::GlobalYieldRate, &500u128); // 5% base rate
+        env.storage()
+            .instance()
+            .set(&DataKey::LastRebase, &env.ledger().timestamp());
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldMode, &YieldMode::Fixed);
+        env.storage().instance().set(&DataKey::FixedFloor, &true);
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldIndex, &YIELD_INDEX_PRECISION);
+
+        mark_initialized(&env, STORAGE_VERSION);
+        extend_instance_ttl(&env);
+
+        log!(&env, "YieldToken initialized with admin: {}", admin);
+    }
+
+    /// Check whether the contract has been initialized
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get the semantic version of the deployed contract
+    pub fn version(env: Env) -> String {
+        String::from_str(&env, CONTRACT_VERSION)
+    }
+
+    /// Get contract name, version and storage schema versions
+    pub fn contract_info(env: Env) -> ContractInfo {
+        ContractInfo {
+            name: Symbol::new(&env, REGISTRY_YIELD_TOKEN),
+            version: String::from_str(&env, CONTRACT_VERSION),
+            schema_version: STORAGE_VERSION,
+            stored_schema_version: stored_schema_version(&env),
+        }
+    }
+
+    /// Migrate storage to the schema version of the current WASM (admin only)
+    ///
+    /// Moves config and totals; holder accounts are moved with `migrate_accounts`.
+    ///
+    /// Storage from before versioning also needs the `deployer` contract that
+    /// created this one, whose record of the admin is trusted over the stored one.
+    pub fn migrate(env: Env, admin: Address, deployer: Option<Address>) {
+        admin.require_auth();
+
+        // Until the v2 migration has run the admin still sits under its v1 key
+        let stored_admin: Option<Address> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .or_else(|| env.storage().instance().get(&v1::ADMIN));
+        let stored_version = begin_migration(&env, STORAGE_VERSION);
+        verify_migration_admin(&env, stored_version, &admin, stored_admin, deployer);
+
+        match stored_version {
+            0 | 1 => Self::migrate_v1_instance(&env),
+            _ => panic_with_error!(&env, ContractError::InvalidMigration),
+        }
+        finish_migration(&env, stored_version, STORAGE_VERSION);
+    }
+
+    /// Move schema v1 holder accounts to their `DataKey` entries (admin only)
+    ///
+    /// Persistent storage cannot be enumerated, so the holders are supplied
+    /// in batches from indexed mint events once `migrate` has run.
+    pub fn migrate_accounts(env: Env, admin: Address, users: Vec<Address>) -> u32 {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can migrate storage");
+        }
+
+        let mut migrated = 0u32;
+        for user in users.iter() {
+            if migrate_persistent_entry(
+                &env,
+                &(v1::BALANCE, user.clone()),
+                &DataKey::Balance(user.clone()),
+            ) {
+                migrate_persistent_entry(
+                    &env,
+                    &(v1::USER_YIELD, user.clone()),
+                    &DataKey::UserYield(user.clone()),
+                );
+                migrate_persistent_entry(
+                    &env,
+                    &(v1::USER_INDEX, user.clone()),
+                    &DataKey::UserIndex(user.clone()),
+                );
+                migrated += 1;
+            }
+
+            // Index every supplied holder so rebase passes reach accounts opened before the index
+            if Self::balance(env.clone(), user.clone()) > 0 {
+                Self::track_holder(&env, &user);
+            }
+        }
+
+        log!(&env, "Migrated {} holder accounts", migrated);
+
+        migrated
+    }
+
+    /// Get user's current interest rate based on their holdings and lock periods
+    pub fn get_user_interest_rate(env: Env, user: Address) -> u128 {
+        let yield_info = Self::get_user_yield_info(&env, &user);
+        yield_info.yield_rate
+    }
+
+    /// Update global yield rate (admin or an authorized vault)
+    pub fn update_global_yield_rate(env: Env, caller: Address, new_rate: u128) {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if caller != admin && !Self::is_authorized_vault(env.clone(), caller.clone()) {
+            panic!("Only admin or an authorized vault can update the yield rate");
+        }
+
+        let old_rate: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::GlobalYieldRate)
+            .unwrap_or(500);
+        env.storage()
+            .instance()
+            .set(&DataKey::GlobalYieldRate, &new_rate);
+        Self::record_rate_change(&env, None, old_rate, new_rate);
+        log!(&env, "Global yield rate updated to: {}", new_rate);
+    }
+
+    /// Get recorded global and per-holder rate changes, oldest first
+    ///
+    /// Holders appear when their first deposit sets a rate and whenever a later deposit
+    /// changes it. `limit` is capped at `RATE_HISTORY_PAGE_SIZE`.
+    pub fn get_rate_history(env: Env, offset: u64, limit: u32) -> Vec<RateChange> {
+        let count = Self::get_rate_change_count(env.clone());
+        let start = offset.min(count);
+        let end = (start + limit.min(RATE_HISTORY_PAGE_SIZE) as u64).min(count);
+
+        let mut history = Vec::new(&env);
+        for index in start..end {
+            if let Some(change) = env.storage().persistent().get(&DataKey::RateChange(index)) {
+                history.push_back(change);
+            }
+        }
+        history
+    }
+
+    /// Get the number of rate changes recorded
+    pub fn get_rate_change_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::RateChangeCount)
+            .unwrap_or(0)
+    }
+
+    /// Compound interest for a specific user
+    pub fn compound_interest(env: Env, user: Address) -> u128 {
+        let mut yield_info = Self::get_user_yield_info(&env, &user);
+        let current_time = Self::accrual_time(&env);
+
+        if current_time > yield_info.last_compound_time {
+            // Streaming holders accrue into their stream and keep a flat balance
+            if let Some(mut stream) = Self::load_yield_stream(&env, &user) {
+                let streamed = Self::calculate_stream_yield(&yield_info, current_time);
+                stream.claimable += streamed;
+                yield_info.total_yield_earned += streamed;
+                yield_info.last_compound_time = current_time;
+                Self::record_yield(&env, &user, streamed);
+
+                Self::set_user_yield_info(&env, &user, &yield_info);
+                Self::store_yield_stream(&env, &user, &stream);
+                Self::sync_user_index(&env, &user);
+
+                return yield_info.principal;
+            }
+
+            let new_yield = Self::project_balance(&env, &user, &yield_info, current_time);
+
+            let accrued = new_yield - yield_info.principal;
+            yield_info.total_yield_earned += accrued;
+            yield_info.principal = new_yield;
+            yield_info.last_compound_time =
+                Self::compound_checkpoint(&env, &user, yield_info.last_compound_time, current_time);
+
+            Self::set_user_yield_info(&env, &user, &yield_info);
+            Self::set_balance(&env, &user, new_yield);
+            Self::sync_user_index(&env, &user);
+            Self::record_yield(&env, &user, accrued);
+
+            // Compounded yield is minted into the balance, so total supply keeps
+            // matching the sum of balances and profit distributions divide by it
+            let total_supply = Self::total_supply(env.clone()) as u128;
+            env.storage()
+                .instance()
+                .set(&DataKey::TotalSupply, &(total_supply + accrued));
+
+            log!(
+                &env,
+                "Compounded interest for user: {}, new balance: {}",
+                user,
+                new_yield
+            );
+        }
+
+        yield_info.principal
+    }
+
+    /// Get a user's balance including yield accrued since their last compound, without compounding
+    pub fn accrued_balance(env: Env, user: Address) -> u128 {
+        let yield_info = Self::get_user_yield_info(&env, &user);
+        let current_time = Self::accrual_time(&env);
+
+        if current_time > yield_info.last_compound_time && !Self::is_streaming(&env, &user) {
+            Self::project_balance(&env, &user, &yield_info, current_time)
+        } else {
+            Self::balance(env.clone(), user) as u128
+        }
+    }
+
+    /// Compound a user's accrued yield into their balance, returning the amount newly accrued
+    ///
+    /// Permissionless and moves no tokens; call before snapshots, audits or profit distributions.
+    pub fn accrue(env: Env, user: Address) -> u128 {
+        let principal_before = Self::get_user_yield_info(&env, &user).principal;
+        let principal_after = Self::compound_interest(env.clone(), user);
+        extend_instance_ttl(&env);
+
+        principal_after - principal_before
+    }
+
+    /// Compound up to `limit` indexed holders starting at slot `cursor`
+    ///
+    /// `limit` is capped at `REBASE_BATCH_SIZE`; continue from `next_cursor` until it is None.
+    pub fn accrue_all(env: Env, cursor: u64, limit: u32) -> AccrualBatch {
+        let holder_count = Self::get_holder_count(env.clone());
+        let start = cursor.min(holder_count);
+        let end = (start + (limit as u64).min(REBASE_BATCH_SIZE)).min(holder_count);
+
+        let accrued = Self::compound_holders(&env, start, end);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Accrued {} across holder slots {} to {}",
+            accrued,
+            start,
+            end
+        );
+
+        AccrualBatch {
+            accrued,
+            processed: (end - start) as u32,
+            next_cursor: if end < holder_count { Some(end) } else { None },
+        }
+    }
+
+    /// Compound another holder's yield, paying the caller a tip from the newly accrued amount
+    ///
+    /// The tip is `get_compound_tip()` bps of the accrual. It is burned from the holder
+    /// and owed to the caller in USDC, which the USDC vault pays from reserves through
+    /// `claim_keeper_tips`, so keeper bots are rewarded for keeping balances fresh.
+    /// Returns the tip owed.
+    pub fn compound_for(env: Env, caller: Address, user: Address) -> u128 {
+        caller.require_auth();
+
+        let accrued = Self::accrue(env.clone(), user.clone());
+        let tip = accrued * Self::get_compound_tip(env.clone()) as u128 / BASIS_POINTS;
+        if tip == 0 || caller == user {
+            return 0;
+        }
+
+        // The tip comes out of the yield just credited, leaving the user's earlier balance intact
+        let mut yield_info = Self::get_user_yield_info(&env, &user);
+        yield_info.principal -= tip;
+        yield_info.total_yield_earned -= tip;
+        Self::set_user_yield_info(&env, &user, &yield_info);
+
+        let user_balance = Self::balance(env.clone(), user.clone()) as u128;
+        Self::set_balance(&env, &user, user_balance - tip);
+        let total_supply = Self::total_supply(env.clone()) as u128;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total_supply - tip));
+        Self::record_burn(&env, &user, tip);
+
+        let owed = Self::get_keeper_tips(env.clone(), caller.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::KeeperTips(caller.clone()), &(owed + tip));
+        extend_persistent_ttl(&env, &DataKey::KeeperTips(caller.clone()));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Compounded {} for {}; caller {} tipped {}",
+            accrued,
+            user,
+            caller,
+            tip
+        );
+
+        tip
+    }
+
+    /// Get the tips a keeper has earned from `compound_for` and not yet claimed
+    pub fn get_keeper_tips(env: Env, keeper: Address) -> u128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::KeeperTips(keeper))
+            .unwrap_or(0)
+    }
+
+    /// Empty a keeper's earned tips for the vault to pay out, returning the amount taken
+    pub fn take_keeper_tips(env: Env, vault_contract: Address, keeper: Address) -> u128 {
+        Self::require_authorized_vault(&env, &vault_contract);
+        vault_contract.require_auth_for_args((keeper.clone(),).into_val(&env));
+        keeper.require_auth();
+
+        let claimed = Self::get_keeper_tips(env.clone(), keeper.clone());
+        env.storage()
+            .persistent()
+            .remove(&DataKey::KeeperTips(keeper.clone()));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Keeper tips of {} taken for keeper: {} by vault: {}",
+            claimed,
+            keeper,
+            vault_contract
+        );
+
+        claimed
+    }
+
+    /// Get the keeper tip on `compound_for` in bps of newly accrued yield
+    pub fn get_compound_tip(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::CompoundTip)
+            .unwrap_or(0)
+    }
+
+    /// Set the keeper tip on `compound_for`, up to `MAX_COMPOUND_TIP_BPS` (fee setter, or governance or admin if none is set)
+    pub fn set_compound_tip(env: Env, caller: Address, tip_bps: u32) {
+        caller.require_auth();
+
+        // Once assigned, the fee setter alone changes the tip
+        match role_holder(&env, Role::FeeSetter) {
+            Some(fee_setter) if fee_setter == caller => {}
+            Some(_) => panic_with_error!(&env, ContractError::MissingRole),
+            None => {
+                let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+                let governance: Option<Address> =
+                    env.storage().instance().get(&DataKey::Governance);
+                if caller != admin && governance != Some(caller.clone()) {
+                    panic!("Only governance or admin can set the compound tip");
+                }
+            }
+        }
+
+        if tip_bps > MAX_COMPOUND_TIP_BPS {
+            panic!("Compound tip cannot exceed {} bps", MAX_COMPOUND_TIP_BPS);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::CompoundTip, &tip_bps);
+
+        log!(&env, "Compound tip set to {} bps", tip_bps);
+    }
+
+    /// Switch a holder between compounding yield into their balance and streaming it
+    ///
+    /// While streaming, yield accrues per second at the holder's rate into a stream
+    /// that the vault pays out on `claim_stream`, and the balance stays flat. Yield
+    /// accrued before the switch is settled under the old mode first; a stream can
+    /// only be stopped once everything streamed has been claimed.
+    pub fn set_yield_stream(env: Env, user: Address, enabled: bool) {
+        user.require_auth();
+
+        Self::compound_interest(env.clone(), user.clone());
+
+        if enabled && Self::get_tranche(env.clone(), user.clone()) == Some(RateTranche::Variable) {
+            panic!("Variable-rate yield cannot be streamed");
+        }
+
+        match (enabled, Self::load_yield_stream(&env, &user)) {
+            (true, Some(_)) => panic!("Yield is already streamed"),
+            (true, None) => {
+                let stream = YieldStream {
+                    claimable: 0,
+                    total_claimed: 0,
+                    started_at: env.ledger().timestamp(),
+                };
+                Self::store_yield_stream(&env, &user, &stream);
+            }
+            (false, Some(stream)) => {
+                if stream.claimable > 0 {
+                    panic!("Claim streamed yield before stopping the stream");
+                }
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::YieldStream(user.clone()));
+            }
+            (false, None) => panic!("Yield is not streamed"),
+        }
+        extend_instance_ttl(&env);
+
+        log!(&env, "Yield streaming for {} set to {}", user, enabled);
+    }
+
+    /// Set how often a vault deposit's fixed-schedule yield compounds
+    ///
+    /// Called by the vault right after `mint_for_deposit`, which resets every holder
+    /// to daily compounding. Weekly yield compounds once per whole week; at-maturity
+    /// yield accrues as simple interest and is credited once `matures_at` has passed.
+    pub fn set_compounding(
+        env: Env,
+        vault_contract: Address,
+        user: Address,
+        frequency: CompoundingFrequency,
+        matures_at: u64,
+    ) {
+        Self::require_authorized_vault(&env, &vault_contract);
+        vault_contract
+            .require_auth_for_args((user.clone(), frequency.clone(), matures_at).into_val(&env));
+        user.require_auth();
+
+        let key = DataKey::Compounding(user.clone());
+        if frequency == CompoundingFrequency::Daily {
+            env.storage().persistent().remove(&key);
+        } else {
+            if Self::is_streaming(&env, &user) {
+                panic!("Streamed yield is not compounded");
+            }
+            let plan = CompoundingPlan {
+                frequency: frequency.clone(),
+                matures_at,
+            };
+            env.storage().persistent().set(&key, &plan);
+            extend_persistent_ttl(&env, &key);
+        }
+
+        log!(
+            &env,
+            "Compounding for user: {} set to {:?} by vault: {}",
+            user,
+            frequency,
+            vault_contract
+        );
+    }
+
+    /// Get a holder's compounding plan, if it is not the daily default
+    pub fn get_compounding(env: Env, user: Address) -> Option<CompoundingPlan> {
+        env.storage().persistent().get(&DataKey::Compounding(user))
+    }
+
+    /// Get a holder's yield stream, with yield streamed up to now counted as claimable
+    pub fn get_yield_stream(env: Env, user: Address) -> Option<YieldStream> {
+        let mut stream = Self::load_yield_stream(&env, &user)?;
+        let yield_info = Self::get_user_yield_info(&env, &user);
+        let current_time = Self::accrual_time(&env);
+        if current_time > yield_info.last_compound_time {
+            stream.claimable += Self::calculate_stream_yield(&yield_info, current_time);
+        }
+        Some(stream)
+    }
+
+    /// Empty a holder's stream for the vault to pay out, returning the amount taken
+    pub fn take_streamed_yield(env: Env, vault_contract: Address, user: Address) -> u128 {
+        Self::require_authorized_vault(&env, &vault_contract);
+        vault_contract.require_auth_for_args((user.clone(),).into_val(&env));
+        user.require_auth();
+
+        Self::compound_interest(env.clone(), user.clone());
+        let mut stream =
+            Self::load_yield_stream(&env, &user).unwrap_or_else(|| panic!("Yield is not streamed"));
+        let claimed = stream.claimable;
+        stream.claimable = 0;
+        stream.total_claimed += claimed;
+        Self::store_yield_stream(&env, &user, &stream);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Streamed yield of {} taken for user: {} by vault: {}",
+            claimed,
+            user,
+            vault_contract
+        );
+
+        claimed
+    }
+
+    /// Record a balance snapshot and return its ID (governance or admin)
+    ///
+    /// Balances are kept as last checkpointed, so run `accrue_all` first for
+    /// yield-inclusive balances.
+    pub fn take_snapshot(env: Env, caller: Address) -> u32 {
+        caller.require_auth();
+
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if caller != admin && governance != Some(caller.clone()) {
+            panic!("Only governance or admin can take a snapshot");
+        }
+
+        let snapshot_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::SnapshotCounter)
+            .unwrap_or(0)
+            + 1;
+        let snapshot = BalanceSnapshot {
+            id: snapshot_id,
+            taken_at: env.ledger().timestamp(),
+            total_supply: Self::total_supply(env.clone()) as u128,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::SnapshotCounter, &snapshot_id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Snapshot(snapshot_id), &snapshot);
+        extend_persistent_ttl(&env, &DataKey::Snapshot(snapshot_id));
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Snapshot {} taken with total supply {}",
+            snapshot_id,
+            snapshot.total_supply
+        );
+
+        snapshot_id
+    }
+
+    /// Get a balance snapshot by ID
+    pub fn get_snapshot(env: Env, snapshot_id: u32) -> Option<BalanceSnapshot> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Snapshot(snapshot_id))
+    }
+
+    /// Get a holder's balance as it stood when a snapshot was taken
+    pub fn balance_at(env: Env, user: Address, snapshot_id: u32) -> u128 {
+        if Self::get_snapshot(env.clone(), snapshot_id).is_none() {
+            panic!("Snapshot not found");
+        }
+
+        // The first checkpoint at or after the snapshot holds the balance it saw
+        let count: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CheckpointCount(user.clone()))
+            .unwrap_or(0);
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let mid = (low + high) / 2;
+            if Self::get_checkpoint(&env, &user, mid).snapshot_id < snapshot_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        if low < count {
+            Self::get_checkpoint(&env, &user, low).balance
+        } else {
+            // Unchanged since the snapshot
+            Self::balance(env, user) as u128
+        }
+    }
+
+    /// Mint tokens for vault deposits
+    ///
+    /// Only vaults the admin has authorized may mint. The vault authorizes the exact
+    /// user, amount, vault type and yield rate rather than the call as a whole, so its
+    /// signature cannot be replayed for a different mint.
+    pub fn mint_for_deposit(
+        env: Env,
+        vault_contract: Address,
+        user: Address,
+        amount: u128,
+        vault_type: VaultType,
+        yield_rate: u128,
+    ) {
+        Self::require_authorized_vault(&env, &vault_contract);
+        vault_contract.require_auth_for_args(
+            (user.clone(), amount, vault_type.clone(), yield_rate).into_val(&env),
+        );
+
+        // A streaming holder's yield so far stays in the stream rather than being reset
+        if Self::is_streaming(&env, &user) {
+            Self::compound_interest(env.clone(), user.clone());
+        }
+
+        let current_time = env.ledger().timestamp();
+        let current_balance = Self::balance(env.clone(), user.clone()) as u128;
+        let new_balance = current_balance + amount;
+
+        // A first deposit is recorded as a change from the default rate
+        let previous_rate = env
+            .storage()
+            .persistent()
+            .get(&DataKey::UserYield(user.clone()))
+            .map(|info: UserYieldInfo| info.yield_rate);
+        if previous_rate != Some(yield_rate) {
+            let old_rate = previous_rate.unwrap_or(500);
+            Self::record_rate_change(&env, Some(user.clone()), old_rate, yield_rate);
+        }
+
+        // Every deposit starts on daily compounding until its vault sets otherwise
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Compounding(user.clone()));
+
+        // Update user's yield info
+        let yield_info = UserYieldInfo {
+            principal: new_balance,
+            yield_rate,
+            last_compound_time: current_time,
+            total_yield_earned: 0,
+        };
+
+        Self::set_user_yield_info(&env, &user, &yield_info);
+        Self::set_balance(&env, &user, new_balance);
+        Self::sync_user_index(&env, &user);
+
+        // Update total supply
+        let total_supply = Self::total_supply(env.clone()) as u128;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total_supply + amount));
+        Self::record_mint(&env, &user, amount);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Minted {} tokens for user: {} from vault: {}",
+            amount,
+            user,
+            vault_contract
+        );
+    }
+
+    /// Burn tokens for vault withdrawals (authorized vaults only)
+    pub fn burn_for_withdrawal(env: Env, vault_contract: Address, user: Address, amount: u128) {
+        Self::require_authorized_vault(&env, &vault_contract);
+        vault_contract.require_auth_for_args((user.clone(), amount).into_val(&env));
+
+        let current_balance = Self::balance(env.clone(), user.clone()) as u128;
+        if current_balance < amount {
+            panic!("Insufficient balance for burn");
+        }
+
+        let new_balance = current_balance - amount;
+        Self::set_balance(&env, &user, new_balance);
+
+        // Update total supply
+        let total_supply = Self::total_supply(env.clone()) as u128;
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalSupply, &(total_supply - amount));
+        Self::record_burn(&env, &user, amount);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Burned {} tokens for user: {} from vault: {}",
+            amount,
+            user,
+            vault_contract
+        );
+    }
+
+    /// Start a rebase pass once the interval has passed and compound the next batch of holders
+    ///
+    /// Each call compounds at most `REBASE_BATCH_SIZE` holders from a stored cursor,
+    /// so a full pass is spread across as many calls as the holder count needs.
+    /// Returns the number of holders compounded.
+    pub fn rebase(env: Env) -> u32 {
+        let current_time = env.ledger().timestamp();
+        let last_rebase = env
+            .storage()
+            .instance()
+            .get(&DataKey::LastRebase)
+            .unwrap_or(0u64);
+
+        let mut cursor: Option<u64> = env.storage().instance().get(&DataKey::RebaseCursor);
+        if cursor.is_none() && current_time >= last_rebase + REBASE_INTERVAL {
+            env.storage()
+                .instance()
+                .set(&DataKey::LastRebase, &current_time);
+            cursor = Some(0);
+
+            log!(&env, "Global rebase started at timestamp: {}", current_time);
+        }
+
+        let Some(start) = cursor else {
+            return 0;
+        };
+
+        let holder_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::HolderCount)
+            .unwrap_or(0);
+        let end = (start + REBASE_BATCH_SIZE).min(holder_count);
+        Self::compound_holders(&env, start, end);
+
+        if end >= holder_count {
+            env.storage().instance().remove(&DataKey::RebaseCursor);
+            log!(
+                &env,
+                "Rebase pass complete: {} holders compounded",
+                holder_count
+            );
+        } else {
+            env.storage().instance().set(&DataKey::RebaseCursor, &end);
+        }
+        extend_instance_ttl(&env);
+
+        (end - start) as u32
+    }
+
+    /// Get the next holder slot of an in-progress rebase pass, if any
+    pub fn get_rebase_cursor(env: Env) -> Option<u64> {
+        env.storage().instance().get(&DataKey::RebaseCursor)
+    }
+
+    /// Get the number of indexed holders
+    pub fn get_holder_count(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::HolderCount)
+            .unwrap_or(0)
+    }
+
+    /// Get lifetime mint, burn and yield totals alongside the current supply and yield index
+    ///
+    /// Yield counts once it is credited to a balance or stream, so run `accrue_all`
+    /// first for figures that include yield accrued since holders last compounded.
+    pub fn get_global_stats(env: Env) -> TokenStats {
+        TokenStats {
+            total_minted: env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalMinted)
+                .unwrap_or(0),
+            total_burned: env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalBurned)
+                .unwrap_or(0),
+            total_yield_distributed: env
+                .storage()
+                .instance()
+                .get(&DataKey::TotalYieldDistributed)
+                .unwrap_or(0),
+            total_supply: Self::total_supply(env.clone()) as u128,
+            holder_count: Self::get_holder_count(env.clone()),
+            yield_index: Self::get_yield_index(env),
+        }
+    }
+
+    /// Get the realized APY, in basis points, over the last `APY_WINDOW_EPOCHS` closed epochs
+    ///
+    /// Built from the yield the epochs governance closed actually paid and distributed,
+    /// not the advertised rate schedule; see `shared::realized_apy`. `None` covers all
+    /// vaults. Zero until governance is set and an epoch closes with TVL in the vault.
+    pub fn current_apy(env: Env, vault_type: Option<VaultType>) -> u128 {
+        let Some(governance) = env
+            .storage()
+            .instance()
+            .get::<_, Address>(&DataKey::Governance)
+        else {
+            return 0;
+        };
+        let summaries: Vec<EpochSummary> = invoke_peer(
+            &env,
+            &governance,
+            "get_recent_epoch_summaries",
+            (APY_WINDOW_EPOCHS as u32,).into_val(&env),
+            ContractError::PeerCallFailed,
+        );
+
+        realized_apy(&summaries, &vault_type).unwrap_or_else(|| panic!("Realized APY overflow"))
+    }
+
+    /// Get the stored accounting figures the state digest is computed over
+    ///
+    /// Supply, lifetime mint, burn and yield totals, the yield index and holder count.
+    pub fn accounting_state(env: Env) -> Map<Symbol, u128> {
+        let stats = Self::get_global_stats(env.clone());
+        let mut figures = Map::new(&env);
+        figures.set(symbol_short!("supply"), stats.total_supply);
+        figures.set(symbol_short!("minted"), stats.total_minted);
+        figures.set(symbol_short!("burned"), stats.total_burned);
+        figures.set(symbol_short!("yield_out"), stats.total_yield_distributed);
+        figures.set(symbol_short!("yield_idx"), stats.yield_index);
+        figures.set(symbol_short!("holders"), stats.holder_count as u128);
+        figures
+    }
+
+    /// Get the SHA-256 digest of `accounting_state()`, recorded by governance each epoch
+    pub fn state_digest(env: Env) -> BytesN<32> {
+        digest_accounting(&env, &Self::accounting_state(env.clone()))
+    }
+
+    /// Get a holder's lifetime minted, burned and earned figures
+    pub fn get_holder_stats(env: Env, user: Address) -> HolderStats {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HolderStats(user))
+            .unwrap_or(HolderStats {
+                minted: 0,
+                burned: 0,
+                yield_earned: 0,
+            })
+    }
+
+    /// Choose between the fixed-rate and variable-rate tranche
+    ///
+    /// Fixed-rate holders earn their lock period's schedule whatever the yield mode;
+    /// variable-rate holders earn only realized profits distributed through the yield
+    /// index. Holders who never choose follow the yield mode. The choice can only be
+    /// made or changed while the holder has no balance, i.e. before depositing.
+    pub fn choose_tranche(env: Env, user: Address, tranche: RateTranche) {
+        user.require_auth();
+
+        if Self::balance(env.clone(), user.clone()) > 0 {
+            panic!("Rate tranche can only be chosen with no balance");
+        }
+        if tranche == RateTranche::Variable && Self::is_streaming(&env, &user) {
+            panic!("Variable-rate yield cannot be streamed");
+        }
+
+        let previous = Self::get_tranche(env.clone(), user.clone());
+        if previous.as_ref() == Some(&tranche) {
+            return;
+        }
+        if let Some(previous) = previous {
+            let mut stats = Self::get_tranche_stats(env.clone(), previous.clone());
+            stats.holders -= 1;
+            Self::store_tranche_stats(&env, &previous, &stats);
+        }
+        let mut stats = Self::get_tranche_stats(env.clone(), tranche.clone());
+        stats.holders += 1;
+        Self::store_tranche_stats(&env, &tranche, &stats);
+
+        let key = DataKey::Tranche(user.clone());
+        env.storage().persistent().set(&key, &tranche);
+        extend_persistent_ttl(&env, &key);
+        extend_instance_ttl(&env);
+
+        log!(&env, "{} chose the {:?} rate tranche", user, tranche);
+    }
+
+    /// Get a holder's rate tranche, if they chose one
+    pub fn get_tranche(env: Env, user: Address) -> Option<RateTranche> {
+        env.storage().persistent().get(&DataKey::Tranche(user))
+    }
+
+    /// Get a tranche's current supply, holder count and lifetime yield
+    pub fn get_tranche_stats(env: Env, tranche: RateTranche) -> TrancheStats {
+        env.storage()
+            .instance()
+            .get(&DataKey::TrancheStats(tranche))
+            .unwrap_or(TrancheStats {
+                supply: 0,
+                holders: 0,
+                yield_earned: 0,
+            })
+    }
+
+    /// Distribute a period's realized profits via the yield index (profit reporter only)
+    ///
+    /// Only holders accruing through the index share in it: variable-rate holders, plus
+    /// holders without a tranche while the yield mode is profit-driven.
+    pub fn distribute_profits(env: Env, reporter: Address, report: ProfitReport) {
+        reporter.require_auth();
+
+        let stored_reporter: Option<Address> =
+            env.storage().instance().get(&DataKey::ProfitReporter);
+        if stored_reporter != Some(reporter.clone()) {
+            panic!("Only the profit reporter can distribute profits");
+        }
+
+        if env.storage().instance().has(&DataKey::SettledAt) {
+            panic_with_error!(&env, ContractError::GlobalSettlement);
+        }
+
+        let total_supply = Self::total_supply(env.clone()) as u128;
+        if total_supply == 0 {
+            panic!("No yield token holders to distribute profits to");
+        }
+
+        let (yield_index, new_index) = Self::raise_yield_index(&env, report.yield_distributed);
+        env.storage()
+            .instance()
+            .set(&DataKey::LastProfitReport, &report);
+
+        log!(
+            &env,
+            "Distributed {} of {} profit to holders. Yield index: {} -> {}",
+            report.yield_distributed,
+            report.total_profit,
+            yield_index,
+            new_index
+        );
+    }
+
+    /// Distribute yield a vault harvested from its strategies via the yield index (strategy vault only)
+    pub fn distribute_strategy_yield(env: Env, vault_contract: Address, amount: u128) {
+        vault_contract.require_auth_for_args((amount,).into_val(&env));
+
+        let strategy_vault: Option<Address> = env.storage().instance().get(&DataKey::StrategyVault);
+        if strategy_vault != Some(vault_contract.clone()) {
+            panic!("Only the strategy vault can distribute strategy yield");
+        }
+
+        if env.storage().instance().has(&DataKey::SettledAt) {
+            panic_with_error!(&env, ContractError::GlobalSettlement);
+        }
+
+        if Self::total_supply(env.clone()) == 0 {
+            panic!("No yield token holders to distribute profits to");
+        }
+
+        let (yield_index, new_index) = Self::raise_yield_index(&env, amount);
+        extend_instance_ttl(&env);
+
+        log!(
+            &env,
+            "Distributed {} of strategy yield from vault {}. Yield index: {} -> {}",
+            amount,
+            vault_contract,
+            yield_index,
+            new_index
+        );
+    }
+
+    /// Select fixed-schedule or profit-driven yield, and whether the fixed schedule is a floor (admin only)
+    pub fn set_yield_mode(env: Env, admin: Address, mode: YieldMode, fixed_floor: bool) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can change the yield mode");
+        }
+
+        env.storage().instance().set(&DataKey::YieldMode, &mode);
+        env.storage()
+            .instance()
+            .set(&DataKey::FixedFloor, &fixed_floor);
+
+        log!(
+            &env,
+            "Yield mode set to {:?} (fixed floor: {})",
+            mode,
+            fixed_floor
+        );
+    }
+
+    /// Set the address allowed to report profits, normally governance (admin only)
+    pub fn set_profit_reporter(env: Env, admin: Address, reporter: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the profit reporter");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ProfitReporter, &reporter);
+
+        log!(&env, "Profit reporter set to: {}", reporter);
+    }
+
+    /// Set the vault allowed to distribute harvested strategy yield, normally the USDC vault (admin only)
+    pub fn set_strategy_vault(env: Env, admin: Address, vault_contract: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the strategy vault");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::StrategyVault, &vault_contract);
+
+        log!(&env, "Strategy vault set to: {}", vault_contract);
+    }
+
+    /// Get the vault allowed to distribute harvested strategy yield, if set
+    pub fn get_strategy_vault(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::StrategyVault)
+    }
+
+    /// Allow or revoke a vault's right to mint, burn and manage holder yield (admin only)
+    pub fn set_authorized_vault(
+        env: Env,
+        admin: Address,
+        vault_contract: Address,
+        authorized: bool,
+    ) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can authorize vaults");
+        }
+
+        let key = DataKey::AuthorizedVault(vault_contract.clone());
+        if authorized {
+            env.storage().persistent().set(&key, &true);
+            extend_persistent_ttl(&env, &key);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        log!(&env, "Vault {} authorized: {}", vault_contract, authorized);
+    }
+
+    /// Check whether a vault may mint, burn and manage holder yield
+    pub fn is_authorized_vault(env: Env, vault_contract: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::AuthorizedVault(vault_contract))
+    }
+
+    /// Set the governance contract allowed to trigger global settlement (admin only)
+    pub fn set_governance(env: Env, admin: Address, governance: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can set the governance contract");
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Governance, &governance);
 
-#[contract]
-pub struct YieldToken;
+        log!(&env, "Governance contract set to: {}", governance);
+    }
 
-#[contractimpl]
-impl YieldToken {
-    /// Initialize the yield token contract
-    pub fn initialize(env: Env, admin: Address, metadata: TokenMetadata) {
-        admin.require_auth();
+    /// Stop all yield accrual for global settlement (governance or admin)
+    ///
+    /// Balances keep whatever accrued up to this moment and never grow afterwards.
+    pub fn enter_settlement(env: Env, caller: Address) {
+        caller.require_auth();
 
-        // Set initial metadata
-        env.storage().instance().set(&METADATA, &metadata);
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        let governance: Option<Address> = env.storage().instance().get(&DataKey::Governance);
+        if caller != admin && governance != Some(caller.clone()) {
+            panic!("Only governance or admin can trigger settlement");
+        }
 
-        // Initialize global state
-        env.storage().instance().set(&TOTAL_SUPPLY, &0u128);
-        env.storage().instance().set(&GLOBAL_YIELD_RATE, &500u128); // 5% base rate
+        if env.storage().instance().has(&DataKey::SettledAt) {
+            panic_with_error!(&env, ContractError::GlobalSettlement);
+        }
+
+        let settled_at = env.ledger().timestamp();
         env.storage()
             .instance()
-            .set(&LAST_REBASE, &env.ledger().timestamp());
+            .set(&DataKey::SettledAt, &settled_at);
+        extend_instance_ttl(&env);
 
-        log!(&env, "YieldToken initialized with admin: {}", admin);
+        log!(
+            &env,
+            "Yield accrual stopped for global settlement at {}",
+            settled_at
+        );
     }
 
-    /// Get user's current interest rate based on their holdings and lock periods
-    pub fn get_user_interest_rate(env: Env, user: Address) -> u128 {
-        let yield_info = Self::get_user_yield_info(&env, &user);
-        yield_info.yield_rate
+    /// Get the time yield accrual stopped for global settlement, if it has
+    pub fn get_settled_at(env: Env) -> Option<u64> {
+        env.storage().instance().get(&DataKey::SettledAt)
     }
 
-    /// Update global yield rate (only callable by authorized contracts)
-    pub fn update_global_yield_rate(env: Env, caller: Address, new_rate: u128) {
+    /// Get current yield mode
+    pub fn get_yield_mode(env: Env) -> YieldMode {
+        env.storage()
+            .instance()
+            .get(&DataKey::YieldMode)
+            .unwrap_or(YieldMode::Fixed)
+    }
+
+    /// Get current global yield index (scaled by 1e12)
+    pub fn get_yield_index(env: Env) -> u128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::YieldIndex)
+            .unwrap_or(YIELD_INDEX_PRECISION)
+    }
+
+    /// Get the most recently distributed profit report
+    pub fn get_last_profit_report(env: Env) -> Option<ProfitReport> {
+        env.storage().instance().get(&DataKey::LastProfitReport)
+    }
+
+    /// Calculate compound yield with daily compounding
+    fn calculate_compound_yield(principal: u128, annual_rate: u128, time_elapsed: u64) -> u128 {
+        compound_daily(principal, annual_rate, time_elapsed)
+            .unwrap_or_else(|| panic!("Compound yield overflow"))
+    }
+
+    /// Grant a role to an address, or revoke it with None to hand it back to the admin (admin only)
+    pub fn set_role(env: Env, admin: Address, role: Role, holder: Option<Address>) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can assign roles");
+        }
+
+        set_role_holder(&env, role.clone(), holder.clone());
+        extend_instance_ttl(&env);
+
+        log!(&env, "Role {:?} assigned to {:?}", role, holder);
+    }
+
+    /// Get the address holding a role, None while the admin holds it
+    pub fn get_role(env: Env, role: Role) -> Option<Address> {
+        role_holder(&env, role)
+    }
+
+    /// Pause yield token transfers (pauser, or admin if no pauser is set)
+    ///
+    /// Vault mints, burns and yield accrual carry on while transfers are paused.
+    pub fn pause(env: Env, caller: Address) {
         caller.require_auth();
-        // TODO: Add authorization check for vault contracts
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
 
-        env.storage().instance().set(&GLOBAL_YIELD_RATE, &new_rate);
-        log!(&env, "Global yield rate updated to: {}", new_rate);
+        set_paused(&env, true);
+        extend_instance_ttl(&env);
+
+        log!(&env, "Paused by {}", caller);
     }
 
-    /// Compound interest for a specific user
-    pub fn compound_interest(env: Env, user: Address) -> u128 {
-        let mut yield_info = Self::get_user_yield_info(&env, &user);
-        let current_time = env.ledger().timestamp();
+    /// Lift a pause (pauser, or admin if no pauser is set)
+    pub fn unpause(env: Env, caller: Address) {
+        caller.require_auth();
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Pauser, &caller, &admin);
 
-        if current_time > yield_info.last_compound_time {
-            let time_elapsed = current_time - yield_info.last_compound_time;
-            let new_yield = Self::calculate_compound_yield(
-                &env,
-                yield_info.principal,
-                yield_info.yield_rate,
-                time_elapsed,
-            );
+        set_paused(&env, false);
 
-            yield_info.total_yield_earned += new_yield - yield_info.principal;
-            yield_info.principal = new_yield;
-            yield_info.last_compound_time = current_time;
+        log!(&env, "Unpaused by {}", caller);
+    }
 
-            Self::set_user_yield_info(&env, &user, &yield_info);
-            Self::set_balance(&env, &user, new_yield);
+    /// Check whether yield token transfers are paused
+    pub fn is_paused(env: Env) -> bool {
+        shared::is_paused(&env)
+    }
 
-            log!(
-                &env,
-                "Compounded interest for user: {}, new balance: {}",
-                user,
-                new_yield
-            );
+    /// Transfer admin rights to a new address, e.g. the multisig contract (admin only)
+    pub fn set_admin(env: Env, admin: Address, new_admin: Address) {
+        admin.require_auth();
+
+        let stored_admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        if admin != stored_admin {
+            panic!("Only admin can transfer admin rights");
         }
 
-        yield_info.principal
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
+
+        log!(&env, "Admin transferred from {} to {}", admin, new_admin);
     }
 
-    /// Mint tokens for vault deposits
-    pub fn mint_for_deposit(
-        env: Env,
-        vault_contract: Address,
-        user: Address,
-        amount: u128,
-        vault_type: VaultType,
-        yield_rate: u128,
-    ) {
-        vault_contract.require_auth();
-        // TODO: Add vault contract authorization check
+    /// Get current admin address
+    pub fn get_admin(env: Env) -> Address {
+        env.storage().instance().get(&DataKey::Admin).unwrap()
+    }
 
-        let current_time = env.ledger().timestamp();
-        let current_balance = Self::balance(env.clone(), user.clone()) as u128;
-        let new_balance = current_balance + amount;
+    /// Upgrade the contract WASM (upgrader, or admin if no upgrader is set; expected to be the timelock)
+    pub fn upgrade(env: Env, caller: Address, new_wasm_hash: BytesN<32>) {
+        caller.require_auth();
 
-        // Update user's yield info
-        let yield_info = UserYieldInfo {
-            principal: new_balance,
-            yield_rate,
-            last_compound_time: current_time,
-            total_yield_earned: 0,
-        };
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        require_role(&env, Role::Upgrader, &caller, &admin);
 
-        Self::set_user_yield_info(&env, &user, &yield_info);
-        Self::set_balance(&env, &user, new_balance);
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
 
-        // Update total supply
-        let total_supply = Self::total_supply(env.clone()) as u128;
-        env.storage()
-            .instance()
-            .set(&TOTAL_SUPPLY, &(total_supply + amount));
+        log!(&env, "Contract upgraded to WASM hash: {}", new_wasm_hash);
+    }
 
-        log!(
-            &env,
-            "Minted {} tokens for user: {} from vault: {}",
-            amount,
-            user,
-            vault_contract
-        );
+    /// Internal helper functions
+    fn migrate_v1_instance(env: &Env) {
+        migrate_instance_entry(env, &v1::ADMIN, &DataKey::Admin);
+        migrate_instance_entry(env, &v1::METADATA, &DataKey::Metadata);
+        migrate_instance_entry(env, &v1::TOTAL_SUPPLY, &DataKey::TotalSupply);
+        migrate_instance_entry(env, &v1::GLOBAL_YIELD_RATE, &DataKey::GlobalYieldRate);
+        migrate_instance_entry(env, &v1::LAST_REBASE, &DataKey::LastRebase);
+        migrate_instance_entry(env, &v1::YIELD_MODE, &DataKey::YieldMode);
+        migrate_instance_entry(env, &v1::FIXED_FLOOR, &DataKey::FixedFloor);
+        migrate_instance_entry(env, &v1::YIELD_INDEX, &DataKey::YieldIndex);
+        migrate_instance_entry(env, &v1::PROFIT_REPORTER, &DataKey::ProfitReporter);
+        migrate_instance_entry(env, &v1::LAST_PROFIT_REPORT, &DataKey::LastProfitReport);
     }
 
-    /// Burn tokens for vault withdrawals
-    pub fn burn_for_withdrawal(env: Env, vault_contract: Address, user: Address, amount: u128) {
-        vault_contract.require_auth();
+    fn project_balance(
+        env: &Env,
+        user: &Address,
+        yield_info: &UserYieldInfo,
+        current_time: u64,
+    ) -> u128 {
+        let time_elapsed = current_time - yield_info.last_compound_time;
+        let fixed_yield = match Self::get_compounding(env.clone(), user.clone()) {
+            None => Self::calculate_compound_yield(
+                yield_info.principal,
+                yield_info.yield_rate,
+                time_elapsed,
+            ),
+            // Nothing is credited under at-maturity compounding until the lock expires
+            Some(plan)
+                if plan.frequency == CompoundingFrequency::AtMaturity
+                    && current_time < plan.matures_at =>
+            {
+                yield_info.principal
+            }
+            Some(plan) => compound_at(
+                yield_info.principal,
+                yield_info.yield_rate,
+                time_elapsed,
+                &plan.frequency,
+            )
+            .unwrap_or_else(|| panic!("Compound yield overflow")),
+        };
 
-        let current_balance = Self::balance(env.clone(), user.clone()) as u128;
-        if current_balance < amount {
-            panic!("Insufficient balance for burn");
+        // A chosen tranche overrides the yield mode
+        let mode = match Self::get_tranche(env.clone(), user.clone()) {
+            Some(RateTranche::Fixed) => return fixed_yield,
+            Some(RateTranche::Variable) => {
+                return Self::calculate_index_yield(env, user, yield_info.principal)
+            }
+            None => Self::get_yield_mode(env.clone()),
+        };
+
+        match mode {
+            YieldMode::Fixed => fixed_yield,
+            YieldMode::ProfitDriven => {
+                let profit_yield = Self::calculate_index_yield(env, user, yield_info.principal);
+                let floor_enabled: bool = env
+                    .storage()
+                    .instance()
+                    .get(&DataKey::FixedFloor)
+                    .unwrap_or(true);
+
+                // The fixed schedule acts as a minimum return when enabled
+                if floor_enabled && fixed_yield > profit_yield {
+                    fixed_yield
+                } else {
+                    profit_yield
+                }
+            }
+        }
+    }
+
+    fn compound_checkpoint(
+        env: &Env,
+        user: &Address,
+        last_compound_time: u64,
+        current_time: u64,
+    ) -> u64 {
+        // Time not yet compounded stays pending so a rebase cannot round it away
+        match Self::get_compounding(env.clone(), user.clone()) {
+            None => current_time,
+            Some(plan) => match plan.frequency {
+                CompoundingFrequency::Daily => current_time,
+                CompoundingFrequency::Weekly => {
+                    let week = 7 * SECONDS_PER_DAY;
+                    last_compound_time + (current_time - last_compound_time) / week * week
+                }
+                CompoundingFrequency::AtMaturity if current_time < plan.matures_at => {
+                    last_compound_time
+                }
+                CompoundingFrequency::AtMaturity => current_time,
+            },
         }
+    }
 
-        let new_balance = current_balance - amount;
-        Self::set_balance(&env, &user, new_balance);
+    fn calculate_stream_yield(yield_info: &UserYieldInfo, current_time: u64) -> u128 {
+        let time_elapsed = current_time - yield_info.last_compound_time;
+        stream_yield(yield_info.principal, yield_info.yield_rate, time_elapsed)
+            .unwrap_or_else(|| panic!("Stream yield overflow"))
+    }
 
-        // Update total supply
-        let total_supply = Self::total_supply(env.clone()) as u128;
+    fn load_yield_stream(env: &Env, user: &Address) -> Option<YieldStream> {
         env.storage()
-            .instance()
-            .set(&TOTAL_SUPPLY, &(total_supply - amount));
-
-        log!(
-            &env,
-            "Burned {} tokens for user: {} from vault: {}",
-            amount,
-            user,
-            vault_contract
-        );
+            .persistent()
+            .get(&DataKey::YieldStream(user.clone()))
     }
 
-    /// Perform global rebase if interval has passed
-    pub fn rebase(env: Env) {
-        let current_time = env.ledger().timestamp();
-        let last_rebase = env.storage().instance().get(&LAST_REBASE).unwrap_or(0u64);
+    fn store_yield_stream(env: &Env, user: &Address, stream: &YieldStream) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::YieldStream(user.clone()), stream);
+        extend_persistent_ttl(env, &DataKey::YieldStream(user.clone()));
+    }
 
-        if current_time >= last_rebase + REBASE_INTERVAL {
-            // This would update all user balances based on global yield
-            // For now, we'll mark the rebase time and let individual compound_interest calls handle the math
-            env.storage().instance().set(&LAST_REBASE, &current_time);
+    fn is_streaming(env: &Env, user: &Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::YieldStream(user.clone()))
+    }
 
-            log!(
-                &env,
-                "Global rebase executed at timestamp: {}",
-                current_time
-            );
+    fn require_authorized_vault(env: &Env, vault_contract: &Address) {
+        if !Self::is_authorized_vault(env.clone(), vault_contract.clone()) {
+            panic!("Vault is not authorized");
         }
     }
 
-    /// Calculate compound yield using simplified formula
-    fn calculate_compound_yield(
-        env: &Env,
-        principal: u128,
-        annual_rate: u128,
-        time_elapsed: u64,
-    ) -> u128 {
-        // Daily compounding: A = P(1 + r/365)^(t/86400)
-        // Simplified to avoid complex exponentiation in smart contract
-        let days_elapsed = time_elapsed / 86400; // Convert seconds to days
-        let daily_rate = annual_rate / 365; // Basis points per day
+    fn accrual_time(env: &Env) -> u64 {
+        // Yield stops accruing once global settlement starts
+        let current_time = env.ledger().timestamp();
+        let settled_at: Option<u64> = env.storage().instance().get(&DataKey::SettledAt);
+        settled_at.map_or(current_time, |settled_at| settled_at.min(current_time))
+    }
 
-        let mut result = principal;
-        for _ in 0..days_elapsed {
-            // Apply daily compound interest
-            result = result + (result * daily_rate) / 10000;
+    fn compound_holders(env: &Env, start: u64, end: u64) -> u128 {
+        let mut accrued = 0u128;
+        for slot in start..end {
+            let holder: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Holder(slot))
+                .unwrap();
+            let principal_before = Self::get_user_yield_info(env, &holder).principal;
+            accrued += Self::compound_interest(env.clone(), holder) - principal_before;
         }
+        accrued
+    }
 
-        result
+    fn record_rate_change(env: &Env, holder: Option<Address>, old_rate: u128, new_rate: u128) {
+        let index = Self::get_rate_change_count(env.clone());
+        let change = RateChange {
+            changed_at: env.ledger().timestamp(),
+            holder,
+            old_rate,
+            new_rate,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::RateChange(index), &change);
+        extend_persistent_ttl(env, &DataKey::RateChange(index));
+        env.storage()
+            .instance()
+            .set(&DataKey::RateChangeCount, &(index + 1));
     }
 
-    /// Internal helper functions
     fn get_user_yield_info(env: &Env, user: &Address) -> UserYieldInfo {
         env.storage()
             .persistent()
-            .get(&(USER_YIELD.clone(), user.clone()))
+            .get(&DataKey::UserYield(user.clone()))
             .unwrap_or(UserYieldInfo {
                 principal: 0,
                 yield_rate: 500, // Default 5%
@@ -207,13 +1486,199 @@ impl YieldToken {
     fn set_user_yield_info(env: &Env, user: &Address, info: &UserYieldInfo) {
         env.storage()
             .persistent()
-            .set(&(USER_YIELD.clone(), user.clone()), info);
+            .set(&DataKey::UserYield(user.clone()), info);
+        extend_persistent_ttl(env, &DataKey::UserYield(user.clone()));
+    }
+
+    fn calculate_index_yield(env: &Env, user: &Address, principal: u128) -> u128 {
+        // Grow the balance by the index movement since the user's last sync
+        let yield_index = Self::get_yield_index(env.clone());
+        let user_index: u128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::UserIndex(user.clone()))
+            .unwrap_or(yield_index);
+
+        (principal * yield_index) / user_index
+    }
+
+    fn raise_yield_index(env: &Env, amount: u128) -> (u128, u128) {
+        // Spread the amount over the supply that reads the index; returns the index before and after
+        let yield_index = Self::get_yield_index(env.clone());
+        let new_index = yield_index + (amount * YIELD_INDEX_PRECISION) / Self::index_supply(env);
+        env.storage()
+            .instance()
+            .set(&DataKey::YieldIndex, &new_index);
+        (yield_index, new_index)
+    }
+
+    fn index_supply(env: &Env) -> u128 {
+        // Fixed-rate balances never read the index, and neither do untranched ones in fixed mode
+        let total_supply = Self::total_supply(env.clone()) as u128;
+        let index_supply = match Self::get_yield_mode(env.clone()) {
+            YieldMode::Fixed => Self::get_tranche_stats(env.clone(), RateTranche::Variable).supply,
+            YieldMode::ProfitDriven => total_supply
+                .saturating_sub(Self::get_tranche_stats(env.clone(), RateTranche::Fixed).supply),
+        };
+
+        // With no index-accruing supply the index moves for nobody, as before tranches
+        if index_supply == 0 {
+            total_supply
+        } else {
+            index_supply
+        }
+    }
+
+    fn sync_user_index(env: &Env, user: &Address) {
+        let yield_index = Self::get_yield_index(env.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::UserIndex(user.clone()), &yield_index);
+        extend_persistent_ttl(env, &DataKey::UserIndex(user.clone()));
     }
 
     fn set_balance(env: &Env, user: &Address, amount: u128) {
+        Self::checkpoint_balance(env, user);
+        if let Some(tranche) = Self::get_tranche(env.clone(), user.clone()) {
+            let previous = Self::balance(env.clone(), user.clone()) as u128;
+            let mut stats = Self::get_tranche_stats(env.clone(), tranche.clone());
+            stats.supply = stats.supply + amount - previous;
+            Self::store_tranche_stats(env, &tranche, &stats);
+        }
+        env.storage()
+            .persistent()
+            .set(&DataKey::Balance(user.clone()), &amount);
+        extend_persistent_ttl(env, &DataKey::Balance(user.clone()));
+
+        if amount > 0 {
+            Self::track_holder(env, user);
+        }
+    }
+
+    fn checkpoint_balance(env: &Env, user: &Address) {
+        // Only the first change after each snapshot needs the old balance kept
+        let snapshot_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::SnapshotCounter)
+            .unwrap_or(0);
+        if snapshot_id == 0 {
+            return;
+        }
+
+        let count_key = DataKey::CheckpointCount(user.clone());
+        let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        if count > 0 && Self::get_checkpoint(env, user, count - 1).snapshot_id == snapshot_id {
+            return;
+        }
+
+        let checkpoint = BalanceCheckpoint {
+            snapshot_id,
+            balance: Self::balance(env.clone(), user.clone()) as u128,
+        };
+        let checkpoint_key = DataKey::Checkpoint(user.clone(), count);
+        env.storage().persistent().set(&checkpoint_key, &checkpoint);
+        env.storage().persistent().set(&count_key, &(count + 1));
+        extend_persistent_ttl(env, &checkpoint_key);
+        extend_persistent_ttl(env, &count_key);
+    }
+
+    fn get_checkpoint(env: &Env, user: &Address, index: u32) -> BalanceCheckpoint {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Checkpoint(user.clone(), index))
+            .unwrap()
+    }
+
+    fn record_mint(env: &Env, user: &Address, amount: u128) {
+        let total: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalMinted)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalMinted, &(total + amount));
+
+        let mut stats = Self::get_holder_stats(env.clone(), user.clone());
+        stats.minted += amount;
+        Self::store_holder_stats(env, user, &stats);
+    }
+
+    fn record_burn(env: &Env, user: &Address, amount: u128) {
+        let total: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalBurned)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalBurned, &(total + amount));
+
+        let mut stats = Self::get_holder_stats(env.clone(), user.clone());
+        stats.burned += amount;
+        Self::store_holder_stats(env, user, &stats);
+    }
+
+    fn record_yield(env: &Env, user: &Address, amount: u128) {
+        if amount == 0 {
+            return;
+        }
+
+        let total: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalYieldDistributed)
+            .unwrap_or(0);
+        env.storage()
+            .instance()
+            .set(&DataKey::TotalYieldDistributed, &(total + amount));
+
+        let mut stats = Self::get_holder_stats(env.clone(), user.clone());
+        stats.yield_earned += amount;
+        Self::store_holder_stats(env, user, &stats);
+
+        if let Some(tranche) = Self::get_tranche(env.clone(), user.clone()) {
+            let mut stats = Self::get_tranche_stats(env.clone(), tranche.clone());
+            stats.yield_earned += amount;
+            Self::store_tranche_stats(env, &tranche, &stats);
+        }
+    }
+
+    fn store_holder_stats(env: &Env, user: &Address, stats: &HolderStats) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::HolderStats(user.clone()), stats);
+        extend_persistent_ttl(env, &DataKey::HolderStats(user.clone()));
+    }
+
+    fn store_tranche_stats(env: &Env, tranche: &RateTranche, stats: &TrancheStats) {
+        env.storage()
+            .instance()
+            .set(&DataKey::TrancheStats(tranche.clone()), stats);
+    }
+
+    fn track_holder(env: &Env, user: &Address) {
+        // Append-only index walked by rebase; emptied accounts compound to a no-op
+        let slot_key = DataKey::HolderSlot(user.clone());
+        if env.storage().persistent().has(&slot_key) {
+            return;
+        }
+
+        let holder_count: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::HolderCount)
+            .unwrap_or(0);
         env.storage()
             .persistent()
-            .set(&(BALANCE.clone(), user.clone()), &amount);
+            .set(&DataKey::Holder(holder_count), user);
+        env.storage().persistent().set(&slot_key, &holder_count);
+        extend_persistent_ttl(env, &DataKey::Holder(holder_count));
+        extend_persistent_ttl(env, &slot_key);
+        env.storage()
+            .instance()
+            .set(&DataKey::HolderCount, &(holder_count + 1));
     }
 }
 
@@ -234,7 +1699,7 @@ impl TokenInterface for YieldToken {
         let balance: u128 = env
             .storage()
             .persistent()
-            .get(&(BALANCE.clone(), id))
+            .get(&DataKey::Balance(id))
             .unwrap_or(0);
         balance as i128
     }
@@ -242,6 +1707,7 @@ impl TokenInterface for YieldToken {
     fn transfer(env: Env, from: Address, to: Address, amount: i128) {
         // Basic transfer implementation - could be restricted based on lock periods
         from.require_auth();
+        ensure_not_paused(&env);
 
         if amount < 0 {
             panic!("Transfer amount cannot be negative");
@@ -283,10 +1749,16 @@ impl TokenInterface for YieldToken {
 
         Self::set_balance(&env, &from, balance - amount);
 
-        let total_supply = env.storage().instance().get(&TOTAL_SUPPLY).unwrap_or(0u128);
+        let total_supply = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0u128);
         env.storage()
             .instance()
-            .set(&TOTAL_SUPPLY, &(total_supply - amount));
+            .set(&DataKey::TotalSupply, &(total_supply - amount));
+        Self::record_burn(&env, &from, amount);
+        extend_instance_ttl(&env);
     }
 
     fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
@@ -299,12 +1771,12 @@ impl TokenInterface for YieldToken {
     }
 
     fn name(env: Env) -> String {
-        let metadata: TokenMetadata = env.storage().instance().get(&METADATA).unwrap();
+        let metadata: TokenMetadata = env.storage().instance().get(&DataKey::Metadata).unwrap();
         metadata.name
     }
 
     fn symbol(env: Env) -> String {
-        let metadata: TokenMetadata = env.storage().instance().get(&METADATA).unwrap();
+        let metadata: TokenMetadata = env.storage().instance().get(&DataKey::Metadata).unwrap();
         metadata.symbol
     }
 }
@@ -314,7 +1786,11 @@ impl TokenInterface for YieldToken {
 impl YieldToken {
     /// Get total supply (not part of TokenInterface)
     pub fn total_supply(env: Env) -> i128 {
-        let supply: u128 = env.storage().instance().get(&TOTAL_SUPPLY).unwrap_or(0);
+        let supply: u128 = env
+            .storage()
+            .instance()
+            .get(&DataKey::TotalSupply)
+            .unwrap_or(0);
         supply as i128
     }
 }
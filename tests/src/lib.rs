@@ -0,0 +1,16 @@
+//! End-to-end scenarios that register every protocol contract in one test `Env`
+#![cfg(test)]
+
+mod mocks;
+mod setup;
+mod test_client;
+mod test_deployer;
+mod test_escrow;
+mod test_governance;
+mod test_hedging;
+mod test_loans;
+mod test_multisig;
+mod test_receipts;
+mod test_reentrancy;
+mod test_vaults;
+mod test_yield_token;
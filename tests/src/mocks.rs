@@ -0,0 +1,131 @@
+use soroban_sdk::token::TokenClient;
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env, IntoVal, Symbol};
+
+#[contracttype]
+enum MockKey {
+    Target,
+    Price,
+    UpdatedAt,
+    Output,
+    Yield,
+}
+
+/// Token whose `transfer` calls back into a vault's `withdraw` once armed
+#[contract]
+pub struct ReentrantToken;
+
+#[contractimpl]
+impl ReentrantToken {
+    pub fn arm(env: Env, vault: Address) {
+        env.storage().instance().set(&MockKey::Target, &vault);
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, _amount: i128) {
+        let vault: Option<Address> = env.storage().instance().get(&MockKey::Target);
+        if let Some(vault) = vault {
+            // Try to withdraw the same position again while the first payout is in flight
+            let user = if to == vault { from } else { to };
+            env.invoke_contract::<u128>(
+                &vault,
+                &Symbol::new(&env, "withdraw"),
+                (user,).into_val(&env),
+            );
+        }
+    }
+
+    pub fn balance(_env: Env, _id: Address) -> i128 {
+        0
+    }
+}
+
+/// Price feed returning one settable price for every symbol, or failing until set
+///
+/// Prices read as updated at the current ledger time unless pinned with `set_updated_at`.
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn set_price(env: Env, price: u128) {
+        env.storage().instance().set(&MockKey::Price, &price);
+    }
+
+    pub fn set_updated_at(env: Env, timestamp: u64) {
+        env.storage()
+            .instance()
+            .set(&MockKey::UpdatedAt, &timestamp);
+    }
+
+    pub fn get_price(env: Env, _symbol: Symbol) -> u128 {
+        env.storage().instance().get(&MockKey::Price).unwrap()
+    }
+
+    pub fn last_updated(env: Env, _symbol: Symbol) -> u64 {
+        env.storage()
+            .instance()
+            .get(&MockKey::UpdatedAt)
+            .unwrap_or(env.ledger().timestamp())
+    }
+}
+
+/// DEX router paying a settable amount of `asset_out` from its own balance for any swap
+#[contract]
+pub struct MockRouter;
+
+#[contractimpl]
+impl MockRouter {
+    pub fn set_output(env: Env, amount: u128) {
+        env.storage().instance().set(&MockKey::Output, &amount);
+    }
+
+    /// Always claims to have met the minimum, whatever it actually sends
+    pub fn swap(
+        env: Env,
+        _asset_in: Address,
+        asset_out: Address,
+        _amount_in: u128,
+        min_amount_out: u128,
+        to: Address,
+    ) -> u128 {
+        let amount: u128 = env.storage().instance().get(&MockKey::Output).unwrap_or(0);
+        TokenClient::new(&env, &asset_out).transfer(
+            &env.current_contract_address(),
+            &to,
+            &(amount as i128),
+        );
+        min_amount_out
+    }
+}
+
+/// Yield strategy that holds what it is sent and pays a settable yield on harvest
+#[contract]
+pub struct MockStrategy;
+
+#[contractimpl]
+impl MockStrategy {
+    pub fn set_yield(env: Env, amount: u128) {
+        env.storage().instance().set(&MockKey::Yield, &amount);
+    }
+
+    pub fn deposit(_env: Env, _vault: Address, _asset: Address, _amount: u128) {}
+
+    pub fn withdraw(env: Env, vault: Address, asset: Address, amount: u128) -> u128 {
+        TokenClient::new(&env, &asset).transfer(
+            &env.current_contract_address(),
+            &vault,
+            &(amount as i128),
+        );
+        amount
+    }
+
+    pub fn harvest(env: Env, vault: Address, asset: Address) -> u128 {
+        let amount: u128 = env.storage().instance().get(&MockKey::Yield).unwrap_or(0);
+        env.storage().instance().remove(&MockKey::Yield);
+        TokenClient::new(&env, &asset).transfer(
+            &env.current_contract_address(),
+            &vault,
+            &(amount as i128),
+        );
+        amount
+    }
+}
@@ -0,0 +1,262 @@
+use crate::mocks::{MockOracle, MockOracleClient};
+use allowlist::{Allowlist, AllowlistClient};
+use coffee_collateral::CoffeeCollateralClient;
+use deployer::{Deployer, DeployerClient};
+use escrow::{Escrow, EscrowClient};
+use gold_vault::GoldVaultClient;
+use governance::GovernanceClient;
+use hedging::{Hedging, HedgingClient};
+use multisig::MultisigClient;
+use receipts::{Receipts, ReceiptsClient};
+use registry::RegistryClient;
+use rewards::{Rewards, RewardsClient};
+use shared::{
+    CommitteeMember, ExpertiseArea, ProtocolConfig, ProtocolWasmHashes, REGISTRY_ALLOWLIST,
+    REGISTRY_ESCROW, REGISTRY_HEDGING, REGISTRY_ORACLE, REGISTRY_RECEIPTS, TIMELOCK_MIN_DELAY,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{vec, Address, BytesN, Env, String, Symbol, Vec};
+use soroban_token_sdk::metadata::TokenMetadata;
+use timelock::{Timelock, TimelockClient};
+use usdc_vault::USDCVaultClient;
+use yield_token::YieldTokenClient;
+
+pub const DAY: u64 = 24 * 60 * 60;
+pub const USDC: u128 = 10_000_000; // 7 decimals, matching Stellar assets
+pub const MIN_PROPOSAL_TOKENS: u128 = 1_000 * USDC;
+pub const OUNCE: u128 = 10_000_000; // 7 decimals, matching Stellar assets
+pub const GOLD_PRICE: u128 = 2_000_000_000; // $2,000 per ounce at 6 decimals
+
+const YIELD_TOKEN_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/yield_token.wasm");
+const USDC_VAULT_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/usdc_vault.wasm");
+const GOLD_VAULT_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/gold_vault.wasm");
+const GOVERNANCE_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/governance.wasm");
+const COFFEE_COLLATERAL_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/coffee_collateral.wasm");
+const ORACLE_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/oracle.wasm");
+const MULTISIG_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/multisig.wasm");
+const REGISTRY_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/registry.wasm");
+
+/// Upload the release WASM of every contract the deployer creates
+pub fn upload_protocol_wasm(env: &Env) -> ProtocolWasmHashes {
+    let upload = |wasm: &[u8]| env.deployer().upload_contract_wasm(wasm);
+    ProtocolWasmHashes {
+        yield_token: upload(YIELD_TOKEN_WASM),
+        usdc_vault: upload(USDC_VAULT_WASM),
+        gold_vault: upload(GOLD_VAULT_WASM),
+        governance: upload(GOVERNANCE_WASM),
+        coffee_collateral: upload(COFFEE_COLLATERAL_WASM),
+        oracle: upload(ORACLE_WASM),
+        multisig: upload(MULTISIG_WASM),
+        registry: upload(REGISTRY_WASM),
+    }
+}
+
+/// Every protocol contract registered and wired together in a single `Env`
+///
+/// The deployer brings up the core contracts from the release WASM, so run
+/// `cargo build --target wasm32-unknown-unknown --release` first; the rest are
+/// registered natively and added to the deployed registry. The registry's price
+/// feed is a mock quoting one price for every symbol, so tests that price coffee
+/// or USDC call `use_own_price_feeds` and point the contract at their own feed.
+/// The timelock takes proposals from governance but is left out of the registry,
+/// so DAO votes apply at once unless a test registers it.
+pub struct Protocol<'a> {
+    pub env: &'a Env,
+    pub admin: Address,
+    pub committee: Vec<Address>,
+    pub usdc: TokenClient<'a>,
+    pub usdc_admin: StellarAssetClient<'a>,
+    pub gold: TokenClient<'a>,
+    pub gold_admin: StellarAssetClient<'a>,
+    pub oracle: MockOracleClient<'a>,
+    pub registry: RegistryClient<'a>,
+    pub yield_token: YieldTokenClient<'a>,
+    pub usdc_vault: USDCVaultClient<'a>,
+    pub gold_vault: GoldVaultClient<'a>,
+    pub governance: GovernanceClient<'a>,
+    pub coffee: CoffeeCollateralClient<'a>,
+    pub rewards: RewardsClient<'a>,
+    pub receipts: ReceiptsClient<'a>,
+    pub escrow: EscrowClient<'a>,
+    pub allowlist: AllowlistClient<'a>,
+    pub hedging: HedgingClient<'a>,
+    pub multisig: MultisigClient<'a>,
+    pub timelock: TimelockClient<'a>,
+}
+
+impl<'a> Protocol<'a> {
+    /// Register and initialize the protocol with a five-member committee
+    pub fn setup(env: &'a Env) -> Self {
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+        // The deployed contracts run as WASM, which meters far above the native ones
+        env.budget().reset_unlimited();
+
+        let admin = Address::generate(env);
+
+        let usdc_sac = env.register_stellar_asset_contract_v2(admin.clone());
+        let usdc = TokenClient::new(env, &usdc_sac.address());
+        let usdc_admin = StellarAssetClient::new(env, &usdc_sac.address());
+
+        let gold_sac = env.register_stellar_asset_contract_v2(admin.clone());
+        let gold = TokenClient::new(env, &gold_sac.address());
+        let gold_admin = StellarAssetClient::new(env, &gold_sac.address());
+
+        let oracle = MockOracleClient::new(env, &env.register_contract(None, MockOracle));
+        oracle.set_price(&GOLD_PRICE);
+
+        let expertise = [
+            ExpertiseArea::CoffeeIndustry,
+            ExpertiseArea::RiskManagement,
+            ExpertiseArea::Trading,
+            ExpertiseArea::Agriculture,
+            ExpertiseArea::RiskManagement,
+        ];
+        let mut committee = Vec::new(env);
+        let mut members = Vec::new(env);
+        for area in expertise {
+            let member = Address::generate(env);
+            committee.push_back(member.clone());
+            members.push_back(CommitteeMember {
+                address: member,
+                expertise: area,
+                vote_weight: 1,
+            });
+        }
+
+        // The core contracts come up wired together from one deployer call
+        let deployer = DeployerClient::new(env, &env.register_contract(None, Deployer));
+        let addresses = deployer.deploy_protocol(
+            &admin,
+            &BytesN::from_array(env, &[0; 32]),
+            &upload_protocol_wasm(env),
+            &TokenMetadata {
+                decimal: 7,
+                name: String::from_str(env, "Coffee Yield Token"),
+                symbol: String::from_str(env, "CYT"),
+            },
+            &ProtocolConfig {
+                usdc_contract: usdc.address.clone(),
+                supported_gold_assets: vec![env, gold.address.clone()],
+                initial_committee: members,
+                min_proposal_tokens: MIN_PROPOSAL_TOKENS,
+                multisig_signers: committee.clone(),
+                multisig_threshold: 3,
+            },
+        );
+        let registry = RegistryClient::new(env, &addresses.registry);
+        let yield_token = YieldTokenClient::new(env, &addresses.yield_token);
+        let usdc_vault = USDCVaultClient::new(env, &addresses.usdc_vault);
+        let gold_vault = GoldVaultClient::new(env, &addresses.gold_vault);
+        let governance = GovernanceClient::new(env, &addresses.governance);
+        let coffee = CoffeeCollateralClient::new(env, &addresses.coffee_collateral);
+        let multisig = MultisigClient::new(env, &addresses.multisig);
+
+        // Price gold from the mock instead of the deployed oracle
+        registry.set_address(&admin, &Symbol::new(env, REGISTRY_ORACLE), &oracle.address);
+
+        let rewards = RewardsClient::new(env, &env.register_contract(None, Rewards));
+        rewards.initialize(&admin, &usdc.address, &(7 * DAY), &0);
+
+        let receipts = ReceiptsClient::new(env, &env.register_contract(None, Receipts));
+        receipts.initialize(&admin);
+
+        let escrow = EscrowClient::new(env, &env.register_contract(None, Escrow));
+        escrow.initialize(&admin);
+
+        let allowlist = AllowlistClient::new(env, &env.register_contract(None, Allowlist));
+        allowlist.initialize(&admin);
+
+        let hedging = HedgingClient::new(env, &env.register_contract(None, Hedging));
+        hedging.initialize(&admin, &governance.address, &coffee.address);
+
+        let timelock = TimelockClient::new(env, &env.register_contract(None, Timelock));
+        timelock.initialize(&governance.address, &TIMELOCK_MIN_DELAY);
+
+        for (name, address) in [
+            (REGISTRY_RECEIPTS, &receipts.address),
+            (REGISTRY_ESCROW, &escrow.address),
+            (REGISTRY_ALLOWLIST, &allowlist.address),
+            (REGISTRY_HEDGING, &hedging.address),
+        ] {
+            registry.set_address(&admin, &Symbol::new(env, name), address);
+        }
+
+        usdc_vault.set_rewards(&admin, &rewards.address);
+        gold_vault.set_rewards(&admin, &rewards.address);
+        hedging.set_registry(&admin, &registry.address);
+        for vault in [&usdc_vault.address, &gold_vault.address] {
+            rewards.add_vault(&admin, vault);
+            receipts.add_vault(&admin, vault);
+        }
+
+        Protocol {
+            env,
+            admin,
+            committee,
+            usdc,
+            usdc_admin,
+            gold,
+            gold_admin,
+            oracle,
+            registry,
+            yield_token,
+            usdc_vault,
+            gold_vault,
+            governance,
+            coffee,
+            rewards,
+            receipts,
+            escrow,
+            allowlist,
+            hedging,
+            multisig,
+            timelock,
+        }
+    }
+
+    /// Create a user holding `amount` USDC
+    pub fn funded_user(&self, amount: u128) -> Address {
+        let user = Address::generate(self.env);
+        self.usdc_admin.mint(&user, &(amount as i128));
+        user
+    }
+
+    /// Create a user holding `amount` of the gold asset
+    pub fn gold_holder(&self, amount: u128) -> Address {
+        let user = Address::generate(self.env);
+        self.gold_admin.mint(&user, &(amount as i128));
+        user
+    }
+
+    /// Fund the USDC vault's reserve that pays out accrued yield on withdrawal
+    pub fn fund_usdc_reserve(&self, amount: u128) {
+        self.usdc_admin
+            .mint(&self.usdc_vault.address, &(amount as i128));
+    }
+
+    /// Drop the mock from the registry so contracts fall back to their own price feeds
+    pub fn use_own_price_feeds(&self) {
+        self.registry
+            .remove_address(&self.admin, &Symbol::new(self.env, REGISTRY_ORACLE));
+    }
+
+    /// Committee member at `index`
+    pub fn member(&self, index: u32) -> Address {
+        self.committee.get(index).unwrap()
+    }
+
+    /// Move the ledger clock forward
+    pub fn advance_time(&self, seconds: u64) {
+        self.env.ledger().with_mut(|li| li.timestamp += seconds);
+    }
+}
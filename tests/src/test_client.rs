@@ -0,0 +1,386 @@
+//! The client's operation builders encode contract arguments by hand, so each
+//! one is decoded and invoked here against the contract it targets: a changed
+//! signature fails these tests rather than the signed transaction.
+
+use crate::setup::{Protocol, DAY, OUNCE, USDC};
+use shared::{
+    airdrop_leaf, merkle_parent, DepositTag, LockPeriod, ProtocolParameter, VoteType, WithdrawalCap,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, Symbol, TryFromVal, Val, Vec};
+use stellar_defi_vault_client::tx::{self, Result};
+use stellar_defi_vault_client::xdr::{HostFunction, Operation, OperationBody, ScAddress, ScVal};
+
+/// Decode a built operation and invoke it in the test `Env` as the network would
+fn submit<T: TryFromVal<Env, Val>>(env: &Env, operation: Result<Operation>) -> T {
+    let OperationBody::InvokeHostFunction(op) = operation.unwrap().body else {
+        panic!("Expected a host function invocation");
+    };
+    let HostFunction::InvokeContract(call) = op.host_function else {
+        panic!("Expected a contract invocation");
+    };
+
+    let contract = Address::try_from_val(env, &call.contract_address).unwrap();
+    let function = Symbol::try_from_val(env, &ScVal::Symbol(call.function_name)).unwrap();
+    let mut args = Vec::new(env);
+    for arg in call.args.iter() {
+        args.push_back(Val::try_from_val(env, arg).unwrap());
+    }
+    env.invoke_contract(&contract, &function, args)
+}
+
+/// Decode a single encoded argument back into its contract type
+fn decode<T: TryFromVal<Env, Val>>(env: &Env, value: Result<ScVal>) -> T {
+    let val = Val::try_from_val(env, &value.unwrap()).unwrap();
+    T::try_from_val(env, &val).unwrap()
+}
+
+fn sc(address: &Address) -> ScAddress {
+    address.into()
+}
+
+#[test]
+fn encoded_enums_decode_to_every_variant() {
+    let env = Env::default();
+
+    for lock_period in [
+        LockPeriod::ThreeMonths,
+        LockPeriod::SixMonths,
+        LockPeriod::TwelveMonths,
+    ] {
+        let decoded: LockPeriod = decode(&env, tx::lock_period_val(&lock_period));
+        assert_eq!(decoded, lock_period);
+    }
+
+    for vote in [VoteType::For, VoteType::Against, VoteType::Abstain] {
+        let decoded: VoteType = decode(&env, tx::vote_type_val(&vote));
+        assert_eq!(decoded, vote);
+    }
+
+    for parameter in [
+        ProtocolParameter::MinimumLockPeriod,
+        ProtocolParameter::MaximumYieldRate,
+        ProtocolParameter::CollateralRatio,
+        ProtocolParameter::ProtocolFeeRate,
+        ProtocolParameter::EmergencyWithdrawFee,
+        ProtocolParameter::TreasurySwap,
+        ProtocolParameter::FeeSwitch,
+    ] {
+        let decoded: ProtocolParameter = decode(&env, tx::parameter_val(&parameter));
+        assert_eq!(decoded, parameter);
+    }
+
+    let amount = u128::MAX - 7;
+    let decoded: u128 = decode(&env, Ok(tx::u128_val(amount)));
+    assert_eq!(decoded, amount);
+    let decoded: BytesN<32> = decode(&env, tx::bytes32(&[9; 32]));
+    assert_eq!(decoded, BytesN::from_array(&env, &[9; 32]));
+}
+
+#[test]
+fn usdc_deposit_operations_open_positions() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let vault = sc(&p.usdc_vault.address);
+    let amount = 1_000 * USDC;
+
+    let user = p.funded_user(amount);
+    let terms_hash: BytesN<32> = submit(
+        &env,
+        tx::usdc_deposit(&vault, &sc(&user), amount, &LockPeriod::SixMonths),
+    );
+    let receipt = p.usdc_vault.get_active_receipt(&user).unwrap();
+    assert_eq!(receipt.terms_hash, terms_hash);
+    assert_eq!(receipt.terms.amount, amount);
+    assert_eq!(receipt.terms.lock_period, LockPeriod::SixMonths);
+
+    // Exported receipts move through the receipts contract
+    let token_id: u64 = submit(&env, tx::export_receipt(&vault, &sc(&user)));
+    let custodian = Address::generate(&env);
+    let receipts = sc(&p.receipts.address);
+    submit::<()>(
+        &env,
+        tx::transfer_receipt(&receipts, &sc(&user), &sc(&custodian), token_id),
+    );
+    assert_eq!(p.receipts.owner_of(&token_id), custodian);
+
+    let tagged = p.funded_user(amount);
+    let exchange = Address::generate(&env);
+    submit::<BytesN<32>>(
+        &env,
+        tx::usdc_deposit_tagged(
+            &vault,
+            &sc(&tagged),
+            amount,
+            &LockPeriod::ThreeMonths,
+            &sc(&exchange),
+            42,
+        ),
+    );
+    assert_eq!(
+        p.usdc_vault.get_deposit_tag(&tagged),
+        Some(DepositTag {
+            integrator: exchange,
+            customer_id: 42,
+        })
+    );
+
+    // Operators and keepers pull from the user's allowance
+    let expiration = env.ledger().sequence() + 100_000;
+    let approved = p.funded_user(amount);
+    let operator = Address::generate(&env);
+    p.usdc_vault
+        .set_deposit_operator(&approved, &operator, &true);
+    p.usdc.approve(
+        &approved,
+        &p.usdc_vault.address,
+        &(amount as i128),
+        &expiration,
+    );
+    submit::<BytesN<32>>(
+        &env,
+        tx::usdc_deposit_from(
+            &vault,
+            &sc(&operator),
+            &sc(&approved),
+            amount,
+            &LockPeriod::TwelveMonths,
+        ),
+    );
+    let deposit = p.usdc_vault.get_deposit_info(&approved).unwrap();
+    assert_eq!(deposit.amount, amount);
+    assert_eq!(deposit.lock_period, LockPeriod::TwelveMonths);
+
+    let saver = p.funded_user(amount);
+    let keeper = Address::generate(&env);
+    p.usdc.approve(
+        &saver,
+        &p.usdc_vault.address,
+        &(amount as i128),
+        &expiration,
+    );
+    p.usdc_vault
+        .create_standing_order(&saver, &amount, &(30 * DAY), &LockPeriod::ThreeMonths);
+    assert!(submit::<bool>(
+        &env,
+        tx::execute_standing_order(&vault, &sc(&keeper), &sc(&saver)),
+    ));
+    assert_eq!(
+        p.usdc_vault.get_deposit_info(&saver).unwrap().amount,
+        amount
+    );
+}
+
+#[test]
+fn usdc_withdraw_operations_pay_out() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let vault = sc(&p.usdc_vault.address);
+    p.fund_usdc_reserve(500 * USDC);
+
+    let alice = p.funded_user(1_000 * USDC);
+    let bob = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&alice, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&bob, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.advance_time(91 * DAY);
+
+    let withdrawn: u128 = submit(&env, tx::usdc_withdraw(&vault, &sc(&alice)));
+    assert!(withdrawn > 1_000 * USDC);
+    assert_eq!(p.usdc.balance(&alice) as u128, withdrawn);
+
+    // A capped withdrawal leaves the rest in the queue for a later epoch
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(600 * USDC)));
+    p.advance_time(DAY);
+    assert_eq!(
+        submit::<u128>(&env, tx::usdc_withdraw(&vault, &sc(&bob))),
+        600 * USDC
+    );
+    let queued = p.usdc_vault.get_queued_withdrawal(&bob);
+    assert!(queued > 0);
+
+    p.advance_time(DAY);
+    assert_eq!(
+        submit::<u128>(&env, tx::claim_queued_withdrawal(&vault, &sc(&bob))),
+        queued
+    );
+    assert_eq!(p.usdc_vault.get_queued_withdrawal(&bob), 0);
+}
+
+#[test]
+fn gold_vault_operations_deposit_and_withdraw() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let vault = sc(&p.gold_vault.address);
+    let gold = sc(&p.gold.address);
+
+    let user = p.gold_holder(OUNCE);
+    submit::<()>(
+        &env,
+        tx::gold_deposit(&vault, &sc(&user), &gold, OUNCE, &LockPeriod::ThreeMonths),
+    );
+    let deposit = p.gold_vault.get_deposit_info(&user).unwrap();
+    assert_eq!(deposit.lock_period, LockPeriod::ThreeMonths);
+    assert_eq!(p.gold.balance(&user), 0);
+
+    let approved = p.gold_holder(OUNCE);
+    let operator = Address::generate(&env);
+    p.gold_vault
+        .set_deposit_operator(&approved, &operator, &true);
+    let expiration = env.ledger().sequence() + 1_000;
+    p.gold.approve(
+        &approved,
+        &p.gold_vault.address,
+        &(OUNCE as i128),
+        &expiration,
+    );
+    submit::<()>(
+        &env,
+        tx::gold_deposit_from(
+            &vault,
+            &sc(&operator),
+            &sc(&approved),
+            &gold,
+            OUNCE,
+            &LockPeriod::ThreeMonths,
+        ),
+    );
+    assert!(p.gold_vault.get_deposit_info(&approved).is_some());
+    assert_eq!(p.gold.balance(&approved), 0);
+
+    p.gold_admin.mint(&p.gold_vault.address, &(OUNCE as i128));
+    p.advance_time(91 * DAY);
+
+    let withdrawn: u128 = submit(&env, tx::gold_withdraw(&vault, &sc(&user)));
+    assert!(withdrawn > OUNCE);
+    assert_eq!(p.gold.balance(&user) as u128, withdrawn);
+
+    let withdrawn: u128 = submit(
+        &env,
+        tx::gold_withdraw_with_min(&vault, &sc(&approved), OUNCE),
+    );
+    assert!(withdrawn > OUNCE);
+    assert_eq!(p.gold.balance(&approved) as u128, withdrawn);
+}
+
+#[test]
+fn yield_token_operations_accrue_compound_and_stream() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let yield_token = sc(&p.yield_token.address);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::TwelveMonths);
+
+    p.advance_time(30 * DAY);
+    let preview = p.yield_token.accrued_balance(&user);
+    let accrued: u128 = submit(&env, tx::accrue(&yield_token, &sc(&user)));
+    assert_eq!(accrued, preview - amount);
+
+    p.yield_token.set_compound_tip(&p.admin, &500);
+    p.advance_time(30 * DAY);
+    let bot = Address::generate(&env);
+    let tip: u128 = submit(&env, tx::compound_for(&yield_token, &sc(&bot), &sc(&user)));
+    assert!(tip > 0);
+    assert_eq!(p.yield_token.get_keeper_tips(&bot), tip);
+
+    submit::<()>(&env, tx::set_yield_stream(&yield_token, &sc(&user), true));
+    assert!(p.yield_token.get_yield_stream(&user).is_some());
+
+    p.advance_time(10 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    let claimable = p.yield_token.get_yield_stream(&user).unwrap().claimable;
+    let vault = sc(&p.usdc_vault.address);
+    let claimed: u128 = submit(&env, tx::claim_stream(&vault, &sc(&user)));
+    assert!(claimed > 0);
+    assert_eq!(claimed, claimable);
+    assert_eq!(p.usdc.balance(&user) as u128, claimed);
+
+    submit::<()>(&env, tx::set_yield_stream(&yield_token, &sc(&user), false));
+    assert_eq!(p.yield_token.get_yield_stream(&user), None);
+}
+
+#[test]
+fn governance_operations_propose_and_vote() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let governance = sc(&p.governance.address);
+
+    let proposer = p.funded_user(5_000 * USDC);
+    p.usdc_vault
+        .deposit(&proposer, &(5_000 * USDC), &LockPeriod::TwelveMonths);
+
+    let proposal_id: BytesN<32> = submit(
+        &env,
+        tx::propose_parameter_change(
+            &governance,
+            &sc(&proposer),
+            &ProtocolParameter::MaximumYieldRate,
+            1_200,
+        ),
+    );
+    let proposal = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(proposal.parameter, ProtocolParameter::MaximumYieldRate);
+    assert_eq!(proposal.new_value, 1_200);
+
+    submit::<()>(
+        &env,
+        tx::vote(
+            &governance,
+            &sc(&proposer),
+            &proposal_id.to_array(),
+            &VoteType::Against,
+        ),
+    );
+    let proposal = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(proposal.votes_for, 0);
+    assert_eq!(proposal.votes_against, 5_000 * USDC);
+}
+
+#[test]
+fn rewards_operations_claim_emissions_and_airdrops() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let rewards = sc(&p.rewards.address);
+
+    p.rewards.set_emission(&p.admin, &(700 * USDC));
+    p.usdc_admin
+        .mint(&p.rewards.address, &((10_000 * USDC) as i128));
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    p.advance_time(7 * DAY);
+    let claimed: u128 = submit(&env, tx::claim_rewards(&rewards, &sc(&user)));
+    assert_eq!(claimed, 700 * USDC);
+    assert_eq!(p.usdc.balance(&user) as u128, claimed);
+
+    // A two-leaf tree: each claimant's proof is the other's leaf
+    let treasury = p.funded_user(300 * USDC);
+    p.rewards.set_treasury(&p.admin, &treasury);
+    let other = Address::generate(&env);
+    let leaf = airdrop_leaf(&env, &user, 100 * USDC);
+    let sibling = airdrop_leaf(&env, &other, 200 * USDC);
+    let root = merkle_parent(&env, &leaf, &sibling);
+    let airdrop_id = p
+        .rewards
+        .create_airdrop(&treasury, &p.usdc.address, &1, &root, &(300 * USDC));
+
+    let paid: u128 = submit(
+        &env,
+        tx::claim_airdrop(
+            &rewards,
+            &sc(&user),
+            airdrop_id,
+            100 * USDC,
+            &[sibling.to_array()],
+        ),
+    );
+    assert_eq!(paid, 100 * USDC);
+    assert_eq!(p.usdc.balance(&user) as u128, claimed + paid);
+}
@@ -0,0 +1,198 @@
+use crate::setup::{upload_protocol_wasm, GOLD_PRICE, MIN_PROPOSAL_TOKENS};
+use coffee_collateral::CoffeeCollateralClient;
+use deployer::{Deployer, DeployerClient};
+use gold_vault::GoldVaultClient;
+use governance::GovernanceClient;
+use multisig::MultisigClient;
+use oracle::PriceOracleClient;
+use registry::RegistryClient;
+use shared::{
+    CommitteeMember, ContractError, ExpertiseArea, ProtocolConfig, ProtocolWasmHashes, Role,
+    REGISTRY_GOVERNANCE, REGISTRY_ORACLE, TOTAL_COMMITTEE_SIZE,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{vec, Address, BytesN, Env, Error, String, Symbol, Vec};
+use soroban_token_sdk::metadata::TokenMetadata;
+use usdc_vault::USDCVaultClient;
+use yield_token::YieldTokenClient;
+
+/// A registered deployer with every protocol WASM uploaded and a config to deploy with
+struct Bootstrap<'a> {
+    deployer: DeployerClient<'a>,
+    wasm_hashes: ProtocolWasmHashes,
+    token_metadata: TokenMetadata,
+    config: ProtocolConfig,
+}
+
+impl<'a> Bootstrap<'a> {
+    fn new(env: &'a Env) -> Self {
+        env.mock_all_auths();
+        env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+
+        let wasm_hashes = upload_protocol_wasm(env);
+
+        let issuer = Address::generate(env);
+        let usdc = env.register_stellar_asset_contract_v2(issuer.clone());
+        let gold = env.register_stellar_asset_contract_v2(issuer);
+
+        let mut initial_committee = Vec::new(env);
+        let mut multisig_signers = Vec::new(env);
+        for _ in 0..TOTAL_COMMITTEE_SIZE {
+            let member = Address::generate(env);
+            multisig_signers.push_back(member.clone());
+            initial_committee.push_back(CommitteeMember {
+                address: member,
+                expertise: ExpertiseArea::CoffeeIndustry,
+                vote_weight: 1,
+            });
+        }
+
+        Bootstrap {
+            deployer: DeployerClient::new(env, &env.register_contract(None, Deployer)),
+            wasm_hashes,
+            token_metadata: TokenMetadata {
+                decimal: 7,
+                name: String::from_str(env, "Coffee Yield Token"),
+                symbol: String::from_str(env, "CYT"),
+            },
+            config: ProtocolConfig {
+                usdc_contract: usdc.address(),
+                supported_gold_assets: vec![env, gold.address()],
+                initial_committee,
+                min_proposal_tokens: MIN_PROPOSAL_TOKENS,
+                multisig_signers,
+                multisig_threshold: 3,
+            },
+        }
+    }
+}
+
+#[test]
+fn deploy_protocol_wires_its_own_oracle_and_hands_every_contract_to_the_admin() {
+    let env = Env::default();
+    let b = Bootstrap::new(&env);
+    let admin = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+
+    let addresses =
+        b.deployer
+            .deploy_protocol(&admin, &salt, &b.wasm_hashes, &b.token_metadata, &b.config);
+    assert_eq!(
+        b.deployer.get_deployment(&admin, &salt),
+        Some(addresses.clone())
+    );
+
+    let yield_token = YieldTokenClient::new(&env, &addresses.yield_token);
+    let gold_vault = GoldVaultClient::new(&env, &addresses.gold_vault);
+    let oracle = PriceOracleClient::new(&env, &addresses.oracle);
+    assert_eq!(yield_token.get_admin(), admin);
+    assert_eq!(
+        USDCVaultClient::new(&env, &addresses.usdc_vault).get_admin(),
+        admin
+    );
+    assert_eq!(gold_vault.get_admin(), admin);
+    assert_eq!(
+        GovernanceClient::new(&env, &addresses.governance).get_admin(),
+        admin
+    );
+    assert_eq!(
+        CoffeeCollateralClient::new(&env, &addresses.coffee_collateral).get_admin(),
+        admin
+    );
+    assert_eq!(oracle.get_admin(), admin);
+    assert!(yield_token.is_authorized_vault(&addresses.usdc_vault));
+    assert!(yield_token.is_authorized_vault(&addresses.gold_vault));
+
+    // Each contract records the admin it was deployed for, for pre-versioning migrations
+    let record = b.deployer.get_deployed_contract(&addresses.oracle).unwrap();
+    assert_eq!(record.admin, admin);
+    assert_eq!(
+        env.deployer()
+            .with_address(b.deployer.address.clone(), record.salt)
+            .deployed_address(),
+        addresses.oracle
+    );
+
+    // The multisig comes up with its signer set in the same transaction
+    let multisig = MultisigClient::new(&env, &addresses.multisig);
+    assert_eq!(multisig.get_signers(), b.config.multisig_signers);
+    assert_eq!(multisig.get_threshold(), 3);
+    assert!(multisig
+        .try_initialize(&vec![&env, admin.clone()], &1)
+        .is_err());
+
+    // Peers resolve each other through the registry deployed alongside them
+    let registry = RegistryClient::new(&env, &addresses.registry);
+    assert_eq!(registry.get_admin(), admin);
+    assert_eq!(
+        registry.get_address(&Symbol::new(&env, REGISTRY_ORACLE)),
+        Some(addresses.oracle.clone())
+    );
+    assert_eq!(
+        registry.get_address(&Symbol::new(&env, REGISTRY_GOVERNANCE)),
+        Some(addresses.governance.clone())
+    );
+
+    // The gold vault prices gold from the oracle deployed alongside it
+    let gold = b.config.supported_gold_assets.get(0).unwrap();
+    oracle.set_price(&admin, &Symbol::new(&env, "PAXG/USD"), &GOLD_PRICE);
+    assert_eq!(gold_vault.get_gold_price(&gold), GOLD_PRICE);
+}
+
+#[test]
+fn deployment_addresses_are_bound_to_the_admin() {
+    let env = Env::default();
+    let b = Bootstrap::new(&env);
+    let admin = Address::generate(&env);
+    let squatter = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+
+    // Reusing someone else's salt neither takes their addresses nor blocks their deployment
+    let squatted = b.deployer.deploy_protocol(
+        &squatter,
+        &salt,
+        &b.wasm_hashes,
+        &b.token_metadata,
+        &b.config,
+    );
+    assert_eq!(b.deployer.get_deployment(&admin, &salt), None);
+
+    let addresses =
+        b.deployer
+            .deploy_protocol(&admin, &salt, &b.wasm_hashes, &b.token_metadata, &b.config);
+    assert_ne!(addresses.yield_token, squatted.yield_token);
+    assert_ne!(addresses.oracle, squatted.oracle);
+    assert_eq!(b.deployer.get_deployment(&admin, &salt), Some(addresses));
+    assert_eq!(b.deployer.get_deployment(&squatter, &salt), Some(squatted));
+
+    // The same admin cannot deploy twice with one salt
+    assert!(b
+        .deployer
+        .try_deploy_protocol(&admin, &salt, &b.wasm_hashes, &b.token_metadata, &b.config)
+        .is_err());
+}
+
+#[test]
+fn a_paused_deployer_refuses_new_deployments() {
+    let env = Env::default();
+    let b = Bootstrap::new(&env);
+    let owner = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+
+    b.deployer.initialize(&owner);
+    b.deployer
+        .set_role(&owner, &Role::Pauser, &Some(pauser.clone()));
+    assert!(b.deployer.try_pause(&owner).is_err());
+    b.deployer.pause(&pauser);
+    assert_eq!(
+        b.deployer
+            .try_deploy_protocol(&admin, &salt, &b.wasm_hashes, &b.token_metadata, &b.config),
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+
+    b.deployer.unpause(&pauser);
+    b.deployer
+        .deploy_protocol(&admin, &salt, &b.wasm_hashes, &b.token_metadata, &b.config);
+}
@@ -0,0 +1,263 @@
+use crate::setup::{Protocol, DAY, USDC};
+use escrow::{Escrow, EscrowClient};
+use registry::{Registry, RegistryClient};
+use shared::{
+    EscrowStatus, HoldbackStatus, ProposalStatus, MAX_QA_HOLDBACK_BPS, QA_SAMPLE_WINDOW,
+    REGISTRY_USDC_VAULT,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, String, Symbol};
+
+fn register_escrow<'a>(p: &Protocol<'a>, partner: &Address) -> EscrowClient<'a> {
+    let escrow = EscrowClient::new(p.env, &p.env.register_contract(None, Escrow));
+    escrow.initialize(&p.admin);
+    escrow.add_partner(&p.admin, partner);
+    p.governance.set_escrow(&p.admin, &escrow.address);
+
+    let registry = RegistryClient::new(p.env, &p.env.register_contract(None, Registry));
+    registry.initialize(&p.admin);
+    registry.set_address(
+        &p.admin,
+        &Symbol::new(p.env, REGISTRY_USDC_VAULT),
+        &p.usdc_vault.address,
+    );
+    p.governance.set_registry(&p.admin, &registry.address);
+    escrow
+}
+
+fn executed_loan(p: &Protocol, amount: u128) -> (BytesN<32>, Address) {
+    let lot = p.coffee.create_coffee_asset(
+        &Address::generate(p.env),
+        &String::from_str(p.env, "COL-HUI-2024-007"),
+        &85,
+        &10_000,
+        &(amount * 3 / 2),
+        &String::from_str(p.env, "Huila, Colombia"),
+        &String::from_str(p.env, "2024-10"),
+    );
+    let borrower = Address::generate(p.env);
+    let loan_id =
+        p.governance
+            .submit_loan_proposal(&p.member(0), &borrower, &amount, &lot, &800, &180);
+    for i in 0..3 {
+        p.governance.approve_loan(&loan_id, &p.member(i));
+    }
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    assert_eq!(
+        p.governance.get_loan_proposal(&loan_id).unwrap().status,
+        ProposalStatus::Executed
+    );
+
+    // Loan funds are paid out of governance's holdings
+    p.usdc_admin.mint(&p.governance.address, &(amount as i128));
+    (loan_id, borrower)
+}
+
+#[test]
+fn partner_claims_escrowed_loan_funds_once_delivery_is_confirmed() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let partner = Address::generate(&env);
+    let escrow = register_escrow(&p, &partner);
+
+    let amount = 10_000 * USDC;
+    let (loan_id, borrower) = executed_loan(&p, amount);
+
+    // Only registered partners and timeouts within bounds are accepted
+    let outsider = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_disburse_to_escrow(
+            &p.member(0),
+            &loan_id,
+            &p.usdc.address,
+            &outsider,
+            &(7 * DAY)
+        )
+        .is_err());
+    assert!(p
+        .governance
+        .try_disburse_to_escrow(
+            &p.member(0),
+            &loan_id,
+            &p.usdc.address,
+            &partner,
+            &(60 * DAY)
+        )
+        .is_err());
+
+    let escrow_id = p.governance.disburse_to_escrow(
+        &p.member(0),
+        &loan_id,
+        &p.usdc.address,
+        &partner,
+        &(7 * DAY),
+    );
+    assert_eq!(p.governance.get_loan_escrow(&loan_id), Some(escrow_id));
+    assert_eq!(p.usdc.balance(&escrow.address), amount as i128);
+    assert_eq!(escrow.get_reserved(&p.usdc.address), amount);
+    assert!(p
+        .governance
+        .try_disburse_to_escrow(
+            &p.member(0),
+            &loan_id,
+            &p.usdc.address,
+            &partner,
+            &(7 * DAY)
+        )
+        .is_err());
+
+    let record = escrow.get_escrow(&escrow_id).unwrap();
+    assert_eq!(record.recipient, borrower);
+    assert_eq!(record.refund_to, p.usdc_vault.address);
+    assert_eq!(record.status, EscrowStatus::Open);
+
+    // The partner is paid only after the borrower confirms the fiat arrived
+    assert!(escrow.try_claim(&partner, &escrow_id).is_err());
+    let delivery_ref = BytesN::from_array(&env, &[7; 32]);
+    assert!(escrow
+        .try_confirm_delivery(&partner, &escrow_id, &delivery_ref)
+        .is_err());
+    escrow.confirm_delivery(&borrower, &escrow_id, &delivery_ref);
+    assert!(escrow.try_refund(&escrow_id).is_err());
+
+    assert_eq!(escrow.claim(&partner, &escrow_id), amount);
+    assert_eq!(p.usdc.balance(&partner), amount as i128);
+    assert_eq!(escrow.get_reserved(&p.usdc.address), 0);
+    let claimed = escrow.get_escrow(&escrow_id).unwrap();
+    assert_eq!(claimed.status, EscrowStatus::Claimed);
+    assert_eq!(claimed.delivery_ref, Some(delivery_ref));
+    assert!(escrow.try_claim(&partner, &escrow_id).is_err());
+}
+
+#[test]
+fn unconfirmed_escrows_are_refunded_to_the_vault_after_the_timeout() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let partner = Address::generate(&env);
+    let escrow = register_escrow(&p, &partner);
+
+    let amount = 5_000 * USDC;
+    let (loan_id, borrower) = executed_loan(&p, amount);
+    let escrow_id = p.governance.disburse_to_escrow(
+        &p.member(0),
+        &loan_id,
+        &p.usdc.address,
+        &partner,
+        &(3 * DAY),
+    );
+
+    // Funds stay put until the timeout passes
+    p.advance_time(3 * DAY);
+    assert!(escrow.try_refund(&escrow_id).is_err());
+    p.advance_time(1);
+    assert!(escrow
+        .try_confirm_delivery(&borrower, &escrow_id, &BytesN::from_array(&env, &[1; 32]))
+        .is_err());
+
+    let vault_before = p.usdc.balance(&p.usdc_vault.address);
+    assert_eq!(escrow.refund(&escrow_id), amount);
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address),
+        vault_before + amount as i128
+    );
+    assert_eq!(
+        escrow.get_escrow(&escrow_id).unwrap().status,
+        EscrowStatus::Refunded
+    );
+    assert!(escrow.try_claim(&partner, &escrow_id).is_err());
+}
+
+#[test]
+fn qa_holdbacks_settle_on_the_lots_sample_result() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let partner = Address::generate(&env);
+    let lab = Address::generate(&env);
+    let escrow = register_escrow(&p, &partner);
+    escrow.add_qa_lab(&p.admin, &lab);
+    assert!(p
+        .governance
+        .try_set_qa_holdback(&p.admin, &(MAX_QA_HOLDBACK_BPS + 1))
+        .is_err());
+    p.governance.set_qa_holdback(&p.admin, &500);
+
+    // 5% of the proceeds waits on the lot's sample
+    let amount = 10_000 * USDC;
+    let (loan_id, borrower) = executed_loan(&p, amount);
+    let escrow_id = p.governance.disburse_to_escrow(
+        &p.member(0),
+        &loan_id,
+        &p.usdc.address,
+        &partner,
+        &(7 * DAY),
+    );
+    assert_eq!(escrow.get_escrow(&escrow_id).unwrap().amount, 9_500 * USDC);
+    let holdback_id = p.governance.get_loan_holdback(&loan_id).unwrap();
+    let holdback = escrow.get_holdback(&holdback_id).unwrap();
+    assert_eq!(holdback.amount, 500 * USDC);
+    assert_eq!(
+        Some(holdback.lot),
+        p.governance
+            .get_loan_proposal(&loan_id)
+            .map(|loan| loan.collateral)
+    );
+    assert_eq!(escrow.get_reserved(&p.usdc.address), amount);
+
+    // Only a registered lab can post, and a passing sample pays the borrower
+    let sample = BytesN::from_array(&env, &[9; 32]);
+    assert!(escrow
+        .try_post_sample_result(&partner, &holdback_id, &sample, &true)
+        .is_err());
+    assert!(escrow.try_claw_back_holdback(&holdback_id).is_err());
+    assert_eq!(
+        escrow.post_sample_result(&lab, &holdback_id, &sample, &true),
+        500 * USDC
+    );
+    assert_eq!(p.usdc.balance(&borrower), (500 * USDC) as i128);
+    let released = escrow.get_holdback(&holdback_id).unwrap();
+    assert_eq!(released.status, HoldbackStatus::Released);
+    assert_eq!(released.sample_hash, Some(sample.clone()));
+    assert_eq!(released.lab, Some(lab.clone()));
+    assert!(escrow
+        .try_post_sample_result(&lab, &holdback_id, &sample, &false)
+        .is_err());
+
+    // A failing sample claws the holdback back to the vault
+    let (second_loan, second_borrower) = executed_loan(&p, amount);
+    p.governance.disburse_to_escrow(
+        &p.member(0),
+        &second_loan,
+        &p.usdc.address,
+        &partner,
+        &(7 * DAY),
+    );
+    let failed_id = p.governance.get_loan_holdback(&second_loan).unwrap();
+    let vault_before = p.usdc.balance(&p.usdc_vault.address);
+    escrow.post_sample_result(&lab, &failed_id, &sample, &false);
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address),
+        vault_before + (500 * USDC) as i128
+    );
+    assert_eq!(p.usdc.balance(&second_borrower), 0);
+    assert_eq!(
+        escrow.get_holdback(&failed_id).unwrap().status,
+        HoldbackStatus::ClawedBack
+    );
+
+    // Unsampled holdbacks return to the vault once the window closes
+    let (third_loan, _) = executed_loan(&p, amount);
+    p.governance.disburse_to_escrow(
+        &p.member(0),
+        &third_loan,
+        &p.usdc.address,
+        &partner,
+        &(7 * DAY),
+    );
+    let unsampled_id = p.governance.get_loan_holdback(&third_loan).unwrap();
+    p.advance_time(QA_SAMPLE_WINDOW + 1);
+    assert!(escrow
+        .try_post_sample_result(&lab, &unsampled_id, &sample, &true)
+        .is_err());
+    assert_eq!(escrow.claw_back_holdback(&unsampled_id), 500 * USDC);
+}
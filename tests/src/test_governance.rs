@@ -0,0 +1,1418 @@
+use crate::mocks::{
+    MockOracle, MockOracleClient, MockRouter, MockRouterClient, MockStrategy, MockStrategyClient,
+};
+use crate::setup::{Protocol, DAY, MIN_PROPOSAL_TOKENS, USDC};
+use registry::{Registry, RegistryClient};
+use shared::{
+    ContractError, FeeSwitch, FeeSwitchChange, LockPeriod, ProposalKind, ProposalStatus,
+    ProtocolParameter, RevenueStream, TradeParams, TradeSweep, TreasuryTarget, VoteType,
+    VotingRules, BENEFICIARY_CHANGE_DELAY, DEFAULT_VOTING_PERIOD, EXECUTION_PRICE_PRECISION,
+    LEDGER_CLOSE_SECONDS, MAX_FEE_PARAMETER_BPS, MAX_YIELD_RATE_BPS, MEMBERSHIP_ACCEPTANCE_WINDOW,
+    MIN_COLLATERAL_RATIO_BPS, REGISTRY_ORACLE, REGISTRY_TIMELOCK, REGISTRY_TREASURY,
+    SIMPLE_MAJORITY_BPS, TIMELOCK_MIN_DELAY, USDC_PEG_PRICE,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{vec, Address, BytesN, Env, Error, Symbol};
+
+/// Deposit into the USDC vault so the user holds yield tokens (voting power)
+fn token_holder(p: &Protocol, amount: u128) -> Address {
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::TwelveMonths);
+    user
+}
+
+#[test]
+fn parameter_change_passes_after_voting_period() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let opponent = token_holder(&p, 2_000 * USDC);
+
+    let proposal_id = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&opponent, &proposal_id, &VoteType::Against);
+    assert!(p
+        .governance
+        .try_vote_on_proposal(&opponent, &proposal_id, &VoteType::For)
+        .is_err());
+
+    let proposal = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(proposal.id, proposal_id);
+    assert_eq!(proposal.votes_for, 5_000 * USDC);
+    assert_eq!(proposal.votes_against, 2_000 * USDC);
+
+    // Execution waits for the 7-day voting window to close
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+
+    p.advance_time(7 * DAY + 1);
+    let late_voter = token_holder(&p, 3_000 * USDC);
+    assert!(p
+        .governance
+        .try_vote_on_proposal(&late_voter, &proposal_id, &VoteType::Against)
+        .is_err());
+
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    let executed = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(executed.status, ProposalStatus::Executed);
+    assert_eq!(executed.new_value, 1_200);
+}
+
+#[test]
+fn parameter_change_rejected_by_vote() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 1_500 * USDC);
+    let opponent = token_holder(&p, 4_000 * USDC);
+
+    let proposal_id =
+        p.governance
+            .propose_parameter_change(&proposer, &ProtocolParameter::ProtocolFeeRate, &300);
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&opponent, &proposal_id, &VoteType::Against);
+
+    p.advance_time(7 * DAY + 1);
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+    assert_ne!(
+        p.governance
+            .get_governance_proposal(&proposal_id)
+            .unwrap()
+            .status,
+        ProposalStatus::Executed
+    );
+}
+
+#[test]
+fn abstentions_count_toward_quorum_only() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 2_000 * USDC);
+    let opponent = token_holder(&p, 1_000 * USDC);
+    let abstainer = token_holder(&p, 4_000 * USDC);
+    p.governance.set_quorum(&p.admin, &(6_000 * USDC));
+
+    let proposal_id = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&opponent, &proposal_id, &VoteType::Against);
+    assert!(!p.governance.get_vote_tally(&proposal_id).quorum_reached);
+
+    p.governance
+        .vote_on_proposal(&abstainer, &proposal_id, &VoteType::Abstain);
+    let tally = p.governance.get_vote_tally(&proposal_id);
+    assert_eq!(tally.votes_for, 2_000 * USDC);
+    assert_eq!(tally.votes_against, 1_000 * USDC);
+    assert_eq!(tally.votes_abstain, 4_000 * USDC);
+    assert!(tally.quorum_reached);
+    assert_eq!(
+        p.governance.get_vote(&proposal_id, &abstainer),
+        Some(VoteType::Abstain)
+    );
+
+    // The abstainer's larger balance does not sway the outcome
+    p.advance_time(7 * DAY + 1);
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    assert_eq!(
+        p.governance
+            .get_governance_proposal(&proposal_id)
+            .unwrap()
+            .status,
+        ProposalStatus::Executed
+    );
+
+    // Without enough turnout a majority is not enough
+    let second =
+        p.governance
+            .propose_parameter_change(&proposer, &ProtocolParameter::ProtocolFeeRate, &300);
+    p.governance
+        .vote_on_proposal(&proposer, &second, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &second)
+        .is_err());
+}
+
+#[test]
+fn votes_open_after_the_review_period() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    assert!(p
+        .governance
+        .try_set_review_period(&p.admin, &(8 * DAY))
+        .is_err());
+    p.governance.set_review_period(&p.admin, &(2 * DAY));
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let proposal_id = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+    let proposal = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    let submitted_at = env.ledger().timestamp();
+    assert_eq!(proposal.voting_start, submitted_at + 2 * DAY);
+    assert_eq!(proposal.voting_deadline, proposal.voting_start + 7 * DAY);
+
+    // Nobody can vote while the proposal is under review
+    assert!(p
+        .governance
+        .try_vote_on_proposal(&proposer, &proposal_id, &VoteType::For)
+        .is_err());
+    p.advance_time(2 * DAY);
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+
+    // The full voting window still follows the review period
+    p.advance_time(7 * DAY);
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+    p.advance_time(1);
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+}
+
+#[test]
+fn parameter_changes_are_held_to_hard_bounds() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let out_of_bounds = Err(Ok(Error::from_contract_error(
+        ContractError::ParameterOutOfBounds as u32,
+    )));
+    assert_eq!(
+        p.governance.try_propose_parameter_change(
+            &proposer,
+            &ProtocolParameter::CollateralRatio,
+            &(MIN_COLLATERAL_RATIO_BPS - 1),
+        ),
+        out_of_bounds
+    );
+    assert_eq!(
+        p.governance.try_propose_parameter_change(
+            &proposer,
+            &ProtocolParameter::EmergencyWithdrawFee,
+            &(MAX_FEE_PARAMETER_BPS + 1),
+        ),
+        out_of_bounds
+    );
+    assert_eq!(
+        p.governance.try_propose_parameter_change(
+            &proposer,
+            &ProtocolParameter::MaximumYieldRate,
+            &(MAX_YIELD_RATE_BPS + 1),
+        ),
+        out_of_bounds
+    );
+
+    // The bounds themselves are valid values
+    p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::CollateralRatio,
+        &MIN_COLLATERAL_RATIO_BPS,
+    );
+    p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::ProtocolFeeRate,
+        &MAX_FEE_PARAMETER_BPS,
+    );
+}
+
+#[test]
+fn proposing_requires_minimum_tokens() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let small_holder = token_holder(&p, MIN_PROPOSAL_TOKENS - USDC);
+    let result = p.governance.try_propose_parameter_change(
+        &small_holder,
+        &ProtocolParameter::CollateralRatio,
+        &17_500,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn shutdown_settles_vault_pro_rata() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Governance finds the vault through the registry and is allowed to settle it
+    p.yield_token
+        .set_governance(&p.admin, &p.governance.address);
+    p.usdc_vault.set_governance(&p.admin, &p.governance.address);
+
+    let small = token_holder(&p, 1_000 * USDC);
+    let large = token_holder(&p, 3_000 * USDC);
+
+    // The vault loses a tenth of its USDC
+    p.usdc.burn(&p.usdc_vault.address, &((400 * USDC) as i128));
+    p.advance_time(10 * DAY);
+
+    p.governance.approve_shutdown(&p.member(0));
+    p.governance.approve_shutdown(&p.member(1));
+    assert!(p.governance.try_approve_shutdown(&p.member(1)).is_err());
+    assert_eq!(p.governance.get_settled_at(), None);
+
+    p.governance.approve_shutdown(&p.member(2));
+    let settled_at = env.ledger().timestamp();
+    assert_eq!(p.governance.get_settled_at(), Some(settled_at));
+    assert_eq!(p.yield_token.get_settled_at(), Some(settled_at));
+
+    let snapshot = p.usdc_vault.get_settlement().unwrap();
+    assert_eq!(snapshot.held_value, 3_600 * USDC);
+    assert_eq!(snapshot.recorded_liquidity, 4_000 * USDC);
+
+    // Yield is frozen and the normal paths are closed
+    let frozen = p.yield_token.accrued_balance(&small);
+    p.advance_time(30 * DAY);
+    assert_eq!(p.yield_token.accrued_balance(&small), frozen);
+    assert_eq!(
+        p.usdc_vault.try_withdraw(&small),
+        Err(Ok(Error::from_contract_error(
+            ContractError::GlobalSettlement as u32
+        )))
+    );
+    let late = p.funded_user(USDC);
+    assert!(p
+        .usdc_vault
+        .try_deposit(&late, &USDC, &LockPeriod::ThreeMonths)
+        .is_err());
+    assert!(p
+        .governance
+        .try_submit_loan_proposal(&p.member(0), &late, &USDC, &late, &800, &180)
+        .is_err());
+
+    // Locks are waived and each depositor gets the same fraction back
+    assert_eq!(p.usdc_vault.redeem_settlement(&small), 900 * USDC);
+    assert_eq!(p.usdc_vault.redeem_settlement(&large), 2_700 * USDC);
+    assert_eq!(p.usdc.balance(&p.usdc_vault.address), 0);
+    assert_eq!(p.yield_token.balance(&small), 0);
+    assert!(p.usdc_vault.try_redeem_settlement(&small).is_err());
+}
+
+#[test]
+fn only_a_committee_shutdown_settles_and_late_funds_are_shared_on_refresh() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    p.yield_token
+        .set_governance(&p.admin, &p.governance.address);
+    let small = token_holder(&p, 1_000 * USDC);
+    let large = token_holder(&p, 3_000 * USDC);
+
+    // A strategy holding a quarter of the USDC cannot return it when settlement starts
+    let strategy = MockStrategyClient::new(&env, &env.register_contract(None, MockStrategy));
+    p.usdc_vault
+        .register_strategy(&p.admin, &strategy.address, &3_000);
+    p.usdc_vault
+        .allocate(&p.admin, &strategy.address, &(1_000 * USDC));
+    p.usdc.burn(&strategy.address, &((1_000 * USDC) as i128));
+
+    // Nothing to redeem or refresh before settlement, and neither the admin nor a
+    // depositor can start it without the committee
+    assert!(p.usdc_vault.try_redeem_settlement(&small).is_err());
+    assert!(p.usdc_vault.try_refresh_settlement().is_err());
+    assert!(p.usdc_vault.try_enter_settlement(&p.admin).is_err());
+    assert!(p.usdc_vault.try_enter_settlement(&small).is_err());
+    assert!(p.gold_vault.try_enter_settlement(&p.admin).is_err());
+    assert_eq!(p.usdc_vault.get_settlement(), None);
+
+    for member in 0..3 {
+        p.governance.approve_shutdown(&p.member(member));
+    }
+    let settled_at = env.ledger().timestamp();
+    let snapshot = p.usdc_vault.get_settlement().unwrap();
+    assert_eq!(snapshot.held_value, 3_000 * USDC);
+    assert_eq!(snapshot.recorded_liquidity, 4_000 * USDC);
+    assert_eq!(
+        p.usdc_vault.try_enter_settlement(&p.governance.address),
+        Err(Ok(Error::from_contract_error(
+            ContractError::GlobalSettlement as u32
+        )))
+    );
+
+    // Once the strategy recovers, anyone can re-snapshot and every depositor is made whole
+    p.usdc_admin
+        .mint(&strategy.address, &((1_000 * USDC) as i128));
+    p.advance_time(DAY);
+    let refreshed = p.usdc_vault.refresh_settlement();
+    assert_eq!(refreshed.settled_at, settled_at);
+    assert_eq!(refreshed.held_value, 4_000 * USDC);
+    assert_eq!(p.usdc_vault.get_total_allocated(), 0);
+
+    assert_eq!(p.usdc_vault.redeem_settlement(&small), 1_000 * USDC);
+    assert!(p.usdc_vault.try_redeem_settlement(&small).is_err());
+    assert!(p.usdc_vault.try_redeem_settlement(&p.admin).is_err());
+    assert_eq!(p.usdc_vault.redeem_settlement(&large), 3_000 * USDC);
+    assert_eq!(p.usdc.balance(&p.usdc_vault.address), 0);
+}
+
+#[test]
+fn large_trades_fill_in_slices_at_a_weighted_price() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    StellarAssetClient::new(&env, &gold.address).mint(&router.address, &1_000_000_000);
+    p.usdc_admin
+        .mint(&p.governance.address, &((2_000 * USDC) as i128));
+    p.governance.set_trade_router(&p.admin, &router.address);
+
+    let params = TradeParams {
+        asset_in: p.usdc.address.clone(),
+        asset_out: gold.address.clone(),
+        amount_in: 2_000 * USDC,
+        min_amount_out: 10_000_000,
+        deadline: env.ledger().timestamp() + DAY,
+    };
+    let trade_id = p.governance.submit_trade_proposal(&p.member(0), &params);
+    assert_eq!(p.governance.get_trade_remaining(&trade_id), 2_000 * USDC);
+
+    // Each slice must meet its share of the minimum out
+    router.set_output(&2_400_000);
+    assert_eq!(
+        p.governance
+            .try_execute_trade_partial(&p.member(1), &trade_id, &(500 * USDC)),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+    router.set_output(&2_600_000);
+    assert_eq!(
+        p.governance
+            .execute_trade_partial(&p.member(1), &trade_id, &(500 * USDC)),
+        2_600_000
+    );
+    assert_eq!(p.governance.get_trade_remaining(&trade_id), 1_500 * USDC);
+    assert_eq!(p.governance.get_trade_record(&trade_id), None);
+    assert!(p
+        .governance
+        .try_execute_trade_partial(&p.member(1), &trade_id, &(1_600 * USDC))
+        .is_err());
+
+    // Executing the trade fills the remainder and closes it
+    router.set_output(&7_600_000);
+    p.governance.execute_trade(&p.member(2), &trade_id);
+    let fill = p.governance.get_trade_fill(&trade_id).unwrap();
+    assert_eq!(fill.filled_in, 2_000 * USDC);
+    assert_eq!(fill.filled_out, 10_200_000);
+    assert_eq!(fill.slices, 2);
+    let record = p.governance.get_trade_record(&trade_id).unwrap();
+    assert_eq!(record.amount_out, 10_200_000);
+    assert_eq!(
+        record.execution_price,
+        10_200_000 * EXECUTION_PRICE_PRECISION / (2_000 * USDC)
+    );
+    assert_eq!(record.executor, p.member(2));
+    assert_eq!(gold.balance(&p.governance.address), 10_200_000);
+    assert_eq!(p.governance.get_trade_remaining(&trade_id), 0);
+    assert_eq!(p.governance.get_open_trades().len(), 0);
+}
+
+#[test]
+fn approved_limit_orders_fill_once_the_oracle_crosses_the_limit() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    StellarAssetClient::new(&env, &gold.address)
+        .mint(&p.governance.address, &((10 * USDC) as i128));
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    p.usdc_admin
+        .mint(&router.address, &((30_000 * USDC) as i128));
+    p.governance.set_trade_router(&p.admin, &router.address);
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    oracle.set_price(&(1_950 * USDC_PEG_PRICE));
+    let registry = RegistryClient::new(&env, &env.register_contract(None, Registry));
+    registry.initialize(&p.admin);
+    registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_ORACLE),
+        &oracle.address,
+    );
+    p.governance.set_registry(&p.admin, &registry.address);
+
+    // Sell 10 gold for at least $2,000 each, with $19,000 as the overall floor
+    let params = TradeParams {
+        asset_in: gold.address.clone(),
+        asset_out: p.usdc.address.clone(),
+        amount_in: 10 * USDC,
+        min_amount_out: 19_000 * USDC,
+        deadline: env.ledger().timestamp() + 10 * DAY,
+    };
+    let feed = Symbol::new(&env, "XAU/USD");
+    assert!(p
+        .governance
+        .try_submit_limit_order(&p.member(0), &params, &feed, &0)
+        .is_err());
+    let trade_id =
+        p.governance
+            .submit_limit_order(&p.member(0), &params, &feed, &(2_000 * USDC_PEG_PRICE));
+    assert_eq!(
+        p.governance.get_limit_order(&trade_id).unwrap().approvals,
+        1
+    );
+
+    // Keepers wait for the committee approvals
+    let keeper = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_execute_limit_order(&keeper, &trade_id)
+        .is_err());
+    assert!(p
+        .governance
+        .try_approve_limit_order(&p.member(0), &trade_id)
+        .is_err());
+    assert!(!p.governance.approve_limit_order(&p.member(1), &trade_id));
+    assert!(p.governance.approve_limit_order(&p.member(2), &trade_id));
+
+    // Limit orders only execute through the keeper path, and only past the limit
+    assert!(p
+        .governance
+        .try_execute_trade(&p.member(1), &trade_id)
+        .is_err());
+    assert!(p
+        .governance
+        .try_execute_limit_order(&keeper, &trade_id)
+        .is_err());
+
+    // A fill below the limit price is refused even when it clears the trade's minimum
+    oracle.set_price(&(2_050 * USDC_PEG_PRICE));
+    router.set_output(&(19_500 * USDC));
+    assert_eq!(
+        p.governance.try_execute_limit_order(&keeper, &trade_id),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+
+    router.set_output(&(20_400 * USDC));
+    assert_eq!(
+        p.governance.execute_limit_order(&keeper, &trade_id),
+        20_400 * USDC
+    );
+    let record = p.governance.get_trade_record(&trade_id).unwrap();
+    assert_eq!(record.amount_out, 20_400 * USDC);
+    assert_eq!(record.executor, keeper);
+    assert_eq!(gold.balance(&p.governance.address), 0);
+    assert_eq!(p.governance.get_open_trades().len(), 0);
+}
+
+#[test]
+fn treasury_swaps_pass_a_vote_and_stay_within_the_target_allocation() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let treasury = Address::generate(&env);
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    oracle.set_price(&USDC_PEG_PRICE);
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    p.governance.set_trade_router(&p.admin, &router.address);
+    let registry = RegistryClient::new(&env, &env.register_contract(None, Registry));
+    registry.initialize(&p.admin);
+    registry.set_address(&p.admin, &Symbol::new(&env, REGISTRY_TREASURY), &treasury);
+    registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_ORACLE),
+        &oracle.address,
+    );
+    p.governance.set_registry(&p.admin, &registry.address);
+
+    // Fees have left the treasury 70% gold against a 50/50 target
+    StellarAssetClient::new(&env, &gold.address).mint(&treasury, &((7_000 * USDC) as i128));
+    p.usdc_admin.mint(&treasury, &((3_000 * USDC) as i128));
+    p.usdc_admin
+        .mint(&router.address, &((5_000 * USDC) as i128));
+    let target = |asset: &Address, feed: &str| TreasuryTarget {
+        asset: asset.clone(),
+        price_feed: Symbol::new(&env, feed),
+        target_bps: 5_000,
+    };
+    let mut targets = vec![&env, target(&gold.address, "XAU/USD")];
+    assert!(p
+        .governance
+        .try_set_treasury_allocation(&p.admin, &targets)
+        .is_err());
+    targets.push_back(target(&p.usdc.address, "USDC/USD"));
+    p.governance.set_treasury_allocation(&p.admin, &targets);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let swap = |asset_in: &Address, asset_out: &Address, amount_in: u128| TradeParams {
+        asset_in: asset_in.clone(),
+        asset_out: asset_out.clone(),
+        amount_in,
+        min_amount_out: amount_in * 99 / 100,
+        deadline: env.ledger().timestamp() + 10 * DAY,
+    };
+    // Swaps must carry their terms and move holdings towards the targets, not past them
+    assert!(p
+        .governance
+        .try_propose_parameter_change(&proposer, &ProtocolParameter::TreasurySwap, &1)
+        .is_err());
+    assert!(p
+        .governance
+        .try_propose_treasury_swap(
+            &proposer,
+            &swap(&p.usdc.address, &gold.address, 1_000 * USDC)
+        )
+        .is_err());
+    assert!(p
+        .governance
+        .try_propose_treasury_swap(
+            &proposer,
+            &swap(&gold.address, &p.usdc.address, 2_500 * USDC)
+        )
+        .is_err());
+    let mut expiring = swap(&gold.address, &p.usdc.address, 2_000 * USDC);
+    expiring.deadline = env.ledger().timestamp() + DAY;
+    assert!(p
+        .governance
+        .try_propose_treasury_swap(&proposer, &expiring)
+        .is_err());
+
+    let terms = swap(&gold.address, &p.usdc.address, 2_000 * USDC);
+    let proposal_id = p.governance.propose_treasury_swap(&proposer, &terms);
+    assert_eq!(p.governance.get_treasury_swap(&proposal_id), Some(terms));
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+
+    // The router's short delivery is caught against the treasury's own balance
+    router.set_output(&(1_900 * USDC));
+    assert_eq!(
+        p.governance
+            .try_execute_governance_proposal(&proposer, &proposal_id),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+
+    router.set_output(&(1_995 * USDC));
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    assert_eq!(gold.balance(&treasury), (5_000 * USDC) as i128);
+    assert_eq!(p.usdc.balance(&treasury), (4_995 * USDC) as i128);
+    let record = p.governance.get_trade_record(&proposal_id).unwrap();
+    assert_eq!(record.amount_out, 1_995 * USDC);
+    assert_eq!(record.executor, proposer);
+    assert_eq!(
+        p.governance
+            .get_governance_proposal(&proposal_id)
+            .unwrap()
+            .status,
+        ProposalStatus::Executed
+    );
+}
+
+#[test]
+fn trade_execution_enforces_min_amount_out_on_chain() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    StellarAssetClient::new(&env, &gold.address).mint(&router.address, &1_000_000_000);
+    p.usdc_admin
+        .mint(&p.governance.address, &((4_000 * USDC) as i128));
+
+    let params = TradeParams {
+        asset_in: p.usdc.address.clone(),
+        asset_out: gold.address.clone(),
+        amount_in: 2_000 * USDC,
+        min_amount_out: 10_000_000,
+        deadline: env.ledger().timestamp() + DAY,
+    };
+    let trade_id = p.governance.submit_trade_proposal(&p.member(0), &params);
+    assert!(p
+        .governance
+        .try_execute_trade(&p.member(1), &trade_id)
+        .is_err());
+    p.governance.set_trade_router(&p.admin, &router.address);
+
+    // The router reports success but delivers short: the trade reverts
+    router.set_output(&9_900_000);
+    assert_eq!(
+        p.governance.try_execute_trade(&p.member(1), &trade_id),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+    assert_eq!(
+        p.usdc.balance(&p.governance.address),
+        (4_000 * USDC) as i128
+    );
+
+    router.set_output(&10_050_000);
+    p.governance.execute_trade(&p.member(1), &trade_id);
+
+    let record = p.governance.get_trade_record(&trade_id).unwrap();
+    assert_eq!(record.amount_out, 10_050_000);
+    assert_eq!(
+        record.execution_price,
+        10_050_000 * EXECUTION_PRICE_PRECISION / (2_000 * USDC)
+    );
+    assert_eq!(record.executor, p.member(1));
+    assert_eq!(gold.balance(&p.governance.address), 10_050_000);
+    assert!(p
+        .governance
+        .try_execute_trade(&p.member(1), &trade_id)
+        .is_err());
+}
+
+#[test]
+fn expired_trades_are_cancelled_or_swept() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let trade = |deadline: u64| TradeParams {
+        asset_in: p.usdc.address.clone(),
+        asset_out: Address::generate(&env),
+        amount_in: 1_000 * USDC,
+        min_amount_out: 1,
+        deadline,
+    };
+    let now = env.ledger().timestamp();
+    let cancelled = p
+        .governance
+        .submit_trade_proposal(&p.member(0), &trade(now + DAY));
+    let first = p
+        .governance
+        .submit_trade_proposal(&p.member(0), &trade(now + DAY));
+    let second = p
+        .governance
+        .submit_trade_proposal(&p.member(1), &trade(now + 2 * DAY));
+    let live = p
+        .governance
+        .submit_trade_proposal(&p.member(1), &trade(now + 10 * DAY));
+
+    // Only the proposer can withdraw a trade
+    assert!(p
+        .governance
+        .try_cancel_trade(&p.member(1), &cancelled)
+        .is_err());
+    p.governance.cancel_trade(&p.member(0), &cancelled);
+    assert!(p
+        .governance
+        .try_execute_trade(&p.member(1), &cancelled)
+        .is_err());
+
+    // Keepers are paid per expired proposal removed
+    p.usdc_admin
+        .mint(&p.governance.address, &((10 * USDC) as i128));
+    p.governance
+        .set_trade_sweep_bounty(&p.admin, &p.usdc.address, &USDC);
+    let keeper = Address::generate(&env);
+    assert_eq!(
+        p.governance.sweep_expired_trades(&keeper, &0, &5),
+        TradeSweep {
+            swept: 0,
+            next_cursor: None
+        }
+    );
+
+    // Sweeps page through the open trades, which shift down as expired ones go
+    p.advance_time(3 * DAY);
+    assert_eq!(
+        p.governance.sweep_expired_trades(&keeper, &1, &1),
+        TradeSweep {
+            swept: 1,
+            next_cursor: Some(1)
+        }
+    );
+    assert_eq!(
+        p.governance.get_open_trades(),
+        vec![&env, first.clone(), live.clone()]
+    );
+    assert_eq!(
+        p.governance.sweep_expired_trades(&keeper, &0, &5),
+        TradeSweep {
+            swept: 1,
+            next_cursor: None
+        }
+    );
+    assert_eq!(p.usdc.balance(&keeper), (2 * USDC) as i128);
+    assert_eq!(p.governance.get_open_trades(), vec![&env, live]);
+    assert!(p.governance.try_cancel_trade(&p.member(0), &first).is_err());
+    assert!(p
+        .governance
+        .try_cancel_trade(&p.member(1), &second)
+        .is_err());
+}
+
+#[test]
+fn ledger_clock_holds_deadlines_until_the_sequence_agrees() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    p.usdc_vault.set_governance(&p.admin, &p.governance.address);
+    p.fund_usdc_reserve(100 * USDC);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let saver = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&saver, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+
+    // Governance anchors its own clock and every registered vault's
+    assert!(p.governance.try_set_ledger_clock(&proposer, &true).is_err());
+    p.governance.set_ledger_clock(&p.admin, &true);
+    let clock = p.governance.get_ledger_clock().unwrap();
+    assert_eq!(clock.anchor_time, env.ledger().timestamp());
+    assert_eq!(p.usdc_vault.get_ledger_clock(), Some(clock));
+
+    let proposal_id = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+
+    // The timestamp alone runs past the deadline, but too few ledgers have closed
+    let close_ledgers = |seconds: u64| {
+        env.ledger()
+            .with_mut(|li| li.sequence_number += (seconds / LEDGER_CLOSE_SECONDS) as u32);
+    };
+    p.advance_time(7 * DAY + 1);
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+    close_ledgers(7 * DAY + LEDGER_CLOSE_SECONDS);
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+
+    // Lock expiries in the vault are held the same way
+    p.advance_time(90 * DAY);
+    assert!(p.usdc_vault.try_withdraw(&saver).is_err());
+    close_ledgers(90 * DAY);
+    assert!(p.usdc_vault.withdraw(&saver) >= 1_000 * USDC);
+
+    // As are beneficiary changes
+    let heir = Address::generate(&env);
+    let new_wallet = Address::generate(&env);
+    p.usdc_vault
+        .deposit_to_beneficiary(&saver, &(1_000 * USDC), &LockPeriod::ThreeMonths, &heir);
+    p.usdc_vault.change_beneficiary(&saver, &new_wallet);
+    p.advance_time(BENEFICIARY_CHANGE_DELAY);
+    assert_eq!(p.usdc_vault.get_payout_address(&saver), heir);
+    close_ledgers(BENEFICIARY_CHANGE_DELAY);
+    assert_eq!(p.usdc_vault.get_payout_address(&saver), new_wallet);
+
+    // And recovery claims
+    let recovery = Address::generate(&env);
+    p.usdc_vault.set_recovery(&saver, &recovery, &(365 * DAY));
+    p.advance_time(455 * DAY);
+    assert!(p.usdc_vault.try_claim_recovery(&recovery, &saver).is_err());
+    close_ledgers(455 * DAY);
+    assert!(p.usdc_vault.claim_recovery(&recovery, &saver) >= 1_000 * USDC);
+
+    p.governance.set_ledger_clock(&p.admin, &false);
+    assert_eq!(p.governance.get_ledger_clock(), None);
+    assert_eq!(p.usdc_vault.get_ledger_clock(), None);
+}
+
+#[test]
+fn fee_switches_waive_or_split_revenue_streams() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let treasury = Address::generate(&env);
+    let insurance_fund = Address::generate(&env);
+    p.usdc_vault.set_treasury(&p.admin, &treasury);
+    p.usdc_vault.set_insurance_fund(&p.admin, &insurance_fund);
+
+    let amount = 1_000 * USDC;
+    let penalty = amount / 10;
+    let first = p.funded_user(amount);
+    let second = p.funded_user(amount);
+    for user in [&first, &second] {
+        p.usdc_vault
+            .deposit(user, &amount, &LockPeriod::ThreeMonths);
+    }
+
+    // Streams stay on and route everything to the treasury until governance votes otherwise
+    assert_eq!(
+        p.usdc_vault
+            .get_fee_switch(&RevenueStream::EarlyExitPenalty),
+        FeeSwitch {
+            enabled: true,
+            insurance_bps: 0,
+        }
+    );
+
+    // Switches carry their stream and keep the insurance share within 100%
+    let proposer = token_holder(&p, 5_000 * USDC);
+    assert!(p
+        .governance
+        .try_propose_parameter_change(&proposer, &ProtocolParameter::FeeSwitch, &2_500)
+        .is_err());
+    assert_eq!(
+        p.governance.try_propose_fee_switch(
+            &proposer,
+            &RevenueStream::EarlyExitPenalty,
+            &FeeSwitch {
+                enabled: true,
+                insurance_bps: 10_001,
+            },
+        ),
+        Err(Ok(Error::from_contract_error(
+            ContractError::ParameterOutOfBounds as u32
+        )))
+    );
+
+    let split = FeeSwitch {
+        enabled: true,
+        insurance_bps: 2_500,
+    };
+    let proposal_id =
+        p.governance
+            .propose_fee_switch(&proposer, &RevenueStream::EarlyExitPenalty, &split);
+    assert_eq!(
+        p.governance.get_fee_switch_change(&proposal_id),
+        Some(FeeSwitchChange {
+            stream: RevenueStream::EarlyExitPenalty,
+            switch: split.clone(),
+        })
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    assert_eq!(
+        p.usdc_vault
+            .get_fee_switch(&RevenueStream::EarlyExitPenalty),
+        split
+    );
+    let entry = p.governance.get_changelog(&0, &1).get(0).unwrap();
+    assert_eq!(entry.kind, ProposalKind::FeeSwitch);
+    assert_eq!(entry.new_value, 2_500);
+
+    p.usdc_vault.emergency_withdraw(&p.admin, &first);
+    assert_eq!(p.usdc.balance(&insurance_fund), (penalty / 4) as i128);
+    assert_eq!(p.usdc.balance(&treasury), (penalty * 3 / 4) as i128);
+    assert_eq!(p.usdc_vault.get_fees_routed(), penalty);
+
+    // A disabled stream is waived, not charged
+    let off = FeeSwitch {
+        enabled: false,
+        insurance_bps: 2_500,
+    };
+    let proposal_id =
+        p.governance
+            .propose_fee_switch(&proposer, &RevenueStream::EarlyExitPenalty, &off);
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    assert_eq!(p.usdc_vault.get_exit_fee(&second), 0);
+    assert_eq!(p.usdc_vault.emergency_withdraw(&p.admin, &second), amount);
+    assert_eq!(p.usdc.balance(&second), amount as i128);
+    assert_eq!(p.usdc_vault.get_fees_routed(), penalty);
+}
+
+#[test]
+fn voting_rules_set_period_threshold_and_minimum_per_parameter() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let defaults = p
+        .governance
+        .get_voting_rules(&ProtocolParameter::ProtocolFeeRate);
+    assert_eq!(defaults.voting_period, DEFAULT_VOTING_PERIOD);
+    assert_eq!(defaults.pass_threshold_bps, SIMPLE_MAJORITY_BPS);
+    assert_eq!(defaults.min_proposal_tokens, MIN_PROPOSAL_TOKENS);
+
+    let too_short = VotingRules {
+        voting_period: DAY - 1,
+        pass_threshold_bps: SIMPLE_MAJORITY_BPS,
+        min_proposal_tokens: MIN_PROPOSAL_TOKENS,
+    };
+    let unreachable = VotingRules {
+        voting_period: 3 * DAY,
+        pass_threshold_bps: 9_500,
+        min_proposal_tokens: MIN_PROPOSAL_TOKENS,
+    };
+    for rules in [too_short, unreachable] {
+        assert_eq!(
+            p.governance.try_set_voting_rules(
+                &p.admin,
+                &ProtocolParameter::ProtocolFeeRate,
+                &Some(rules)
+            ),
+            Err(Ok(Error::from_contract_error(
+                ContractError::ParameterOutOfBounds as u32
+            )))
+        );
+    }
+
+    // Fee changes need a two-thirds super-majority over a three-day vote
+    let fee_rules = VotingRules {
+        voting_period: 3 * DAY,
+        pass_threshold_bps: 6_667,
+        min_proposal_tokens: 4_000 * USDC,
+    };
+    p.governance.set_voting_rules(
+        &p.admin,
+        &ProtocolParameter::ProtocolFeeRate,
+        &Some(fee_rules.clone()),
+    );
+    assert_eq!(
+        p.governance
+            .get_voting_rules(&ProtocolParameter::ProtocolFeeRate),
+        fee_rules
+    );
+
+    let small_holder = token_holder(&p, 2_000 * USDC);
+    assert!(p
+        .governance
+        .try_propose_parameter_change(&small_holder, &ProtocolParameter::ProtocolFeeRate, &300)
+        .is_err());
+    // Other parameters keep the default minimum
+    p.governance.propose_parameter_change(
+        &small_holder,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let opponent = token_holder(&p, 3_000 * USDC);
+    let proposal_id =
+        p.governance
+            .propose_parameter_change(&proposer, &ProtocolParameter::ProtocolFeeRate, &300);
+    let proposal = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(proposal.voting_deadline - proposal.voting_start, 3 * DAY);
+    assert_eq!(p.governance.get_pass_threshold(&proposal_id), 6_667);
+
+    // Relaxing the rules mid-vote does not lower the bar for this proposal
+    p.governance
+        .set_voting_rules(&p.admin, &ProtocolParameter::ProtocolFeeRate, &None);
+    assert_eq!(p.governance.get_pass_threshold(&proposal_id), 6_667);
+
+    p.advance_time(proposal.voting_start - env.ledger().timestamp());
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&opponent, &proposal_id, &VoteType::Against);
+
+    // 62.5% in favour is a majority but short of the super-majority
+    p.advance_time(3 * DAY + 1);
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+}
+
+#[test]
+fn appointed_committee_members_act_only_after_accepting() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Replace the last seat; the sitting members stay active
+    let mut committee = p.governance.get_committee_members();
+    let mut seat = committee.get(4).unwrap();
+    let outgoing = seat.address.clone();
+    let appointee = Address::generate(&env);
+    let late_appointee = Address::generate(&env);
+    seat.address = appointee.clone();
+    committee.set(4, seat.clone());
+    p.governance.update_committee(&p.admin, &committee);
+
+    let delegate = Address::generate(&env);
+    assert_eq!(p.governance.get_pending_membership(&p.member(0)), None);
+    p.governance
+        .set_committee_delegate(&p.member(0), &Address::generate(&env), &DAY);
+    assert_eq!(
+        p.governance.get_pending_membership(&appointee),
+        Some(env.ledger().timestamp() + MEMBERSHIP_ACCEPTANCE_WINDOW)
+    );
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&appointee, &delegate, &DAY)
+        .is_err());
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&outgoing, &delegate, &DAY)
+        .is_err());
+
+    p.governance.accept_membership(&appointee);
+    assert_eq!(p.governance.get_pending_membership(&appointee), None);
+    p.governance
+        .set_committee_delegate(&appointee, &delegate, &DAY);
+
+    // An appointment left unaccepted past the window cannot be taken up
+    seat.address = late_appointee.clone();
+    committee.set(3, seat);
+    p.governance.update_committee(&p.admin, &committee);
+    assert_eq!(p.governance.get_pending_membership(&appointee), None);
+    p.advance_time(MEMBERSHIP_ACCEPTANCE_WINDOW + 1);
+    assert!(p.governance.try_accept_membership(&late_appointee).is_err());
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&late_appointee, &Address::generate(&env), &DAY)
+        .is_err());
+}
+
+#[test]
+fn dependent_proposals_execute_only_after_their_prerequisites() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let raise_cap = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_500,
+    );
+    assert!(p
+        .governance
+        .try_propose_dependent_change(
+            &proposer,
+            &ProtocolParameter::ProtocolFeeRate,
+            &300,
+            &vec![&env, BytesN::from_array(&env, &[7; 32])],
+        )
+        .is_err());
+
+    let dependent = p.governance.propose_dependent_change(
+        &proposer,
+        &ProtocolParameter::ProtocolFeeRate,
+        &300,
+        &vec![&env, raise_cap.clone()],
+    );
+    assert_eq!(
+        p.governance.get_prerequisites(&dependent),
+        vec![&env, raise_cap.clone()]
+    );
+    assert_eq!(p.governance.get_prerequisites(&raise_cap).len(), 0);
+
+    p.governance
+        .vote_on_proposal(&proposer, &raise_cap, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&proposer, &dependent, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+
+    // Out of order the dependent change waits, still pending
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &dependent)
+        .is_err());
+    assert_eq!(
+        p.governance
+            .get_governance_proposal(&dependent)
+            .unwrap()
+            .status,
+        ProposalStatus::Pending
+    );
+
+    p.governance
+        .execute_governance_proposal(&proposer, &raise_cap);
+    p.governance
+        .execute_governance_proposal(&proposer, &dependent);
+    assert_eq!(
+        p.governance
+            .get_governance_proposal(&dependent)
+            .unwrap()
+            .status,
+        ProposalStatus::Executed
+    );
+}
+
+#[test]
+fn parameter_changes_wait_out_the_timelock_delay() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_TIMELOCK),
+        &p.timelock.address,
+    );
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let proposal_id = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &proposal_id, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+
+    // A passing vote only queues the change behind the timelock's delay
+    p.governance
+        .execute_governance_proposal(&proposer, &proposal_id);
+    let queued = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(queued.status, ProposalStatus::Approved);
+    assert_eq!(
+        p.governance
+            .get_parameter_value(&ProtocolParameter::MaximumYieldRate),
+        None
+    );
+    assert!(p
+        .governance
+        .try_execute_governance_proposal(&proposer, &proposal_id)
+        .is_err());
+    assert!(p
+        .governance
+        .try_apply_governance_proposal(&p.admin, &proposal_id, &proposer)
+        .is_err());
+
+    let operation = p.timelock.get_operation(&1).unwrap();
+    assert_eq!(operation.target, p.governance.address);
+    assert_eq!(
+        operation.function,
+        Symbol::new(&env, "apply_governance_proposal")
+    );
+    assert!(p.timelock.try_execute(&1).is_err());
+
+    p.advance_time(TIMELOCK_MIN_DELAY);
+    p.timelock.execute(&1);
+    let executed = p.governance.get_governance_proposal(&proposal_id).unwrap();
+    assert_eq!(executed.status, ProposalStatus::Executed);
+    assert_eq!(
+        p.governance
+            .get_parameter_value(&ProtocolParameter::MaximumYieldRate),
+        Some(1_200)
+    );
+    assert_eq!(
+        p.governance.get_changelog(&0, &1).get(0).unwrap().executor,
+        proposer
+    );
+}
+
+#[test]
+fn timelock_settings_change_only_through_queued_operations() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Governance is the proposer; delays below the floor and outside proposers are refused
+    let governance = p.governance.address.clone();
+    assert!(p
+        .timelock
+        .try_schedule_delay_update(&governance, &DAY, &TIMELOCK_MIN_DELAY)
+        .is_err());
+    assert!(p
+        .timelock
+        .try_schedule_delay_update(&p.admin, &(3 * DAY), &TIMELOCK_MIN_DELAY)
+        .is_err());
+
+    let delay_update =
+        p.timelock
+            .schedule_delay_update(&governance, &(3 * DAY), &TIMELOCK_MIN_DELAY);
+    let successor = Address::generate(&env);
+    let proposer_update =
+        p.timelock
+            .schedule_proposer_update(&governance, &successor, &TIMELOCK_MIN_DELAY);
+    assert!(p.timelock.try_execute(&delay_update).is_err());
+
+    p.advance_time(TIMELOCK_MIN_DELAY);
+    p.timelock.execute(&delay_update);
+    p.timelock.execute(&proposer_update);
+    assert_eq!(p.timelock.get_min_delay(), 3 * DAY);
+    assert_eq!(p.timelock.get_proposer(), successor);
+
+    // The old proposer is out and the longer delay binds new operations
+    assert!(p
+        .timelock
+        .try_schedule_proposer_update(&governance, &governance, &(3 * DAY))
+        .is_err());
+    assert!(p
+        .timelock
+        .try_schedule_proposer_update(&successor, &governance, &TIMELOCK_MIN_DELAY)
+        .is_err());
+    p.timelock
+        .schedule_proposer_update(&successor, &governance, &(3 * DAY));
+}
+
+#[test]
+fn executed_proposals_are_kept_in_a_paginated_changelog() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let proposer = token_holder(&p, 5_000 * USDC);
+    let first = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_200,
+    );
+    let dependent = p.governance.propose_dependent_change(
+        &proposer,
+        &ProtocolParameter::ProtocolFeeRate,
+        &300,
+        &vec![&env, first.clone()],
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &first, &VoteType::For);
+    p.governance
+        .vote_on_proposal(&proposer, &dependent, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+    p.governance.execute_governance_proposal(&proposer, &first);
+    p.governance
+        .execute_governance_proposal(&proposer, &dependent);
+
+    let second = p.governance.propose_parameter_change(
+        &proposer,
+        &ProtocolParameter::MaximumYieldRate,
+        &1_500,
+    );
+    p.governance
+        .vote_on_proposal(&proposer, &second, &VoteType::For);
+    p.advance_time(7 * DAY + 1);
+    let executor = Address::generate(&env);
+    p.governance.execute_governance_proposal(&executor, &second);
+
+    assert_eq!(p.governance.get_changelog_count(), 3);
+    assert_eq!(
+        p.governance
+            .get_parameter_value(&ProtocolParameter::MaximumYieldRate),
+        Some(1_500)
+    );
+
+    let changelog = p.governance.get_changelog(&0, &10);
+    assert_eq!(changelog.len(), 3);
+    let opening = changelog.get(0).unwrap();
+    assert_eq!(opening.proposal_id, first);
+    assert_eq!(opening.kind, ProposalKind::ParameterChange);
+    assert_eq!(opening.old_value, None);
+    assert_eq!(opening.new_value, 1_200);
+    assert_eq!(
+        changelog.get(1).unwrap().kind,
+        ProposalKind::DependentChange
+    );
+
+    // Pages are read from an offset, and stop at the end of the log
+    let page = p.governance.get_changelog(&2, &10);
+    assert_eq!(page.len(), 1);
+    let latest = page.get(0).unwrap();
+    assert_eq!(latest.index, 2);
+    assert_eq!(latest.proposal_id, second);
+    assert_eq!(latest.old_value, Some(1_200));
+    assert_eq!(latest.new_value, 1_500);
+    assert_eq!(latest.executor, executor);
+    assert_eq!(latest.executed_at, env.ledger().timestamp());
+    assert_eq!(p.governance.get_changelog(&1, &1).len(), 1);
+    assert_eq!(p.governance.get_changelog(&5, &10).len(), 0);
+}
+
+#[test]
+fn profit_reports_are_escrowed_and_distributed_after_committee_approval() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    token_holder(&p, 10_000 * USDC);
+
+    let profit = 100 * USDC;
+    let reporter = p.member(0);
+    p.usdc_admin.mint(&reporter, &((2 * profit) as i128));
+    let index_before = p.yield_token.get_yield_index();
+    let vault_usdc = p.usdc.balance(&p.usdc_vault.address);
+
+    // The whole profit is escrowed with governance and nothing moves yet
+    let report_id = p.governance.submit_profit_report(&reporter, &profit, &0);
+    assert_eq!(p.usdc.balance(&reporter), profit as i128);
+    assert_eq!(p.usdc.balance(&p.governance.address), profit as i128);
+    assert_eq!(p.governance.get_profit_report(&report_id), None);
+    assert_eq!(p.yield_token.get_yield_index(), index_before);
+
+    // One report per epoch, and withdrawing frees the epoch again
+    assert!(p
+        .governance
+        .try_submit_profit_report(&reporter, &profit, &0)
+        .is_err());
+    assert!(p
+        .governance
+        .try_withdraw_profit_report(&p.member(1), &report_id)
+        .is_err());
+    assert_eq!(
+        p.governance.withdraw_profit_report(&reporter, &report_id),
+        profit
+    );
+    assert_eq!(p.usdc.balance(&reporter), (2 * profit) as i128);
+    assert_eq!(
+        p.governance
+            .get_profit_report_proposal(&report_id)
+            .unwrap()
+            .status,
+        ProposalStatus::Rejected
+    );
+    assert!(p
+        .governance
+        .try_approve_profit_report(&p.member(0), &report_id)
+        .is_err());
+
+    let report_id = p.governance.submit_profit_report(&reporter, &profit, &0);
+    p.governance.approve_profit_report(&p.member(0), &report_id);
+    assert!(p
+        .governance
+        .try_approve_profit_report(&p.member(0), &report_id)
+        .is_err());
+    p.governance.approve_profit_report(&p.member(1), &report_id);
+    assert_eq!(p.yield_token.get_yield_index(), index_before);
+
+    // The third approval funds the vault, then raises the yield index
+    p.governance.approve_profit_report(&p.member(2), &report_id);
+    let report = p.governance.get_profit_report(&report_id).unwrap();
+    assert_eq!(report.yield_distributed + report.protocol_fee, profit);
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address),
+        vault_usdc + report.yield_distributed as i128
+    );
+    assert_eq!(
+        p.usdc.balance(&p.governance.address),
+        report.protocol_fee as i128
+    );
+    assert!(p.yield_token.get_yield_index() > index_before);
+    assert_eq!(
+        p.governance
+            .get_profit_report_proposal(&report_id)
+            .unwrap()
+            .status,
+        ProposalStatus::Executed
+    );
+
+    // The next report waits for the next epoch
+    assert!(p
+        .governance
+        .try_submit_profit_report(&reporter, &profit, &0)
+        .is_err());
+    p.advance_time(DAY);
+    p.governance.close_epoch();
+    p.governance.submit_profit_report(&reporter, &profit, &0);
+}
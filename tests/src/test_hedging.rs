@@ -0,0 +1,94 @@
+use crate::mocks::{MockOracle, MockOracleClient};
+use crate::setup::{Protocol, DAY, USDC};
+use hedging::{Hedging, HedgingClient};
+use shared::HedgeStatus;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, BytesN, Env, String};
+
+#[test]
+fn committee_hedges_the_pledged_coffee_book() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let hedging = HedgingClient::new(&env, &env.register_contract(None, Hedging));
+    hedging.initialize(&p.admin, &p.governance.address, &p.coffee.address);
+
+    // A 20,000 kg lot backing a loan makes up the collateral book
+    let lot = p.coffee.create_coffee_asset(
+        &Address::generate(&env),
+        &String::from_str(&env, "ETH-YRG-2024-001"),
+        &88,
+        &20_000,
+        &(30_000 * USDC),
+        &String::from_str(&env, "Yirgacheffe, Ethiopia"),
+        &String::from_str(&env, "2024-11"),
+    );
+    let loan_id = BytesN::from_array(&env, &[3u8; 32]);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(20_000 * USDC));
+    assert_eq!(p.coffee.get_pledged_quantity(), 20_000);
+
+    let partner = Address::generate(&env);
+    hedging.add_partner(&p.admin, &partner);
+    let expiry = env.ledger().timestamp() + 90 * DAY;
+    let entry = USDC * 3 / 2;
+
+    // Only committee members hedge, only through known venues and only up to the book
+    assert!(hedging
+        .try_open_hedge(&Address::generate(&env), &partner, &12_000, &entry, &expiry)
+        .is_err());
+    assert!(hedging
+        .try_open_hedge(
+            &p.member(0),
+            &Address::generate(&env),
+            &12_000,
+            &entry,
+            &expiry
+        )
+        .is_err());
+    assert!(hedging
+        .try_open_hedge(&p.member(0), &partner, &20_001, &entry, &expiry)
+        .is_err());
+
+    let first = hedging.open_hedge(&p.member(0), &partner, &12_000, &entry, &expiry);
+    assert_eq!(hedging.get_hedge_ratio(), 6_000);
+
+    let router = Address::generate(&env);
+    hedging.set_trade_router(&p.admin, &router);
+    let second = hedging.open_hedge(&p.member(1), &router, &4_000, &(USDC * 6 / 5), &expiry);
+    assert_eq!(hedging.get_hedge_ratio(), 8_000);
+    assert!(hedging
+        .try_open_hedge(&p.member(1), &router, &5_000, &entry, &expiry)
+        .is_err());
+
+    hedging.resize_hedge(&p.member(2), &second, &2_000);
+    assert_eq!(hedging.get_hedged_quantity(), 14_000);
+
+    // Open shorts are marked to the coffee base price
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.coffee.set_price_oracle(&p.admin, &oracle.address);
+    oracle.set_price(&USDC);
+    p.coffee.refresh_base_price();
+    let summary = hedging.get_hedge_summary();
+    assert_eq!(summary.book_kg, 20_000);
+    assert_eq!(summary.hedged_kg, 14_000);
+    assert_eq!(summary.hedge_ratio_bps, 7_000);
+    assert_eq!(summary.open_hedges, 2);
+    assert_eq!(summary.unrealized_pnl, (6_400 * USDC) as i128);
+
+    // Closing a short after the price rose books a loss
+    assert_eq!(
+        hedging.close_hedge(&p.member(0), &first, &(2 * USDC)),
+        -((6_000 * USDC) as i128)
+    );
+    assert!(hedging
+        .try_close_hedge(&p.member(0), &first, &(2 * USDC))
+        .is_err());
+    let closed = hedging.get_hedge(&first).unwrap();
+    assert_eq!(closed.status, HedgeStatus::Closed);
+    assert_eq!(closed.exit_price, 2 * USDC);
+    assert_eq!(hedging.get_open_hedges(), vec![&env, second]);
+    assert_eq!(hedging.get_realized_pnl(), -((6_000 * USDC) as i128));
+    assert_eq!(hedging.get_hedge_ratio(), 1_000);
+}
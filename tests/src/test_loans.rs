@@ -0,0 +1,2121 @@
+use crate::mocks::{MockOracle, MockOracleClient, MockStrategy, MockStrategyClient};
+use crate::setup::{Protocol, DAY, OUNCE, USDC};
+use gold_vault::{GoldVault, GoldVaultClient};
+use registry::{Registry, RegistryClient};
+use shared::{
+    AlertThresholds, AlertTier, BadDebtSource, CollateralStatus, ContractError, EpochSummary,
+    GradePoint, LockPeriod, PrepaymentPolicy, ProposalStatus, RepaymentStructure, StateDigest,
+    VaultType, WriteOffFunding, CREDIT_SCORE_MAX, CREDIT_SCORE_NEUTRAL, MAX_SESSION_KEY_LEDGERS,
+    RATE_LOCK_WINDOW, REGISTRY_COFFEE_COLLATERAL, REGISTRY_GOLD_VAULT, REGISTRY_ORACLE,
+    REGISTRY_TREASURY,
+};
+use soroban_sdk::testutils::{Address as _, Events, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{
+    symbol_short, vec, Address, BytesN, Env, Error, Map, String, Symbol, TryFromVal,
+};
+
+fn coffee_lot(p: &Protocol, value_usd: u128) -> Address {
+    let issuer = Address::generate(p.env);
+    p.coffee.create_coffee_asset(
+        &issuer,
+        &String::from_str(p.env, "ETH-YRG-2024-001"),
+        &88,
+        &20_000,
+        &value_usd,
+        &String::from_str(p.env, "Yirgacheffe, Ethiopia"),
+        &String::from_str(p.env, "2024-11"),
+    )
+}
+
+fn approved_loan(p: &Protocol, collateral: &Address, amount: u128) -> BytesN<32> {
+    let borrower = Address::generate(p.env);
+    let proposal_id =
+        p.governance
+            .submit_loan_proposal(&p.member(0), &borrower, &amount, collateral, &800, &180);
+
+    p.governance.approve_loan(&proposal_id, &p.member(0));
+    p.governance.approve_loan(&proposal_id, &p.member(1));
+    let pending = p.governance.get_loan_proposal(&proposal_id).unwrap();
+    assert_eq!(pending.status, ProposalStatus::Pending);
+    assert_eq!(pending.approvals, 2);
+
+    p.governance.approve_loan(&proposal_id, &p.member(2));
+    proposal_id
+}
+
+#[test]
+fn loan_runs_from_approval_through_repayment_and_collateral_release() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let depositor = p.funded_user(5_000 * USDC);
+    p.usdc_vault
+        .deposit(&depositor, &(5_000 * USDC), &LockPeriod::ThreeMonths);
+    let position_id = p.usdc_vault.get_active_receipt(&depositor).unwrap().id;
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let proposal_id = approved_loan(&p, &lot, loan_amount);
+    p.governance.pledge_vault_position(
+        &p.member(0),
+        &proposal_id,
+        &p.usdc_vault.address,
+        &depositor,
+        &position_id,
+    );
+
+    let approved = p.governance.get_loan_proposal(&proposal_id).unwrap();
+    assert_eq!(approved.id, proposal_id);
+    assert_eq!(approved.status, ProposalStatus::Approved);
+
+    // Members cannot approve twice and outsiders cannot approve at all
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &p.member(0))
+        .is_err());
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &Address::generate(&env))
+        .is_err());
+
+    p.governance.execute_loan(&p.member(3), &proposal_id);
+    assert_eq!(
+        p.governance.get_loan_proposal(&proposal_id).unwrap().status,
+        ProposalStatus::Executed
+    );
+
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &proposal_id, &loan_amount);
+    assert!(p.coffee.verify_collateral(&proposal_id));
+    assert_eq!(
+        p.coffee.get_loan_collateral(&proposal_id),
+        Some(lot.clone())
+    );
+    assert_eq!(p.coffee.get_pledged_quantity(), 20_000);
+    assert!(p
+        .coffee
+        .try_retire_asset(
+            &p.coffee.get_owner(&lot),
+            &lot,
+            &BytesN::from_array(&env, &[7; 32])
+        )
+        .is_err());
+
+    // Collateral stays pledged until the whole principal is repaid
+    p.governance
+        .record_repayment(&p.member(0), &proposal_id, &(4_000 * USDC));
+    assert_eq!(
+        p.governance.get_loan_outstanding(&proposal_id),
+        Some(6_000 * USDC)
+    );
+    assert!(p.governance.try_release_vault_liens(&proposal_id).is_err());
+
+    p.governance
+        .record_repayment(&p.member(0), &proposal_id, &(6_000 * USDC));
+    assert_eq!(p.governance.get_loan_outstanding(&proposal_id), None);
+    let borrower = p
+        .governance
+        .get_loan_proposal(&proposal_id)
+        .unwrap()
+        .borrower;
+    assert_eq!(
+        p.governance.get_credit_history(&borrower).on_time_repaid,
+        loan_amount
+    );
+
+    // Releasing frees both the vault position and the coffee lot
+    assert_eq!(p.governance.release_vault_liens(&proposal_id), 1);
+    assert_eq!(p.usdc_vault.get_encumbrance(&depositor, &position_id), None);
+    assert!(!p.coffee.verify_collateral(&proposal_id));
+    assert_eq!(p.coffee.get_loan_collateral(&proposal_id), None);
+    assert_eq!(p.coffee.get_pledged_loan(&lot), None);
+    assert_eq!(p.coffee.get_pledged_quantity(), 0);
+    assert_eq!(
+        p.coffee.get_collateral_info(&lot).unwrap().status,
+        CollateralStatus::Active
+    );
+    assert!(p
+        .coffee
+        .try_release_collateral(&p.governance.address, &proposal_id)
+        .is_err());
+
+    p.advance_time(91 * DAY);
+    p.fund_usdc_reserve(1_000 * USDC);
+    assert!(p.usdc_vault.withdraw(&depositor) >= 5_000 * USDC);
+}
+
+#[test]
+fn pledged_vault_positions_cannot_redeem_settlement_until_released() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.yield_token
+        .set_governance(&p.admin, &p.governance.address);
+
+    let depositor = p.funded_user(5_000 * USDC);
+    p.usdc_vault
+        .deposit(&depositor, &(5_000 * USDC), &LockPeriod::ThreeMonths);
+    let position_id = p.usdc_vault.get_active_receipt(&depositor).unwrap().id;
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+    p.governance.pledge_vault_position(
+        &p.member(0),
+        &loan_id,
+        &p.usdc_vault.address,
+        &depositor,
+        &position_id,
+    );
+    p.governance.execute_loan(&p.member(3), &loan_id);
+
+    for member in 0..3 {
+        p.governance.approve_shutdown(&p.member(member));
+    }
+    assert!(p.usdc_vault.get_settlement().is_some());
+
+    // Settlement waives the lock but not the lien
+    assert!(p.usdc_vault.try_redeem_settlement(&depositor).is_err());
+
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(10_000 * USDC));
+    assert_eq!(p.governance.release_vault_liens(&loan_id), 1);
+    assert!(p.usdc_vault.redeem_settlement(&depositor) > 0);
+}
+
+#[test]
+fn undercollateralized_loan_is_rejected() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 12_000 * USDC);
+    let proposal_id = approved_loan(&p, &lot, loan_amount);
+
+    let result =
+        p.coffee
+            .try_register_collateral(&p.governance.address, &lot, &proposal_id, &loan_amount);
+    assert!(result.is_err());
+    assert!(!p.coffee.verify_collateral(&proposal_id));
+}
+
+#[test]
+fn default_liquidation_shortfall_is_written_off() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let proposal_id = approved_loan(&p, &lot, loan_amount);
+    p.governance.execute_loan(&p.member(3), &proposal_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &proposal_id, &loan_amount);
+
+    // Collateral can only be liquidated once an installment is overdue
+    assert!(p
+        .coffee
+        .try_liquidate_collateral(&p.governance.address, &proposal_id, &(7_000 * USDC))
+        .is_err());
+    p.advance_time(31 * DAY);
+
+    // The lot sells for less than the outstanding loan
+    let shortfall =
+        p.coffee
+            .liquidate_collateral(&p.governance.address, &proposal_id, &(7_000 * USDC));
+    assert_eq!(shortfall, 3_000 * USDC);
+    assert_eq!(p.coffee.get_liquidation_shortfall(&proposal_id), shortfall);
+    assert_eq!(
+        p.coffee.get_collateral_info(&lot).unwrap().status,
+        CollateralStatus::Liquidated
+    );
+    assert!(!p.coffee.verify_collateral(&proposal_id));
+
+    let record_id = p.governance.record_bad_debt(
+        &p.member(0),
+        &BadDebtSource::LiquidationShortfall,
+        &proposal_id,
+        &p.usdc_vault.address,
+        &shortfall,
+    );
+    assert_eq!(p.governance.total_bad_debt(), shortfall);
+
+    p.governance
+        .propose_write_off(&p.member(0), &record_id, &WriteOffFunding::FutureFees);
+    for index in 0..3 {
+        p.governance.approve_write_off(&p.member(index), &record_id);
+    }
+
+    let record = p.governance.get_bad_debt(&record_id).unwrap();
+    assert_eq!(record.status, ProposalStatus::Executed);
+    assert_eq!(p.governance.total_bad_debt(), 0);
+    assert_eq!(p.governance.get_pending_fee_write_off(), shortfall);
+}
+
+#[test]
+fn repayment_history_drives_credit_score() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let proposal_id = approved_loan(&p, &lot, loan_amount);
+    let borrower = p
+        .governance
+        .get_loan_proposal(&proposal_id)
+        .unwrap()
+        .borrower;
+
+    // Unscored until something is repaid or defaulted
+    assert_eq!(
+        p.governance.get_credit_score(&borrower),
+        CREDIT_SCORE_NEUTRAL
+    );
+    assert!(p
+        .governance
+        .try_record_repayment(&p.member(0), &proposal_id, &USDC)
+        .is_err());
+
+    p.governance.execute_loan(&p.member(3), &proposal_id);
+    assert_eq!(p.governance.get_credit_history(&borrower).loans_executed, 1);
+
+    assert!(p
+        .governance
+        .record_repayment(&p.member(0), &proposal_id, &(6_000 * USDC)));
+    assert_eq!(p.governance.get_credit_score(&borrower), CREDIT_SCORE_MAX);
+
+    // 180-day term; the remainder arrives after the due date
+    p.advance_time(181 * DAY);
+    assert!(!p
+        .governance
+        .record_repayment(&p.member(0), &proposal_id, &(2_000 * USDC)));
+
+    // Liquidation shortfalls on the borrower's loan count as defaults
+    p.governance.record_bad_debt(
+        &p.member(0),
+        &BadDebtSource::LiquidationShortfall,
+        &proposal_id,
+        &p.usdc_vault.address,
+        &(2_000 * USDC),
+    );
+
+    let history = p.governance.get_credit_history(&borrower);
+    assert_eq!(history.on_time_repaid, 6_000 * USDC);
+    assert_eq!(history.late_repaid, 2_000 * USDC);
+    assert_eq!(history.defaulted, 2_000 * USDC);
+    // (6_000 + 2_000 / 2) / 10_000
+    assert_eq!(p.governance.get_credit_score(&borrower), 700);
+}
+
+#[test]
+fn epoch_summary_reports_tvl_and_active_loans() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 5_000 * USDC;
+    let depositor = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&depositor, &amount, &LockPeriod::SixMonths);
+
+    // Two loans go out; one is repaid in full and the other in part
+    let lot = coffee_lot(&p, 24_000 * USDC);
+    let first = approved_loan(&p, &lot, 10_000 * USDC);
+    let second = approved_loan(&p, &lot, 5_000 * USDC);
+    p.governance.execute_loan(&p.member(3), &first);
+    p.governance.execute_loan(&p.member(3), &second);
+    assert_eq!(p.governance.get_active_loans(), 2);
+
+    p.governance
+        .record_repayment(&p.member(0), &first, &(10_000 * USDC));
+    p.governance
+        .record_repayment(&p.member(0), &second, &(1_000 * USDC));
+    assert_eq!(p.governance.get_loan_outstanding(&first), None);
+    assert_eq!(
+        p.governance.get_loan_outstanding(&second),
+        Some(4_000 * USDC)
+    );
+
+    // Epochs close once per rebase interval
+    assert!(p.governance.try_close_epoch().is_err());
+    p.advance_time(DAY);
+    let summary = p.governance.close_epoch();
+    assert_eq!(summary.epoch, 1);
+    assert_eq!(summary.usdc_vault_tvl, amount);
+    assert_eq!(summary.gold_vault_tvl, 0);
+    assert_eq!(summary.active_loans, 1);
+    assert_eq!(summary.penalty_fees, 0);
+
+    // The whole summary is carried by one event
+    let (contract, _, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, p.governance.address);
+    assert_eq!(EpochSummary::try_from_val(&env, &data).unwrap(), summary);
+    assert_eq!(p.governance.get_epoch_summary(&1), Some(summary));
+    assert!(p.governance.try_close_epoch().is_err());
+
+    // A liquidation closes the remaining loan
+    p.governance.record_bad_debt(
+        &p.member(0),
+        &BadDebtSource::LiquidationShortfall,
+        &second,
+        &p.usdc_vault.address,
+        &(4_000 * USDC),
+    );
+    assert_eq!(p.governance.get_active_loans(), 0);
+}
+
+#[test]
+fn realized_apy_follows_distributed_yield_over_closed_epochs() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    assert_eq!(p.yield_token.current_apy(&None), 0);
+
+    let amount = 5_000 * USDC;
+    let depositor = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&depositor, &amount, &LockPeriod::SixMonths);
+    let gold_user = p.gold_holder(OUNCE);
+    p.gold_vault
+        .deposit(&gold_user, &p.gold.address, &OUNCE, &LockPeriod::SixMonths);
+    let gold_tvl = p.gold_vault.get_vault_balance();
+    let total_tvl = amount + gold_tvl;
+
+    // Strategy yield is credited to the vault that harvested it
+    let strategy = MockStrategyClient::new(&env, &env.register_contract(None, MockStrategy));
+    p.usdc_vault
+        .register_strategy(&p.admin, &strategy.address, &3_000);
+    p.usdc_vault
+        .allocate(&p.admin, &strategy.address, &(1_000 * USDC));
+    p.usdc_admin.mint(&strategy.address, &((5 * USDC) as i128));
+    strategy.set_yield(&(5 * USDC));
+    p.yield_token
+        .set_strategy_vault(&p.admin, &p.usdc_vault.address);
+    assert_eq!(p.usdc_vault.harvest(&strategy.address), 5 * USDC);
+    assert_eq!(p.usdc_vault.get_yield_paid(), 5 * USDC);
+
+    // Profit-report yield is shared across vaults by TVL through the one yield index
+    p.usdc_admin.mint(&p.member(0), &((10 * USDC) as i128));
+    let report_id = p
+        .governance
+        .submit_profit_report(&p.member(0), &(10 * USDC), &0);
+    for index in 0..3 {
+        p.governance
+            .approve_profit_report(&p.member(index), &report_id);
+    }
+    p.advance_time(DAY);
+    let summary = p.governance.close_epoch();
+    assert_eq!(summary.usdc_yield_paid, 5 * USDC);
+    assert_eq!(summary.gold_yield_paid, 0);
+
+    let seconds = (summary.ended_at - summary.started_at) as u128;
+    let year = 365 * DAY as u128 * 10_000;
+    let shared = summary.yield_distributed;
+    let usdc_apy = (5 * USDC + shared * amount / total_tvl) * year / (amount * seconds);
+    let gold_apy = (shared * gold_tvl / total_tvl) * year / (gold_tvl * seconds);
+    assert!(gold_apy > 0);
+    assert!(usdc_apy > gold_apy);
+    assert_eq!(p.yield_token.current_apy(&Some(VaultType::USDC)), usdc_apy);
+    assert_eq!(p.yield_token.current_apy(&Some(VaultType::PAXG)), gold_apy);
+    assert_eq!(
+        p.yield_token.current_apy(&None),
+        (5 * USDC + shared) * year / (total_tvl * seconds)
+    );
+
+    // An epoch without distributions dilutes the realized rate
+    p.advance_time(DAY);
+    let quiet = p.governance.close_epoch();
+    assert_eq!(quiet.usdc_yield_paid, 0);
+    let seconds = seconds + (quiet.ended_at - quiet.started_at) as u128;
+    assert_eq!(
+        p.yield_token.current_apy(&None),
+        (5 * USDC + shared) * year / (total_tvl * seconds)
+    );
+    assert_eq!(
+        p.governance.get_recent_epoch_summaries(&1),
+        vec![&env, quiet]
+    );
+}
+
+#[test]
+fn epoch_close_records_state_digests_auditors_can_rebuild() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 5_000 * USDC;
+    let depositor = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&depositor, &amount, &LockPeriod::SixMonths);
+    let lot = coffee_lot(&p, 24_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    assert_eq!(p.governance.get_outstanding_principal(), 10_000 * USDC);
+
+    // Governance and every reachable contract report a digest; the gold vault is not deployed
+    p.advance_time(DAY);
+    p.governance.close_epoch();
+    let digests = p.governance.get_epoch_digests(&1);
+    let contracts: Vec<Address> = digests.iter().map(|entry| entry.contract).collect();
+    assert_eq!(
+        contracts,
+        std::vec![
+            p.governance.address.clone(),
+            p.usdc_vault.address.clone(),
+            p.yield_token.address.clone(),
+        ]
+    );
+    assert_eq!(digests.get(0).unwrap().digest, p.governance.state_digest());
+    assert_eq!(digests.get(1).unwrap().digest, p.usdc_vault.state_digest());
+    assert_eq!(digests.get(2).unwrap().digest, p.yield_token.state_digest());
+
+    // The digest event precedes the epoch summary
+    let events = env.events().all();
+    let (_, _, data) = events.get(events.len() - 2).unwrap();
+    assert_eq!(
+        soroban_sdk::Vec::<StateDigest>::try_from_val(&env, &data).unwrap(),
+        digests
+    );
+
+    // An auditor rebuilding the loan figures off-chain gets the same digest
+    let mut figures = Map::new(&env);
+    figures.set(symbol_short!("bad_debt"), 0u128);
+    figures.set(symbol_short!("principal"), 10_000 * USDC);
+    figures.set(symbol_short!("loans"), 1u128);
+    assert_eq!(p.governance.accounting_state(), figures);
+    let rebuilt: BytesN<32> = env.crypto().sha256(&figures.to_xdr(&env)).into();
+    assert_eq!(rebuilt, digests.get(0).unwrap().digest);
+
+    // Repayments change the loan figures and so the digest
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(4_000 * USDC));
+    assert_eq!(p.governance.get_outstanding_principal(), 6_000 * USDC);
+    assert_ne!(p.governance.state_digest(), digests.get(0).unwrap().digest);
+}
+
+#[test]
+fn loan_tranches_release_per_milestone_with_collateral_checks() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // The lot covers 8_000 at 150%, so only the first two milestones can be released.
+    // It is proposed before the coffee contract is registered, which skips the
+    // submission-time check against the whole amount.
+    let lot = coffee_lot(&p, 12_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+
+    let milestones = vec![
+        &env,
+        String::from_str(&env, "planting"),
+        String::from_str(&env, "harvest"),
+        String::from_str(&env, "export"),
+    ];
+
+    // Tranches must add up to the principal
+    assert!(p
+        .governance
+        .try_define_loan_tranches(
+            &p.member(0),
+            &loan_id,
+            &milestones,
+            &vec![&env, 4_000 * USDC, 4_000 * USDC, 1_000 * USDC],
+        )
+        .is_err());
+    p.governance.define_loan_tranches(
+        &p.member(0),
+        &loan_id,
+        &milestones,
+        &vec![&env, 4_000 * USDC, 4_000 * USDC, 2_000 * USDC],
+    );
+
+    // Nothing is released before the loan is executed
+    assert!(p
+        .governance
+        .try_approve_tranche(&p.member(0), &loan_id, &0)
+        .is_err());
+    p.governance.execute_loan(&p.member(3), &loan_id);
+
+    assert!(!p.governance.approve_tranche(&p.member(0), &loan_id, &0));
+    assert!(p
+        .governance
+        .try_approve_tranche(&p.member(0), &loan_id, &0)
+        .is_err());
+    // Milestones are released in order
+    assert!(p
+        .governance
+        .try_approve_tranche(&p.member(0), &loan_id, &1)
+        .is_err());
+    assert!(!p.governance.approve_tranche(&p.member(1), &loan_id, &0));
+    assert!(p.governance.approve_tranche(&p.member(2), &loan_id, &0));
+
+    for i in 0..3 {
+        p.governance.approve_tranche(&p.member(i), &loan_id, &1);
+    }
+    let tranches = p.governance.get_loan_tranches(&loan_id);
+    assert_eq!(
+        tranches.get(1).unwrap().disbursed_at,
+        Some(env.ledger().timestamp())
+    );
+    assert_eq!(tranches.get(2).unwrap().disbursed_at, None);
+
+    // Releasing the export tranche would leave the loan under-collateralised
+    p.governance.approve_tranche(&p.member(0), &loan_id, &2);
+    p.governance.approve_tranche(&p.member(1), &loan_id, &2);
+    assert!(p
+        .governance
+        .try_approve_tranche(&p.member(2), &loan_id, &2)
+        .is_err());
+    assert_eq!(
+        p.governance
+            .get_loan_tranches(&loan_id)
+            .get(2)
+            .unwrap()
+            .approvals,
+        2
+    );
+}
+
+#[test]
+fn repayment_schedule_drives_overdue_detection() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // 12_000 at 8% over 180 days: six monthly installments of 2_000
+    let lot = coffee_lot(&p, 18_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 12_000 * USDC);
+    assert_eq!(p.governance.get_next_payment(&loan_id), None);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+
+    let start = env.ledger().timestamp();
+    let schedule = p.governance.get_repayment_schedule(&loan_id);
+    assert_eq!(schedule.len(), 6);
+    let first = schedule.get(0).unwrap();
+    assert_eq!(first.due_at, start + 30 * DAY);
+    assert_eq!(first.principal, 2_000 * USDC);
+    // Interest accrues on the principal still outstanding over each period, rounded up
+    assert_eq!(
+        first.interest,
+        (12_000 * USDC * 800 * 30).div_ceil(365 * 10_000)
+    );
+    let last = schedule.get(5).unwrap();
+    assert_eq!(Some(last.due_at), p.governance.get_loan_due_at(&loan_id));
+    assert_eq!(
+        last.interest,
+        (2_000 * USDC * 800 * 30).div_ceil(365 * 10_000)
+    );
+
+    // A repayment covers installments in due order
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(3_000 * USDC));
+    let next = p.governance.get_next_payment(&loan_id).unwrap();
+    assert_eq!(next.due_at, start + 60 * DAY);
+    assert_eq!(next.principal_repaid, 1_000 * USDC);
+
+    p.advance_time(60 * DAY);
+    assert!(!p.governance.is_loan_overdue(&loan_id));
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(12_000 * USDC));
+    assert!(p
+        .coffee
+        .try_liquidate_collateral(&p.governance.address, &loan_id, &(9_000 * USDC))
+        .is_err());
+
+    // Missing the second installment puts the loan in default
+    p.advance_time(DAY);
+    assert!(p.governance.is_loan_overdue(&loan_id));
+    let borrower = p.governance.get_loan_proposal(&loan_id).unwrap().borrower;
+    assert!(!p
+        .governance
+        .record_repayment(&p.member(0), &loan_id, &(500 * USDC)));
+    assert_eq!(
+        p.governance.get_credit_history(&borrower).late_repaid,
+        500 * USDC
+    );
+    assert!(p.governance.is_loan_overdue(&loan_id));
+    assert_eq!(
+        p.coffee
+            .liquidate_collateral(&p.governance.address, &loan_id, &(8_000 * USDC)),
+        4_000 * USDC
+    );
+}
+
+#[test]
+fn balloon_loans_pay_interest_until_the_principal_falls_due() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // 12_000 at 8% over 180 days: five interest-only installments, then the balloon
+    let lot = coffee_lot(&p, 18_000 * USDC);
+    let loan_id = p.governance.submit_structured_loan_proposal(
+        &p.member(0),
+        &Address::generate(&env),
+        &(12_000 * USDC),
+        &lot,
+        &800,
+        &180,
+        &RepaymentStructure::Balloon,
+    );
+    assert_eq!(
+        p.governance.get_loan_structure(&loan_id),
+        RepaymentStructure::Balloon
+    );
+    for i in 0..3 {
+        p.governance.approve_loan(&loan_id, &p.member(i));
+    }
+    p.governance.execute_loan(&p.member(3), &loan_id);
+
+    let start = env.ledger().timestamp();
+    let monthly_interest = (12_000 * USDC * 800 * 30).div_ceil(365 * 10_000);
+    let schedule = p.governance.get_repayment_schedule(&loan_id);
+    assert_eq!(schedule.len(), 6);
+    for installment in schedule.iter().take(5) {
+        assert_eq!(installment.principal, 0);
+        assert_eq!(installment.interest, monthly_interest);
+    }
+    assert_eq!(schedule.get(5).unwrap().principal, 12_000 * USDC);
+    assert_eq!(
+        p.governance
+            .simulate_loan(&(12_000 * USDC), &800, &180, &RepaymentStructure::Balloon)
+            .schedule,
+        schedule
+    );
+
+    // Paying the first month's interest leaves the principal untouched
+    assert_eq!(
+        p.governance.get_next_payment(&loan_id).unwrap().due_at,
+        start + 30 * DAY
+    );
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &monthly_interest);
+    assert_eq!(p.governance.get_interest_paid(&loan_id), monthly_interest);
+    assert_eq!(
+        p.governance.get_loan_outstanding(&loan_id),
+        Some(12_000 * USDC)
+    );
+    assert_eq!(
+        p.governance.get_next_payment(&loan_id).unwrap().due_at,
+        start + 60 * DAY
+    );
+
+    // Missing an interest-only installment is a default like a missed amortization
+    p.advance_time(60 * DAY);
+    assert!(!p.governance.is_loan_overdue(&loan_id));
+    p.advance_time(1);
+    assert!(p.governance.is_loan_overdue(&loan_id));
+
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &monthly_interest);
+    assert!(!p.governance.is_loan_overdue(&loan_id));
+
+    // Principal is repaid only once every scheduled interest payment is covered
+    p.governance.record_repayment(
+        &p.member(0),
+        &loan_id,
+        &(4 * monthly_interest + 1_000 * USDC),
+    );
+    assert_eq!(
+        p.governance.get_interest_paid(&loan_id),
+        6 * monthly_interest
+    );
+    assert_eq!(
+        p.governance.get_loan_outstanding(&loan_id),
+        Some(11_000 * USDC)
+    );
+    let balloon = p.governance.get_next_payment(&loan_id).unwrap();
+    assert_eq!(Some(balloon.due_at), p.governance.get_loan_due_at(&loan_id));
+    assert_eq!(balloon.principal_repaid, 1_000 * USDC);
+}
+
+#[test]
+fn early_repayment_applies_the_prepayment_policy() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.yield_token
+        .set_profit_reporter(&p.admin, &p.governance.address);
+    let depositor = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&depositor, &(1_000 * USDC), &LockPeriod::SixMonths);
+
+    let lot = coffee_lot(&p, 36_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 12_000 * USDC);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    let schedule = p.governance.get_repayment_schedule(&loan_id);
+    let remaining_interest: u128 = schedule.iter().skip(1).map(|i| i.interest).sum();
+
+    // Without a policy the borrower owes every installment's interest
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(2_000 * USDC));
+    let quote = p.governance.quote_early_repayment(&loan_id);
+    assert_eq!(quote.principal, 10_000 * USDC);
+    assert_eq!(quote.interest_charged, remaining_interest);
+    assert_eq!(quote.interest_rebated, 0);
+
+    assert!(p
+        .governance
+        .try_set_prepayment_policy(&p.member(0), &PrepaymentPolicy::ProRated)
+        .is_err());
+    p.governance
+        .set_prepayment_policy(&p.admin, &PrepaymentPolicy::ProRated);
+
+    // Halfway through the second installment only half its interest has accrued
+    p.advance_time(45 * DAY);
+    let accrued = schedule.get(1).unwrap().interest.div_ceil(2);
+    let repayment = p.governance.record_early_repayment(&p.member(0), &loan_id);
+    assert_eq!(repayment.principal, 10_000 * USDC);
+    assert_eq!(repayment.interest_charged, accrued);
+    assert_eq!(repayment.interest_rebated, remaining_interest - accrued);
+    assert_eq!(repayment.prepayment_fee, 0);
+    assert_eq!(p.governance.get_early_repayment(&loan_id), Some(repayment));
+    assert_eq!(p.governance.get_loan_outstanding(&loan_id), None);
+    assert_eq!(p.governance.get_next_payment(&loan_id), None);
+    assert_eq!(
+        p.governance.get_interest_rebated(),
+        remaining_interest - accrued
+    );
+    assert!(p
+        .governance
+        .try_record_early_repayment(&p.member(0), &loan_id)
+        .is_err());
+
+    // The charged interest is added to the next profit report
+    assert_eq!(p.governance.get_prepayment_income(), accrued);
+    let depositor = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&depositor, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.usdc_admin
+        .mint(&p.member(0), &((1_000 * USDC + accrued) as i128));
+    let report_id = p
+        .governance
+        .submit_profit_report(&p.member(0), &(1_000 * USDC), &0);
+    let proposal = p.governance.get_profit_report_proposal(&report_id).unwrap();
+    assert_eq!(
+        proposal.report.coffee_lending_profit,
+        1_000 * USDC + accrued
+    );
+    assert_eq!(p.governance.get_prepayment_income(), accrued);
+    for index in 0..3 {
+        p.governance
+            .approve_profit_report(&p.member(index), &report_id);
+    }
+    assert_eq!(p.governance.get_prepayment_income(), 0);
+
+    // A prepayment fee is charged on the principal, capped at 5%
+    assert!(p
+        .governance
+        .try_set_prepayment_policy(&p.admin, &PrepaymentPolicy::PrepaymentFee(600))
+        .is_err());
+    p.governance
+        .set_prepayment_policy(&p.admin, &PrepaymentPolicy::PrepaymentFee(100));
+    let second = approved_loan(&p, &lot, 12_000 * USDC);
+    p.governance.execute_loan(&p.member(3), &second);
+    let repayment = p.governance.record_early_repayment(&p.member(0), &second);
+    assert_eq!(repayment.interest_charged, 0);
+    assert_eq!(repayment.prepayment_fee, 120 * USDC);
+    assert_eq!(p.governance.get_prepayment_income(), 120 * USDC);
+}
+
+#[test]
+fn foreign_currency_loan_is_collateralised_at_its_usd_value() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    let registry = RegistryClient::new(&env, &env.register_contract(None, Registry));
+    registry.initialize(&p.admin);
+    registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_ORACLE),
+        &oracle.address,
+    );
+    registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_COFFEE_COLLATERAL),
+        &p.coffee.address,
+    );
+    p.governance.set_registry(&p.admin, &registry.address);
+
+    let eurc = Address::generate(&env);
+    let borrower = Address::generate(&env);
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let submit = || {
+        p.governance.try_submit_loan_proposal_in(
+            &p.member(0),
+            &borrower,
+            &(10_000 * USDC),
+            &eurc,
+            &lot,
+            &800,
+            &180,
+        )
+    };
+    assert!(submit().is_err());
+
+    let eurc_vault = Address::generate(&env);
+    let feed = Symbol::new(&env, "EURC_USD");
+    p.governance
+        .set_loan_currency(&p.admin, &eurc, &eurc_vault, &feed);
+    oracle.set_price(&1_060_000);
+    let loan_id = submit().unwrap().unwrap();
+    assert_eq!(p.governance.get_loan_denomination(&loan_id), Some(eurc));
+    for i in 0..3 {
+        p.governance.approve_loan(&loan_id, &p.member(i));
+    }
+
+    // If EURC rises to $1.08 the loan needs $16_200 of collateral, more than the lot is worth
+    oracle.set_price(&1_080_000);
+    assert_eq!(p.governance.get_loan_usd_value(&loan_id), 10_800 * USDC);
+    assert!(p
+        .governance
+        .try_execute_loan(&p.member(3), &loan_id)
+        .is_err());
+
+    oracle.set_price(&1_060_000);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    assert_eq!(
+        p.governance.get_loan_proposal(&loan_id).unwrap().status,
+        ProposalStatus::Executed
+    );
+}
+
+#[test]
+fn delegate_approvals_count_as_the_members() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let delegate = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&p.member(0), &p.member(1), &(7 * DAY))
+        .is_err());
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&p.member(0), &delegate, &(31 * DAY))
+        .is_err());
+    p.governance
+        .set_committee_delegate(&p.member(0), &delegate, &(7 * DAY));
+    // One address cannot stand in for two members at once
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&p.member(1), &delegate, &(7 * DAY))
+        .is_err());
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let borrower = Address::generate(&env);
+    let proposal_id = p.governance.submit_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &(10_000 * USDC),
+        &lot,
+        &800,
+        &180,
+    );
+
+    // The delegate's approval is the member's, so the member cannot add a second
+    p.governance.approve_loan(&proposal_id, &delegate);
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &p.member(0))
+        .is_err());
+    p.governance.approve_loan(&proposal_id, &p.member(1));
+    assert_eq!(
+        p.governance
+            .get_loan_proposal(&proposal_id)
+            .unwrap()
+            .approvals,
+        2
+    );
+
+    // Delegations lapse on their own and can be revoked early
+    p.advance_time(7 * DAY);
+    assert_eq!(p.governance.get_committee_delegate(&p.member(0)), None);
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &delegate)
+        .is_err());
+
+    p.governance
+        .set_committee_delegate(&p.member(2), &delegate, &DAY);
+    p.governance.revoke_committee_delegate(&p.member(2));
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &delegate)
+        .is_err());
+    p.governance.approve_loan(&proposal_id, &p.member(2));
+    assert_eq!(
+        p.governance.get_loan_proposal(&proposal_id).unwrap().status,
+        ProposalStatus::Approved
+    );
+}
+
+#[test]
+fn session_keys_only_vote_on_loans_until_they_expire() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let key = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_register_session_key(&p.member(0), &key, &0)
+        .is_err());
+    assert!(p
+        .governance
+        .try_register_session_key(&p.member(0), &key, &(MAX_SESSION_KEY_LEDGERS + 1))
+        .is_err());
+    assert!(p
+        .governance
+        .try_register_session_key(&p.member(0), &p.member(1), &100)
+        .is_err());
+    p.governance.register_session_key(&p.member(0), &key, &100);
+    let session = p.governance.get_session_key(&p.member(0)).unwrap();
+    assert_eq!(session.expires_ledger, env.ledger().sequence() + 100);
+    assert!(p
+        .governance
+        .try_register_session_key(&p.member(1), &key, &100)
+        .is_err());
+    assert!(p
+        .governance
+        .try_set_committee_delegate(&p.member(1), &key, &DAY)
+        .is_err());
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let borrower = Address::generate(&env);
+    let proposal = |proposer: &Address| {
+        p.governance.try_submit_loan_proposal(
+            proposer,
+            &borrower,
+            &(10_000 * USDC),
+            &lot,
+            &800,
+            &180,
+        )
+    };
+    // The key signs loan votes only; everything else still needs the member
+    assert!(proposal(&key).is_err());
+    let proposal_id = proposal(&p.member(0)).unwrap().unwrap();
+    p.governance.approve_loan(&proposal_id, &key);
+    assert!(p
+        .governance
+        .try_approve_loan(&proposal_id, &p.member(0))
+        .is_err());
+
+    // Keys lapse after their ledgers and can be revoked early
+    let second = proposal(&p.member(1)).unwrap().unwrap();
+    env.ledger().with_mut(|li| li.sequence_number += 100);
+    assert_eq!(p.governance.get_session_key(&p.member(0)), None);
+    assert!(p.governance.try_reject_loan(&second, &key).is_err());
+
+    p.governance.register_session_key(&p.member(0), &key, &100);
+    p.governance.reject_loan(&second, &key);
+    p.governance.revoke_session_key(&p.member(0));
+    assert!(p.governance.try_approve_loan(&proposal_id, &key).is_err());
+    assert_eq!(p.governance.get_member_stats(&p.member(0)).rejections, 1);
+}
+
+#[test]
+fn unfundable_loan_proposals_are_rejected_at_submission() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let borrower = Address::generate(&env);
+    let submit = |lot: &Address, amount: u128| {
+        p.governance
+            .try_submit_loan_proposal(&p.member(0), &borrower, &amount, lot, &800, &180)
+    };
+
+    // Unknown lots and lots worth under 150% of the loan never reach the committee
+    assert!(submit(&Address::generate(&env), 10_000 * USDC).is_err());
+    let lot = coffee_lot(&p, 14_000 * USDC);
+    assert!(submit(&lot, 10_000 * USDC).is_err());
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let proposal_id = submit(&lot, 10_000 * USDC).unwrap().unwrap();
+    for i in 0..3 {
+        p.governance.approve_loan(&proposal_id, &p.member(i));
+    }
+    p.governance.execute_loan(&p.member(3), &proposal_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &proposal_id, &(10_000 * USDC));
+    assert_eq!(p.coffee.get_pledged_loan(&lot), Some(proposal_id));
+
+    // A pledged lot cannot back a second loan
+    assert!(submit(&lot, 5_000 * USDC).is_err());
+}
+
+#[test]
+fn member_stats_track_submissions_and_responses() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let borrower = Address::generate(&env);
+    let approved = p.governance.submit_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &(10_000 * USDC),
+        &lot,
+        &800,
+        &180,
+    );
+    let rejected = p.governance.submit_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &(5_000 * USDC),
+        &lot,
+        &800,
+        &180,
+    );
+
+    p.advance_time(DAY);
+    p.governance.approve_loan(&approved, &p.member(1));
+    p.governance.reject_loan(&rejected, &p.member(1));
+    assert!(p
+        .governance
+        .try_reject_loan(&approved, &p.member(1))
+        .is_err());
+
+    // Three rejections leave too few members to approve
+    p.advance_time(2 * DAY);
+    for i in 2..4 {
+        p.governance.reject_loan(&rejected, &p.member(i));
+    }
+    assert_eq!(
+        p.governance.get_loan_proposal(&rejected).unwrap().status,
+        ProposalStatus::Rejected
+    );
+    assert!(p
+        .governance
+        .try_approve_loan(&rejected, &p.member(4))
+        .is_err());
+
+    let proposer = p.governance.get_member_stats(&p.member(0));
+    assert_eq!(proposer.proposals_submitted, 2);
+    assert_eq!(proposer.approvals + proposer.rejections, 0);
+    assert_eq!(proposer.average_response_time, 0);
+
+    let stats = p.governance.get_member_stats(&p.member(1));
+    assert_eq!(stats.proposals_submitted, 0);
+    assert_eq!(stats.approvals, 1);
+    assert_eq!(stats.rejections, 1);
+    assert_eq!(stats.total_response_time, 2 * DAY);
+    assert_eq!(stats.average_response_time, DAY);
+    assert_eq!(
+        p.governance
+            .get_member_stats(&p.member(2))
+            .average_response_time,
+        3 * DAY
+    );
+}
+
+#[test]
+fn committee_stipends_are_paid_from_the_treasury_for_active_periods() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let treasury = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&treasury, &(1_000 * USDC), &LockPeriod::TwelveMonths);
+    p.governance.set_treasury(&p.admin, &treasury);
+    p.governance
+        .set_compensation_schedule(&p.admin, &(100 * USDC), &(30 * DAY), &2, &0);
+
+    // Member 0 proposes and approves; member 1 only approves
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    approved_loan(&p, &lot, 10_000 * USDC);
+    assert!(p.governance.try_claim_stipend(&p.member(0)).is_err());
+    assert!(p
+        .governance
+        .try_claim_stipend(&Address::generate(&env))
+        .is_err());
+
+    // Only the member active enough for the period is paid; the other forfeits it
+    p.advance_time(30 * DAY);
+    assert_eq!(p.governance.claim_stipend(&p.member(0)), 100 * USDC);
+    assert_eq!(p.yield_token.balance(&p.member(0)), (100 * USDC) as i128);
+    assert_eq!(p.yield_token.balance(&treasury), (900 * USDC) as i128);
+    assert_eq!(p.governance.claim_stipend(&p.member(1)), 0);
+    let account = p.governance.get_stipend_account(&p.member(1));
+    assert_eq!(
+        account.claimed_through,
+        p.governance.get_compensation_schedule().unwrap().started_at + 30 * DAY
+    );
+    assert!(p.governance.try_claim_stipend(&p.member(1)).is_err());
+
+    // Slow responders are blocked once a response-time limit is set
+    let borrower = Address::generate(&env);
+    let proposal_id = p.governance.submit_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &(5_000 * USDC),
+        &lot,
+        &800,
+        &180,
+    );
+    p.advance_time(4 * DAY);
+    p.governance.reject_loan(&proposal_id, &p.member(1));
+    p.governance
+        .set_compensation_schedule(&p.admin, &(100 * USDC), &DAY, &1, &DAY);
+    p.advance_time(DAY);
+    assert!(p.governance.try_claim_stipend(&p.member(1)).is_err());
+    assert_eq!(p.governance.claim_stipend(&p.member(0)), 100 * USDC);
+    assert_eq!(
+        p.governance.get_stipend_account(&p.member(0)).total_claimed,
+        200 * USDC
+    );
+}
+
+#[test]
+fn frozen_loans_stop_disbursing_until_the_committee_unfreezes_them() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+    p.governance.define_loan_tranches(
+        &p.member(0),
+        &loan_id,
+        &vec![
+            &env,
+            String::from_str(&env, "planting"),
+            String::from_str(&env, "harvest"),
+        ],
+        &vec![&env, 5_000 * USDC, 5_000 * USDC],
+    );
+
+    // Only executed loans with principal outstanding can be frozen
+    assert!(p
+        .governance
+        .try_freeze_loan(&p.member(4), &loan_id)
+        .is_err());
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    let first_due = p.governance.get_next_payment(&loan_id).unwrap().due_at;
+
+    assert!(p
+        .governance
+        .try_freeze_loan(&Address::generate(&env), &loan_id)
+        .is_err());
+    p.governance.freeze_loan(&p.member(4), &loan_id);
+    assert!(p
+        .governance
+        .try_freeze_loan(&p.member(3), &loan_id)
+        .is_err());
+    assert_eq!(
+        p.governance.get_loan_freeze(&loan_id).unwrap().frozen_by,
+        p.member(4)
+    );
+
+    // No disbursements or early repayment while frozen, and the repayment clock stops
+    assert!(p
+        .governance
+        .try_approve_tranche(&p.member(0), &loan_id, &0)
+        .is_err());
+    assert!(p
+        .governance
+        .try_record_early_repayment(&p.member(0), &loan_id)
+        .is_err());
+    p.advance_time(40 * DAY);
+    assert!(!p.governance.is_loan_overdue(&loan_id));
+
+    assert!(!p.governance.approve_unfreeze(&p.member(0), &loan_id));
+    assert!(p
+        .governance
+        .try_approve_unfreeze(&p.member(0), &loan_id)
+        .is_err());
+    assert!(!p.governance.approve_unfreeze(&p.member(1), &loan_id));
+    assert!(p.governance.approve_unfreeze(&p.member(2), &loan_id));
+    assert_eq!(p.governance.get_loan_freeze(&loan_id), None);
+
+    // Due dates move out by the time spent frozen
+    assert_eq!(
+        p.governance.get_next_payment(&loan_id).unwrap().due_at,
+        first_due + 40 * DAY
+    );
+    assert!(!p.governance.is_loan_overdue(&loan_id));
+    assert!(!p.governance.approve_tranche(&p.member(0), &loan_id, &0));
+
+    // A later freeze needs a fresh round of approvals
+    p.governance.freeze_loan(&p.member(1), &loan_id);
+    assert!(!p.governance.approve_unfreeze(&p.member(0), &loan_id));
+}
+
+#[test]
+fn pledged_vault_positions_are_locked_until_the_loan_is_repaid() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.usdc_vault.set_governance(&p.admin, &p.governance.address);
+
+    let depositor = p.funded_user(5_000 * USDC);
+    p.usdc_vault
+        .deposit(&depositor, &(5_000 * USDC), &LockPeriod::ThreeMonths);
+    let position_id = p.usdc_vault.get_active_receipt(&depositor).unwrap().id;
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+
+    // Only the loan manager places liens, and only on the owner's open position
+    assert!(p
+        .usdc_vault
+        .try_encumber_position(&p.admin, &depositor, &position_id, &loan_id)
+        .is_err());
+    assert!(p
+        .governance
+        .try_pledge_vault_position(
+            &p.member(0),
+            &loan_id,
+            &p.usdc_vault.address,
+            &depositor,
+            &(position_id + 1),
+        )
+        .is_err());
+    p.governance.pledge_vault_position(
+        &p.member(0),
+        &loan_id,
+        &p.usdc_vault.address,
+        &depositor,
+        &position_id,
+    );
+    let encumbrance = p
+        .usdc_vault
+        .get_encumbrance(&depositor, &position_id)
+        .unwrap();
+    assert_eq!(encumbrance.loan_id, loan_id);
+    assert_eq!(encumbrance.lien_holder, p.governance.address);
+    assert_eq!(encumbrance.amount, 5_000 * USDC);
+
+    p.advance_time(91 * DAY);
+    assert!(p.usdc_vault.try_withdraw(&depositor).is_err());
+    assert!(p.governance.try_release_vault_liens(&loan_id).is_err());
+
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(10_000 * USDC));
+    assert_eq!(p.governance.release_vault_liens(&loan_id), 1);
+    assert_eq!(p.governance.get_vault_liens(&loan_id).len(), 0);
+    assert_eq!(p.usdc_vault.get_encumbrance(&depositor, &position_id), None);
+
+    p.fund_usdc_reserve(1_000 * USDC);
+    assert!(p.usdc_vault.withdraw(&depositor) >= 5_000 * USDC);
+}
+
+#[test]
+fn dual_collateral_loans_combine_coffee_and_gold_and_seize_gold_for_shortfalls() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let gold_admin = StellarAssetClient::new(&env, &gold_sac.address());
+    let vault = GoldVaultClient::new(&env, &env.register_contract(None, GoldVault));
+    vault.initialize(
+        &p.admin,
+        &p.yield_token.address,
+        &p.oracle.address,
+        &vec![&env, gold.address.clone()],
+    );
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+    vault.set_governance(&p.admin, &p.governance.address);
+
+    let treasury = Address::generate(&env);
+    let registry = RegistryClient::new(&env, &env.register_contract(None, Registry));
+    registry.initialize(&p.admin);
+    for (name, address) in [
+        (REGISTRY_COFFEE_COLLATERAL, &p.coffee.address),
+        (REGISTRY_GOLD_VAULT, &vault.address),
+        (REGISTRY_TREASURY, &treasury),
+    ] {
+        registry.set_address(&p.admin, &Symbol::new(&env, name), address);
+    }
+    p.governance.set_registry(&p.admin, &registry.address);
+
+    // Two ounces of gold at $2,000 each
+    let ounce = 10_000_000u128;
+    let owner = Address::generate(&env);
+    gold_admin.mint(&owner, &((2 * ounce) as i128));
+    vault.deposit(
+        &owner,
+        &gold.address,
+        &(2 * ounce),
+        &LockPeriod::ThreeMonths,
+    );
+    gold_admin.mint(&vault.address, &(ounce as i128));
+    let position_id = vault.get_active_receipt(&owner).unwrap().id;
+    assert_eq!(vault.get_position_value(&owner), 4_000 * USDC);
+
+    // A $12,000 lot alone cannot back $10,000 at 150%, but with the gold it can
+    let borrower = Address::generate(&env);
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 12_000 * USDC);
+    assert!(p
+        .governance
+        .try_submit_loan_proposal(&p.member(0), &borrower, &loan_amount, &lot, &800, &180)
+        .is_err());
+    assert!(p
+        .governance
+        .try_submit_dual_collateral_loan_proposal(
+            &p.member(0),
+            &borrower,
+            &loan_amount,
+            &lot,
+            &800,
+            &180,
+            &owner,
+            &(position_id + 1),
+        )
+        .is_err());
+    let loan_id = p.governance.submit_dual_collateral_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &loan_amount,
+        &lot,
+        &800,
+        &180,
+        &owner,
+        &position_id,
+    );
+    assert_eq!(
+        p.governance.get_gold_collateral(&loan_id).unwrap().owner,
+        owner
+    );
+    assert_eq!(
+        vault.get_encumbrance(&owner, &position_id).unwrap().loan_id,
+        loan_id
+    );
+    let combined = p.governance.get_combined_collateral(&loan_id);
+    assert_eq!(combined.coffee_value_usd, 12_000 * USDC);
+    assert_eq!(combined.gold_value_usd, 4_000 * USDC);
+    assert_eq!(combined.loan_value_usd, loan_amount);
+    assert_eq!(combined.ltv_bps, 6_250);
+
+    for i in 0..3 {
+        p.governance.approve_loan(&loan_id, &p.member(i));
+    }
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &loan_amount);
+
+    // The gold stays locked past its own lock, and is only seized after a short liquidation
+    p.advance_time(91 * DAY);
+    assert!(vault.try_withdraw(&owner).is_err());
+    let keeper = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_seize_gold_collateral(&keeper, &loan_id)
+        .is_err());
+    let shortfall = p
+        .coffee
+        .liquidate_collateral(&p.governance.address, &loan_id, &(7_000 * USDC));
+    assert_eq!(shortfall, 3_000 * USDC);
+    p.governance.record_bad_debt(
+        &p.member(0),
+        &BadDebtSource::LiquidationShortfall,
+        &loan_id,
+        &p.usdc_vault.address,
+        &shortfall,
+    );
+
+    // Closing the loan does not free gold that still owes the shortfall
+    assert_eq!(p.governance.release_vault_liens(&loan_id), 0);
+    assert!(p.governance.get_gold_collateral(&loan_id).is_some());
+
+    assert_eq!(p.governance.total_bad_debt(), shortfall);
+
+    // Gold worth the shortfall goes to the vault that bore it, not the treasury
+    assert_eq!(
+        p.governance.seize_gold_collateral(&keeper, &loan_id),
+        3_000 * USDC
+    );
+    let seizure = p.governance.get_gold_seizure(&loan_id).unwrap();
+    assert!(seizure.gold_seized > 2 * ounce);
+    assert_eq!(seizure.creditor, p.usdc_vault.address);
+    assert_eq!(seizure.gold_to_creditor, 3 * ounce / 2);
+    assert_eq!(gold.balance(&p.usdc_vault.address), (3 * ounce / 2) as i128);
+    assert_eq!(gold.balance(&treasury), 0);
+    assert_eq!(
+        gold.balance(&owner),
+        (seizure.gold_seized - 3 * ounce / 2) as i128
+    );
+    assert_eq!(vault.get_deposit_info(&owner), None);
+    assert_eq!(p.governance.get_gold_collateral(&loan_id), None);
+    assert!(p
+        .governance
+        .try_seize_gold_collateral(&keeper, &loan_id)
+        .is_err());
+
+    // The recovery clears the shortfall from the bad-debt books
+    assert_eq!(p.governance.total_bad_debt(), 0);
+    let record = p.governance.get_bad_debt(&1).unwrap();
+    assert_eq!(record.amount, 0);
+    assert_eq!(record.status, ProposalStatus::Executed);
+}
+
+/// Pledge two ounces of gold from a new owner toward an executed $10,000 loan on a $12,000 lot
+///
+/// The gold vault holds an extra ounce so the position's yield can be paid in full.
+fn gold_backed_loan(p: &Protocol) -> (Address, BytesN<32>) {
+    let owner = p.gold_holder(2 * OUNCE);
+    p.gold_vault.deposit(
+        &owner,
+        &p.gold.address,
+        &(2 * OUNCE),
+        &LockPeriod::ThreeMonths,
+    );
+    p.gold_admin.mint(&p.gold_vault.address, &(OUNCE as i128));
+    let position_id = p.gold_vault.get_active_receipt(&owner).unwrap().id;
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(p, 12_000 * USDC);
+    let loan_id = p.governance.submit_dual_collateral_loan_proposal(
+        &p.member(0),
+        &Address::generate(p.env),
+        &loan_amount,
+        &lot,
+        &800,
+        &180,
+        &owner,
+        &position_id,
+    );
+    for i in 0..3 {
+        p.governance.approve_loan(&loan_id, &p.member(i));
+    }
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &loan_amount);
+    (owner, loan_id)
+}
+
+#[test]
+fn repaid_dual_collateral_loans_release_their_gold_unseized() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let (owner, loan_id) = gold_backed_loan(&p);
+
+    p.advance_time(91 * DAY);
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(10_000 * USDC));
+
+    // With no liquidation shortfall there is nothing to seize, and the lien comes off
+    let keeper = Address::generate(&env);
+    assert!(p
+        .governance
+        .try_seize_gold_collateral(&keeper, &loan_id)
+        .is_err());
+    assert_eq!(p.governance.release_vault_liens(&loan_id), 1);
+    assert_eq!(p.governance.get_gold_collateral(&loan_id), None);
+    assert_eq!(p.governance.get_gold_seizure(&loan_id), None);
+    assert!(p
+        .governance
+        .try_seize_gold_collateral(&keeper, &loan_id)
+        .is_err());
+
+    p.gold_vault.withdraw(&owner);
+    assert!(p.gold.balance(&owner) > (2 * OUNCE) as i128);
+    assert_eq!(p.governance.total_bad_debt(), 0);
+}
+
+#[test]
+fn gold_seized_before_the_shortfall_is_recorded_nets_it_down() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let (owner, loan_id) = gold_backed_loan(&p);
+
+    // A $7,000 shortfall exceeds the gold, so all of it goes to the USDC vault
+    p.advance_time(91 * DAY);
+    let shortfall = p
+        .coffee
+        .liquidate_collateral(&p.governance.address, &loan_id, &(3_000 * USDC));
+    assert_eq!(shortfall, 7_000 * USDC);
+    let keeper = Address::generate(&env);
+    let recovered = p.governance.seize_gold_collateral(&keeper, &loan_id);
+    let seizure = p.governance.get_gold_seizure(&loan_id).unwrap();
+    assert!(recovered > 4_000 * USDC && recovered < shortfall);
+    assert_eq!(seizure.creditor, p.usdc_vault.address);
+    assert_eq!(seizure.gold_to_creditor, seizure.gold_seized);
+    assert_eq!(
+        p.gold.balance(&p.usdc_vault.address),
+        seizure.gold_seized as i128
+    );
+    assert_eq!(p.gold.balance(&owner), 0);
+
+    // Only what the gold did not cover is booked as bad debt
+    let record_id = p.governance.record_bad_debt(
+        &p.member(0),
+        &BadDebtSource::LiquidationShortfall,
+        &loan_id,
+        &p.usdc_vault.address,
+        &shortfall,
+    );
+    assert_eq!(
+        p.governance.get_bad_debt(&record_id).unwrap().amount,
+        shortfall - recovered
+    );
+    assert_eq!(p.governance.total_bad_debt(), shortfall - recovered);
+}
+
+#[test]
+fn proposed_rates_hold_until_the_rate_lock_expires() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.governance.set_base_loan_rate(&p.admin, &500);
+
+    // Proposals cannot undercut the base rate
+    let borrower = Address::generate(&env);
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    assert!(p
+        .governance
+        .try_submit_loan_proposal(&p.member(0), &borrower, &(10_000 * USDC), &lot, &400, &180)
+        .is_err());
+
+    let locked_id = approved_loan(&p, &lot, 10_000 * USDC);
+    let expiring_id = approved_loan(&p, &coffee_lot(&p, 16_000 * USDC), 10_000 * USDC);
+    let rate_lock = p.governance.get_rate_lock(&locked_id).unwrap();
+    assert_eq!(rate_lock.base_rate, 500);
+    assert_eq!(rate_lock.locked_rate, 800);
+    assert_eq!(rate_lock.expires_at, rate_lock.locked_at + RATE_LOCK_WINDOW);
+
+    // A base rate rise does not reach a loan executed inside its lock
+    p.governance.set_base_loan_rate(&p.admin, &700);
+    p.advance_time(RATE_LOCK_WINDOW);
+    p.governance.execute_loan(&p.member(3), &locked_id);
+    assert_eq!(
+        p.governance
+            .get_loan_proposal(&locked_id)
+            .unwrap()
+            .interest_rate,
+        800
+    );
+
+    // Once the lock has expired the loan keeps its spread over the new base rate
+    p.advance_time(1);
+    p.governance.execute_loan(&p.member(3), &expiring_id);
+    assert_eq!(
+        p.governance
+            .get_loan_proposal(&expiring_id)
+            .unwrap()
+            .interest_rate,
+        1_000
+    );
+}
+
+#[test]
+fn simulated_loans_match_the_executed_schedule() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 10_000 * USDC;
+    let preview = p
+        .governance
+        .simulate_loan(&amount, &800, &180, &RepaymentStructure::Amortizing);
+    assert_eq!(preview.required_collateral, amount * 3 / 2);
+    assert_eq!(preview.schedule.len(), 6);
+    let total_interest: u128 = preview.schedule.iter().map(|i| i.interest).sum();
+    assert_eq!(preview.total_interest, total_interest);
+    assert_eq!(preview.protocol_fee, total_interest / 5);
+
+    // Executing the same terms in the same ledger produces the previewed schedule
+    let lot = coffee_lot(&p, preview.required_collateral);
+    let loan_id = approved_loan(&p, &lot, amount);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    assert_eq!(
+        p.governance.get_repayment_schedule(&loan_id),
+        preview.schedule
+    );
+    assert_eq!(p.governance.get_loan_due_at(&loan_id), Some(preview.due_at));
+
+    p.governance.set_base_loan_rate(&p.admin, &900);
+    assert!(p
+        .governance
+        .try_simulate_loan(&amount, &800, &180, &RepaymentStructure::Amortizing)
+        .is_err());
+}
+
+#[test]
+fn coffee_lots_are_revalued_from_the_price_index_and_grade_curve() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    assert!(p.coffee.try_price_per_kg(&88).is_err());
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.coffee.set_price_oracle(&p.admin, &oracle.address);
+    oracle.set_price(&USDC);
+    assert_eq!(p.coffee.refresh_base_price(), USDC);
+
+    // Without a curve every grade trades at the base price
+    assert_eq!(p.coffee.price_per_kg(&88), USDC);
+
+    let curve = vec![
+        &env,
+        GradePoint {
+            grade: 60,
+            multiplier_bps: 6_000,
+        },
+        GradePoint {
+            grade: 80,
+            multiplier_bps: 9_000,
+        },
+        GradePoint {
+            grade: 90,
+            multiplier_bps: 12_000,
+        },
+    ];
+    assert!(p
+        .coffee
+        .try_set_grade_curve(&Address::generate(&env), &curve)
+        .is_err());
+    let unordered = vec![
+        &env,
+        GradePoint {
+            grade: 80,
+            multiplier_bps: 9_000,
+        },
+        GradePoint {
+            grade: 60,
+            multiplier_bps: 6_000,
+        },
+    ];
+    assert!(p.coffee.try_set_grade_curve(&p.admin, &unordered).is_err());
+    p.coffee.set_grade_curve(&p.governance.address, &curve);
+
+    // Grades are interpolated between points and clamped outside them
+    assert_eq!(p.coffee.grade_multiplier(&40), 6_000);
+    assert_eq!(p.coffee.grade_multiplier(&70), 7_500);
+    assert_eq!(p.coffee.grade_multiplier(&88), 11_400);
+    assert_eq!(p.coffee.grade_multiplier(&100), 12_000);
+    assert_eq!(p.coffee.price_per_kg(&88), USDC * 114 / 100);
+
+    // 20_000 kg of grade 88 at $1.14 per kg
+    assert_eq!(p.coffee.refresh_valuation(&lot), 22_800 * USDC);
+    assert_eq!(
+        p.coffee
+            .get_collateral_info(&lot)
+            .unwrap()
+            .estimated_value_usd,
+        22_800 * USDC
+    );
+
+    // A base price older than a day cannot drive valuations
+    p.advance_time(2 * DAY);
+    assert!(p.coffee.try_refresh_valuation(&lot).is_err());
+    oracle.set_price(&(USDC / 2));
+    p.coffee.refresh_base_price();
+    assert_eq!(p.coffee.refresh_valuation(&lot), 11_400 * USDC);
+
+    p.coffee.mark_expired(&p.admin, &lot);
+    assert!(p.coffee.try_refresh_valuation(&lot).is_err());
+}
+
+#[test]
+fn anyone_can_liquidate_a_defaulted_loan_for_a_bounty() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, loan_amount);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &loan_amount);
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.coffee.set_price_oracle(&p.admin, &oracle.address);
+    p.coffee
+        .set_liquidation_proceeds(&p.admin, &p.usdc.address, &p.usdc_vault.address);
+    assert!(p
+        .coffee
+        .try_set_liquidation_bounty(&p.admin, &1_500)
+        .is_err());
+    p.coffee.set_liquidation_bounty(&p.admin, &500);
+
+    // Nothing can be liquidated before an installment is overdue past the grace period
+    assert!(p
+        .governance
+        .try_set_liquidation_grace_period(&p.admin, &(31 * DAY))
+        .is_err());
+    p.governance
+        .set_liquidation_grace_period(&p.admin, &(3 * DAY));
+    let liquidator = p.funded_user(10_000 * USDC);
+    assert!(p.coffee.try_liquidate(&liquidator, &loan_id).is_err());
+    p.advance_time(31 * DAY);
+    oracle.set_price(&(USDC * 45 / 100));
+    p.coffee.refresh_base_price();
+    assert!(p.governance.is_loan_overdue(&loan_id));
+    assert!(p.coffee.try_liquidate(&liquidator, &loan_id).is_err());
+    p.advance_time(3 * DAY);
+    p.coffee.refresh_base_price();
+
+    // 20_000 kg at $0.45 recovers $9_000; the liquidator keeps 5% of it
+    let bounty = p.coffee.liquidate(&liquidator, &loan_id);
+    assert_eq!(bounty, 450 * USDC);
+    assert_eq!(p.usdc.balance(&liquidator), (1_450 * USDC) as i128);
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address),
+        (8_550 * USDC) as i128
+    );
+    assert_eq!(p.coffee.get_liquidation_shortfall(&loan_id), 1_450 * USDC);
+    assert_eq!(p.coffee.get_liquidator(&lot), Some(liquidator.clone()));
+    assert_eq!(
+        p.coffee.get_collateral_info(&lot).unwrap().status,
+        CollateralStatus::Liquidated
+    );
+
+    // A liquidated lot cannot be sold twice
+    assert!(p.coffee.try_liquidate(&liquidator, &loan_id).is_err());
+}
+
+#[test]
+fn liquidation_surplus_is_refunded_to_the_borrower() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let loan_amount = 10_000 * USDC;
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, loan_amount);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &loan_amount);
+    let borrower = p.governance.get_loan_proposal(&loan_id).unwrap().borrower;
+    let borrower_balance = p.usdc.balance(&borrower);
+    let vault_balance = p.usdc.balance(&p.usdc_vault.address);
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.coffee.set_price_oracle(&p.admin, &oracle.address);
+    p.coffee
+        .set_liquidation_proceeds(&p.admin, &p.usdc.address, &p.usdc_vault.address);
+    p.coffee.set_liquidation_bounty(&p.admin, &500);
+
+    // 20_000 kg at $0.60 recovers $12_000: $600 bounty, $10_000 to the vault, $1_400 back
+    let liquidator = p.funded_user(12_000 * USDC);
+    p.advance_time(31 * DAY);
+    oracle.set_price(&(USDC * 60 / 100));
+    p.coffee.refresh_base_price();
+    assert_eq!(p.coffee.liquidate(&liquidator, &loan_id), 600 * USDC);
+
+    assert_eq!(p.usdc.balance(&liquidator), (600 * USDC) as i128);
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address) - vault_balance,
+        loan_amount as i128
+    );
+    assert_eq!(
+        p.usdc.balance(&borrower) - borrower_balance,
+        (1_400 * USDC) as i128
+    );
+    assert_eq!(p.coffee.get_liquidation_shortfall(&loan_id), 0);
+}
+
+#[test]
+fn unencumbered_lots_sell_through_listings_and_offers() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.coffee.set_market_token(&p.admin, &p.usdc.address);
+
+    let lot = coffee_lot(&p, 60_000 * USDC);
+    let farmer = p.coffee.get_owner(&lot);
+    let price = 50_000 * USDC;
+    assert!(p
+        .coffee
+        .try_list_lot(&Address::generate(&env), &lot, &price)
+        .is_err());
+    p.coffee.list_lot(&farmer, &lot, &price);
+
+    // Buyers are protected against the asking price moving up
+    let roaster = p.funded_user(price);
+    assert_eq!(
+        p.coffee.try_buy_lot(&roaster, &lot, &(price - 1)),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+    p.coffee.buy_lot(&roaster, &lot, &price);
+    assert_eq!(p.coffee.get_owner(&lot), roaster);
+    assert_eq!(p.usdc.balance(&farmer), price as i128);
+    assert!(p.coffee.get_listing(&lot).is_none());
+
+    // Offers are escrowed until the owner accepts or the buyer withdraws
+    let bid = 55_000 * USDC;
+    let trader = p.funded_user(bid);
+    let lowballer = p.funded_user(bid);
+    p.coffee.make_offer(&trader, &lot, &bid);
+    p.coffee.make_offer(&lowballer, &lot, &(bid / 2));
+    assert_eq!(p.usdc.balance(&p.coffee.address), (bid + bid / 2) as i128);
+    assert_eq!(
+        p.coffee.get_offer(&lot, &trader).unwrap().token,
+        p.usdc.address
+    );
+
+    // Escrowed offers settle in the token they were made in
+    p.coffee.set_market_token(&p.admin, &p.gold.address);
+    assert_eq!(p.coffee.withdraw_offer(&lowballer, &lot), bid / 2);
+    assert_eq!(p.usdc.balance(&lowballer), bid as i128);
+
+    assert!(p.coffee.try_accept_offer(&farmer, &lot, &trader).is_err());
+    assert_eq!(p.coffee.accept_offer(&roaster, &lot, &trader), bid);
+    assert_eq!(p.coffee.get_owner(&lot), trader);
+    assert_eq!(p.usdc.balance(&roaster), bid as i128);
+    let sale = p.coffee.get_last_sale(&lot).unwrap();
+    assert_eq!(sale.seller, roaster);
+    assert_eq!(sale.price, bid);
+    assert_eq!(sale.token, p.usdc.address);
+
+    // A lot pledged to a loan can't be sold
+    let loan_id = BytesN::from_array(&env, &[7u8; 32]);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(20_000 * USDC));
+    assert!(p.coffee.try_list_lot(&trader, &lot, &price).is_err());
+}
+
+#[test]
+fn falling_valuations_raise_tiered_health_alerts() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // 20,000 kg backing a 20,000 USDC loan: each 1% of price moves health by 100 bps
+    let lot = coffee_lot(&p, 30_000 * USDC);
+    let loan_id = BytesN::from_array(&env, &[9u8; 32]);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(20_000 * USDC));
+    assert_eq!(p.coffee.get_health_factor(&loan_id), 15_000);
+
+    let thresholds = AlertThresholds {
+        watch_bps: 14_000,
+        warning_bps: 12_000,
+        critical_bps: 11_000,
+    };
+    assert!(p
+        .coffee
+        .try_set_alert_thresholds(&Address::generate(&env), &Some(thresholds.clone()))
+        .is_err());
+    let inverted = AlertThresholds {
+        watch_bps: 11_000,
+        warning_bps: 12_000,
+        critical_bps: 14_000,
+    };
+    assert!(p
+        .coffee
+        .try_set_alert_thresholds(&p.admin, &Some(inverted))
+        .is_err());
+    p.coffee
+        .set_alert_thresholds(&p.governance.address, &Some(thresholds.clone()));
+    assert_eq!(p.coffee.get_alert_thresholds(), Some(thresholds));
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.coffee.set_price_oracle(&p.admin, &oracle.address);
+    let revalue = |price_per_kg: u128| {
+        oracle.set_price(&price_per_kg);
+        p.coffee.refresh_base_price();
+        p.coffee.refresh_valuation(&lot);
+    };
+    let last_alert = || {
+        let (contract, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(contract, p.coffee.address);
+        assert_eq!(
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap(),
+            Symbol::new(&env, "alert")
+        );
+        <(AlertTier, u128)>::try_from_val(&env, &data).unwrap()
+    };
+
+    revalue(USDC * 3 / 2);
+    assert_eq!(p.coffee.get_alert_tier(&loan_id), AlertTier::Healthy);
+
+    // Each threshold crossed on the way down escalates the alert
+    revalue(USDC * 13 / 10);
+    assert_eq!(last_alert(), (AlertTier::Watch, 13_000));
+    revalue(USDC * 23 / 20);
+    assert_eq!(last_alert(), (AlertTier::Warning, 11_500));
+    revalue(USDC);
+    assert_eq!(last_alert(), (AlertTier::Critical, 10_000));
+    assert_eq!(p.coffee.get_alert_tier(&loan_id), AlertTier::Critical);
+
+    // A recovery is announced too, so monitors can stand down
+    revalue(USDC * 3 / 2);
+    assert_eq!(last_alert(), (AlertTier::Healthy, 15_000));
+    assert_eq!(p.coffee.get_alert_tier(&loan_id), AlertTier::Healthy);
+}
+
+#[test]
+fn syndicated_loans_share_repayments_pro_rata() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+
+    let anchor = p.funded_user(10_000 * USDC);
+    let partner = p.funded_user(1_500 * USDC);
+    assert!(p
+        .governance
+        .try_fund_loan(&anchor, &loan_id, &p.usdc.address, &(3_000 * USDC))
+        .is_err());
+    p.governance.set_syndicate_lender(&p.admin, &anchor, &true);
+    p.governance.set_syndicate_lender(&p.admin, &partner, &true);
+
+    p.governance
+        .fund_loan(&anchor, &loan_id, &p.usdc.address, &(3_000 * USDC));
+    assert_eq!(
+        p.governance
+            .fund_loan(&partner, &loan_id, &p.usdc.address, &(1_000 * USDC)),
+        4_000 * USDC
+    );
+    // Lenders cannot fund more than the principal
+    assert!(p
+        .governance
+        .try_fund_loan(&anchor, &loan_id, &p.usdc.address, &(7_000 * USDC))
+        .is_err());
+    assert_eq!(
+        p.usdc.balance(&p.governance.address),
+        (4_000 * USDC) as i128
+    );
+    assert_eq!(p.governance.get_syndicated_amount(&loan_id), 4_000 * USDC);
+
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    assert!(p
+        .governance
+        .try_fund_loan(&anchor, &loan_id, &p.usdc.address, &(1_000 * USDC))
+        .is_err());
+
+    // Half the principal comes back; each lender is credited its share of it
+    p.governance
+        .record_repayment(&p.member(0), &loan_id, &(5_000 * USDC));
+    let syndicate = p.governance.get_loan_syndicate(&loan_id);
+    assert_eq!(syndicate.get(0).unwrap().credited, 1_500 * USDC);
+    assert_eq!(syndicate.get(1).unwrap().credited, 500 * USDC);
+
+    assert_eq!(
+        p.governance.claim_syndicate_repayment(&anchor, &loan_id),
+        1_500 * USDC
+    );
+    assert_eq!(p.usdc.balance(&anchor), (8_500 * USDC) as i128);
+    assert!(p
+        .governance
+        .try_claim_syndicate_repayment(&anchor, &loan_id)
+        .is_err());
+    assert_eq!(
+        p.governance
+            .get_loan_syndicate(&loan_id)
+            .get(0)
+            .unwrap()
+            .claimed,
+        1_500 * USDC
+    );
+
+    // Funding committed to a loan the committee rejects can be taken back
+    let borrower = Address::generate(&env);
+    let rejected = p.governance.submit_loan_proposal(
+        &p.member(0),
+        &borrower,
+        &(2_000 * USDC),
+        &coffee_lot(&p, 4_000 * USDC),
+        &800,
+        &180,
+    );
+    p.governance
+        .fund_loan(&partner, &rejected, &p.usdc.address, &(500 * USDC));
+    assert!(p
+        .governance
+        .try_withdraw_loan_funding(&partner, &rejected)
+        .is_err());
+    for member in 0..3 {
+        p.governance.reject_loan(&rejected, &p.member(member));
+    }
+    assert_eq!(
+        p.governance.withdraw_loan_funding(&partner, &rejected),
+        500 * USDC
+    );
+    assert!(p.governance.get_loan_syndicate(&rejected).is_empty());
+}
+
+#[test]
+fn collateral_indexes_feed_the_summary_dashboard() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let summary = p.coffee.get_collateral_summary();
+    assert_eq!(
+        (summary.active, summary.liquidated, summary.expired),
+        (1, 0, 0)
+    );
+    assert_eq!(summary.active_value_usd, 16_000 * USDC);
+    assert_eq!(summary.average_grade, 88);
+    assert_eq!(p.coffee.list_active_collateral(), vec![&env, lot.clone()]);
+
+    let loan_id = approved_loan(&p, &lot, 10_000 * USDC);
+    p.governance.execute_loan(&p.member(3), &loan_id);
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(10_000 * USDC));
+    p.coffee
+        .register_collateral(&p.governance.address, &lot, &loan_id, &(10_000 * USDC));
+    assert_eq!(
+        p.coffee.list_collateralized_loans(&0, &10),
+        vec![&env, loan_id]
+    );
+    assert!(p.coffee.list_collateralized_loans(&1, &10).is_empty());
+
+    // Expiry moves the lot between status indexes and out of the active totals
+    p.coffee.mark_expired(&p.admin, &lot);
+    assert!(p
+        .coffee
+        .list_collateral(&CollateralStatus::Active, &0, &10)
+        .is_empty());
+    assert_eq!(
+        p.coffee
+            .list_collateral(&CollateralStatus::Expired, &0, &10),
+        vec![&env, lot]
+    );
+    let summary = p.coffee.get_collateral_summary();
+    assert_eq!(
+        (summary.active, summary.liquidated, summary.expired),
+        (0, 0, 1)
+    );
+    assert_eq!(summary.active_value_usd, 0);
+    assert_eq!(summary.average_grade, 0);
+}
+
+#[test]
+fn delivered_lots_are_retired_out_of_the_active_book() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let lot = coffee_lot(&p, 16_000 * USDC);
+    let owner = p.coffee.get_owner(&lot);
+    p.coffee.list_lot(&owner, &lot, &(15_000 * USDC));
+
+    let proof = BytesN::from_array(&env, &[7; 32]);
+    assert!(p
+        .coffee
+        .try_retire_asset(&Address::generate(&env), &lot, &proof)
+        .is_err());
+    p.coffee.retire_asset(&owner, &lot, &proof);
+
+    let info = p.coffee.get_collateral_info(&lot).unwrap();
+    assert_eq!(info.status, CollateralStatus::Retired);
+    assert_eq!(p.coffee.get_delivery_proof(&lot), Some(proof.clone()));
+    assert!(p.coffee.get_listing(&lot).is_none());
+    assert!(p.coffee.list_active_collateral().is_empty());
+    assert_eq!(
+        p.coffee
+            .list_collateral(&CollateralStatus::Retired, &0, &10),
+        vec![&env, lot.clone()]
+    );
+    let summary = p.coffee.get_collateral_summary();
+    assert_eq!((summary.active, summary.retired), (0, 1));
+    assert_eq!(summary.active_value_usd, 0);
+
+    // A retired lot can be neither retired again, sold nor pledged
+    assert!(p.coffee.try_retire_asset(&owner, &lot, &proof).is_err());
+    assert!(p
+        .coffee
+        .try_list_lot(&owner, &lot, &(15_000 * USDC))
+        .is_err());
+    assert!(p
+        .coffee
+        .try_register_collateral(
+            &p.governance.address,
+            &lot,
+            &BytesN::from_array(&env, &[1; 32]),
+            &(1_000 * USDC)
+        )
+        .is_err());
+}
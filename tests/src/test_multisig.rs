@@ -0,0 +1,238 @@
+use crate::setup::{Protocol, USDC};
+use multisig::{Multisig, MultisigClient};
+use shared::{
+    AdminOp, ContractError, LockPeriod, LockTierCap, ProposalStatus, Role, TIMELOCK_MIN_DELAY,
+};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol, Val, Vec};
+
+fn admin_op(env: &Env, target: &Address, function: &str, args: Vec<Val>) -> AdminOp {
+    AdminOp {
+        target: target.clone(),
+        function: Symbol::new(env, function),
+        args,
+    }
+}
+
+#[test]
+fn batched_admin_calls_apply_together_or_not_at_all() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let signers = vec![&env, p.member(0), p.member(1), p.member(2)];
+    let multisig = MultisigClient::new(&env, &env.register_contract(None, Multisig));
+    multisig.initialize(&signers, &2);
+    p.usdc_vault.set_admin(&p.admin, &multisig.address);
+
+    let vault = &p.usdc_vault.address;
+    let admin = multisig.address.clone();
+    let pauser = Address::generate(&env);
+    let cap = LockTierCap {
+        max_share_bps: 4_000,
+        min_tvl: 1_000 * USDC,
+    };
+    let tier_cap = admin_op(
+        &env,
+        vault,
+        "set_tier_cap",
+        vec![
+            &env,
+            admin.into_val(&env),
+            LockPeriod::TwelveMonths.into_val(&env),
+            cap.into_val(&env),
+        ],
+    );
+    let role = admin_op(
+        &env,
+        vault,
+        "set_role",
+        vec![
+            &env,
+            admin.into_val(&env),
+            Role::Pauser.into_val(&env),
+            Some(pauser.clone()).into_val(&env),
+        ],
+    );
+
+    // An out-of-range depeg band fails the whole batch, including the calls before it
+    let bad_band = admin_op(
+        &env,
+        vault,
+        "set_depeg_band",
+        vec![&env, admin.into_val(&env), 10_001u32.into_val(&env)],
+    );
+    let failing = multisig.propose_batch(
+        &p.member(0),
+        &vec![&env, tier_cap.clone(), role.clone(), bad_band],
+    );
+    multisig.confirm_action(&p.member(1), &failing);
+    assert!(multisig.try_execute_action(&p.member(0), &failing).is_err());
+    assert_eq!(p.usdc_vault.get_tier_cap(&LockPeriod::TwelveMonths), None);
+    assert_eq!(p.usdc_vault.get_role(&Role::Pauser), None);
+    multisig.cancel_action(&p.member(0), &failing);
+
+    let band = admin_op(
+        &env,
+        vault,
+        "set_depeg_band",
+        vec![&env, admin.into_val(&env), 100u32.into_val(&env)],
+    );
+    let ops = vec![&env, tier_cap, role, band];
+    let batch = multisig.propose_batch(&p.member(0), &ops);
+    assert_eq!(multisig.get_batch(&batch), Some(ops));
+    multisig.confirm_action(&p.member(2), &batch);
+    multisig.execute_action(&p.member(2), &batch);
+
+    assert_eq!(
+        multisig.get_action(&batch).unwrap().status,
+        ProposalStatus::Executed
+    );
+    assert_eq!(
+        p.usdc_vault.get_tier_cap(&LockPeriod::TwelveMonths),
+        Some(cap)
+    );
+    assert_eq!(p.usdc_vault.get_role(&Role::Pauser), Some(pauser));
+    assert_eq!(p.usdc_vault.get_depeg_band(), Some(100));
+}
+
+#[test]
+fn signer_rotation_is_proposed_confirmed_and_applied_by_the_multisig() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let multisig = &p.multisig;
+    let newcomer = Address::generate(&env);
+    let rotated = vec![&env, p.member(0), p.member(1), newcomer.clone()];
+
+    // Invalid sets are refused at proposal time, and outsiders cannot propose
+    assert!(multisig
+        .try_propose_signer_update(&p.member(0), &rotated, &4)
+        .is_err());
+    assert!(multisig
+        .try_propose_signer_update(&newcomer, &rotated, &2)
+        .is_err());
+
+    let action_id = multisig.propose_signer_update(&p.member(0), &rotated, &2);
+    multisig.confirm_action(&p.member(1), &action_id);
+
+    // Only signers can revoke, and a revoked confirmation drops below the threshold
+    assert!(multisig
+        .try_revoke_confirmation(&newcomer, &action_id)
+        .is_err());
+    multisig.revoke_confirmation(&p.member(1), &action_id);
+    assert!(multisig
+        .try_execute_action(&p.member(0), &action_id)
+        .is_err());
+    multisig.confirm_action(&p.member(1), &action_id);
+    assert!(multisig
+        .try_execute_action(&p.member(0), &action_id)
+        .is_err());
+    multisig.confirm_action(&p.member(2), &action_id);
+    multisig.execute_action(&p.member(2), &action_id);
+
+    assert_eq!(multisig.get_signers(), rotated);
+    assert_eq!(multisig.get_threshold(), 2);
+    assert_eq!(
+        multisig.get_action(&action_id).unwrap().status,
+        ProposalStatus::Executed
+    );
+
+    // Rotated-out members lose their seat and the newcomer takes it
+    let next = multisig.propose_signer_update(&newcomer, &rotated, &3);
+    assert!(multisig.try_confirm_action(&p.member(2), &next).is_err());
+    multisig.confirm_action(&p.member(0), &next);
+    multisig.execute_action(&newcomer, &next);
+    assert_eq!(multisig.get_threshold(), 3);
+}
+
+#[test]
+fn stale_approvals_are_recounted_against_the_current_signer_set() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let multisig = &p.multisig;
+    let pauser = Address::generate(&env);
+
+    // Approved 3-of-5 with two of its confirmations from members about to leave
+    let stale = multisig.propose_role_update(&p.member(3), &Role::Pauser, &Some(pauser.clone()));
+    multisig.confirm_action(&p.member(4), &stale);
+    multisig.confirm_action(&p.member(0), &stale);
+    assert_eq!(
+        multisig.get_action(&stale).unwrap().status,
+        ProposalStatus::Approved
+    );
+
+    let remaining = vec![&env, p.member(0), p.member(1), p.member(2)];
+    let rotation = multisig.propose_signer_update(&p.member(0), &remaining, &2);
+    multisig.confirm_action(&p.member(1), &rotation);
+    multisig.confirm_action(&p.member(2), &rotation);
+    multisig.execute_action(&p.member(0), &rotation);
+
+    // Only member 0 still holds a seat, so the old approval no longer executes
+    assert!(multisig.try_execute_action(&p.member(0), &stale).is_err());
+    assert_eq!(multisig.get_role(&Role::Pauser), None);
+
+    multisig.confirm_action(&p.member(1), &stale);
+    multisig.execute_action(&p.member(1), &stale);
+    assert_eq!(multisig.get_role(&Role::Pauser), Some(pauser));
+    assert_eq!(multisig.get_action(&stale).unwrap().confirmations, 2);
+}
+
+#[test]
+fn multisig_and_timelock_grant_their_pauser_through_their_own_updates() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let paused = Err(Ok(Error::from_contract_error(ContractError::Paused as u32)));
+    let pauser = Address::generate(&env);
+
+    // With no admin to fall back on, nobody can pause until a pauser is granted
+    let multisig = &p.multisig;
+    assert!(multisig.try_pause(&p.member(0)).is_err());
+    let grant = multisig.propose_role_update(&p.member(0), &Role::Pauser, &Some(pauser.clone()));
+    multisig.confirm_action(&p.member(1), &grant);
+    multisig.confirm_action(&p.member(2), &grant);
+    multisig.execute_action(&p.member(0), &grant);
+    assert_eq!(multisig.get_role(&Role::Pauser), Some(pauser.clone()));
+
+    // A pause holds calls to other contracts, but the signers can still revoke the pauser
+    multisig.pause(&pauser);
+    let names = multisig.propose_action(
+        &p.member(0),
+        &p.registry.address,
+        &Symbol::new(&env, "get_names"),
+        &Vec::new(&env),
+    );
+    let revoke = multisig.propose_role_update(&p.member(0), &Role::Pauser, &None);
+    for action_id in [names, revoke] {
+        multisig.confirm_action(&p.member(1), &action_id);
+        multisig.confirm_action(&p.member(2), &action_id);
+    }
+    assert_eq!(multisig.try_execute_action(&p.member(0), &names), paused);
+    multisig.execute_action(&p.member(0), &revoke);
+    assert!(!multisig.is_paused());
+    multisig.execute_action(&p.member(0), &names);
+
+    // The timelock grants its pauser through a queued update
+    let governance = p.governance.address.clone();
+    let grant = p.timelock.schedule_role_update(
+        &governance,
+        &Role::Pauser,
+        &Some(pauser.clone()),
+        &TIMELOCK_MIN_DELAY,
+    );
+    p.advance_time(TIMELOCK_MIN_DELAY);
+    p.timelock.execute(&grant);
+    p.timelock.pause(&pauser);
+
+    let call = p.timelock.schedule(
+        &governance,
+        &p.registry.address,
+        &Symbol::new(&env, "get_names"),
+        &Vec::new(&env),
+        &TIMELOCK_MIN_DELAY,
+    );
+    p.advance_time(TIMELOCK_MIN_DELAY);
+    assert_eq!(p.timelock.try_execute(&call), paused);
+    p.timelock.unpause(&pauser);
+    p.timelock.execute(&call);
+}
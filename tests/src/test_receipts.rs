@@ -0,0 +1,184 @@
+use crate::setup::{Protocol, DAY, USDC};
+use shared::{hash_deposit_terms, ExitFeeSchedule, LockPeriod, VaultType};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, String};
+
+#[test]
+fn deposit_issues_receipt_closed_on_withdraw() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    let receipt = p.usdc_vault.get_active_receipt(&user).unwrap();
+    assert_eq!(receipt.id, 1);
+    assert_eq!(receipt.terms.depositor, user);
+    assert_eq!(receipt.terms.amount, amount);
+    assert_eq!(receipt.terms.yield_rate, 500);
+    assert_eq!(receipt.terms_hash, hash_deposit_terms(&env, &receipt.terms));
+    assert_eq!(receipt.nft_id, None);
+
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    p.usdc_vault.withdraw(&user);
+
+    // The record outlives the position for audit
+    assert!(p.usdc_vault.get_active_receipt(&user).is_none());
+    let closed = p.usdc_vault.get_receipt(&receipt.id).unwrap();
+    assert_eq!(closed.closed_at, Some(env.ledger().timestamp()));
+    assert_eq!(closed.terms_hash, receipt.terms_hash);
+}
+
+#[test]
+fn deposit_certificate_binds_terms_and_fee_policy() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 2_000 * USDC;
+    let user = p.funded_user(amount);
+    let other = Address::generate(&env);
+    p.usdc_vault.set_exit_fee_schedule(
+        &p.admin,
+        &Some(ExitFeeSchedule {
+            start_bps: 500,
+            end_bps: 100,
+        }),
+    );
+    assert_eq!(p.usdc_vault.get_fee_policy_version(), 1);
+
+    let hash = p.usdc_vault.deposit(&user, &amount, &LockPeriod::SixMonths);
+    let receipt = p.usdc_vault.get_active_receipt(&user).unwrap();
+    assert_eq!(receipt.terms_hash, hash);
+    assert_eq!(receipt.terms.fee_policy_version, 1);
+    assert!(p.usdc_vault.verify_certificate(&user, &receipt.id, &hash));
+
+    // Only the depositor's own position and its exact terms match
+    assert!(!p.usdc_vault.verify_certificate(&other, &receipt.id, &hash));
+    assert!(!p
+        .usdc_vault
+        .verify_certificate(&user, &(receipt.id + 1), &hash));
+    let mut altered = receipt.terms.clone();
+    altered.fee_policy_version = 2;
+    assert!(!p.usdc_vault.verify_certificate(
+        &user,
+        &receipt.id,
+        &hash_deposit_terms(&env, &altered)
+    ));
+
+    // A later fee change does not touch the recorded terms
+    p.usdc_vault.set_exit_fee_schedule(&p.admin, &None);
+    assert_eq!(p.usdc_vault.get_fee_policy_version(), 2);
+    assert!(p.usdc_vault.verify_certificate(&user, &receipt.id, &hash));
+}
+
+#[test]
+fn exported_receipt_is_transferable_and_settled_on_withdraw() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let receipts = &p.receipts;
+
+    let amount = 5_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault.deposit(&user, &amount, &LockPeriod::SixMonths);
+
+    let token_id = p.usdc_vault.export_receipt(&user);
+    assert_eq!(receipts.owner_of(&token_id), user);
+    assert!(receipts.verify_terms(&token_id));
+    assert_eq!(
+        p.usdc_vault.get_active_receipt(&user).unwrap().nft_id,
+        Some(token_id)
+    );
+    assert!(p.usdc_vault.try_export_receipt(&user).is_err());
+
+    let custodian = Address::generate(&env);
+    receipts.transfer(&user, &custodian, &token_id);
+    assert_eq!(receipts.owner_of(&token_id), custodian);
+    assert_eq!(receipts.balance(&user), 0);
+    assert_eq!(receipts.balance(&custodian), 1);
+    assert!(receipts.try_transfer(&user, &user, &token_id).is_err());
+
+    // Withdrawal stays with the depositor and settles the token
+    p.advance_time(180 * DAY);
+    p.fund_usdc_reserve(500 * USDC);
+    p.usdc_vault.withdraw(&user);
+    assert!(receipts.get_receipt(&token_id).closed_at.is_some());
+}
+
+#[test]
+fn only_registered_vaults_mint_receipt_tokens() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let receipts = &p.receipts;
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    let receipt = p.usdc_vault.get_active_receipt(&user).unwrap();
+
+    // A user cannot mint their own token, even from genuine terms
+    assert!(receipts.try_mint(&user, &user, &receipt).is_err());
+
+    // Tampered terms no longer match the hash
+    let mut tampered = receipt.clone();
+    tampered.terms.amount *= 10;
+    assert!(receipts
+        .try_mint(&p.usdc_vault.address, &user, &tampered)
+        .is_err());
+}
+
+#[test]
+fn token_uri_renders_the_certificate_from_stored_terms() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let receipts = &p.receipts;
+    receipts.set_asset_type(&p.admin, &p.usdc.address, &VaultType::USDC);
+    assert!(receipts
+        .try_set_asset_type(&Address::generate(&env), &p.usdc.address, &VaultType::PAXG)
+        .is_err());
+
+    let amount = 2_500 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::TwelveMonths);
+    let token_id = p.usdc_vault.export_receipt(&user);
+    let terms = receipts.get_receipt(&token_id).terms;
+
+    let uri = render(&receipts.token_uri(&token_id));
+    assert!(uri.starts_with("data:application/json;utf8,{\"name\":\"Term Deposit #1\""));
+    assert!(uri.contains("{\"trait_type\":\"Vault Type\",\"value\":\"USDC\"}"));
+    assert!(uri.contains("{\"trait_type\":\"Lock Period\",\"value\":\"12 months\"}"));
+    assert!(uri.contains(&format!(
+        "{{\"trait_type\":\"Amount\",\"value\":\"{}\"}}",
+        amount
+    )));
+    assert!(uri.contains(&format!(
+        "{{\"display_type\":\"date\",\"trait_type\":\"Unlock Time\",\"value\":{}}}",
+        terms.unlock_time
+    )));
+    assert!(uri.contains("{\"trait_type\":\"Status\",\"value\":\"Locked\"}]"));
+    let hash: std::string::String = receipts
+        .get_receipt(&token_id)
+        .terms_hash
+        .to_array()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert!(uri.ends_with(&format!("\"terms_hash\":\"{}\"}}", hash)));
+
+    // Status follows the position through maturity and withdrawal
+    p.advance_time(365 * DAY);
+    assert!(render(&receipts.token_uri(&token_id)).contains("\"value\":\"Matured\""));
+    p.fund_usdc_reserve(500 * USDC);
+    p.usdc_vault.withdraw(&user);
+    assert!(render(&receipts.token_uri(&token_id)).contains("\"value\":\"Withdrawn\""));
+}
+
+fn render(uri: &String) -> std::string::String {
+    let mut buffer = std::vec![0u8; uri.len() as usize];
+    uri.copy_into_slice(&mut buffer);
+    std::string::String::from_utf8(buffer).unwrap()
+}
@@ -0,0 +1,70 @@
+use crate::mocks::{ReentrantToken, ReentrantTokenClient};
+use crate::setup::{Protocol, DAY, USDC};
+use shared::{enter_guard, exit_guard, ContractError, LockPeriod};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env, Error};
+use usdc_vault::{USDCVault, USDCVaultClient};
+
+fn contract_error(error: ContractError) -> Error {
+    Error::from_contract_error(error as u32)
+}
+
+#[test]
+fn nested_withdraw_from_token_callback_reverts() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let token = ReentrantTokenClient::new(&env, &env.register_contract(None, ReentrantToken));
+    let vault = USDCVaultClient::new(&env, &env.register_contract(None, USDCVault));
+    vault.initialize(&p.admin, &token.address, &p.yield_token.address);
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+
+    let amount = 1_000 * USDC;
+    let user = Address::generate(&env);
+    vault.deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    token.arm(&vault.address);
+    p.advance_time(90 * DAY);
+
+    // The payout re-enters `withdraw`; the nested call fails and the whole withdrawal reverts
+    let result = vault.try_withdraw(&user);
+    assert_eq!(
+        result,
+        Err(Ok(contract_error(ContractError::TokenTransferFailed)))
+    );
+    assert!(vault.get_deposit_info(&user).is_some());
+    assert_eq!(vault.get_vault_balance(), amount);
+    assert_eq!(p.yield_token.balance(&user), amount as i128);
+}
+
+#[test]
+fn guarded_entrypoints_reject_calls_while_guard_is_held() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+
+    // Simulate a call already in progress inside the vault
+    env.as_contract(&p.usdc_vault.address, || enter_guard(&env));
+    assert_eq!(
+        p.usdc_vault.try_withdraw(&user),
+        Err(Ok(contract_error(ContractError::Reentrancy)))
+    );
+
+    let other = p.funded_user(amount);
+    assert_eq!(
+        p.usdc_vault
+            .try_deposit(&other, &amount, &LockPeriod::SixMonths),
+        Err(Ok(contract_error(ContractError::Reentrancy)))
+    );
+
+    env.as_contract(&p.usdc_vault.address, || exit_guard(&env));
+    assert!(p.usdc_vault.withdraw(&user) > amount);
+    assert!(p.usdc_vault.try_withdraw(&user).is_err());
+}
@@ -0,0 +1,2105 @@
+use crate::mocks::{
+    MockOracle, MockOracleClient, MockRouter, MockRouterClient, MockStrategy, MockStrategyClient,
+};
+use crate::setup::{Protocol, DAY, GOLD_PRICE, OUNCE, USDC};
+use gold_vault::{GoldVault, GoldVaultClient};
+use rewards::{Rewards, RewardsClient};
+use shared::{
+    compound_daily, ContractError, DepositTag, DormancyPolicy, ExitFeeSchedule, FeeRebateTier,
+    FeeSwitch, LiquidityFacility, LockPeriod, LockTierCap, PriceSnapshot, RevenueStream, Role,
+    VoucherStatus, WithdrawalCap, YieldFeeSchedule, BENEFICIARY_CHANGE_DELAY, DUST_PRECISION,
+    FACILITY_LIQUIDATION_BONUS_BPS, GOLD_PRICE_PRECISION, MAX_GOLD_HAIRCUT_BPS,
+    MAX_ORACLE_PRICE_AGE, MAX_STRATEGY_SHARE_BPS, REGISTRY_TRADE_ROUTER, SHARE_PRICE_PRECISION,
+};
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::{vec, Address, Bytes, BytesN, Env, Error, String, Symbol, TryFromVal};
+use usdc_vault::{USDCVault, USDCVaultClient};
+
+#[test]
+fn deposit_compound_withdraw() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 10_000 * USDC;
+    let user = p.funded_user(amount);
+    p.fund_usdc_reserve(500 * USDC);
+
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    assert_eq!(p.usdc.balance(&user), 0);
+    assert_eq!(p.yield_token.balance(&user), amount as i128);
+    assert_eq!(p.usdc_vault.get_vault_balance(), amount);
+    assert!(p.usdc_vault.health_check().healthy);
+
+    // Locked funds cannot leave before the unlock time
+    assert!(p.usdc_vault.try_withdraw(&user).is_err());
+
+    p.advance_time(30 * DAY);
+    let compounded = p.yield_token.compound_interest(&user);
+    assert!(compounded > amount);
+    assert_eq!(p.yield_token.balance(&user), compounded as i128);
+
+    p.advance_time(61 * DAY);
+    let withdrawn = p.usdc_vault.withdraw(&user);
+
+    assert!(withdrawn > compounded);
+    assert_eq!(p.usdc.balance(&user), withdrawn as i128);
+    assert_eq!(p.yield_token.balance(&user), 0);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 0);
+    assert!(p.usdc_vault.get_deposit_info(&user).is_none());
+    assert!(p.usdc_vault.health_check().healthy);
+}
+
+#[test]
+fn usdc_withdrawals_release_only_the_principal_they_recorded() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(500 * USDC);
+
+    let early = p.funded_user(1_000 * USDC);
+    let late = p.funded_user(3_000 * USDC);
+    p.usdc_vault
+        .deposit(&early, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&late, &(3_000 * USDC), &LockPeriod::TwelveMonths);
+
+    // The payout includes yield, but recorded liquidity only drops by the principal
+    p.advance_time(91 * DAY);
+    assert!(p.usdc_vault.withdraw(&early) > 1_000 * USDC);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 3_000 * USDC);
+}
+
+#[test]
+fn gold_withdrawals_release_only_the_deposit_value_they_recorded() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let early = p.gold_holder(OUNCE);
+    let late = p.gold_holder(2 * OUNCE);
+    p.gold_vault
+        .deposit(&early, &p.gold.address, &OUNCE, &LockPeriod::ThreeMonths);
+    p.gold_vault.deposit(
+        &late,
+        &p.gold.address,
+        &(2 * OUNCE),
+        &LockPeriod::TwelveMonths,
+    );
+    let late_value = p.gold_vault.get_deposit_info(&late).unwrap().amount;
+    p.gold_admin.mint(&p.gold_vault.address, &(OUNCE as i128));
+
+    // Gold paid for yield leaves the vault, but recorded liquidity only drops by the deposit value
+    p.advance_time(91 * DAY);
+    assert!(p.gold_vault.withdraw(&early) > OUNCE);
+    assert_eq!(p.gold_vault.get_vault_balance(), late_value);
+}
+
+#[test]
+fn one_active_deposit_per_user() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let user = p.funded_user(2_000 * USDC);
+    p.usdc_vault
+        .deposit(&user, &(1_000 * USDC), &LockPeriod::SixMonths);
+
+    let second = p
+        .usdc_vault
+        .try_deposit(&user, &(1_000 * USDC), &LockPeriod::SixMonths);
+    assert!(second.is_err());
+    assert_eq!(p.usdc.balance(&user), (1_000 * USDC) as i128);
+}
+
+#[test]
+fn failed_yield_mint_reverts_deposit_with_typed_error() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Point a vault at a contract that has no `mint_for_deposit`
+    let vault = USDCVaultClient::new(&env, &env.register_contract(None, USDCVault));
+    vault.initialize(&p.admin, &p.usdc.address, &p.governance.address);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    let result = vault.try_deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    assert_eq!(
+        result,
+        Err(Ok(Error::from_contract_error(
+            ContractError::YieldTokenCallFailed as u32
+        )))
+    );
+    assert_eq!(p.usdc.balance(&user), amount as i128);
+    assert_eq!(vault.get_vault_balance(), 0);
+}
+
+#[test]
+fn unmigrated_deposits_block_new_positions_and_are_never_overwritten() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Put a live position back under its schema v1 key, as an upgrade leaves it
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(2 * amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    let position = p.usdc_vault.get_deposit_info(&user).unwrap();
+    let v1_key = (Symbol::new(&env, "DEPOSIT"), user.clone());
+    env.as_contract(&p.usdc_vault.address, || {
+        env.storage().persistent().set(&v1_key, &position);
+        env.storage()
+            .persistent()
+            .remove(&usdc_vault::DataKey::Deposit(user.clone()));
+    });
+
+    // A new deposit cannot shadow the unmigrated one
+    assert!(p
+        .usdc_vault
+        .try_deposit(&user, &amount, &LockPeriod::ThreeMonths)
+        .is_err());
+    assert_eq!(
+        p.usdc_vault
+            .migrate_deposits(&p.admin, &vec![&env, user.clone()]),
+        1
+    );
+    assert_eq!(p.usdc_vault.get_deposit_info(&user), Some(position.clone()));
+
+    // Migration refuses to replace a record already under the new key
+    env.as_contract(&p.usdc_vault.address, || {
+        env.storage().persistent().set(&v1_key, &position);
+    });
+    assert_eq!(
+        p.usdc_vault
+            .try_migrate_deposits(&p.admin, &vec![&env, user.clone()]),
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidMigration as u32
+        )))
+    );
+}
+
+#[test]
+fn rewards_outage_does_not_block_deposits() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // A rewards address that rejects `register_stake` and `remove_stake`
+    p.usdc_vault.set_rewards(&p.admin, &p.governance.address);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    assert_eq!(p.usdc_vault.get_vault_balance(), amount);
+
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    assert!(p.usdc_vault.withdraw(&user) >= amount);
+}
+
+#[test]
+fn reward_epochs_are_read_from_emission_spans() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Epochs shorter than a day are refused
+    let short = RewardsClient::new(&env, &env.register_contract(None, Rewards));
+    assert!(short
+        .try_initialize(&p.admin, &p.usdc.address, &(DAY - 1), &0)
+        .is_err());
+
+    // 700 USDC per week-long epoch, to a single three-month stake
+    p.rewards.set_emission(&p.admin, &(700 * USDC));
+    p.usdc_admin
+        .mint(&p.rewards.address, &((10_000 * USDC) as i128));
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    // One claim accrues three and a half idle epochs in a single step
+    p.advance_time(24 * DAY + DAY / 2);
+    assert_eq!(p.rewards.claim_rewards(&user), 2_450 * USDC);
+    for epoch in 0..3 {
+        assert_eq!(p.rewards.get_epoch(&epoch).emitted, 700 * USDC);
+    }
+    let current = p.rewards.get_epoch(&3);
+    assert_eq!(
+        (current.emitted, current.claimed),
+        (350 * USDC, 2_450 * USDC)
+    );
+
+    // A rate change applies from the moment it is set
+    p.rewards.set_emission(&p.admin, &(1_400 * USDC));
+    p.advance_time(3 * DAY + DAY / 2);
+    assert_eq!(p.rewards.claim_rewards(&user), 700 * USDC);
+    assert_eq!(p.rewards.get_epoch(&3).emitted, 1_050 * USDC);
+    assert_eq!(p.rewards.get_epoch(&4).emitted, 0);
+}
+
+#[test]
+fn lock_tier_cap_limits_share_of_tvl() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // Twelve-month locks may hold at most 40% of the vault
+    let cap = LockTierCap {
+        max_share_bps: 4_000,
+        min_tvl: 0,
+    };
+    p.usdc_vault
+        .set_tier_cap(&p.admin, &LockPeriod::TwelveMonths, &cap);
+    assert_eq!(
+        p.usdc_vault.get_tier_cap(&LockPeriod::TwelveMonths),
+        Some(cap)
+    );
+
+    let early = p.funded_user(1_000 * USDC);
+    assert_eq!(
+        p.usdc_vault
+            .try_deposit(&early, &(1_000 * USDC), &LockPeriod::TwelveMonths),
+        Err(Ok(Error::from_contract_error(
+            ContractError::TierCapExceeded as u32
+        )))
+    );
+
+    let short = p.funded_user(6_000 * USDC);
+    p.usdc_vault
+        .deposit(&short, &(6_000 * USDC), &LockPeriod::ThreeMonths);
+
+    let long = p.funded_user(4_000 * USDC);
+    p.usdc_vault
+        .deposit(&long, &(4_000 * USDC), &LockPeriod::TwelveMonths);
+    assert_eq!(
+        p.usdc_vault.get_tier_balance(&LockPeriod::TwelveMonths),
+        4_000 * USDC
+    );
+
+    // 40% exactly is allowed, anything more is not
+    let late = p.funded_user(USDC);
+    assert!(p
+        .usdc_vault
+        .try_deposit(&late, &USDC, &LockPeriod::TwelveMonths)
+        .is_err());
+    p.usdc_vault.deposit(&late, &USDC, &LockPeriod::SixMonths);
+
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    p.usdc_vault.withdraw(&short);
+    assert_eq!(p.usdc_vault.get_tier_balance(&LockPeriod::ThreeMonths), 0);
+}
+
+#[test]
+fn tier_caps_are_validated_and_restricted() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let too_high = LockTierCap {
+        max_share_bps: 10_001,
+        min_tvl: 0,
+    };
+    assert_eq!(
+        p.usdc_vault
+            .try_set_tier_cap(&p.admin, &LockPeriod::SixMonths, &too_high),
+        Err(Ok(Error::from_contract_error(
+            ContractError::InvalidTierCap as u32
+        )))
+    );
+
+    let cap = LockTierCap {
+        max_share_bps: 5_000,
+        min_tvl: 1_000_000 * USDC,
+    };
+    let outsider = Address::generate(&env);
+    assert!(p
+        .usdc_vault
+        .try_set_tier_cap(&outsider, &LockPeriod::SixMonths, &cap)
+        .is_err());
+
+    // The configured governance contract may manage caps alongside the admin
+    p.usdc_vault.set_governance(&p.admin, &p.governance.address);
+    p.usdc_vault
+        .set_tier_cap(&p.governance.address, &LockPeriod::SixMonths, &cap);
+
+    // Below the activation TVL the share cap does not apply
+    let user = p.funded_user(10_000 * USDC);
+    p.usdc_vault
+        .deposit(&user, &(10_000 * USDC), &LockPeriod::SixMonths);
+}
+
+#[test]
+fn share_price_tracks_vault_coverage() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    // An empty vault prices shares at par
+    assert_eq!(p.usdc_vault.get_share_price(), SHARE_PRICE_PRECISION);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    assert_eq!(p.usdc_vault.get_share_price(), SHARE_PRICE_PRECISION);
+
+    // Accrual is previewed without writing to the holder's balance
+    p.advance_time(30 * DAY);
+    let accrued = p.yield_token.accrued_balance(&user);
+    assert!(accrued > amount);
+    assert_eq!(p.yield_token.balance(&user), amount as i128);
+
+    // Losing a quarter of the backing USDC drops the price to 0.75
+    p.usdc.burn(&p.usdc_vault.address, &((amount / 4) as i128));
+    assert_eq!(
+        p.usdc_vault.get_share_price(),
+        SHARE_PRICE_PRECISION * 3 / 4
+    );
+}
+
+#[test]
+fn usdc_depeg_pauses_deposits_and_flags_withdrawals() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let oracle = MockOracleClient::new(&env, &env.register_contract(None, MockOracle));
+    p.use_own_price_feeds();
+    p.usdc_vault.set_oracle(&p.admin, &oracle.address);
+
+    // No band configured: the peg is not checked
+    assert!(p.usdc_vault.is_usdc_pegged());
+    assert!(p
+        .usdc_vault
+        .try_set_depeg_band(&Address::generate(&env), &100)
+        .is_err());
+    assert!(p.usdc_vault.try_set_depeg_band(&p.admin, &10_001).is_err());
+    p.usdc_vault.set_depeg_band(&p.admin, &100);
+
+    // A band without a price fails closed
+    assert!(!p.usdc_vault.is_usdc_pegged());
+    oracle.set_price(&995_000);
+    assert_eq!(p.usdc_vault.get_usdc_price(), Some(995_000));
+    assert!(p.usdc_vault.is_usdc_pegged());
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    oracle.set_price(&980_000);
+    assert!(!p.usdc_vault.is_usdc_pegged());
+    let late = p.funded_user(amount);
+    let result = p
+        .usdc_vault
+        .try_deposit(&late, &amount, &LockPeriod::ThreeMonths);
+    assert_eq!(
+        result,
+        Err(Ok(Error::from_contract_error(
+            ContractError::UsdcDepegged as u32
+        )))
+    );
+
+    // Withdrawals still go through and are tallied
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    assert!(p.usdc_vault.withdraw(&user) >= amount);
+    assert_eq!(p.usdc_vault.get_depeg_withdrawn(), amount);
+}
+
+#[test]
+fn emergency_penalty_is_routed_to_treasury() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let penalty = amount / 10;
+    let first = p.funded_user(amount);
+    let second = p.funded_user(amount);
+    let remaining = p.funded_user(amount);
+    for user in [&first, &second, &remaining] {
+        p.usdc_vault
+            .deposit(user, &amount, &LockPeriod::ThreeMonths);
+    }
+
+    // Without a treasury the penalty is held but not counted as depositor liquidity
+    p.usdc_vault.emergency_withdraw(&p.admin, &first);
+    assert_eq!(p.usdc_vault.get_unrouted_fees(), penalty);
+    assert_eq!(p.usdc_vault.health_check().held_value, 2 * amount);
+    assert_eq!(p.usdc_vault.get_share_price(), SHARE_PRICE_PRECISION);
+    assert!(p.usdc_vault.try_sweep_fees().is_err());
+
+    let treasury = Address::generate(&env);
+    assert!(p
+        .usdc_vault
+        .try_set_treasury(&Address::generate(&env), &treasury)
+        .is_err());
+    p.usdc_vault.set_treasury(&p.admin, &treasury);
+    assert_eq!(p.usdc_vault.sweep_fees(), penalty);
+    assert_eq!(p.usdc.balance(&treasury), penalty as i128);
+    assert_eq!(p.usdc_vault.get_unrouted_fees(), 0);
+
+    // With a treasury configured the penalty leaves the vault immediately
+    p.usdc_vault.emergency_withdraw(&p.admin, &second);
+    assert_eq!(p.usdc.balance(&treasury), (2 * penalty) as i128);
+    assert_eq!(p.usdc_vault.get_fees_routed(), 2 * penalty);
+    assert_eq!(p.usdc.balance(&p.usdc_vault.address), amount as i128);
+    assert!(p.usdc_vault.health_check().liquidity_covered);
+}
+
+#[test]
+fn deposit_from_pulls_pre_approved_usdc() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    let operator = Address::generate(&env);
+
+    // The operator needs the user's approval before it can deposit for them
+    assert!(p
+        .usdc_vault
+        .try_deposit_from(&operator, &user, &amount, &LockPeriod::SixMonths)
+        .is_err());
+    p.usdc_vault.set_deposit_operator(&user, &operator, &true);
+    assert!(p.usdc_vault.is_deposit_operator(&user, &operator));
+
+    // Without an allowance the pull fails with a typed error
+    assert_eq!(
+        p.usdc_vault
+            .try_deposit_from(&operator, &user, &amount, &LockPeriod::SixMonths),
+        Err(Ok(Error::from_contract_error(
+            ContractError::TokenTransferFailed as u32
+        )))
+    );
+
+    let expiration = env.ledger().sequence() + 1_000;
+    p.usdc
+        .approve(&user, &p.usdc_vault.address, &(amount as i128), &expiration);
+    p.usdc_vault
+        .deposit_from(&operator, &user, &amount, &LockPeriod::SixMonths);
+
+    assert_eq!(p.usdc.balance(&user), 0);
+    assert_eq!(p.usdc.allowance(&user, &p.usdc_vault.address), 0);
+    assert_eq!(p.usdc_vault.get_deposit_info(&user).unwrap().amount, amount);
+    assert_eq!(p.yield_token.balance(&user), amount as i128);
+
+    p.usdc_vault.set_deposit_operator(&user, &operator, &false);
+    assert!(!p.usdc_vault.is_deposit_operator(&user, &operator));
+}
+
+#[test]
+fn standing_order_deposits_each_period() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(1_000 * USDC);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(2 * amount);
+    let keeper = Address::generate(&env);
+    let expiration = env.ledger().sequence() + 100_000;
+    p.usdc.approve(
+        &user,
+        &p.usdc_vault.address,
+        &((2 * amount) as i128),
+        &expiration,
+    );
+
+    assert!(p
+        .usdc_vault
+        .try_create_standing_order(&user, &amount, &3_600, &LockPeriod::ThreeMonths)
+        .is_err());
+    p.usdc_vault
+        .create_standing_order(&user, &amount, &(30 * DAY), &LockPeriod::ThreeMonths);
+
+    // The first period is due immediately and pulls from the allowance
+    assert!(p.usdc_vault.execute_standing_order(&keeper, &user));
+    assert_eq!(p.usdc_vault.get_deposit_info(&user).unwrap().amount, amount);
+    assert!(p
+        .usdc_vault
+        .try_execute_standing_order(&keeper, &user)
+        .is_err());
+
+    // Periods that find the position still open are skipped
+    p.advance_time(30 * DAY);
+    assert!(!p.usdc_vault.execute_standing_order(&keeper, &user));
+
+    p.advance_time(61 * DAY);
+    p.usdc_vault.withdraw(&user);
+    assert!(p.usdc_vault.execute_standing_order(&keeper, &user));
+
+    let order = p.usdc_vault.get_standing_order(&user).unwrap();
+    assert_eq!(order.executions, 2);
+    assert_eq!(order.skipped, 1);
+    assert_eq!(order.next_execution, env.ledger().timestamp() + 30 * DAY);
+
+    // The user can push the next period back or stop the order entirely
+    p.usdc_vault.skip_standing_order(&user);
+    assert_eq!(
+        p.usdc_vault
+            .get_standing_order(&user)
+            .unwrap()
+            .next_execution,
+        env.ledger().timestamp() + 60 * DAY
+    );
+    p.usdc_vault.cancel_standing_order(&user);
+    assert_eq!(p.usdc_vault.get_standing_order(&user), None);
+    assert!(p
+        .usdc_vault
+        .try_execute_standing_order(&keeper, &user)
+        .is_err());
+}
+
+#[test]
+fn exit_fee_decays_over_the_lock() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let treasury = Address::generate(&env);
+    p.usdc_vault.set_treasury(&p.admin, &treasury);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    // The flat 10% applies until governance sets a schedule
+    assert_eq!(p.usdc_vault.get_exit_fee(&user), amount / 10);
+    let rising = ExitFeeSchedule {
+        start_bps: 100,
+        end_bps: 500,
+    };
+    assert!(p
+        .usdc_vault
+        .try_set_exit_fee_schedule(&p.admin, &Some(rising))
+        .is_err());
+    let schedule = ExitFeeSchedule {
+        start_bps: 500,
+        end_bps: 100,
+    };
+    assert!(p
+        .usdc_vault
+        .try_set_exit_fee_schedule(&Address::generate(&env), &Some(schedule.clone()))
+        .is_err());
+    p.usdc_vault
+        .set_exit_fee_schedule(&p.admin, &Some(schedule));
+    assert_eq!(p.usdc_vault.get_exit_fee(&user), amount * 5 / 100);
+
+    // Halfway through the lock the fee is halfway down, and it lands in the treasury
+    p.advance_time(45 * DAY);
+    assert_eq!(p.usdc_vault.get_exit_fee(&user), amount * 3 / 100);
+    assert_eq!(
+        p.usdc_vault.emergency_withdraw(&p.admin, &user),
+        amount * 97 / 100
+    );
+    assert_eq!(p.usdc.balance(&treasury), (amount * 3 / 100) as i128);
+
+    p.usdc_vault.set_exit_fee_schedule(&p.admin, &None);
+    assert_eq!(p.usdc_vault.get_exit_fee_schedule(), None);
+}
+
+#[test]
+fn gold_haircut_buffer_is_released_at_withdrawal() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let gold_admin = StellarAssetClient::new(&env, &gold_sac.address());
+    let vault = GoldVaultClient::new(&env, &env.register_contract(None, GoldVault));
+    vault.initialize(
+        &p.admin,
+        &p.yield_token.address,
+        &p.oracle.address,
+        &vec![&env, gold.address.clone()],
+    );
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+
+    assert!(vault
+        .try_set_haircut(&p.admin, &(MAX_GOLD_HAIRCUT_BPS + 1))
+        .is_err());
+    vault.set_haircut(&p.admin, &200);
+
+    // One ounce mints on 98% of its value; the other 2% of the gold is buffered
+    let ounce = 10_000_000u128;
+    let full_value = vault.get_usd_value(&gold.address, &ounce);
+    let user = Address::generate(&env);
+    gold_admin.mint(&user, &(ounce as i128));
+    vault.deposit(&user, &gold.address, &ounce, &LockPeriod::ThreeMonths);
+
+    let minted = full_value * 98 / 100;
+    assert_eq!(vault.get_deposit_info(&user).unwrap().amount, minted);
+    assert_eq!(vault.get_vault_balance(), minted);
+    assert_eq!(p.yield_token.balance(&user), minted as i128);
+    let buffer = ounce * 2 / 100;
+    assert_eq!(vault.get_haircut_buffer(&user), buffer);
+
+    // At a steady price the whole buffer comes back on top of principal and yield
+    gold_admin.mint(&vault.address, &(ounce as i128));
+    p.advance_time(90 * DAY);
+    let accrued = p.yield_token.accrued_balance(&user);
+    let returned = vault.withdraw(&user);
+    assert_eq!(returned, ounce * accrued / full_value + buffer);
+    assert_eq!(gold.balance(&user), returned as i128);
+    assert_eq!(vault.get_haircut_buffer(&user), 0);
+
+    // The fraction of a unit rounded off the payout is booked as dust
+    let dust = ounce * accrued % full_value * DUST_PRECISION / full_value;
+    assert_eq!(vault.get_rounding_dust(&gold.address), dust);
+}
+
+#[test]
+fn withdrawals_beyond_the_epoch_cap_are_queued() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(500 * USDC);
+
+    let alice = p.funded_user(1_000 * USDC);
+    let bob = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&alice, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&bob, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    p.advance_time(91 * DAY);
+
+    assert_eq!(p.usdc_vault.get_withdrawal_capacity(), None);
+    assert!(p
+        .usdc_vault
+        .try_set_withdrawal_cap(&alice, &Some(WithdrawalCap::Absolute(USDC)))
+        .is_err());
+    assert!(p
+        .usdc_vault
+        .try_set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::TvlShare(0)))
+        .is_err());
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::TvlShare(2_500)));
+    assert_eq!(p.usdc_vault.get_withdrawal_capacity(), Some(500 * USDC));
+
+    // Only this epoch's capacity is paid out; the rest waits in the queue
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(600 * USDC)));
+    assert_eq!(p.usdc_vault.withdraw(&alice), 600 * USDC);
+    let alice_queued = p.usdc_vault.get_queued_withdrawal(&alice);
+    assert!(alice_queued > 400 * USDC);
+    assert_eq!(p.usdc_vault.withdraw(&bob), 0);
+    let bob_queued = p.usdc_vault.get_queued_withdrawal(&bob);
+    assert_eq!(p.usdc_vault.get_queued_total(), alice_queued + bob_queued);
+    assert_eq!(p.usdc_vault.get_withdrawal_capacity(), Some(0));
+    assert!(p.usdc_vault.try_claim_queued_withdrawal(&alice).is_err());
+
+    // Each epoch releases more of the queue
+    p.advance_time(DAY);
+    assert_eq!(p.usdc_vault.claim_queued_withdrawal(&alice), alice_queued);
+    let bob_paid = p.usdc_vault.claim_queued_withdrawal(&bob);
+    assert_eq!(bob_paid, 600 * USDC - alice_queued);
+    assert_eq!(
+        p.usdc_vault.get_queued_withdrawal(&bob),
+        bob_queued - bob_paid
+    );
+
+    p.usdc_vault.set_withdrawal_cap(&p.admin, &None);
+    assert_eq!(
+        p.usdc_vault.claim_queued_withdrawal(&bob),
+        bob_queued - bob_paid
+    );
+    assert_eq!(p.usdc_vault.get_queued_total(), 0);
+    assert_eq!(p.usdc.balance(&alice) as u128, 600 * USDC + alice_queued);
+    assert_eq!(p.usdc.balance(&bob) as u128, bob_queued);
+}
+
+#[test]
+fn queued_withdrawals_are_paid_in_order_before_new_ones() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(500 * USDC);
+
+    let alice = p.funded_user(1_000 * USDC);
+    let bob = p.funded_user(1_000 * USDC);
+    let carol = p.funded_user(1_000 * USDC);
+    for user in [&alice, &bob, &carol] {
+        p.usdc_vault
+            .deposit(user, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    }
+    p.advance_time(91 * DAY);
+
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(800 * USDC)));
+    assert_eq!(p.usdc_vault.withdraw(&alice), 800 * USDC);
+
+    // Changing the cap mid-epoch keeps what was already withdrawn
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(1_000 * USDC)));
+    assert_eq!(p.usdc_vault.get_withdrawal_capacity(), Some(200 * USDC));
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(500 * USDC)));
+    assert_eq!(p.usdc_vault.get_withdrawal_capacity(), Some(0));
+    p.usdc_vault
+        .set_withdrawal_cap(&p.admin, &Some(WithdrawalCap::Absolute(1_000 * USDC)));
+
+    // The remaining capacity goes to the queue first, so a new withdrawal gets nothing
+    let alice_queued = p.usdc_vault.get_queued_withdrawal(&alice);
+    assert!(alice_queued > 200 * USDC);
+    assert_eq!(p.usdc_vault.withdraw(&bob), 0);
+    let bob_queued = p.usdc_vault.get_queued_withdrawal(&bob);
+    assert_eq!(p.usdc_vault.get_queue_head().unwrap().user, alice);
+
+    // A later claimant pays the claims ahead of theirs before being paid
+    p.advance_time(DAY);
+    assert_eq!(p.usdc_vault.withdraw(&carol), 0);
+    assert_eq!(
+        p.usdc_vault.claim_queued_withdrawal(&bob),
+        1_000 * USDC - alice_queued
+    );
+    assert_eq!(p.usdc_vault.get_queued_withdrawal(&alice), 0);
+    assert_eq!(p.usdc.balance(&alice) as u128, 800 * USDC + alice_queued);
+    assert_eq!(p.usdc_vault.get_queue_head().unwrap().user, bob);
+
+    // Anyone can move the queue along in later epochs
+    p.advance_time(DAY);
+    let carol_queued = p.usdc_vault.get_queued_withdrawal(&carol);
+    let remaining = bob_queued - (1_000 * USDC - alice_queued);
+    assert_eq!(p.usdc_vault.process_withdrawal_queue(), 1_000 * USDC);
+    assert_eq!(p.usdc_vault.get_queued_withdrawal(&bob), 0);
+    assert_eq!(
+        p.usdc_vault.get_queued_withdrawal(&carol),
+        carol_queued - (1_000 * USDC - remaining)
+    );
+}
+
+#[test]
+fn simulated_deposits_match_the_position_opened() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 10_000 * USDC;
+    let preview = p
+        .usdc_vault
+        .simulate_deposit(&amount, &LockPeriod::SixMonths);
+    assert_eq!(preview.yield_rate, 750);
+    assert_eq!(preview.tokens_minted, amount);
+    assert_eq!(preview.early_exit_fee, amount / 10);
+    assert!(preview.projected_yield > amount * 750 / 10_000 / 2);
+    assert!(p
+        .usdc_vault
+        .try_simulate_deposit(&0, &LockPeriod::SixMonths)
+        .is_err());
+
+    // The preview describes the position a deposit opens in the same ledger
+    let user = p.funded_user(amount);
+    p.usdc_vault.deposit(&user, &amount, &LockPeriod::SixMonths);
+    assert_eq!(p.yield_token.balance(&user), preview.tokens_minted as i128);
+    assert_eq!(p.usdc_vault.get_lock_expiry(&user), preview.unlock_time);
+    assert_eq!(p.usdc_vault.get_exit_fee(&user), preview.early_exit_fee);
+
+    p.advance_time(180 * DAY);
+    assert_eq!(
+        p.yield_token.accrued_balance(&user),
+        amount + preview.projected_yield
+    );
+}
+
+#[test]
+fn tagged_deposits_carry_the_integrator_customer_reference() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(1_000 * USDC);
+
+    let amount = 5_000 * USDC;
+    let custodian = p.funded_user(amount);
+    let exchange = Address::generate(&env);
+    p.usdc_vault.deposit_tagged(
+        &custodian,
+        &amount,
+        &LockPeriod::ThreeMonths,
+        &exchange,
+        &42,
+    );
+
+    let tag = DepositTag {
+        integrator: exchange,
+        customer_id: 42,
+    };
+    assert_eq!(p.usdc_vault.get_deposit_tag(&custodian), Some(tag.clone()));
+
+    // The deposit event carries the tag for off-chain reconciliation
+    let (contract, _, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, p.usdc_vault.address);
+    let (deposited, lock_period, unlock_time, event_tag) =
+        <(u128, LockPeriod, u64, Option<DepositTag>)>::try_from_val(&env, &data).unwrap();
+    assert_eq!(deposited, amount);
+    assert_eq!(lock_period, LockPeriod::ThreeMonths);
+    assert_eq!(unlock_time, p.usdc_vault.get_lock_expiry(&custodian));
+    assert_eq!(event_tag, Some(tag));
+
+    // The tag goes with the position
+    p.advance_time(90 * DAY);
+    p.usdc_vault.withdraw(&custodian);
+    assert_eq!(p.usdc_vault.get_deposit_tag(&custodian), None);
+}
+
+#[test]
+fn gold_withdrawals_honour_the_minimum_gold_out() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let gold_admin = StellarAssetClient::new(&env, &gold_sac.address());
+    let vault = GoldVaultClient::new(&env, &env.register_contract(None, GoldVault));
+    vault.initialize(
+        &p.admin,
+        &p.yield_token.address,
+        &p.oracle.address,
+        &vec![&env, gold.address.clone()],
+    );
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+
+    let ounce = 10_000_000u128;
+    let full_value = vault.get_usd_value(&gold.address, &ounce);
+    let user = Address::generate(&env);
+    gold_admin.mint(&user, &(ounce as i128));
+    vault.deposit(&user, &gold.address, &ounce, &LockPeriod::ThreeMonths);
+    gold_admin.mint(&vault.address, &(ounce as i128));
+    p.advance_time(90 * DAY);
+
+    // Asking for more than the position is worth reverts and leaves it open
+    let owed = ounce * p.yield_token.accrued_balance(&user) / full_value;
+    assert_eq!(
+        vault.try_withdraw_with_min(&user, &(owed + 1)),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+    assert!(vault.get_deposit_info(&user).is_some());
+
+    assert_eq!(vault.withdraw_with_min(&user, &owed), owed);
+    assert_eq!(gold.balance(&user), owed as i128);
+}
+
+#[test]
+fn gold_positions_keep_the_price_they_were_deposited_at() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let gold_admin = StellarAssetClient::new(&env, &gold_sac.address());
+    let vault = GoldVaultClient::new(&env, &env.register_contract(None, GoldVault));
+    vault.initialize(
+        &p.admin,
+        &p.yield_token.address,
+        &p.oracle.address,
+        &vec![&env, gold.address.clone()],
+    );
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+
+    let ounce = 10_000_000u128;
+    let user = Address::generate(&env);
+    assert_eq!(vault.get_deposit_price(&user), None);
+    gold_admin.mint(&user, &(ounce as i128));
+    vault.deposit(&user, &gold.address, &ounce, &LockPeriod::ThreeMonths);
+
+    // The snapshot names the feed and the ledger the valuation was read in
+    let price = vault.get_deposit_price(&user).unwrap();
+    assert_eq!(
+        price,
+        PriceSnapshot {
+            feed: String::from_str(&env, "PAXG/USD"),
+            price: 2_000_000_000,
+            ledger: env.ledger().sequence(),
+            observed_at: env.ledger().timestamp(),
+        }
+    );
+    assert_eq!(
+        vault.get_deposit_info(&user).unwrap().amount,
+        vault.get_usd_value(&gold.address, &ounce)
+    );
+
+    // Closing the position clears it
+    gold_admin.mint(&vault.address, &(ounce as i128));
+    p.advance_time(90 * DAY);
+    vault.withdraw(&user);
+    assert_eq!(vault.get_deposit_price(&user), None);
+}
+
+#[test]
+fn gold_is_valued_at_the_live_oracle_price_and_never_a_stale_one() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let user = p.gold_holder(OUNCE);
+    p.gold_vault
+        .deposit(&user, &p.gold.address, &OUNCE, &LockPeriod::ThreeMonths);
+    assert_eq!(
+        p.gold_vault.get_deposit_price(&user).unwrap().price,
+        GOLD_PRICE
+    );
+    assert_eq!(p.gold_vault.get_position_value(&user), 2_000 * USDC);
+
+    // Valuations follow the feed
+    p.oracle.set_price(&(GOLD_PRICE * 11 / 10));
+    assert_eq!(p.gold_vault.get_position_value(&user), 2_200 * USDC);
+
+    // A feed not updated within the last hour is refused, for deposits and withdrawals alike
+    let stale = Err(Ok(Error::from_contract_error(
+        ContractError::StalePrice as u32,
+    )));
+    p.oracle
+        .set_updated_at(&(env.ledger().timestamp() - MAX_ORACLE_PRICE_AGE - 1));
+    assert_eq!(
+        p.gold_vault.try_get_usd_value(&p.gold.address, &OUNCE),
+        stale
+    );
+    let other = p.gold_holder(OUNCE);
+    assert_eq!(
+        p.gold_vault
+            .try_deposit(&other, &p.gold.address, &OUNCE, &LockPeriod::ThreeMonths),
+        stale
+    );
+    p.advance_time(90 * DAY);
+    assert_eq!(p.gold_vault.try_withdraw(&user), stale);
+
+    // So is a zero quote
+    p.oracle.set_updated_at(&env.ledger().timestamp());
+    p.oracle.set_price(&0);
+    assert_eq!(
+        p.gold_vault.try_get_usd_value(&p.gold.address, &OUNCE),
+        stale
+    );
+
+    p.oracle.set_price(&GOLD_PRICE);
+    p.gold_admin.mint(&p.gold_vault.address, &(OUNCE as i128));
+    p.gold_vault.withdraw(&user);
+    assert!(p.gold.balance(&user) > OUNCE as i128);
+}
+
+#[test]
+fn gold_strategy_allocations_are_capped_and_recalled_for_withdrawals() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_sac = env.register_stellar_asset_contract_v2(p.admin.clone());
+    let gold = TokenClient::new(&env, &gold_sac.address());
+    let gold_admin = StellarAssetClient::new(&env, &gold_sac.address());
+    let vault = GoldVaultClient::new(&env, &env.register_contract(None, GoldVault));
+    vault.initialize(
+        &p.admin,
+        &p.yield_token.address,
+        &p.oracle.address,
+        &vec![&env, gold.address.clone()],
+    );
+    p.yield_token
+        .set_authorized_vault(&p.admin, &vault.address, &true);
+    let strategy = MockStrategyClient::new(&env, &env.register_contract(None, MockStrategy));
+
+    let ounce = 10_000_000u128;
+    let user = Address::generate(&env);
+    gold_admin.mint(&user, &(ounce as i128));
+    vault.deposit(&user, &gold.address, &ounce, &LockPeriod::ThreeMonths);
+    gold_admin.mint(&vault.address, &(ounce as i128 / 10));
+    let held_value = vault.health_check().held_value;
+
+    // Only whitelisted strategies, and never more than the hard cap
+    assert!(vault
+        .try_set_strategy(&p.admin, &gold.address, &strategy.address, &5_000)
+        .is_err());
+    vault.set_strategy_whitelisted(&p.admin, &strategy.address, &true);
+    assert!(vault
+        .try_set_strategy(
+            &p.admin,
+            &gold.address,
+            &strategy.address,
+            &(MAX_STRATEGY_SHARE_BPS + 1),
+        )
+        .is_err());
+    vault.set_strategy(&p.admin, &gold.address, &strategy.address, &5_000);
+
+    // Half of the 1.1 ounces held can go out, no more
+    assert!(vault
+        .try_allocate_to_strategy(&p.admin, &gold.address, &(ounce * 11 / 20 + 1))
+        .is_err());
+    vault.allocate_to_strategy(&p.admin, &gold.address, &(ounce / 2));
+    assert_eq!(gold.balance(&strategy.address), ounce as i128 / 2);
+    assert_eq!(
+        vault.get_strategy(&gold.address).unwrap().allocated,
+        ounce / 2
+    );
+    assert_eq!(vault.health_check().held_value, held_value);
+
+    // Harvested yield stays in the vault
+    gold_admin.mint(&strategy.address, &1_000);
+    strategy.set_yield(&1_000);
+    assert_eq!(vault.harvest_strategy(&gold.address), 1_000);
+    assert_eq!(vault.get_strategy(&gold.address).unwrap().harvested, 1_000);
+
+    // A withdrawal larger than the idle gold recalls the difference from the strategy
+    p.advance_time(90 * DAY);
+    let idle = ounce * 6 / 10 + 1_000;
+    let returned = vault.withdraw(&user);
+    assert!(returned > idle);
+    assert_eq!(gold.balance(&user), returned as i128);
+    assert_eq!(gold.balance(&vault.address), 0);
+    assert_eq!(
+        vault.get_strategy(&gold.address).unwrap().allocated,
+        ounce / 2 - (returned - idle)
+    );
+}
+
+#[test]
+fn usdc_strategies_are_capped_harvested_into_the_index_and_recalled_for_withdrawals() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 10_000 * USDC;
+    let user = p.funded_user(amount);
+    p.fund_usdc_reserve(500 * USDC);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    let held_value = p.usdc_vault.health_check().held_value;
+
+    let first = MockStrategyClient::new(&env, &env.register_contract(None, MockStrategy));
+    let second = MockStrategyClient::new(&env, &env.register_contract(None, MockStrategy));
+    assert!(p
+        .usdc_vault
+        .try_register_strategy(&p.admin, &first.address, &(MAX_STRATEGY_SHARE_BPS + 1))
+        .is_err());
+    p.usdc_vault
+        .register_strategy(&p.admin, &first.address, &3_000);
+    p.usdc_vault
+        .register_strategy(&p.admin, &second.address, &MAX_STRATEGY_SHARE_BPS);
+    assert!(p
+        .usdc_vault
+        .try_register_strategy(&p.admin, &first.address, &3_000)
+        .is_err());
+
+    // 30% of the 10,500 USDC held can go to the first strategy, no more
+    assert!(p
+        .usdc_vault
+        .try_allocate(&p.admin, &first.address, &(3_150 * USDC + 1))
+        .is_err());
+    p.usdc_vault
+        .allocate(&p.admin, &first.address, &(3_000 * USDC));
+    assert_eq!(p.usdc.balance(&first.address), (3_000 * USDC) as i128);
+    assert_eq!(p.usdc_vault.health_check().held_value, held_value);
+
+    // All strategies together stay within the total cap
+    assert!(p
+        .usdc_vault
+        .try_allocate(&p.admin, &second.address, &(2_250 * USDC + 1))
+        .is_err());
+    p.usdc_vault
+        .allocate(&p.admin, &second.address, &(2_000 * USDC));
+    assert_eq!(p.usdc_vault.get_total_allocated(), 5_000 * USDC);
+
+    // Harvested yield stays in the vault and raises the yield index
+    p.usdc_admin.mint(&first.address, &(100 * USDC as i128));
+    first.set_yield(&(100 * USDC));
+    assert!(p.usdc_vault.try_harvest(&first.address).is_err());
+    p.yield_token
+        .set_strategy_vault(&p.admin, &p.usdc_vault.address);
+    let yield_index = p.yield_token.get_yield_index();
+    assert_eq!(p.usdc_vault.harvest(&first.address), 100 * USDC);
+    assert!(p.yield_token.get_yield_index() > yield_index);
+    assert_eq!(
+        p.usdc_vault.get_strategy(&first.address).unwrap().harvested,
+        100 * USDC
+    );
+
+    // A withdrawal larger than the idle USDC recalls the difference, first strategy first
+    p.advance_time(91 * DAY);
+    let idle = 5_600 * USDC;
+    let withdrawn = p.usdc_vault.withdraw(&user);
+    assert!(withdrawn > idle + 3_000 * USDC);
+    assert_eq!(p.usdc.balance(&user), withdrawn as i128);
+    assert_eq!(
+        p.usdc_vault.get_strategy(&first.address).unwrap().allocated,
+        0
+    );
+    assert_eq!(
+        p.usdc_vault.get_total_allocated(),
+        5_000 * USDC - (withdrawn - idle)
+    );
+
+    // Governance or admin brings the rest back
+    let remaining = p.usdc_vault.get_total_allocated();
+    assert_eq!(
+        p.usdc_vault
+            .deallocate(&p.admin, &second.address, &remaining),
+        remaining
+    );
+    assert_eq!(p.usdc_vault.get_total_allocated(), 0);
+}
+
+#[test]
+fn the_gold_vault_borrows_usdc_liquidity_to_pay_out_and_repays_it_from_sold_gold() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_user = p.gold_holder(OUNCE);
+    let other_gold_user = p.gold_holder(OUNCE);
+    for user in [&gold_user, &other_gold_user] {
+        p.gold_vault
+            .deposit(user, &p.gold.address, &OUNCE, &LockPeriod::ThreeMonths);
+    }
+    let amount = 5_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    p.advance_time(91 * DAY);
+
+    // Draws are capped at three quarters of the gold vault's recorded holdings
+    let limit = p.gold_vault.get_vault_balance() * 3 / 4;
+    let facility = LiquidityFacility {
+        cap: limit + 500 * USDC,
+        rate_bps: 1_000,
+        max_term: 7 * DAY,
+        max_ltv_bps: 7_500,
+    };
+    assert!(p
+        .gold_vault
+        .try_draw_liquidity(&p.admin, &p.usdc_vault.address, &limit, &DAY)
+        .is_err());
+    p.usdc_vault
+        .set_liquidity_facility(&p.admin, &p.gold_vault.address, &Some(facility));
+    assert!(p
+        .gold_vault
+        .try_draw_liquidity(&gold_user, &p.usdc_vault.address, &limit, &DAY)
+        .is_err());
+    assert!(p
+        .gold_vault
+        .try_draw_liquidity(&p.admin, &p.usdc_vault.address, &(limit + 1), &DAY)
+        .is_err());
+    assert!(p
+        .gold_vault
+        .try_draw_liquidity(&p.admin, &p.usdc_vault.address, &limit, &(8 * DAY))
+        .is_err());
+
+    let draw = p
+        .gold_vault
+        .draw_liquidity(&p.admin, &p.usdc_vault.address, &limit, &(7 * DAY));
+    assert_eq!(draw.asset, p.usdc.address);
+    assert_eq!(draw.maturity, env.ledger().timestamp() + 7 * DAY);
+    assert_eq!(p.usdc.balance(&p.gold_vault.address), limit as i128);
+    assert_eq!(p.usdc_vault.get_facility_lent(), limit);
+    assert_eq!(
+        p.gold_vault.get_liquidity_lender(),
+        Some(p.usdc_vault.address.clone())
+    );
+    assert!(p
+        .gold_vault
+        .try_draw_liquidity(&p.admin, &p.usdc_vault.address, &USDC, &DAY)
+        .is_err());
+
+    // Lent USDC still backs depositors
+    assert!(p.usdc_vault.health_check().healthy);
+    assert_eq!(p.usdc_vault.get_share_price(), SHARE_PRICE_PRECISION);
+
+    // A matured depositor is paid in the drawn USDC and their gold stays behind
+    assert!(p
+        .gold_vault
+        .try_withdraw_in_liquidity(&gold_user, &(limit + 1))
+        .is_err());
+    let paid = p
+        .gold_vault
+        .withdraw_in_liquidity(&gold_user, &(2_000 * USDC));
+    assert!(paid > 2_000 * USDC);
+    assert_eq!(p.usdc.balance(&gold_user), paid as i128);
+    assert_eq!(p.gold.balance(&gold_user), 0);
+    assert_eq!(p.gold.balance(&p.gold_vault.address), (2 * OUNCE) as i128);
+
+    // That gold is sold for USDC through the trade router, never below the minimum
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    p.usdc_admin
+        .mint(&router.address, &((2_100 * USDC) as i128));
+    p.registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_TRADE_ROUTER),
+        &router.address,
+    );
+    router.set_output(&(100 * USDC));
+    assert_eq!(
+        p.gold_vault
+            .try_swap_for_repayment(&p.admin, &p.gold.address, &OUNCE, &(2_000 * USDC)),
+        Err(Ok(Error::from_contract_error(
+            ContractError::SlippageExceeded as u32
+        )))
+    );
+    router.set_output(&(2_100 * USDC));
+    assert_eq!(
+        p.gold_vault
+            .swap_for_repayment(&p.admin, &p.gold.address, &OUNCE, &(2_000 * USDC)),
+        2_100 * USDC
+    );
+    assert_eq!(p.gold.balance(&p.gold_vault.address), OUNCE as i128);
+
+    // Anyone can then repay the draw with its interest
+    p.advance_time(2 * DAY);
+    let interest = (limit * 1_000 * 2 + 10_000 * 365 - 1) / (10_000 * 365);
+    assert_eq!(
+        p.usdc_vault.get_facility_interest(&p.gold_vault.address),
+        interest
+    );
+    p.yield_token
+        .set_strategy_vault(&p.admin, &p.usdc_vault.address);
+    let yield_index = p.yield_token.get_yield_index();
+    assert_eq!(p.gold_vault.repay_liquidity(), limit + interest);
+    assert!(p.yield_token.get_yield_index() > yield_index);
+    assert_eq!(p.usdc_vault.get_facility_draw(&p.gold_vault.address), None);
+    assert_eq!(p.usdc_vault.get_facility_lent(), 0);
+    assert_eq!(
+        p.usdc.balance(&p.gold_vault.address),
+        (2_100 * USDC - paid - interest) as i128
+    );
+    assert_eq!(
+        p.usdc.balance(&p.usdc_vault.address),
+        (amount + interest) as i128
+    );
+}
+
+#[test]
+fn an_overdue_facility_draw_is_liquidated_against_the_borrowing_vault() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let gold_user = p.gold_holder(OUNCE);
+    p.gold_vault.deposit(
+        &gold_user,
+        &p.gold.address,
+        &OUNCE,
+        &LockPeriod::ThreeMonths,
+    );
+    let amount = 5_000 * USDC;
+    let user = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+    p.yield_token
+        .set_strategy_vault(&p.admin, &p.usdc_vault.address);
+
+    let limit = 1_000 * USDC;
+    p.usdc_vault.set_liquidity_facility(
+        &p.admin,
+        &p.gold_vault.address,
+        &Some(LiquidityFacility {
+            cap: limit,
+            rate_bps: 1_000,
+            max_term: 7 * DAY,
+            max_ltv_bps: 5_000,
+        }),
+    );
+    p.gold_vault
+        .draw_liquidity(&p.admin, &p.usdc_vault.address, &limit, &DAY);
+
+    // Only the lender can take collateral, and only once the draw is overdue
+    let liquidator = p.funded_user(2_000 * USDC);
+    assert!(p
+        .gold_vault
+        .try_surrender_facility_collateral(&liquidator, &liquidator, &USDC)
+        .is_err());
+    assert!(p
+        .usdc_vault
+        .try_liquidate_facility(&liquidator, &p.gold_vault.address)
+        .is_err());
+
+    // The liquidator repays the draw and takes gold worth the debt plus the bonus
+    p.advance_time(2 * DAY);
+    assert!(p.usdc_vault.is_facility_overdue(&p.gold_vault.address));
+    let owed = limit + p.usdc_vault.get_facility_interest(&p.gold_vault.address);
+    assert_eq!(
+        p.usdc_vault
+            .liquidate_facility(&liquidator, &p.gold_vault.address),
+        owed
+    );
+    let collateral_value = owed * (10_000 + FACILITY_LIQUIDATION_BONUS_BPS) / 10_000;
+    let seized = (collateral_value * GOLD_PRICE_PRECISION).div_ceil(GOLD_PRICE);
+    assert_eq!(p.gold.balance(&liquidator), seized as i128);
+    assert_eq!(p.usdc.balance(&liquidator), (2_000 * USDC - owed) as i128);
+    assert_eq!(
+        p.gold.balance(&p.gold_vault.address),
+        (OUNCE - seized) as i128
+    );
+    assert_eq!(p.usdc_vault.get_facility_draw(&p.gold_vault.address), None);
+    assert_eq!(p.usdc_vault.get_facility_lent(), 0);
+}
+
+#[test]
+fn the_usdc_vault_borrows_gold_liquidity_to_pay_out_and_buys_it_back() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let user = p.funded_user(2_000 * USDC);
+    let other = p.funded_user(4_000 * USDC);
+    p.usdc_vault
+        .deposit(&user, &(2_000 * USDC), &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&other, &(4_000 * USDC), &LockPeriod::ThreeMonths);
+    p.fund_usdc_reserve(500 * USDC);
+    p.advance_time(91 * DAY);
+
+    let gold_user = p.gold_holder(2 * OUNCE);
+    p.gold_vault.deposit(
+        &gold_user,
+        &p.gold.address,
+        &(2 * OUNCE),
+        &LockPeriod::ThreeMonths,
+    );
+
+    // The gold's value is held to 80% of the USDC vault's holdings
+    p.gold_vault.set_liquidity_facility(
+        &p.admin,
+        &p.usdc_vault.address,
+        &Some(LiquidityFacility {
+            cap: 3 * OUNCE,
+            rate_bps: 1_000,
+            max_term: 7 * DAY,
+            max_ltv_bps: 8_000,
+        }),
+    );
+    assert!(p
+        .usdc_vault
+        .try_draw_liquidity(
+            &p.admin,
+            &p.gold_vault.address,
+            &p.gold.address,
+            &(3 * OUNCE),
+            &DAY
+        )
+        .is_err());
+    let draw = p.usdc_vault.draw_liquidity(
+        &p.admin,
+        &p.gold_vault.address,
+        &p.gold.address,
+        &(2 * OUNCE),
+        &(7 * DAY),
+    );
+    assert_eq!(draw.asset, p.gold.address);
+    assert_eq!(p.gold.balance(&p.usdc_vault.address), (2 * OUNCE) as i128);
+    assert_eq!(p.gold_vault.get_facility_lent(&p.gold.address), 2 * OUNCE);
+    assert!(p.gold_vault.health_check().healthy);
+
+    // A matured depositor is paid in the drawn gold at the lender's price
+    assert!(p
+        .usdc_vault
+        .try_withdraw_in_liquidity(&user, &(2 * OUNCE))
+        .is_err());
+    let paid = p.usdc_vault.withdraw_in_liquidity(&user, &OUNCE);
+    assert_eq!(p.gold.balance(&user), paid as i128);
+    assert_eq!(p.usdc.balance(&user), 0);
+    assert_eq!(
+        p.gold.balance(&p.usdc_vault.address),
+        (2 * OUNCE - paid) as i128
+    );
+
+    // Idle USDC buys the gold back before repaying
+    let router = MockRouterClient::new(&env, &env.register_contract(None, MockRouter));
+    p.gold_admin
+        .mint(&router.address, &((3 * OUNCE / 2) as i128));
+    router.set_output(&(3 * OUNCE / 2));
+    p.registry.set_address(
+        &p.admin,
+        &Symbol::new(&env, REGISTRY_TRADE_ROUTER),
+        &router.address,
+    );
+    assert_eq!(
+        p.usdc_vault
+            .swap_for_repayment(&p.admin, &(3_000 * USDC), &OUNCE),
+        3 * OUNCE / 2
+    );
+
+    p.advance_time(DAY);
+    let interest = p.gold_vault.get_facility_interest(&p.usdc_vault.address);
+    assert!(interest > 0);
+    assert_eq!(p.usdc_vault.repay_liquidity(), 2 * OUNCE + interest);
+    assert_eq!(p.gold_vault.get_facility_draw(&p.usdc_vault.address), None);
+    assert_eq!(p.gold_vault.get_facility_lent(&p.gold.address), 0);
+    assert_eq!(
+        p.gold.balance(&p.gold_vault.address),
+        (2 * OUNCE + interest) as i128
+    );
+    assert!(p.gold_vault.health_check().healthy);
+}
+
+#[test]
+fn roles_split_pausing_upgrades_and_fees_away_from_the_admin() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let missing_role = Err(Ok(Error::from_contract_error(
+        ContractError::MissingRole as u32,
+    )));
+
+    // Until a role is granted the admin holds it
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    assert_eq!(p.usdc_vault.get_role(&Role::Pauser), None);
+    p.usdc_vault.pause(&p.admin);
+    assert_eq!(
+        p.usdc_vault
+            .try_deposit(&user, &amount, &LockPeriod::ThreeMonths),
+        Err(Ok(Error::from_contract_error(ContractError::Paused as u32)))
+    );
+    p.usdc_vault.unpause(&p.admin);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    // Once granted, only the holder can pause, and exits stay open while paused
+    let pauser = Address::generate(&env);
+    p.usdc_vault
+        .set_role(&p.admin, &Role::Pauser, &Some(pauser.clone()));
+    assert_eq!(p.usdc_vault.try_pause(&p.admin), missing_role);
+    p.usdc_vault.pause(&pauser);
+    assert!(p.usdc_vault.is_paused());
+    p.fund_usdc_reserve(100 * USDC);
+    p.advance_time(91 * DAY);
+    assert!(p.usdc_vault.withdraw(&user) > amount);
+
+    let upgrader = Address::generate(&env);
+    p.usdc_vault
+        .set_role(&p.admin, &Role::Upgrader, &Some(upgrader));
+    assert_eq!(
+        p.usdc_vault
+            .try_upgrade(&p.admin, &BytesN::from_array(&env, &[0; 32])),
+        missing_role
+    );
+
+    // The fee setter replaces governance and the admin for fees until revoked
+    let fee_setter = Address::generate(&env);
+    let schedule = Some(ExitFeeSchedule {
+        start_bps: 500,
+        end_bps: 100,
+    });
+    p.usdc_vault
+        .set_role(&p.admin, &Role::FeeSetter, &Some(fee_setter.clone()));
+    assert_eq!(
+        p.usdc_vault.try_set_exit_fee_schedule(&p.admin, &schedule),
+        missing_role
+    );
+    p.usdc_vault.set_exit_fee_schedule(&fee_setter, &schedule);
+    assert_eq!(p.usdc_vault.get_exit_fee_schedule(), schedule);
+    let off = FeeSwitch {
+        enabled: false,
+        insurance_bps: 0,
+    };
+    assert_eq!(
+        p.usdc_vault
+            .try_set_fee_switch(&p.admin, &RevenueStream::YieldSkim, &off),
+        missing_role
+    );
+    p.usdc_vault
+        .set_fee_switch(&fee_setter, &RevenueStream::YieldSkim, &off);
+    assert_eq!(p.usdc_vault.get_fee_switch(&RevenueStream::YieldSkim), off);
+
+    p.usdc_vault.set_role(&p.admin, &Role::FeeSetter, &None);
+    p.usdc_vault.set_exit_fee_schedule(&p.admin, &None);
+    assert_eq!(p.usdc_vault.get_exit_fee_schedule(), None);
+}
+
+#[test]
+fn pausing_the_registry_and_allowlist_stops_changes_but_not_revocations() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    let paused = Err(Ok(Error::from_contract_error(ContractError::Paused as u32)));
+
+    // Without a pauser the admin pauses the registry, freezing its entries
+    let name = Symbol::new(&env, REGISTRY_TRADE_ROUTER);
+    let router = Address::generate(&env);
+    p.registry.pause(&p.admin);
+    assert_eq!(p.registry.try_set_address(&p.admin, &name, &router), paused);
+    p.registry.unpause(&p.admin);
+    p.registry.set_address(&p.admin, &name, &router);
+
+    // A granted pauser halts new approvals while revocations still go through
+    let pilot = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    p.allowlist.allow_many(&p.admin, &vec![&env, pilot.clone()]);
+    p.allowlist
+        .set_role(&p.admin, &Role::Pauser, &Some(pauser.clone()));
+    assert!(p.allowlist.try_pause(&p.admin).is_err());
+    p.allowlist.pause(&pauser);
+    assert_eq!(
+        p.allowlist
+            .try_set_allowed(&p.admin, &Address::generate(&env), &true),
+        paused
+    );
+    p.allowlist.set_allowed(&p.admin, &pilot, &false);
+    assert_eq!(p.allowlist.allowed_count(), 0);
+}
+
+#[test]
+fn reporter_exports_open_positions_in_pages() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let first = p.funded_user(amount);
+    let second = p.funded_user(amount);
+    let third = p.funded_user(amount);
+    p.usdc_vault
+        .deposit(&first, &amount, &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&second, &amount, &LockPeriod::TwelveMonths);
+    p.usdc_vault
+        .deposit(&third, &amount, &LockPeriod::ThreeMonths);
+    p.advance_time(91 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    p.usdc_vault.withdraw(&third);
+
+    let reporter = Address::generate(&env);
+    p.usdc_vault
+        .set_role(&p.admin, &Role::Reporter, &Some(reporter.clone()));
+    assert_eq!(
+        p.usdc_vault.try_export_positions(&p.admin, &0, &10),
+        Err(Ok(Error::from_contract_error(
+            ContractError::MissingRole as u32
+        )))
+    );
+
+    let page = p.usdc_vault.export_positions(&reporter, &0, &2);
+    assert_eq!(page.next_cursor, Some(2));
+    assert_eq!(page.records.len(), 2);
+    let record = page.records.get(1).unwrap();
+    assert_eq!(record.vault, p.usdc_vault.address);
+    assert_eq!(record.owner, second);
+    assert_eq!(record.position_id, Some(2));
+    assert_eq!(record.principal, amount);
+    assert_eq!(
+        record.accrued_yield,
+        compound_daily(amount, 1000, 91 * DAY).unwrap() - amount
+    );
+    assert_eq!(record.lock_period, LockPeriod::TwelveMonths);
+    assert_eq!(record.encumbrance, None);
+
+    // The withdrawn depositor's slot is scanned but yields no record
+    let page = p.usdc_vault.export_positions(&reporter, &2, &10);
+    assert_eq!(page.next_cursor, None);
+    assert_eq!(page.records.len(), 0);
+}
+
+#[test]
+fn launch_phase_limits_deposits_to_the_allowlist() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let allowlist = &p.allowlist;
+    assert!(p
+        .usdc_vault
+        .try_start_launch_phase(&Address::generate(&env))
+        .is_err());
+    p.usdc_vault.start_launch_phase(&p.admin);
+    assert!(p.usdc_vault.is_launch_phase());
+
+    let amount = 1_000 * USDC;
+    let pilot = p.funded_user(amount);
+    let outsider = p.funded_user(amount);
+    allowlist.allow_many(&p.admin, &vec![&env, pilot.clone()]);
+    assert_eq!(allowlist.allowed_count(), 1);
+
+    p.usdc_vault
+        .deposit(&pilot, &amount, &LockPeriod::ThreeMonths);
+    let result = p
+        .usdc_vault
+        .try_deposit(&outsider, &amount, &LockPeriod::ThreeMonths);
+    assert_eq!(
+        result,
+        Err(Ok(Error::from_contract_error(
+            ContractError::NotAllowlisted as u32
+        )))
+    );
+
+    // Governance opens the vault to the public
+    assert!(p
+        .usdc_vault
+        .try_end_launch_phase(&Address::generate(&env))
+        .is_err());
+    p.usdc_vault.end_launch_phase(&p.governance.address);
+    assert!(!p.usdc_vault.is_launch_phase());
+    p.usdc_vault
+        .deposit(&outsider, &amount, &LockPeriod::ThreeMonths);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 2 * amount);
+}
+
+#[test]
+fn recovery_address_claims_a_position_left_inactive_past_unlock() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(amount);
+    let recovery = Address::generate(&env);
+    p.usdc_vault
+        .deposit(&user, &amount, &LockPeriod::ThreeMonths);
+
+    assert!(p
+        .usdc_vault
+        .try_set_recovery(&user, &recovery, &(30 * DAY))
+        .is_err());
+    p.usdc_vault.set_recovery(&user, &recovery, &(730 * DAY));
+    assert_eq!(
+        p.usdc_vault.get_recovery_time(&user),
+        Some(p.usdc_vault.get_lock_expiry(&user) + 730 * DAY)
+    );
+
+    // A check-in after unlock restarts the inactivity period
+    p.advance_time(300 * DAY);
+    p.usdc_vault.check_in(&user);
+    p.advance_time(520 * DAY);
+    assert!(p.usdc_vault.try_claim_recovery(&recovery, &user).is_err());
+
+    p.advance_time(210 * DAY);
+    assert!(p
+        .usdc_vault
+        .try_claim_recovery(&Address::generate(&env), &user)
+        .is_err());
+    p.fund_usdc_reserve(200 * USDC);
+    let paid = p.usdc_vault.claim_recovery(&recovery, &user);
+
+    assert!(paid > amount);
+    assert_eq!(p.usdc.balance(&recovery), paid as i128);
+    assert_eq!(p.usdc.balance(&user), 0);
+    assert!(p.usdc_vault.get_deposit_info(&user).is_none());
+    assert!(p.usdc_vault.get_recovery(&user).is_none());
+}
+
+#[test]
+fn withdrawals_pay_the_beneficiary_named_at_deposit() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let amount = 1_000 * USDC;
+    let user = p.funded_user(2 * amount);
+    let cold_wallet = Address::generate(&env);
+    let attacker = Address::generate(&env);
+    p.usdc_vault
+        .deposit_to_beneficiary(&user, &amount, &LockPeriod::ThreeMonths, &cold_wallet);
+    assert_eq!(p.usdc_vault.get_payout_address(&user), cold_wallet);
+
+    // A compromised hot key can neither re-designate at deposit nor switch at once
+    assert!(p
+        .usdc_vault
+        .try_deposit_to_beneficiary(&user, &amount, &LockPeriod::ThreeMonths, &attacker)
+        .is_err());
+    p.usdc_vault.change_beneficiary(&user, &attacker);
+    assert_eq!(p.usdc_vault.get_payout_address(&user), cold_wallet);
+    assert!(p
+        .usdc_vault
+        .try_cancel_beneficiary_change(&attacker, &user)
+        .is_err());
+    p.usdc_vault.cancel_beneficiary_change(&cold_wallet, &user);
+    assert_eq!(p.usdc_vault.get_beneficiary(&user).unwrap().pending, None);
+
+    p.advance_time(90 * DAY);
+    p.fund_usdc_reserve(100 * USDC);
+    let paid = p.usdc_vault.withdraw(&user);
+    assert!(paid > amount);
+    assert_eq!(p.usdc.balance(&cold_wallet), paid as i128);
+    assert_eq!(p.usdc.balance(&user), amount as i128);
+
+    // An uncontested change takes over once the delay has passed
+    let new_wallet = Address::generate(&env);
+    p.usdc_vault.change_beneficiary(&user, &new_wallet);
+    p.advance_time(BENEFICIARY_CHANGE_DELAY);
+    assert_eq!(p.usdc_vault.get_payout_address(&user), new_wallet);
+    p.usdc_vault
+        .deposit_to_beneficiary(&user, &amount, &LockPeriod::ThreeMonths, &new_wallet);
+}
+
+#[test]
+fn yield_fee_rebates_favour_large_and_long_deposits() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let treasury = Address::generate(&env);
+    p.usdc_vault.set_treasury(&p.admin, &treasury);
+    let schedule = YieldFeeSchedule {
+        fee_bps: 2_000,
+        size_rebates: vec![
+            &env,
+            FeeRebateTier {
+                min_amount: 5_000 * USDC,
+                rebate_bps: 5_000,
+            },
+        ],
+        long_lock_rebate_bps: 10_000,
+    };
+    assert!(p
+        .usdc_vault
+        .try_set_yield_fee_schedule(
+            &p.admin,
+            &Some(YieldFeeSchedule {
+                fee_bps: 2_001,
+                ..schedule.clone()
+            })
+        )
+        .is_err());
+    p.usdc_vault
+        .set_yield_fee_schedule(&p.admin, &Some(schedule));
+
+    let small = 1_000 * USDC;
+    let large = 10_000 * USDC;
+    let retail = p.funded_user(small);
+    let anchor = p.funded_user(large);
+    let patient = p.funded_user(small);
+    p.usdc_vault
+        .deposit(&retail, &small, &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&anchor, &large, &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&patient, &small, &LockPeriod::TwelveMonths);
+    assert_eq!(p.usdc_vault.get_fee_rebate(&retail), 0);
+    assert_eq!(p.usdc_vault.get_fee_rebate(&anchor), 5_000);
+    assert_eq!(p.usdc_vault.get_fee_rebate(&patient), 10_000);
+
+    p.advance_time(365 * DAY);
+    p.fund_usdc_reserve(1_000 * USDC);
+    let mut expected_fees = 0;
+    for (user, principal, fee_share_bps) in [
+        (&retail, small, 2_000),
+        (&anchor, large, 1_000),
+        (&patient, small, 0),
+    ] {
+        let earned = p.yield_token.accrued_balance(user) - principal;
+        let fee = earned * fee_share_bps / 10_000;
+        assert_eq!(p.usdc_vault.withdraw(user), principal + earned - fee);
+        expected_fees += fee;
+    }
+
+    assert!(expected_fees > 0);
+    assert_eq!(p.usdc.balance(&treasury), expected_fees as i128);
+    assert_eq!(p.usdc_vault.get_fees_routed(), expected_fees);
+}
+
+#[test]
+fn vouchers_open_deposits_in_the_claimant_name() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let cooperative = p.funded_user(3_000 * USDC);
+    let member = Address::generate(&env);
+    let code = Bytes::from_slice(&env, b"COOP-2024-17");
+    let code_hash: BytesN<32> = env.crypto().sha256(&code).into();
+
+    assert!(p
+        .usdc_vault
+        .try_create_voucher(
+            &cooperative,
+            &(500 * USDC),
+            &LockPeriod::ThreeMonths,
+            &None,
+            &None
+        )
+        .is_err());
+    let named = p.usdc_vault.create_voucher(
+        &cooperative,
+        &(1_000 * USDC),
+        &LockPeriod::SixMonths,
+        &Some(member.clone()),
+        &None,
+    );
+    let coded = p.usdc_vault.create_voucher(
+        &cooperative,
+        &(1_500 * USDC),
+        &LockPeriod::ThreeMonths,
+        &None,
+        &Some(code_hash),
+    );
+
+    // Voucher funds are held apart from depositor liquidity until claimed
+    assert_eq!(p.usdc_vault.get_voucher_reserve(), 2_500 * USDC);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 0);
+    assert_eq!(p.usdc_vault.health_check().held_value, 0);
+
+    let stranger = Address::generate(&env);
+    assert!(p
+        .usdc_vault
+        .try_claim_voucher(&stranger, &named, &None)
+        .is_err());
+    p.usdc_vault.claim_voucher(&member, &named, &None);
+    let deposit = p.usdc_vault.get_deposit_info(&member).unwrap();
+    assert_eq!(deposit.amount, 1_000 * USDC);
+    assert_eq!(deposit.lock_period, LockPeriod::SixMonths);
+    assert_eq!(p.yield_token.balance(&member), (1_000 * USDC) as i128);
+    assert!(p
+        .usdc_vault
+        .try_claim_voucher(&member, &named, &None)
+        .is_err());
+
+    // Anyone holding the code can claim a coded voucher
+    let wrong = Bytes::from_slice(&env, b"COOP-2024-18");
+    assert!(p
+        .usdc_vault
+        .try_claim_voucher(&stranger, &coded, &None)
+        .is_err());
+    assert!(p
+        .usdc_vault
+        .try_claim_voucher(&stranger, &coded, &Some(wrong))
+        .is_err());
+    p.usdc_vault.claim_voucher(&stranger, &coded, &Some(code));
+    assert_eq!(
+        p.usdc_vault.get_deposit_info(&stranger).unwrap().amount,
+        1_500 * USDC
+    );
+    let claimed = p.usdc_vault.get_voucher(&coded).unwrap();
+    assert_eq!(claimed.status, VoucherStatus::Claimed);
+    assert_eq!(claimed.claimed_by, Some(stranger));
+    assert_eq!(p.usdc_vault.get_voucher_reserve(), 0);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 2_500 * USDC);
+
+    // Unclaimed vouchers go back to the funder
+    let unused = p.usdc_vault.create_voucher(
+        &cooperative,
+        &(500 * USDC),
+        &LockPeriod::ThreeMonths,
+        &Some(Address::generate(&env)),
+        &None,
+    );
+    assert!(p.usdc_vault.try_cancel_voucher(&member, &unused).is_err());
+    assert!(p
+        .usdc_vault
+        .try_cancel_voucher(&cooperative, &named)
+        .is_err());
+    assert_eq!(
+        p.usdc_vault.cancel_voucher(&cooperative, &unused),
+        500 * USDC
+    );
+    assert_eq!(p.usdc.balance(&cooperative), (500 * USDC) as i128);
+    assert_eq!(p.usdc_vault.get_voucher_reserve(), 0);
+}
+
+#[test]
+fn dormant_small_positions_are_consolidated_into_queued_claims() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(300 * USDC);
+
+    let amount = 10 * USDC;
+    let small = p.funded_user(amount);
+    let large = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&small, &amount, &LockPeriod::ThreeMonths);
+    p.usdc_vault
+        .deposit(&large, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    let deposit_time = env.ledger().timestamp();
+
+    let policy = DormancyPolicy {
+        max_amount: 50 * USDC,
+        idle_period: 180 * DAY,
+        grace_period: 30 * DAY,
+    };
+    assert!(p
+        .usdc_vault
+        .try_set_dormancy_policy(&small, &Some(policy.clone()))
+        .is_err());
+    p.usdc_vault
+        .set_dormancy_policy(&p.admin, &Some(policy.clone()));
+
+    // Only small positions left past unlock and the idle period can be flagged
+    let keeper = Address::generate(&env);
+    p.advance_time(90 * DAY);
+    assert!(p.usdc_vault.try_flag_dormant(&keeper, &small).is_err());
+    p.advance_time(180 * DAY);
+    assert!(p.usdc_vault.try_flag_dormant(&keeper, &large).is_err());
+    p.usdc_vault.flag_dormant(&keeper, &small);
+    let flagged_at = env.ledger().timestamp();
+    assert_eq!(p.usdc_vault.get_dormant_since(&small), Some(flagged_at));
+    assert!(p.usdc_vault.try_flag_dormant(&keeper, &small).is_err());
+
+    // Consolidation waits out the grace period; yield stops at its end however late the keeper is
+    assert!(p
+        .usdc_vault
+        .try_consolidate_dormant(&keeper, &small)
+        .is_err());
+    p.advance_time(30 * DAY + 365 * DAY);
+    let consolidated = p.usdc_vault.consolidate_dormant(&keeper, &small);
+    let accrual_cap = compound_daily(
+        amount,
+        p.usdc_vault.calculate_yield_rate(&LockPeriod::ThreeMonths),
+        flagged_at + policy.grace_period - deposit_time,
+    )
+    .unwrap();
+    assert!(consolidated > amount);
+    assert!(consolidated <= accrual_cap);
+
+    // The position is gone and only the queued claim remains
+    assert_eq!(p.usdc_vault.get_deposit_info(&small), None);
+    assert_eq!(p.usdc_vault.get_dormant_since(&small), None);
+    assert_eq!(p.yield_token.balance(&small), 0);
+    assert_eq!(p.usdc_vault.get_queued_withdrawal(&small), consolidated);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 1_000 * USDC);
+    assert_eq!(p.usdc_vault.claim_queued_withdrawal(&small), consolidated);
+    assert_eq!(p.usdc.balance(&small) as u128, consolidated);
+
+    // A depositor who withdraws during the grace period clears the flag
+    p.usdc_vault.set_dormancy_policy(
+        &p.admin,
+        &Some(DormancyPolicy {
+            max_amount: 1_000 * USDC,
+            ..policy
+        }),
+    );
+    p.usdc_vault.flag_dormant(&keeper, &large);
+    p.usdc_vault.withdraw(&large);
+    assert_eq!(p.usdc_vault.get_dormant_since(&large), None);
+}
+
+#[test]
+fn laddered_deposits_open_a_rung_per_lock_tier() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+    p.fund_usdc_reserve(1_000 * USDC);
+
+    let user = p.funded_user(12_000 * USDC);
+    assert!(p
+        .usdc_vault
+        .try_deposit_laddered(&user, &(10_000 * USDC), &vec![&env, 5_000, 5_000])
+        .is_err());
+    assert!(p
+        .usdc_vault
+        .try_deposit_laddered(&user, &(10_000 * USDC), &vec![&env, 2_500, 2_500, 4_000])
+        .is_err());
+
+    p.usdc_vault
+        .deposit_laddered(&user, &(10_000 * USDC), &vec![&env, 2_500, 2_500, 5_000]);
+    let short = p
+        .usdc_vault
+        .get_ladder_rung(&user, &LockPeriod::ThreeMonths)
+        .unwrap();
+    assert_eq!(short.amount, 2_500 * USDC);
+    assert_eq!(short.unlock_time, short.deposit_time + 90 * DAY);
+    assert_eq!(
+        p.usdc_vault.get_tier_balance(&LockPeriod::TwelveMonths),
+        5_000 * USDC
+    );
+    assert_eq!(p.usdc_vault.get_vault_balance(), 10_000 * USDC);
+    assert_eq!(p.yield_token.balance(&user), 0);
+
+    // Rungs sit beside a regular deposit but each tier holds only one
+    p.usdc_vault
+        .deposit(&user, &(1_000 * USDC), &LockPeriod::SixMonths);
+    assert!(p
+        .usdc_vault
+        .try_deposit_laddered(&user, &(1_000 * USDC), &vec![&env, 0, 0, 10_000])
+        .is_err());
+
+    assert!(p
+        .usdc_vault
+        .try_withdraw_rung(&user, &LockPeriod::ThreeMonths)
+        .is_err());
+    p.advance_time(90 * DAY);
+    let expected = compound_daily(2_500 * USDC, 500, 90 * DAY).unwrap();
+    assert_eq!(
+        p.usdc_vault.withdraw_rung(&user, &LockPeriod::ThreeMonths),
+        expected
+    );
+    assert_eq!(p.usdc.balance(&user), (1_000 * USDC + expected) as i128);
+    assert!(p
+        .usdc_vault
+        .get_ladder_rung(&user, &LockPeriod::ThreeMonths)
+        .is_none());
+    assert_eq!(p.usdc_vault.get_vault_balance(), 8_500 * USDC);
+
+    // The longer rungs stay locked
+    assert!(p
+        .usdc_vault
+        .try_withdraw_rung(&user, &LockPeriod::SixMonths)
+        .is_err());
+}
+
+#[test]
+fn health_checks_hold_the_shared_supply_to_both_vaults_and_flag_stale_gold_prices() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let usdc_user = p.funded_user(1_000 * USDC);
+    p.usdc_vault
+        .deposit(&usdc_user, &(1_000 * USDC), &LockPeriod::ThreeMonths);
+    let gold_user = p.gold_holder(OUNCE);
+    p.gold_vault.deposit(
+        &gold_user,
+        &p.gold.address,
+        &OUNCE,
+        &LockPeriod::ThreeMonths,
+    );
+    let minted = p.usdc_vault.get_minted_liquidity() + p.gold_vault.get_minted_liquidity();
+    assert_eq!(p.yield_token.total_supply() as u128, minted);
+    assert!(p.usdc_vault.health_check().healthy);
+    assert!(p.gold_vault.health_check().healthy);
+
+    // Supply minted by the gold vault no longer masks a gap against the USDC vault
+    p.yield_token.burn(&gold_user, &((100 * USDC) as i128));
+    let report = p.usdc_vault.health_check();
+    assert!(report.yield_token_supply >= report.recorded_liquidity);
+    assert!(!report.supply_consistent);
+    assert!(!report.healthy);
+    assert!(!p.gold_vault.health_check().supply_consistent);
+
+    // A stale gold price is flagged rather than aborting the check
+    p.oracle.set_updated_at(&env.ledger().timestamp());
+    p.advance_time(MAX_ORACLE_PRICE_AGE + 1);
+    let report = p.gold_vault.health_check();
+    assert!(!report.price_fresh);
+    assert!(!report.liquidity_covered);
+    assert!(!report.healthy);
+    assert!(p.usdc_vault.health_check().price_fresh);
+
+    p.oracle.set_updated_at(&env.ledger().timestamp());
+    let report = p.gold_vault.health_check();
+    assert!(report.price_fresh);
+    assert!(report.liquidity_covered);
+}
+
+#[test]
+fn settlement_redeems_open_ladder_rungs_with_the_deposit() {
+    let env = Env::default();
+    let p = Protocol::setup(&env);
+
+    let user = p.funded_user(7_000 * USDC);
+    let laddered_only = p.funded_user(2_000 * USDC);
+    p.usdc_vault
+        .deposit_laddered(&user, &(6_000 * USDC), &vec![&env, 2_500, 2_500, 5_000]);
+    p.usdc_vault
+        .deposit(&user, &(1_000 * USDC), &LockPeriod::SixMonths);
+    p.usdc_vault
+        .deposit_laddered(&laddered_only, &(2_000 * USDC), &vec![&env, 0, 0, 10_000]);
+    assert_eq!(p.usdc_vault.get_ladder_balance(), 8_000 * USDC);
+
+    // Rungs mint no yield tokens, so only the regular deposit is held to the supply
+    let report = p.usdc_vault.health_check();
+    assert_eq!(report.recorded_liquidity, 9_000 * USDC);
+    assert!(report.supply_consistent);
+    assert!(report.healthy);
+
+    // Exports list each rung beside the regular deposit
+    let reporter = Address::generate(&env);
+    p.usdc_vault
+        .set_role(&p.admin, &Role::Reporter, &Some(reporter.clone()));
+    let page = p.usdc_vault.export_positions(&reporter, &0, &10);
+    assert_eq!(page.records.len(), 5);
+    let rung = page.records.get(1).unwrap();
+    assert_eq!(rung.owner, user);
+    assert_eq!(rung.position_id, None);
+    assert_eq!(rung.principal, 1_500 * USDC);
+    assert_eq!(rung.lock_period, LockPeriod::ThreeMonths);
+    assert_eq!(page.records.get(4).unwrap().owner, laddered_only);
+
+    // Settlement counts the rungs and redeems them with the deposit, locks waived
+    p.usdc_vault.enter_settlement(&p.governance.address);
+    let snapshot = p.usdc_vault.get_settlement().unwrap();
+    assert_eq!(snapshot.recorded_liquidity, 9_000 * USDC);
+    assert_eq!(snapshot.held_value, 9_000 * USDC);
+    assert!(p
+        .usdc_vault
+        .try_withdraw_rung(&user, &LockPeriod::ThreeMonths)
+        .is_err());
+
+    assert_eq!(p.usdc_vault.redeem_settlement(&user), 7_000 * USDC);
+    assert_eq!(p.usdc.balance(&user), (7_000 * USDC) as i128);
+    assert_eq!(p.yield_token.balance(&user), 0);
+    assert!(p
+        .usdc_vault
+        .get_ladder_rung(&user, &LockPeriod::TwelveMonths)
+        .is_none());
+    assert!(p.usdc_vault.try_redeem_settlement(&user).is_err());
+
+    assert_eq!(p.usdc_vault.redeem_settlement(&laddered_only), 2_000 * USDC);
+    assert_eq!(p.usdc_vault.get_ladder_balance(), 0);
+    assert_eq!(p.usdc_vault.get_vault_balance(), 0);
+    assert_eq!(p.usdc_vault.get_tier_balance(&LockPeriod::TwelveMonths), 0);
+    assert_eq!(p.usdc.balance(&p.usdc_vault.address), 0);
+}